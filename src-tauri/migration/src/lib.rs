@@ -2,6 +2,53 @@ pub use sea_orm_migration::prelude::*;
 
 mod m20220101_000001_create_feeds_table;
 mod m20240101_000002_create_feed_entries_table;
+mod m20240101_000003_create_folders_table;
+mod m20240101_000004_add_entry_enclosure_fields;
+mod m20240101_000005_create_settings_table;
+mod m20240101_000006_create_fetch_log_table;
+mod m20240101_000007_add_feed_image_policy;
+mod m20240101_000008_add_feed_schedule_fields;
+mod m20240101_000009_create_tags_and_entry_filters;
+mod m20240101_000010_add_feed_publisher_schedule_hints;
+mod m20240101_000011_add_feed_mark_read_on_scroll;
+mod m20240101_000012_add_entry_guid;
+mod m20240101_000013_add_entry_source_updates;
+mod m20240101_000014_create_playback_state_table;
+mod m20240101_000015_add_entry_podcast_fields;
+mod m20240101_000016_add_feed_auto_title;
+mod m20240101_000017_add_folder_parent;
+mod m20240101_000018_create_annotation_table;
+mod m20240101_000019_create_profile_table;
+mod m20240101_000020_make_feed_image_policy_optional;
+mod m20240101_000021_create_bandwidth_usage_table;
+mod m20240101_000022_add_entry_reading_time;
+mod m20240101_000023_create_entry_translation_table;
+mod m20240101_000024_add_entry_story_clusters;
+mod m20240101_000025_create_digest_table;
+mod m20240101_000026_create_webhook_tables;
+mod m20240101_000027_create_share_history_table;
+mod m20240101_000028_add_feed_source_type;
+mod m20240101_000029_add_entry_hidden;
+mod m20240101_000030_add_canonical_links;
+mod m20240101_000031_add_entry_open_tracking;
+mod m20240101_000032_add_entry_read_at;
+mod m20240101_000033_add_entry_starred_at;
+mod m20240101_000034_add_feed_icon;
+mod m20240101_000035_add_feed_format;
+mod m20240101_000036_add_feed_max_entries;
+mod m20240101_000037_add_entry_title_search_index;
+mod m20240101_000038_add_entry_event_times;
+mod m20240101_000039_add_feed_full_content;
+mod m20240101_000040_add_feed_tls;
+mod m20240101_000041_add_feed_fetch_overrides;
+mod m20240101_000042_create_alert_tables;
+mod m20240101_000043_create_state_log_table;
+mod m20240101_000044_create_refresh_history_table;
+mod m20240101_000045_create_entry_archive_table;
+mod m20240101_000046_create_entry_snapshot_table;
+mod m20240101_000047_add_feed_hide_read_after_days;
+mod m20240101_000048_add_feed_preferences;
+mod m20240101_000049_rename_entry_canonical_url_to_dedup_key;
 
 pub struct Migrator;
 
@@ -11,6 +58,53 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20220101_000001_create_feeds_table::Migration),
             Box::new(m20240101_000002_create_feed_entries_table::Migration),
+            Box::new(m20240101_000003_create_folders_table::Migration),
+            Box::new(m20240101_000004_add_entry_enclosure_fields::Migration),
+            Box::new(m20240101_000005_create_settings_table::Migration),
+            Box::new(m20240101_000006_create_fetch_log_table::Migration),
+            Box::new(m20240101_000007_add_feed_image_policy::Migration),
+            Box::new(m20240101_000008_add_feed_schedule_fields::Migration),
+            Box::new(m20240101_000009_create_tags_and_entry_filters::Migration),
+            Box::new(m20240101_000010_add_feed_publisher_schedule_hints::Migration),
+            Box::new(m20240101_000011_add_feed_mark_read_on_scroll::Migration),
+            Box::new(m20240101_000012_add_entry_guid::Migration),
+            Box::new(m20240101_000013_add_entry_source_updates::Migration),
+            Box::new(m20240101_000014_create_playback_state_table::Migration),
+            Box::new(m20240101_000015_add_entry_podcast_fields::Migration),
+            Box::new(m20240101_000016_add_feed_auto_title::Migration),
+            Box::new(m20240101_000017_add_folder_parent::Migration),
+            Box::new(m20240101_000018_create_annotation_table::Migration),
+            Box::new(m20240101_000019_create_profile_table::Migration),
+            Box::new(m20240101_000020_make_feed_image_policy_optional::Migration),
+            Box::new(m20240101_000021_create_bandwidth_usage_table::Migration),
+            Box::new(m20240101_000022_add_entry_reading_time::Migration),
+            Box::new(m20240101_000023_create_entry_translation_table::Migration),
+            Box::new(m20240101_000024_add_entry_story_clusters::Migration),
+            Box::new(m20240101_000025_create_digest_table::Migration),
+            Box::new(m20240101_000026_create_webhook_tables::Migration),
+            Box::new(m20240101_000027_create_share_history_table::Migration),
+            Box::new(m20240101_000028_add_feed_source_type::Migration),
+            Box::new(m20240101_000029_add_entry_hidden::Migration),
+            Box::new(m20240101_000030_add_canonical_links::Migration),
+            Box::new(m20240101_000031_add_entry_open_tracking::Migration),
+            Box::new(m20240101_000032_add_entry_read_at::Migration),
+            Box::new(m20240101_000033_add_entry_starred_at::Migration),
+            Box::new(m20240101_000034_add_feed_icon::Migration),
+            Box::new(m20240101_000035_add_feed_format::Migration),
+            Box::new(m20240101_000036_add_feed_max_entries::Migration),
+            Box::new(m20240101_000037_add_entry_title_search_index::Migration),
+            Box::new(m20240101_000038_add_entry_event_times::Migration),
+            Box::new(m20240101_000039_add_feed_full_content::Migration),
+            Box::new(m20240101_000040_add_feed_tls::Migration),
+            Box::new(m20240101_000041_add_feed_fetch_overrides::Migration),
+            Box::new(m20240101_000042_create_alert_tables::Migration),
+            Box::new(m20240101_000043_create_state_log_table::Migration),
+            Box::new(m20240101_000044_create_refresh_history_table::Migration),
+            Box::new(m20240101_000045_create_entry_archive_table::Migration),
+            Box::new(m20240101_000046_create_entry_snapshot_table::Migration),
+            Box::new(m20240101_000047_add_feed_hide_read_after_days::Migration),
+            Box::new(m20240101_000048_add_feed_preferences::Migration),
+            Box::new(m20240101_000049_rename_entry_canonical_url_to_dedup_key::Migration),
         ]
     }
 }
@@ -0,0 +1,73 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000004_add_entry_enclosure_fields"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Add enclosure metadata and a queryable media duration.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .add_column(ColumnDef::new(FeedEntry::EnclosureUrl).text())
+                    .add_column(ColumnDef::new(FeedEntry::EnclosureType).string())
+                    .add_column(ColumnDef::new(FeedEntry::EnclosureLength).big_integer())
+                    .add_column(ColumnDef::new(FeedEntry::DurationSeconds).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Duration filters and sorts ("episodes under 30 minutes") hit this index
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feed_entries_duration_seconds")
+                    .table(FeedEntry::Table)
+                    .col(FeedEntry::DurationSeconds)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    // Define how to rollback this migration: Drop the enclosure columns.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_feed_entries_duration_seconds")
+                    .table(FeedEntry::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .drop_column(FeedEntry::EnclosureUrl)
+                    .drop_column(FeedEntry::EnclosureType)
+                    .drop_column(FeedEntry::EnclosureLength)
+                    .drop_column(FeedEntry::DurationSeconds)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+    EnclosureUrl,
+    EnclosureType,
+    EnclosureLength,
+    DurationSeconds,
+}
@@ -0,0 +1,100 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000006_create_fetch_log_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the FetchLog table recording every feed fetch.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(FetchLog::Table)
+                    .col(
+                        ColumnDef::new(FetchLog::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(FetchLog::FeedId).integer().not_null())
+                    .col(
+                        ColumnDef::new(FetchLog::FetchedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(FetchLog::DurationMs).big_integer().not_null())
+                    .col(ColumnDef::new(FetchLog::Success).boolean().not_null())
+                    .col(
+                        ColumnDef::new(FetchLog::EntriesAdded)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(FetchLog::ErrorType).string())
+                    .col(ColumnDef::new(FetchLog::ErrorMessage).text())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_fetch_log_feed_id")
+                    .from(FetchLog::Table, FetchLog::FeedId)
+                    .to(Feed::Table, Feed::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Stats read the most recent fetches of a single feed
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_fetch_log_feed_id_fetched_at")
+                    .table(FetchLog::Table)
+                    .col(FetchLog::FeedId)
+                    .col(FetchLog::FetchedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    // Define how to rollback this migration: Drop the FetchLog table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(FetchLog::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum FetchLog {
+    Table,
+    Id,
+    FeedId,
+    FetchedAt,
+    DurationMs,
+    Success,
+    EntriesAdded,
+    ErrorType,
+    ErrorMessage,
+}
+
+// Reference to the Feed table from the first migration
+#[derive(Iden)]
+pub enum Feed {
+    Table,
+    Id,
+}
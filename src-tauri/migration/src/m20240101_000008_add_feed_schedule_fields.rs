@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000008_add_feed_schedule_fields"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Add the adaptive refresh interval and next scheduled fetch.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Feed::Table)
+                    .add_column(ColumnDef::new(Feed::FetchIntervalMinutes).integer())
+                    .add_column(ColumnDef::new(Feed::NextFetchAt).timestamp())
+                    .to_owned(),
+            )
+            .await?;
+
+        // The scheduler looks up due feeds on every tick
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feed_next_fetch_at")
+                    .table(Feed::Table)
+                    .col(Feed::NextFetchAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    // Define how to rollback this migration: Drop the schedule columns.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_feed_next_fetch_at")
+                    .table(Feed::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Feed::Table)
+                    .drop_column(Feed::FetchIntervalMinutes)
+                    .drop_column(Feed::NextFetchAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Feed {
+    Table,
+    FetchIntervalMinutes,
+    NextFetchAt,
+}
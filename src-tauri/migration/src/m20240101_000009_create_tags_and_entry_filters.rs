@@ -0,0 +1,192 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000009_create_tags_and_entry_filters"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create tags, add snoozing, and index every entry list filter.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tag::Table)
+                    .col(
+                        ColumnDef::new(Tag::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Tag::Name).string().not_null().unique_key())
+                    .col(
+                        ColumnDef::new(Tag::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(EntryTag::Table)
+                    .col(ColumnDef::new(EntryTag::EntryId).integer().not_null())
+                    .col(ColumnDef::new(EntryTag::TagId).integer().not_null())
+                    .col(
+                        ColumnDef::new(EntryTag::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(EntryTag::EntryId)
+                            .col(EntryTag::TagId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_entry_tag_entry_id")
+                            .from(EntryTag::Table, EntryTag::EntryId)
+                            .to(FeedEntry::Table, FeedEntry::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_entry_tag_tag_id")
+                            .from(EntryTag::Table, EntryTag::TagId)
+                            .to(Tag::Table, Tag::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Tag views look up entries by tag (the primary key covers lookups by entry)
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_entry_tag_tag_id")
+                    .table(EntryTag::Table)
+                    .col(EntryTag::TagId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Snoozed entries are hidden until this time
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .add_column(ColumnDef::new(FeedEntry::SnoozedUntil).timestamp())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Indexes backing the entry list filters: unread/starred per feed, starred
+        // across all feeds, and snoozed entries
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feed_entries_feed_id_is_read")
+                    .table(FeedEntry::Table)
+                    .col(FeedEntry::FeedId)
+                    .col(FeedEntry::IsRead)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feed_entries_is_starred")
+                    .table(FeedEntry::Table)
+                    .col(FeedEntry::IsStarred)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feed_entries_snoozed_until")
+                    .table(FeedEntry::Table)
+                    .col(FeedEntry::SnoozedUntil)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    // Define how to rollback this migration: Drop the tag tables, snooze column and filter indexes.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for index_name in [
+            "idx_feed_entries_feed_id_is_read",
+            "idx_feed_entries_is_starred",
+            "idx_feed_entries_snoozed_until",
+        ] {
+            manager
+                .drop_index(
+                    Index::drop()
+                        .name(index_name)
+                        .table(FeedEntry::Table)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .drop_column(FeedEntry::SnoozedUntil)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(EntryTag::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Tag::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Tag {
+    Table,
+    Id,
+    Name,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+pub enum EntryTag {
+    Table,
+    EntryId,
+    TagId,
+    CreatedAt,
+}
+
+// Reference to the FeedEntry table from the second migration
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+    Id,
+    FeedId,
+    IsRead,
+    IsStarred,
+    SnoozedUntil,
+}
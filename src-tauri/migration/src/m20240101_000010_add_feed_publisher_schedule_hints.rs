@@ -0,0 +1,50 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000010_add_feed_publisher_schedule_hints"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Store the RSS ttl, skipHours and skipDays a feed publishes.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Feed::Table)
+                    .add_column(ColumnDef::new(Feed::TtlMinutes).integer())
+                    // Comma-separated hours (0-23, GMT)
+                    .add_column(ColumnDef::new(Feed::SkipHours).string())
+                    // Comma-separated day names
+                    .add_column(ColumnDef::new(Feed::SkipDays).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the schedule hint columns.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Feed::Table)
+                    .drop_column(Feed::TtlMinutes)
+                    .drop_column(Feed::SkipHours)
+                    .drop_column(Feed::SkipDays)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Feed {
+    Table,
+    TtlMinutes,
+    SkipHours,
+    SkipDays,
+}
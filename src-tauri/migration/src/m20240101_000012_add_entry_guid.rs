@@ -0,0 +1,119 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000012_add_entry_guid"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Identify entries by (feed_id, guid) instead of a globally unique link.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .add_column(ColumnDef::new(FeedEntry::Guid).text())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing entries were deduplicated by link, so the link is their guid
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(FeedEntry::Table)
+                    .value(FeedEntry::Guid, Expr::col(FeedEntry::Link))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .modify_column(ColumnDef::new(FeedEntry::Guid).text().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // The same article may appear in several feeds, so links are no longer unique
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_feed_entries_link_unique")
+                    .table(FeedEntry::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feed_entries_link")
+                    .table(FeedEntry::Table)
+                    .col(FeedEntry::Link)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Conflict target for upserting refreshed entries
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feed_entries_feed_id_guid_unique")
+                    .table(FeedEntry::Table)
+                    .col(FeedEntry::FeedId)
+                    .col(FeedEntry::Guid)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Restore the unique link index and drop the guid column.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for index_name in ["idx_feed_entries_feed_id_guid_unique", "idx_feed_entries_link"] {
+            manager
+                .drop_index(
+                    Index::drop()
+                        .name(index_name)
+                        .table(FeedEntry::Table)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feed_entries_link_unique")
+                    .table(FeedEntry::Table)
+                    .col(FeedEntry::Link)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .drop_column(FeedEntry::Guid)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+// Reference to the FeedEntry table from the second migration
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+    FeedId,
+    Link,
+    Guid,
+}
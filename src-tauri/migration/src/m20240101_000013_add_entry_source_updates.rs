@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000013_add_entry_source_updates"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Track when publishers edit entries after we first saw them.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .add_column(ColumnDef::new(FeedEntry::UpdatedAtSource).timestamp())
+                    .add_column(
+                        ColumnDef::new(FeedEntry::IsUpdated)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Backs the "recently updated" list
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feed_entries_is_updated_updated_at_source")
+                    .table(FeedEntry::Table)
+                    .col(FeedEntry::IsUpdated)
+                    .col(FeedEntry::UpdatedAtSource)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the index and update tracking columns.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_feed_entries_is_updated_updated_at_source")
+                    .table(FeedEntry::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .drop_column(FeedEntry::UpdatedAtSource)
+                    .drop_column(FeedEntry::IsUpdated)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+// Reference to the FeedEntry table from the second migration
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+    UpdatedAtSource,
+    IsUpdated,
+}
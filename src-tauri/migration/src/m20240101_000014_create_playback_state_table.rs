@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000014_create_playback_state_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the PlaybackState table for resuming podcast episodes.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PlaybackState::Table)
+                    .col(
+                        ColumnDef::new(PlaybackState::EntryId)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PlaybackState::PositionSeconds)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(PlaybackState::DurationSeconds).integer())
+                    .col(
+                        ColumnDef::new(PlaybackState::Finished)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(PlaybackState::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_playback_state_entry_id")
+                            .from(PlaybackState::Table, PlaybackState::EntryId)
+                            .to(FeedEntry::Table, FeedEntry::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the PlaybackState table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PlaybackState::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum PlaybackState {
+    Table,
+    EntryId,
+    PositionSeconds,
+    DurationSeconds,
+    Finished,
+    UpdatedAt,
+}
+
+// Reference to the FeedEntry table from the second migration
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+    Id,
+}
@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000015_add_entry_podcast_fields"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Add iTunes podcast metadata to FeedEntry.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .add_column(ColumnDef::new(FeedEntry::EpisodeNumber).integer())
+                    .add_column(ColumnDef::new(FeedEntry::SeasonNumber).integer())
+                    .add_column(ColumnDef::new(FeedEntry::IsExplicit).boolean())
+                    .add_column(ColumnDef::new(FeedEntry::ArtworkUrl).text())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the podcast metadata columns.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .drop_column(FeedEntry::EpisodeNumber)
+                    .drop_column(FeedEntry::SeasonNumber)
+                    .drop_column(FeedEntry::IsExplicit)
+                    .drop_column(FeedEntry::ArtworkUrl)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+// Reference to the FeedEntry table from the second migration
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+    EpisodeNumber,
+    SeasonNumber,
+    IsExplicit,
+    ArtworkUrl,
+}
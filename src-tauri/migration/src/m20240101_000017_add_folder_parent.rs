@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000017_add_folder_parent"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Let folders nest inside other folders.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Folder::Table)
+                    .add_column(ColumnDef::new(Folder::ParentId).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Deleting a folder moves its subfolders to the top level, like its feeds
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_folder_parent_id")
+                    .from(Folder::Table, Folder::ParentId)
+                    .to(Folder::Table, Folder::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_folder_parent_id")
+                    .table(Folder::Table)
+                    .col(Folder::ParentId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Flatten folders by dropping the parent column.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Folder::Table)
+                    .drop_column(Folder::ParentId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+// Reference to the Folder table from the third migration
+#[derive(Iden)]
+pub enum Folder {
+    Table,
+    Id,
+    ParentId,
+}
@@ -0,0 +1,86 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000018_create_annotation_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the Annotation table for notes attached to entries.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Annotation::Table)
+                    .col(
+                        ColumnDef::new(Annotation::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Annotation::EntryId).integer().not_null())
+                    .col(ColumnDef::new(Annotation::Note).text().not_null())
+                    .col(
+                        ColumnDef::new(Annotation::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Annotation::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_annotation_entry_id")
+                            .from(Annotation::Table, Annotation::EntryId)
+                            .to(FeedEntry::Table, FeedEntry::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_annotation_entry_id")
+                    .table(Annotation::Table)
+                    .col(Annotation::EntryId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the Annotation table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Annotation::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Annotation {
+    Table,
+    Id,
+    EntryId,
+    Note,
+    CreatedAt,
+    UpdatedAt,
+}
+
+// Reference to the FeedEntry table from the second migration
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+    Id,
+}
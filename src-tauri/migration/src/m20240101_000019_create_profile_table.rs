@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000019_create_profile_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the Profile table listing the other library databases.
+    // Only the table in the main (DATABASE_URL) database is used.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Profile::Table)
+                    .col(
+                        ColumnDef::new(Profile::Name)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Profile::DatabaseUrl).text().not_null())
+                    .col(
+                        ColumnDef::new(Profile::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the Profile table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Profile::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Profile {
+    Table,
+    Name,
+    DatabaseUrl,
+    CreatedAt,
+}
@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000020_make_feed_image_policy_optional"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Let feeds follow the global image policy unless they override it.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Feed::Table)
+                    // NULL means the feed follows the global setting
+                    .modify_column(ColumnDef::new(Feed::ImagePolicy).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // sea-query has no way to express DROP DEFAULT
+        manager
+            .get_connection()
+            .execute_unprepared(r#"ALTER TABLE "feed" ALTER COLUMN "image_policy" DROP DEFAULT"#)
+            .await?;
+
+        // "all" was the old default, so those feeds never chose it
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Feed::Table)
+                    .value(Feed::ImagePolicy, Option::<String>::None)
+                    .and_where(Expr::col(Feed::ImagePolicy).eq("all"))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Restore the "all" default on every feed without an override.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Feed::Table)
+                    .value(Feed::ImagePolicy, "all")
+                    .and_where(Expr::col(Feed::ImagePolicy).is_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Feed::Table)
+                    .modify_column(
+                        ColumnDef::new(Feed::ImagePolicy)
+                            .string()
+                            .not_null()
+                            .default("all"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+// Reference to the Feed table from the first migration
+#[derive(Iden)]
+pub enum Feed {
+    Table,
+    ImagePolicy,
+}
@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000021_create_bandwidth_usage_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the BandwidthUsage table with bytes downloaded per feed per day.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BandwidthUsage::Table)
+                    .col(ColumnDef::new(BandwidthUsage::FeedId).integer().not_null())
+                    .col(ColumnDef::new(BandwidthUsage::Day).date().not_null())
+                    .col(
+                        ColumnDef::new(BandwidthUsage::Bytes)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(BandwidthUsage::FeedId)
+                            .col(BandwidthUsage::Day),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_bandwidth_usage_feed_id")
+                            .from(BandwidthUsage::Table, BandwidthUsage::FeedId)
+                            .to(Feed::Table, Feed::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Usage reports cover a range of days across every feed
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_bandwidth_usage_day")
+                    .table(BandwidthUsage::Table)
+                    .col(BandwidthUsage::Day)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the BandwidthUsage table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BandwidthUsage::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum BandwidthUsage {
+    Table,
+    FeedId,
+    Day,
+    Bytes,
+}
+
+// Reference to the Feed table from the first migration
+#[derive(Iden)]
+pub enum Feed {
+    Table,
+    Id,
+}
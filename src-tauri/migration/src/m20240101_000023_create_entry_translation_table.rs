@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000023_create_entry_translation_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the EntryTranslation table caching translated entries,
+    // one row per entry and target language.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EntryTranslation::Table)
+                    .col(ColumnDef::new(EntryTranslation::EntryId).integer().not_null())
+                    .col(ColumnDef::new(EntryTranslation::TargetLang).string().not_null())
+                    .col(ColumnDef::new(EntryTranslation::Provider).string().not_null())
+                    // Fingerprint of the text that was translated, so edited entries are translated again
+                    .col(ColumnDef::new(EntryTranslation::SourceHash).string().not_null())
+                    .col(ColumnDef::new(EntryTranslation::Title).text().not_null())
+                    .col(ColumnDef::new(EntryTranslation::Description).text())
+                    .col(ColumnDef::new(EntryTranslation::Content).text())
+                    .col(
+                        ColumnDef::new(EntryTranslation::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(EntryTranslation::EntryId)
+                            .col(EntryTranslation::TargetLang),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_entry_translation_entry_id")
+                            .from(EntryTranslation::Table, EntryTranslation::EntryId)
+                            .to(FeedEntry::Table, FeedEntry::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the EntryTranslation table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EntryTranslation::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum EntryTranslation {
+    Table,
+    EntryId,
+    TargetLang,
+    Provider,
+    SourceHash,
+    Title,
+    Description,
+    Content,
+    CreatedAt,
+}
+
+// Reference to the FeedEntry table from the second migration
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+    Id,
+}
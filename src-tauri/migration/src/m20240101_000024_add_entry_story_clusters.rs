@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000024_add_entry_story_clusters"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Add the canonical URL and story cluster used to group
+    // the same story published by different feeds.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .add_column(ColumnDef::new(FeedEntry::CanonicalUrl).text())
+                    .add_column(ColumnDef::new(FeedEntry::StoryClusterId).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        // New entries are matched against others by canonical URL
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feed_entries_canonical_url")
+                    .table(FeedEntry::Table)
+                    .col(FeedEntry::CanonicalUrl)
+                    .to_owned(),
+            )
+            .await?;
+
+        // The deduplicated timeline looks up the other members of each cluster
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feed_entries_story_cluster_id")
+                    .table(FeedEntry::Table)
+                    .col(FeedEntry::StoryClusterId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the story cluster columns and their indexes.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for index_name in ["idx_feed_entries_story_cluster_id", "idx_feed_entries_canonical_url"] {
+            manager
+                .drop_index(
+                    Index::drop()
+                        .name(index_name)
+                        .table(FeedEntry::Table)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .drop_column(FeedEntry::CanonicalUrl)
+                    .drop_column(FeedEntry::StoryClusterId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+// Reference to the FeedEntry table from the second migration
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+    CanonicalUrl,
+    StoryClusterId,
+}
@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000025_create_digest_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the Digest table storing generated digests
+    // of unread highlights, rendered as both Markdown and HTML.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Digest::Table)
+                    .col(
+                        ColumnDef::new(Digest::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Digest::Title).string().not_null())
+                    .col(ColumnDef::new(Digest::RangeStart).timestamp().not_null())
+                    .col(ColumnDef::new(Digest::RangeEnd).timestamp().not_null())
+                    .col(ColumnDef::new(Digest::EntryCount).integer().not_null())
+                    .col(ColumnDef::new(Digest::Markdown).text().not_null())
+                    .col(ColumnDef::new(Digest::Html).text().not_null())
+                    .col(
+                        ColumnDef::new(Digest::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Digests are listed newest first
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_digest_created_at")
+                    .table(Digest::Table)
+                    .col(Digest::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the Digest table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Digest::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Digest {
+    Table,
+    Id,
+    Title,
+    RangeStart,
+    RangeEnd,
+    EntryCount,
+    Markdown,
+    Html,
+    CreatedAt,
+}
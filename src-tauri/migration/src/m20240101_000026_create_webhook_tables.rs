@@ -0,0 +1,170 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000026_create_webhook_tables"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the Webhook table of user-defined endpoints
+    // notified about newly saved entries, and the WebhookDelivery log of each attempt to notify one.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Webhook::Table)
+                    .col(
+                        ColumnDef::new(Webhook::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Webhook::Name).string().not_null())
+                    .col(ColumnDef::new(Webhook::Url).text().not_null())
+                    // Filters; a webhook with none of them set receives every new entry
+                    .col(ColumnDef::new(Webhook::FeedId).integer())
+                    .col(ColumnDef::new(Webhook::TagId).integer())
+                    .col(ColumnDef::new(Webhook::Keyword).string())
+                    .col(ColumnDef::new(Webhook::Enabled).boolean().not_null().default(true))
+                    .col(
+                        ColumnDef::new(Webhook::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Webhook::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webhook_feed_id")
+                            .from(Webhook::Table, Webhook::FeedId)
+                            .to(Feed::Table, Feed::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webhook_tag_id")
+                            .from(Webhook::Table, Webhook::TagId)
+                            .to(Tag::Table, Tag::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebhookDelivery::Table)
+                    .col(
+                        ColumnDef::new(WebhookDelivery::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(WebhookDelivery::WebhookId).integer().not_null())
+                    .col(ColumnDef::new(WebhookDelivery::EntryCount).integer().not_null())
+                    // "delivered" or "failed"
+                    .col(ColumnDef::new(WebhookDelivery::Status).string().not_null())
+                    .col(ColumnDef::new(WebhookDelivery::Attempts).integer().not_null())
+                    .col(ColumnDef::new(WebhookDelivery::ResponseStatus).integer())
+                    .col(ColumnDef::new(WebhookDelivery::Error).text())
+                    .col(
+                        ColumnDef::new(WebhookDelivery::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(WebhookDelivery::CompletedAt).timestamp().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webhook_delivery_webhook_id")
+                            .from(WebhookDelivery::Table, WebhookDelivery::WebhookId)
+                            .to(Webhook::Table, Webhook::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The delivery log is read per webhook, newest first
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_delivery_webhook_id_created_at")
+                    .table(WebhookDelivery::Table)
+                    .col(WebhookDelivery::WebhookId)
+                    .col(WebhookDelivery::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    // Define how to rollback this migration: Drop the WebhookDelivery and Webhook tables.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WebhookDelivery::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Webhook::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Webhook {
+    Table,
+    Id,
+    Name,
+    Url,
+    FeedId,
+    TagId,
+    Keyword,
+    Enabled,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+pub enum WebhookDelivery {
+    Table,
+    Id,
+    WebhookId,
+    EntryCount,
+    Status,
+    Attempts,
+    ResponseStatus,
+    Error,
+    CreatedAt,
+    CompletedAt,
+}
+
+// Reference to the Feed table from the first migration
+#[derive(Iden)]
+pub enum Feed {
+    Table,
+    Id,
+}
+
+// Reference to the Tag table from the ninth migration
+#[derive(Iden)]
+pub enum Tag {
+    Table,
+    Id,
+}
@@ -0,0 +1,88 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000027_create_share_history_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the ShareHistory table recording entries
+    // posted to social networks.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShareHistory::Table)
+                    .col(
+                        ColumnDef::new(ShareHistory::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ShareHistory::EntryId).integer().not_null())
+                    // "mastodon" or "bluesky"
+                    .col(ColumnDef::new(ShareHistory::Network).string().not_null())
+                    .col(ColumnDef::new(ShareHistory::Comment).text())
+                    // Link to the published post, when the network returns one
+                    .col(ColumnDef::new(ShareHistory::PostUrl).text())
+                    .col(
+                        ColumnDef::new(ShareHistory::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_share_history_entry_id")
+                            .from(ShareHistory::Table, ShareHistory::EntryId)
+                            .to(FeedEntry::Table, FeedEntry::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_share_history_entry_id")
+                    .table(ShareHistory::Table)
+                    .col(ShareHistory::EntryId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    // Define how to rollback this migration: Drop the ShareHistory table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ShareHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum ShareHistory {
+    Table,
+    Id,
+    EntryId,
+    Network,
+    Comment,
+    PostUrl,
+    CreatedAt,
+}
+
+// Reference to the FeedEntry table from the second migration
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+    Id,
+}
@@ -0,0 +1,73 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000031_add_entry_open_tracking"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Count how often each entry's link is opened, and when
+    // it last was.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .add_column(
+                        ColumnDef::new(FeedEntry::OpenCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(ColumnDef::new(FeedEntry::LastOpenedAt).timestamp())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Backs the per-feed open counts the ranked timeline looks up
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feed_entries_feed_id_last_opened_at")
+                    .table(FeedEntry::Table)
+                    .col(FeedEntry::FeedId)
+                    .col(FeedEntry::LastOpenedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the index and open tracking columns.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_feed_entries_feed_id_last_opened_at")
+                    .table(FeedEntry::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .drop_column(FeedEntry::OpenCount)
+                    .drop_column(FeedEntry::LastOpenedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+    FeedId,
+    OpenCount,
+    LastOpenedAt,
+}
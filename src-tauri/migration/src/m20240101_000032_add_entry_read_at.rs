@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000032_add_entry_read_at"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Remember when each entry was read. Entries read
+    // before this migration have no timestamp and stay out of the reading history.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .add_column(ColumnDef::new(FeedEntry::ReadAt).timestamp())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Backs the recently read list
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feed_entries_read_at")
+                    .table(FeedEntry::Table)
+                    .col(FeedEntry::ReadAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the index and read timestamp.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_feed_entries_read_at")
+                    .table(FeedEntry::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .drop_column(FeedEntry::ReadAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+    ReadAt,
+}
@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000037_add_entry_title_search_index"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Index entry titles for full-text prefix search. The
+    // 'simple' configuration doesn't stem, so a partly typed word still matches its prefix.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // sea-query can't express an expression index
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"CREATE INDEX "idx_feed_entry_title_search" ON "feed_entry" USING GIN (to_tsvector('simple', "title"))"#,
+            )
+            .await?;
+        Ok(())
+    }
+
+    // Define how to rollback this migration: Drop the index.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_feed_entry_title_search")
+                    .table(FeedEntry::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+}
@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000041_add_feed_fetch_overrides"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Let a feed override the fetcher's request timeout,
+    // retry count and per-host delay. NULL follows the global setting.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Feed::Table)
+                    .add_column(ColumnDef::new(Feed::RequestTimeoutSeconds).integer())
+                    .add_column(ColumnDef::new(Feed::MaxRetries).integer())
+                    .add_column(ColumnDef::new(Feed::RateLimitDelayMs).integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the overrides.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Feed::Table)
+                    .drop_column(Feed::RequestTimeoutSeconds)
+                    .drop_column(Feed::MaxRetries)
+                    .drop_column(Feed::RateLimitDelayMs)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Feed {
+    Table,
+    RequestTimeoutSeconds,
+    MaxRetries,
+    RateLimitDelayMs,
+}
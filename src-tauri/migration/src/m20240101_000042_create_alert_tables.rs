@@ -0,0 +1,196 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000042_create_alert_tables"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the AlertRule table of keywords and patterns
+    // to be alerted about, and the Alert inbox of the entries that matched them.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AlertRule::Table)
+                    .col(
+                        ColumnDef::new(AlertRule::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AlertRule::Name).string().not_null())
+                    // A keyword, or a regular expression when IsRegex is set
+                    .col(ColumnDef::new(AlertRule::Pattern).text().not_null())
+                    .col(ColumnDef::new(AlertRule::IsRegex).boolean().not_null().default(false))
+                    // Scope; a rule with neither set watches every feed
+                    .col(ColumnDef::new(AlertRule::FeedId).integer())
+                    .col(ColumnDef::new(AlertRule::FolderId).integer())
+                    .col(ColumnDef::new(AlertRule::Enabled).boolean().not_null().default(true))
+                    // Matches are still collected while snoozed, without notifications
+                    .col(ColumnDef::new(AlertRule::SnoozedUntil).timestamp())
+                    .col(
+                        ColumnDef::new(AlertRule::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(AlertRule::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_alert_rule_feed_id")
+                            .from(AlertRule::Table, AlertRule::FeedId)
+                            .to(Feed::Table, Feed::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_alert_rule_folder_id")
+                            .from(AlertRule::Table, AlertRule::FolderId)
+                            .to(Folder::Table, Folder::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Alert::Table)
+                    .col(
+                        ColumnDef::new(Alert::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Alert::RuleId).integer().not_null())
+                    .col(ColumnDef::new(Alert::EntryId).integer().not_null())
+                    .col(ColumnDef::new(Alert::IsRead).boolean().not_null().default(false))
+                    // Set until the desktop notification for this match has been shown
+                    .col(ColumnDef::new(Alert::NotificationPending).boolean().not_null().default(false))
+                    .col(
+                        ColumnDef::new(Alert::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_alert_alert_rule_id")
+                            .from(Alert::Table, Alert::RuleId)
+                            .to(AlertRule::Table, AlertRule::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_alert_entry_id")
+                            .from(Alert::Table, Alert::EntryId)
+                            .to(FeedEntry::Table, FeedEntry::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // An entry alerts once per rule, even when it is saved again
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_alert_alert_rule_id_entry_id")
+                    .table(Alert::Table)
+                    .col(Alert::RuleId)
+                    .col(Alert::EntryId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // The inbox is read newest first
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_alert_created_at")
+                    .table(Alert::Table)
+                    .col(Alert::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    // Define how to rollback this migration: Drop the Alert and AlertRule tables.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Alert::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(AlertRule::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum AlertRule {
+    Table,
+    Id,
+    Name,
+    Pattern,
+    IsRegex,
+    FeedId,
+    FolderId,
+    Enabled,
+    SnoozedUntil,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+pub enum Alert {
+    Table,
+    Id,
+    #[iden = "alert_rule_id"]
+    RuleId,
+    EntryId,
+    IsRead,
+    NotificationPending,
+    CreatedAt,
+}
+
+// Reference to the Feed table from the first migration
+#[derive(Iden)]
+pub enum Feed {
+    Table,
+    Id,
+}
+
+// Reference to the FeedEntry table from the second migration
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+    Id,
+}
+
+// Reference to the Folder table from the third migration
+#[derive(Iden)]
+pub enum Folder {
+    Table,
+    Id,
+}
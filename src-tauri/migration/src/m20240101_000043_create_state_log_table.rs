@@ -0,0 +1,85 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000043_create_state_log_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the StateLog table, which records every
+    // change to an entry's read or starred flag with when it was made, so copies of the
+    // library can be merged last-writer-wins per entry and field.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(StateLog::Table)
+                    .col(
+                        ColumnDef::new(StateLog::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(StateLog::EntryId).integer().not_null())
+                    // "read" or "starred"
+                    .col(ColumnDef::new(StateLog::Field).string().not_null())
+                    .col(ColumnDef::new(StateLog::Value).boolean().not_null())
+                    .col(ColumnDef::new(StateLog::ChangedAt).timestamp().not_null())
+                    // Where the change was made: "local", or the device it was merged from
+                    .col(ColumnDef::new(StateLog::Origin).string().not_null().default("local"))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_state_log_entry_id")
+                            .from(StateLog::Table, StateLog::EntryId)
+                            .to(FeedEntry::Table, FeedEntry::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The latest change to an entry's field is looked up on every merge
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_state_log_entry_id_field_changed_at")
+                    .table(StateLog::Table)
+                    .col(StateLog::EntryId)
+                    .col(StateLog::Field)
+                    .col(StateLog::ChangedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the StateLog table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StateLog::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum StateLog {
+    Table,
+    Id,
+    EntryId,
+    Field,
+    Value,
+    ChangedAt,
+    Origin,
+}
+
+// Reference to the FeedEntry table from the second migration
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+    Id,
+}
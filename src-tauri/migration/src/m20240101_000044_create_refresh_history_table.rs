@@ -0,0 +1,73 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000044_create_refresh_history_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the RefreshHistory table, which keeps the
+    // outcome of every refresh of the whole library, manual or scheduled, across restarts.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RefreshHistory::Table)
+                    .col(
+                        ColumnDef::new(RefreshHistory::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    // "manual" or "scheduled"
+                    .col(ColumnDef::new(RefreshHistory::Source).string().not_null())
+                    .col(ColumnDef::new(RefreshHistory::FinishedAt).timestamp().not_null())
+                    .col(ColumnDef::new(RefreshHistory::TotalProcessed).integer().not_null())
+                    .col(ColumnDef::new(RefreshHistory::SuccessfulCount).integer().not_null())
+                    .col(ColumnDef::new(RefreshHistory::FailedCount).integer().not_null())
+                    .col(ColumnDef::new(RefreshHistory::EntriesAdded).integer().not_null())
+                    .col(ColumnDef::new(RefreshHistory::DurationSeconds).big_integer().not_null())
+                    // JSON array of the refresh's errors
+                    .col(ColumnDef::new(RefreshHistory::Errors).text().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // History is listed and pruned newest first
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_refresh_history_finished_at")
+                    .table(RefreshHistory::Table)
+                    .col(RefreshHistory::FinishedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the RefreshHistory table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RefreshHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum RefreshHistory {
+    Table,
+    Id,
+    Source,
+    FinishedAt,
+    TotalProcessed,
+    SuccessfulCount,
+    FailedCount,
+    EntriesAdded,
+    DurationSeconds,
+    Errors,
+}
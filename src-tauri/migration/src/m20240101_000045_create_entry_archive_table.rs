@@ -0,0 +1,91 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000045_create_entry_archive_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the EntryArchive table, the cold tier old
+    // entries are moved to. The title and link stay plain for listing; the rest of the entry
+    // is kept as compressed JSON.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EntryArchive::Table)
+                    .col(
+                        ColumnDef::new(EntryArchive::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(EntryArchive::FeedId).integer().not_null())
+                    .col(ColumnDef::new(EntryArchive::Guid).text().not_null())
+                    .col(ColumnDef::new(EntryArchive::Title).string().not_null())
+                    .col(ColumnDef::new(EntryArchive::Link).text().not_null())
+                    .col(ColumnDef::new(EntryArchive::PublishedAt).timestamp().null())
+                    .col(ColumnDef::new(EntryArchive::CreatedAt).timestamp().not_null())
+                    .col(ColumnDef::new(EntryArchive::ArchivedAt).timestamp().not_null())
+                    // Deflate-compressed JSON of the entry's remaining fields
+                    .col(ColumnDef::new(EntryArchive::Payload).binary().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_entry_archive_feed_id")
+                            .from(EntryArchive::Table, EntryArchive::FeedId)
+                            .to(Feed::Table, Feed::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // A feed's archived guids are checked on every save, so archived entries aren't
+        // added back as new
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_entry_archive_feed_id_guid")
+                    .table(EntryArchive::Table)
+                    .col(EntryArchive::FeedId)
+                    .col(EntryArchive::Guid)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the EntryArchive table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EntryArchive::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum EntryArchive {
+    Table,
+    Id,
+    FeedId,
+    Guid,
+    Title,
+    Link,
+    PublishedAt,
+    CreatedAt,
+    ArchivedAt,
+    Payload,
+}
+
+// Reference to the Feed table from the first migration
+#[derive(Iden)]
+pub enum Feed {
+    Table,
+    Id,
+}
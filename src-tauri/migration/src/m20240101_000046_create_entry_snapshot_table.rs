@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000046_create_entry_snapshot_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the EntrySnapshot table, a permanent copy of
+    // each starred entry's article. There's deliberately no foreign key to the entry: the
+    // snapshot outlives the entry and its feed.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EntrySnapshot::Table)
+                    .col(
+                        ColumnDef::new(EntrySnapshot::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(EntrySnapshot::EntryId).integer().not_null().unique_key())
+                    .col(ColumnDef::new(EntrySnapshot::FeedTitle).string().null())
+                    .col(ColumnDef::new(EntrySnapshot::Title).string().not_null())
+                    .col(ColumnDef::new(EntrySnapshot::Link).text().not_null())
+                    .col(ColumnDef::new(EntrySnapshot::Content).text().not_null())
+                    // JSON list of the image URLs saved alongside the content
+                    .col(ColumnDef::new(EntrySnapshot::ImageUrls).text().not_null())
+                    .col(ColumnDef::new(EntrySnapshot::SnapshottedAt).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the EntrySnapshot table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EntrySnapshot::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum EntrySnapshot {
+    Table,
+    Id,
+    EntryId,
+    FeedTitle,
+    Title,
+    Link,
+    Content,
+    ImageUrls,
+    SnapshottedAt,
+}
@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20240101_000049_rename_entry_canonical_url_to_dedup_key"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Rename the normalized link entries are deduplicated
+    // by to dedup_key, so it isn't mistaken for the canonical link read from the page.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_feed_entries_canonical_url")
+                    .table(FeedEntry::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .rename_column(FeedEntry::CanonicalUrl, FeedEntry::DedupKey)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feed_entries_dedup_key")
+                    .table(FeedEntry::Table)
+                    .col(FeedEntry::DedupKey)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Rename the column and its index back.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_feed_entries_dedup_key")
+                    .table(FeedEntry::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FeedEntry::Table)
+                    .rename_column(FeedEntry::DedupKey, FeedEntry::CanonicalUrl)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feed_entries_canonical_url")
+                    .table(FeedEntry::Table)
+                    .col(FeedEntry::CanonicalUrl)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum FeedEntry {
+    Table,
+    CanonicalUrl,
+    DedupKey,
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use sea_orm::*;
+use sea_orm::sea_query::Expr;
+use tauri::State;
+use chrono::{DateTime as ChronoDateTime};
+use crate::entities::{prelude::*, *};
+use crate::models::{
+    AppState,
+    AlertMatcher,
+    AlertResponse,
+    AlertRuleResponse,
+    CreateAlertRuleRequest,
+    UpdateAlertRuleRequest,
+};
+
+fn validate_alert_rule(name: &str, pattern: &str, is_regex: bool) -> Result<(String, String), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Alert rule name cannot be empty".to_string());
+    }
+    AlertMatcher::new(pattern, is_regex)?;
+    Ok((name.to_string(), pattern.trim().to_string()))
+}
+
+// CREATE - Add an alert rule; entries matching it from then on land in the alerts inbox
+#[tauri::command]
+pub async fn create_alert_rule(
+    state: State<'_, AppState>,
+    request: CreateAlertRuleRequest,
+) -> Result<AlertRuleResponse, String> {
+    let is_regex = request.is_regex.unwrap_or(false);
+    let (name, pattern) = validate_alert_rule(&request.name, &request.pattern, is_regex)?;
+
+    let now = chrono::Utc::now().naive_utc();
+    let new_rule = alert_rule::ActiveModel {
+        name: ActiveValue::Set(name),
+        pattern: ActiveValue::Set(pattern),
+        is_regex: ActiveValue::Set(is_regex),
+        feed_id: ActiveValue::Set(request.feed_id),
+        folder_id: ActiveValue::Set(request.folder_id),
+        enabled: ActiveValue::Set(request.enabled.unwrap_or(true)),
+        snoozed_until: ActiveValue::Set(None),
+        created_at: ActiveValue::Set(now),
+        updated_at: ActiveValue::Set(now),
+        ..Default::default()
+    };
+
+    let result = new_rule
+        .insert(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to create alert rule: {}", e))?;
+
+    Ok(result.into())
+}
+
+// READ - Get all alert rules
+#[tauri::command]
+pub async fn get_alert_rules(state: State<'_, AppState>) -> Result<Vec<AlertRuleResponse>, String> {
+    let rules = AlertRule::find()
+        .order_by_asc(alert_rule::Column::Name)
+        .all(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to fetch alert rules: {}", e))?;
+
+    Ok(rules.into_iter().map(|rule| rule.into()).collect())
+}
+
+// UPDATE - Replace an alert rule's pattern, scope and enabled state
+#[tauri::command]
+pub async fn update_alert_rule(
+    state: State<'_, AppState>,
+    request: UpdateAlertRuleRequest,
+) -> Result<AlertRuleResponse, String> {
+    let db = &state.db().await;
+
+    let (name, pattern) = validate_alert_rule(&request.name, &request.pattern, request.is_regex)?;
+
+    let existing_rule = AlertRule::find_by_id(request.id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch alert rule: {}", e))?
+        .ok_or("Alert rule not found")?;
+
+    let mut updated_rule: alert_rule::ActiveModel = existing_rule.into();
+    updated_rule.name = ActiveValue::Set(name);
+    updated_rule.pattern = ActiveValue::Set(pattern);
+    updated_rule.is_regex = ActiveValue::Set(request.is_regex);
+    updated_rule.feed_id = ActiveValue::Set(request.feed_id);
+    updated_rule.folder_id = ActiveValue::Set(request.folder_id);
+    updated_rule.enabled = ActiveValue::Set(request.enabled);
+    updated_rule.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+    let result = updated_rule
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to update alert rule: {}", e))?;
+
+    Ok(result.into())
+}
+
+// UTILITY - Silence an alert rule's notifications until the given time, or unsnooze it when
+// until is None. Its matches keep arriving in the inbox meanwhile.
+#[tauri::command]
+pub async fn snooze_alert_rule(
+    state: State<'_, AppState>,
+    id: i32,
+    until: Option<String>,
+) -> Result<AlertRuleResponse, String> {
+    let db = &state.db().await;
+
+    let snoozed_until = match until {
+        Some(until) => Some(
+            ChronoDateTime::parse_from_rfc3339(&until)
+                .map_err(|e| format!("Invalid snooze time: {}", e))?
+                .naive_utc(),
+        ),
+        None => None,
+    };
+
+    let existing_rule = AlertRule::find_by_id(id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch alert rule: {}", e))?
+        .ok_or("Alert rule not found")?;
+
+    let mut updated_rule: alert_rule::ActiveModel = existing_rule.into();
+    updated_rule.snoozed_until = ActiveValue::Set(snoozed_until);
+    updated_rule.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+    let result = updated_rule
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to snooze alert rule: {}", e))?;
+
+    Ok(result.into())
+}
+
+// DELETE - Delete an alert rule and its alerts
+#[tauri::command]
+pub async fn delete_alert_rule(state: State<'_, AppState>, id: i32) -> Result<String, String> {
+    let result = AlertRule::delete_by_id(id)
+        .exec(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to delete alert rule: {}", e))?;
+
+    if result.rows_affected == 0 {
+        return Err("Alert rule not found".to_string());
+    }
+
+    Ok(format!("Alert rule with ID {} deleted successfully", id))
+}
+
+// READ - The alerts inbox, newest first, optionally only unread alerts or one rule's
+#[tauri::command]
+pub async fn get_alerts(
+    state: State<'_, AppState>,
+    alert_rule_id: Option<i32>,
+    unread_only: Option<bool>,
+    limit: Option<u64>,
+) -> Result<Vec<AlertResponse>, String> {
+    let db = &state.db().await;
+
+    let mut query = Alert::find();
+    if let Some(alert_rule_id) = alert_rule_id {
+        query = query.filter(alert::Column::AlertRuleId.eq(alert_rule_id));
+    }
+    if unread_only.unwrap_or(false) {
+        query = query.filter(alert::Column::IsRead.eq(false));
+    }
+    let alerts = query
+        .order_by_desc(alert::Column::CreatedAt)
+        .order_by_desc(alert::Column::Id)
+        .limit(limit.unwrap_or(100))
+        .find_also_related(FeedEntry)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch alerts: {}", e))?;
+
+    let rule_names: HashMap<i32, String> = AlertRule::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch alert rules: {}", e))?
+        .into_iter()
+        .map(|rule| (rule.id, rule.name))
+        .collect();
+
+    Ok(alerts
+        .into_iter()
+        .filter_map(|(alert, entry)| {
+            Some(AlertResponse {
+                id: alert.id,
+                alert_rule_id: alert.alert_rule_id,
+                rule_name: rule_names.get(&alert.alert_rule_id)?.clone(),
+                is_read: alert.is_read,
+                created_at: alert.created_at.to_string(),
+                entry: entry?.into(),
+            })
+        })
+        .collect())
+}
+
+// READ - How many alerts haven't been read yet
+#[tauri::command]
+pub async fn get_unread_alert_count(state: State<'_, AppState>) -> Result<u64, String> {
+    Alert::find()
+        .filter(alert::Column::IsRead.eq(false))
+        .count(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to count unread alerts: {}", e))
+}
+
+// UPDATE - Mark the given alerts as read, or every alert when ids is None
+#[tauri::command]
+pub async fn mark_alerts_read(state: State<'_, AppState>, ids: Option<Vec<i32>>) -> Result<u64, String> {
+    let mut update = Alert::update_many()
+        .col_expr(alert::Column::IsRead, Expr::value(true))
+        .filter(alert::Column::IsRead.eq(false));
+    if let Some(ids) = ids {
+        update = update.filter(alert::Column::Id.is_in(ids));
+    }
+
+    let result = update
+        .exec(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to mark alerts as read: {}", e))?;
+
+    Ok(result.rows_affected)
+}
+
+// DELETE - Dismiss alerts from the inbox; their entries are kept
+#[tauri::command]
+pub async fn delete_alerts(state: State<'_, AppState>, ids: Vec<i32>) -> Result<u64, String> {
+    let result = Alert::delete_many()
+        .filter(alert::Column::Id.is_in(ids))
+        .exec(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to delete alerts: {}", e))?;
+
+    Ok(result.rows_affected)
+}
@@ -0,0 +1,97 @@
+use sea_orm::*;
+use tauri::State;
+use crate::entities::{prelude::*, *};
+use crate::models::{AppState, AnnotationResponse, CreateAnnotationRequest, UpdateAnnotationRequest, normalize_note};
+
+// CREATE - Attach a note to an entry
+#[tauri::command]
+pub async fn create_annotation(
+    state: State<'_, AppState>,
+    request: CreateAnnotationRequest,
+) -> Result<AnnotationResponse, String> {
+    let db = &state.db().await;
+
+    let note = normalize_note(&request.note)?;
+
+    FeedEntry::find_by_id(request.entry_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entry: {}", e))?
+        .ok_or("Feed entry not found")?;
+
+    let now = chrono::Utc::now().naive_utc();
+    let new_annotation = annotation::ActiveModel {
+        entry_id: ActiveValue::Set(request.entry_id),
+        note: ActiveValue::Set(note),
+        created_at: ActiveValue::Set(now),
+        updated_at: ActiveValue::Set(now),
+        ..Default::default()
+    };
+
+    let result = new_annotation
+        .insert(db)
+        .await
+        .map_err(|e| format!("Failed to create annotation: {}", e))?;
+
+    Ok(result.into())
+}
+
+// READ - Get the notes on an entry, oldest first
+#[tauri::command]
+pub async fn get_entry_annotations(
+    state: State<'_, AppState>,
+    entry_id: i32,
+) -> Result<Vec<AnnotationResponse>, String> {
+    let annotations = Annotation::find()
+        .filter(annotation::Column::EntryId.eq(entry_id))
+        .order_by_asc(annotation::Column::CreatedAt)
+        .order_by_asc(annotation::Column::Id)
+        .all(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to fetch annotations: {}", e))?;
+
+    Ok(annotations.into_iter().map(|annotation| annotation.into()).collect())
+}
+
+// UPDATE - Edit a note
+#[tauri::command]
+pub async fn update_annotation(
+    state: State<'_, AppState>,
+    request: UpdateAnnotationRequest,
+) -> Result<AnnotationResponse, String> {
+    let db = &state.db().await;
+
+    let note = normalize_note(&request.note)?;
+
+    let existing_annotation = Annotation::find_by_id(request.id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch annotation: {}", e))?
+        .ok_or("Annotation not found")?;
+
+    let mut updated_annotation: annotation::ActiveModel = existing_annotation.into();
+    updated_annotation.note = ActiveValue::Set(note);
+    updated_annotation.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+    let result = updated_annotation
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to update annotation: {}", e))?;
+
+    Ok(result.into())
+}
+
+// DELETE - Delete a note
+#[tauri::command]
+pub async fn delete_annotation(state: State<'_, AppState>, id: i32) -> Result<String, String> {
+    let result = Annotation::delete_by_id(id)
+        .exec(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to delete annotation: {}", e))?;
+
+    if result.rows_affected == 0 {
+        return Err("Annotation not found".to_string());
+    }
+
+    Ok(format!("Annotation with ID {} deleted successfully", id))
+}
@@ -0,0 +1,49 @@
+use tauri::State;
+use crate::models::{
+    AppState,
+    ArchiveConfig,
+    ArchivedEntry,
+    archive_old_entries,
+    load_archive_config,
+    search_archived_entries,
+    set_setting_value,
+    ARCHIVE,
+    DEFAULT_ARCHIVE_SEARCH_LIMIT,
+};
+
+#[tauri::command]
+pub async fn get_archive_settings(state: State<'_, AppState>) -> Result<ArchiveConfig, String> {
+    Ok(load_archive_config(&state.db().await).await)
+}
+
+// UPDATE - Turn archiving on or off and set how old entries must be. Takes effect on the
+// scheduler's next daily run.
+#[tauri::command]
+pub async fn update_archive_settings(
+    state: State<'_, AppState>,
+    settings: ArchiveConfig,
+) -> Result<ArchiveConfig, String> {
+    settings.validate()?;
+    set_setting_value(&state.db().await, ARCHIVE, &settings).await?;
+    Ok(settings)
+}
+
+// Archive old entries right away, whether or not the scheduler would. Returns how many moved.
+#[tauri::command]
+pub async fn archive_old_entries_now(state: State<'_, AppState>) -> Result<u64, String> {
+    let db = state.db().await;
+    let config = load_archive_config(&db).await;
+    archive_old_entries(&db, &config, chrono::Utc::now().naive_utc()).await
+}
+
+// Archived entries aren't in the regular search; this decompresses and scans them on demand
+#[tauri::command]
+pub async fn search_archive(
+    state: State<'_, AppState>,
+    query: String,
+    feed_id: Option<i32>,
+    limit: Option<u64>,
+) -> Result<Vec<ArchivedEntry>, String> {
+    let limit = limit.unwrap_or(DEFAULT_ARCHIVE_SEARCH_LIMIT);
+    search_archived_entries(&state.db().await, &query, feed_id, limit).await
+}
@@ -0,0 +1,40 @@
+use tauri::{AppHandle, State};
+use crate::models::{
+    AppState,
+    BackupConfig,
+    BackupStatus,
+    back_up_library,
+    load_backup_config,
+    load_backup_status,
+    set_setting_value,
+    BACKUP,
+};
+
+#[tauri::command]
+pub async fn get_backup_settings(state: State<'_, AppState>) -> Result<BackupConfig, String> {
+    Ok(load_backup_config(&state.db().await).await)
+}
+
+// UPDATE - Turn scheduled backups on or off, and set where they go, how often and how many are
+// kept. The scheduler writes the first one on its next tick.
+#[tauri::command]
+pub async fn update_backup_settings(
+    state: State<'_, AppState>,
+    settings: BackupConfig,
+) -> Result<BackupConfig, String> {
+    settings.validate()?;
+    set_setting_value(&state.db().await, BACKUP, &settings).await?;
+    Ok(settings)
+}
+
+// Back up right away, whether or not backups are scheduled. Returns the files written.
+#[tauri::command]
+pub async fn back_up_now(app: AppHandle, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    back_up_library(&app, &state.db().await, chrono::Utc::now().naive_utc()).await
+}
+
+// READ - When the last backup ran and how it went, when the next is due, and the backups kept
+#[tauri::command]
+pub async fn get_backup_status(app: AppHandle, state: State<'_, AppState>) -> Result<BackupStatus, String> {
+    load_backup_status(&app, &state.db().await).await
+}
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+use sea_orm::*;
+use tauri::State;
+use crate::entities::prelude::*;
+use crate::models::{AppState, BandwidthUsageResponse, load_bandwidth_usage, set_setting_value, METERED_MODE};
+
+// How far back usage is reported when no range is given
+const DEFAULT_USAGE_DAYS: u32 = 30;
+
+// READ - Bytes downloaded per feed per day over the last `days` days (today included)
+#[tauri::command]
+pub async fn get_bandwidth_usage(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+) -> Result<Vec<BandwidthUsageResponse>, String> {
+    let db = &state.db().await;
+    let days = days.unwrap_or(DEFAULT_USAGE_DAYS).max(1);
+    let since = chrono::Utc::now().date_naive() - chrono::Duration::days(days as i64 - 1);
+
+    let usage = load_bandwidth_usage(db, since).await?;
+
+    let feed_titles: HashMap<i32, Option<String>> = Feed::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feeds: {}", e))?
+        .into_iter()
+        .map(|feed| (feed.id, feed.title))
+        .collect();
+
+    Ok(usage
+        .into_iter()
+        .map(|usage| BandwidthUsageResponse {
+            feed_id: usage.feed_id,
+            feed_title: feed_titles.get(&usage.feed_id).cloned().flatten(),
+            day: usage.day.format("%Y-%m-%d").to_string(),
+            bytes: usage.bytes,
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_metered_mode(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(*state.metered_mode.read().await)
+}
+
+// UPDATE - Metered mode fetches one feed at a time, polls less often, and skips enclosure
+// and proxied image downloads. Applies right away.
+#[tauri::command]
+pub async fn set_metered_mode(state: State<'_, AppState>, enabled: bool) -> Result<bool, String> {
+    set_setting_value(&state.home_db, METERED_MODE, &enabled).await?;
+
+    *state.metered_mode.write().await = enabled;
+    Ok(enabled)
+}
@@ -0,0 +1,93 @@
+use sea_orm::*;
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindowBuilder};
+use url::Url;
+use crate::entities::prelude::*;
+use crate::models::{AppState, StoredCookie, add_feed_cookies, parse_netscape_cookies, stored_cookie, store_feed_cookies};
+
+// Label of the window a feed's site is logged into from
+fn login_window_label(feed_id: i32) -> String {
+    format!("feed-login-{}", feed_id)
+}
+
+// The page to log in on: the one given, or the home page of the feed's site
+async fn login_page(state: &State<'_, AppState>, feed_id: i32, url: Option<String>) -> Result<Url, String> {
+    let url = match url {
+        Some(url) => url,
+        None => {
+            let feed = Feed::find_by_id(feed_id)
+                .one(&state.db().await)
+                .await
+                .map_err(|e| format!("Failed to fetch feed: {}", e))?
+                .ok_or("Feed not found")?;
+            let feed_url = Url::parse(&feed.url).map_err(|e| format!("Invalid feed URL: {}", e))?;
+            format!("{}/", feed_url.origin().ascii_serialization())
+        }
+    };
+    let url = Url::parse(&url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Cannot log in on {}", url));
+    }
+    Ok(url)
+}
+
+// CREATE - Import cookies from a Netscape cookies.txt into a feed's jar, used when fetching its
+// entries' pages. Returns how many cookies the jar holds.
+#[tauri::command]
+pub async fn import_feed_cookies(feed_id: i32, cookies_txt: String) -> Result<usize, String> {
+    let cookies = parse_netscape_cookies(&cookies_txt)?;
+    if cookies.is_empty() {
+        return Err("No cookies found".to_string());
+    }
+    add_feed_cookies(feed_id, cookies).await
+}
+
+// CREATE - Open a browser window on the feed's site (or `url`) for the user to log in
+#[tauri::command]
+pub async fn open_feed_login(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    feed_id: i32,
+    url: Option<String>,
+) -> Result<(), String> {
+    let url = login_page(&state, feed_id, url).await?;
+    let label = login_window_label(feed_id);
+    if let Some(window) = app.get_webview_window(&label) {
+        return window.set_focus().map_err(|e| format!("Failed to focus login window: {}", e));
+    }
+
+    WebviewWindowBuilder::new(&app, label, WebviewUrl::External(url))
+        .title("Log in")
+        .inner_size(900.0, 700.0)
+        .build()
+        .map_err(|e| format!("Failed to open login window: {}", e))?;
+    Ok(())
+}
+
+// UPDATE - Keep the cookies the login window collected for the site it ended on, and close it.
+// Returns how many cookies the feed's jar holds.
+#[tauri::command]
+pub async fn finish_feed_login(app: AppHandle, feed_id: i32) -> Result<usize, String> {
+    let window = app
+        .get_webview_window(&login_window_label(feed_id))
+        .ok_or("No login window is open for this feed")?;
+    let page_url = window.url().map_err(|e| format!("Failed to read login page: {}", e))?;
+    // Reading cookies can deadlock on the main thread on Windows, so it runs on a worker
+    let cookies = {
+        let (window, page_url) = (window.clone(), page_url.clone());
+        tokio::task::spawn_blocking(move || window.cookies_for_url(page_url))
+            .await
+            .map_err(|e| format!("Failed to read cookies: {}", e))?
+            .map_err(|e| format!("Failed to read cookies: {}", e))?
+    };
+    let cookies: Vec<StoredCookie> = cookies.iter().filter_map(|cookie| stored_cookie(cookie, &page_url)).collect();
+
+    let count = add_feed_cookies(feed_id, cookies).await?;
+    window.close().map_err(|e| format!("Failed to close login window: {}", e))?;
+    Ok(count)
+}
+
+// DELETE - Forget every cookie stored for a feed
+#[tauri::command]
+pub async fn clear_feed_cookies(feed_id: i32) -> Result<(), String> {
+    store_feed_cookies(feed_id, &[]).await
+}
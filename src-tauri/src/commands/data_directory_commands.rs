@@ -0,0 +1,87 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, State};
+use crate::models::{AppState, DATA_DIRECTORY, copy_and_verify_directory, resolve_data_directory, set_setting_value};
+
+// How long a move waits for fetches already under way to finish
+const FETCH_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[tauri::command]
+pub async fn get_data_directory(app: AppHandle, state: State<'_, AppState>) -> Result<String, String> {
+    let data_directory = resolve_data_directory(&app, &state.db().await).await?;
+    Ok(data_directory.to_string_lossy().to_string())
+}
+
+// Move local files (image cache, downloads) to a new data directory: copy, verify, switch,
+// then clean up the old directory. The fetcher is paused for the duration of the move.
+// The database lives on the Postgres server from DATABASE_URL, so it is not moved.
+#[tauri::command]
+pub async fn move_data_directory(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    new_path: String,
+) -> Result<String, String> {
+    let current_directory = resolve_data_directory(&app, &state.db().await).await?;
+    let new_directory = PathBuf::from(&new_path);
+
+    if !new_directory.is_absolute() {
+        return Err("New data directory must be an absolute path".to_string());
+    }
+    if new_directory == current_directory {
+        return Err("Data directory is already at that location".to_string());
+    }
+    if state.downloads.has_active().await {
+        return Err("Wait for downloads to finish before moving the data directory".to_string());
+    }
+
+    // Hold the fetch queue (queued feeds wait rather than being lost) and let fetches already
+    // under way finish, so nothing writes to the old directory while it's copied
+    let paused_fetcher = match &state.async_fetcher {
+        Some(fetcher) if !fetcher.is_paused() => {
+            fetcher.pause();
+            Some(fetcher)
+        }
+        _ => None,
+    };
+    if let Some(fetcher) = &state.async_fetcher {
+        if !fetcher.wait_for_in_flight(FETCH_DRAIN_TIMEOUT).await {
+            if let Some(fetcher) = paused_fetcher {
+                fetcher.resume();
+            }
+            return Err("Feeds are still being fetched; try moving the data directory again shortly".to_string());
+        }
+    }
+
+    let result = async {
+        let (from, to) = (current_directory.clone(), new_directory.clone());
+        let files_copied = tokio::task::spawn_blocking(move || copy_and_verify_directory(&from, &to))
+            .await
+            .map_err(|e| format!("Failed to move data directory: {}", e))??;
+
+        set_setting_value(&state.db().await, DATA_DIRECTORY, &new_path).await?;
+
+        // The new directory is live at this point, so a failed cleanup only leaves stale files behind
+        if current_directory.exists() {
+            if let Err(e) = tokio::fs::remove_dir_all(&current_directory).await {
+                eprintln!("⚠️ Failed to remove old data directory {}: {}", current_directory.display(), e);
+            }
+        }
+
+        Ok::<usize, String>(files_copied)
+    }
+    .await;
+
+    if let Some(fetcher) = paused_fetcher {
+        fetcher.resume();
+    }
+
+    let files_copied = result?;
+    println!(
+        "📦 Moved data directory from {} to {} ({} files)",
+        current_directory.display(),
+        new_directory.display(),
+        files_copied
+    );
+
+    Ok(format!("Moved {} files to {}", files_copied, new_path))
+}
@@ -0,0 +1,22 @@
+use std::collections::HashMap;
+use crate::models::{FaultRule, clear_fault_rules, fault_rules, set_fault_rule};
+
+// Hidden commands for trying the fetcher against simulated network faults.
+// Only built with the `fault-injection` feature.
+
+#[tauri::command]
+pub fn debug_set_fault_rule(target: String, rule: FaultRule) -> Result<HashMap<String, FaultRule>, String> {
+    set_fault_rule(&target, rule);
+    Ok(fault_rules())
+}
+
+#[tauri::command]
+pub fn debug_get_fault_rules() -> Result<HashMap<String, FaultRule>, String> {
+    Ok(fault_rules())
+}
+
+#[tauri::command]
+pub fn debug_clear_fault_rules() -> Result<String, String> {
+    clear_fault_rules();
+    Ok("Fault rules cleared".to_string())
+}
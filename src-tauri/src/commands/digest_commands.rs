@@ -0,0 +1,164 @@
+use chrono::{DateTime as ChronoDateTime, NaiveDateTime};
+use sea_orm::*;
+use tauri::State;
+use crate::entities::{prelude::*, *};
+use crate::models::{
+    AppState,
+    DigestConfig,
+    DigestRangeRequest,
+    DigestResponse,
+    EmailConfig,
+    generate_digest as build_and_save_digest,
+    load_digest_config,
+    load_email_config,
+    send_digest_email as deliver_digest_email,
+    set_setting_value,
+    DIGEST,
+    EMAIL,
+};
+
+fn parse_range_bound(value: Option<&str>, field: &str) -> Result<Option<NaiveDateTime>, String> {
+    value
+        .map(|value| {
+            ChronoDateTime::parse_from_rfc3339(value)
+                .map(|dt| dt.naive_utc())
+                .map_err(|e| format!("Invalid {}: {}", field, e))
+        })
+        .transpose()
+}
+
+#[tauri::command]
+pub async fn get_digest_settings(state: State<'_, AppState>) -> Result<DigestConfig, String> {
+    Ok(load_digest_config(&state.db().await).await)
+}
+
+#[tauri::command]
+pub async fn update_digest_settings(
+    state: State<'_, AppState>,
+    settings: DigestConfig,
+) -> Result<DigestConfig, String> {
+    settings.validate()?;
+    set_setting_value(&state.db().await, DIGEST, &settings).await?;
+    Ok(settings)
+}
+
+#[tauri::command]
+pub async fn get_email_settings(state: State<'_, AppState>) -> Result<EmailConfig, String> {
+    Ok(load_email_config(&state.db().await).await)
+}
+
+// The SMTP password is not part of the settings; it is saved with store_secret as
+// an integration_api_key secret named "smtp"
+#[tauri::command]
+pub async fn update_email_settings(
+    state: State<'_, AppState>,
+    settings: EmailConfig,
+) -> Result<EmailConfig, String> {
+    settings.validate()?;
+    set_setting_value(&state.db().await, EMAIL, &settings).await?;
+    Ok(settings)
+}
+
+// CREATE - Compile the unread entries saved in a range (the last day by default) into a digest
+#[tauri::command]
+pub async fn generate_digest(
+    state: State<'_, AppState>,
+    range: Option<DigestRangeRequest>,
+) -> Result<DigestResponse, String> {
+    let db = &state.db().await;
+    let range = range.unwrap_or_default();
+
+    let until = parse_range_bound(range.until.as_deref(), "until")?.unwrap_or_else(|| chrono::Utc::now().naive_utc());
+    let since = parse_range_bound(range.since.as_deref(), "since")?.unwrap_or(until - chrono::Duration::days(1));
+
+    let config = load_digest_config(db).await;
+    let digest = build_and_save_digest(db, &config, since, until).await?;
+
+    Ok(digest.into())
+}
+
+// READ - Stored digests, newest first
+#[tauri::command]
+pub async fn get_digests(
+    state: State<'_, AppState>,
+    limit: Option<u64>,
+) -> Result<Vec<DigestResponse>, String> {
+    let db = &state.db().await;
+
+    let mut query = Digest::find().order_by_desc(digest::Column::CreatedAt);
+    if let Some(limit) = limit {
+        query = query.limit(limit);
+    }
+
+    let digests = query
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch digests: {}", e))?;
+
+    Ok(digests.into_iter().map(|digest| digest.into()).collect())
+}
+
+// SEND - Email a stored digest (the newest one when no id is given) to the configured address
+#[tauri::command]
+pub async fn send_digest_email(state: State<'_, AppState>, id: Option<i32>) -> Result<(), String> {
+    let db = &state.db().await;
+
+    let query = match id {
+        Some(id) => Digest::find_by_id(id),
+        None => Digest::find().order_by_desc(digest::Column::CreatedAt),
+    };
+    let digest = query
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch digest: {}", e))?
+        .ok_or("Digest not found")?;
+
+    deliver_digest_email(&load_email_config(db).await, &digest).await
+}
+
+// EXPORT - Write a digest to a file as "markdown" or "html"
+#[tauri::command]
+pub async fn export_digest(
+    state: State<'_, AppState>,
+    id: i32,
+    format: String,
+    path: String,
+) -> Result<(), String> {
+    let db = &state.db().await;
+
+    let digest = Digest::find_by_id(id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch digest: {}", e))?
+        .ok_or("Digest not found")?;
+
+    let contents = match format.as_str() {
+        "markdown" => digest.markdown,
+        "html" => digest.html,
+        other => return Err(format!("Unsupported digest format: {}", other)),
+    };
+
+    tokio::fs::write(&path, contents)
+        .await
+        .map_err(|e| format!("Failed to write digest file: {}", e))?;
+
+    println!("📤 Exported digest {} to {}", id, path);
+    Ok(())
+}
+
+// DELETE - Delete a stored digest
+#[tauri::command]
+pub async fn delete_digest(state: State<'_, AppState>, id: i32) -> Result<String, String> {
+    let db = &state.db().await;
+
+    let result = Digest::delete_by_id(id)
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to delete digest: {}", e))?;
+
+    if result.rows_affected == 0 {
+        return Err("Digest not found".to_string());
+    }
+
+    Ok(format!("Digest with ID {} deleted successfully", id))
+}
@@ -0,0 +1,58 @@
+use sea_orm::*;
+use tauri::State;
+use crate::entities::{prelude::*, *};
+use crate::models::{
+    AppState,
+    DisplayPolicyConfig,
+    FeedResponse,
+    load_display_policy_config,
+    set_setting_value,
+    DISPLAY_POLICY,
+};
+
+#[tauri::command]
+pub async fn get_display_policy(state: State<'_, AppState>) -> Result<DisplayPolicyConfig, String> {
+    Ok(load_display_policy_config(&state.db().await).await)
+}
+
+// UPDATE - Change how long read entries stay in entry lists across every view
+#[tauri::command]
+pub async fn update_display_policy(
+    state: State<'_, AppState>,
+    settings: DisplayPolicyConfig,
+) -> Result<DisplayPolicyConfig, String> {
+    set_setting_value(&state.db().await, DISPLAY_POLICY, &settings).await?;
+    Ok(settings)
+}
+
+// UPDATE - Override how many days one feed's read entries stay listed (0 keeps them listed),
+// or follow the global policy when None
+#[tauri::command]
+pub async fn set_feed_hide_read_after_days(
+    state: State<'_, AppState>,
+    feed_id: i32,
+    hide_read_after_days: Option<i32>,
+) -> Result<FeedResponse, String> {
+    let db = &state.db().await;
+
+    if hide_read_after_days.is_some_and(|days| days < 0) {
+        return Err("A feed's read entry cutoff cannot be negative".to_string());
+    }
+
+    let existing_feed = Feed::find_by_id(feed_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed: {}", e))?
+        .ok_or("Feed not found")?;
+
+    let mut updated_feed: feed::ActiveModel = existing_feed.into();
+    updated_feed.hide_read_after_days = ActiveValue::Set(hide_read_after_days);
+    updated_feed.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+    let result = updated_feed
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to update feed: {}", e))?;
+
+    Ok(result.into())
+}
@@ -0,0 +1,82 @@
+use tauri::State;
+use crate::models::{
+    AppState,
+    BlockedDomain,
+    DomainAction,
+    DomainRulesApplied,
+    DomainRulesConfig,
+    apply_domain_rules as apply_rules_to_entries,
+    load_domain_rules_config,
+    normalize_domain,
+    set_setting_value,
+    DOMAIN_RULES,
+};
+
+#[tauri::command]
+pub async fn get_domain_rules(state: State<'_, AppState>) -> Result<DomainRulesConfig, String> {
+    Ok(load_domain_rules_config(&state.db().await).await)
+}
+
+// UPDATE - Replace the blocked and allowed domain lists. New rules apply to entries saved from
+// now on; apply_domain_rules brings existing entries in line.
+#[tauri::command]
+pub async fn update_domain_rules(
+    state: State<'_, AppState>,
+    settings: DomainRulesConfig,
+) -> Result<DomainRulesConfig, String> {
+    let db = &state.db().await;
+
+    let settings = settings.normalized()?;
+    set_setting_value(db, DOMAIN_RULES, &settings).await?;
+
+    Ok(settings)
+}
+
+// CREATE - Block a domain, or change what happens to a blocked domain's entries
+#[tauri::command]
+pub async fn block_domain(
+    state: State<'_, AppState>,
+    domain: String,
+    action: Option<DomainAction>,
+) -> Result<DomainRulesConfig, String> {
+    let db = &state.db().await;
+
+    let domain = normalize_domain(&domain).ok_or_else(|| format!("Invalid domain: {}", domain))?;
+    let action = action.unwrap_or_default();
+    let mut config = load_domain_rules_config(db).await;
+    match config.blocked.iter_mut().find(|rule| rule.domain == domain) {
+        Some(rule) => rule.action = action,
+        None => config.blocked.push(BlockedDomain { domain, action }),
+    }
+    set_setting_value(db, DOMAIN_RULES, &config).await?;
+
+    Ok(config)
+}
+
+// DELETE - Stop blocking a domain
+#[tauri::command]
+pub async fn unblock_domain(state: State<'_, AppState>, domain: String) -> Result<DomainRulesConfig, String> {
+    let db = &state.db().await;
+
+    let domain = normalize_domain(&domain).ok_or_else(|| format!("Invalid domain: {}", domain))?;
+    let mut config = load_domain_rules_config(db).await;
+    config.blocked.retain(|rule| rule.domain != domain);
+    set_setting_value(db, DOMAIN_RULES, &config).await?;
+
+    Ok(config)
+}
+
+// UPDATE - Apply the current rules to entries already saved
+#[tauri::command]
+pub async fn apply_domain_rules(state: State<'_, AppState>) -> Result<DomainRulesApplied, String> {
+    let db = &state.db().await;
+
+    let config = load_domain_rules_config(db).await;
+    let applied = apply_rules_to_entries(db, &config).await?;
+    println!(
+        "🚫 Domain rules applied: {} hidden, {} unhidden, {} marked read",
+        applied.hidden, applied.unhidden, applied.marked_read
+    );
+
+    Ok(applied)
+}
@@ -0,0 +1,47 @@
+use sea_orm::*;
+use tauri::{AppHandle, State};
+use crate::entities::prelude::*;
+use crate::models::{AppState, DOWNLOADS_DIR, DownloadInfo, download_file_name, resolve_data_directory};
+
+// CREATE - Download an entry's enclosure (podcast episode, PDF, ...) into the data directory.
+// Progress is reported through "download:progress" events; an interrupted download resumes.
+#[tauri::command]
+pub async fn download_enclosure(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    entry_id: i32,
+) -> Result<DownloadInfo, String> {
+    if *state.metered_mode.read().await {
+        return Err("Metered mode is on; turn it off to download enclosures".to_string());
+    }
+
+    let entry = FeedEntry::find_by_id(entry_id)
+        .one(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entry: {}", e))?
+        .ok_or("Feed entry not found")?;
+
+    let url = entry.enclosure_url.ok_or("Entry has no enclosure to download")?;
+    let path = resolve_data_directory(&app, &state.db().await)
+        .await?
+        .join(DOWNLOADS_DIR)
+        .join(download_file_name(entry_id, &url));
+
+    Ok(state.downloads.enqueue(app, entry_id, url, path).await)
+}
+
+// READ - Get every download started this session
+#[tauri::command]
+pub async fn get_downloads(state: State<'_, AppState>) -> Result<Vec<DownloadInfo>, String> {
+    Ok(state.downloads.list().await)
+}
+
+// UTILITY - Cancel a queued or running download, keeping what was downloaded so far
+#[tauri::command]
+pub async fn cancel_download(state: State<'_, AppState>, entry_id: i32) -> Result<DownloadInfo, String> {
+    state
+        .downloads
+        .cancel(entry_id)
+        .await
+        .ok_or_else(|| format!("No download for entry {}", entry_id))
+}
@@ -1,8 +1,9 @@
+use std::collections::HashSet;
 use sea_orm::*;
-use tauri::State;
+use tauri::{AppHandle, State};
 use chrono;
 use crate::entities::{prelude::*, *};
-use crate::models::{AppState, CreateFeedRequest, UpdateFeedRequest, FeedResponse, FeedRefreshStatus, RefreshResponse, RefreshProgress, RefreshSummary, RefreshError, fetch_and_parse_feed, parse_feed_content, ParsedFeed, FetchPriority};
+use crate::models::{AppState, CreateFeedRequest, UpdateFeedRequest, FeedResponse, RefreshResponse, RefreshStartStatus, RefreshProgress, RefreshSummary, RefreshHistoryEntry, load_refresh_history, DEFAULT_REFRESH_HISTORY_LIMIT, fetch_and_parse_feed, parse_feed_content, ParsedFeed, AsyncFeedFetcher, FetchPriority, FeedStatsResponse, FEED_STATS_FETCH_WINDOW, compute_feed_stats, load_post_dates, ImagePolicy, CircuitBreakerStatus, RateLimiterStats, FetchQueueStatus, FetchMetricsSnapshot, FeedHealthReport, load_feed_health_reports, validate_fetch_url, load_allow_private_addresses, FeedSourceType, feed_source_type, create_subscription, FeedOpenStatsResponse, load_most_opened_feeds, self_and_descendants, FolderRefreshProgress, next_operation_id, track_folder_refresh, track_refresh, GithubWatch, github_feed_url, parse_github_repository, extraction_selector, extract_content, fetch_page_html, sanitize_html, load_privacy_config, feed_cookie_header, store_feed_cookies, parse_ca_certificates, pinned_fingerprint, fetch_full_content, Operation, OperationKind, OperationProgress, FeedRecommendation, load_feed_recommendations, DEFAULT_RECOMMENDATION_LIMIT, FeedPreferences};
 
 // CREATE - Insert a new feed
 #[tauri::command]
@@ -10,39 +11,43 @@ pub async fn create_feed(
     state: State<'_, AppState>,
     request: CreateFeedRequest,
 ) -> Result<FeedResponse, String> {
-    let db = &state.db;
-    
-    let now = chrono::Utc::now().naive_utc();
-    
-    let new_feed = feed::ActiveModel {
-        url: ActiveValue::Set(request.url),
-        title: ActiveValue::Set(request.title),
-        description: ActiveValue::Set(request.description),
-        created_at: ActiveValue::Set(now),
-        updated_at: ActiveValue::Set(now),
-        last_fetched_at: ActiveValue::Set(None),
-        ..Default::default()
+    let created_feed = create_subscription(&state.db().await, request).await?;
+    Ok(created_feed.into())
+}
+
+// CREATE - Follow a GitHub repository's releases or tags, given "owner/repo" or its URL
+#[tauri::command]
+pub async fn subscribe_github_repository(
+    state: State<'_, AppState>,
+    repository: String,
+    watch: Option<GithubWatch>,
+    folder_id: Option<i32>,
+) -> Result<FeedResponse, String> {
+    let (owner, repo) = parse_github_repository(&repository)?;
+    let watch = watch.unwrap_or_default();
+    let title = match watch {
+        GithubWatch::Releases => format!("{}/{} releases", owner, repo),
+        GithubWatch::Tags => format!("{}/{} tags", owner, repo),
     };
-    
-    let result = Feed::insert(new_feed)
-        .exec(db)
-        .await
-        .map_err(|e| format!("Failed to create feed: {}", e))?;
-    
-    // Fetch the created feed to return it
-    let created_feed = Feed::find_by_id(result.last_insert_id)
-        .one(db)
-        .await
-        .map_err(|e| format!("Failed to fetch created feed: {}", e))?
-        .ok_or("Failed to find created feed")?;
-    
+
+    let created_feed = create_subscription(
+        &state.db().await,
+        CreateFeedRequest {
+            url: github_feed_url(&owner, &repo, watch),
+            title: Some(title),
+            description: None,
+            folder_id,
+            source_type: Some(FeedSourceType::Github),
+        },
+    )
+    .await?;
     Ok(created_feed.into())
 }
 
 // READ - Get all feeds
 #[tauri::command]
 pub async fn get_all_feeds(state: State<'_, AppState>) -> Result<Vec<FeedResponse>, String> {
-    let db = &state.db;
+    let db = &state.db().await;
     
     let feeds = Feed::find()
         .all(db)
@@ -58,7 +63,7 @@ pub async fn get_feed_by_id(
     state: State<'_, AppState>,
     id: i32,
 ) -> Result<Option<FeedResponse>, String> {
-    let db = &state.db;
+    let db = &state.db().await;
     
     let feed = Feed::find_by_id(id)
         .one(db)
@@ -74,7 +79,7 @@ pub async fn get_feed_by_url(
     state: State<'_, AppState>,
     url: String,
 ) -> Result<Option<FeedResponse>, String> {
-    let db = &state.db;
+    let db = &state.db().await;
     
     let feed = Feed::find()
         .filter(feed::Column::Url.eq(url))
@@ -85,13 +90,146 @@ pub async fn get_feed_by_url(
     Ok(feed.map(|f| f.into()))
 }
 
+// READ - Get posting frequency and fetch reliability statistics for a feed
+#[tauri::command]
+pub async fn get_feed_stats(
+    state: State<'_, AppState>,
+    feed_id: i32,
+) -> Result<FeedStatsResponse, String> {
+    let db = &state.db().await;
+
+    Feed::find_by_id(feed_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed: {}", e))?
+        .ok_or("Feed not found")?;
+
+    let post_dates = load_post_dates(db, feed_id).await?;
+
+    let fetch_logs = FetchLog::find()
+        .filter(fetch_log::Column::FeedId.eq(feed_id))
+        .order_by_desc(fetch_log::Column::FetchedAt)
+        .limit(FEED_STATS_FETCH_WINDOW)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch fetch log: {}", e))?;
+
+    Ok(compute_feed_stats(feed_id, &post_dates, &fetch_logs, chrono::Utc::now().naive_utc()))
+}
+
+// READ - Get the feeds whose entries are opened most often
+#[tauri::command]
+pub async fn get_most_opened_feeds(
+    state: State<'_, AppState>,
+    limit: Option<u64>,
+) -> Result<Vec<FeedOpenStatsResponse>, String> {
+    load_most_opened_feeds(&state.db().await, limit.unwrap_or(10)).await
+}
+
+// READ - Get every feed's health (healthy, failing, dead or auth-broken) from its recent fetches
+#[tauri::command]
+pub async fn get_feed_health(state: State<'_, AppState>) -> Result<Vec<FeedHealthReport>, String> {
+    load_feed_health_reports(&state.db().await, chrono::Utc::now().naive_utc()).await
+}
+
+// READ - Suggest feeds to subscribe to from the sites subscriptions link to, their blogrolls
+// and their authors' other sites. Fetches subscribed and suggested sites' home pages, so it
+// can take a while.
+#[tauri::command]
+pub async fn get_feed_recommendations(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+) -> Result<Vec<FeedRecommendation>, String> {
+    if *state.metered_mode.read().await {
+        return Err("Metered mode is on; turn it off to look for feed recommendations".to_string());
+    }
+    let fetcher = state.async_fetcher.as_ref().ok_or("Async feed fetcher not available")?;
+
+    load_feed_recommendations(
+        &state.db().await,
+        fetcher.transport().as_ref(),
+        fetcher.config().allow_private_addresses,
+        limit.unwrap_or(DEFAULT_RECOMMENDATION_LIMIT),
+    )
+    .await
+}
+
+// READ - Preview what an extraction rule pulls out of a page, sanitized as an entry's content
+// would be. None when the rule matches nothing; without a rule, the heuristic is previewed.
+// Given a feed, the page is requested with that feed's cookies and TLS options.
+#[tauri::command]
+pub async fn test_extraction_rule(
+    state: State<'_, AppState>,
+    url: String,
+    selector: Option<String>,
+    feed_id: Option<i32>,
+) -> Result<Option<String>, String> {
+    let fetcher = state.async_fetcher.as_ref().ok_or("Async feed fetcher not available")?;
+    validate_fetch_url(&url, fetcher.config().allow_private_addresses).await?;
+
+    let (transport, cookies) = match feed_id {
+        Some(feed_id) => {
+            let feed = Feed::find_by_id(feed_id)
+                .one(&state.db().await)
+                .await
+                .map_err(|e| format!("Failed to fetch feed: {}", e))?
+                .ok_or("Feed not found")?;
+            (fetcher.transport_for(&feed.url).await, feed_cookie_header(feed_id, &url).await)
+        }
+        None => (fetcher.transport(), None),
+    };
+    let html = fetch_page_html(transport.as_ref(), &url, cookies).await?;
+    let image_policy = load_privacy_config(&state.db().await).await.image_policy;
+    Ok(extract_content(&html, selector.as_deref())?.map(|article| sanitize_html(&article, Some(&url), image_policy)))
+}
+
+// UPDATE - Replace the content of a feed's existing entries with the articles from their
+// pages, as fetch_full_content does for new entries. Runs in the background, reporting
+// "operation:progress" events under the returned operation's id until it finishes or is
+// cancelled with "operation:cancel".
+#[tauri::command]
+pub async fn fetch_feed_full_content(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    feed_id: i32,
+) -> Result<OperationProgress, String> {
+    let fetcher = state.async_fetcher.as_ref().ok_or("Async feed fetcher not available")?;
+    let db = state.db().await;
+    let feed = Feed::find_by_id(feed_id)
+        .one(&db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed: {}", e))?
+        .ok_or("Feed not found")?;
+    let entries = FeedEntry::find()
+        .filter(feed_entry::Column::FeedId.eq(feed_id))
+        .order_by_desc(feed_entry::Column::PublishedAt)
+        .all(&db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entries: {}", e))?;
+
+    let transport = fetcher.transport_for(&feed.url).await;
+    let image_policy = load_privacy_config(&db).await.image_policy;
+    let operation = Operation::start(&app, OperationKind::FullContent, entries.len());
+    let progress = operation.progress();
+
+    tauri::async_runtime::spawn(async move {
+        let result = fetch_full_content(db, transport, feed_id, feed.extraction_rule, image_policy, entries, Some(&operation)).await;
+        if let Err(e) = &result {
+            eprintln!("❌ Failed to fetch full content: {}", e);
+        }
+        operation.finish(result);
+    });
+
+    Ok(progress)
+}
+
 // UPDATE - Update an existing feed
 #[tauri::command]
 pub async fn update_feed(
     state: State<'_, AppState>,
     request: UpdateFeedRequest,
 ) -> Result<FeedResponse, String> {
-    let db = &state.db;
+    let db = &state.db().await;
     
     // First, find the existing feed
     let existing_feed = Feed::find_by_id(request.id)
@@ -100,19 +238,66 @@ pub async fn update_feed(
         .map_err(|e| format!("Failed to fetch feed: {}", e))?
         .ok_or("Feed not found")?;
     
+    // A new URL gets the same checks as subscribing to it
+    if let Some(url) = &request.url {
+        feed_source_type(&existing_feed.source_type)
+            .validate_url(url, load_allow_private_addresses(db).await)
+            .await?;
+    }
+
     // Create an active model for updating
     let mut updated_feed: feed::ActiveModel = existing_feed.into();
+    // The fetcher keys per-feed TLS clients by URL, so either change means rebuilding them
+    let tls_changed = request.url.is_some() || request.tls_ca_certificate.is_some() || request.tls_pinned_certificate.is_some();
     
     // Update fields if provided
     if let Some(url) = request.url {
         updated_feed.url = ActiveValue::Set(url);
     }
+    // A hand-edited title or description stops being overwritten by the publisher's
+    if request.title.is_some() || request.description.is_some() {
+        updated_feed.auto_title = ActiveValue::Set(false);
+    }
     if let Some(title) = request.title {
         updated_feed.title = ActiveValue::Set(Some(title));
     }
     if let Some(description) = request.description {
         updated_feed.description = ActiveValue::Set(Some(description));
     }
+    if let Some(auto_title) = request.auto_title {
+        updated_feed.auto_title = ActiveValue::Set(auto_title);
+    }
+    if let Some(image_policy) = request.image_policy {
+        let image_policy: ImagePolicy = image_policy.parse()?;
+        updated_feed.image_policy = ActiveValue::Set(Some(image_policy.as_str().to_string()));
+    }
+    if let Some(resolve_canonical_links) = request.resolve_canonical_links {
+        updated_feed.resolve_canonical_links = ActiveValue::Set(resolve_canonical_links);
+    }
+    if let Some(fetch_full_content) = request.fetch_full_content {
+        updated_feed.fetch_full_content = ActiveValue::Set(fetch_full_content);
+    }
+    if let Some(extraction_rule) = request.extraction_rule {
+        let extraction_rule = extraction_rule.trim();
+        if !extraction_rule.is_empty() {
+            extraction_selector(extraction_rule)?;
+        }
+        updated_feed.extraction_rule = ActiveValue::Set(Some(extraction_rule.to_string()).filter(|rule| !rule.is_empty()));
+    }
+    if let Some(ca_certificate) = request.tls_ca_certificate {
+        let ca_certificate = ca_certificate.trim();
+        if !ca_certificate.is_empty() {
+            parse_ca_certificates(ca_certificate)?;
+        }
+        updated_feed.tls_ca_certificate = ActiveValue::Set(Some(ca_certificate.to_string()).filter(|pem| !pem.is_empty()));
+    }
+    if let Some(pinned_certificate) = request.tls_pinned_certificate {
+        let pinned_sha256 = match pinned_certificate.trim() {
+            "" => None,
+            pinned_certificate => Some(pinned_fingerprint(pinned_certificate)?),
+        };
+        updated_feed.tls_pinned_sha256 = ActiveValue::Set(pinned_sha256);
+    }
     
     // Always update the updated_at timestamp
     updated_feed.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
@@ -121,17 +306,95 @@ pub async fn update_feed(
         .update(db)
         .await
         .map_err(|e| format!("Failed to update feed: {}", e))?;
+    if tls_changed {
+        if let Some(fetcher) = &state.async_fetcher {
+            fetcher.load_feed_overrides(db).await?;
+        }
+    }
     
     Ok(result.into())
 }
 
+// UPDATE - Override the fetcher's request timeout, retry count and per-host delay for one feed,
+// or follow the global settings where None. Applies from the feed's next fetch.
+#[tauri::command]
+pub async fn set_feed_fetch_overrides(
+    state: State<'_, AppState>,
+    feed_id: i32,
+    request_timeout_seconds: Option<i32>,
+    max_retries: Option<i32>,
+    rate_limit_delay_ms: Option<i32>,
+) -> Result<FeedResponse, String> {
+    let db = &state.db().await;
+
+    if request_timeout_seconds.is_some_and(|seconds| !(1..=600).contains(&seconds)) {
+        return Err("A feed's request timeout must be between 1 and 600 seconds".to_string());
+    }
+    if max_retries.is_some_and(|retries| !(0..=10).contains(&retries)) {
+        return Err("A feed's retry count must be between 0 and 10".to_string());
+    }
+    if rate_limit_delay_ms.is_some_and(|delay| !(0..=60_000).contains(&delay)) {
+        return Err("A feed's request delay must be between 0 and 60000 milliseconds".to_string());
+    }
+
+    let existing_feed = Feed::find_by_id(feed_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed: {}", e))?
+        .ok_or("Feed not found")?;
+
+    let mut updated_feed: feed::ActiveModel = existing_feed.into();
+    updated_feed.request_timeout_seconds = ActiveValue::Set(request_timeout_seconds);
+    updated_feed.max_retries = ActiveValue::Set(max_retries);
+    updated_feed.rate_limit_delay_ms = ActiveValue::Set(rate_limit_delay_ms);
+    updated_feed.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+    let result = updated_feed
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to update feed: {}", e))?;
+    if let Some(fetcher) = &state.async_fetcher {
+        fetcher.load_feed_overrides(db).await?;
+    }
+
+    Ok(result.into())
+}
+
+// UPDATE - Set how a feed's entries are listed by default: their order, the view the feed
+// opens in, and whether entries open in the browser
+#[tauri::command]
+pub async fn set_feed_preferences(
+    state: State<'_, AppState>,
+    feed_id: i32,
+    preferences: FeedPreferences,
+) -> Result<FeedResponse, String> {
+    let db = &state.db().await;
+
+    let existing_feed = Feed::find_by_id(feed_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed: {}", e))?
+        .ok_or("Feed not found")?;
+
+    let mut updated_feed: feed::ActiveModel = existing_feed.into();
+    updated_feed.preferences = ActiveValue::Set(preferences.to_column()?);
+    updated_feed.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+    let result = updated_feed
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to update feed: {}", e))?;
+
+    Ok(result.into())
+}
+
 // UPDATE - Update last_fetched_at timestamp
 #[tauri::command]
 pub async fn update_feed_last_fetched(
     state: State<'_, AppState>,
     id: i32,
 ) -> Result<FeedResponse, String> {
-    let db = &state.db;
+    let db = &state.db().await;
     
     let existing_feed = Feed::find_by_id(id)
         .one(db)
@@ -156,7 +419,7 @@ pub async fn update_feed_last_fetched(
 // DELETE - Delete a feed by ID
 #[tauri::command]
 pub async fn delete_feed(state: State<'_, AppState>, id: i32) -> Result<String, String> {
-    let db = &state.db;
+    let db = &state.db().await;
     
     // First check if the feed exists
     let existing_feed = Feed::find_by_id(id)
@@ -175,6 +438,10 @@ pub async fn delete_feed(state: State<'_, AppState>, id: i32) -> Result<String,
         .delete(db)
         .await
         .map_err(|e| format!("Failed to delete feed: {}", e))?;
+    // The feed is gone either way; a keychain failure only leaves its cookies behind
+    if let Err(e) = store_feed_cookies(id, &[]).await {
+        eprintln!("❌ Failed to delete cookies of feed {}: {}", id, e);
+    }
     
     Ok(format!("Feed with ID {} deleted successfully", id))
 }
@@ -187,7 +454,9 @@ pub fn greet(name: &str) -> String {
 // FEED PARSING COMMANDS
 
 #[tauri::command]
-pub async fn fetch_and_parse_feed_command(url: String) -> Result<ParsedFeed, String> {
+pub async fn fetch_and_parse_feed_command(state: State<'_, AppState>, url: String) -> Result<ParsedFeed, String> {
+    validate_fetch_url(&url, load_allow_private_addresses(&state.db().await).await).await?;
+    
     fetch_and_parse_feed(&url)
         .await
         .map_err(|e| e.to_string())
@@ -221,6 +490,28 @@ pub async fn stop_async_fetcher(state: State<'_, AppState>) -> Result<String, St
     }
 }
 
+// Hold queued fetches (e.g. on a metered connection) while letting in-flight ones finish.
+// Scheduled background refreshes are skipped while paused.
+#[tauri::command]
+pub async fn pause_fetch_queue(state: State<'_, AppState>) -> Result<String, String> {
+    if let Some(fetcher) = &state.async_fetcher {
+        fetcher.pause();
+        Ok("Fetch queue paused".to_string())
+    } else {
+        Err("Async feed fetcher not available".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn resume_fetch_queue(state: State<'_, AppState>) -> Result<String, String> {
+    if let Some(fetcher) = &state.async_fetcher {
+        fetcher.resume();
+        Ok("Fetch queue resumed".to_string())
+    } else {
+        Err("Async feed fetcher not available".to_string())
+    }
+}
+
 #[tauri::command]
 pub async fn get_async_fetcher_status(state: State<'_, AppState>) -> Result<bool, String> {
     if let Some(fetcher) = &state.async_fetcher {
@@ -231,12 +522,70 @@ pub async fn get_async_fetcher_status(state: State<'_, AppState>) -> Result<bool
 }
 
 #[tauri::command]
-pub fn queue_feed_for_async_fetch(
+pub async fn get_circuit_breaker_status(state: State<'_, AppState>) -> Result<Vec<CircuitBreakerStatus>, String> {
+    if let Some(fetcher) = &state.async_fetcher {
+        Ok(fetcher.circuit_breaker().statuses().await)
+    } else {
+        Err("Async feed fetcher not available".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn get_rate_limiter_stats(state: State<'_, AppState>) -> Result<Vec<RateLimiterStats>, String> {
+    if let Some(fetcher) = &state.async_fetcher {
+        Ok(fetcher.rate_limiter().stats().await)
+    } else {
+        Err("Async feed fetcher not available".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn get_queue_status(state: State<'_, AppState>) -> Result<FetchQueueStatus, String> {
+    if let Some(fetcher) = &state.async_fetcher {
+        Ok(fetcher.queue_status().await)
+    } else {
+        Err("Async feed fetcher not available".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn reset_circuit_breaker(state: State<'_, AppState>, domain: String) -> Result<String, String> {
+    if let Some(fetcher) = &state.async_fetcher {
+        fetcher.circuit_breaker().reset(&domain).await;
+        Ok(format!("Circuit breaker for '{}' reset", domain))
+    } else {
+        Err("Async feed fetcher not available".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn get_fetch_metrics(state: State<'_, AppState>) -> Result<FetchMetricsSnapshot, String> {
+    if let Some(fetcher) = &state.async_fetcher {
+        Ok(fetcher.metrics().snapshot().await)
+    } else {
+        Err("Async feed fetcher not available".to_string())
+    }
+}
+
+// Same metrics in Prometheus text exposition format
+#[tauri::command]
+pub async fn export_fetch_metrics_prometheus(state: State<'_, AppState>) -> Result<String, String> {
+    if let Some(fetcher) = &state.async_fetcher {
+        Ok(fetcher.metrics().snapshot().await.to_prometheus_text())
+    } else {
+        Err("Async feed fetcher not available".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn queue_feed_for_async_fetch(
     state: State<'_, AppState>,
     url: String,
     priority: Option<String>,
 ) -> Result<String, String> {
     if let Some(fetcher) = &state.async_fetcher {
+        validate_fetch_url(&url, fetcher.config().allow_private_addresses).await?;
+        
         let fetch_priority = match priority.as_deref() {
             Some("low") => FetchPriority::Low,
             Some("normal") => FetchPriority::Normal,
@@ -245,7 +594,7 @@ pub fn queue_feed_for_async_fetch(
             _ => FetchPriority::Normal,
         };
         
-        fetcher.queue_feed(url.clone(), fetch_priority)
+        fetcher.queue_feed(url.clone(), FeedSourceType::Http, fetch_priority)
             .map_err(|e| format!("Failed to queue feed: {}", e))?;
         
         Ok(format!("Feed '{}' queued for async fetching", url))
@@ -254,6 +603,8 @@ pub fn queue_feed_for_async_fetch(
     }
 }
 
+// DEPRECATED: listen for fetch:result events instead. The app's fetcher saves every result
+// to the library, so this only ever returns results of a fetcher without a database.
 #[tauri::command]
 pub async fn get_async_fetch_results(state: State<'_, AppState>) -> Result<Vec<String>, String> {
     if let Some(fetcher) = &state.async_fetcher {
@@ -291,7 +642,7 @@ pub async fn fetch_multiple_feeds_async(
         
         let mut queued_count = 0;
         for url in urls {
-            if fetcher.queue_feed(url, fetch_priority.clone()).is_ok() {
+            if fetcher.queue_feed(url, FeedSourceType::Http, fetch_priority.clone()).is_ok() {
                 queued_count += 1;
             }
         }
@@ -304,9 +655,27 @@ pub async fn fetch_multiple_feeds_async(
 
 // REFRESH COMMANDS
 
+// Response for a refresh that was refused because another one is still running
+async fn refresh_in_progress_response(fetcher: &AsyncFeedFetcher) -> RefreshResponse {
+    let progress = fetcher.get_refresh_progress().await;
+    RefreshResponse {
+        success: false,
+        status: RefreshStartStatus::AlreadyInProgress,
+        message: format!(
+            "A refresh is already in progress ({} of {} feeds done)",
+            progress.completed_feeds, progress.total_feeds
+        ),
+        total_feeds: progress.total_feeds,
+        estimated_completion_time: progress.estimated_time_remaining,
+        operation_id: None,
+    }
+}
+
+// Pass force to cancel a refresh that is still running and start over. The refresh is listed
+// among the operations, under the returned operation_id, and can be cancelled there.
 #[tauri::command]
-pub async fn refresh_all_feeds(state: State<'_, AppState>) -> Result<RefreshResponse, String> {
-    let db = &state.db;
+pub async fn refresh_all_feeds(app: AppHandle, state: State<'_, AppState>, force: Option<bool>) -> Result<RefreshResponse, String> {
+    let db = &state.db().await;
     
     // Get all feeds from database
     let feeds = Feed::find()
@@ -317,39 +686,52 @@ pub async fn refresh_all_feeds(state: State<'_, AppState>) -> Result<RefreshResp
     if feeds.is_empty() {
         return Ok(RefreshResponse {
             success: false,
+            status: RefreshStartStatus::NoFeeds,
             message: "No feeds found to refresh".to_string(),
             total_feeds: 0,
             estimated_completion_time: None,
+            operation_id: None,
         });
     }
     
     let total_feeds = feeds.len();
     
     if let Some(fetcher) = &state.async_fetcher {
-        // Start the refresh operation tracking
-        fetcher.start_refresh_operation(total_feeds).await;
+        // Start the refresh operation tracking, unless another refresh is still running
+        let feed_urls = feeds.iter().map(|feed| feed.url.clone()).collect();
+        let status = fetcher.start_refresh_operation(feed_urls, force.unwrap_or(false)).await;
+        if status == RefreshStartStatus::AlreadyInProgress {
+            return Ok(refresh_in_progress_response(fetcher).await);
+        }
         
         // Start async fetcher if not running
         if !fetcher.is_running().await {
             fetcher.start().await;
         }
         
-        // Queue all feeds for high-priority fetching
-        let mut queued_count = 0;
+        // Queue all feeds for high-priority fetching, listening first so the tracker misses nothing
+        let statuses = fetcher.subscribe_feed_statuses();
+        let mut queued_urls = HashSet::new();
         for feed in feeds {
-            if fetcher.queue_feed(feed.url.clone(), FetchPriority::High).is_ok() {
-                queued_count += 1;
+            if fetcher.queue_feed(feed.url.clone(), feed_source_type(&feed.source_type), FetchPriority::High).is_ok() {
+                queued_urls.insert(feed.url);
             }
         }
+        let queued_count = queued_urls.len();
+        let operation = Operation::start(&app, OperationKind::Refresh, queued_count);
+        let operation_id = operation.id();
+        tauri::async_runtime::spawn(track_refresh(app, operation, queued_urls, statuses));
         
         // Estimate completion time (rough calculation: 2 seconds per feed)
         let estimated_time = Some((total_feeds as u64) * 2);
         
         Ok(RefreshResponse {
             success: true,
+            status,
             message: format!("Started refreshing {} feeds", queued_count),
             total_feeds: queued_count,
             estimated_completion_time: estimated_time,
+            operation_id: Some(operation_id),
         })
     } else {
         Err("Async feed fetcher not available".to_string())
@@ -357,8 +739,13 @@ pub async fn refresh_all_feeds(state: State<'_, AppState>) -> Result<RefreshResp
 }
 
 #[tauri::command]
-pub async fn refresh_single_feed(state: State<'_, AppState>, feed_id: i32) -> Result<RefreshResponse, String> {
-    let db = &state.db;
+pub async fn refresh_single_feed(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    feed_id: i32,
+    force: Option<bool>,
+) -> Result<RefreshResponse, String> {
+    let db = &state.db().await;
     
     // Get specific feed by ID
     let feed = Feed::find_by_id(feed_id)
@@ -369,7 +756,10 @@ pub async fn refresh_single_feed(state: State<'_, AppState>, feed_id: i32) -> Re
     
     if let Some(fetcher) = &state.async_fetcher {
         // Start the refresh operation tracking for single feed
-        fetcher.start_refresh_operation(1).await;
+        let status = fetcher.start_refresh_operation(vec![feed.url.clone()], force.unwrap_or(false)).await;
+        if status == RefreshStartStatus::AlreadyInProgress {
+            return Ok(refresh_in_progress_response(fetcher).await);
+        }
         
         // Start async fetcher if not running
         if !fetcher.is_running().await {
@@ -377,20 +767,79 @@ pub async fn refresh_single_feed(state: State<'_, AppState>, feed_id: i32) -> Re
         }
         
         // Queue the feed for critical priority fetching
-        fetcher.queue_feed(feed.url.clone(), FetchPriority::Critical)
+        let statuses = fetcher.subscribe_feed_statuses();
+        fetcher.queue_feed(feed.url.clone(), feed_source_type(&feed.source_type), FetchPriority::Critical)
             .map_err(|e| format!("Failed to queue feed: {}", e))?;
+        let operation = Operation::start(&app, OperationKind::Refresh, 1);
+        let operation_id = operation.id();
+        tauri::async_runtime::spawn(track_refresh(app, operation, HashSet::from([feed.url.clone()]), statuses));
         
         Ok(RefreshResponse {
             success: true,
+            status,
             message: format!("Started refreshing feed: {}", feed.title.unwrap_or(feed.url.clone())),
             total_feeds: 1,
             estimated_completion_time: Some(5), // 5 seconds estimate for single feed
+            operation_id: Some(operation_id),
         })
     } else {
         Err("Async feed fetcher not available".to_string())
     }
 }
 
+// Refresh only the feeds in a folder and its subfolders. This runs alongside any global refresh
+// rather than replacing it; its progress arrives as refresh:folder_progress events tagged with
+// the returned operation_id.
+#[tauri::command]
+pub async fn refresh_folder(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    folder_id: i32,
+) -> Result<FolderRefreshProgress, String> {
+    let db = &state.db().await;
+
+    let folders = Folder::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch folders: {}", e))?;
+    let folder_ids = self_and_descendants(&folders, folder_id);
+    if folder_ids.is_empty() {
+        return Err("Folder not found".to_string());
+    }
+
+    let feeds = Feed::find()
+        .filter(feed::Column::FolderId.is_in(folder_ids))
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feeds: {}", e))?;
+
+    let fetcher = state.async_fetcher.as_ref().ok_or("Async feed fetcher not available")?;
+    if feeds.is_empty() {
+        return Ok(FolderRefreshProgress::new(next_operation_id(), folder_id, 0));
+    }
+
+    // Listen before queueing, so no completion can slip past the tracker
+    let statuses = fetcher.subscribe_feed_statuses();
+    if !fetcher.is_running().await {
+        fetcher.start().await;
+    }
+
+    let mut pending_urls = HashSet::new();
+    for feed in feeds {
+        if fetcher.queue_feed(feed.url.clone(), feed_source_type(&feed.source_type), FetchPriority::High).is_ok() {
+            pending_urls.insert(feed.url);
+        }
+    }
+    if pending_urls.is_empty() {
+        return Ok(FolderRefreshProgress::new(next_operation_id(), folder_id, 0));
+    }
+
+    let operation = Operation::start(&app, OperationKind::Refresh, pending_urls.len());
+    let progress = FolderRefreshProgress::new(operation.id(), folder_id, pending_urls.len());
+    tauri::async_runtime::spawn(track_folder_refresh(app, operation, progress.clone(), pending_urls, statuses));
+    Ok(progress)
+}
+
 #[tauri::command]
 pub async fn get_refresh_progress(state: State<'_, AppState>) -> Result<RefreshProgress, String> {
     if let Some(fetcher) = &state.async_fetcher {
@@ -407,4 +856,13 @@ pub async fn get_last_refresh_summary(state: State<'_, AppState>) -> Result<Opti
     } else {
         Err("Async feed fetcher not available".to_string())
     }
-} 
\ No newline at end of file
+}
+
+// READ - Finished refreshes, manual and scheduled, newest first
+#[tauri::command]
+pub async fn get_refresh_history(
+    state: State<'_, AppState>,
+    limit: Option<u64>,
+) -> Result<Vec<RefreshHistoryEntry>, String> {
+    load_refresh_history(&state.db().await, limit.unwrap_or(DEFAULT_REFRESH_HISTORY_LIMIT)).await
+}
\ No newline at end of file
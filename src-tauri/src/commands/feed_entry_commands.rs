@@ -1,5 +1,7 @@
 use sea_orm::*;
-use tauri::State;
+use sea_orm::sea_query::Expr;
+use tauri::{AppHandle, State};
+use tauri_plugin_opener::OpenerExt;
 use chrono::{DateTime as ChronoDateTime};
 use crate::entities::{prelude::*, *};
 use crate::models::{
@@ -8,7 +10,37 @@ use crate::models::{
     UpdateFeedEntryRequest, 
     FeedEntryResponse,
     CreateFeedWithEntriesRequest,
-    FeedWithEntriesResponse
+    FeedWithEntriesResponse,
+    MediaEntryQueryRequest,
+    EntryQueryRequest,
+    AdvancedEntryQueryRequest,
+    BulkEntryFilter,
+    BulkEntryAction,
+    build_entry_query,
+    build_advanced_entry_query,
+    apply_display_policy,
+    apply_feed_preferences,
+    load_display_policy,
+    run_bulk_action,
+    undo_bulk_action,
+    TimelineEntryResponse,
+    dedup_key,
+    entries_with_annotations,
+    entry_reading_stats,
+    load_cluster_feed_counts,
+    load_domain_rules_config,
+    load_allow_private_addresses,
+    validate_fetch_url,
+    load_related_entries,
+    RelatedEntry,
+    DEFAULT_RELATED_ENTRIES_LIMIT,
+    resolve_entry_canonical_link,
+    share_link,
+    snapshot_starred_entries,
+    only_story_representatives,
+    record_state_changes,
+    StateField,
+    LOCAL_ORIGIN,
 };
 
 // CREATE - Insert a new feed entry
@@ -17,7 +49,7 @@ pub async fn create_feed_entry(
     state: State<'_, AppState>,
     request: CreateFeedEntryRequest,
 ) -> Result<FeedEntryResponse, String> {
-    let db = &state.db;
+    let db = &state.db().await;
     
     let now = chrono::Utc::now().naive_utc();
     
@@ -31,10 +63,14 @@ pub async fn create_feed_entry(
         None
     };
     
+    let reading_stats = entry_reading_stats(request.content.as_deref(), request.description.as_deref());
+
     let new_entry = feed_entry::ActiveModel {
         feed_id: ActiveValue::Set(request.feed_id),
         title: ActiveValue::Set(request.title),
         description: ActiveValue::Set(request.description),
+        guid: ActiveValue::Set(request.link.clone()),
+        dedup_key: ActiveValue::Set(dedup_key(&request.link)),
         link: ActiveValue::Set(request.link),
         content: ActiveValue::Set(request.content),
         published_at: ActiveValue::Set(published_at),
@@ -42,6 +78,8 @@ pub async fn create_feed_entry(
         updated_at: ActiveValue::Set(now),
         is_read: ActiveValue::Set(false),
         is_starred: ActiveValue::Set(false),
+        word_count: ActiveValue::Set(reading_stats.map(|stats| stats.word_count)),
+        reading_time_minutes: ActiveValue::Set(reading_stats.map(|stats| stats.reading_time_minutes)),
         ..Default::default()
     };
     
@@ -66,7 +104,12 @@ pub async fn create_feed_with_entries(
     state: State<'_, AppState>,
     request: CreateFeedWithEntriesRequest,
 ) -> Result<FeedWithEntriesResponse, String> {
-    let db = &state.db;
+    let db = &state.db().await;
+    
+    validate_fetch_url(&request.url, load_allow_private_addresses(db).await).await?;
+    if let Some(domain) = load_domain_rules_config(db).await.blocked_subscription(&request.url) {
+        return Err(format!("Subscribing to {} is blocked", domain));
+    }
     
     // Start a transaction
     let txn = db.begin().await.map_err(|e| format!("Failed to start transaction: {}", e))?;
@@ -76,6 +119,7 @@ pub async fn create_feed_with_entries(
     // Create the feed first
     let new_feed = feed::ActiveModel {
         url: ActiveValue::Set(request.url),
+        auto_title: ActiveValue::Set(request.title.is_none()),
         title: ActiveValue::Set(request.title),
         description: ActiveValue::Set(request.description),
         created_at: ActiveValue::Set(now),
@@ -106,10 +150,14 @@ pub async fn create_feed_with_entries(
             None
         };
         
+        let reading_stats = entry_reading_stats(entry_request.content.as_deref(), entry_request.description.as_deref());
+
         let new_entry = feed_entry::ActiveModel {
             feed_id: ActiveValue::Set(feed_id),
             title: ActiveValue::Set(entry_request.title),
             description: ActiveValue::Set(entry_request.description),
+            guid: ActiveValue::Set(entry_request.link.clone()),
+            dedup_key: ActiveValue::Set(dedup_key(&entry_request.link)),
             link: ActiveValue::Set(entry_request.link),
             content: ActiveValue::Set(entry_request.content),
             published_at: ActiveValue::Set(published_at),
@@ -117,6 +165,8 @@ pub async fn create_feed_with_entries(
             updated_at: ActiveValue::Set(now),
             is_read: ActiveValue::Set(false),
             is_starred: ActiveValue::Set(false),
+            word_count: ActiveValue::Set(reading_stats.map(|stats| stats.word_count)),
+            reading_time_minutes: ActiveValue::Set(reading_stats.map(|stats| stats.reading_time_minutes)),
             ..Default::default()
         };
         
@@ -152,6 +202,7 @@ pub async fn create_feed_with_entries(
         created_at: created_feed.created_at.to_string(),
         updated_at: created_feed.updated_at.to_string(),
         last_fetched_at: created_feed.last_fetched_at.map(|dt| dt.to_string()),
+        folder_id: created_feed.folder_id,
         entries: created_entries,
     })
 }
@@ -162,16 +213,221 @@ pub async fn get_feed_entries(
     state: State<'_, AppState>,
     feed_id: i32,
 ) -> Result<Vec<FeedEntryResponse>, String> {
-    let db = &state.db;
+    let db = &state.db().await;
     
     let entries = FeedEntry::find()
         .filter(feed_entry::Column::FeedId.eq(feed_id))
+        .filter(feed_entry::Column::IsHidden.eq(false))
         .order_by_desc(feed_entry::Column::PublishedAt)
         .all(db)
         .await
         .map_err(|e| format!("Failed to fetch feed entries: {}", e))?;
     
-    Ok(entries.into_iter().map(|entry| entry.into()).collect())
+    entries_with_annotations(db, entries).await
+}
+
+// READ - Get entries with media, filtered and sorted by duration
+#[tauri::command]
+pub async fn get_media_entries(
+    state: State<'_, AppState>,
+    request: MediaEntryQueryRequest,
+) -> Result<Vec<FeedEntryResponse>, String> {
+    let db = &state.db().await;
+    
+    let mut query = FeedEntry::find()
+        .filter(feed_entry::Column::DurationSeconds.is_not_null())
+        .filter(feed_entry::Column::IsHidden.eq(false));
+    
+    if let Some(feed_id) = request.feed_id {
+        query = query.filter(feed_entry::Column::FeedId.eq(feed_id));
+    }
+    if let Some(min_duration) = request.min_duration_seconds {
+        query = query.filter(feed_entry::Column::DurationSeconds.gte(min_duration));
+    }
+    if let Some(max_duration) = request.max_duration_seconds {
+        query = query.filter(feed_entry::Column::DurationSeconds.lte(max_duration));
+    }
+    
+    query = match request.sort.as_deref() {
+        Some("shortest") => query.order_by_asc(feed_entry::Column::DurationSeconds),
+        Some("longest") => query.order_by_desc(feed_entry::Column::DurationSeconds),
+        _ => query.order_by_desc(feed_entry::Column::PublishedAt),
+    };
+    
+    if let Some(limit) = request.limit {
+        query = query.limit(limit);
+    }
+    
+    let entries = query
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch media entries: {}", e))?;
+    
+    entries_with_annotations(db, entries).await
+}
+
+// READ - Get entries matching any combination of folder, feed, tag, state and date filters.
+// Entries read longer ago than the display policy allows are left out. A single feed's list
+// follows its preferred order and view unless the request sets them.
+#[tauri::command]
+pub async fn query_entries(
+    state: State<'_, AppState>,
+    mut request: EntryQueryRequest,
+) -> Result<Vec<FeedEntryResponse>, String> {
+    let db = &state.db().await;
+    
+    apply_feed_preferences(db, &mut request).await?;
+    let now = chrono::Utc::now().naive_utc();
+    let policy = load_display_policy(db).await?;
+    let entries = apply_display_policy(build_entry_query(&request, now)?, &request, &policy, now)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to query feed entries: {}", e))?;
+    
+    entries_with_annotations(db, entries).await
+}
+
+// READ - Get entries for a smart view combining lists of folders, feeds and tags with state,
+// text and date filters, in a single query
+#[tauri::command]
+pub async fn query_entries_advanced(
+    state: State<'_, AppState>,
+    mut request: AdvancedEntryQueryRequest,
+) -> Result<Vec<FeedEntryResponse>, String> {
+    let db = &state.db().await;
+
+    apply_feed_preferences(db, &mut request.base).await?;
+    let now = chrono::Utc::now().naive_utc();
+    let policy = load_display_policy(db).await?;
+    let entries = apply_display_policy(build_advanced_entry_query(&request, now)?, &request.base, &policy, now)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to query feed entries: {}", e))?;
+
+    entries_with_annotations(db, entries).await
+}
+
+// READ - Entry list like query_entries, with each entry's coverage by other feeds. When
+// deduplicated, a story published by several feeds appears once.
+#[tauri::command]
+pub async fn get_timeline(
+    state: State<'_, AppState>,
+    mut request: EntryQueryRequest,
+    deduplicated: Option<bool>,
+) -> Result<Vec<TimelineEntryResponse>, String> {
+    let db = &state.db().await;
+
+    apply_feed_preferences(db, &mut request).await?;
+    let now = chrono::Utc::now().naive_utc();
+    let policy = load_display_policy(db).await?;
+    let mut query = apply_display_policy(build_entry_query(&request, now)?, &request, &policy, now);
+    if deduplicated.unwrap_or(false) {
+        query = only_story_representatives(query);
+    }
+
+    let entries = query
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to query feed entries: {}", e))?;
+
+    let mut story_cluster_ids: Vec<i32> = entries.iter().filter_map(|entry| entry.story_cluster_id).collect();
+    story_cluster_ids.sort_unstable();
+    story_cluster_ids.dedup();
+    let feed_counts = load_cluster_feed_counts(db, story_cluster_ids).await?;
+
+    Ok(entries_with_annotations(db, entries)
+        .await?
+        .into_iter()
+        .map(|entry| {
+            let feed_count = entry
+                .story_cluster_id
+                .and_then(|story_cluster_id| feed_counts.get(&story_cluster_id).copied())
+                .unwrap_or(1);
+            TimelineEntryResponse {
+                entry,
+                also_covered_by: feed_count.saturating_sub(1),
+            }
+        })
+        .collect())
+}
+
+// READ - Get entries the publisher changed after we first saved them, most recently updated first
+#[tauri::command]
+pub async fn get_recently_updated_entries(
+    state: State<'_, AppState>,
+    feed_id: Option<i32>,
+    limit: Option<u64>,
+) -> Result<Vec<FeedEntryResponse>, String> {
+    let db = &state.db().await;
+
+    let mut query = FeedEntry::find()
+        .filter(feed_entry::Column::IsUpdated.eq(true))
+        .filter(feed_entry::Column::IsHidden.eq(false))
+        .order_by_desc(feed_entry::Column::UpdatedAtSource);
+
+    if let Some(feed_id) = feed_id {
+        query = query.filter(feed_entry::Column::FeedId.eq(feed_id));
+    }
+    if let Some(limit) = limit {
+        query = query.limit(limit);
+    }
+
+    let entries = query
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch updated entries: {}", e))?;
+
+    entries_with_annotations(db, entries).await
+}
+
+// READ - Get entries the user read, most recently read first, to find an article again
+#[tauri::command]
+pub async fn get_recently_read(
+    state: State<'_, AppState>,
+    limit: Option<u64>,
+) -> Result<Vec<FeedEntryResponse>, String> {
+    let db = &state.db().await;
+
+    let entries = FeedEntry::find()
+        .filter(feed_entry::Column::ReadAt.is_not_null())
+        .filter(feed_entry::Column::IsHidden.eq(false))
+        .order_by_desc(feed_entry::Column::ReadAt)
+        .limit(limit.unwrap_or(50))
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch recently read entries: {}", e))?;
+
+    entries_with_annotations(db, entries).await
+}
+
+// READ - Entries to suggest reading after this one: ones with similar titles, and the feed's
+// posts from around the same time
+#[tauri::command]
+pub async fn get_related_entries(
+    state: State<'_, AppState>,
+    entry_id: i32,
+    limit: Option<u64>,
+) -> Result<Vec<RelatedEntry>, String> {
+    load_related_entries(&state.db().await, entry_id, limit.unwrap_or(DEFAULT_RELATED_ENTRIES_LIMIT)).await
+}
+
+// UPDATE - Fetch an entry's page for the canonical link it declares, unless already known.
+// Returns None when the page declares none.
+#[tauri::command]
+pub async fn resolve_canonical_link(
+    state: State<'_, AppState>,
+    entry_id: i32,
+) -> Result<Option<String>, String> {
+    let db = &state.db().await;
+    let fetcher = state.async_fetcher.as_ref().ok_or("Async feed fetcher not available")?;
+    
+    let entry = FeedEntry::find_by_id(entry_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entry: {}", e))?
+        .ok_or("Feed entry not found")?;
+    
+    resolve_entry_canonical_link(db, fetcher.transport().as_ref(), &entry).await
 }
 
 // READ - Get entry by ID
@@ -180,14 +436,17 @@ pub async fn get_feed_entry_by_id(
     state: State<'_, AppState>,
     id: i32,
 ) -> Result<Option<FeedEntryResponse>, String> {
-    let db = &state.db;
+    let db = &state.db().await;
     
     let entry = FeedEntry::find_by_id(id)
         .one(db)
         .await
         .map_err(|e| format!("Failed to fetch feed entry: {}", e))?;
     
-    Ok(entry.map(|e| e.into()))
+    match entry {
+        Some(entry) => Ok(entries_with_annotations(db, vec![entry]).await?.pop()),
+        None => Ok(None),
+    }
 }
 
 // UPDATE - Update feed entry
@@ -196,7 +455,7 @@ pub async fn update_feed_entry(
     state: State<'_, AppState>,
     request: UpdateFeedEntryRequest,
 ) -> Result<FeedEntryResponse, String> {
-    let db = &state.db;
+    let db = &state.db().await;
     
     let existing_entry = FeedEntry::find_by_id(request.id)
         .one(db)
@@ -204,6 +463,15 @@ pub async fn update_feed_entry(
         .map_err(|e| format!("Failed to fetch feed entry: {}", e))?
         .ok_or("Feed entry not found")?;
     
+    // Recount only when the text changes
+    let reading_stats = (request.content.is_some() || request.description.is_some()).then(|| {
+        entry_reading_stats(
+            request.content.as_deref().or(existing_entry.content.as_deref()),
+            request.description.as_deref().or(existing_entry.description.as_deref()),
+        )
+    });
+    
+    let (was_read, was_starred) = (existing_entry.is_read, existing_entry.is_starred);
     let mut updated_entry: feed_entry::ActiveModel = existing_entry.into();
     
     // Update fields if provided
@@ -216,11 +484,21 @@ pub async fn update_feed_entry(
     if let Some(content) = request.content {
         updated_entry.content = ActiveValue::Set(Some(content));
     }
+    if let Some(reading_stats) = reading_stats {
+        updated_entry.word_count = ActiveValue::Set(reading_stats.map(|stats| stats.word_count));
+        updated_entry.reading_time_minutes = ActiveValue::Set(reading_stats.map(|stats| stats.reading_time_minutes));
+    }
     if let Some(is_read) = request.is_read {
         updated_entry.is_read = ActiveValue::Set(is_read);
+        if is_read != was_read {
+            updated_entry.read_at = ActiveValue::Set(is_read.then(|| chrono::Utc::now().naive_utc()));
+        }
     }
     if let Some(is_starred) = request.is_starred {
         updated_entry.is_starred = ActiveValue::Set(is_starred);
+        if is_starred != was_starred {
+            updated_entry.starred_at = ActiveValue::Set(is_starred.then(|| chrono::Utc::now().naive_utc()));
+        }
     }
     
     // Always update the updated_at timestamp
@@ -230,6 +508,13 @@ pub async fn update_feed_entry(
         .update(db)
         .await
         .map_err(|e| format!("Failed to update feed entry: {}", e))?;
+
+    if result.is_read != was_read {
+        record_state_changes(db, &[result.id], StateField::Read, result.is_read, result.updated_at, LOCAL_ORIGIN).await?;
+    }
+    if result.is_starred != was_starred {
+        record_state_changes(db, &[result.id], StateField::Starred, result.is_starred, result.updated_at, LOCAL_ORIGIN).await?;
+    }
     
     Ok(result.into())
 }
@@ -240,7 +525,7 @@ pub async fn delete_feed_entry(
     state: State<'_, AppState>,
     id: i32,
 ) -> Result<String, String> {
-    let db = &state.db;
+    let db = &state.db().await;
     
     let existing_entry = FeedEntry::find_by_id(id)
         .one(db)
@@ -261,6 +546,45 @@ pub async fn delete_feed_entry(
     Ok(format!("Feed entry with ID {} deleted successfully", id))
 }
 
+// UTILITY - Open an entry's link in the browser, counting the open for stats and ranking
+#[tauri::command]
+pub async fn open_entry_link(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: i32,
+) -> Result<FeedEntryResponse, String> {
+    let db = &state.db().await;
+    
+    let entry = FeedEntry::find_by_id(id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entry: {}", e))?
+        .ok_or("Feed entry not found")?;
+    
+    app.opener()
+        .open_url(share_link(&entry), None::<&str>)
+        .map_err(|e| format!("Failed to open link: {}", e))?;
+    
+    FeedEntry::update_many()
+        .col_expr(feed_entry::Column::OpenCount, Expr::col(feed_entry::Column::OpenCount).add(1))
+        .col_expr(feed_entry::Column::LastOpenedAt, Expr::value(chrono::Utc::now().naive_utc()))
+        .filter(feed_entry::Column::Id.eq(id))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to record entry open: {}", e))?;
+    
+    let entry = FeedEntry::find_by_id(id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entry: {}", e))?
+        .ok_or("Feed entry not found")?;
+    
+    entries_with_annotations(db, vec![entry])
+        .await?
+        .pop()
+        .ok_or_else(|| "Feed entry not found".to_string())
+}
+
 // UTILITY - Mark entry as read/unread
 #[tauri::command]
 pub async fn mark_entry_as_read(
@@ -280,13 +604,40 @@ pub async fn mark_entry_as_read(
     update_feed_entry(state, request).await
 }
 
-// UTILITY - Star/unstar entry
+// UTILITY - Clear an entry's updated flag once the reader has seen the changes
+#[tauri::command]
+pub async fn acknowledge_entry_update(
+    state: State<'_, AppState>,
+    id: i32,
+) -> Result<FeedEntryResponse, String> {
+    let db = &state.db().await;
+
+    let existing_entry = FeedEntry::find_by_id(id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entry: {}", e))?
+        .ok_or("Feed entry not found")?;
+
+    let mut updated_entry: feed_entry::ActiveModel = existing_entry.into();
+    updated_entry.is_updated = ActiveValue::Set(false);
+
+    let result = updated_entry
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to acknowledge entry update: {}", e))?;
+
+    Ok(result.into())
+}
+
+// UTILITY - Star/unstar entry. A newly starred entry is snapshotted in the background.
 #[tauri::command]
 pub async fn mark_entry_as_starred(
+    app: AppHandle,
     state: State<'_, AppState>,
     id: i32,
     is_starred: bool,
 ) -> Result<FeedEntryResponse, String> {
+    let db = state.db().await;
     let request = UpdateFeedEntryRequest {
         id,
         title: None,
@@ -296,5 +647,82 @@ pub async fn mark_entry_as_starred(
         is_starred: Some(is_starred),
     };
     
-    update_feed_entry(state, request).await
-} 
\ No newline at end of file
+    let entry = update_feed_entry(state, request).await?;
+    if is_starred {
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = snapshot_starred_entries(&app, &db).await {
+                eprintln!("❌ Snapshotting starred entries failed: {}", e);
+            }
+        });
+    }
+    Ok(entry)
+}
+// UTILITY - Apply one action to every entry matching a filter, returning how many rows changed.
+// Marking read and starring can be undone with undo_last_bulk_action for BULK_UNDO_WINDOW.
+#[tauri::command]
+pub async fn bulk_update_entries(
+    state: State<'_, AppState>,
+    filter: BulkEntryFilter,
+    action: BulkEntryAction,
+) -> Result<u64, String> {
+    let db = &state.db().await;
+    
+    let (rows_affected, undo) = run_bulk_action(db, &filter, &action, chrono::Utc::now().naive_utc()).await?;
+    *state.last_bulk_action.write().await = undo;
+    
+    println!("📦 Bulk {:?} affected {} entries", action, rows_affected);
+    
+    Ok(rows_affected)
+}
+
+// UTILITY - Revert the latest bulk mark-read or star, returning how many entries were restored
+#[tauri::command]
+pub async fn undo_last_bulk_action(state: State<'_, AppState>) -> Result<u64, String> {
+    let db = &state.db().await;
+    
+    let undo = state.last_bulk_action.write().await.take().ok_or("Nothing to undo")?;
+    if undo.is_expired() {
+        return Err("The last bulk action can no longer be undone".to_string());
+    }
+    
+    let restored = undo_bulk_action(db, &undo).await?;
+    println!("↩️ Undid bulk action on {} entries", restored);
+    
+    Ok(restored)
+}
+
+// UTILITY - Snooze an entry until the given time, or unsnooze it when until is None
+#[tauri::command]
+pub async fn snooze_entry(
+    state: State<'_, AppState>,
+    id: i32,
+    until: Option<String>,
+) -> Result<FeedEntryResponse, String> {
+    let db = &state.db().await;
+    
+    let snoozed_until = match until {
+        Some(until) => Some(
+            ChronoDateTime::parse_from_rfc3339(&until)
+                .map_err(|e| format!("Invalid snooze time: {}", e))?
+                .naive_utc(),
+        ),
+        None => None,
+    };
+    
+    let existing_entry = FeedEntry::find_by_id(id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entry: {}", e))?
+        .ok_or("Feed entry not found")?;
+    
+    let mut updated_entry: feed_entry::ActiveModel = existing_entry.into();
+    updated_entry.snoozed_until = ActiveValue::Set(snoozed_until);
+    updated_entry.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
+    
+    let result = updated_entry
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to snooze feed entry: {}", e))?;
+    
+    Ok(result.into())
+}
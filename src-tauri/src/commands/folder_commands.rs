@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use sea_orm::*;
+use tauri::State;
+use crate::entities::{prelude::*, *};
+use crate::models::{
+    AppState,
+    CreateFolderRequest,
+    UpdateFolderRequest,
+    FolderResponse,
+    FolderUnreadCount,
+    FeedResponse,
+    roll_up_unread_counts,
+    would_create_cycle,
+};
+
+// CREATE - Insert a new folder
+#[tauri::command]
+pub async fn create_folder(
+    state: State<'_, AppState>,
+    request: CreateFolderRequest,
+) -> Result<FolderResponse, String> {
+    let db = &state.db().await;
+
+    let now = chrono::Utc::now().naive_utc();
+
+    let new_folder = folder::ActiveModel {
+        name: ActiveValue::Set(request.name),
+        parent_id: ActiveValue::Set(request.parent_id),
+        created_at: ActiveValue::Set(now),
+        updated_at: ActiveValue::Set(now),
+        ..Default::default()
+    };
+
+    let created_folder = new_folder
+        .insert(db)
+        .await
+        .map_err(|e| format!("Failed to create folder: {}", e))?;
+
+    Ok(created_folder.into())
+}
+
+// READ - Get all folders
+#[tauri::command]
+pub async fn get_all_folders(state: State<'_, AppState>) -> Result<Vec<FolderResponse>, String> {
+    let db = &state.db().await;
+
+    let folders = Folder::find()
+        .order_by_asc(folder::Column::Name)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch folders: {}", e))?;
+
+    Ok(folders.into_iter().map(|folder| folder.into()).collect())
+}
+
+// READ - Get unread counts for every folder, both direct and rolled up over its subfolders
+#[tauri::command]
+pub async fn get_folder_unread_counts(state: State<'_, AppState>) -> Result<Vec<FolderUnreadCount>, String> {
+    let db = &state.db().await;
+
+    let folders = Folder::find()
+        .order_by_asc(folder::Column::Name)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch folders: {}", e))?;
+
+    let direct_counts: Vec<(i32, i64)> = FeedEntry::find()
+        .select_only()
+        .column(feed::Column::FolderId)
+        .column_as(feed_entry::Column::Id.count(), "unread_count")
+        .inner_join(Feed)
+        .filter(feed_entry::Column::IsRead.eq(false))
+        .filter(feed_entry::Column::IsHidden.eq(false))
+        .filter(feed::Column::FolderId.is_not_null())
+        .group_by(feed::Column::FolderId)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to count unread entries: {}", e))?;
+
+    let direct_counts: HashMap<i32, u64> = direct_counts
+        .into_iter()
+        .map(|(folder_id, count)| (folder_id, count.max(0) as u64))
+        .collect();
+
+    Ok(roll_up_unread_counts(&folders, &direct_counts))
+}
+
+// UPDATE - Rename a folder
+#[tauri::command]
+pub async fn update_folder(
+    state: State<'_, AppState>,
+    request: UpdateFolderRequest,
+) -> Result<FolderResponse, String> {
+    let db = &state.db().await;
+
+    let existing_folder = Folder::find_by_id(request.id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch folder: {}", e))?
+        .ok_or("Folder not found")?;
+
+    let mut updated_folder: folder::ActiveModel = existing_folder.into();
+    updated_folder.name = ActiveValue::Set(request.name);
+    updated_folder.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+    let result = updated_folder
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to update folder: {}", e))?;
+
+    Ok(result.into())
+}
+
+// UPDATE - Move a folder inside another folder, or to the top level when parent_id is None
+#[tauri::command]
+pub async fn move_folder(
+    state: State<'_, AppState>,
+    folder_id: i32,
+    parent_id: Option<i32>,
+) -> Result<FolderResponse, String> {
+    let db = &state.db().await;
+
+    let folders = Folder::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch folders: {}", e))?;
+
+    let existing_folder = folders
+        .iter()
+        .find(|folder| folder.id == folder_id)
+        .cloned()
+        .ok_or("Folder not found")?;
+
+    if let Some(parent_id) = parent_id {
+        if !folders.iter().any(|folder| folder.id == parent_id) {
+            return Err("Parent folder not found".to_string());
+        }
+        if would_create_cycle(&folders, folder_id, parent_id) {
+            return Err("A folder cannot be moved inside itself or one of its subfolders".to_string());
+        }
+    }
+
+    let mut updated_folder: folder::ActiveModel = existing_folder.into();
+    updated_folder.parent_id = ActiveValue::Set(parent_id);
+    updated_folder.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+    let result = updated_folder
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to move folder: {}", e))?;
+
+    Ok(result.into())
+}
+
+// DELETE - Delete a folder (its feeds and subfolders are moved to the top level, not deleted)
+#[tauri::command]
+pub async fn delete_folder(state: State<'_, AppState>, id: i32) -> Result<String, String> {
+    let db = &state.db().await;
+
+    let result = Folder::delete_by_id(id)
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to delete folder: {}", e))?;
+
+    if result.rows_affected == 0 {
+        return Err("Folder not found".to_string());
+    }
+
+    Ok(format!("Folder with ID {} deleted successfully", id))
+}
+
+// UPDATE - Move a feed into a folder, or out of any folder when folder_id is None
+#[tauri::command]
+pub async fn move_feed_to_folder(
+    state: State<'_, AppState>,
+    feed_id: i32,
+    folder_id: Option<i32>,
+) -> Result<FeedResponse, String> {
+    let db = &state.db().await;
+
+    let existing_feed = Feed::find_by_id(feed_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed: {}", e))?
+        .ok_or("Feed not found")?;
+
+    let mut updated_feed: feed::ActiveModel = existing_feed.into();
+    updated_feed.folder_id = ActiveValue::Set(folder_id);
+    updated_feed.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+    let result = updated_feed
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to move feed: {}", e))?;
+
+    Ok(result.into())
+}
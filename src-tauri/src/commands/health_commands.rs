@@ -0,0 +1,10 @@
+use tauri::AppHandle;
+use crate::models::{collect_health, HealthReport};
+
+// READ - Get the app's health: database connectivity and latency, migration version,
+// background fetching and scheduler state, library size and the most recent error. Cheap
+// enough to poll for a status indicator.
+#[tauri::command]
+pub async fn get_health(app: AppHandle) -> Result<HealthReport, String> {
+    Ok(collect_health(&app).await)
+}
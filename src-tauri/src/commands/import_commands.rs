@@ -0,0 +1,753 @@
+use std::collections::HashMap;
+use futures::stream::{self, StreamExt};
+use sea_orm::*;
+use tauri::{AppHandle, Emitter, Manager, State};
+use chrono::DateTime as ChronoDateTime;
+use crate::entities::{prelude::*, *};
+use crate::models::{
+    AppState, EntryQueryRequest, ImportFormat, ImportOperation, ImportProgress, ImportSummary, ImportedSubscriptions,
+    Operation, OperationKind, StarterPackFeedResponse, StarterPackResponse, ENTRIES_MARKDOWN_HEADER, entry_markdown,
+    build_entry_query, build_feed_bundle, build_opml, find_starter_pack, load_allow_private_addresses, load_annotations,
+    load_domain_rules_config, parse_import, record_state_changes, starter_packs, validate_fetch_url, StateField,
+    LOCAL_ORIGIN,
+};
+
+// Event emitted as an import's initial fetch makes progress
+const IMPORT_PROGRESS_EVENT: &str = "import:progress";
+
+// Look up a folder by name, creating it inside parent_id if needed. Folder names are unique,
+// so an existing folder is reused wherever it sits. Returns the id and whether it was created.
+async fn find_or_create_folder<C: ConnectionTrait>(
+    db: &C,
+    name: &str,
+    parent_id: Option<i32>,
+) -> Result<(i32, bool), String> {
+    let existing_folder = Folder::find()
+        .filter(folder::Column::Name.eq(name))
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch folder: {}", e))?;
+
+    if let Some(existing_folder) = existing_folder {
+        return Ok((existing_folder.id, false));
+    }
+
+    let now = chrono::Utc::now().naive_utc();
+    let new_folder = folder::ActiveModel {
+        name: ActiveValue::Set(name.to_string()),
+        parent_id: ActiveValue::Set(parent_id),
+        created_at: ActiveValue::Set(now),
+        updated_at: ActiveValue::Set(now),
+        ..Default::default()
+    };
+
+    let created_folder = new_folder
+        .insert(db)
+        .await
+        .map_err(|e| format!("Failed to create folder: {}", e))?;
+
+    Ok((created_folder.id, true))
+}
+
+// Walk down a folder path, creating any folders that don't exist yet. Returns the innermost
+// folder's id, or None for an empty path.
+async fn find_or_create_folder_path<'a, C: ConnectionTrait>(
+    db: &C,
+    path: impl IntoIterator<Item = &'a String>,
+    folder_ids: &mut HashMap<String, i32>,
+    summary: &mut ImportSummary,
+    created_folder_ids: &mut Vec<i32>,
+) -> Result<Option<i32>, String> {
+    let mut folder_id = None;
+    for name in path {
+        let id = match folder_ids.get(name) {
+            Some(id) => *id,
+            None => {
+                let (id, created) = find_or_create_folder(db, name, folder_id).await?;
+                if created {
+                    summary.folders_created += 1;
+                    created_folder_ids.push(id);
+                }
+                folder_ids.insert(name.clone(), id);
+                id
+            }
+        };
+        folder_id = Some(id);
+    }
+    Ok(folder_id)
+}
+
+// Records created by an import, so the import can be fetched and rolled back afterwards
+struct ImportedRecords {
+    summary: ImportSummary,
+    created_feeds: Vec<feed::Model>,
+    created_folder_ids: Vec<i32>,
+}
+
+// Read, parse and save an export file in a single transaction. With an operation, progress is
+// reported per subscription and a cancelled import stops before anything is committed.
+async fn import_file(
+    db: &DatabaseConnection,
+    path: &str,
+    format: &str,
+    operation: Option<&Operation>,
+) -> Result<ImportedRecords, String> {
+    let format: ImportFormat = format.parse()?;
+
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| format!("Failed to read import file: {}", e))?;
+
+    let subscriptions = parse_import(&content, format).map_err(|e| e.to_string())?;
+
+    save_subscriptions(db, subscriptions, format.as_str(), operation).await
+}
+
+// Save parsed subscriptions in a single transaction; source labels the import summary
+async fn save_subscriptions(
+    db: &DatabaseConnection,
+    subscriptions: ImportedSubscriptions,
+    source: &str,
+    operation: Option<&Operation>,
+) -> Result<ImportedRecords, String> {
+    let mut summary = ImportSummary {
+        format: source.to_string(),
+        ..Default::default()
+    };
+    let mut created_feeds = Vec::new();
+    let mut created_folder_ids = Vec::new();
+
+    // Import everything or nothing
+    let txn = db.begin().await.map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let now = chrono::Utc::now().naive_utc();
+    let mut folder_ids: HashMap<String, i32> = HashMap::new();
+    let mut feed_ids: HashMap<String, i32> = HashMap::new();
+    let domain_rules = load_domain_rules_config(db).await;
+    let allow_private_addresses = load_allow_private_addresses(db).await;
+    if let Some(operation) = operation {
+        operation.restart(subscriptions.feeds.len(), "Saving subscriptions");
+    }
+
+    for path in &subscriptions.folders {
+        find_or_create_folder_path(&txn, path, &mut folder_ids, &mut summary, &mut created_folder_ids).await?;
+    }
+
+    for imported_feed in subscriptions.feeds {
+        if let Some(operation) = operation {
+            if operation.is_cancelled() {
+                return Err("Import cancelled".to_string());
+            }
+            operation.record(true);
+        }
+
+        if domain_rules.blocked_subscription(&imported_feed.url).is_some() {
+            summary.feeds_skipped += 1;
+            continue;
+        }
+        // Export files are as untrusted as a pasted URL
+        if let Err(e) = validate_fetch_url(&imported_feed.url, allow_private_addresses).await {
+            eprintln!("⚠️ Skipping imported feed {}: {}", imported_feed.url, e);
+            summary.feeds_skipped += 1;
+            continue;
+        }
+
+        let folder_path = imported_feed.parent_folders.iter().chain(imported_feed.folder.iter());
+        let folder_id =
+            find_or_create_folder_path(&txn, folder_path, &mut folder_ids, &mut summary, &mut created_folder_ids).await?;
+
+        let existing_feed = Feed::find()
+            .filter(feed::Column::Url.eq(&imported_feed.url))
+            .one(&txn)
+            .await
+            .map_err(|e| format!("Failed to fetch feed by URL: {}", e))?;
+
+        if let Some(existing_feed) = existing_feed {
+            feed_ids.insert(existing_feed.url, existing_feed.id);
+            summary.feeds_skipped += 1;
+            continue;
+        }
+
+        let new_feed = feed::ActiveModel {
+            url: ActiveValue::Set(imported_feed.url.clone()),
+            auto_title: ActiveValue::Set(imported_feed.title.is_none()),
+            title: ActiveValue::Set(imported_feed.title),
+            description: ActiveValue::Set(imported_feed.description),
+            created_at: ActiveValue::Set(now),
+            updated_at: ActiveValue::Set(now),
+            last_fetched_at: ActiveValue::Set(None),
+            folder_id: ActiveValue::Set(folder_id),
+            ..Default::default()
+        };
+
+        let created_feed = new_feed
+            .insert(&txn)
+            .await
+            .map_err(|e| format!("Failed to create feed: {}", e))?;
+
+        feed_ids.insert(created_feed.url.clone(), created_feed.id);
+        created_feeds.push(created_feed);
+        summary.feeds_imported += 1;
+    }
+
+    for imported_entry in subscriptions.entries {
+        let Some(feed_id) = feed_ids.get(&imported_entry.feed_url).copied() else {
+            continue;
+        };
+
+        // The same link can be saved by several feeds; only this feed's copy takes the state
+        let existing_entry = FeedEntry::find()
+            .filter(feed_entry::Column::FeedId.eq(feed_id))
+            .filter(feed_entry::Column::Link.eq(&imported_entry.link))
+            .one(&txn)
+            .await
+            .map_err(|e| format!("Failed to check for existing entry: {}", e))?;
+
+        if let Some(existing_entry) = existing_entry {
+            // Carry over read/starred state onto entries we already have
+            if existing_entry.is_read != imported_entry.is_read
+                || existing_entry.is_starred != imported_entry.is_starred
+            {
+                if existing_entry.is_read != imported_entry.is_read {
+                    record_state_changes(&txn, &[existing_entry.id], StateField::Read, imported_entry.is_read, now, LOCAL_ORIGIN).await?;
+                }
+                if existing_entry.is_starred != imported_entry.is_starred {
+                    record_state_changes(&txn, &[existing_entry.id], StateField::Starred, imported_entry.is_starred, now, LOCAL_ORIGIN).await?;
+                }
+                let mut updated_entry: feed_entry::ActiveModel = existing_entry.into();
+                updated_entry.is_read = ActiveValue::Set(imported_entry.is_read);
+                updated_entry.is_starred = ActiveValue::Set(imported_entry.is_starred);
+                updated_entry.updated_at = ActiveValue::Set(now);
+                updated_entry
+                    .update(&txn)
+                    .await
+                    .map_err(|e| format!("Failed to update feed entry: {}", e))?;
+                summary.entries_updated += 1;
+            }
+            continue;
+        }
+
+        let published_at = imported_entry.published_at
+            .as_deref()
+            .and_then(|p| ChronoDateTime::parse_from_rfc3339(p).ok())
+            .map(|dt| dt.naive_utc());
+
+        let new_entry = feed_entry::ActiveModel {
+            feed_id: ActiveValue::Set(feed_id),
+            title: ActiveValue::Set(imported_entry.title),
+            description: ActiveValue::Set(None),
+            guid: ActiveValue::Set(imported_entry.link.clone()),
+            link: ActiveValue::Set(imported_entry.link),
+            content: ActiveValue::Set(imported_entry.content),
+            published_at: ActiveValue::Set(published_at),
+            created_at: ActiveValue::Set(now),
+            updated_at: ActiveValue::Set(now),
+            is_read: ActiveValue::Set(imported_entry.is_read),
+            is_starred: ActiveValue::Set(imported_entry.is_starred),
+            ..Default::default()
+        };
+
+        FeedEntry::insert(new_entry)
+            .exec(&txn)
+            .await
+            .map_err(|e| format!("Failed to create feed entry: {}", e))?;
+
+        summary.entries_imported += 1;
+    }
+
+    txn.commit().await.map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(ImportedRecords {
+        summary,
+        created_feeds,
+        created_folder_ids,
+    })
+}
+
+// Delete what an import created. Entries fetched for the imported feeds cascade with them.
+async fn rollback_import(
+    db: &DatabaseConnection,
+    feed_ids: &[i32],
+    folder_ids: &[i32],
+) -> Result<(), String> {
+    let txn = db.begin().await.map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    Feed::delete_many()
+        .filter(feed::Column::Id.is_in(feed_ids.to_vec()))
+        .exec(&txn)
+        .await
+        .map_err(|e| format!("Failed to delete imported feeds: {}", e))?;
+
+    // Folders are only removed if nothing else was moved into them in the meantime
+    for folder_id in folder_ids {
+        let feeds_in_folder = Feed::find()
+            .filter(feed::Column::FolderId.eq(*folder_id))
+            .count(&txn)
+            .await
+            .map_err(|e| format!("Failed to count folder feeds: {}", e))?;
+
+        if feeds_in_folder == 0 {
+            Folder::delete_by_id(*folder_id)
+                .exec(&txn)
+                .await
+                .map_err(|e| format!("Failed to delete imported folder: {}", e))?;
+        }
+    }
+
+    txn.commit().await.map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(())
+}
+
+// Fetch every feed created by an import, reporting progress as each one finishes.
+// Stops early and rolls back the whole import if it is cancelled.
+async fn run_import_fetch(app: AppHandle, import_id: String, feeds: Vec<feed::Model>) {
+    let state = app.state::<AppState>();
+
+    let Some(fetcher) = &state.async_fetcher else {
+        eprintln!("❌ Async fetcher not initialized, skipping initial fetch for import {}", import_id);
+        if let Some(operation) = state.import_operations.read().await.get(&import_id) {
+            operation.operation.finish(Err("Async feed fetcher not available".to_string()));
+        }
+        return;
+    };
+
+    let concurrency = fetcher.config().max_concurrent_requests.max(1);
+    let mut fetches = stream::iter(feeds)
+        .map(|feed| async move {
+            let result = fetcher.fetch_and_save_feed(&feed).await;
+            (feed, result)
+        })
+        .buffer_unordered(concurrency);
+
+    let mut cancelled = false;
+
+    while let Some((_feed, result)) = fetches.next().await {
+        let mut operations = state.import_operations.write().await;
+        let Some(operation) = operations.get_mut(&import_id) else {
+            return;
+        };
+
+        operation.progress.completed_feeds += 1;
+        operation.operation.record(result.is_ok());
+        match result {
+            Ok(saved) => operation.progress.entries_added += saved.added,
+            Err(error) => {
+                operation.progress.failed_feeds += 1;
+                operation.progress.errors.push(error);
+            }
+        }
+
+        if let Err(e) = app.emit(IMPORT_PROGRESS_EVENT, &operation.progress) {
+            eprintln!("❌ Failed to emit import progress: {}", e);
+        }
+
+        if operation.operation.is_cancelled() {
+            cancelled = true;
+            break;
+        }
+    }
+
+    // Dropping the stream abandons any fetches still in flight
+    drop(fetches);
+
+    // The rollback needs the database lock, which profile switching takes before the import
+    // lock, so the import lock is let go while rolling back
+    let rollback = {
+        let operations = state.import_operations.read().await;
+        let Some(operation) = operations.get(&import_id) else {
+            return;
+        };
+        (cancelled || operation.operation.is_cancelled())
+            .then(|| (operation.created_feed_ids.clone(), operation.created_folder_ids.clone()))
+    };
+    let rolled_back = match rollback {
+        Some((feed_ids, folder_ids)) => Some(rollback_import(&state.db().await, &feed_ids, &folder_ids).await),
+        None => None,
+    };
+
+    let mut operations = state.import_operations.write().await;
+    let Some(operation) = operations.get_mut(&import_id) else {
+        return;
+    };
+
+    match rolled_back {
+        Some(Ok(())) => {
+            println!("↩️ Rolled back import {} ({} feeds)", import_id, operation.created_feed_ids.len());
+            operation.progress.status = "rolled_back".to_string();
+            operation.operation.finish(Ok(()));
+        }
+        Some(Err(e)) => {
+            eprintln!("❌ Failed to roll back import {}: {}", import_id, e);
+            operation.operation.finish(Err(e));
+        }
+        None => {
+            println!(
+                "📥 Initial fetch for import {} finished: {} entries added, {} feeds failed",
+                import_id, operation.progress.entries_added, operation.progress.failed_feeds
+            );
+            operation.progress.status = "completed".to_string();
+            operation.operation.finish(Ok(()));
+        }
+    }
+
+    if let Err(e) = app.emit(IMPORT_PROGRESS_EVENT, &operation.progress) {
+        eprintln!("❌ Failed to emit import progress: {}", e);
+    }
+}
+
+// IMPORT - Import subscriptions (and read state where present) from another reader's export
+#[tauri::command]
+pub async fn import_from(
+    state: State<'_, AppState>,
+    path: String,
+    format: String,
+) -> Result<ImportSummary, String> {
+    let ImportedRecords { summary, .. } = import_file(&state.db().await, &path, &format, None).await?;
+
+    println!(
+        "📥 Imported {} feeds ({} skipped, {} folders created) and {} entries from {}",
+        summary.feeds_imported,
+        summary.feeds_skipped,
+        summary.folders_created,
+        summary.entries_imported,
+        summary.format
+    );
+
+    Ok(summary)
+}
+
+// EXPORT - Write every subscription to an OPML file, keeping the folder hierarchy
+#[tauri::command]
+pub async fn export_opml(state: State<'_, AppState>, path: String) -> Result<usize, String> {
+    let db = &state.db().await;
+
+    let folders = Folder::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch folders: {}", e))?;
+    let feeds = Feed::find()
+        .order_by_asc(feed::Column::Title)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feeds: {}", e))?;
+
+    tokio::fs::write(&path, build_opml(&folders, &feeds))
+        .await
+        .map_err(|e| format!("Failed to write OPML file: {}", e))?;
+
+    println!("📤 Exported {} feeds to {}", feeds.len(), path);
+    Ok(feeds.len())
+}
+
+// EXPORT - Write a folder, its subfolders and their feeds to a bundle file to share as a
+// subscription pack. Returns how many feeds it holds.
+#[tauri::command]
+pub async fn export_feed_bundle(
+    state: State<'_, AppState>,
+    folder_id: i32,
+    path: String,
+    description: Option<String>,
+) -> Result<usize, String> {
+    let db = &state.db().await;
+
+    let folders = Folder::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch folders: {}", e))?;
+    let root = folders
+        .iter()
+        .find(|folder| folder.id == folder_id)
+        .ok_or("Folder not found")?;
+    let feeds = Feed::find()
+        .order_by_asc(feed::Column::Title)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feeds: {}", e))?;
+
+    let bundle = build_feed_bundle(root, &folders, &feeds, description, chrono::Utc::now().naive_utc());
+    let feed_count = bundle.folders.iter().map(|folder| folder.feed_count).sum();
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| format!("Failed to serialize feed bundle: {}", e))?;
+    tokio::fs::write(&path, json)
+        .await
+        .map_err(|e| format!("Failed to write feed bundle: {}", e))?;
+
+    println!("📤 Exported {} feeds of {} to {}", feed_count, bundle.name, path);
+    Ok(feed_count)
+}
+
+// IMPORT - Add a shared subscription pack, recreating its folders. Feeds already subscribed
+// to are left where they are.
+#[tauri::command]
+pub async fn import_feed_bundle(state: State<'_, AppState>, path: String) -> Result<ImportSummary, String> {
+    let ImportedRecords { summary, .. } = import_file(&state.db().await, &path, ImportFormat::Bundle.as_str(), None).await?;
+
+    println!(
+        "📥 Imported {} feeds ({} skipped, {} folders created) from bundle {}",
+        summary.feeds_imported, summary.feeds_skipped, summary.folders_created, path
+    );
+
+    Ok(summary)
+}
+
+// EXPORT - Write the entries matching a query, with their notes, to a Markdown file.
+// Reports "operation:progress" events as entries are written; when cancelled with
+// "operation:cancel", no file is written.
+#[tauri::command]
+pub async fn export_entries_markdown(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: EntryQueryRequest,
+    path: String,
+) -> Result<usize, String> {
+    let operation = Operation::start(&app, OperationKind::Export, 0);
+    let result = write_entries_markdown(&state, &request, &path, &operation).await;
+    match &result {
+        Err(_) if operation.is_cancelled() => operation.finish(Ok(())),
+        Err(e) => operation.finish(Err(e.clone())),
+        Ok(_) => operation.finish(Ok(())),
+    };
+    result
+}
+
+async fn write_entries_markdown(
+    state: &AppState,
+    request: &EntryQueryRequest,
+    path: &str,
+    operation: &Operation,
+) -> Result<usize, String> {
+    let db = &state.db().await;
+
+    let entries = build_entry_query(request, chrono::Utc::now().naive_utc())?
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to query feed entries: {}", e))?;
+    let entry_ids: Vec<i32> = entries.iter().map(|entry| entry.id).collect();
+    let annotations = load_annotations(db, &entry_ids).await?;
+
+    operation.restart(entries.len(), "Writing entries");
+    let mut markdown = String::from(ENTRIES_MARKDOWN_HEADER);
+    for entry in &entries {
+        if operation.is_cancelled() {
+            return Err("Export cancelled".to_string());
+        }
+        markdown.push_str(&entry_markdown(entry, &annotations));
+        operation.record(true);
+    }
+
+    tokio::fs::write(path, markdown)
+        .await
+        .map_err(|e| format!("Failed to write Markdown file: {}", e))?;
+
+    println!("📤 Exported {} entries to {}", entries.len(), path);
+    Ok(entries.len())
+}
+
+// IMPORT - Import an export file and fetch the new feeds in the background.
+// Progress is reported through "import:progress" events, and through "operation:progress"
+// events from the start of saving. The import can be cancelled (and rolled back) with
+// cancel_import or an "operation:cancel" event until the fetch completes.
+#[tauri::command]
+pub async fn import_and_fetch(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    format: String,
+) -> Result<ImportProgress, String> {
+    let operation = Operation::start(&app, OperationKind::Import, 0);
+    let records = import_file(&state.db().await, &path, &format, Some(&operation)).await;
+    start_import_fetch(app, &state, operation, records).await
+}
+
+// Register a saved import and fetch its new feeds in the background, or finish its operation
+// if saving failed
+async fn start_import_fetch(
+    app: AppHandle,
+    state: &AppState,
+    operation: Operation,
+    records: Result<ImportedRecords, String>,
+) -> Result<ImportProgress, String> {
+    let ImportedRecords {
+        summary,
+        created_feeds,
+        created_folder_ids,
+    } = match records {
+        Ok(records) => records,
+        Err(e) => {
+            operation.finish(if operation.is_cancelled() { Ok(()) } else { Err(e.clone()) });
+            return Err(e);
+        }
+    };
+    operation.restart(created_feeds.len(), "Fetching new feeds");
+
+    let import_id = format!("import-{}", chrono::Utc::now().timestamp_millis());
+    let progress = ImportProgress {
+        import_id: import_id.clone(),
+        operation_id: operation.id(),
+        status: "fetching".to_string(),
+        summary,
+        total_feeds: created_feeds.len(),
+        completed_feeds: 0,
+        failed_feeds: 0,
+        entries_added: 0,
+        errors: Vec::new(),
+    };
+
+    state.import_operations.write().await.insert(
+        import_id.clone(),
+        ImportOperation {
+            progress: progress.clone(),
+            operation,
+            created_feed_ids: created_feeds.iter().map(|feed| feed.id).collect(),
+            created_folder_ids,
+        },
+    );
+
+    println!("📥 Started import {} with {} new feeds to fetch", import_id, created_feeds.len());
+
+    tauri::async_runtime::spawn(run_import_fetch(app, import_id, created_feeds));
+
+    Ok(progress)
+}
+
+// READ - The built-in starter packs, marking the feeds already subscribed to
+#[tauri::command]
+pub async fn get_available_packs(state: State<'_, AppState>) -> Result<Vec<StarterPackResponse>, String> {
+    let subscribed_urls: Vec<String> = Feed::find()
+        .all(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to fetch feeds: {}", e))?
+        .into_iter()
+        .map(|feed| feed.url)
+        .collect();
+
+    Ok(starter_packs()
+        .iter()
+        .map(|pack| StarterPackResponse {
+            id: pack.id.to_string(),
+            name: pack.name.to_string(),
+            description: pack.description.to_string(),
+            feeds: pack
+                .feeds
+                .iter()
+                .map(|feed| StarterPackFeedResponse {
+                    url: feed.url.to_string(),
+                    title: feed.title.to_string(),
+                    description: feed.description.to_string(),
+                    subscribed: subscribed_urls.iter().any(|url| url == feed.url),
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+// IMPORT - Subscribe to a starter pack in a folder named after it and fetch its feeds. Progress
+// and cancellation work as for import_and_fetch.
+#[tauri::command]
+pub async fn install_pack(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<ImportProgress, String> {
+    let pack = find_starter_pack(&id).ok_or_else(|| format!("Unknown starter pack: {}", id))?;
+
+    let operation = Operation::start(&app, OperationKind::Import, 0);
+    let source = format!("starter pack {}", pack.id);
+    let records = save_subscriptions(&state.db().await, pack.subscriptions(), &source, Some(&operation)).await;
+    start_import_fetch(app, &state, operation, records).await
+}
+
+// READ - Get the latest progress of an import started with import_and_fetch
+#[tauri::command]
+pub async fn get_import_progress(
+    state: State<'_, AppState>,
+    import_id: String,
+) -> Result<ImportProgress, String> {
+    let operations = state.import_operations.read().await;
+    let operation = operations.get(&import_id).ok_or("Import not found")?;
+    Ok(operation.progress.clone())
+}
+
+// CANCEL - Stop an import's initial fetch and roll back its feeds, folders and fetched entries
+#[tauri::command]
+pub async fn cancel_import(
+    state: State<'_, AppState>,
+    import_id: String,
+) -> Result<ImportProgress, String> {
+    let mut operations = state.import_operations.write().await;
+    let operation = operations.get_mut(&import_id).ok_or("Import not found")?;
+
+    if operation.progress.status != "fetching" {
+        return Err(format!("Import is already {}", operation.progress.status));
+    }
+
+    operation.operation.cancel();
+    operation.progress.status = "cancelling".to_string();
+
+    Ok(operation.progress.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::models::{AsyncFeedFetcher, FetcherConfig, ImportedEntry, ImportedFeed};
+    use crate::models::http_transport::{MockResponse, MockTransport};
+
+    // Runs against the database TEST_DATABASE_URL names, which must have the migrations applied
+    #[tokio::test]
+    async fn test_imported_entries_keep_their_state_through_the_first_fetch_on_a_real_database() {
+        let Ok(database_url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL is not set, skipping");
+            return;
+        };
+        let db = Database::connect(database_url).await.unwrap();
+        // An address literal, so the import's URL check doesn't need DNS
+        let feed_url = format!("https://203.0.113.1/{}.xml", chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default());
+        let link = feed_url.replace(".xml", "/1");
+        let subscriptions = ImportedSubscriptions {
+            feeds: vec![ImportedFeed {
+                url: feed_url.clone(),
+                title: Some("Imported".to_string()),
+                description: None,
+                site_url: None,
+                folder: None,
+                parent_folders: vec![],
+            }],
+            entries: vec![ImportedEntry {
+                feed_url: feed_url.clone(),
+                title: "First".to_string(),
+                link: link.clone(),
+                content: None,
+                published_at: None,
+                is_read: true,
+                is_starred: true,
+            }],
+            folders: vec![],
+        };
+        let imported = save_subscriptions(&db, subscriptions, "miniflux", None).await.unwrap();
+
+        // The feed names the entry by a guid of its own, which the import had no way to know
+        let transport = Arc::new(MockTransport::new());
+        transport.respond(&feed_url, MockResponse::ok(&format!(
+            r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Imported</title><link>https://203.0.113.1</link><description>Test</description><item><title>First</title><link>{}</link><guid isPermaLink="false">tag:203.0.113.1,2024:1</guid></item></channel></rss>"#,
+            link
+        )));
+        let fetcher = AsyncFeedFetcher::new_with_db(
+            FetcherConfig { transport: Some(transport), ..Default::default() },
+            Some(Arc::new(db.clone())),
+        );
+        let feed = &imported.created_feeds[0];
+        let saved = fetcher.fetch_and_save_feed(feed).await;
+        let entries = FeedEntry::find()
+            .filter(feed_entry::Column::FeedId.eq(feed.id))
+            .all(&db)
+            .await
+            .unwrap();
+        Feed::delete_by_id(feed.id).exec(&db).await.unwrap();
+
+        assert_eq!(saved.unwrap().added, 0);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].guid, "tag:203.0.113.1,2024:1");
+        assert!(entries[0].is_read && entries[0].is_starred);
+    }
+}
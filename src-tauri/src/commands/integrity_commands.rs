@@ -0,0 +1,17 @@
+use tauri::State;
+use crate::models::{check_library_integrity, compact_state_log, AppState, IntegrityReport};
+
+// UTILITY - Check the library for rows whose parent is gone and for entries a feed has twice.
+// With repair, orphans are deleted (or detached from a missing folder) and duplicates merged
+// into their oldest copy; the report then lists what was fixed.
+#[tauri::command]
+pub async fn check_integrity(state: State<'_, AppState>, repair: Option<bool>) -> Result<IntegrityReport, String> {
+    check_library_integrity(&state.db().await, repair.unwrap_or(false)).await
+}
+
+// UTILITY - Drop the read and starred changes that later changes have replaced from the
+// entry state log, as the scheduler does daily. Returns how many were dropped.
+#[tauri::command]
+pub async fn compact_entry_state_log(state: State<'_, AppState>) -> Result<u64, String> {
+    compact_state_log(&state.db().await).await
+}
@@ -1,6 +1,86 @@
 pub mod feed_commands;
 pub mod feed_entry_commands;
+pub mod notification_commands;
+pub mod folder_commands;
+pub mod import_commands;
+pub mod settings_commands;
+pub mod scheduler_commands;
+pub mod tag_commands;
+pub mod data_directory_commands;
+pub mod reading_commands;
+pub mod download_commands;
+pub mod playback_commands;
+pub mod annotation_commands;
+pub mod profile_commands;
+pub mod secret_commands;
+pub mod privacy_commands;
+pub mod bandwidth_commands;
+pub mod translation_commands;
+pub mod digest_commands;
+pub mod republish_commands;
+pub mod webhook_commands;
+pub mod social_commands;
+pub mod domain_rule_commands;
+pub mod mute_commands;
+pub mod tray_commands;
+pub mod open_request_commands;
+pub mod subscribe_endpoint_commands;
+pub mod retention_commands;
+pub mod display_policy_commands;
+pub mod archive_commands;
+pub mod snapshot_commands;
+pub mod backup_commands;
+pub mod search_commands;
+pub mod cookie_commands;
+pub mod operation_commands;
+pub mod integrity_commands;
+pub mod health_commands;
+pub mod startup_commands;
+pub mod alert_commands;
+pub mod sync_commands;
+#[cfg(feature = "fault-injection")]
+pub mod debug_commands;
 
 // Re-export all commands
 pub use feed_commands::*;
-pub use feed_entry_commands::*; 
\ No newline at end of file
+pub use feed_entry_commands::*;
+pub use notification_commands::*;
+pub use folder_commands::*;
+pub use import_commands::*;
+pub use settings_commands::*;
+pub use scheduler_commands::*;
+pub use tag_commands::*;
+pub use data_directory_commands::*;
+pub use reading_commands::*;
+pub use download_commands::*;
+pub use playback_commands::*;
+pub use annotation_commands::*;
+pub use profile_commands::*;
+pub use secret_commands::*;
+pub use privacy_commands::*;
+pub use bandwidth_commands::*;
+pub use translation_commands::*;
+pub use digest_commands::*;
+pub use republish_commands::*;
+pub use webhook_commands::*;
+pub use social_commands::*;
+pub use domain_rule_commands::*;
+pub use mute_commands::*;
+pub use tray_commands::*;
+pub use open_request_commands::*;
+pub use subscribe_endpoint_commands::*;
+pub use retention_commands::*;
+pub use display_policy_commands::*;
+pub use archive_commands::*;
+pub use snapshot_commands::*;
+pub use backup_commands::*;
+pub use search_commands::*;
+pub use cookie_commands::*;
+pub use operation_commands::*;
+pub use integrity_commands::*;
+pub use health_commands::*;
+pub use startup_commands::*;
+pub use alert_commands::*;
+pub use sync_commands::*;
+#[cfg(feature = "fault-injection")]
+pub use debug_commands::*; 
\ No newline at end of file
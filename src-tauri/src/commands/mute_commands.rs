@@ -0,0 +1,74 @@
+use chrono::DateTime as ChronoDateTime;
+use tauri::State;
+use crate::models::{
+    AppState,
+    MuteRulesConfig,
+    hide_muted_entries,
+    load_mute_rules_config,
+    normalize_mute_keyword,
+    set_setting_value,
+    unhide_unmuted_entries,
+    MUTE_RULES,
+};
+
+#[tauri::command]
+pub async fn get_mutes(state: State<'_, AppState>) -> Result<MuteRulesConfig, String> {
+    Ok(load_mute_rules_config(&state.db().await).await)
+}
+
+// CREATE - Hide entries mentioning a keyword until `until` (RFC 3339), or until unmuted if
+// no time is given. Unread entries already saved are hidden too; muting a keyword again
+// changes when the mute lapses.
+#[tauri::command]
+pub async fn mute_keyword(
+    state: State<'_, AppState>,
+    keyword: String,
+    until: Option<String>,
+) -> Result<MuteRulesConfig, String> {
+    let db = &state.db().await;
+
+    let keyword = normalize_mute_keyword(&keyword).ok_or("Mute keyword cannot be empty")?;
+    let expires_at = match until {
+        Some(until) => Some(
+            ChronoDateTime::parse_from_rfc3339(&until)
+                .map_err(|e| format!("Invalid mute expiry: {}", e))?
+                .naive_utc(),
+        ),
+        None => None,
+    };
+    let now = chrono::Utc::now().naive_utc();
+    if expires_at.is_some_and(|expires_at| expires_at <= now) {
+        return Err("Mute expiry must be in the future".to_string());
+    }
+
+    let mut config = load_mute_rules_config(db).await;
+    config.mute(keyword.clone(), expires_at);
+    set_setting_value(db, MUTE_RULES, &config).await?;
+
+    let just_this = MuteRulesConfig {
+        rules: config.rules.iter().filter(|rule| rule.keyword == keyword).cloned().collect(),
+    };
+    let hidden = hide_muted_entries(db, &just_this, now).await?;
+    println!("🔇 Muted {}: {} entries hidden", keyword, hidden);
+
+    Ok(config)
+}
+
+// DELETE - Lift a mute before it lapses, showing again the entries it hid
+#[tauri::command]
+pub async fn unmute_keyword(state: State<'_, AppState>, keyword: String) -> Result<MuteRulesConfig, String> {
+    let db = &state.db().await;
+
+    let keyword = normalize_mute_keyword(&keyword).ok_or("Mute keyword cannot be empty")?;
+    let mut config = load_mute_rules_config(db).await;
+    let Some(lifted) = config.unmute(&keyword) else {
+        return Ok(config);
+    };
+    set_setting_value(db, MUTE_RULES, &config).await?;
+
+    let now = chrono::Utc::now().naive_utc();
+    let unhidden = unhide_unmuted_entries(db, vec![lifted], &config, now).await?;
+    println!("🔈 Unmuted {}: {} entries shown again", keyword, unhidden);
+
+    Ok(config)
+}
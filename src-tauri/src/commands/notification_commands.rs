@@ -0,0 +1,20 @@
+use tauri::State;
+use crate::models::{AppState, NotificationConfig, set_setting_value, NOTIFICATIONS};
+
+#[tauri::command]
+pub async fn get_notification_settings(state: State<'_, AppState>) -> Result<NotificationConfig, String> {
+    Ok(state.notification_config.read().await.clone())
+}
+
+#[tauri::command]
+pub async fn update_notification_settings(
+    state: State<'_, AppState>,
+    settings: NotificationConfig,
+) -> Result<NotificationConfig, String> {
+    settings.validate()?;
+    set_setting_value(&state.db().await, NOTIFICATIONS, &settings).await?;
+    
+    let mut config = state.notification_config.write().await;
+    *config = settings;
+    Ok(config.clone())
+}
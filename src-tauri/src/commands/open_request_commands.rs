@@ -0,0 +1,13 @@
+use tauri::State;
+use crate::models::{AppState, OpenRequest};
+
+// READ - Take the feed:// links and OPML files waiting to be opened. The frontend asks the user
+// to confirm each, then subscribes with create_feed or imports with import_and_fetch.
+#[tauri::command]
+pub async fn take_pending_open_requests(state: State<'_, AppState>) -> Result<Vec<OpenRequest>, String> {
+    let mut pending = state
+        .pending_open_requests
+        .lock()
+        .map_err(|e| format!("Failed to read open requests: {}", e))?;
+    Ok(std::mem::take(&mut *pending))
+}
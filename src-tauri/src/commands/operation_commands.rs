@@ -0,0 +1,16 @@
+use tauri::State;
+use crate::models::{AppState, OperationProgress};
+
+// READ - Get every background operation (refreshes, imports, exports, full-content fetches,
+// downloads) started this session, running ones and the most recently finished
+#[tauri::command]
+pub async fn list_operations(state: State<'_, AppState>) -> Result<Vec<OperationProgress>, String> {
+    Ok(state.operations.list())
+}
+
+// UTILITY - Ask a running operation to stop. It reports "cancelling" until its work has
+// wound down, then "cancelled".
+#[tauri::command]
+pub async fn cancel_operation(state: State<'_, AppState>, operation_id: u64) -> Result<OperationProgress, String> {
+    state.operations.cancel(operation_id)
+}
@@ -0,0 +1,66 @@
+use sea_orm::*;
+use sea_orm::sea_query::OnConflict;
+use tauri::State;
+use crate::entities::{prelude::*, *};
+use crate::models::{AppState, PlaybackStateResponse, SavePlaybackPositionRequest, is_playback_finished};
+
+// UPDATE - Save where playback of an episode got to, so it can be resumed later
+#[tauri::command]
+pub async fn save_playback_position(
+    state: State<'_, AppState>,
+    request: SavePlaybackPositionRequest,
+) -> Result<PlaybackStateResponse, String> {
+    let db = &state.db().await;
+
+    if request.position_seconds < 0 {
+        return Err("Playback position cannot be negative".to_string());
+    }
+
+    let entry = FeedEntry::find_by_id(request.entry_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entry: {}", e))?
+        .ok_or("Feed entry not found")?;
+
+    let duration_seconds = request.duration_seconds.or(entry.duration_seconds);
+    let finished = is_playback_finished(request.position_seconds, duration_seconds, request.finished);
+
+    let playback = playback_state::ActiveModel {
+        entry_id: ActiveValue::Set(request.entry_id),
+        position_seconds: ActiveValue::Set(request.position_seconds),
+        duration_seconds: ActiveValue::Set(duration_seconds),
+        finished: ActiveValue::Set(finished),
+        updated_at: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+    };
+
+    let saved = PlaybackState::insert(playback)
+        .on_conflict(
+            OnConflict::column(playback_state::Column::EntryId)
+                .update_columns([
+                    playback_state::Column::PositionSeconds,
+                    playback_state::Column::DurationSeconds,
+                    playback_state::Column::Finished,
+                    playback_state::Column::UpdatedAt,
+                ])
+                .to_owned(),
+        )
+        .exec_with_returning(db)
+        .await
+        .map_err(|e| format!("Failed to save playback position: {}", e))?;
+
+    Ok(saved.into())
+}
+
+// READ - Get the saved playback position for an episode, if it has been played
+#[tauri::command]
+pub async fn get_playback_position(
+    state: State<'_, AppState>,
+    entry_id: i32,
+) -> Result<Option<PlaybackStateResponse>, String> {
+    let playback = PlaybackState::find_by_id(entry_id)
+        .one(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to fetch playback position: {}", e))?;
+
+    Ok(playback.map(|playback| playback.into()))
+}
@@ -0,0 +1,54 @@
+use sea_orm::*;
+use tauri::State;
+use crate::entities::{prelude::*, *};
+use crate::models::{AppState, FeedResponse, ImagePolicy, PrivacyConfig, set_setting_value, PRIVACY};
+
+#[tauri::command]
+pub async fn get_privacy_settings(state: State<'_, AppState>) -> Result<PrivacyConfig, String> {
+    Ok(state.privacy_config.read().await.clone())
+}
+
+// Applies to entries saved from the next fetch on; stored links are not rewritten
+#[tauri::command]
+pub async fn update_privacy_settings(
+    state: State<'_, AppState>,
+    settings: PrivacyConfig,
+) -> Result<PrivacyConfig, String> {
+    set_setting_value(&state.db().await, PRIVACY, &settings).await?;
+
+    let mut config = state.privacy_config.write().await;
+    *config = settings;
+    Ok(config.clone())
+}
+
+// UPDATE - Override the image policy for one feed, or follow the global setting when None.
+// Applies to entries saved from the next fetch on.
+#[tauri::command]
+pub async fn set_feed_image_policy(
+    state: State<'_, AppState>,
+    feed_id: i32,
+    image_policy: Option<String>,
+) -> Result<FeedResponse, String> {
+    let db = &state.db().await;
+
+    let image_policy = image_policy
+        .map(|policy| policy.parse::<ImagePolicy>())
+        .transpose()?;
+
+    let existing_feed = Feed::find_by_id(feed_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed: {}", e))?
+        .ok_or("Feed not found")?;
+
+    let mut updated_feed: feed::ActiveModel = existing_feed.into();
+    updated_feed.image_policy = ActiveValue::Set(image_policy.map(|policy| policy.as_str().to_string()));
+    updated_feed.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+    let result = updated_feed
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to update feed: {}", e))?;
+
+    Ok(result.into())
+}
@@ -0,0 +1,159 @@
+use std::sync::Arc;
+use sea_orm::*;
+use tauri::{AppHandle, Emitter, State};
+use crate::entities::{prelude::*, *};
+use crate::models::{
+    AppState, CreateProfileRequest, ProfileResponse, ACTIVE_PROFILE, DEFAULT_PROFILE, PROFILE_SWITCHED_EVENT,
+    connect_database, ensure_library_schema, load_notification_config, load_privacy_config, load_scheduler_config,
+    open_profile_database, set_setting_value, validate_profile_name,
+};
+
+// CREATE - Register another library database as a profile. The database must already be migrated.
+#[tauri::command]
+pub async fn create_profile(
+    state: State<'_, AppState>,
+    request: CreateProfileRequest,
+) -> Result<ProfileResponse, String> {
+    let name = validate_profile_name(&request.name)?;
+
+    let existing = Profile::find_by_id(name.clone())
+        .one(&state.home_db)
+        .await
+        .map_err(|e| format!("Failed to fetch profile: {}", e))?;
+    if existing.is_some() {
+        return Err(format!("Profile \"{}\" already exists", name));
+    }
+
+    // Make sure the database is reachable and set up before saving it
+    let db = connect_database(request.database_url.clone())
+        .await
+        .map_err(|e| format!("Failed to connect to profile database: {}", e))?;
+    ensure_library_schema(&db).await?;
+    let _ = db.close().await;
+
+    let new_profile = profile::ActiveModel {
+        name: ActiveValue::Set(name),
+        database_url: ActiveValue::Set(request.database_url),
+        created_at: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+    };
+
+    let result = new_profile
+        .insert(&state.home_db)
+        .await
+        .map_err(|e| format!("Failed to create profile: {}", e))?;
+
+    Ok(ProfileResponse {
+        name: result.name,
+        is_active: false,
+        created_at: Some(result.created_at.to_string()),
+    })
+}
+
+// READ - Get the default profile and every other profile, marking the active one
+#[tauri::command]
+pub async fn get_profiles(state: State<'_, AppState>) -> Result<Vec<ProfileResponse>, String> {
+    let active_profile = state.active_profile.read().await.clone();
+
+    let profiles = Profile::find()
+        .order_by_asc(profile::Column::Name)
+        .all(&state.home_db)
+        .await
+        .map_err(|e| format!("Failed to fetch profiles: {}", e))?;
+
+    let mut responses = vec![ProfileResponse {
+        name: DEFAULT_PROFILE.to_string(),
+        is_active: active_profile == DEFAULT_PROFILE,
+        created_at: None,
+    }];
+    responses.extend(profiles.into_iter().map(|profile| ProfileResponse {
+        is_active: profile.name == active_profile,
+        name: profile.name,
+        created_at: Some(profile.created_at.to_string()),
+    }));
+
+    Ok(responses)
+}
+
+// UPDATE - Switch the whole app to another profile's library. Refuses while a refresh, import or
+// download is running, since those write to the current library. Fetcher settings from the
+// new profile take effect on the next restart; notification, scheduler and privacy settings apply now.
+#[tauri::command]
+pub async fn switch_profile(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<ProfileResponse, String> {
+    let name = name.trim().to_string();
+    if *state.active_profile.read().await == name {
+        return Err(format!("Profile \"{}\" is already active", name));
+    }
+
+    if let Some(fetcher) = &state.async_fetcher {
+        if fetcher.get_refresh_progress().await.is_active {
+            return Err("Wait for the refresh to finish before switching profiles".to_string());
+        }
+    }
+    if state.downloads.has_active().await {
+        return Err("Wait for downloads to finish before switching profiles".to_string());
+    }
+    let import_running = state
+        .import_operations
+        .read()
+        .await
+        .values()
+        .any(|operation| matches!(operation.progress.status.as_str(), "fetching" | "cancelling"));
+    if import_running {
+        return Err("Wait for the import to finish before switching profiles".to_string());
+    }
+
+    let new_db = open_profile_database(&state.home_db, &name).await?;
+
+    // Hold the write lock for the whole swap so no command sees a half-switched app
+    let mut db = state.db.write().await;
+    if let Some(fetcher) = &state.async_fetcher {
+        fetcher.switch_database(Arc::new(new_db.clone())).await?;
+        fetcher.load_feed_overrides(&new_db).await?;
+    }
+    *state.notification_config.write().await = load_notification_config(&new_db).await;
+    *state.scheduler_config.write().await = load_scheduler_config(&new_db).await;
+    *state.privacy_config.write().await = load_privacy_config(&new_db).await;
+    state.import_operations.write().await.clear();
+    *state.last_bulk_action.write().await = None;
+    *db = new_db;
+    *state.active_profile.write().await = name.clone();
+    drop(db);
+
+    set_setting_value(&state.home_db, ACTIVE_PROFILE, &name).await?;
+
+    let response = ProfileResponse {
+        name,
+        is_active: true,
+        created_at: None,
+    };
+    let _ = app.emit(PROFILE_SWITCHED_EVENT, &response);
+
+    println!("👤 Switched to profile {}", response.name);
+    Ok(response)
+}
+
+// DELETE - Remove a profile from the list. Its database is left untouched.
+#[tauri::command]
+pub async fn delete_profile(state: State<'_, AppState>, name: String) -> Result<String, String> {
+    if name == DEFAULT_PROFILE {
+        return Err("The default profile cannot be deleted".to_string());
+    }
+    if *state.active_profile.read().await == name {
+        return Err("Switch to another profile before deleting this one".to_string());
+    }
+
+    let result = Profile::delete_by_id(name.clone())
+        .exec(&state.home_db)
+        .await
+        .map_err(|e| format!("Failed to delete profile: {}", e))?;
+
+    if result.rows_affected == 0 {
+        return Err("Profile not found".to_string());
+    }
+
+    Ok(format!("Profile {} deleted; its database was not removed", name))
+}
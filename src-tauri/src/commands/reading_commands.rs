@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use sea_orm::*;
+use sea_orm::sea_query::Expr;
+use tauri::{AppHandle, Emitter, State};
+use crate::entities::{prelude::*, *};
+use crate::models::{
+    AppState,
+    FeedResponse,
+    ReadingConfig,
+    ReadProgressResponse,
+    UpdateReadProgressRequest,
+    load_reading_config,
+    mark_read_on_scroll_enabled,
+    record_state_changes,
+    set_setting_value,
+    StateField,
+    LOCAL_ORIGIN,
+    READING,
+};
+
+// Emitted with a ReadProgressResponse whenever update_read_progress marks entries read,
+// so every open window can update its unread state
+pub const ENTRIES_MARKED_READ_EVENT: &str = "entries:marked_read";
+
+#[tauri::command]
+pub async fn get_reading_settings(state: State<'_, AppState>) -> Result<ReadingConfig, String> {
+    Ok(load_reading_config(&state.db().await).await)
+}
+
+#[tauri::command]
+pub async fn update_reading_settings(
+    state: State<'_, AppState>,
+    settings: ReadingConfig,
+) -> Result<ReadingConfig, String> {
+    set_setting_value(&state.db().await, READING, &settings).await?;
+    Ok(settings)
+}
+
+// UPDATE - Override mark-read-on-scroll for one feed, or follow the global setting when None
+#[tauri::command]
+pub async fn set_feed_mark_read_on_scroll(
+    state: State<'_, AppState>,
+    feed_id: i32,
+    enabled: Option<bool>,
+) -> Result<FeedResponse, String> {
+    let db = &state.db().await;
+
+    let existing_feed = Feed::find_by_id(feed_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed: {}", e))?
+        .ok_or("Feed not found")?;
+
+    let mut updated_feed: feed::ActiveModel = existing_feed.into();
+    updated_feed.mark_read_on_scroll = ActiveValue::Set(enabled);
+    updated_feed.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+    let result = updated_feed
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to update feed: {}", e))?;
+
+    Ok(result.into())
+}
+
+// UPDATE - Report entries the user scrolled past; unread ones are marked read where the
+// global or per-feed policy asks for it
+#[tauri::command]
+pub async fn update_read_progress(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: UpdateReadProgressRequest,
+) -> Result<ReadProgressResponse, String> {
+    let db = &state.db().await;
+
+    if request.scrolled_past_entry_ids.is_empty() {
+        return Ok(ReadProgressResponse { marked_read_entry_ids: Vec::new() });
+    }
+
+    let config = load_reading_config(db).await;
+
+    let unread_entries = FeedEntry::find()
+        .filter(feed_entry::Column::Id.is_in(request.scrolled_past_entry_ids))
+        .filter(feed_entry::Column::IsRead.eq(false))
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entries: {}", e))?;
+
+    let mut feed_ids: Vec<i32> = unread_entries.iter().map(|entry| entry.feed_id).collect();
+    feed_ids.sort_unstable();
+    feed_ids.dedup();
+
+    let feed_overrides: HashMap<i32, Option<bool>> = Feed::find()
+        .filter(feed::Column::Id.is_in(feed_ids))
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feeds: {}", e))?
+        .into_iter()
+        .map(|feed| (feed.id, feed.mark_read_on_scroll))
+        .collect();
+
+    let marked_read_entry_ids: Vec<i32> = unread_entries
+        .iter()
+        .filter(|entry| {
+            let feed_override = feed_overrides.get(&entry.feed_id).copied().flatten();
+            mark_read_on_scroll_enabled(&config, feed_override)
+        })
+        .map(|entry| entry.id)
+        .collect();
+
+    if !marked_read_entry_ids.is_empty() {
+        let now = chrono::Utc::now().naive_utc();
+        FeedEntry::update_many()
+            .col_expr(feed_entry::Column::IsRead, Expr::value(true))
+            .col_expr(feed_entry::Column::ReadAt, Expr::value(now))
+            .col_expr(feed_entry::Column::UpdatedAt, Expr::value(now))
+            .filter(feed_entry::Column::Id.is_in(marked_read_entry_ids.clone()))
+            .exec(db)
+            .await
+            .map_err(|e| format!("Failed to mark entries as read: {}", e))?;
+        record_state_changes(db, &marked_read_entry_ids, StateField::Read, true, now, LOCAL_ORIGIN).await?;
+    }
+
+    let response = ReadProgressResponse { marked_read_entry_ids };
+    if !response.marked_read_entry_ids.is_empty() {
+        if let Err(e) = app.emit(ENTRIES_MARKED_READ_EVENT, &response) {
+            eprintln!("❌ Failed to emit read progress: {}", e);
+        }
+    }
+
+    Ok(response)
+}
@@ -0,0 +1,49 @@
+use tauri::{AppHandle, State};
+use crate::models::{
+    AppState,
+    RepublishConfig,
+    build_republished_feed,
+    load_republish_config,
+    set_setting_value,
+    REPUBLISH,
+};
+
+#[tauri::command]
+pub async fn get_republish_settings(state: State<'_, AppState>) -> Result<RepublishConfig, String> {
+    Ok(load_republish_config(&state.db().await).await)
+}
+
+// Saving the settings restarts the feed server with them (or stops it)
+#[tauri::command]
+pub async fn update_republish_settings(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    settings: RepublishConfig,
+) -> Result<RepublishConfig, String> {
+    settings.validate()?;
+    set_setting_value(&state.db().await, REPUBLISH, &settings).await?;
+    state.feed_server.apply(&app, &settings).await?;
+    Ok(settings)
+}
+
+// EXPORT - Write the starred entries, or the entries with a tag, to an Atom feed file
+#[tauri::command]
+pub async fn export_atom_feed(
+    state: State<'_, AppState>,
+    tag_id: Option<i32>,
+    path: String,
+) -> Result<usize, String> {
+    let db = &state.db().await;
+    let config = load_republish_config(db).await;
+
+    let (atom, entry_count) = build_republished_feed(db, tag_id, config.max_entries, None)
+        .await?
+        .ok_or("Tag not found")?;
+
+    tokio::fs::write(&path, atom)
+        .await
+        .map_err(|e| format!("Failed to write Atom file: {}", e))?;
+
+    println!("📤 Exported {} entries to {}", entry_count, path);
+    Ok(entry_count)
+}
@@ -0,0 +1,58 @@
+use sea_orm::*;
+use tauri::State;
+use crate::entities::{prelude::*, *};
+use crate::models::{
+    AppState,
+    FeedResponse,
+    RetentionConfig,
+    load_retention_config,
+    set_setting_value,
+    RETENTION,
+};
+
+#[tauri::command]
+pub async fn get_retention_settings(state: State<'_, AppState>) -> Result<RetentionConfig, String> {
+    Ok(load_retention_config(&state.db().await).await)
+}
+
+// UPDATE - Change the global entry cap. Feeds are pruned to it after their next save.
+#[tauri::command]
+pub async fn update_retention_settings(
+    state: State<'_, AppState>,
+    settings: RetentionConfig,
+) -> Result<RetentionConfig, String> {
+    set_setting_value(&state.db().await, RETENTION, &settings).await?;
+    Ok(settings)
+}
+
+// UPDATE - Override the entry cap for one feed (0 keeps everything), or follow the global
+// setting when None. Applies from the feed's next save.
+#[tauri::command]
+pub async fn set_feed_max_entries(
+    state: State<'_, AppState>,
+    feed_id: i32,
+    max_entries: Option<i32>,
+) -> Result<FeedResponse, String> {
+    let db = &state.db().await;
+
+    if max_entries.is_some_and(|max_entries| max_entries < 0) {
+        return Err("A feed's entry cap cannot be negative".to_string());
+    }
+
+    let existing_feed = Feed::find_by_id(feed_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed: {}", e))?
+        .ok_or("Feed not found")?;
+
+    let mut updated_feed: feed::ActiveModel = existing_feed.into();
+    updated_feed.max_entries = ActiveValue::Set(max_entries);
+    updated_feed.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+    let result = updated_feed
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to update feed: {}", e))?;
+
+    Ok(result.into())
+}
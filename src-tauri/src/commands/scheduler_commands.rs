@@ -0,0 +1,20 @@
+use tauri::State;
+use crate::models::{AppState, SchedulerConfig, set_setting_value, SCHEDULER};
+
+#[tauri::command]
+pub async fn get_scheduler_settings(state: State<'_, AppState>) -> Result<SchedulerConfig, String> {
+    Ok(state.scheduler_config.read().await.clone())
+}
+
+#[tauri::command]
+pub async fn update_scheduler_settings(
+    state: State<'_, AppState>,
+    settings: SchedulerConfig,
+) -> Result<SchedulerConfig, String> {
+    settings.validate()?;
+    set_setting_value(&state.db().await, SCHEDULER, &settings).await?;
+
+    let mut config = state.scheduler_config.write().await;
+    *config = settings;
+    Ok(config.clone())
+}
@@ -0,0 +1,13 @@
+use tauri::State;
+use crate::models::{AppState, SearchSuggestion, load_search_suggestions};
+
+// READ - Autocomplete for the search bar: feed titles, tags and recent entry titles matching
+// what has been typed so far
+#[tauri::command]
+pub async fn search_suggestions(
+    state: State<'_, AppState>,
+    prefix: String,
+    limit: Option<u64>,
+) -> Result<Vec<SearchSuggestion>, String> {
+    load_search_suggestions(&state.db().await, &prefix, limit.unwrap_or(5), chrono::Utc::now().naive_utc()).await
+}
@@ -0,0 +1,18 @@
+use crate::models::{SecretKind, delete_secret_value, store_secret_value};
+
+// UPDATE - Save a credential in the OS keychain, replacing any previous value.
+// Secret values are write-only from the UI; the backend reads them when it needs them.
+#[tauri::command]
+pub async fn store_secret(kind: SecretKind, name: String, value: String) -> Result<(), String> {
+    if value.is_empty() {
+        return Err("Secret value cannot be empty".to_string());
+    }
+
+    store_secret_value(kind, name, value).await
+}
+
+// DELETE - Remove a credential from the OS keychain
+#[tauri::command]
+pub async fn delete_secret(kind: SecretKind, name: String) -> Result<bool, String> {
+    delete_secret_value(kind, name).await
+}
@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+use tauri::State;
+use crate::models::{AppState, get_setting_value, set_setting_value, get_all_setting_values, validate_setting_value};
+
+// READ - Get a single setting value (None if never set)
+#[tauri::command]
+pub async fn get_setting(
+    state: State<'_, AppState>,
+    key: String,
+) -> Result<Option<serde_json::Value>, String> {
+    get_setting_value(&state.db().await, &key).await
+}
+
+// UPDATE - Insert or replace a setting value, once it's checked against the setting's type
+#[tauri::command]
+pub async fn set_setting(
+    state: State<'_, AppState>,
+    key: String,
+    value: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    validate_setting_value(&key, &value)?;
+    set_setting_value(&state.db().await, &key, &value).await?;
+    Ok(value)
+}
+
+// READ - Get every stored setting
+#[tauri::command]
+pub async fn get_all_settings(
+    state: State<'_, AppState>,
+) -> Result<HashMap<String, serde_json::Value>, String> {
+    get_all_setting_values(&state.db().await).await
+}
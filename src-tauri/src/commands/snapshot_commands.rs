@@ -0,0 +1,21 @@
+use tauri::{AppHandle, State};
+use crate::models::{
+    AppState,
+    EntrySnapshotResponse,
+    SnapshotStorageUsage,
+    load_entry_snapshot,
+    load_snapshot_storage_usage,
+};
+
+// READ - The permanent copy of a starred entry, None until it's been taken. Still available
+// after the entry, or its feed, is deleted.
+#[tauri::command]
+pub async fn get_snapshot(state: State<'_, AppState>, entry_id: i32) -> Result<Option<EntrySnapshotResponse>, String> {
+    load_entry_snapshot(&state.db().await, entry_id).await
+}
+
+// READ - How much space snapshots of starred entries take, in the database and on disk
+#[tauri::command]
+pub async fn get_snapshot_storage_usage(app: AppHandle, state: State<'_, AppState>) -> Result<SnapshotStorageUsage, String> {
+    load_snapshot_storage_usage(&app, &state.db().await).await
+}
@@ -0,0 +1,171 @@
+use sea_orm::*;
+use tauri::State;
+use crate::entities::{prelude::*, *};
+use crate::models::{
+    AppState,
+    ShareFormat,
+    ShareHistoryResponse,
+    SocialConfig,
+    SocialNetwork,
+    compose_post_text,
+    connect_bluesky,
+    disconnect_social_account as forget_social_account,
+    finish_mastodon_authorization,
+    format_entry_snippet,
+    load_social_config,
+    publish_post,
+    resolve_entry_canonical_link,
+    set_setting_value,
+    start_mastodon_authorization,
+    SOCIAL,
+};
+
+#[tauri::command]
+pub async fn get_social_settings(state: State<'_, AppState>) -> Result<SocialConfig, String> {
+    Ok(load_social_config(&state.db().await).await)
+}
+
+// Register with a Mastodon instance and return the page to open in the browser. The code
+// shown there after authorizing is passed to finish_mastodon_auth.
+#[tauri::command]
+pub async fn start_mastodon_auth(state: State<'_, AppState>, instance: String) -> Result<String, String> {
+    let db = &state.db().await;
+
+    let mut config = load_social_config(db).await;
+    let authorize_url = start_mastodon_authorization(&mut config, &instance).await?;
+    set_setting_value(db, SOCIAL, &config).await?;
+
+    Ok(authorize_url)
+}
+
+#[tauri::command]
+pub async fn finish_mastodon_auth(state: State<'_, AppState>, code: String) -> Result<SocialConfig, String> {
+    let db = &state.db().await;
+
+    let mut config = load_social_config(db).await;
+    finish_mastodon_authorization(&mut config, &code).await?;
+    set_setting_value(db, SOCIAL, &config).await?;
+
+    Ok(config)
+}
+
+// Bluesky accounts connect with an app password (Settings > Privacy and security > App passwords)
+#[tauri::command]
+pub async fn connect_bluesky_account(
+    state: State<'_, AppState>,
+    handle: String,
+    app_password: String,
+) -> Result<SocialConfig, String> {
+    let db = &state.db().await;
+
+    let mut config = load_social_config(db).await;
+    connect_bluesky(&mut config, &handle, app_password).await?;
+    set_setting_value(db, SOCIAL, &config).await?;
+
+    Ok(config)
+}
+
+#[tauri::command]
+pub async fn disconnect_social_account(
+    state: State<'_, AppState>,
+    network: SocialNetwork,
+) -> Result<SocialConfig, String> {
+    let db = &state.db().await;
+
+    let mut config = load_social_config(db).await;
+    forget_social_account(&mut config, network).await?;
+    set_setting_value(db, SOCIAL, &config).await?;
+
+    Ok(config)
+}
+
+// CREATE - Post an entry's title and link, with an optional comment, to a connected account
+#[tauri::command]
+pub async fn share_entry(
+    state: State<'_, AppState>,
+    entry_id: i32,
+    network: SocialNetwork,
+    comment: Option<String>,
+) -> Result<ShareHistoryResponse, String> {
+    let db = &state.db().await;
+
+    let entry = FeedEntry::find_by_id(entry_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entry: {}", e))?
+        .ok_or("Feed entry not found")?;
+
+    // Share the page's canonical link rather than a feed redirect or mobile variant
+    let canonical_link = match &state.async_fetcher {
+        Some(fetcher) => resolve_entry_canonical_link(db, fetcher.transport().as_ref(), &entry).await?,
+        None => entry.canonical_link.clone(),
+    };
+    let link = canonical_link.as_deref().unwrap_or(&entry.link);
+
+    let comment = comment.map(|comment| comment.trim().to_string()).filter(|comment| !comment.is_empty());
+    let text = compose_post_text(network, &entry.title, link, comment.as_deref())?;
+    let post_url = publish_post(&load_social_config(db).await, network, &text, link).await?;
+
+    let share = share_history::ActiveModel {
+        entry_id: ActiveValue::Set(entry_id),
+        network: ActiveValue::Set(network.as_str().to_string()),
+        comment: ActiveValue::Set(comment),
+        post_url: ActiveValue::Set(post_url),
+        created_at: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+        ..Default::default()
+    };
+    let result = share
+        .insert(db)
+        .await
+        .map_err(|e| format!("Failed to record share: {}", e))?;
+
+    println!("📣 Shared entry {} to {}", entry_id, network.as_str());
+    Ok(result.into())
+}
+
+// READ - An entry as plain text, Markdown, HTML or a quote of the selected text, for copying
+// or handing to another app
+#[tauri::command]
+pub async fn format_entry_for_sharing(
+    state: State<'_, AppState>,
+    entry_id: i32,
+    format: ShareFormat,
+    selected_text: Option<String>,
+) -> Result<String, String> {
+    let db = &state.db().await;
+
+    let (entry, feed) = FeedEntry::find_by_id(entry_id)
+        .find_also_related(Feed)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entry: {}", e))?
+        .ok_or("Feed entry not found")?;
+    let feed_title = feed.and_then(|feed| feed.title);
+
+    format_entry_snippet(&entry, feed_title.as_deref(), format, selected_text.as_deref())
+}
+
+// READ - Shares of one entry, or of every entry, newest first
+#[tauri::command]
+pub async fn get_share_history(
+    state: State<'_, AppState>,
+    entry_id: Option<i32>,
+    limit: Option<u64>,
+) -> Result<Vec<ShareHistoryResponse>, String> {
+    let mut query = ShareHistory::find()
+        .order_by_desc(share_history::Column::CreatedAt)
+        .order_by_desc(share_history::Column::Id);
+    if let Some(entry_id) = entry_id {
+        query = query.filter(share_history::Column::EntryId.eq(entry_id));
+    }
+    if let Some(limit) = limit {
+        query = query.limit(limit);
+    }
+
+    let shares = query
+        .all(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to fetch share history: {}", e))?;
+
+    Ok(shares.into_iter().map(|share| share.into()).collect())
+}
@@ -0,0 +1,44 @@
+use tauri::{AppHandle, State};
+use crate::models::{configured_database_url, open_home_database, save_database_url, StartupState, StartupStatus};
+
+// READ - Get whether the app started with its database, and if not why, so the frontend can
+// show the setup screen instead of the library
+#[tauri::command]
+pub async fn get_startup_status(state: State<'_, StartupState>) -> Result<StartupStatus, String> {
+    Ok(state.status.lock().unwrap().clone())
+}
+
+// UTILITY - Try the database again, with a URL from the setup screen or the configured one.
+// Once it connects, a new URL is saved for the next launches and the app restarts into the
+// library; otherwise the returned status says what went wrong this time.
+#[tauri::command]
+pub async fn retry_startup(
+    app: AppHandle,
+    state: State<'_, StartupState>,
+    database_url: Option<String>,
+) -> Result<StartupStatus, String> {
+    if state.status.lock().unwrap().ready {
+        return Ok(state.status.lock().unwrap().clone());
+    }
+
+    let entered_url = database_url.map(|database_url| database_url.trim().to_string()).filter(|database_url| !database_url.is_empty());
+    let database_url = match entered_url.clone() {
+        Some(database_url) => Some(database_url),
+        None => configured_database_url().await,
+    };
+
+    match open_home_database(database_url.as_deref()).await {
+        Ok(db) => {
+            let _ = db.close().await;
+            if let Some(database_url) = &entered_url {
+                save_database_url(database_url).await?;
+            }
+            println!("🔄 Database available, restarting");
+            app.restart()
+        }
+        Err(status) => {
+            *state.status.lock().unwrap() = status.clone();
+            Ok(status)
+        }
+    }
+}
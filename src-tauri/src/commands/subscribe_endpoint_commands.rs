@@ -0,0 +1,43 @@
+use tauri::{AppHandle, State};
+use crate::models::{
+    AppState,
+    SubscribeEndpointConfig,
+    load_or_create_endpoint_token,
+    load_subscribe_endpoint_config,
+    regenerate_endpoint_token,
+    set_setting_value,
+    SUBSCRIBE_ENDPOINT,
+};
+
+#[tauri::command]
+pub async fn get_subscribe_endpoint_settings(state: State<'_, AppState>) -> Result<SubscribeEndpointConfig, String> {
+    Ok(load_subscribe_endpoint_config(&state.home_db).await)
+}
+
+// Saving the settings restarts the subscribe endpoint with them (or stops it)
+#[tauri::command]
+pub async fn update_subscribe_endpoint_settings(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    settings: SubscribeEndpointConfig,
+) -> Result<SubscribeEndpointConfig, String> {
+    settings.validate()?;
+    set_setting_value(&state.home_db, SUBSCRIBE_ENDPOINT, &settings).await?;
+    state.subscribe_endpoint.apply(&app, &settings).await?;
+    Ok(settings)
+}
+
+// READ - The token to paste into the browser extension or bookmarklet
+#[tauri::command]
+pub async fn get_subscribe_endpoint_token() -> Result<String, String> {
+    load_or_create_endpoint_token().await
+}
+
+// UPDATE - Replace the token, e.g. after it leaked. The running endpoint switches to it at once.
+#[tauri::command]
+pub async fn regenerate_subscribe_endpoint_token(app: AppHandle, state: State<'_, AppState>) -> Result<String, String> {
+    let token = regenerate_endpoint_token().await?;
+    let settings = load_subscribe_endpoint_config(&state.home_db).await;
+    state.subscribe_endpoint.apply(&app, &settings).await?;
+    Ok(token)
+}
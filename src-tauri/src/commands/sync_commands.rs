@@ -0,0 +1,36 @@
+use tauri::State;
+use crate::models::{
+    AppState,
+    SyncConfig,
+    SyncReport,
+    SyncStatus,
+    load_sync_status,
+    set_setting_value,
+    SYNC,
+};
+
+// The sync settings along with this device's id, whether a passphrase is saved and how the
+// last sync went
+#[tauri::command]
+pub async fn get_sync_status(state: State<'_, AppState>) -> Result<SyncStatus, String> {
+    load_sync_status(&state.db().await).await
+}
+
+// The passphrase and storage passwords are not part of the settings; they are saved with
+// store_secret as sync_token secrets named "passphrase", "webdav" or "s3". Every device
+// syncing together needs the same passphrase.
+#[tauri::command]
+pub async fn update_sync_settings(
+    state: State<'_, AppState>,
+    settings: SyncConfig,
+) -> Result<SyncConfig, String> {
+    settings.validate()?;
+    set_setting_value(&state.db().await, SYNC, &settings).await?;
+    Ok(settings)
+}
+
+// UTILITY - Sync with the other devices now, whether or not background sync is on
+#[tauri::command]
+pub async fn sync_now(state: State<'_, AppState>) -> Result<SyncReport, String> {
+    state.sync_engine.sync(&state.db().await).await
+}
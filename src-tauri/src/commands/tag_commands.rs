@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use sea_orm::*;
+use sea_orm::sea_query::{Expr, OnConflict, Query};
+use tauri::State;
+use crate::entities::{prelude::*, *};
+use crate::models::{AppState, ClassifierConfig, CreateTagRequest, TagResponse, TagWithCountResponse, load_classifier_config, set_setting_value, CLASSIFIER};
+
+async fn find_tag<C: ConnectionTrait>(db: &C, id: i32) -> Result<tag::Model, String> {
+    Tag::find_by_id(id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch tag: {}", e))?
+        .ok_or_else(|| format!("Tag {} not found", id))
+}
+
+// CREATE - Insert a new tag
+#[tauri::command]
+pub async fn create_tag(
+    state: State<'_, AppState>,
+    request: CreateTagRequest,
+) -> Result<TagResponse, String> {
+    let db = &state.db().await;
+
+    let new_tag = tag::ActiveModel {
+        name: ActiveValue::Set(request.name),
+        created_at: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+        ..Default::default()
+    };
+
+    let created_tag = new_tag
+        .insert(db)
+        .await
+        .map_err(|e| format!("Failed to create tag: {}", e))?;
+
+    Ok(created_tag.into())
+}
+
+// READ - Get all tags
+#[tauri::command]
+pub async fn get_all_tags(state: State<'_, AppState>) -> Result<Vec<TagResponse>, String> {
+    let db = &state.db().await;
+
+    let tags = Tag::find()
+        .order_by_asc(tag::Column::Name)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch tags: {}", e))?;
+
+    Ok(tags.into_iter().map(|tag| tag.into()).collect())
+}
+
+// READ - Get all tags with how many entries carry each one
+#[tauri::command]
+pub async fn get_tags_with_counts(state: State<'_, AppState>) -> Result<Vec<TagWithCountResponse>, String> {
+    let db = &state.db().await;
+
+    let tags = Tag::find()
+        .order_by_asc(tag::Column::Name)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch tags: {}", e))?;
+
+    let counts: Vec<(i32, i64)> = EntryTag::find()
+        .select_only()
+        .column(entry_tag::Column::TagId)
+        .column_as(entry_tag::Column::EntryId.count(), "entry_count")
+        .group_by(entry_tag::Column::TagId)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to count tagged entries: {}", e))?;
+    let counts: HashMap<i32, i64> = counts.into_iter().collect();
+
+    Ok(tags
+        .into_iter()
+        .map(|tag| TagWithCountResponse {
+            entry_count: counts.get(&tag.id).copied().unwrap_or(0).max(0) as u64,
+            id: tag.id,
+            name: tag.name,
+            created_at: tag.created_at.to_string(),
+        })
+        .collect())
+}
+
+// READ - Get the tags on an entry
+#[tauri::command]
+pub async fn get_entry_tags(
+    state: State<'_, AppState>,
+    entry_id: i32,
+) -> Result<Vec<TagResponse>, String> {
+    let db = &state.db().await;
+
+    let tags = Tag::find()
+        .inner_join(EntryTag)
+        .filter(entry_tag::Column::EntryId.eq(entry_id))
+        .order_by_asc(tag::Column::Name)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch entry tags: {}", e))?;
+
+    Ok(tags.into_iter().map(|tag| tag.into()).collect())
+}
+
+// UPDATE - Add a tag to an entry (no-op if it is already tagged)
+#[tauri::command]
+pub async fn add_tag_to_entry(
+    state: State<'_, AppState>,
+    entry_id: i32,
+    tag_id: i32,
+) -> Result<String, String> {
+    let db = &state.db().await;
+
+    let new_entry_tag = entry_tag::ActiveModel {
+        entry_id: ActiveValue::Set(entry_id),
+        tag_id: ActiveValue::Set(tag_id),
+        created_at: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+    };
+
+    EntryTag::insert(new_entry_tag)
+        .on_conflict(
+            sea_query::OnConflict::columns([entry_tag::Column::EntryId, entry_tag::Column::TagId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .do_nothing()
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to tag entry: {}", e))?;
+
+    Ok(format!("Tag {} added to entry {}", tag_id, entry_id))
+}
+
+// UPDATE - Remove a tag from an entry
+#[tauri::command]
+pub async fn remove_tag_from_entry(
+    state: State<'_, AppState>,
+    entry_id: i32,
+    tag_id: i32,
+) -> Result<String, String> {
+    let db = &state.db().await;
+
+    EntryTag::delete_by_id((entry_id, tag_id))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to untag entry: {}", e))?;
+
+    Ok(format!("Tag {} removed from entry {}", tag_id, entry_id))
+}
+
+// UPDATE - Rename a tag
+#[tauri::command]
+pub async fn rename_tag(
+    state: State<'_, AppState>,
+    id: i32,
+    name: String,
+) -> Result<TagResponse, String> {
+    let db = &state.db().await;
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Tag name cannot be empty".to_string());
+    }
+
+    let existing_tag = find_tag(db, id).await?;
+
+    let name_taken = Tag::find()
+        .filter(tag::Column::Name.eq(&name))
+        .filter(tag::Column::Id.ne(id))
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch tag: {}", e))?;
+    if name_taken.is_some() {
+        return Err(format!("A tag named \"{}\" already exists; merge the tags instead", name));
+    }
+
+    let mut updated_tag: tag::ActiveModel = existing_tag.into();
+    updated_tag.name = ActiveValue::Set(name);
+
+    let result = updated_tag
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to rename tag: {}", e))?;
+
+    Ok(result.into())
+}
+
+// UPDATE - Merge one tag into another: every entry tagged `from` is tagged `into`, then `from` is deleted
+#[tauri::command]
+pub async fn merge_tags(
+    state: State<'_, AppState>,
+    from_tag_id: i32,
+    into_tag_id: i32,
+) -> Result<TagResponse, String> {
+    let db = &state.db().await;
+
+    if from_tag_id == into_tag_id {
+        return Err("Cannot merge a tag into itself".to_string());
+    }
+
+    let txn = db.begin().await.map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    find_tag(&txn, from_tag_id).await?;
+    let into_tag = find_tag(&txn, into_tag_id).await?;
+
+    // Copy the tagging over in one statement, skipping entries that already have both tags
+    let retag = Query::insert()
+        .into_table(EntryTag)
+        .columns([entry_tag::Column::EntryId, entry_tag::Column::TagId, entry_tag::Column::CreatedAt])
+        .select_from(
+            Query::select()
+                .column(entry_tag::Column::EntryId)
+                .expr(Expr::value(into_tag_id))
+                .column(entry_tag::Column::CreatedAt)
+                .from(EntryTag)
+                .and_where(entry_tag::Column::TagId.eq(from_tag_id))
+                .to_owned(),
+        )
+        .map_err(|e| format!("Failed to build tag merge: {}", e))?
+        .on_conflict(
+            OnConflict::columns([entry_tag::Column::EntryId, entry_tag::Column::TagId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned();
+
+    txn.execute(txn.get_database_backend().build(&retag))
+        .await
+        .map_err(|e| format!("Failed to merge tags: {}", e))?;
+
+    EntryTag::delete_many()
+        .filter(entry_tag::Column::TagId.eq(from_tag_id))
+        .exec(&txn)
+        .await
+        .map_err(|e| format!("Failed to merge tags: {}", e))?;
+
+    Tag::delete_by_id(from_tag_id)
+        .exec(&txn)
+        .await
+        .map_err(|e| format!("Failed to delete merged tag: {}", e))?;
+
+    txn.commit().await.map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(into_tag.into())
+}
+
+// DELETE - Delete a tag and remove it from every entry
+#[tauri::command]
+pub async fn delete_tag(state: State<'_, AppState>, id: i32) -> Result<String, String> {
+    let db = &state.db().await;
+
+    let txn = db.begin().await.map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let untagged = EntryTag::delete_many()
+        .filter(entry_tag::Column::TagId.eq(id))
+        .exec(&txn)
+        .await
+        .map_err(|e| format!("Failed to untag entries: {}", e))?;
+
+    let result = Tag::delete_by_id(id)
+        .exec(&txn)
+        .await
+        .map_err(|e| format!("Failed to delete tag: {}", e))?;
+
+    if result.rows_affected == 0 {
+        return Err("Tag not found".to_string());
+    }
+
+    txn.commit().await.map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(format!("Tag with ID {} deleted from {} entries", id, untagged.rows_affected))
+}
+
+#[tauri::command]
+pub async fn get_classifier_settings(state: State<'_, AppState>) -> Result<ClassifierConfig, String> {
+    Ok(load_classifier_config(&state.db().await).await)
+}
+
+// Applies to entries added from the next fetch on; existing entries keep their tags
+#[tauri::command]
+pub async fn update_classifier_settings(
+    state: State<'_, AppState>,
+    settings: ClassifierConfig,
+) -> Result<ClassifierConfig, String> {
+    set_setting_value(&state.db().await, CLASSIFIER, &settings).await?;
+    Ok(settings)
+}
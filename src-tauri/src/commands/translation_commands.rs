@@ -0,0 +1,60 @@
+use sea_orm::*;
+use tauri::State;
+use crate::entities::prelude::*;
+use crate::models::{
+    AppState,
+    EntryTranslationResponse,
+    TranslationConfig,
+    effective_image_policy,
+    load_or_translate_entry,
+    load_translation_config,
+    set_setting_value,
+    TRANSLATION,
+};
+
+#[tauri::command]
+pub async fn get_translation_settings(state: State<'_, AppState>) -> Result<TranslationConfig, String> {
+    Ok(load_translation_config(&state.db().await).await)
+}
+
+// API keys are not part of the settings; they are saved with store_secret as
+// integration_api_key secrets named "deepl" or "libre_translate"
+#[tauri::command]
+pub async fn update_translation_settings(
+    state: State<'_, AppState>,
+    settings: TranslationConfig,
+) -> Result<TranslationConfig, String> {
+    set_setting_value(&state.db().await, TRANSLATION, &settings).await?;
+    Ok(settings)
+}
+
+// READ - Translate an entry into target_lang (e.g. "de", "pt-br") with the configured provider.
+// Translations are cached per entry and language until the entry's text changes.
+#[tauri::command]
+pub async fn translate_entry(
+    state: State<'_, AppState>,
+    entry_id: i32,
+    target_lang: String,
+) -> Result<EntryTranslationResponse, String> {
+    let db = &state.db().await;
+
+    let entry = FeedEntry::find_by_id(entry_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entry: {}", e))?
+        .ok_or("Feed entry not found")?;
+
+    let feed = Feed::find_by_id(entry.feed_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed: {}", e))?
+        .ok_or("Feed not found")?;
+
+    let default_image_policy = state.privacy_config.read().await.image_policy;
+    let image_policy = effective_image_policy(feed.image_policy.as_deref(), default_image_policy);
+    let config = load_translation_config(db).await;
+
+    let (translation, cached) = load_or_translate_entry(db, &config, &entry, &target_lang, image_policy).await?;
+
+    Ok(EntryTranslationResponse::from_model(translation, cached))
+}
@@ -0,0 +1,18 @@
+use std::sync::atomic::Ordering;
+use tauri::State;
+use crate::models::{AppState, set_setting_value, CLOSE_TO_TRAY};
+
+#[tauri::command]
+pub async fn get_close_to_tray(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.close_to_tray.load(Ordering::Relaxed))
+}
+
+// UPDATE - Whether closing the main window hides it to the tray, where feeds keep refreshing
+// in the background, instead of quitting
+#[tauri::command]
+pub async fn set_close_to_tray(state: State<'_, AppState>, enabled: bool) -> Result<bool, String> {
+    set_setting_value(&state.home_db, CLOSE_TO_TRAY, &enabled).await?;
+
+    state.close_to_tray.store(enabled, Ordering::Relaxed);
+    Ok(enabled)
+}
@@ -0,0 +1,130 @@
+use sea_orm::*;
+use tauri::State;
+use crate::entities::{prelude::*, *};
+use crate::models::{
+    AppState,
+    CreateWebhookRequest,
+    UpdateWebhookRequest,
+    WebhookDeliveryResponse,
+    WebhookResponse,
+    normalize_keyword,
+    parse_fetch_url,
+};
+
+fn validate_webhook(name: &str, url: &str) -> Result<(String, String), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Webhook name cannot be empty".to_string());
+    }
+    // Endpoints on this machine or the local network (e.g. a self-hosted n8n) are allowed
+    let url = parse_fetch_url(url)?;
+    Ok((name.to_string(), url.to_string()))
+}
+
+// CREATE - Add a webhook that is sent newly saved entries matching its filters
+#[tauri::command]
+pub async fn create_webhook(
+    state: State<'_, AppState>,
+    request: CreateWebhookRequest,
+) -> Result<WebhookResponse, String> {
+    let (name, url) = validate_webhook(&request.name, &request.url)?;
+
+    let now = chrono::Utc::now().naive_utc();
+    let new_webhook = webhook::ActiveModel {
+        name: ActiveValue::Set(name),
+        url: ActiveValue::Set(url),
+        feed_id: ActiveValue::Set(request.feed_id),
+        tag_id: ActiveValue::Set(request.tag_id),
+        keyword: ActiveValue::Set(normalize_keyword(request.keyword)),
+        enabled: ActiveValue::Set(request.enabled.unwrap_or(true)),
+        created_at: ActiveValue::Set(now),
+        updated_at: ActiveValue::Set(now),
+        ..Default::default()
+    };
+
+    let result = new_webhook
+        .insert(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to create webhook: {}", e))?;
+
+    Ok(result.into())
+}
+
+// READ - Get all webhooks
+#[tauri::command]
+pub async fn get_webhooks(state: State<'_, AppState>) -> Result<Vec<WebhookResponse>, String> {
+    let webhooks = Webhook::find()
+        .order_by_asc(webhook::Column::Name)
+        .all(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to fetch webhooks: {}", e))?;
+
+    Ok(webhooks.into_iter().map(|webhook| webhook.into()).collect())
+}
+
+// UPDATE - Replace a webhook's endpoint, filters and enabled state
+#[tauri::command]
+pub async fn update_webhook(
+    state: State<'_, AppState>,
+    request: UpdateWebhookRequest,
+) -> Result<WebhookResponse, String> {
+    let db = &state.db().await;
+
+    let (name, url) = validate_webhook(&request.name, &request.url)?;
+
+    let existing_webhook = Webhook::find_by_id(request.id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch webhook: {}", e))?
+        .ok_or("Webhook not found")?;
+
+    let mut updated_webhook: webhook::ActiveModel = existing_webhook.into();
+    updated_webhook.name = ActiveValue::Set(name);
+    updated_webhook.url = ActiveValue::Set(url);
+    updated_webhook.feed_id = ActiveValue::Set(request.feed_id);
+    updated_webhook.tag_id = ActiveValue::Set(request.tag_id);
+    updated_webhook.keyword = ActiveValue::Set(normalize_keyword(request.keyword));
+    updated_webhook.enabled = ActiveValue::Set(request.enabled);
+    updated_webhook.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+    let result = updated_webhook
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to update webhook: {}", e))?;
+
+    Ok(result.into())
+}
+
+// DELETE - Delete a webhook and its delivery log
+#[tauri::command]
+pub async fn delete_webhook(state: State<'_, AppState>, id: i32) -> Result<String, String> {
+    let result = Webhook::delete_by_id(id)
+        .exec(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to delete webhook: {}", e))?;
+
+    if result.rows_affected == 0 {
+        return Err("Webhook not found".to_string());
+    }
+
+    Ok(format!("Webhook with ID {} deleted successfully", id))
+}
+
+// READ - A webhook's delivery log, newest first
+#[tauri::command]
+pub async fn get_webhook_deliveries(
+    state: State<'_, AppState>,
+    webhook_id: i32,
+    limit: Option<u64>,
+) -> Result<Vec<WebhookDeliveryResponse>, String> {
+    let deliveries = WebhookDelivery::find()
+        .filter(webhook_delivery::Column::WebhookId.eq(webhook_id))
+        .order_by_desc(webhook_delivery::Column::CreatedAt)
+        .order_by_desc(webhook_delivery::Column::Id)
+        .limit(limit.unwrap_or(50))
+        .all(&state.db().await)
+        .await
+        .map_err(|e| format!("Failed to fetch webhook deliveries: {}", e))?;
+
+    Ok(deliveries.into_iter().map(|delivery| delivery.into()).collect())
+}
@@ -15,12 +15,76 @@ pub struct Model {
     pub created_at: DateTime,
     pub updated_at: DateTime,
     pub last_fetched_at: Option<DateTime>,
+    pub folder_id: Option<i32>,
+    pub image_policy: Option<String>,
+    pub fetch_interval_minutes: Option<i32>,
+    pub next_fetch_at: Option<DateTime>,
+    pub ttl_minutes: Option<i32>,
+    pub skip_hours: Option<String>,
+    pub skip_days: Option<String>,
+    pub mark_read_on_scroll: Option<bool>,
+    pub auto_title: bool,
+    pub source_type: String,
+    pub resolve_canonical_links: bool,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub icon: Option<String>,
+    pub format: Option<String>,
+    pub content_hash: Option<String>,
+    pub max_entries: Option<i32>,
+    pub fetch_full_content: bool,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub extraction_rule: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub tls_ca_certificate: Option<String>,
+    pub tls_pinned_sha256: Option<String>,
+    pub request_timeout_seconds: Option<i32>,
+    pub max_retries: Option<i32>,
+    pub rate_limit_delay_ms: Option<i32>,
+    pub hide_read_after_days: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub preferences: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::alert_rule::Entity")]
+    AlertRule,
+    #[sea_orm(has_many = "super::bandwidth_usage::Entity")]
+    BandwidthUsage,
+    #[sea_orm(has_many = "super::entry_archive::Entity")]
+    EntryArchive,
     #[sea_orm(has_many = "super::feed_entry::Entity")]
     FeedEntry,
+    #[sea_orm(has_many = "super::fetch_log::Entity")]
+    FetchLog,
+    #[sea_orm(
+        belongs_to = "super::folder::Entity",
+        from = "Column::FolderId",
+        to = "super::folder::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    Folder,
+    #[sea_orm(has_many = "super::webhook::Entity")]
+    Webhook,
+}
+
+impl Related<super::alert_rule::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AlertRule.def()
+    }
+}
+
+impl Related<super::bandwidth_usage::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BandwidthUsage.def()
+    }
+}
+
+impl Related<super::entry_archive::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::EntryArchive.def()
+    }
 }
 
 impl Related<super::feed_entry::Entity> for Entity {
@@ -29,4 +93,22 @@ impl Related<super::feed_entry::Entity> for Entity {
     }
 }
 
+impl Related<super::fetch_log::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::FetchLog.def()
+    }
+}
+
+impl Related<super::folder::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Folder.def()
+    }
+}
+
+impl Related<super::webhook::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Webhook.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -11,7 +11,6 @@ pub struct Model {
     pub title: String,
     #[sea_orm(column_type = "Text", nullable)]
     pub description: Option<String>,
-    #[sea_orm(unique)]
     pub link: String,
     #[sea_orm(column_type = "Text", nullable)]
     pub content: Option<String>,
@@ -20,10 +19,47 @@ pub struct Model {
     pub updated_at: DateTime,
     pub is_read: bool,
     pub is_starred: bool,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub enclosure_url: Option<String>,
+    pub enclosure_type: Option<String>,
+    pub enclosure_length: Option<i64>,
+    pub duration_seconds: Option<i32>,
+    pub snoozed_until: Option<DateTime>,
+    #[sea_orm(column_type = "Text")]
+    pub guid: String,
+    pub updated_at_source: Option<DateTime>,
+    pub is_updated: bool,
+    pub episode_number: Option<i32>,
+    pub season_number: Option<i32>,
+    pub is_explicit: Option<bool>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub artwork_url: Option<String>,
+    pub word_count: Option<i32>,
+    pub reading_time_minutes: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub dedup_key: Option<String>,
+    pub story_cluster_id: Option<i32>,
+    pub is_hidden: bool,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub canonical_link: Option<String>,
+    pub open_count: i32,
+    pub last_opened_at: Option<DateTime>,
+    pub read_at: Option<DateTime>,
+    pub starred_at: Option<DateTime>,
+    pub event_starts_at: Option<DateTime>,
+    pub event_ends_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::alert::Entity")]
+    Alert,
+    #[sea_orm(has_many = "super::annotation::Entity")]
+    Annotation,
+    #[sea_orm(has_many = "super::entry_tag::Entity")]
+    EntryTag,
+    #[sea_orm(has_many = "super::entry_translation::Entity")]
+    EntryTranslation,
     #[sea_orm(
         belongs_to = "super::feed::Entity",
         from = "Column::FeedId",
@@ -32,6 +68,36 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Feed,
+    #[sea_orm(has_one = "super::playback_state::Entity")]
+    PlaybackState,
+    #[sea_orm(has_many = "super::share_history::Entity")]
+    ShareHistory,
+    #[sea_orm(has_many = "super::state_log::Entity")]
+    StateLog,
+}
+
+impl Related<super::alert::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Alert.def()
+    }
+}
+
+impl Related<super::annotation::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Annotation.def()
+    }
+}
+
+impl Related<super::entry_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::EntryTag.def()
+    }
+}
+
+impl Related<super::entry_translation::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::EntryTranslation.def()
+    }
 }
 
 impl Related<super::feed::Entity> for Entity {
@@ -40,4 +106,31 @@ impl Related<super::feed::Entity> for Entity {
     }
 }
 
+impl Related<super::playback_state::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PlaybackState.def()
+    }
+}
+
+impl Related<super::share_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ShareHistory.def()
+    }
+}
+
+impl Related<super::state_log::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StateLog.def()
+    }
+}
+
+impl Related<super::tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::entry_tag::Relation::Tag.def()
+    }
+    fn via() -> Option<RelationDef> {
+        Some(super::entry_tag::Relation::FeedEntry.def().rev())
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
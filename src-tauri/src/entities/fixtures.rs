@@ -0,0 +1,94 @@
+//! Models for unit tests, with every column at the value a new row gets. Tests spell out only
+//! the columns they're about, e.g. `feed::Model { folder_id: Some(2), ..fixtures::feed(1) }`.
+
+use chrono::NaiveDateTime;
+use super::{feed, feed_entry};
+
+// A time written as "2024-06-01 08:00"
+pub fn at(s: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+}
+
+// When fixture rows were created
+pub fn created_at() -> NaiveDateTime {
+    at("2024-06-01 08:00")
+}
+
+// An HTTP feed at https://example.com/{id}.xml
+pub fn feed(id: i32) -> feed::Model {
+    feed::Model {
+        id,
+        url: format!("https://example.com/{}.xml", id),
+        title: None,
+        description: None,
+        created_at: created_at(),
+        updated_at: created_at(),
+        last_fetched_at: None,
+        folder_id: None,
+        image_policy: None,
+        fetch_interval_minutes: None,
+        next_fetch_at: None,
+        ttl_minutes: None,
+        skip_hours: None,
+        skip_days: None,
+        mark_read_on_scroll: None,
+        auto_title: false,
+        source_type: "http".to_string(),
+        resolve_canonical_links: false,
+        icon: None,
+        format: None,
+        content_hash: None,
+        max_entries: None,
+        fetch_full_content: false,
+        extraction_rule: None,
+        tls_ca_certificate: None,
+        tls_pinned_sha256: None,
+        request_timeout_seconds: None,
+        max_retries: None,
+        rate_limit_delay_ms: None,
+        hide_read_after_days: None,
+        preferences: None,
+    }
+}
+
+// An unread entry of a feed, linking to https://example.com/{id}
+pub fn feed_entry(id: i32, feed_id: i32) -> feed_entry::Model {
+    let link = format!("https://example.com/{}", id);
+    feed_entry::Model {
+        id,
+        feed_id,
+        title: format!("Entry {}", id),
+        description: None,
+        link: link.clone(),
+        content: None,
+        published_at: None,
+        created_at: created_at(),
+        updated_at: created_at(),
+        is_read: false,
+        is_starred: false,
+        enclosure_url: None,
+        enclosure_type: None,
+        enclosure_length: None,
+        duration_seconds: None,
+        snoozed_until: None,
+        guid: link,
+        updated_at_source: None,
+        is_updated: false,
+        episode_number: None,
+        season_number: None,
+        is_explicit: None,
+        artwork_url: None,
+        word_count: None,
+        reading_time_minutes: None,
+        dedup_key: None,
+        story_cluster_id: None,
+        is_hidden: false,
+        canonical_link: None,
+        open_count: 0,
+        last_opened_at: None,
+        read_at: None,
+        starred_at: None,
+        event_starts_at: None,
+        event_ends_at: None,
+    }
+}
@@ -2,5 +2,27 @@
 
 pub mod prelude;
 
+pub mod alert;
+pub mod alert_rule;
+pub mod annotation;
+pub mod bandwidth_usage;
+pub mod digest;
+pub mod entry_translation;
+pub mod entry_archive;
+pub mod entry_snapshot;
+pub mod entry_tag;
 pub mod feed;
 pub mod feed_entry;
+pub mod fetch_log;
+#[cfg(test)]
+pub mod fixtures;
+pub mod folder;
+pub mod playback_state;
+pub mod profile;
+pub mod refresh_history;
+pub mod setting;
+pub mod share_history;
+pub mod state_log;
+pub mod tag;
+pub mod webhook;
+pub mod webhook_delivery;
@@ -1,4 +1,24 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.12
 
+pub use super::alert::Entity as Alert;
+pub use super::alert_rule::Entity as AlertRule;
+pub use super::annotation::Entity as Annotation;
+pub use super::bandwidth_usage::Entity as BandwidthUsage;
+pub use super::digest::Entity as Digest;
+pub use super::entry_archive::Entity as EntryArchive;
+pub use super::entry_snapshot::Entity as EntrySnapshot;
+pub use super::entry_tag::Entity as EntryTag;
+pub use super::entry_translation::Entity as EntryTranslation;
 pub use super::feed::Entity as Feed;
 pub use super::feed_entry::Entity as FeedEntry;
+pub use super::fetch_log::Entity as FetchLog;
+pub use super::folder::Entity as Folder;
+pub use super::playback_state::Entity as PlaybackState;
+pub use super::profile::Entity as Profile;
+pub use super::refresh_history::Entity as RefreshHistory;
+pub use super::setting::Entity as Setting;
+pub use super::share_history::Entity as ShareHistory;
+pub use super::state_log::Entity as StateLog;
+pub use super::tag::Entity as Tag;
+pub use super::webhook::Entity as Webhook;
+pub use super::webhook_delivery::Entity as WebhookDelivery;
@@ -1,49 +1,282 @@
-use sea_orm::*;
+use std::collections::HashMap;
 use std::env;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::Manager;
+use tokio::sync::broadcast;
+use tokio::sync::{watch, RwLock};
 
 mod entities;
 mod models;
 mod commands;
 
-use models::{AppState, AsyncFeedFetcher, FetcherConfig};
+use models::{AppState, AsyncFeedFetcher, DownloadManager, FeedServer, NotificationOutbox, OperationRegistry, SubscribeEndpoint, FeedRefreshStatus, RefreshSummary, SchedulerConfig, SchedulerStatus, StartupState, StartupStatus, SyncEngine, DEFAULT_PROFILE, IMAGE_PROXY_SCHEME, MAIN_WINDOW, TRAY_ID, handle_image_proxy_request, configured_database_url, open_home_database, load_close_to_tray, load_fetcher_config, load_metered_mode, load_notification_config, load_privacy_config, load_republish_config, load_scheduler_config, load_subscribe_endpoint_config, handle_exit_requested, open_profile_database, parse_open_request, queue_open_requests, run_alert_notifier, run_fetch_result_events, run_local_feed_watcher, run_refresh_history_recorder, run_notification_outbox, run_refresh_summary_notifier, run_scheduler, run_tray_updater, show_main_window, startup_profile_name};
 use commands::*;
 
-async fn setup_database() -> Result<DatabaseConnection, DbErr> {
-    dotenv::dotenv().ok();
+// Without a database only the startup commands are served, so the frontend can show its setup
+// screen and retry; a successful retry restarts the app into the library
+fn run_setup(status: StartupStatus, context: tauri::Context<tauri::Wry>) -> tauri::Result<()> {
+    tauri::Builder::default()
+        .manage(StartupState::new(status))
+        .plugin(tauri_plugin_opener::init())
+        .invoke_handler(tauri::generate_handler![get_startup_status, retry_startup])
+        .run(context)
+}
+
+// Tray icon showing the unread count, with quick actions that keep working while the main
+// window is closed to the tray
+fn setup_tray(app: &tauri::App, refresh_summaries: broadcast::Receiver<RefreshSummary>) -> tauri::Result<()> {
+    let refresh_now = MenuItem::with_id(app, "refresh_now", "Refresh now", true, None::<&str>)?;
+    let pause_fetching = CheckMenuItem::with_id(app, "pause_fetching", "Pause fetching", true, false, None::<&str>)?;
+    let open_reader = MenuItem::with_id(app, "open_reader", "Open reader", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[&refresh_now, &pause_fetching, &open_reader, &PredefinedMenuItem::separator(app)?, &quit],
+    )?;
+
+    let pause_item = pause_fetching.clone();
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(move |app, event| match event.id().as_ref() {
+            "refresh_now" => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = refresh_all_feeds(app.clone(), app.state(), None).await {
+                        eprintln!("❌ {}", e);
+                    }
+                });
+            }
+            "pause_fetching" => {
+                let app = app.clone();
+                let pause_item = pause_item.clone();
+                tauri::async_runtime::spawn(async move {
+                    let paused = app.state::<AppState>().async_fetcher.as_ref().is_some_and(|fetcher| fetcher.is_paused());
+                    let result = if paused {
+                        resume_fetch_queue(app.state()).await
+                    } else {
+                        pause_fetch_queue(app.state()).await
+                    };
+                    match result {
+                        Ok(_) => {
+                            let _ = pause_item.set_checked(!paused);
+                        }
+                        Err(e) => {
+                            eprintln!("❌ {}", e);
+                            let _ = pause_item.set_checked(paused);
+                        }
+                    }
+                });
+            }
+            "open_reader" => show_main_window(app),
+            "quit" => app.exit(0),
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    let tray = builder.build(app)?;
+
+    tauri::async_runtime::spawn(run_tray_updater(app.handle().clone(), tray, pause_fetching, refresh_summaries));
+    Ok(())
+}
+
+// Everything opened before the window: the main database, the active profile's library and
+// the settings read from them
+pub struct Startup {
+    app_state: AppState,
+    startup_status: StartupStatus,
+    refresh_summaries: broadcast::Receiver<RefreshSummary>,
+    tray_refresh_summaries: broadcast::Receiver<RefreshSummary>,
+    alert_refresh_summaries: broadcast::Receiver<RefreshSummary>,
+    fetch_results: broadcast::Receiver<FeedRefreshStatus>,
+    history_refresh_summaries: broadcast::Receiver<RefreshSummary>,
+    scheduler_config: Arc<RwLock<SchedulerConfig>>,
+    shutdown_receiver: watch::Receiver<bool>,
+}
+
+// Connect to the databases and load the settings once, before the window is created. Without
+// the main database, the status to show the setup screen with instead.
+pub async fn prepare_startup() -> Result<Startup, StartupStatus> {
+    let database_url = configured_database_url().await;
+    let home_db = open_home_database(database_url.as_deref()).await?;
+    let startup_status = StartupStatus::ready(database_url.as_deref().unwrap_or_default());
+    
+    // Open the last used profile's library, falling back to the main database
+    let mut active_profile = startup_profile_name(&home_db).await;
+    let db = match open_profile_database(&home_db, &active_profile).await {
+        Ok(db) => db,
+        Err(e) => {
+            eprintln!("❌ Failed to open profile {}: {}", active_profile, e);
+            active_profile = DEFAULT_PROFILE.to_string();
+            home_db.clone()
+        }
+    };
+    
+    // Initialize async feed fetcher with database connection for automatic integration
+    let fetcher_config = load_fetcher_config(&db).await;
+    let db_arc = Arc::new(db.clone());
+    let async_fetcher = AsyncFeedFetcher::new_with_db(fetcher_config, Some(db_arc));
+    let refresh_summaries = async_fetcher.subscribe_refresh_summaries();
+    let tray_refresh_summaries = async_fetcher.subscribe_refresh_summaries();
+    let alert_refresh_summaries = async_fetcher.subscribe_refresh_summaries();
+    let fetch_results = async_fetcher.subscribe_feed_statuses();
+    let history_refresh_summaries = async_fetcher.subscribe_refresh_summaries();
+    let notification_config = Arc::new(RwLock::new(load_notification_config(&db).await));
+    let scheduler_config = Arc::new(RwLock::new(load_scheduler_config(&db).await));
+    let privacy_config = async_fetcher.privacy_config();
+    *privacy_config.write().await = load_privacy_config(&db).await;
+    let metered_mode = async_fetcher.metered_mode();
+    *metered_mode.write().await = load_metered_mode(&home_db).await;
+    if let Err(e) = async_fetcher.load_feed_overrides(&db).await {
+        eprintln!("❌ Failed to load per-feed fetch settings: {}", e);
+    }
+    let close_to_tray = load_close_to_tray(&home_db).await;
     
-    let database_url = env::var("DATABASE_URL")
-        .expect("DATABASE_URL must be set in environment variables or .env file");
+    let (shutdown_signal, shutdown_receiver) = watch::channel(false);
     
-    let db = Database::connect(&database_url).await?;
-    Ok(db)
+    let app_state = AppState {
+        db: RwLock::new(db),
+        home_db,
+        active_profile: RwLock::new(active_profile),
+        async_fetcher: Some(async_fetcher),
+        notification_config: notification_config.clone(),
+        notifications: NotificationOutbox::new(notification_config),
+        scheduler_config: scheduler_config.clone(),
+        scheduler_status: RwLock::new(SchedulerStatus::default()),
+        privacy_config,
+        metered_mode,
+        close_to_tray: AtomicBool::new(close_to_tray),
+        operations: OperationRegistry::default(),
+        import_operations: Arc::new(RwLock::new(HashMap::new())),
+        last_bulk_action: RwLock::new(None),
+        pending_open_requests: Mutex::new(Vec::new()),
+        downloads: DownloadManager::new(),
+        feed_server: FeedServer::new(),
+        subscribe_endpoint: SubscribeEndpoint::new(),
+        sync_engine: SyncEngine::new(),
+        shutdown_signal,
+    };
+
+    Ok(Startup {
+        app_state,
+        startup_status,
+        refresh_summaries,
+        tray_refresh_summaries,
+        alert_refresh_summaries,
+        fetch_results,
+        history_refresh_summaries,
+        scheduler_config,
+        shutdown_receiver,
+    })
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::async_runtime::block_on(async {
-        let db = setup_database().await.expect("Failed to setup database");
-        
-        // Initialize async feed fetcher with database connection for automatic integration
-        let fetcher_config = FetcherConfig::default();
-        let db_arc = Arc::new(db.clone());
-        let async_fetcher = AsyncFeedFetcher::new_with_db(fetcher_config, Some(db_arc));
-        
-        let app_state = AppState { 
-            db,
-            async_fetcher: Some(async_fetcher),
-        };
-
-        tauri::Builder::default()
+// Run the app with what prepare_startup opened, or in setup mode if it couldn't
+pub fn run_with(startup: Result<Startup, StartupStatus>) -> tauri::Result<()> {
+    let context = tauri::generate_context!();
+    match startup {
+        Err(status) => {
+            eprintln!("❌ Starting without a database: {}", status.error.as_deref().unwrap_or_default());
+            run_setup(status, context)
+        }
+        Ok(Startup {
+            app_state,
+            startup_status,
+            refresh_summaries,
+            tray_refresh_summaries,
+            alert_refresh_summaries,
+            fetch_results,
+            history_refresh_summaries,
+            scheduler_config,
+            shutdown_receiver,
+        }) => tauri::Builder::default()
             .manage(app_state)
+            .manage(StartupState::new(startup_status))
             .plugin(tauri_plugin_opener::init())
             .plugin(tauri_plugin_http::init())
+            .plugin(tauri_plugin_notification::init())
+            // Images in entries using the proxy image policy are served from the local cache
+            .register_asynchronous_uri_scheme_protocol(IMAGE_PROXY_SCHEME, |ctx, request, responder| {
+                handle_image_proxy_request(ctx.app_handle().clone(), request, responder);
+            })
+            // Closing the main window hides it to the tray, so scheduled refreshes keep running
+            .on_window_event(|window, event| {
+                if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                    if window.label() == MAIN_WINDOW && window.state::<AppState>().close_to_tray.load(Ordering::Relaxed) {
+                        api.prevent_close();
+                        let _ = window.hide();
+                    }
+                }
+            })
+            .setup(move |app| {
+                setup_tray(app, tray_refresh_summaries)?;
+
+                // Windows and Linux pass feed:// links and OPML files to open as arguments
+                let requests = env::args().skip(1).filter_map(|argument| parse_open_request(&argument)).collect();
+                queue_open_requests(app.handle(), requests);
+
+                // Show desktop notifications for completed refreshes, and anything held back by
+                // quiet hours, the hourly limit or batching once it can be shown
+                tauri::async_runtime::spawn(run_refresh_summary_notifier(app.handle().clone(), refresh_summaries));
+                tauri::async_runtime::spawn(run_notification_outbox(app.handle().clone()));
+
+                // Notify about entries matching alert rules once each refresh has saved them
+                tauri::async_runtime::spawn(run_alert_notifier(app.handle().clone(), alert_refresh_summaries));
+
+                // Tell the frontend about each feed as it is fetched and saved
+                tauri::async_runtime::spawn(run_fetch_result_events(app.handle().clone(), fetch_results));
+
+                // Keep every finished refresh in the refresh history
+                tauri::async_runtime::spawn(run_refresh_history_recorder(app.handle().clone(), history_refresh_summaries));
+
+                // Refresh feeds in the background on their adaptive schedule
+                tauri::async_runtime::spawn(run_scheduler(app.handle().clone(), scheduler_config, shutdown_receiver));
+
+                // Refresh local file and Markdown directory feeds when their files change
+                tauri::async_runtime::spawn(run_local_feed_watcher(app.handle().clone()));
+
+                // Serve republished feeds, and accept subscriptions from the browser extension,
+                // if they were left on
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let state = app_handle.state::<AppState>();
+                    let config = load_republish_config(&state.db().await).await;
+                    if let Err(e) = state.feed_server.apply(&app_handle, &config).await {
+                        eprintln!("❌ {}", e);
+                    }
+                    let config = load_subscribe_endpoint_config(&state.home_db).await;
+                    if let Err(e) = state.subscribe_endpoint.apply(&app_handle, &config).await {
+                        eprintln!("❌ {}", e);
+                    }
+                });
+                Ok(())
+            })
             .invoke_handler(tauri::generate_handler![
                 greet,
+                // Startup commands
+                get_startup_status,
+                retry_startup,
                 create_feed,
+                subscribe_github_repository,
                 get_all_feeds,
                 get_feed_by_id,
                 get_feed_by_url,
+                get_feed_stats,
+                get_most_opened_feeds,
+                get_feed_health,
+                get_feed_recommendations,
+                test_extraction_rule,
+                fetch_feed_full_content,
+                set_feed_fetch_overrides,
+                set_feed_preferences,
                 update_feed,
                 update_feed_last_fetched,
                 delete_feed,
@@ -51,26 +284,262 @@ pub fn run() {
                 parse_feed_content_command,
                 start_async_fetcher,
                 stop_async_fetcher,
+                pause_fetch_queue,
+                resume_fetch_queue,
                 get_async_fetcher_status,
+                get_circuit_breaker_status,
+                get_rate_limiter_stats,
+                get_queue_status,
+                reset_circuit_breaker,
+                get_fetch_metrics,
+                export_fetch_metrics_prometheus,
                 queue_feed_for_async_fetch,
                 get_async_fetch_results,
                 fetch_multiple_feeds_async,
                 // Refresh commands
                 refresh_all_feeds,
                 refresh_single_feed,
+                refresh_folder,
                 get_refresh_progress,
                 get_last_refresh_summary,
+                get_refresh_history,
                 // Feed Entry commands
                 create_feed_entry,
                 create_feed_with_entries,
                 get_feed_entries,
+                get_media_entries,
+                query_entries,
+                query_entries_advanced,
+                get_timeline,
+                get_recently_updated_entries,
+                get_recently_read,
+                get_related_entries,
                 get_feed_entry_by_id,
+                resolve_canonical_link,
                 update_feed_entry,
                 delete_feed_entry,
                 mark_entry_as_read,
-                mark_entry_as_starred
+                open_entry_link,
+                mark_entry_as_starred,
+                acknowledge_entry_update,
+                snooze_entry,
+                bulk_update_entries,
+                undo_last_bulk_action,
+                // Notification commands
+                get_notification_settings,
+                update_notification_settings,
+                // Folder commands
+                create_folder,
+                get_all_folders,
+                update_folder,
+                delete_folder,
+                move_feed_to_folder,
+                move_folder,
+                get_folder_unread_counts,
+                // Import commands
+                import_from,
+                export_opml,
+                export_feed_bundle,
+                import_feed_bundle,
+                export_entries_markdown,
+                export_atom_feed,
+                get_republish_settings,
+                update_republish_settings,
+                import_and_fetch,
+                get_import_progress,
+                cancel_import,
+                get_available_packs,
+                install_pack,
+                // Settings commands
+                get_setting,
+                set_setting,
+                get_all_settings,
+                // Scheduler commands
+                get_scheduler_settings,
+                update_scheduler_settings,
+                // Tag commands
+                create_tag,
+                get_all_tags,
+                get_entry_tags,
+                add_tag_to_entry,
+                remove_tag_from_entry,
+                get_tags_with_counts,
+                rename_tag,
+                merge_tags,
+                delete_tag,
+                get_classifier_settings,
+                update_classifier_settings,
+                // Data directory commands
+                get_data_directory,
+                move_data_directory,
+                // Reading commands
+                get_reading_settings,
+                update_reading_settings,
+                set_feed_mark_read_on_scroll,
+                update_read_progress,
+                // Download commands
+                download_enclosure,
+                cancel_download,
+                get_downloads,
+                // Playback commands
+                save_playback_position,
+                get_playback_position,
+                // Annotation commands
+                create_annotation,
+                get_entry_annotations,
+                update_annotation,
+                delete_annotation,
+                // Profile commands
+                create_profile,
+                get_profiles,
+                switch_profile,
+                delete_profile,
+                // Secret commands
+                store_secret,
+                delete_secret,
+                // Privacy commands
+                get_privacy_settings,
+                update_privacy_settings,
+                set_feed_image_policy,
+                // Bandwidth commands
+                get_bandwidth_usage,
+                get_metered_mode,
+                set_metered_mode,
+                // Translation commands
+                get_translation_settings,
+                update_translation_settings,
+                translate_entry,
+                // Digest commands
+                get_digest_settings,
+                update_digest_settings,
+                generate_digest,
+                get_digests,
+                export_digest,
+                delete_digest,
+                get_email_settings,
+                update_email_settings,
+                send_digest_email,
+                // Webhook commands
+                create_webhook,
+                get_webhooks,
+                update_webhook,
+                delete_webhook,
+                get_webhook_deliveries,
+                // Alert commands
+                create_alert_rule,
+                get_alert_rules,
+                update_alert_rule,
+                snooze_alert_rule,
+                delete_alert_rule,
+                get_alerts,
+                get_unread_alert_count,
+                mark_alerts_read,
+                delete_alerts,
+                // Sync commands
+                get_sync_status,
+                update_sync_settings,
+                sync_now,
+                // Social sharing commands
+                get_social_settings,
+                start_mastodon_auth,
+                finish_mastodon_auth,
+                connect_bluesky_account,
+                disconnect_social_account,
+                share_entry,
+                format_entry_for_sharing,
+                get_share_history,
+                // Domain rule commands
+                get_domain_rules,
+                update_domain_rules,
+                block_domain,
+                unblock_domain,
+                apply_domain_rules,
+                // Mute commands
+                get_mutes,
+                mute_keyword,
+                unmute_keyword,
+                // Retention commands
+                get_retention_settings,
+                update_retention_settings,
+                set_feed_max_entries,
+                // Display policy commands
+                get_display_policy,
+                update_display_policy,
+                set_feed_hide_read_after_days,
+                // Archive commands
+                get_archive_settings,
+                update_archive_settings,
+                archive_old_entries_now,
+                search_archive,
+                // Snapshot commands
+                get_snapshot,
+                get_snapshot_storage_usage,
+                // Backup commands
+                get_backup_settings,
+                update_backup_settings,
+                back_up_now,
+                get_backup_status,
+                // Search commands
+                search_suggestions,
+                // Cookie commands
+                import_feed_cookies,
+                open_feed_login,
+                finish_feed_login,
+                clear_feed_cookies,
+                // Operation commands
+                list_operations,
+                cancel_operation,
+                // Integrity commands
+                check_integrity,
+                compact_entry_state_log,
+                // Health commands
+                get_health,
+                // Subscribe endpoint commands
+                get_subscribe_endpoint_settings,
+                update_subscribe_endpoint_settings,
+                get_subscribe_endpoint_token,
+                regenerate_subscribe_endpoint_token,
+                // Open request commands
+                take_pending_open_requests,
+                // Tray commands
+                get_close_to_tray,
+                set_close_to_tray,
+                // Debug commands (fault-injection builds only)
+                #[cfg(feature = "fault-injection")]
+                debug_set_fault_rule,
+                #[cfg(feature = "fault-injection")]
+                debug_get_fault_rules,
+                #[cfg(feature = "fault-injection")]
+                debug_clear_fault_rules
             ])
-            .run(tauri::generate_context!())
-            .expect("error while running tauri application");
-    });
+            .build(context)
+            .map(|app| app.run(handle_run_event)),
+    }
+}
+
+fn handle_run_event(app: &tauri::AppHandle, event: tauri::RunEvent) {
+    match event {
+        // Let background work finish cleanly before quitting
+        tauri::RunEvent::ExitRequested { api, code, .. } => handle_exit_requested(app, &api, code),
+        // macOS hands over feed:// links and OPML files as open events instead of arguments
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        tauri::RunEvent::Opened { urls } => {
+            let requests = urls.iter().filter_map(|url| parse_open_request(url.as_str())).collect();
+            queue_open_requests(app, requests);
+        }
+        _ => {}
+    }
+}
+
+// Mobile builds start here instead of main, so they own the runtime the same way
+#[cfg(mobile)]
+#[tauri::mobile_entry_point]
+pub fn run() {
+    let runtime = tokio::runtime::Runtime::new().expect("failed to start the async runtime");
+    tauri::async_runtime::set(runtime.handle().clone());
+    let startup = runtime.block_on(prepare_startup());
+    if let Err(e) = run_with(startup) {
+        eprintln!("❌ Failed to run the app: {}", e);
+        std::process::exit(1);
+    }
 }
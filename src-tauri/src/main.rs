@@ -2,5 +2,12 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
-    reader_lib::run()
+    // The databases are opened on this runtime before the window, and tauri runs on it after
+    let runtime = tokio::runtime::Runtime::new().expect("failed to start the async runtime");
+    tauri::async_runtime::set(runtime.handle().clone());
+    let startup = runtime.block_on(reader_lib::prepare_startup());
+    if let Err(e) = reader_lib::run_with(startup) {
+        eprintln!("❌ Failed to run the app: {}", e);
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+use regex::{Regex, RegexBuilder};
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::*;
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast;
+use crate::entities::{prelude::*, *};
+use crate::models::folder_tree::self_and_descendants;
+use crate::models::notifications::{build_alert_notification, Notification};
+use crate::models::reading::html_to_text;
+use crate::models::responses::RefreshSummary;
+use crate::models::state::AppState;
+
+// Keeps a pathological pattern from the setup screen from eating memory on every refresh
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+// An alert rule's pattern, compiled once per batch of entries
+#[derive(Debug)]
+pub enum AlertMatcher {
+    // Lowercased, matched anywhere in the text
+    Keyword(String),
+    Regex(Regex),
+}
+
+impl AlertMatcher {
+    pub fn new(pattern: &str, is_regex: bool) -> Result<Self, String> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return Err("Alert pattern cannot be empty".to_string());
+        }
+        if !is_regex {
+            return Ok(AlertMatcher::Keyword(pattern.to_lowercase()));
+        }
+        RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .size_limit(REGEX_SIZE_LIMIT)
+            .build()
+            .map(AlertMatcher::Regex)
+            .map_err(|e| format!("Invalid regular expression: {}", e))
+    }
+
+    // Matched against the entry's title and its description without markup
+    pub fn matches_entry(&self, entry: &feed_entry::Model) -> bool {
+        let description = entry.description.as_deref().map(html_to_text).unwrap_or_default();
+        match self {
+            AlertMatcher::Keyword(keyword) => {
+                entry.title.to_lowercase().contains(keyword) || description.to_lowercase().contains(keyword)
+            }
+            AlertMatcher::Regex(regex) => regex.is_match(&entry.title) || regex.is_match(&description),
+        }
+    }
+}
+
+// Whether a rule watches a feed: its feed if it has one, and a feed anywhere in its folder's
+// subtree if it has a folder
+pub fn rule_watches_feed(rule: &alert_rule::Model, feed: &feed::Model, folders: &[folder::Model]) -> bool {
+    if rule.feed_id.is_some_and(|feed_id| feed_id != feed.id) {
+        return false;
+    }
+    match rule.folder_id {
+        Some(folder_id) => feed
+            .folder_id
+            .is_some_and(|feed_folder_id| self_and_descendants(folders, folder_id).contains(&feed_folder_id)),
+        None => true,
+    }
+}
+
+pub fn is_snoozed(rule: &alert_rule::Model, now: chrono::NaiveDateTime) -> bool {
+    rule.snoozed_until.is_some_and(|until| until > now)
+}
+
+// Add a feed's newly saved entries to the alerts inbox for every enabled rule they match.
+// Matches of snoozed rules are kept without a notification. Returns how many alerts were added.
+pub async fn record_alerts<C: ConnectionTrait>(
+    db: &C,
+    feed: &feed::Model,
+    new_entries: &[feed_entry::Model],
+) -> Result<usize, String> {
+    // Entries hidden by domain rules or mutes don't alert
+    let new_entries: Vec<&feed_entry::Model> = new_entries.iter().filter(|entry| !entry.is_hidden).collect();
+    if new_entries.is_empty() {
+        return Ok(0);
+    }
+    let rules = AlertRule::find()
+        .filter(alert_rule::Column::Enabled.eq(true))
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch alert rules: {}", e))?;
+    if rules.is_empty() {
+        return Ok(0);
+    }
+    let folders = if rules.iter().any(|rule| rule.folder_id.is_some()) {
+        Folder::find()
+            .all(db)
+            .await
+            .map_err(|e| format!("Failed to fetch folders: {}", e))?
+    } else {
+        Vec::new()
+    };
+
+    let now = chrono::Utc::now().naive_utc();
+    let mut new_alerts = Vec::new();
+    for rule in rules.iter().filter(|rule| rule_watches_feed(rule, feed, &folders)) {
+        let matcher = match AlertMatcher::new(&rule.pattern, rule.is_regex) {
+            Ok(matcher) => matcher,
+            Err(e) => {
+                eprintln!("❌ Skipping alert rule {}: {}", rule.name, e);
+                continue;
+            }
+        };
+        let notification_pending = !is_snoozed(rule, now);
+        new_alerts.extend(new_entries.iter().filter(|entry| matcher.matches_entry(entry)).map(|entry| {
+            alert::ActiveModel {
+                alert_rule_id: ActiveValue::Set(rule.id),
+                entry_id: ActiveValue::Set(entry.id),
+                is_read: ActiveValue::Set(false),
+                notification_pending: ActiveValue::Set(notification_pending),
+                created_at: ActiveValue::Set(now),
+                ..Default::default()
+            }
+        }));
+    }
+    if new_alerts.is_empty() {
+        return Ok(0);
+    }
+
+    let added = new_alerts.len();
+    Alert::insert_many(new_alerts)
+        .on_conflict(
+            OnConflict::columns([alert::Column::AlertRuleId, alert::Column::EntryId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .do_nothing()
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to save alerts: {}", e))?;
+    Ok(added)
+}
+
+// One notification per rule for the alerts not yet notified, which are then marked as
+// notified. A rule snoozed since its entries matched stays silent.
+pub async fn take_alert_notifications<C: ConnectionTrait>(db: &C) -> Result<Vec<Notification>, String> {
+    let pending = Alert::find()
+        .filter(alert::Column::NotificationPending.eq(true))
+        .order_by_asc(alert::Column::Id)
+        .find_also_related(FeedEntry)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch pending alerts: {}", e))?;
+    if pending.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let rules: HashMap<i32, alert_rule::Model> = AlertRule::find()
+        .filter(alert_rule::Column::Id.is_in(pending.iter().map(|(alert, _)| alert.alert_rule_id)))
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch alert rules: {}", e))?
+        .into_iter()
+        .map(|rule| (rule.id, rule))
+        .collect();
+
+    let now = chrono::Utc::now().naive_utc();
+    let mut titles_by_rule: Vec<(i32, Vec<String>)> = Vec::new();
+    for (alert, entry) in &pending {
+        let (Some(rule), Some(entry)) = (rules.get(&alert.alert_rule_id), entry) else {
+            continue;
+        };
+        if is_snoozed(rule, now) {
+            continue;
+        }
+        match titles_by_rule.iter_mut().find(|(rule_id, _)| *rule_id == rule.id) {
+            Some((_, titles)) => titles.push(entry.title.clone()),
+            None => titles_by_rule.push((rule.id, vec![entry.title.clone()])),
+        }
+    }
+
+    Alert::update_many()
+        .col_expr(alert::Column::NotificationPending, Expr::value(false))
+        .filter(alert::Column::Id.is_in(pending.iter().map(|(alert, _)| alert.id)))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to mark alerts as notified: {}", e))?;
+
+    Ok(titles_by_rule
+        .iter()
+        .filter_map(|(rule_id, titles)| build_alert_notification(&rules[rule_id].name, titles))
+        .collect())
+}
+
+// After each refresh, show a desktop notification for every rule that collected new matches
+pub async fn run_alert_notifier(app: AppHandle, mut summaries: broadcast::Receiver<RefreshSummary>) {
+    loop {
+        match summaries.recv().await {
+            // Pending alerts are read from the database, so skipped summaries lose nothing
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+
+        let state = app.state::<AppState>();
+        let db = state.db().await;
+        let notifications = match take_alert_notifications(&db).await {
+            Ok(notifications) => notifications,
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                continue;
+            }
+        };
+        for notification in notifications {
+            state.notifications.push(&app, notification).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::fixtures::{self, at};
+
+    fn rule(feed_id: Option<i32>, folder_id: Option<i32>, snoozed_until: Option<&str>) -> alert_rule::Model {
+        alert_rule::Model {
+            id: 1,
+            name: "Outages".to_string(),
+            pattern: "outage".to_string(),
+            is_regex: false,
+            feed_id,
+            folder_id,
+            enabled: true,
+            snoozed_until: snoozed_until.map(at),
+            created_at: at("2024-06-01 08:00"),
+            updated_at: at("2024-06-01 08:00"),
+        }
+    }
+
+    fn folder(id: i32, parent_id: Option<i32>) -> folder::Model {
+        folder::Model {
+            id,
+            name: format!("Folder {}", id),
+            created_at: at("2024-06-01 08:00"),
+            updated_at: at("2024-06-01 08:00"),
+            parent_id,
+        }
+    }
+
+    fn feed(id: i32, folder_id: Option<i32>) -> feed::Model {
+        feed::Model { folder_id, ..fixtures::feed(id) }
+    }
+
+    fn entry(title: &str, description: Option<&str>) -> feed_entry::Model {
+        feed_entry::Model {
+            title: title.to_string(),
+            description: description.map(str::to_string),
+            link: "https://example.com/post".to_string(),
+            guid: "https://example.com/post".to_string(),
+            published_at: Some(at("2024-06-01 09:00")),
+            created_at: at("2024-06-01 10:00"),
+            updated_at: at("2024-06-01 10:00"),
+            ..fixtures::feed_entry(7, 1)
+        }
+    }
+
+    #[test]
+    fn test_keyword_and_regex_matching() {
+        let post = entry("Status update", Some("<p>Partial <b>OUTAGE</b> in eu-west-1</p>"));
+
+        assert!(AlertMatcher::new(" outage ", false).unwrap().matches_entry(&post));
+        assert!(!AlertMatcher::new("<b>", false).unwrap().matches_entry(&post));
+        assert!(AlertMatcher::new(r"eu-(west|east)-\d", true).unwrap().matches_entry(&post));
+        assert!(!AlertMatcher::new(r"^outage", true).unwrap().matches_entry(&post));
+        assert!(AlertMatcher::new("(unclosed", true).is_err());
+        assert!(AlertMatcher::new("  ", false).is_err());
+    }
+
+    #[test]
+    fn test_scope_and_snooze() {
+        let folders = vec![folder(1, None), folder(2, Some(1)), folder(3, None)];
+
+        assert!(rule_watches_feed(&rule(None, None, None), &feed(5, None), &folders));
+        assert!(rule_watches_feed(&rule(Some(5), None, None), &feed(5, None), &folders));
+        assert!(!rule_watches_feed(&rule(Some(6), None, None), &feed(5, None), &folders));
+        // A folder's subfolders are watched too
+        assert!(rule_watches_feed(&rule(None, Some(1), None), &feed(5, Some(2)), &folders));
+        assert!(!rule_watches_feed(&rule(None, Some(3), None), &feed(5, Some(2)), &folders));
+        assert!(!rule_watches_feed(&rule(None, Some(1), None), &feed(5, None), &folders));
+
+        let now = at("2024-06-01 12:00");
+        assert!(is_snoozed(&rule(None, None, Some("2024-06-01 13:00")), now));
+        assert!(!is_snoozed(&rule(None, None, Some("2024-06-01 11:00")), now));
+        assert!(!is_snoozed(&rule(None, None, None), now));
+    }
+}
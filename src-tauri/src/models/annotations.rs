@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use sea_orm::*;
+use crate::entities::{prelude::*, *};
+use crate::models::responses::FeedEntryResponse;
+
+// Notes keep the reader's own line breaks; only surrounding whitespace is dropped
+pub fn normalize_note(note: &str) -> Result<String, String> {
+    let note = note.trim();
+    if note.is_empty() {
+        return Err("Note cannot be empty".to_string());
+    }
+    Ok(note.to_string())
+}
+
+// Load the notes on a set of entries in one query, oldest first, keyed by entry
+pub async fn load_annotations<C: ConnectionTrait>(
+    db: &C,
+    entry_ids: &[i32],
+) -> Result<HashMap<i32, Vec<annotation::Model>>, String> {
+    if entry_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let annotations = Annotation::find()
+        .filter(annotation::Column::EntryId.is_in(entry_ids.iter().copied()))
+        .order_by_asc(annotation::Column::CreatedAt)
+        .order_by_asc(annotation::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch annotations: {}", e))?;
+
+    let mut by_entry: HashMap<i32, Vec<annotation::Model>> = HashMap::new();
+    for annotation in annotations {
+        by_entry.entry(annotation.entry_id).or_default().push(annotation);
+    }
+    Ok(by_entry)
+}
+
+// Convert entries to responses with their notes attached
+pub async fn entries_with_annotations<C: ConnectionTrait>(
+    db: &C,
+    entries: Vec<feed_entry::Model>,
+) -> Result<Vec<FeedEntryResponse>, String> {
+    let entry_ids: Vec<i32> = entries.iter().map(|entry| entry.id).collect();
+    let mut annotations = load_annotations(db, &entry_ids).await?;
+
+    Ok(entries
+        .into_iter()
+        .map(|entry| {
+            let notes = annotations.remove(&entry.id).unwrap_or_default();
+            let mut response: FeedEntryResponse = entry.into();
+            response.annotations = notes.into_iter().map(|note| note.into()).collect();
+            response
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_note() {
+        assert_eq!(normalize_note("  Worth citing.\n\nSee section 3.\n").unwrap(), "Worth citing.\n\nSee section 3.");
+        assert!(normalize_note(" \n\t ").is_err());
+    }
+}
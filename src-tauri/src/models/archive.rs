@@ -0,0 +1,362 @@
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use chrono::NaiveDateTime;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use sea_orm::*;
+use sea_orm::sea_query::{OnConflict, Query};
+use serde::{Deserialize, Serialize};
+use crate::entities::{prelude::*, *};
+use crate::models::settings::{get_setting_or, load_archive_config, set_setting_value, ARCHIVED_AT};
+
+// Archived entries returned by a search when no limit is given
+pub const DEFAULT_ARCHIVE_SEARCH_LIMIT: u64 = 50;
+
+// Entries are archived at most this often from the scheduler
+const ARCHIVE_INTERVAL_HOURS: i64 = 24;
+// Entries moved per transaction, and archived entries decompressed per page when searching
+const ARCHIVE_BATCH_SIZE: u64 = 500;
+
+// Old entries can be moved out of the entry table into a compressed archive, which keeps the
+// tables every list and count runs against small. Only entries nobody is using are archived:
+// read, unstarred, untagged and without notes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveConfig {
+    pub enabled: bool,
+    // Entries published (or, without a date, added) longer ago than this are archived
+    pub archive_after_days: u32,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            archive_after_days: 365,
+        }
+    }
+}
+
+impl ArchiveConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.archive_after_days == 0 {
+            return Err("Entries must be at least a day old to be archived".to_string());
+        }
+        Ok(())
+    }
+
+    fn cutoff(&self, now: NaiveDateTime) -> NaiveDateTime {
+        now - chrono::Duration::days(self.archive_after_days as i64)
+    }
+}
+
+// The parts of an archived entry that aren't columns of their own, stored compressed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchivedEntryBody {
+    pub description: Option<String>,
+    pub content: Option<String>,
+    pub enclosure_url: Option<String>,
+    pub enclosure_type: Option<String>,
+    pub enclosure_length: Option<i64>,
+    pub duration_seconds: Option<i32>,
+    pub artwork_url: Option<String>,
+    // Archived before the column was renamed from canonical_url
+    #[serde(alias = "canonical_url")]
+    pub dedup_key: Option<String>,
+    pub read_at: Option<NaiveDateTime>,
+}
+
+impl From<&feed_entry::Model> for ArchivedEntryBody {
+    fn from(entry: &feed_entry::Model) -> Self {
+        ArchivedEntryBody {
+            description: entry.description.clone(),
+            content: entry.content.clone(),
+            enclosure_url: entry.enclosure_url.clone(),
+            enclosure_type: entry.enclosure_type.clone(),
+            enclosure_length: entry.enclosure_length,
+            duration_seconds: entry.duration_seconds,
+            artwork_url: entry.artwork_url.clone(),
+            dedup_key: entry.dedup_key.clone(),
+            read_at: entry.read_at,
+        }
+    }
+}
+
+pub fn compress_entry_body(body: &ArchivedEntryBody) -> Result<Vec<u8>, String> {
+    let json = serde_json::to_vec(body).map_err(|e| format!("Failed to serialize archived entry: {}", e))?;
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder
+        .write_all(&json)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| format!("Failed to compress archived entry: {}", e))
+}
+
+pub fn decompress_entry_body(payload: &[u8]) -> Result<ArchivedEntryBody, String> {
+    let mut json = Vec::new();
+    DeflateDecoder::new(payload)
+        .read_to_end(&mut json)
+        .map_err(|e| format!("Failed to decompress archived entry: {}", e))?;
+    serde_json::from_slice(&json).map_err(|e| format!("Invalid archived entry: {}", e))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedEntry {
+    pub id: i32,
+    pub feed_id: i32,
+    pub guid: String,
+    pub title: String,
+    pub link: String,
+    pub published_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub archived_at: NaiveDateTime,
+    #[serde(flatten)]
+    pub body: ArchivedEntryBody,
+}
+
+impl ArchivedEntry {
+    fn from_row(row: entry_archive::Model) -> Result<Self, String> {
+        Ok(ArchivedEntry {
+            body: decompress_entry_body(&row.payload)?,
+            id: row.id,
+            feed_id: row.feed_id,
+            guid: row.guid,
+            title: row.title,
+            link: row.link,
+            published_at: row.published_at,
+            created_at: row.created_at,
+            archived_at: row.archived_at,
+        })
+    }
+
+    // Every term appears in the title, link, description or content, ignoring case
+    pub fn matches(&self, terms: &[String]) -> bool {
+        let fields = [
+            Some(self.title.as_str()),
+            Some(self.link.as_str()),
+            self.body.description.as_deref(),
+            self.body.content.as_deref(),
+        ];
+        let text = fields.into_iter().flatten().collect::<Vec<_>>().join("\n").to_lowercase();
+        terms.iter().all(|term| text.contains(term.as_str()))
+    }
+}
+
+fn search_terms(query: &str) -> Vec<String> {
+    query.split_whitespace().map(str::to_lowercase).collect()
+}
+
+// Entries older than the cutoff that nobody is using: read, unstarred, untagged, without notes
+fn archivable_entries(cutoff: NaiveDateTime) -> Select<FeedEntry> {
+    FeedEntry::find()
+        .filter(feed_entry::Column::IsRead.eq(true))
+        .filter(feed_entry::Column::IsStarred.eq(false))
+        .filter(
+            Condition::any()
+                .add(feed_entry::Column::PublishedAt.lt(cutoff))
+                .add(
+                    Condition::all()
+                        .add(feed_entry::Column::PublishedAt.is_null())
+                        .add(feed_entry::Column::CreatedAt.lt(cutoff)),
+                ),
+        )
+        .filter(
+            feed_entry::Column::Id.not_in_subquery(
+                Query::select().column(entry_tag::Column::EntryId).from(EntryTag).to_owned(),
+            ),
+        )
+        .filter(
+            feed_entry::Column::Id.not_in_subquery(
+                Query::select().column(annotation::Column::EntryId).from(Annotation).to_owned(),
+            ),
+        )
+        .order_by_asc(feed_entry::Column::Id)
+}
+
+fn archive_row(entry: &feed_entry::Model, archived_at: NaiveDateTime) -> Result<entry_archive::ActiveModel, String> {
+    Ok(entry_archive::ActiveModel {
+        feed_id: ActiveValue::Set(entry.feed_id),
+        guid: ActiveValue::Set(entry.guid.clone()),
+        title: ActiveValue::Set(entry.title.clone()),
+        link: ActiveValue::Set(entry.link.clone()),
+        published_at: ActiveValue::Set(entry.published_at),
+        created_at: ActiveValue::Set(entry.created_at),
+        archived_at: ActiveValue::Set(archived_at),
+        payload: ActiveValue::Set(compress_entry_body(&ArchivedEntryBody::from(entry))?),
+        ..Default::default()
+    })
+}
+
+// Move every archivable entry older than the configured age into the archive, a batch per
+// transaction. Returns how many entries were archived.
+pub async fn archive_old_entries(db: &DatabaseConnection, config: &ArchiveConfig, now: NaiveDateTime) -> Result<u64, String> {
+    config.validate()?;
+    let cutoff = config.cutoff(now);
+    let mut archived = 0;
+
+    loop {
+        let entries = archivable_entries(cutoff)
+            .limit(ARCHIVE_BATCH_SIZE)
+            .all(db)
+            .await
+            .map_err(|e| format!("Failed to fetch feed entries: {}", e))?;
+        if entries.is_empty() {
+            break;
+        }
+        let rows = entries.iter().map(|entry| archive_row(entry, now)).collect::<Result<Vec<_>, _>>()?;
+        let ids: Vec<i32> = entries.iter().map(|entry| entry.id).collect();
+
+        let txn = db.begin().await.map_err(|e| format!("Failed to start transaction: {}", e))?;
+        // An entry archived before, then fetched again, keeps its first archived copy
+        EntryArchive::insert_many(rows)
+            .on_conflict(
+                OnConflict::columns([entry_archive::Column::FeedId, entry_archive::Column::Guid])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .do_nothing()
+            .exec(&txn)
+            .await
+            .map_err(|e| format!("Failed to archive feed entries: {}", e))?;
+        archived += FeedEntry::delete_many()
+            .filter(feed_entry::Column::Id.is_in(ids))
+            .exec(&txn)
+            .await
+            .map_err(|e| format!("Failed to archive feed entries: {}", e))?
+            .rows_affected;
+        txn.commit().await.map_err(|e| format!("Failed to commit transaction: {}", e))?;
+    }
+    Ok(archived)
+}
+
+// Archive old entries when enabled and it hasn't been done for a day. Returns how many were
+// archived, or None when it wasn't due.
+pub async fn archive_old_entries_if_due(db: &DatabaseConnection, now: NaiveDateTime) -> Result<Option<u64>, String> {
+    let config = load_archive_config(db).await;
+    if !config.enabled {
+        return Ok(None);
+    }
+    let last_archived_at: Option<NaiveDateTime> = get_setting_or(db, ARCHIVED_AT, None).await;
+    if last_archived_at.is_some_and(|at| now - at < chrono::Duration::hours(ARCHIVE_INTERVAL_HOURS)) {
+        return Ok(None);
+    }
+
+    let archived = archive_old_entries(db, &config, now).await?;
+    set_setting_value(db, ARCHIVED_AT, &Some(now)).await?;
+    if archived > 0 {
+        println!("🗄️ Archived {} old entries", archived);
+    }
+    Ok(Some(archived))
+}
+
+// Which of the guids the feed has in the archive. Saving skips them, so an archived entry
+// still in the feed's document isn't added back as new.
+pub async fn archived_guids<C: ConnectionTrait>(db: &C, feed_id: i32, guids: &[String]) -> Result<HashSet<String>, String> {
+    let mut archived = HashSet::new();
+    for chunk in guids.chunks(ARCHIVE_BATCH_SIZE as usize) {
+        let found: Vec<String> = EntryArchive::find()
+            .select_only()
+            .column(entry_archive::Column::Guid)
+            .filter(entry_archive::Column::FeedId.eq(feed_id))
+            .filter(entry_archive::Column::Guid.is_in(chunk.to_vec()))
+            .into_tuple()
+            .all(db)
+            .await
+            .map_err(|e| format!("Failed to fetch archived entries: {}", e))?;
+        archived.extend(found);
+    }
+    Ok(archived)
+}
+
+// Search the archive, newest archived first, optionally within one feed. Archived entries are
+// decompressed page by page until `limit` of them match every term of the query.
+pub async fn search_archived_entries<C: ConnectionTrait>(
+    db: &C,
+    query: &str,
+    feed_id: Option<i32>,
+    limit: u64,
+) -> Result<Vec<ArchivedEntry>, String> {
+    let terms = search_terms(query);
+    if terms.is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+
+    let mut select = EntryArchive::find().order_by_desc(entry_archive::Column::Id);
+    if let Some(feed_id) = feed_id {
+        select = select.filter(entry_archive::Column::FeedId.eq(feed_id));
+    }
+    let mut pages = select.paginate(db, ARCHIVE_BATCH_SIZE);
+
+    let mut results = Vec::new();
+    while let Some(rows) = pages.fetch_and_next().await.map_err(|e| format!("Failed to search the archive: {}", e))? {
+        for row in rows {
+            let entry = ArchivedEntry::from_row(row)?;
+            if entry.matches(&terms) {
+                results.push(entry);
+                if results.len() as u64 >= limit {
+                    return Ok(results);
+                }
+            }
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::fixtures::at;
+
+    #[test]
+    fn test_entry_bodies_survive_compression() {
+        let body = ArchivedEntryBody {
+            description: Some("A short summary".to_string()),
+            content: Some("<p>The full article, repeated. </p>".repeat(50)),
+            enclosure_url: Some("https://example.com/episode.mp3".to_string()),
+            enclosure_length: Some(123_456),
+            read_at: Some(at("2023-01-02 08:30")),
+            ..Default::default()
+        };
+
+        let payload = compress_entry_body(&body).unwrap();
+        assert!(payload.len() < serde_json::to_vec(&body).unwrap().len());
+        assert_eq!(decompress_entry_body(&payload).unwrap(), body);
+        assert!(decompress_entry_body(b"not deflate").is_err());
+    }
+
+    #[test]
+    fn test_search_matches_every_term_in_any_field() {
+        let entry = ArchivedEntry {
+            id: 1,
+            feed_id: 2,
+            guid: "post-1".to_string(),
+            title: "Release notes".to_string(),
+            link: "https://example.com/release".to_string(),
+            published_at: None,
+            created_at: at("2022-05-01 10:00"),
+            archived_at: at("2023-05-01 10:00"),
+            body: ArchivedEntryBody {
+                content: Some("<p>Faster SQLite queries</p>".to_string()),
+                ..Default::default()
+            },
+        };
+
+        assert!(entry.matches(&search_terms("release sqlite")));
+        assert!(entry.matches(&search_terms("  RELEASE  ")));
+        assert!(!entry.matches(&search_terms("release postgres")));
+    }
+
+    #[test]
+    fn test_only_old_unused_entries_are_archivable() {
+        let config = ArchiveConfig { enabled: true, archive_after_days: 30 };
+        assert_eq!(config.cutoff(at("2024-03-31 12:00")), at("2024-03-01 12:00"));
+        assert!(ArchiveConfig { archive_after_days: 0, ..config }.validate().is_err());
+
+        let sql = archivable_entries(at("2024-03-01 12:00")).build(DbBackend::Postgres).to_string();
+        assert!(sql.contains(r#""feed_entry"."is_read" = TRUE"#));
+        assert!(sql.contains(r#""feed_entry"."is_starred" = FALSE"#));
+        assert!(sql.contains(r#""feed_entry"."id" NOT IN (SELECT "entry_id" FROM "entry_tag")"#));
+        assert!(sql.contains(r#""feed_entry"."id" NOT IN (SELECT "entry_id" FROM "annotation")"#));
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use chrono::NaiveDateTime;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use crate::entities::{prelude::*, *};
+use crate::models::data_directory::resolve_data_directory;
+use crate::models::exporters::build_opml;
+use crate::models::settings::{get_setting_or, load_backup_config, set_setting_value, BACKUP_STATE};
+
+// Backups go here, under the data directory, unless another directory is configured
+const BACKUPS_DIR: &str = "backups";
+// Backups are named "reader-backup-<UTC time>.opml" (and ".json"), so their names sort by age
+const BACKUP_PREFIX: &str = "reader-backup-";
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+const BACKUP_EXTENSIONS: [&str; 2] = [".opml", ".json"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupFrequency {
+    #[default]
+    Daily,
+    Weekly,
+}
+
+impl BackupFrequency {
+    fn interval(&self) -> chrono::Duration {
+        match self {
+            BackupFrequency::Daily => chrono::Duration::days(1),
+            BackupFrequency::Weekly => chrono::Duration::weeks(1),
+        }
+    }
+}
+
+// Writing the subscriptions to an OPML file every day or week, keeping the last few
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    pub enabled: bool,
+    // Absolute path; None keeps backups in the data directory
+    pub directory: Option<String>,
+    pub frequency: BackupFrequency,
+    // Also write the folders, feeds and every entry's read, star, tag and note state as JSON
+    pub include_json: bool,
+    // Older backups are deleted after each one
+    pub keep_last: u32,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: None,
+            frequency: BackupFrequency::Daily,
+            include_json: false,
+            keep_last: 14,
+        }
+    }
+}
+
+impl BackupConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.keep_last == 0 {
+            return Err("Keep at least one backup".to_string());
+        }
+        if self.directory.as_deref().is_some_and(|directory| !Path::new(directory).is_absolute()) {
+            return Err("The backup directory must be an absolute path".to_string());
+        }
+        Ok(())
+    }
+}
+
+// How the last backups went, kept in the library's settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupState {
+    pub last_backup_at: Option<NaiveDateTime>,
+    pub last_attempted_at: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupFile {
+    pub name: String,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupStatus {
+    pub enabled: bool,
+    pub directory: String,
+    pub last_backup_at: Option<NaiveDateTime>,
+    pub last_attempted_at: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+    pub next_backup_at: Option<NaiveDateTime>,
+    // The backups in the directory, newest first
+    pub backups: Vec<BackupFile>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedFolder {
+    id: i32,
+    name: String,
+    parent_id: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedFeed {
+    id: i32,
+    url: String,
+    title: Option<String>,
+    folder_id: Option<i32>,
+    source_type: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedEntry {
+    feed_id: i32,
+    guid: String,
+    title: String,
+    link: String,
+    published_at: Option<NaiveDateTime>,
+    is_read: bool,
+    is_starred: bool,
+    tags: Vec<String>,
+    notes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct LibraryExport {
+    exported_at: NaiveDateTime,
+    folders: Vec<ExportedFolder>,
+    feeds: Vec<ExportedFeed>,
+    entries: Vec<ExportedEntry>,
+}
+
+// The timestamp part of a backup's file name, if it's one of ours
+fn backup_stem(name: &str) -> Option<&str> {
+    let stem = BACKUP_EXTENSIONS.iter().find_map(|extension| name.strip_suffix(extension))?;
+    let timestamp = stem.strip_prefix(BACKUP_PREFIX)?;
+    NaiveDateTime::parse_from_str(timestamp, BACKUP_TIMESTAMP_FORMAT).ok()?;
+    Some(stem)
+}
+
+// The backups past the newest `keep_last`, given the directory's file names. A backup's OPML
+// and JSON files count as one.
+fn stale_backups(names: &[String], keep_last: u32) -> Vec<String> {
+    let stems: BTreeSet<&str> = names.iter().filter_map(|name| backup_stem(name)).collect();
+    let stale: BTreeSet<&str> = stems.into_iter().rev().skip(keep_last as usize).collect();
+    names
+        .iter()
+        .filter(|name| backup_stem(name).is_some_and(|stem| stale.contains(stem)))
+        .cloned()
+        .collect()
+}
+
+async fn backup_directory<C: ConnectionTrait>(app: &AppHandle, db: &C, config: &BackupConfig) -> Result<PathBuf, String> {
+    match &config.directory {
+        Some(directory) => Ok(PathBuf::from(directory)),
+        None => Ok(resolve_data_directory(app, db).await?.join(BACKUPS_DIR)),
+    }
+}
+
+// Our backup files in a directory with their sizes, newest first
+async fn list_backups(directory: &Path) -> Result<Vec<BackupFile>, String> {
+    let mut backups = Vec::new();
+    let mut read_dir = match tokio::fs::read_dir(directory).await {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(backups),
+        Err(e) => return Err(format!("Failed to read backup directory: {}", e)),
+    };
+    while let Some(dir_entry) = read_dir
+        .next_entry()
+        .await
+        .map_err(|e| format!("Failed to read backup directory: {}", e))?
+    {
+        let name = dir_entry.file_name().to_string_lossy().to_string();
+        if backup_stem(&name).is_none() {
+            continue;
+        }
+        let size_bytes = dir_entry.metadata().await.map(|metadata| metadata.len()).unwrap_or(0);
+        backups.push(BackupFile { name, size_bytes });
+    }
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(backups)
+}
+
+async fn build_library_export<C: ConnectionTrait>(db: &C, now: NaiveDateTime) -> Result<String, String> {
+    let folders = Folder::find()
+        .order_by_asc(folder::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch folders: {}", e))?;
+    let feeds = Feed::find()
+        .order_by_asc(feed::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feeds: {}", e))?;
+
+    let tag_names: HashMap<i32, String> = Tag::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch tags: {}", e))?
+        .into_iter()
+        .map(|tag| (tag.id, tag.name))
+        .collect();
+    let mut tags: HashMap<i32, Vec<String>> = HashMap::new();
+    for entry_tag in EntryTag::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch entry tags: {}", e))?
+    {
+        if let Some(name) = tag_names.get(&entry_tag.tag_id) {
+            tags.entry(entry_tag.entry_id).or_default().push(name.clone());
+        }
+    }
+    let mut notes: HashMap<i32, Vec<String>> = HashMap::new();
+    for annotation in Annotation::find()
+        .order_by_asc(annotation::Column::CreatedAt)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch annotations: {}", e))?
+    {
+        notes.entry(annotation.entry_id).or_default().push(annotation.note);
+    }
+
+    let entries: Vec<(i32, i32, String, String, String, Option<NaiveDateTime>, bool, bool)> = FeedEntry::find()
+        .select_only()
+        .columns([
+            feed_entry::Column::Id,
+            feed_entry::Column::FeedId,
+            feed_entry::Column::Guid,
+            feed_entry::Column::Title,
+            feed_entry::Column::Link,
+            feed_entry::Column::PublishedAt,
+            feed_entry::Column::IsRead,
+            feed_entry::Column::IsStarred,
+        ])
+        .order_by_asc(feed_entry::Column::Id)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entries: {}", e))?;
+
+    let export = LibraryExport {
+        exported_at: now,
+        folders: folders
+            .into_iter()
+            .map(|folder| ExportedFolder { id: folder.id, name: folder.name, parent_id: folder.parent_id })
+            .collect(),
+        feeds: feeds
+            .into_iter()
+            .map(|feed| ExportedFeed {
+                id: feed.id,
+                url: feed.url,
+                title: feed.title,
+                folder_id: feed.folder_id,
+                source_type: feed.source_type,
+            })
+            .collect(),
+        entries: entries
+            .into_iter()
+            .map(|(id, feed_id, guid, title, link, published_at, is_read, is_starred)| ExportedEntry {
+                feed_id,
+                guid,
+                title,
+                link,
+                published_at,
+                is_read,
+                is_starred,
+                tags: tags.remove(&id).unwrap_or_default(),
+                notes: notes.remove(&id).unwrap_or_default(),
+            })
+            .collect(),
+    };
+    serde_json::to_string_pretty(&export).map_err(|e| format!("Failed to serialize library export: {}", e))
+}
+
+// Write a backup and delete the ones past the configured count. Returns the files written.
+async fn write_backup<C: ConnectionTrait>(
+    app: &AppHandle,
+    db: &C,
+    config: &BackupConfig,
+    now: NaiveDateTime,
+) -> Result<Vec<String>, String> {
+    let directory = backup_directory(app, db, config).await?;
+    tokio::fs::create_dir_all(&directory)
+        .await
+        .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    let stem = format!("{}{}", BACKUP_PREFIX, now.format(BACKUP_TIMESTAMP_FORMAT));
+
+    let folders = Folder::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch folders: {}", e))?;
+    let feeds = Feed::find()
+        .order_by_asc(feed::Column::Title)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feeds: {}", e))?;
+    let mut contents = vec![(format!("{}.opml", stem), build_opml(&folders, &feeds))];
+    if config.include_json {
+        contents.push((format!("{}.json", stem), build_library_export(db, now).await?));
+    }
+
+    let mut written = Vec::new();
+    for (name, content) in contents {
+        let path = directory.join(&name);
+        tokio::fs::write(&path, content)
+            .await
+            .map_err(|e| format!("Failed to write backup {}: {}", name, e))?;
+        written.push(path.to_string_lossy().to_string());
+    }
+
+    let names: Vec<String> = list_backups(&directory).await?.into_iter().map(|backup| backup.name).collect();
+    for name in stale_backups(&names, config.keep_last) {
+        if let Err(e) = tokio::fs::remove_file(directory.join(&name)).await {
+            eprintln!("⚠️ Failed to delete old backup {}: {}", name, e);
+        }
+    }
+    Ok(written)
+}
+
+// Back up now, recording how it went for get_backup_status
+pub async fn back_up_library<C: ConnectionTrait>(app: &AppHandle, db: &C, now: NaiveDateTime) -> Result<Vec<String>, String> {
+    let config = load_backup_config(db).await;
+    let result = write_backup(app, db, &config, now).await;
+
+    let mut state: BackupState = get_setting_or(db, BACKUP_STATE, BackupState::default()).await;
+    state.last_attempted_at = Some(now);
+    state.last_error = result.as_ref().err().cloned();
+    if result.is_ok() {
+        state.last_backup_at = Some(now);
+    }
+    set_setting_value(db, BACKUP_STATE, &state).await?;
+
+    if let Ok(written) = &result {
+        println!("💾 Backed up the library to {}", written.join(", "));
+    }
+    result
+}
+
+// Back up in the background when backups are on and a day or week has passed since the last
+// attempt. A failed backup waits for the next interval rather than retrying every tick.
+pub async fn back_up_if_due<C: ConnectionTrait>(app: &AppHandle, db: &C, now: NaiveDateTime) -> Result<Option<Vec<String>>, String> {
+    let config = load_backup_config(db).await;
+    if !config.enabled {
+        return Ok(None);
+    }
+    let state: BackupState = get_setting_or(db, BACKUP_STATE, BackupState::default()).await;
+    if state.last_attempted_at.is_some_and(|at| now - at < config.frequency.interval()) {
+        return Ok(None);
+    }
+    back_up_library(app, db, now).await.map(Some)
+}
+
+pub async fn load_backup_status<C: ConnectionTrait>(app: &AppHandle, db: &C) -> Result<BackupStatus, String> {
+    let config = load_backup_config(db).await;
+    let state: BackupState = get_setting_or(db, BACKUP_STATE, BackupState::default()).await;
+    let directory = backup_directory(app, db, &config).await?;
+    let next_backup_at = config.enabled.then(|| {
+        state
+            .last_attempted_at
+            .map_or_else(|| chrono::Utc::now().naive_utc(), |at| at + config.frequency.interval())
+    });
+
+    Ok(BackupStatus {
+        enabled: config.enabled,
+        directory: directory.to_string_lossy().to_string(),
+        last_backup_at: state.last_backup_at,
+        last_attempted_at: state.last_attempted_at,
+        last_error: state.last_error,
+        next_backup_at,
+        backups: list_backups(&directory).await?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_backups_past_the_newest_are_stale() {
+        let names: Vec<String> = [
+            "reader-backup-20240601-020000.opml",
+            "reader-backup-20240601-020000.json",
+            "reader-backup-20240603-020000.opml",
+            "reader-backup-20240602-020000.opml",
+            "reader-backup-20240602-020000.json",
+            "reader-backup-latest.opml",
+            "notes.txt",
+        ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+
+        assert_eq!(
+            stale_backups(&names, 2),
+            vec!["reader-backup-20240601-020000.opml", "reader-backup-20240601-020000.json"]
+        );
+        assert!(stale_backups(&names, 3).is_empty());
+    }
+
+    #[test]
+    fn test_config_needs_an_absolute_directory_and_a_backup_to_keep() {
+        assert!(BackupConfig::default().validate().is_ok());
+        assert!(BackupConfig { keep_last: 0, ..Default::default() }.validate().is_err());
+        assert!(BackupConfig { directory: Some("backups".to_string()), ..Default::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,93 @@
+use chrono::NaiveDate;
+use sea_orm::*;
+use sea_orm::sea_query::{Expr, OnConflict};
+use crate::entities::{prelude::*, *};
+use crate::models::settings::{get_setting_or, METERED_MODE};
+
+// Under metered mode feeds are fetched one at a time
+pub const METERED_MAX_CONCURRENT_REQUESTS: usize = 1;
+
+// How many times less often feeds are polled under metered mode
+const METERED_INTERVAL_MULTIPLIER: u32 = 4;
+
+// Metered mode belongs to the machine's connection rather than a library, so it is read from
+// the main database whichever profile is open
+pub async fn load_metered_mode<C: ConnectionTrait>(home_db: &C) -> bool {
+    get_setting_or(home_db, METERED_MODE, false).await
+}
+
+pub fn metered_refresh_interval_minutes(interval_minutes: u32, metered_mode: bool) -> u32 {
+    if metered_mode {
+        interval_minutes.saturating_mul(METERED_INTERVAL_MULTIPLIER)
+    } else {
+        interval_minutes
+    }
+}
+
+// Add to a feed's total for the day, creating the row on its first fetch of the day
+fn build_record_bandwidth_statement(feed_id: i32, day: NaiveDate, bytes: u64, backend: DbBackend) -> Statement {
+    let usage = bandwidth_usage::ActiveModel {
+        feed_id: ActiveValue::Set(feed_id),
+        day: ActiveValue::Set(day),
+        bytes: ActiveValue::Set(bytes as i64),
+    };
+
+    BandwidthUsage::insert(usage)
+        .on_conflict(
+            OnConflict::columns([bandwidth_usage::Column::FeedId, bandwidth_usage::Column::Day])
+                .value(
+                    bandwidth_usage::Column::Bytes,
+                    Expr::cust(r#""bandwidth_usage"."bytes" + "excluded"."bytes""#),
+                )
+                .to_owned(),
+        )
+        .build(backend)
+}
+
+pub async fn record_bandwidth<C: ConnectionTrait>(db: &C, feed_id: i32, bytes: u64) {
+    if bytes == 0 {
+        return;
+    }
+
+    let today = chrono::Utc::now().date_naive();
+    let statement = build_record_bandwidth_statement(feed_id, today, bytes, db.get_database_backend());
+    if let Err(e) = db.execute(statement).await {
+        eprintln!("Failed to record bandwidth for feed {}: {}", feed_id, e);
+    }
+}
+
+// Usage rows from `since` on, newest day first and heaviest feed first within a day
+pub async fn load_bandwidth_usage<C: ConnectionTrait>(
+    db: &C,
+    since: NaiveDate,
+) -> Result<Vec<bandwidth_usage::Model>, String> {
+    BandwidthUsage::find()
+        .filter(bandwidth_usage::Column::Day.gte(since))
+        .order_by_desc(bandwidth_usage::Column::Day)
+        .order_by_desc(bandwidth_usage::Column::Bytes)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch bandwidth usage: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_bandwidth_adds_to_the_days_total() {
+        let day = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+
+        let sql = build_record_bandwidth_statement(7, day, 2048, DbBackend::Postgres).to_string();
+
+        assert!(sql.starts_with(r#"INSERT INTO "bandwidth_usage" ("feed_id", "day", "bytes") VALUES (7, '2024-06-01', 2048)"#));
+        assert!(sql.contains(r#"ON CONFLICT ("feed_id", "day") DO UPDATE SET "bytes" = "bandwidth_usage"."bytes" + "excluded"."bytes""#));
+    }
+
+    #[test]
+    fn test_metered_mode_stretches_refresh_intervals() {
+        assert_eq!(metered_refresh_interval_minutes(30, false), 30);
+        assert_eq!(metered_refresh_interval_minutes(30, true), 120);
+        assert_eq!(metered_refresh_interval_minutes(u32::MAX, true), u32::MAX);
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use chrono::NaiveDateTime;
+use sea_orm::*;
+use sea_orm::sea_query::{CaseStatement, Expr};
+use crate::entities::{prelude::*, *};
+use crate::models::entry_query::{build_bulk_entry_statement, bulk_entry_condition};
+use crate::models::requests::{BulkEntryAction, BulkEntryFilter};
+use crate::models::state_log::{record_state_changes, StateField, LOCAL_ORIGIN};
+
+// How long after a bulk action it can still be undone
+pub const BULK_UNDO_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+// Entries restored per statement, keeping well under Postgres's limit of 65,535 parameters
+const UNDO_BATCH_SIZE: usize = 1000;
+
+// What a bulk mark-read or star changed: the flag it set, and the entries whose flag it
+// flipped grouped by the timestamp (read_at, starred_at) they had before
+#[derive(Debug, Clone)]
+pub struct BulkUndo {
+    field: StateField,
+    flag: feed_entry::Column,
+    set_at: feed_entry::Column,
+    value: bool,
+    previous: BTreeMap<Option<NaiveDateTime>, Vec<i32>>,
+    recorded_at: Instant,
+}
+
+impl BulkUndo {
+    pub fn is_expired(&self) -> bool {
+        self.recorded_at.elapsed() > BULK_UNDO_WINDOW
+    }
+
+    // Statements putting every changed entry's flag and timestamp back, one per batch of
+    // entries, with a CASE giving each group of entries the timestamp it had
+    fn restore_statements(&self, backend: DbBackend) -> Vec<Statement> {
+        let previous: Vec<(Option<NaiveDateTime>, i32)> = self
+            .previous
+            .iter()
+            .flat_map(|(set_at, ids)| ids.iter().map(move |id| (*set_at, *id)))
+            .collect();
+        previous
+            .chunks(UNDO_BATCH_SIZE)
+            .map(|batch| {
+                let set_at = batch
+                    .chunk_by(|a, b| a.0 == b.0)
+                    .fold(CaseStatement::new(), |case, group| {
+                        let ids = group.iter().map(|(_, id)| *id);
+                        case.case(feed_entry::Column::Id.is_in(ids), Expr::value(group[0].0))
+                    });
+                FeedEntry::update_many()
+                    .col_expr(self.flag, Expr::value(!self.value))
+                    .col_expr(self.set_at, set_at.into())
+                    .filter(feed_entry::Column::Id.is_in(batch.iter().map(|(_, id)| *id)))
+                    .build(backend)
+            })
+            .collect()
+    }
+
+    fn changed_entry_ids(&self) -> Vec<i32> {
+        self.previous.values().flatten().copied().collect()
+    }
+}
+
+// The field an action sets with its flag and timestamp columns, and the value it sets, for
+// actions that can be undone
+fn undoable_flag(action: &BulkEntryAction) -> Option<(StateField, feed_entry::Column, feed_entry::Column, bool)> {
+    match action {
+        BulkEntryAction::MarkRead { is_read } => Some((StateField::Read, feed_entry::Column::IsRead, feed_entry::Column::ReadAt, *is_read)),
+        BulkEntryAction::Star { is_starred } => Some((StateField::Starred, feed_entry::Column::IsStarred, feed_entry::Column::StarredAt, *is_starred)),
+        BulkEntryAction::Delete | BulkEntryAction::Tag { .. } => None,
+    }
+}
+
+// Run a bulk action in one transaction, remembering the entries it changes when it can be
+// undone. Returns how many rows changed.
+pub async fn run_bulk_action(
+    db: &DatabaseConnection,
+    filter: &BulkEntryFilter,
+    action: &BulkEntryAction,
+    now: NaiveDateTime,
+) -> Result<(u64, Option<BulkUndo>), String> {
+    let txn = db.begin().await.map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let undo = match undoable_flag(action) {
+        Some((field, flag, set_at, value)) => {
+            let changing: Vec<(i32, Option<NaiveDateTime>)> = FeedEntry::find()
+                .select_only()
+                .columns([feed_entry::Column::Id, set_at])
+                .filter(bulk_entry_condition(filter, now))
+                .filter(flag.ne(value))
+                .into_tuple()
+                .all(&txn)
+                .await
+                .map_err(|e| format!("Failed to fetch feed entries: {}", e))?;
+
+            let mut previous: BTreeMap<Option<NaiveDateTime>, Vec<i32>> = BTreeMap::new();
+            for (id, previous_set_at) in changing {
+                previous.entry(previous_set_at).or_default().push(id);
+            }
+            Some(BulkUndo {
+                field,
+                flag,
+                set_at,
+                value,
+                previous,
+                recorded_at: Instant::now(),
+            })
+        }
+        None => None,
+    };
+
+    let statement = build_bulk_entry_statement(filter, action, now, txn.get_database_backend())?;
+    let result = txn
+        .execute(statement)
+        .await
+        .map_err(|e| format!("Failed to bulk update entries: {}", e))?;
+    if let Some(undo) = &undo {
+        record_state_changes(&txn, &undo.changed_entry_ids(), undo.field, undo.value, now, LOCAL_ORIGIN).await?;
+    }
+    txn.commit().await.map_err(|e| format!("Failed to commit bulk update: {}", e))?;
+
+    Ok((result.rows_affected(), undo))
+}
+
+// Put back what a bulk action changed, returning how many entries were restored
+pub async fn undo_bulk_action(db: &DatabaseConnection, undo: &BulkUndo) -> Result<u64, String> {
+    let txn = db.begin().await.map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let mut restored = 0;
+    for statement in undo.restore_statements(txn.get_database_backend()) {
+        restored += txn
+            .execute(statement)
+            .await
+            .map_err(|e| format!("Failed to undo bulk update: {}", e))?
+            .rows_affected();
+    }
+    let now = chrono::Utc::now().naive_utc();
+    record_state_changes(&txn, &undo.changed_entry_ids(), undo.field, !undo.value, now, LOCAL_ORIGIN).await?;
+    txn.commit().await.map_err(|e| format!("Failed to commit undo: {}", e))?;
+
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_restores_each_entry_timestamp() {
+        let read_at = NaiveDateTime::parse_from_str("2024-06-01 08:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let (field, flag, set_at, value) = undoable_flag(&BulkEntryAction::MarkRead { is_read: false }).unwrap();
+        let undo = BulkUndo {
+            field,
+            flag,
+            set_at,
+            value,
+            previous: BTreeMap::from([(None, vec![1, 2]), (Some(read_at), vec![3])]),
+            recorded_at: Instant::now(),
+        };
+
+        let statements: Vec<String> = undo
+            .restore_statements(DbBackend::Postgres)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            statements,
+            vec![concat!(
+                r#"UPDATE "feed_entry" SET "is_read" = TRUE, "read_at" = (CASE WHEN ("feed_entry"."id" IN (1, 2)) THEN NULL "#,
+                r#"WHEN ("feed_entry"."id" IN (3)) THEN '2024-06-01 08:00:00' END) WHERE "feed_entry"."id" IN (1, 2, 3)"#,
+            )]
+        );
+        assert_eq!(undo.changed_entry_ids(), vec![1, 2, 3]);
+        assert!(!undo.is_expired());
+        assert!(undoable_flag(&BulkEntryAction::Delete).is_none());
+    }
+
+    #[test]
+    fn test_undo_restores_large_actions_in_batches() {
+        let (field, flag, set_at, value) = undoable_flag(&BulkEntryAction::MarkRead { is_read: false }).unwrap();
+        // Marking entries unread drops read_at values that mostly differ from entry to entry
+        let start = NaiveDateTime::parse_from_str("2024-06-01 08:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let previous = (1..=2500)
+            .map(|id| (Some(start + chrono::Duration::seconds(id as i64)), vec![id]))
+            .collect();
+        let undo = BulkUndo { field, flag, set_at, value, previous, recorded_at: Instant::now() };
+
+        let statements = undo.restore_statements(DbBackend::Postgres);
+        assert_eq!(statements.len(), 3);
+        assert!(statements.iter().all(|statement| statement.values.as_ref().is_some_and(|values| values.0.len() <= 3 * UNDO_BATCH_SIZE + 1)));
+    }
+}
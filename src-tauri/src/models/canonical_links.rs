@@ -0,0 +1,176 @@
+use std::sync::Arc;
+use std::time::Duration;
+use sea_orm::*;
+use sea_orm::sea_query::Expr;
+use url::Url;
+use crate::entities::{prelude::*, *};
+use crate::models::http_transport::HttpTransport;
+use crate::models::link_cleaner::strip_tracking_parameters;
+use crate::models::story_clusters::{assign_story_clusters, dedup_key};
+
+// How long to wait for an entry's page before giving up on it
+pub const PAGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// A tag's attributes as (lowercase name, value) pairs, e.g. for `<link rel=canonical href="/a">`
+fn tag_attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut chars = tag.chars().skip_while(|c| !c.is_whitespace()).peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == '/').is_some() {}
+        let name: String = std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace() && !matches!(c, '=' | '>' | '/')))
+            .collect();
+        if name.is_empty() {
+            break;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let mut value = String::new();
+        if chars.next_if_eq(&'=').is_some() {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            match chars.next_if(|c| matches!(c, '"' | '\'')) {
+                Some(quote) => value.extend(std::iter::from_fn(|| chars.next_if(|c| *c != quote))),
+                None => value.extend(std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace() && *c != '>'))),
+            }
+            chars.next_if(|c| matches!(c, '"' | '\''));
+        }
+        attributes.push((name.to_ascii_lowercase(), value));
+    }
+    attributes
+}
+
+// The `<link rel="canonical">` a page declares in its head, resolved against the page's URL
+// and cleaned of tracking parameters. Pages pointing every article at their home page are
+// ignored, since that would make every entry the same story.
+pub fn extract_canonical_link(html: &str, page_url: &str) -> Option<String> {
+    let page_url = Url::parse(page_url).ok()?;
+    // Lowercasing ASCII keeps byte offsets, so positions found here index the original too
+    let lowercase = html.to_ascii_lowercase();
+    let head_end = lowercase.find("</head").or_else(|| lowercase.find("<body")).unwrap_or(lowercase.len());
+
+    let mut position = 0;
+    while let Some(found) = lowercase[position..head_end].find("<link") {
+        let start = position + found;
+        let end = lowercase[start..].find('>').map_or(lowercase.len(), |end| start + end);
+        position = end;
+
+        let attributes = tag_attributes(&html[start..end]);
+        let attribute = |name: &str| attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.trim());
+        let is_canonical = attribute("rel")
+            .is_some_and(|rel| rel.split_ascii_whitespace().any(|rel| rel.eq_ignore_ascii_case("canonical")));
+        let Some(href) = attribute("href").filter(|href| is_canonical && !href.is_empty()) else {
+            continue;
+        };
+
+        let canonical = page_url.join(&href.replace("&amp;", "&")).ok()?;
+        if !matches!(canonical.scheme(), "http" | "https") || (canonical.path() == "/" && page_url.path() != "/") {
+            return None;
+        }
+        return Some(strip_tracking_parameters(canonical.as_str()));
+    }
+    None
+}
+
+// Fetch an entry's page and return the canonical link it declares, if any
+pub async fn fetch_canonical_link(transport: &dyn HttpTransport, link: &str) -> Option<String> {
+    let (content_type, body) = tokio::time::timeout(PAGE_TIMEOUT, transport.get(link)).await.ok()?.ok()?;
+    if !content_type.to_ascii_lowercase().contains("html") {
+        return None;
+    }
+    extract_canonical_link(&body, link)
+}
+
+// The link to hand out when sharing or exporting an entry: the canonical one when known
+pub fn share_link(entry: &feed_entry::Model) -> &str {
+    entry.canonical_link.as_deref().unwrap_or(&entry.link)
+}
+
+// Store an entry's canonical link, and compare it for duplicates by that link from now on
+async fn save_canonical_link<C: ConnectionTrait>(db: &C, entry_id: i32, canonical_link: &str) -> Result<(), String> {
+    FeedEntry::update_many()
+        .col_expr(feed_entry::Column::CanonicalLink, Expr::value(canonical_link))
+        .col_expr(feed_entry::Column::DedupKey, Expr::value(dedup_key(canonical_link)))
+        .filter(feed_entry::Column::Id.eq(entry_id))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to save canonical link: {}", e))?;
+    Ok(())
+}
+
+// Give entries not yet grouped into a story another chance now their dedup keys changed
+async fn regroup_unclustered<C: ConnectionTrait>(db: &C, feed_id: i32, entry_ids: Vec<i32>) -> Result<(), String> {
+    if entry_ids.is_empty() {
+        return Ok(());
+    }
+    let entries = FeedEntry::find()
+        .filter(feed_entry::Column::Id.is_in(entry_ids))
+        .filter(feed_entry::Column::StoryClusterId.is_null())
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entries: {}", e))?;
+    assign_story_clusters(db, feed_id, &entries).await
+}
+
+// Resolve one entry's canonical link, fetching its page unless it's known already
+pub async fn resolve_entry_canonical_link<C: ConnectionTrait>(
+    db: &C,
+    transport: &dyn HttpTransport,
+    entry: &feed_entry::Model,
+) -> Result<Option<String>, String> {
+    if entry.canonical_link.is_some() {
+        return Ok(entry.canonical_link.clone());
+    }
+    let Some(canonical_link) = fetch_canonical_link(transport, &entry.link).await else {
+        return Ok(None);
+    };
+    save_canonical_link(db, entry.id, &canonical_link).await?;
+    regroup_unclustered(db, entry.feed_id, vec![entry.id]).await?;
+    Ok(Some(canonical_link))
+}
+
+// Resolve the canonical links of a feed's newly saved entries, one page at a time. Runs in
+// the background after a save, for feeds with resolve_canonical_links set.
+pub async fn resolve_canonical_links(
+    db: DatabaseConnection,
+    transport: Arc<dyn HttpTransport>,
+    feed_id: i32,
+    entries: Vec<feed_entry::Model>,
+) -> Result<(), String> {
+    let mut resolved_ids = Vec::new();
+    for entry in entries.iter().filter(|entry| entry.canonical_link.is_none()) {
+        if let Some(canonical_link) = fetch_canonical_link(transport.as_ref(), &entry.link).await {
+            save_canonical_link(&db, entry.id, &canonical_link).await?;
+            resolved_ids.push(entry.id);
+        }
+    }
+    if !resolved_ids.is_empty() {
+        println!("🔗 Resolved {} canonical links", resolved_ids.len());
+    }
+    regroup_unclustered(&db, feed_id, resolved_ids).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_link_is_read_from_the_head() {
+        let page = "https://m.example.com/news/story-1?utm_source=rss";
+        let html = r#"<html><head>
+            <link rel="stylesheet" href="/style.css">
+            <LINK HREF='/news/story-1?ref=amp&amp;utm_medium=feed' REL="Canonical" />
+            </head><body><link rel="canonical" href="https://elsewhere.example/"></body></html>"#;
+        assert_eq!(
+            extract_canonical_link(html, page).as_deref(),
+            Some("https://m.example.com/news/story-1?ref=amp")
+        );
+
+        let unquoted = r#"<head><link rel=canonical href=https://example.com/news/story-1></head>"#;
+        assert_eq!(extract_canonical_link(unquoted, page).as_deref(), Some("https://example.com/news/story-1"));
+
+        // Only the head counts, and a home page isn't a canonical link for an article
+        assert_eq!(extract_canonical_link(r#"<body><link rel="canonical" href="/a"></body>"#, page), None);
+        assert_eq!(extract_canonical_link(r#"<link rel="canonical" href="https://example.com/">"#, page), None);
+        assert_eq!(extract_canonical_link(r#"<link rel="canonical" href="javascript:void(0)">"#, page), None);
+    }
+}
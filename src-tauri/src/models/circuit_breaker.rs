@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    // Requests flow normally
+    Closed,
+    // Too many recent failures; requests are skipped until the cool-down ends
+    Open,
+    // Cool-down is over and a single probe request is in flight
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerStatus {
+    pub domain: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub retry_after_seconds: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+struct DomainCircuit {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    // When the half-open probe was let through
+    probe_started_at: Option<Instant>,
+}
+
+impl Default for DomainCircuit {
+    fn default() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+            probe_started_at: None,
+        }
+    }
+}
+
+// Per-domain circuit breaker. After `failure_threshold` consecutive failures a domain's
+// circuit opens and fetches to it are skipped for `cooldown`; then one probe is let through
+// (half-open), which either closes the circuit again or re-opens it for another cool-down.
+// A probe that never reports back (e.g. its fetch was dropped) is replaced by another once a
+// further cool-down has passed.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    circuits: Arc<RwLock<HashMap<String, DomainCircuit>>>,
+    failure_threshold: u32,
+    cooldown: Duration,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            circuits: Arc::new(RwLock::new(HashMap::new())),
+            failure_threshold,
+            cooldown,
+        }
+    }
+
+    // Whether a request to the domain may go ahead. Moves an open circuit whose
+    // cool-down has passed to half-open, letting this request through as the probe, and
+    // lets a new probe through if the last one hasn't reported back within a cool-down.
+    pub async fn allow_request(&self, domain: &str) -> bool {
+        // A threshold of 0 disables the breaker
+        if self.failure_threshold == 0 {
+            return true;
+        }
+
+        let mut circuits = self.circuits.write().await;
+        let Some(circuit) = circuits.get_mut(domain) else {
+            return true;
+        };
+
+        let cooled_down = |since: Option<Instant>| since.is_none_or(|since| since.elapsed() >= self.cooldown);
+        match circuit.state {
+            CircuitState::Closed => true,
+            CircuitState::HalfOpen => {
+                let probe_lost = cooled_down(circuit.probe_started_at);
+                if probe_lost {
+                    println!("🔌 Probe for {} never finished, probing again", domain);
+                    circuit.probe_started_at = Some(Instant::now());
+                }
+                probe_lost
+            }
+            CircuitState::Open => {
+                let probe = cooled_down(circuit.opened_at);
+                if probe {
+                    println!("🔌 Circuit half-open for {}, probing", domain);
+                    circuit.state = CircuitState::HalfOpen;
+                    circuit.probe_started_at = Some(Instant::now());
+                }
+                probe
+            }
+        }
+    }
+
+    pub async fn record_success(&self, domain: &str) {
+        let mut circuits = self.circuits.write().await;
+        if let Some(circuit) = circuits.remove(domain) {
+            if circuit.state != CircuitState::Closed {
+                println!("🔌 Circuit closed for {}", domain);
+            }
+        }
+    }
+
+    pub async fn record_failure(&self, domain: &str) {
+        if self.failure_threshold == 0 {
+            return;
+        }
+
+        let mut circuits = self.circuits.write().await;
+        let circuit = circuits.entry(domain.to_string()).or_default();
+        circuit.consecutive_failures += 1;
+
+        let should_open = match circuit.state {
+            CircuitState::HalfOpen => true,
+            CircuitState::Closed => circuit.consecutive_failures >= self.failure_threshold,
+            CircuitState::Open => false,
+        };
+
+        if should_open {
+            println!(
+                "🔌 Circuit open for {} after {} consecutive failures",
+                domain, circuit.consecutive_failures
+            );
+            circuit.state = CircuitState::Open;
+            circuit.opened_at = Some(Instant::now());
+        }
+    }
+
+    // Close a domain's circuit by hand, e.g. after the user fixed a feed URL
+    pub async fn reset(&self, domain: &str) {
+        self.circuits.write().await.remove(domain);
+    }
+
+    // Every domain with recent failures, sorted by domain
+    pub async fn statuses(&self) -> Vec<CircuitBreakerStatus> {
+        let circuits = self.circuits.read().await;
+        let mut statuses: Vec<CircuitBreakerStatus> = circuits
+            .iter()
+            .map(|(domain, circuit)| CircuitBreakerStatus {
+                domain: domain.clone(),
+                state: circuit.state,
+                consecutive_failures: circuit.consecutive_failures,
+                retry_after_seconds: match (circuit.state, circuit.opened_at) {
+                    (CircuitState::Open, Some(opened_at)) => {
+                        Some(self.cooldown.saturating_sub(opened_at.elapsed()).as_secs())
+                    }
+                    _ => None,
+                },
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.domain.cmp(&b.domain));
+        statuses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_circuit_opens_after_threshold() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+
+        for _ in 0..2 {
+            breaker.record_failure("down.example.com").await;
+        }
+        assert!(breaker.allow_request("down.example.com").await);
+
+        breaker.record_failure("down.example.com").await;
+        assert!(!breaker.allow_request("down.example.com").await);
+        assert!(breaker.allow_request("up.example.com").await);
+
+        let statuses = breaker.statuses().await;
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, CircuitState::Open);
+        assert_eq!(statuses[0].consecutive_failures, 3);
+        assert!(statuses[0].retry_after_seconds.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_half_open_probe_closes_or_reopens() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(20));
+
+        breaker.record_failure("example.com").await;
+        assert!(!breaker.allow_request("example.com").await);
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+
+        // Only one probe goes through once the cool-down is over
+        assert!(breaker.allow_request("example.com").await);
+        assert!(!breaker.allow_request("example.com").await);
+
+        // A failed probe re-opens the circuit
+        breaker.record_failure("example.com").await;
+        assert!(!breaker.allow_request("example.com").await);
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+
+        // A successful probe closes it
+        assert!(breaker.allow_request("example.com").await);
+        breaker.record_success("example.com").await;
+        assert!(breaker.allow_request("example.com").await);
+        assert!(breaker.statuses().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_lost_probe_is_replaced_after_a_cool_down() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(20));
+
+        breaker.record_failure("example.com").await;
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(breaker.allow_request("example.com").await);
+        assert!(!breaker.allow_request("example.com").await);
+
+        // The probe never reports back
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(breaker.allow_request("example.com").await);
+        assert!(!breaker.allow_request("example.com").await);
+    }
+
+    #[tokio::test]
+    async fn test_zero_threshold_disables_breaker() {
+        let breaker = CircuitBreaker::new(0, Duration::from_secs(60));
+
+        for _ in 0..10 {
+            breaker.record_failure("example.com").await;
+        }
+
+        assert!(breaker.allow_request("example.com").await);
+    }
+}
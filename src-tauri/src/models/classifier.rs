@@ -0,0 +1,188 @@
+use std::collections::{HashMap, HashSet};
+use sea_orm::*;
+use sea_orm::sea_query::OnConflict;
+use serde::{Deserialize, Serialize};
+use crate::entities::{prelude::*, *};
+use crate::models::reading::html_to_text;
+
+// A topic tag and the keywords (single words or phrases) that suggest it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TopicRule {
+    pub tag: String,
+    pub keywords: Vec<String>,
+}
+
+// Keyword classifier that tags new entries with topics as they are saved.
+// An entry scores one point per keyword occurrence in its text and two in its title,
+// and gets every topic reaching min_score, best first, up to max_topics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClassifierConfig {
+    pub enabled: bool,
+    pub topics: Vec<TopicRule>,
+    pub min_score: u32,
+    pub max_topics: usize,
+}
+
+impl Default for ClassifierConfig {
+    fn default() -> Self {
+        let topic = |tag: &str, keywords: &[&str]| TopicRule {
+            tag: tag.to_string(),
+            keywords: keywords.iter().map(|keyword| keyword.to_string()).collect(),
+        };
+
+        Self {
+            enabled: false,
+            topics: vec![
+                topic("rust", &["rust", "rustc", "cargo", "crates.io", "rustacean"]),
+                topic("ai", &["ai", "artificial intelligence", "machine learning", "llm", "llms", "neural network"]),
+                topic("politics", &["election", "parliament", "senate", "congress", "government", "minister"]),
+            ],
+            min_score: 2,
+            max_topics: 3,
+        }
+    }
+}
+
+// Lowercase words, keeping the punctuation that is part of names like "c++", "c#" or "crates.io"
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || matches!(c, '+' | '#' | '.')))
+        .map(|token| token.trim_matches('.').to_lowercase())
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+// How many times the phrase's words appear in order in the tokens
+fn count_phrase(tokens: &[String], phrase: &[String]) -> u32 {
+    if phrase.is_empty() || phrase.len() > tokens.len() {
+        return 0;
+    }
+    tokens.windows(phrase.len()).filter(|window| *window == phrase).count() as u32
+}
+
+// Topic tags for an entry, best match first
+pub fn classify_entry(config: &ClassifierConfig, title: &str, html: Option<&str>) -> Vec<String> {
+    let title_tokens = tokenize(title);
+    let body_tokens = html.map(|html| tokenize(&html_to_text(html))).unwrap_or_default();
+
+    let mut scores: Vec<(u32, &str)> = config
+        .topics
+        .iter()
+        .filter(|topic| !topic.tag.trim().is_empty())
+        .map(|topic| {
+            let score = topic
+                .keywords
+                .iter()
+                .map(|keyword| {
+                    let phrase = tokenize(keyword);
+                    2 * count_phrase(&title_tokens, &phrase) + count_phrase(&body_tokens, &phrase)
+                })
+                .sum();
+            (score, topic.tag.trim())
+        })
+        .filter(|(score, _)| *score > 0 && *score >= config.min_score)
+        .collect();
+    scores.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+    let mut tags = Vec::new();
+    for (_, tag) in scores {
+        if tags.len() == config.max_topics {
+            break;
+        }
+        if !tags.iter().any(|existing: &String| existing == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+// Attach topic tags to entries, creating any tag that doesn't exist yet. Tags an entry
+// already has are left alone.
+pub async fn apply_topic_tags<C: ConnectionTrait>(db: &C, entry_tags: &[(i32, Vec<String>)]) -> Result<(), String> {
+    let names: HashSet<&str> = entry_tags.iter().flat_map(|(_, tags)| tags.iter().map(String::as_str)).collect();
+    if names.is_empty() {
+        return Ok(());
+    }
+
+    let now = chrono::Utc::now().naive_utc();
+    let new_tags = names.iter().map(|name| tag::ActiveModel {
+        name: ActiveValue::Set(name.to_string()),
+        created_at: ActiveValue::Set(now),
+        ..Default::default()
+    });
+    Tag::insert_many(new_tags)
+        .on_conflict(OnConflict::column(tag::Column::Name).do_nothing().to_owned())
+        .do_nothing()
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to create topic tags: {}", e))?;
+
+    let tag_ids: HashMap<String, i32> = Tag::find()
+        .filter(tag::Column::Name.is_in(names.iter().copied()))
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch topic tags: {}", e))?
+        .into_iter()
+        .map(|tag| (tag.name, tag.id))
+        .collect();
+
+    let entry_tag_models: Vec<entry_tag::ActiveModel> = entry_tags
+        .iter()
+        .flat_map(|(entry_id, tags)| tags.iter().map(move |tag| (*entry_id, tag)))
+        .filter_map(|(entry_id, tag)| {
+            tag_ids.get(tag).map(|tag_id| entry_tag::ActiveModel {
+                entry_id: ActiveValue::Set(entry_id),
+                tag_id: ActiveValue::Set(*tag_id),
+                created_at: ActiveValue::Set(now),
+            })
+        })
+        .collect();
+
+    EntryTag::insert_many(entry_tag_models)
+        .on_conflict(
+            OnConflict::columns([entry_tag::Column::EntryId, entry_tag::Column::TagId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .do_nothing()
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to tag entries: {}", e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keywords_match_whole_words_and_phrases() {
+        let config = ClassifierConfig::default();
+
+        let tags = classify_entry(
+            &config,
+            "Shipping an LLM service in Rust",
+            Some("<p>We moved from Python to Rust. Our <b>machine learning</b> models run behind cargo-built binaries.</p>"),
+        );
+
+        assert_eq!(tags, vec!["rust".to_string(), "ai".to_string()]);
+        // "said" and "aid" don't contain the word "ai"
+        assert!(classify_entry(&config, "Aid said to arrive", Some("Aid workers said so")).is_empty());
+    }
+
+    #[test]
+    fn test_topics_below_min_score_are_skipped() {
+        let config = ClassifierConfig {
+            min_score: 3,
+            max_topics: 1,
+            ..Default::default()
+        };
+
+        assert!(classify_entry(&config, "Election results", None).is_empty());
+        assert_eq!(
+            classify_entry(&config, "Election results", Some("The election and the senate, then rust")),
+            vec!["politics".to_string()]
+        );
+    }
+}
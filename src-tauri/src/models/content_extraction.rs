@@ -0,0 +1,317 @@
+use std::sync::Arc;
+use kuchikiki::traits::TendrilSink;
+use kuchikiki::{NodeRef, Selectors};
+use sea_orm::*;
+use sea_orm::sea_query::Expr;
+use crate::entities::{prelude::*, *};
+use crate::models::canonical_links::PAGE_TIMEOUT;
+use crate::models::cookie_jar::{cookie_header, load_feed_cookies};
+use crate::models::http_transport::HttpTransport;
+use crate::models::operations::Operation;
+use crate::models::reading::entry_reading_stats;
+use crate::models::sanitizer::{sanitize_html, ImagePolicy};
+
+// The heuristic only settles for a container holding at least this many words, so teasers and
+// "related articles" boxes aren't mistaken for the article
+const MIN_ARTICLE_WORDS: usize = 50;
+
+// Elements that are never part of an article's body
+const BOILERPLATE_SELECTOR: &str = "script, style, noscript, nav, aside, form, header, footer";
+
+// Elements that usually wrap the article, most specific first
+const ARTICLE_SELECTORS: [&str; 4] = ["[itemprop=articleBody]", "article", "main", "[role=main]"];
+
+fn css_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// One XPath predicate as CSS: [@attr], [@attr='value'], [contains(@attr, 'value')] or [N]
+fn xpath_predicate_to_css(predicate: &str) -> Option<String> {
+    let predicate = predicate.trim();
+    if let Ok(position) = predicate.parse::<usize>() {
+        return (position > 0).then(|| format!(":nth-of-type({})", position));
+    }
+    let quoted = |value: &str| {
+        let value = value.trim();
+        let quote = value.chars().next().filter(|c| matches!(c, '\'' | '"'))?;
+        value[1..].strip_suffix(quote).map(str::to_string)
+    };
+    let attribute = |name: &str| {
+        let name = name.trim().strip_prefix('@')?;
+        let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+        valid.then(|| name.to_string())
+    };
+
+    if let Some(arguments) = predicate.strip_prefix("contains(").and_then(|rest| rest.strip_suffix(')')) {
+        let (name, value) = arguments.split_once(',')?;
+        return Some(format!("[{}*={}]", attribute(name)?, css_string(&quoted(value)?)));
+    }
+    match predicate.split_once('=') {
+        Some((name, value)) => Some(format!("[{}={}]", attribute(name)?, css_string(&quoted(value)?))),
+        None => Some(format!("[{}]", attribute(predicate)?)),
+    }
+}
+
+// Translate the XPath most people copy out of their browser's inspector into a CSS selector:
+// steps separated by / or //, each an element name or *, with the predicates above.
+// Anything else (axes, functions, text nodes) isn't supported.
+fn xpath_to_css(xpath: &str) -> Result<String, String> {
+    let unsupported = || format!("Unsupported XPath: {}", xpath);
+    let mut css = String::new();
+    let mut rest = xpath.trim();
+
+    while !rest.is_empty() {
+        let combinator = if let Some(after) = rest.strip_prefix("//") {
+            rest = after;
+            " "
+        } else if let Some(after) = rest.strip_prefix('/') {
+            rest = after;
+            " > "
+        } else {
+            return Err(unsupported());
+        };
+        if !css.is_empty() {
+            css.push_str(combinator);
+        }
+
+        let name_end = rest.find(['[', '/']).unwrap_or(rest.len());
+        let name = &rest[..name_end];
+        let valid = name == "*" || (!name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_')));
+        if !valid {
+            return Err(unsupported());
+        }
+        css.push_str(name);
+        rest = &rest[name_end..];
+
+        while let Some(after) = rest.strip_prefix('[') {
+            // Predicates can't nest in the supported forms, but quoted values may hold a ]
+            let mut quote = None;
+            let end = after
+                .char_indices()
+                .find(|&(_, c)| match quote {
+                    Some(open) if c == open => {
+                        quote = None;
+                        false
+                    }
+                    Some(_) => false,
+                    None if matches!(c, '\'' | '"') => {
+                        quote = Some(c);
+                        false
+                    }
+                    None => c == ']',
+                })
+                .map(|(end, _)| end)
+                .ok_or_else(unsupported)?;
+            css.push_str(&xpath_predicate_to_css(&after[..end]).ok_or_else(unsupported)?);
+            rest = &after[end + 1..];
+        }
+    }
+
+    if css.is_empty() {
+        return Err(unsupported());
+    }
+    Ok(css)
+}
+
+// The CSS selector for a feed's extraction rule. Rules starting with / are XPath.
+pub fn extraction_selector(rule: &str) -> Result<String, String> {
+    let rule = rule.trim();
+    let selector = if rule.starts_with('/') { xpath_to_css(rule)? } else { rule.to_string() };
+    Selectors::compile(&selector).map_err(|_| format!("Invalid selector: {}", rule))?;
+    Ok(selector)
+}
+
+fn parse_document(html: &str) -> NodeRef {
+    kuchikiki::parse_html().one(html).document_node
+}
+
+fn word_count(node: &NodeRef) -> usize {
+    node.text_contents().split_whitespace().count()
+}
+
+// Every element the rule matches, in document order. None when nothing matches.
+pub fn extract_with_rule(html: &str, rule: &str) -> Result<Option<String>, String> {
+    let selector = extraction_selector(rule)?;
+    let document = parse_document(html);
+    let matches: Vec<String> = document
+        .select(&selector)
+        .map_err(|_| format!("Invalid selector: {}", rule))?
+        .map(|element| element.as_node().to_string())
+        .collect();
+    Ok((!matches.is_empty()).then(|| matches.concat()))
+}
+
+// Find the article in a page without a rule: an element marked up as the article or the page's
+// main content if one holds enough text, otherwise the element with the most paragraph text.
+pub fn extract_article(html: &str) -> Option<String> {
+    let document = parse_document(html);
+    let boilerplate: Vec<NodeRef> = document
+        .select(BOILERPLATE_SELECTOR)
+        .ok()?
+        .map(|element| element.as_node().clone())
+        .collect();
+    for node in boilerplate {
+        node.detach();
+    }
+
+    for selector in ARTICLE_SELECTORS {
+        let best = document
+            .select(selector)
+            .ok()?
+            .map(|element| element.as_node().clone())
+            .max_by_key(word_count);
+        if let Some(article) = best.filter(|article| word_count(article) >= MIN_ARTICLE_WORDS) {
+            return Some(article.to_string());
+        }
+    }
+
+    // Score each paragraph's parent by the words in its paragraphs
+    let mut scores: Vec<(NodeRef, usize)> = Vec::new();
+    for paragraph in document.select("p").ok()? {
+        let Some(parent) = paragraph.as_node().parent() else {
+            continue;
+        };
+        let words = word_count(paragraph.as_node());
+        match scores.iter_mut().find(|(node, _)| *node == parent) {
+            Some((_, score)) => *score += words,
+            None => scores.push((parent, words)),
+        }
+    }
+    scores
+        .into_iter()
+        .filter(|(_, score)| *score >= MIN_ARTICLE_WORDS)
+        .max_by_key(|(_, score)| *score)
+        .map(|(node, _)| node.to_string())
+}
+
+// The article body of a page, by the feed's rule when it has one
+pub fn extract_content(html: &str, rule: Option<&str>) -> Result<Option<String>, String> {
+    match rule.filter(|rule| !rule.trim().is_empty()) {
+        Some(rule) => extract_with_rule(html, rule),
+        None => Ok(extract_article(html)),
+    }
+}
+
+// Fetch a web page's HTML, failing for anything that isn't a web page. The Cookie header, if
+// given, is for pages behind a login (see cookie_jar).
+pub async fn fetch_page_html(transport: &dyn HttpTransport, url: &str, cookies: Option<String>) -> Result<String, String> {
+    let headers: Vec<(&'static str, String)> = cookies.map(|cookies| ("cookie", cookies)).into_iter().collect();
+    let (content_type, body) = tokio::time::timeout(PAGE_TIMEOUT, transport.get_with_headers(url, &headers))
+        .await
+        .map_err(|_| format!("Timed out fetching {}", url))?
+        .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+    if !content_type.to_ascii_lowercase().contains("html") {
+        return Err(format!("Not a web page: {}", content_type));
+    }
+    Ok(body)
+}
+
+// Replace newly saved entries' content with the article from their pages, one page at a time.
+// Runs in the background after a save, for feeds with fetch_full_content set, or over a
+// feed's existing entries on request, reporting to `operation` and stopping if it's
+// cancelled. Entries whose page can't be fetched or has no recognisable article keep the
+// feed's content. Pages are requested with the cookies stored for the feed, if any.
+pub async fn fetch_full_content(
+    db: DatabaseConnection,
+    transport: Arc<dyn HttpTransport>,
+    feed_id: i32,
+    rule: Option<String>,
+    image_policy: ImagePolicy,
+    entries: Vec<feed_entry::Model>,
+    operation: Option<&Operation>,
+) -> Result<(), String> {
+    let cookies = load_feed_cookies(feed_id).await.unwrap_or_else(|e| {
+        eprintln!("⚠️ Fetching full content without cookies: {}", e);
+        Vec::new()
+    });
+    let mut extracted = 0;
+    for entry in &entries {
+        if operation.is_some_and(Operation::is_cancelled) {
+            break;
+        }
+        let cookie_header = cookie_header(&cookies, &entry.link, chrono::Utc::now().timestamp());
+        let article = match fetch_page_html(transport.as_ref(), &entry.link, cookie_header).await {
+            Ok(html) => extract_content(&html, rule.as_deref())?,
+            Err(_) => None,
+        };
+        if let Some(operation) = operation {
+            operation.record(article.is_some());
+        }
+        let Some(article) = article else {
+            continue;
+        };
+        let content = sanitize_html(&article, Some(&entry.link), image_policy);
+        let reading_stats = entry_reading_stats(Some(&content), entry.description.as_deref());
+
+        FeedEntry::update_many()
+            .col_expr(feed_entry::Column::Content, Expr::value(content))
+            .col_expr(feed_entry::Column::WordCount, Expr::value(reading_stats.map(|stats| stats.word_count)))
+            .col_expr(
+                feed_entry::Column::ReadingTimeMinutes,
+                Expr::value(reading_stats.map(|stats| stats.reading_time_minutes)),
+            )
+            .filter(feed_entry::Column::Id.eq(entry.id))
+            .exec(&db)
+            .await
+            .map_err(|e| format!("Failed to save full content: {}", e))?;
+        extracted += 1;
+    }
+    if extracted > 0 {
+        println!("📰 Fetched the full content of {} entries", extracted);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(count: usize) -> String {
+        vec!["word"; count].join(" ")
+    }
+
+    #[test]
+    fn test_xpath_rules_become_css() {
+        assert_eq!(extraction_selector("div.post-body > p").unwrap(), "div.post-body > p");
+        assert_eq!(extraction_selector("//article").unwrap(), "article");
+        assert_eq!(
+            extraction_selector("//div[@id='main']/div[contains(@class, \"entry\")]").unwrap(),
+            "div[id=\"main\"] > div[class*=\"entry\"]"
+        );
+        assert_eq!(extraction_selector("/html/body//section[2][@data-body]").unwrap(), "html > body section:nth-of-type(2)[data-body]");
+        assert!(extraction_selector("//div/text()").is_err());
+        assert!(extraction_selector("//div[last()]").is_err());
+        assert!(extraction_selector("div[").is_err());
+    }
+
+    #[test]
+    fn test_rule_picks_out_the_matching_elements() {
+        let html = r#"<div class="ad">Buy</div><div class="body"><p>One</p></div><div class="body"><p>Two</p></div>"#;
+        assert_eq!(
+            extract_with_rule(html, "//div[@class='body']").unwrap().as_deref(),
+            Some(r#"<div class="body"><p>One</p></div><div class="body"><p>Two</p></div>"#)
+        );
+        assert_eq!(extract_with_rule(html, ".missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_heuristic_finds_the_article() {
+        let marked_up = format!(
+            "<nav>{}</nav><article><p>Teaser</p></article><article><h1>Title</h1><p>{}</p><script>track()</script></article>",
+            words(80),
+            words(60)
+        );
+        let article = extract_article(&marked_up).unwrap();
+        assert!(article.starts_with("<article><h1>Title</h1>"));
+        assert!(!article.contains("track()"));
+
+        let unmarked = format!(
+            "<div id=\"sidebar\"><p>{}</p></div><div id=\"story\"><p>{}</p><p>{}</p></div>",
+            words(30),
+            words(40),
+            words(40)
+        );
+        assert!(extract_article(&unmarked).unwrap().starts_with("<div id=\"story\">"));
+        assert_eq!(extract_article("<p>Too short</p>"), None);
+    }
+}
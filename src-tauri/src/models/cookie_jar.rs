@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use tauri::webview::cookie::Cookie;
+use url::Url;
+use crate::models::secrets::{delete_secret_value, get_secret_value, store_secret_value, SecretKind};
+
+// A cookie kept for a feed's site, as in a Netscape cookies.txt line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredCookie {
+    pub domain: String,
+    // Whether subdomains of `domain` get the cookie too
+    pub include_subdomains: bool,
+    pub path: String,
+    pub secure: bool,
+    // Unix time; None for a session cookie, kept until replaced or cleared
+    pub expires_at: Option<i64>,
+    pub name: String,
+    pub value: String,
+}
+
+impl StoredCookie {
+    fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str().map(str::to_ascii_lowercase) else {
+            return false;
+        };
+        let domain_matches = host == self.domain
+            || (self.include_subdomains && host.strip_suffix(&self.domain).is_some_and(|prefix| prefix.ends_with('.')));
+        let path = self.path.trim_end_matches('/');
+        let path_matches = url.path() == path
+            || url.path().strip_prefix(path).is_some_and(|rest| rest.starts_with('/'));
+        domain_matches && path_matches && (!self.secure || url.scheme() == "https")
+    }
+
+    fn same_cookie(&self, other: &StoredCookie) -> bool {
+        self.domain == other.domain && self.path == other.path && self.name == other.name
+    }
+}
+
+// Read a Netscape cookies.txt, as exported by browser extensions and curl. Lines are tab-separated
+// domain, subdomains flag, path, secure flag, expiry and name=value fields; HttpOnly cookies are
+// written with a "#HttpOnly_" prefix rather than commented out.
+pub fn parse_netscape_cookies(text: &str) -> Result<Vec<StoredCookie>, String> {
+    let mut cookies = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [domain, include_subdomains, path, secure, expires_at, name, value] = fields[..] else {
+            return Err(format!("Line {} is not a cookies.txt entry", number + 1));
+        };
+        let expires_at: i64 = expires_at
+            .trim()
+            .parse()
+            .map_err(|_| format!("Line {} has an invalid expiry: {}", number + 1, expires_at))?;
+        let domain = domain.trim().to_ascii_lowercase();
+        cookies.push(StoredCookie {
+            include_subdomains: include_subdomains.eq_ignore_ascii_case("TRUE") || domain.starts_with('.'),
+            domain: domain.trim_start_matches('.').to_string(),
+            path: if path.is_empty() { "/".to_string() } else { path.to_string() },
+            secure: secure.eq_ignore_ascii_case("TRUE"),
+            expires_at: (expires_at > 0).then_some(expires_at),
+            name: name.to_string(),
+            value: value.to_string(),
+        });
+    }
+    Ok(cookies)
+}
+
+// A cookie captured from the login window. Cookies without a domain belong to the page's host only.
+pub fn stored_cookie(cookie: &Cookie<'_>, page_url: &Url) -> Option<StoredCookie> {
+    let (domain, include_subdomains) = match cookie.domain() {
+        Some(domain) => (domain.trim_start_matches('.').to_ascii_lowercase(), true),
+        None => (page_url.host_str()?.to_ascii_lowercase(), false),
+    };
+    Some(StoredCookie {
+        domain,
+        include_subdomains,
+        path: cookie.path().unwrap_or("/").to_string(),
+        secure: cookie.secure().unwrap_or(false),
+        expires_at: cookie.expires_datetime().map(|expires_at| expires_at.unix_timestamp()),
+        name: cookie.name().to_string(),
+        value: cookie.value().to_string(),
+    })
+}
+
+// Add cookies to a jar, replacing ones with the same domain, path and name, and drop expired ones
+pub fn merge_cookies(jar: &mut Vec<StoredCookie>, cookies: Vec<StoredCookie>, now: i64) {
+    for cookie in cookies {
+        jar.retain(|kept| !kept.same_cookie(&cookie));
+        jar.push(cookie);
+    }
+    jar.retain(|cookie| !cookie.is_expired(now));
+}
+
+// The Cookie header to send with a request for `url`, longest paths first as browsers do
+pub fn cookie_header(cookies: &[StoredCookie], url: &str, now: i64) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let mut matching: Vec<&StoredCookie> = cookies
+        .iter()
+        .filter(|cookie| !cookie.is_expired(now) && cookie.matches(&url))
+        .collect();
+    matching.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+    let header = matching
+        .iter()
+        .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+        .collect::<Vec<_>>()
+        .join("; ");
+    (!header.is_empty()).then_some(header)
+}
+
+// A feed's cookies live in the keychain with its other credentials, keyed by feed id
+pub async fn load_feed_cookies(feed_id: i32) -> Result<Vec<StoredCookie>, String> {
+    match get_secret_value(SecretKind::FeedCookies, feed_id.to_string()).await? {
+        Some(json) => serde_json::from_str(&json).map_err(|e| format!("Failed to read stored cookies: {}", e)),
+        None => Ok(Vec::new()),
+    }
+}
+
+pub async fn store_feed_cookies(feed_id: i32, cookies: &[StoredCookie]) -> Result<(), String> {
+    if cookies.is_empty() {
+        delete_secret_value(SecretKind::FeedCookies, feed_id.to_string()).await?;
+        return Ok(());
+    }
+    let json = serde_json::to_string(cookies).map_err(|e| format!("Failed to serialize cookies: {}", e))?;
+    store_secret_value(SecretKind::FeedCookies, feed_id.to_string(), json).await
+}
+
+// Add cookies to a feed's jar. Returns how many cookies the jar holds afterwards.
+pub async fn add_feed_cookies(feed_id: i32, cookies: Vec<StoredCookie>) -> Result<usize, String> {
+    let mut jar = load_feed_cookies(feed_id).await?;
+    merge_cookies(&mut jar, cookies, chrono::Utc::now().timestamp());
+    store_feed_cookies(feed_id, &jar).await?;
+    Ok(jar.len())
+}
+
+// The Cookie header a feed's jar has for `url`. Keychain failures count as an empty jar, so a
+// locked keychain only costs the pages behind the login.
+pub async fn feed_cookie_header(feed_id: i32, url: &str) -> Option<String> {
+    let cookies = load_feed_cookies(feed_id).await.ok()?;
+    cookie_header(&cookies, url, chrono::Utc::now().timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOKIES_TXT: &str = "# Netscape HTTP Cookie File\n\
+.example.com\tTRUE\t/\tTRUE\t0\tsession\tabc\n\
+#HttpOnly_news.example.com\tFALSE\t/members\tFALSE\t4102444800\tmember\t42\n\
+example.com\tFALSE\t/\tFALSE\t946684800\told\tgone\n";
+
+    #[test]
+    fn test_cookies_txt_is_read() {
+        let cookies = parse_netscape_cookies(COOKIES_TXT).unwrap();
+        assert_eq!(cookies.len(), 3);
+        assert_eq!(cookies[0].domain, "example.com");
+        assert!(cookies[0].include_subdomains && cookies[0].secure);
+        assert_eq!(cookies[0].expires_at, None);
+        assert_eq!(cookies[1].domain, "news.example.com");
+        assert_eq!(cookies[1].expires_at, Some(4102444800));
+        assert!(parse_netscape_cookies("example.com\tTRUE\t/").is_err());
+    }
+
+    #[test]
+    fn test_header_only_carries_matching_cookies() {
+        let cookies = parse_netscape_cookies(COOKIES_TXT).unwrap();
+        let now = 1_700_000_000;
+        assert_eq!(
+            cookie_header(&cookies, "https://news.example.com/members/article", now).as_deref(),
+            Some("member=42; session=abc")
+        );
+        // The secure cookie stays off plain HTTP, and paths must match on a segment boundary
+        assert_eq!(cookie_header(&cookies, "http://news.example.com/membership", now), None);
+        assert_eq!(cookie_header(&cookies, "https://example.com/", now).as_deref(), Some("session=abc"));
+        assert_eq!(cookie_header(&cookies, "https://badexample.com/", now), None);
+    }
+
+    #[test]
+    fn test_merging_replaces_and_expires_cookies() {
+        let mut jar = parse_netscape_cookies(COOKIES_TXT).unwrap();
+        let mut renewed = jar[0].clone();
+        renewed.value = "def".to_string();
+        merge_cookies(&mut jar, vec![renewed], 1_700_000_000);
+
+        let values: Vec<_> = jar.iter().map(|cookie| cookie.value.as_str()).collect();
+        assert_eq!(values, ["42", "def"]);
+    }
+}
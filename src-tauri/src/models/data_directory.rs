@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use sea_orm::ConnectionTrait;
+use tauri::{AppHandle, Manager};
+use crate::models::settings::{get_setting_or, DATA_DIRECTORY};
+
+// Where local files (image cache, downloads) live: the user's chosen directory,
+// or the platform app data directory by default
+pub async fn resolve_data_directory<C: ConnectionTrait>(app: &AppHandle, db: &C) -> Result<PathBuf, String> {
+    let configured: Option<String> = get_setting_or(db, DATA_DIRECTORY, None).await;
+
+    match configured {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to resolve app data directory: {}", e)),
+    }
+}
+
+fn copy_dir_recursive(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_recursive(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Relative path and size of every file under a directory
+fn directory_manifest(root: &Path) -> io::Result<BTreeMap<PathBuf, u64>> {
+    fn visit(root: &Path, dir: &Path, manifest: &mut BTreeMap<PathBuf, u64>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                visit(root, &path, manifest)?;
+            } else {
+                let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+                manifest.insert(relative, entry.metadata()?.len());
+            }
+        }
+        Ok(())
+    }
+
+    let mut manifest = BTreeMap::new();
+    if root.exists() {
+        visit(root, root, &mut manifest)?;
+    }
+    Ok(manifest)
+}
+
+// Total size of the files under a directory; 0 when it doesn't exist yet
+pub fn directory_size(root: &Path) -> io::Result<u64> {
+    Ok(directory_manifest(root)?.values().sum())
+}
+
+// Copy a data directory to a new location and verify every file arrived intact.
+// The destination must not exist or be empty, and can't be inside the source.
+// On a failed copy or verification the partial destination is removed.
+pub fn copy_and_verify_directory(from: &Path, to: &Path) -> Result<usize, String> {
+    if to.starts_with(from) {
+        return Err("New data directory cannot be inside the current one".to_string());
+    }
+    if to.exists() {
+        let mut entries = fs::read_dir(to).map_err(|e| format!("Failed to read new data directory: {}", e))?;
+        if entries.next().is_some() {
+            return Err("New data directory must be empty".to_string());
+        }
+    }
+
+    let source_manifest = directory_manifest(from)
+        .map_err(|e| format!("Failed to read current data directory: {}", e))?;
+
+    let copied = if from.exists() {
+        copy_dir_recursive(from, to)
+    } else {
+        fs::create_dir_all(to)
+    };
+
+    let verified = copied
+        .and_then(|_| directory_manifest(to))
+        .map_err(|e| format!("Failed to copy data directory: {}", e))
+        .and_then(|copied_manifest| {
+            if copied_manifest == source_manifest {
+                Ok(copied_manifest.len())
+            } else {
+                Err("Copied data directory does not match the original".to_string())
+            }
+        });
+
+    if verified.is_err() {
+        let _ = fs::remove_dir_all(to);
+    }
+
+    verified
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("reader-data-dir-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_copy_and_verify_directory() {
+        let from = temp_dir("copy-from");
+        let to = temp_dir("copy-to");
+        fs::create_dir_all(from.join("downloads")).unwrap();
+        fs::write(from.join("downloads").join("episode.mp3"), b"audio").unwrap();
+        fs::write(from.join("cache.bin"), b"cached").unwrap();
+
+        let copied = copy_and_verify_directory(&from, &to).unwrap();
+
+        assert_eq!(copied, 2);
+        assert_eq!(fs::read(to.join("downloads").join("episode.mp3")).unwrap(), b"audio");
+        assert_eq!(directory_manifest(&from).unwrap(), directory_manifest(&to).unwrap());
+        assert_eq!(directory_size(&to).unwrap(), 11);
+
+        fs::remove_dir_all(&from).unwrap();
+        fs::remove_dir_all(&to).unwrap();
+    }
+
+    #[test]
+    fn test_copy_rejects_non_empty_or_nested_destination() {
+        let from = temp_dir("reject-from");
+        let to = temp_dir("reject-to");
+        fs::create_dir_all(&from).unwrap();
+        fs::create_dir_all(&to).unwrap();
+        fs::write(to.join("existing.txt"), b"keep me").unwrap();
+
+        assert!(copy_and_verify_directory(&from, &to).is_err());
+        assert!(copy_and_verify_directory(&from, &from.join("nested")).is_err());
+        // The existing destination is left alone
+        assert!(to.join("existing.txt").exists());
+
+        fs::remove_dir_all(&from).unwrap();
+        fs::remove_dir_all(&to).unwrap();
+    }
+}
@@ -0,0 +1,29 @@
+use std::env;
+use std::time::Duration;
+use sea_orm::*;
+
+// Read an optional numeric setting from the environment
+fn env_number<T: std::str::FromStr>(name: &str) -> Option<T> {
+    env::var(name).ok().and_then(|value| value.trim().parse().ok())
+}
+
+// Connection pool sizing. These come from the environment rather than the settings table
+// because they are needed before the database can be read.
+fn connect_options(database_url: String) -> ConnectOptions {
+    let mut options = ConnectOptions::new(database_url);
+    if let Some(max_connections) = env_number("DATABASE_MAX_CONNECTIONS") {
+        options.max_connections(max_connections);
+    }
+    if let Some(min_connections) = env_number("DATABASE_MIN_CONNECTIONS") {
+        options.min_connections(min_connections);
+    }
+    if let Some(acquire_timeout_secs) = env_number("DATABASE_ACQUIRE_TIMEOUT_SECS") {
+        options.acquire_timeout(Duration::from_secs(acquire_timeout_secs));
+    }
+    options
+}
+
+// Open a connection pool to a library database
+pub async fn connect_database(database_url: String) -> Result<DatabaseConnection, DbErr> {
+    Database::connect(connect_options(database_url)).await
+}
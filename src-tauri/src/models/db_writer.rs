@@ -0,0 +1,183 @@
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use sea_orm::DatabaseConnection;
+use tokio::sync::{mpsc, oneshot};
+
+type WriteJob = Box<dyn FnOnce(Arc<DatabaseConnection>) -> BoxFuture<'static, ()> + Send>;
+
+enum WriterMessage {
+    Write(WriteJob),
+    // Switch to another database once the writes queued before this have run
+    SwitchDatabase(Arc<DatabaseConnection>, oneshot::Sender<()>),
+}
+
+// Funnels database writes through a single task. Fetches run concurrently, but their
+// writes are applied one at a time, so fetch concurrency never turns into write-lock
+// contention. A write that panics is dropped; the writes after it still run.
+#[derive(Clone)]
+pub struct DbWriter {
+    sender: mpsc::UnboundedSender<WriterMessage>,
+    // Writes queued or running
+    pending: Arc<AtomicUsize>,
+    // The database writes currently go to
+    db: Arc<RwLock<Arc<DatabaseConnection>>>,
+}
+
+impl DbWriter {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<WriterMessage>();
+        let pending = Arc::new(AtomicUsize::new(0));
+
+        let current_db = Arc::new(RwLock::new(db.clone()));
+
+        let writer_pending = pending.clone();
+        let writer_db = current_db.clone();
+        tokio::spawn(async move {
+            let mut db = db;
+            while let Some(message) = receiver.recv().await {
+                match message {
+                    WriterMessage::Write(job) => {
+                        let db = db.clone();
+                        if AssertUnwindSafe(async move { job(db).await }).catch_unwind().await.is_err() {
+                            eprintln!("❌ Database write panicked");
+                        }
+                        writer_pending.fetch_sub(1, Ordering::Relaxed);
+                    }
+                    WriterMessage::SwitchDatabase(new_db, done) => {
+                        *writer_db.write().unwrap() = new_db.clone();
+                        db = new_db;
+                        let _ = done.send(());
+                    }
+                }
+            }
+        });
+
+        Self { sender, pending, db: current_db }
+    }
+
+    // The database writes currently go to, for reads that shouldn't wait behind queued writes
+    pub fn database(&self) -> Arc<DatabaseConnection> {
+        self.db.read().unwrap().clone()
+    }
+
+    // Queue a write without waiting for it to run
+    pub fn submit<F, Fut>(&self, write: F)
+    where
+        F: FnOnce(Arc<DatabaseConnection>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let job: WriteJob = Box::new(move |db| write(db).boxed());
+        self.pending.fetch_add(1, Ordering::Relaxed);
+        if self.sender.send(WriterMessage::Write(job)).is_err() {
+            self.pending.fetch_sub(1, Ordering::Relaxed);
+            eprintln!("❌ Database writer stopped, dropping write");
+        }
+    }
+
+    // Writes waiting for or in the middle of running
+    pub fn pending_writes(&self) -> usize {
+        self.pending.load(Ordering::Relaxed)
+    }
+
+    // Queue a write and wait for its result
+    pub async fn run<F, Fut, T>(&self, write: F) -> Result<T, String>
+    where
+        F: FnOnce(Arc<DatabaseConnection>) -> Fut + Send + 'static,
+        Fut: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let (reply_sender, reply_receiver) = oneshot::channel();
+        self.submit(move |db| async move {
+            let _ = reply_sender.send(write(db).await);
+        });
+
+        reply_receiver
+            .await
+            .map_err(|_| "Database writer stopped before the write completed".to_string())
+    }
+
+    // Wait until every write queued so far has run
+    pub async fn flush(&self) -> Result<(), String> {
+        self.run(|_db| async {}).await
+    }
+
+    // Send later writes to another database (e.g. after switching profiles). Writes already
+    // queued still go to the old one; this returns once they have finished.
+    pub async fn switch_database(&self, db: Arc<DatabaseConnection>) -> Result<(), String> {
+        let (done_sender, done_receiver) = oneshot::channel();
+        self.sender
+            .send(WriterMessage::SwitchDatabase(db, done_sender))
+            .map_err(|_| "Database writer stopped".to_string())?;
+
+        done_receiver
+            .await
+            .map_err(|_| "Database writer stopped before switching databases".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_writes_run_one_at_a_time_in_order() {
+        let writer = DbWriter::new(Arc::new(DatabaseConnection::Disconnected));
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        for i in 0..3u64 {
+            let log = log.clone();
+            writer.submit(move |_db| async move {
+                log.lock().await.push(format!("start {}", i));
+                // Later writes are quicker, so any overlap would reorder the log
+                tokio::time::sleep(Duration::from_millis(30 - i * 10)).await;
+                log.lock().await.push(format!("end {}", i));
+            });
+        }
+
+        let result = writer.run(|_db| async { 42 }).await;
+
+        assert_eq!(result, Ok(42));
+        assert_eq!(
+            *log.lock().await,
+            vec!["start 0", "end 0", "start 1", "end 1", "start 2", "end 2"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_panicking_write_does_not_stop_the_writer() {
+        let writer = DbWriter::new(Arc::new(DatabaseConnection::Disconnected));
+
+        writer.submit(|_db| async { panic!("write failed") });
+        let result = writer.run(|_db| async { 42 }).await;
+
+        assert_eq!(result, Ok(42));
+        // The last write replies just before it's counted as done
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(writer.pending_writes(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_switch_database_applies_after_queued_writes() {
+        let writer = DbWriter::new(Arc::new(DatabaseConnection::Disconnected));
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let queued_log = log.clone();
+        writer.submit(move |_db| async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            queued_log.lock().await.push("queued write");
+        });
+        let new_db = Arc::new(DatabaseConnection::Disconnected);
+        writer.switch_database(new_db.clone()).await.unwrap();
+
+        // The queued write finished before the switch returned
+        assert_eq!(log.lock().await.len(), 1);
+        let used_new_db = writer.run(move |db| async move { Arc::ptr_eq(&db, &new_db) }).await;
+        assert_eq!(used_new_db, Ok(true));
+    }
+}
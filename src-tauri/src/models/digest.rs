@@ -0,0 +1,415 @@
+use std::collections::{BTreeMap, HashMap};
+use chrono::{Local, NaiveDateTime, TimeZone};
+use quick_xml::escape::escape;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use crate::entities::{prelude::*, *};
+use crate::models::exporters::{escape_markdown_text, markdown_link_target};
+use crate::models::reading::html_to_text;
+use crate::models::settings::{get_setting_or, set_setting_value, DIGEST_SCHEDULE};
+use crate::models::story_clusters::load_cluster_feed_counts;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestGrouping {
+    #[default]
+    Feed,
+    Folder,
+}
+
+// What goes into a digest and when one is generated automatically
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DigestConfig {
+    // Generate a digest of the past day every day once the clock passes `hour` (local time)
+    pub scheduled: bool,
+    pub hour: u32,
+    pub group_by: DigestGrouping,
+    // Highlights listed per feed or folder
+    pub entries_per_group: usize,
+    // Include the opening words of each entry
+    pub summarize: bool,
+    pub summary_words: usize,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            scheduled: false,
+            hour: 7,
+            group_by: DigestGrouping::Feed,
+            entries_per_group: 3,
+            summarize: true,
+            summary_words: 40,
+        }
+    }
+}
+
+impl DigestConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hour > 23 {
+            return Err("Digest hour must be between 0 and 23".to_string());
+        }
+        if self.entries_per_group == 0 {
+            return Err("A digest needs at least one entry per group".to_string());
+        }
+        Ok(())
+    }
+}
+
+// When the scheduled digest last ran, so it runs once a day however often the scheduler ticks
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DigestScheduleState {
+    pub last_generated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigestItem {
+    pub title: String,
+    pub link: String,
+    pub summary: Option<String>,
+    pub is_starred: bool,
+    pub also_covered_by: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigestSection {
+    pub heading: String,
+    pub items: Vec<DigestItem>,
+}
+
+// The first `words` words of an entry's text, with an ellipsis if it goes on
+pub fn summarize_text(html: &str, words: usize) -> Option<String> {
+    let text = html_to_text(html);
+    let all_words: Vec<&str> = text.split_whitespace().collect();
+    if all_words.is_empty() || words == 0 {
+        return None;
+    }
+
+    let mut summary = all_words[..all_words.len().min(words)].join(" ");
+    if all_words.len() > words {
+        summary.push('…');
+    }
+    Some(summary)
+}
+
+// Group entries by feed or folder and keep the top entries of each: starred first, then
+// stories covered by the most other feeds, then the newest
+pub fn build_digest_sections(
+    entries: Vec<feed_entry::Model>,
+    feeds: &HashMap<i32, feed::Model>,
+    folders: &HashMap<i32, folder::Model>,
+    coverage: &HashMap<i32, u64>,
+    config: &DigestConfig,
+) -> Vec<DigestSection> {
+    let mut groups: BTreeMap<String, Vec<(feed_entry::Model, u64)>> = BTreeMap::new();
+    for entry in entries {
+        let feed = feeds.get(&entry.feed_id);
+        let heading = match config.group_by {
+            DigestGrouping::Feed => feed
+                .map(|feed| feed.title.clone().unwrap_or_else(|| feed.url.clone()))
+                .unwrap_or_else(|| "Unknown feed".to_string()),
+            DigestGrouping::Folder => feed
+                .and_then(|feed| feed.folder_id)
+                .and_then(|folder_id| folders.get(&folder_id))
+                .map(|folder| folder.name.clone())
+                .unwrap_or_else(|| "Unfiled".to_string()),
+        };
+        let feed_count = entry
+            .story_cluster_id
+            .and_then(|story_cluster_id| coverage.get(&story_cluster_id).copied())
+            .unwrap_or(1);
+        groups.entry(heading).or_default().push((entry, feed_count.saturating_sub(1)));
+    }
+
+    groups
+        .into_iter()
+        .map(|(heading, mut entries)| {
+            entries.sort_by(|(a, a_coverage), (b, b_coverage)| {
+                b.is_starred
+                    .cmp(&a.is_starred)
+                    .then(b_coverage.cmp(a_coverage))
+                    .then(b.published_at.unwrap_or(b.created_at).cmp(&a.published_at.unwrap_or(a.created_at)))
+            });
+            entries.truncate(config.entries_per_group);
+
+            let items = entries
+                .into_iter()
+                .map(|(entry, also_covered_by)| {
+                    let summary = config
+                        .summarize
+                        .then(|| entry.description.as_deref().or(entry.content.as_deref()))
+                        .flatten()
+                        .and_then(|html| summarize_text(html, config.summary_words));
+                    DigestItem {
+                        title: entry.title,
+                        link: entry.link,
+                        summary,
+                        is_starred: entry.is_starred,
+                        also_covered_by,
+                    }
+                })
+                .collect();
+            DigestSection { heading, items }
+        })
+        .collect()
+}
+
+fn coverage_note(also_covered_by: u64) -> Option<String> {
+    match also_covered_by {
+        0 => None,
+        1 => Some("Also covered by 1 other feed".to_string()),
+        count => Some(format!("Also covered by {} other feeds", count)),
+    }
+}
+
+pub fn render_digest_markdown(title: &str, sections: &[DigestSection]) -> String {
+    let mut out = format!("# {}\n", escape_markdown_text(title));
+    if sections.is_empty() {
+        out.push_str("\nNothing new.\n");
+    }
+
+    for section in sections {
+        out.push_str(&format!("\n## {}\n\n", escape_markdown_text(&section.heading)));
+        for item in &section.items {
+            let star = if item.is_starred { " ★" } else { "" };
+            out.push_str(&format!(
+                "- [{}]({}){}\n",
+                escape_markdown_text(&item.title),
+                markdown_link_target(&item.link),
+                star
+            ));
+            if let Some(summary) = &item.summary {
+                out.push_str(&format!("  {}\n", escape_markdown_text(summary)));
+            }
+            if let Some(note) = coverage_note(item.also_covered_by) {
+                out.push_str(&format!("  _{}_\n", note));
+            }
+        }
+    }
+
+    out
+}
+
+pub fn render_digest_html(title: &str, sections: &[DigestSection]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n",
+        escape(title)
+    );
+    if sections.is_empty() {
+        out.push_str("<p>Nothing new.</p>\n");
+    }
+
+    for section in sections {
+        out.push_str(&format!("<h2>{}</h2>\n<ul>\n", escape(&section.heading)));
+        for item in &section.items {
+            let star = if item.is_starred { " ★" } else { "" };
+            out.push_str(&format!("<li><a href=\"{}\">{}</a>{}", escape(&item.link), escape(&item.title), star));
+            if let Some(summary) = &item.summary {
+                out.push_str(&format!("<p>{}</p>", escape(summary)));
+            }
+            if let Some(note) = coverage_note(item.also_covered_by) {
+                out.push_str(&format!("<p><em>{}</em></p>", note));
+            }
+            out.push_str("</li>\n");
+        }
+        out.push_str("</ul>\n");
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+// Compile the unread entries saved between `since` and `until` into a digest and store it
+pub async fn generate_digest<C: ConnectionTrait>(
+    db: &C,
+    config: &DigestConfig,
+    since: NaiveDateTime,
+    until: NaiveDateTime,
+) -> Result<digest::Model, String> {
+    if since >= until {
+        return Err("Digest range must end after it starts".to_string());
+    }
+
+    let entries = FeedEntry::find()
+        .filter(feed_entry::Column::IsRead.eq(false))
+        .filter(feed_entry::Column::IsHidden.eq(false))
+        .filter(feed_entry::Column::CreatedAt.gte(since))
+        .filter(feed_entry::Column::CreatedAt.lt(until))
+        .filter(
+            Condition::any()
+                .add(feed_entry::Column::SnoozedUntil.is_null())
+                .add(feed_entry::Column::SnoozedUntil.lte(until)),
+        )
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch digest entries: {}", e))?;
+
+    let feeds: HashMap<i32, feed::Model> = Feed::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feeds: {}", e))?
+        .into_iter()
+        .map(|feed| (feed.id, feed))
+        .collect();
+    let folders: HashMap<i32, folder::Model> = Folder::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch folders: {}", e))?
+        .into_iter()
+        .map(|folder| (folder.id, folder))
+        .collect();
+
+    let mut story_cluster_ids: Vec<i32> = entries.iter().filter_map(|entry| entry.story_cluster_id).collect();
+    story_cluster_ids.sort_unstable();
+    story_cluster_ids.dedup();
+    let coverage = load_cluster_feed_counts(db, story_cluster_ids).await?;
+
+    let sections = build_digest_sections(entries, &feeds, &folders, &coverage, config);
+    let entry_count: usize = sections.iter().map(|section| section.items.len()).sum();
+    let title = format!("Digest for {}", until.format("%Y-%m-%d"));
+
+    let new_digest = digest::ActiveModel {
+        title: ActiveValue::Set(title.clone()),
+        range_start: ActiveValue::Set(since),
+        range_end: ActiveValue::Set(until),
+        entry_count: ActiveValue::Set(entry_count as i32),
+        markdown: ActiveValue::Set(render_digest_markdown(&title, &sections)),
+        html: ActiveValue::Set(render_digest_html(&title, &sections)),
+        created_at: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+        ..Default::default()
+    };
+
+    new_digest
+        .insert(db)
+        .await
+        .map_err(|e| format!("Failed to save digest: {}", e))
+}
+
+// Today's scheduled digest time in UTC, or None when that local time doesn't exist (DST gap)
+fn scheduled_time_today(hour: u32) -> Option<NaiveDateTime> {
+    let local = Local::now().date_naive().and_hms_opt(hour, 0, 0)?;
+    Local.from_local_datetime(&local).earliest().map(|time| time.naive_utc())
+}
+
+pub fn scheduled_digest_due(
+    last_generated_at: Option<NaiveDateTime>,
+    scheduled_at: NaiveDateTime,
+    now: NaiveDateTime,
+) -> bool {
+    now >= scheduled_at && last_generated_at.is_none_or(|last_generated_at| last_generated_at < scheduled_at)
+}
+
+// Generate the day's digest once its hour has passed. Called from the scheduler loop;
+// returns the digest when one was generated.
+pub async fn generate_scheduled_digest_if_due<C: ConnectionTrait>(
+    db: &C,
+    config: &DigestConfig,
+) -> Result<Option<digest::Model>, String> {
+    if !config.scheduled {
+        return Ok(None);
+    }
+    let Some(scheduled_at) = scheduled_time_today(config.hour) else {
+        return Ok(None);
+    };
+
+    let now = chrono::Utc::now().naive_utc();
+    let mut schedule: DigestScheduleState = get_setting_or(db, DIGEST_SCHEDULE, DigestScheduleState::default()).await;
+    if !scheduled_digest_due(schedule.last_generated_at, scheduled_at, now) {
+        return Ok(None);
+    }
+
+    let digest = generate_digest(db, config, now - chrono::Duration::days(1), now).await?;
+    println!("📰 Generated digest with {} entries", digest.entry_count);
+
+    schedule.last_generated_at = Some(now);
+    set_setting_value(db, DIGEST_SCHEDULE, &schedule).await?;
+    Ok(Some(digest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::fixtures;
+
+    fn entry(id: i32, feed_id: i32, title: &str, is_starred: bool, story_cluster_id: Option<i32>) -> feed_entry::Model {
+        let created_at = NaiveDateTime::parse_from_str("2024-06-01 08:00:00", "%Y-%m-%d %H:%M:%S").unwrap()
+            + chrono::Duration::minutes(id as i64);
+        feed_entry::Model {
+            title: title.to_string(),
+            description: Some("<p>One two three four five six</p>".to_string()),
+            created_at,
+            updated_at: created_at,
+            is_starred,
+            guid: id.to_string(),
+            story_cluster_id,
+            ..fixtures::feed_entry(id, feed_id)
+        }
+    }
+
+    fn feed(id: i32, title: &str) -> feed::Model {
+        feed::Model {
+            title: Some(title.to_string()),
+            ..fixtures::feed(id)
+        }
+    }
+
+    #[test]
+    fn test_sections_keep_top_entries_per_feed() {
+        let feeds = HashMap::from([(1, feed(1, "Beta")), (2, feed(2, "Alpha"))]);
+        let entries = vec![
+            entry(1, 1, "Old", false, None),
+            entry(2, 1, "Newer", false, None),
+            entry(3, 1, "Widely covered", false, Some(9)),
+            entry(4, 1, "Starred", true, None),
+            entry(5, 2, "Only one", false, None),
+        ];
+        let coverage = HashMap::from([(9, 3)]);
+        let config = DigestConfig {
+            entries_per_group: 3,
+            summary_words: 3,
+            ..Default::default()
+        };
+
+        let sections = build_digest_sections(entries, &feeds, &HashMap::new(), &coverage, &config);
+
+        assert_eq!(sections.iter().map(|section| section.heading.as_str()).collect::<Vec<_>>(), vec!["Alpha", "Beta"]);
+        let titles: Vec<&str> = sections[1].items.iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(titles, vec!["Starred", "Widely covered", "Newer"]);
+        assert_eq!(sections[1].items[1].also_covered_by, 2);
+        assert_eq!(sections[1].items[0].summary.as_deref(), Some("One two three…"));
+    }
+
+    #[test]
+    fn test_rendered_digest_escapes_titles() {
+        let sections = vec![DigestSection {
+            heading: "News & Views".to_string(),
+            items: vec![DigestItem {
+                title: "<script> *bold*".to_string(),
+                link: "https://example.com/a (1)".to_string(),
+                summary: None,
+                is_starred: false,
+                also_covered_by: 1,
+            }],
+        }];
+
+        let html = render_digest_html("Digest", &sections);
+        let markdown = render_digest_markdown("Digest", &sections);
+
+        assert!(html.contains("<h2>News &amp; Views</h2>"));
+        assert!(html.contains("&lt;script&gt; *bold*"));
+        assert!(markdown.contains("- [<script> \\*bold\\*](https://example.com/a%20%281%29)\n  _Also covered by 1 other feed_\n"));
+        assert!(render_digest_markdown("Digest", &[]).contains("Nothing new."));
+    }
+
+    #[test]
+    fn test_scheduled_digest_runs_once_after_its_hour() {
+        let at = |time: &str| NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S").unwrap();
+        let scheduled_at = at("2024-06-02 07:00:00");
+
+        assert!(!scheduled_digest_due(None, scheduled_at, at("2024-06-02 06:59:00")));
+        assert!(scheduled_digest_due(None, scheduled_at, at("2024-06-02 07:01:00")));
+        assert!(scheduled_digest_due(Some(at("2024-06-01 07:01:00")), scheduled_at, at("2024-06-02 09:00:00")));
+        assert!(!scheduled_digest_due(Some(at("2024-06-02 07:01:00")), scheduled_at, at("2024-06-02 09:00:00")));
+    }
+}
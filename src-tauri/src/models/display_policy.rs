@@ -0,0 +1,135 @@
+use std::collections::{BTreeMap, HashMap};
+use chrono::NaiveDateTime;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use crate::entities::{prelude::*, *};
+use crate::models::requests::EntryQueryRequest;
+use crate::models::settings::load_display_policy_config;
+
+// How long entries stay in entry lists once read. Individual feeds can override it; the
+// entry list queries apply it, so every view agrees on what's listed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayPolicyConfig {
+    // 0 keeps read entries listed
+    pub hide_read_after_days: u32,
+}
+
+// The policy with every feed's override, ready to filter a query
+#[derive(Debug, Clone, Default)]
+pub struct DisplayPolicy {
+    pub hide_read_after_days: u32,
+    pub feed_overrides: HashMap<i32, u32>,
+}
+
+pub async fn load_display_policy<C: ConnectionTrait>(db: &C) -> Result<DisplayPolicy, String> {
+    let config = load_display_policy_config(db).await;
+    let overrides: Vec<(i32, i32)> = Feed::find()
+        .select_only()
+        .columns([feed::Column::Id, feed::Column::HideReadAfterDays])
+        .filter(feed::Column::HideReadAfterDays.is_not_null())
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feeds: {}", e))?;
+
+    Ok(DisplayPolicy {
+        hide_read_after_days: config.hide_read_after_days,
+        feed_overrides: overrides.into_iter().map(|(feed_id, days)| (feed_id, days.max(0) as u32)).collect(),
+    })
+}
+
+impl DisplayPolicy {
+    // Which entries stay listed, or None when the policy hides nothing. Unread and starred
+    // entries always stay, as do entries read before read times were recorded.
+    fn listed_condition(&self, now: NaiveDateTime) -> Option<Condition> {
+        if self.hide_read_after_days == 0 && self.feed_overrides.values().all(|days| *days == 0) {
+            return None;
+        }
+        let read_since = |days: u32| feed_entry::Column::ReadAt.gte(now - chrono::Duration::days(days as i64));
+
+        let mut by_days: BTreeMap<u32, Vec<i32>> = BTreeMap::new();
+        for (feed_id, days) in &self.feed_overrides {
+            by_days.entry(*days).or_default().push(*feed_id);
+        }
+        let mut overridden: Vec<i32> = self.feed_overrides.keys().copied().collect();
+        overridden.sort_unstable();
+
+        let mut listed = Condition::any()
+            .add(feed_entry::Column::IsRead.eq(false))
+            .add(feed_entry::Column::IsStarred.eq(true))
+            .add(feed_entry::Column::ReadAt.is_null());
+        for (days, mut feed_ids) in by_days {
+            feed_ids.sort_unstable();
+            let in_feeds = feed_entry::Column::FeedId.is_in(feed_ids);
+            listed = listed.add(match days {
+                0 => Condition::all().add(in_feeds),
+                days => Condition::all().add(in_feeds).add(read_since(days)),
+            });
+        }
+        let mut elsewhere = Condition::all();
+        if !overridden.is_empty() {
+            elsewhere = elsewhere.add(feed_entry::Column::FeedId.is_not_in(overridden));
+        }
+        if self.hide_read_after_days > 0 {
+            elsewhere = elsewhere.add(read_since(self.hide_read_after_days));
+        }
+        Some(listed.add(elsewhere))
+    }
+}
+
+// Leave out the entries the display policy hides. Views asking for read or hidden entries
+// explicitly list them all.
+pub fn apply_display_policy(
+    query: Select<feed_entry::Entity>,
+    request: &EntryQueryRequest,
+    policy: &DisplayPolicy,
+    now: NaiveDateTime,
+) -> Select<feed_entry::Entity> {
+    if request.is_read == Some(true) || request.hidden == Some(true) {
+        return query;
+    }
+    match policy.listed_condition(now) {
+        Some(listed) => query.filter(listed),
+        None => query,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::entry_query::build_entry_query;
+
+    fn sql(request: &EntryQueryRequest, policy: &DisplayPolicy) -> String {
+        let now = NaiveDateTime::parse_from_str("2024-06-11 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        apply_display_policy(build_entry_query(request, now).unwrap(), request, policy, now)
+            .build(DbBackend::Postgres)
+            .to_string()
+    }
+
+    #[test]
+    fn test_read_entries_drop_out_after_the_feed_or_global_cutoff() {
+        let policy = DisplayPolicy {
+            hide_read_after_days: 7,
+            feed_overrides: HashMap::from([(3, 0), (5, 1), (4, 1)]),
+        };
+        let sql = sql(&EntryQueryRequest::default(), &policy);
+
+        assert!(sql.contains(r#""feed_entry"."is_read" = FALSE OR "feed_entry"."is_starred" = TRUE OR "feed_entry"."read_at" IS NULL"#));
+        // Feed 3 keeps read entries listed, feeds 4 and 5 list them for a day, the rest for a week
+        assert!(sql.contains(r#"OR "feed_entry"."feed_id" IN (3) OR"#));
+        assert!(sql.contains(r#""feed_entry"."feed_id" IN (4, 5) AND "feed_entry"."read_at" >= '2024-06-10 12:00:00'"#));
+        assert!(sql.contains(r#""feed_entry"."feed_id" NOT IN (3, 4, 5) AND "feed_entry"."read_at" >= '2024-06-04 12:00:00'"#));
+    }
+
+    #[test]
+    fn test_policy_is_skipped_when_it_hides_nothing_or_read_entries_are_asked_for() {
+        let unfiltered = sql(&EntryQueryRequest::default(), &DisplayPolicy::default());
+        assert!(!unfiltered.contains("read_at"));
+
+        let policy = DisplayPolicy { hide_read_after_days: 7, ..Default::default() };
+        let read_view = EntryQueryRequest { is_read: Some(true), ..Default::default() };
+        assert!(!sql(&read_view, &policy).contains("read_at"));
+        assert!(sql(&EntryQueryRequest::default(), &policy).contains(r#""feed_entry"."read_at" >= '2024-06-04 12:00:00'"#));
+    }
+}
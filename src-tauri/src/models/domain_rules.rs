@@ -0,0 +1,222 @@
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use url::Url;
+use crate::entities::{prelude::*, *};
+use crate::models::mutes::muted_entry_ids;
+use crate::models::state_log::{record_state_changes, StateField, LOCAL_ORIGIN};
+
+// What happens to entries linking to a blocked domain when they are saved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DomainAction {
+    // Leave the entry out of entry lists
+    #[default]
+    Hide,
+    // Keep the entry listed but mark it read
+    MarkRead,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockedDomain {
+    pub domain: String,
+    #[serde(default)]
+    pub action: DomainAction,
+}
+
+// Domains whose entries are hidden or marked read as they are saved. A domain covers its
+// subdomains; allowed domains are exempt, e.g. blocking example.com but allowing
+// blog.example.com.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DomainRulesConfig {
+    pub blocked: Vec<BlockedDomain>,
+    pub allowed: Vec<String>,
+    // Refuse to subscribe to feeds hosted on a blocked domain
+    pub block_subscriptions: bool,
+}
+
+// The bare, lowercase host for a domain typed as "Example.com", "*.example.com",
+// "www.example.com" or a whole URL
+pub fn normalize_domain(input: &str) -> Option<String> {
+    let input = input.trim();
+    let host = match Url::parse(input) {
+        Ok(url) if url.has_host() => url.host_str()?.to_string(),
+        _ => input.split(['/', '?', '#']).next()?.to_string(),
+    };
+    let host = host.to_lowercase();
+    let host = host.trim_start_matches("*.").trim_start_matches("www.").trim_end_matches('.');
+    let valid = !host.is_empty()
+        && host.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-'));
+    valid.then(|| host.to_string())
+}
+
+// Whether a host is the domain or one of its subdomains
+fn host_in_domain(host: &str, domain: &str) -> bool {
+    host == domain || host.strip_suffix(domain).is_some_and(|rest| rest.ends_with('.'))
+}
+
+impl DomainRulesConfig {
+    // The same rules with every domain normalized and duplicates dropped (the first block of a
+    // domain wins), or an error naming the first domain that isn't one
+    pub fn normalized(&self) -> Result<Self, String> {
+        let normalize = |domain: &str| normalize_domain(domain).ok_or_else(|| format!("Invalid domain: {}", domain));
+
+        let mut blocked: Vec<BlockedDomain> = Vec::new();
+        for rule in &self.blocked {
+            let domain = normalize(&rule.domain)?;
+            if !blocked.iter().any(|existing| existing.domain == domain) {
+                blocked.push(BlockedDomain { domain, action: rule.action });
+            }
+        }
+        let mut allowed: Vec<String> = Vec::new();
+        for domain in &self.allowed {
+            let domain = normalize(domain)?;
+            if !allowed.contains(&domain) {
+                allowed.push(domain);
+            }
+        }
+
+        Ok(Self {
+            blocked,
+            allowed,
+            block_subscriptions: self.block_subscriptions,
+        })
+    }
+
+    // The blocked domain a link falls under, unless it's allowed. The most specific blocked
+    // domain decides, so a subdomain can be hidden while its parent is only marked read.
+    fn matching_rule(&self, link: &str) -> Option<&BlockedDomain> {
+        let host = Url::parse(link).ok()?.host_str()?.to_lowercase();
+        let host = host.trim_end_matches('.');
+        if self.allowed.iter().any(|domain| host_in_domain(host, domain)) {
+            return None;
+        }
+        self.blocked
+            .iter()
+            .filter(|rule| host_in_domain(host, &rule.domain))
+            .max_by_key(|rule| rule.domain.len())
+    }
+
+    // What to do with an entry linking to `link`, if its domain is blocked
+    pub fn action_for(&self, link: &str) -> Option<DomainAction> {
+        self.matching_rule(link).map(|rule| rule.action)
+    }
+
+    // The blocked domain a new subscription's URL falls under, if subscribing there is refused
+    pub fn blocked_subscription(&self, url: &str) -> Option<&str> {
+        if !self.block_subscriptions {
+            return None;
+        }
+        self.matching_rule(url).map(|rule| rule.domain.as_str())
+    }
+}
+
+// How many existing entries applying the rules changed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DomainRulesApplied {
+    pub hidden: u64,
+    pub unhidden: u64,
+    pub marked_read: u64,
+}
+
+// Apply the rules to entries already saved: hide entries under hidden domains, show again
+// the ones no rule or mute hides anymore, and mark entries under mark-read domains read
+pub async fn apply_domain_rules<C: ConnectionTrait>(db: &C, config: &DomainRulesConfig) -> Result<DomainRulesApplied, String> {
+    let entries: Vec<(i32, String, bool, bool)> = FeedEntry::find()
+        .select_only()
+        .columns([
+            feed_entry::Column::Id,
+            feed_entry::Column::Link,
+            feed_entry::Column::IsHidden,
+            feed_entry::Column::IsRead,
+        ])
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entries: {}", e))?;
+
+    let mut to_hide = Vec::new();
+    let mut to_unhide = Vec::new();
+    let mut to_mark_read = Vec::new();
+    for (id, link, is_hidden, is_read) in entries {
+        let action = config.action_for(&link);
+        let hide = action == Some(DomainAction::Hide);
+        if hide && !is_hidden {
+            to_hide.push(id);
+        } else if !hide && is_hidden {
+            to_unhide.push(id);
+        }
+        if action == Some(DomainAction::MarkRead) && !is_read {
+            to_mark_read.push(id);
+        }
+    }
+
+    let now = chrono::Utc::now().naive_utc();
+    let muted = muted_entry_ids(db, &to_unhide, now).await?;
+    to_unhide.retain(|id| !muted.contains(id));
+
+    let set_column = |ids: Vec<i32>, column: feed_entry::Column, value: bool| async move {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        FeedEntry::update_many()
+            .col_expr(column, sea_query::Expr::value(value))
+            .filter(feed_entry::Column::Id.is_in(ids))
+            .exec(db)
+            .await
+            .map(|result| result.rows_affected)
+            .map_err(|e| format!("Failed to update feed entries: {}", e))
+    };
+
+    record_state_changes(db, &to_mark_read, StateField::Read, true, now, LOCAL_ORIGIN).await?;
+
+    Ok(DomainRulesApplied {
+        hidden: set_column(to_hide, feed_entry::Column::IsHidden, true).await?,
+        unhidden: set_column(to_unhide, feed_entry::Column::IsHidden, false).await?,
+        marked_read: set_column(to_mark_read, feed_entry::Column::IsRead, true).await?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> DomainRulesConfig {
+        DomainRulesConfig {
+            blocked: vec![
+                BlockedDomain { domain: "Example.com".to_string(), action: DomainAction::MarkRead },
+                BlockedDomain { domain: "https://www.ads.example.com/x".to_string(), action: DomainAction::Hide },
+                BlockedDomain { domain: "*.spam.test".to_string(), action: DomainAction::Hide },
+            ],
+            allowed: vec!["blog.example.com".to_string()],
+            block_subscriptions: true,
+        }
+        .normalized()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_domains_are_normalized() {
+        let rules = rules();
+        let domains: Vec<&str> = rules.blocked.iter().map(|rule| rule.domain.as_str()).collect();
+        assert_eq!(domains, vec!["example.com", "ads.example.com", "spam.test"]);
+        assert_eq!(normalize_domain("not a domain"), None);
+        assert!(DomainRulesConfig { allowed: vec!["".to_string()], ..Default::default() }.normalized().is_err());
+    }
+
+    #[test]
+    fn test_most_specific_rule_decides_and_allowed_domains_are_exempt() {
+        let rules = rules();
+        assert_eq!(rules.action_for("https://example.com/post"), Some(DomainAction::MarkRead));
+        assert_eq!(rules.action_for("https://news.example.com/post"), Some(DomainAction::MarkRead));
+        assert_eq!(rules.action_for("https://cdn.ads.example.com/post"), Some(DomainAction::Hide));
+        assert_eq!(rules.action_for("https://blog.example.com/post"), None);
+        assert_eq!(rules.action_for("https://notexample.com/post"), None);
+        assert_eq!(rules.action_for("https://spam.test/"), Some(DomainAction::Hide));
+
+        assert_eq!(rules.blocked_subscription("https://ads.example.com/feed.xml"), Some("ads.example.com"));
+        assert_eq!(rules.blocked_subscription("https://blog.example.com/feed.xml"), None);
+        let open = DomainRulesConfig { block_subscriptions: false, ..rules };
+        assert_eq!(open.blocked_subscription("https://ads.example.com/feed.xml"), None);
+    }
+}
@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_http::reqwest::{self, header, StatusCode};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{RwLock, Semaphore};
+use crate::models::operations::{Operation, OperationKind};
+
+// Subdirectory of the data directory that enclosures are saved to
+pub const DOWNLOADS_DIR: &str = "downloads";
+
+// Event emitted as a download is queued, makes progress, or finishes
+pub const DOWNLOAD_PROGRESS_EVENT: &str = "download:progress";
+
+// Downloads beyond this many wait in the queue
+const MAX_CONCURRENT_DOWNLOADS: usize = 2;
+
+// Minimum time between progress events for a single download
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(500);
+
+// Suffix of a download that hasn't finished yet. Partial files are kept on cancel or
+// failure so the next attempt can resume with a range request.
+const PARTIAL_SUFFIX: &str = ".part";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadStatus {
+    Queued,
+    Downloading,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+impl DownloadStatus {
+    pub fn is_active(&self) -> bool {
+        matches!(self, DownloadStatus::Queued | DownloadStatus::Downloading)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadInfo {
+    pub entry_id: i32,
+    pub url: String,
+    pub file_path: String,
+    pub status: DownloadStatus,
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>,
+    pub error: Option<String>,
+}
+
+struct DownloadHandle {
+    info: DownloadInfo,
+    cancel_requested: Arc<AtomicBool>,
+    // The download's entry in the operations list, while it's queued or running
+    operation: Option<Operation>,
+}
+
+// Where an entry's enclosure is saved: "<entry id>-<file name from the URL>" under the
+// downloads directory, with anything unsafe in the name replaced
+pub fn download_file_name(entry_id: i32, url: &str) -> String {
+    let url_name = url::Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            url.path_segments()
+                .and_then(|mut segments| segments.next_back().map(str::to_string))
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "enclosure".to_string());
+
+    let safe_name: String = url_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+
+    format!("{}-{}", entry_id, safe_name.trim_start_matches('.'))
+}
+
+fn partial_path(path: &Path) -> PathBuf {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(PARTIAL_SUFFIX);
+    PathBuf::from(partial)
+}
+
+// Total size from a Content-Range header such as "bytes 100-199/1000"
+pub fn content_range_total(content_range: &str) -> Option<u64> {
+    content_range.rsplit('/').next()?.trim().parse().ok()
+}
+
+// Queues enclosure downloads and runs a few at a time, tracking each one's progress
+// in memory. Files live under the data directory, so completed downloads survive restarts.
+#[derive(Clone)]
+pub struct DownloadManager {
+    downloads: Arc<RwLock<HashMap<i32, DownloadHandle>>>,
+    slots: Arc<Semaphore>,
+    http_client: reqwest::Client,
+}
+
+impl Default for DownloadManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DownloadManager {
+    pub fn new() -> Self {
+        // No overall timeout: large episodes can take a long time on slow connections
+        let http_client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(30))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            downloads: Arc::new(RwLock::new(HashMap::new())),
+            slots: Arc::new(Semaphore::new(MAX_CONCURRENT_DOWNLOADS)),
+            http_client,
+        }
+    }
+
+    // Queue an entry's enclosure for download to `path` and start it in the background.
+    // Returns the existing download if one for the entry is already queued or running.
+    pub async fn enqueue(&self, app: AppHandle, entry_id: i32, url: String, path: PathBuf) -> DownloadInfo {
+        let mut downloads = self.downloads.write().await;
+        if let Some(existing) = downloads.get(&entry_id) {
+            if existing.info.status.is_active() {
+                return existing.info.clone();
+            }
+        }
+
+        let mut info = DownloadInfo {
+            entry_id,
+            url: url.clone(),
+            file_path: path.to_string_lossy().to_string(),
+            status: DownloadStatus::Queued,
+            downloaded_bytes: 0,
+            total_bytes: None,
+            error: None,
+        };
+
+        if let Ok(metadata) = tokio::fs::metadata(&path).await {
+            info.status = DownloadStatus::Completed;
+            info.downloaded_bytes = metadata.len();
+            info.total_bytes = Some(metadata.len());
+        }
+
+        let cancel_requested = Arc::new(AtomicBool::new(false));
+        let operation = (info.status == DownloadStatus::Queued).then(|| {
+            let operation = Operation::start(&app, OperationKind::Download, 0);
+            let manager = self.clone();
+            operation.on_cancel(move || {
+                tauri::async_runtime::spawn(async move {
+                    manager.cancel(entry_id).await;
+                });
+            });
+            operation
+        });
+        downloads.insert(entry_id, DownloadHandle {
+            info: info.clone(),
+            cancel_requested: cancel_requested.clone(),
+            operation,
+        });
+        drop(downloads);
+
+        if info.status == DownloadStatus::Queued {
+            println!("📥 Queued download of {} for entry {}", url, entry_id);
+            self.emit_progress(&app, &info);
+            let manager = self.clone();
+            tauri::async_runtime::spawn(async move {
+                manager.run_download(app, entry_id, url, path, cancel_requested).await;
+            });
+        }
+
+        info
+    }
+
+    // Stop a queued or running download. The partial file is kept so it can be resumed.
+    pub async fn cancel(&self, entry_id: i32) -> Option<DownloadInfo> {
+        let mut downloads = self.downloads.write().await;
+        let handle = downloads.get_mut(&entry_id)?;
+        if handle.info.status.is_active() {
+            handle.cancel_requested.store(true, Ordering::SeqCst);
+            handle.info.status = DownloadStatus::Cancelled;
+            if let Some(operation) = handle.operation.take() {
+                operation.cancel();
+                operation.finish(Ok(()));
+            }
+        }
+        Some(handle.info.clone())
+    }
+
+    // Every download this session, most recently queued last
+    pub async fn list(&self) -> Vec<DownloadInfo> {
+        let downloads = self.downloads.read().await;
+        let mut infos: Vec<DownloadInfo> = downloads.values().map(|handle| handle.info.clone()).collect();
+        infos.sort_by_key(|info| info.entry_id);
+        infos
+    }
+
+    pub async fn has_active(&self) -> bool {
+        self.downloads.read().await.values().any(|handle| handle.info.status.is_active())
+    }
+
+    fn emit_progress(&self, app: &AppHandle, info: &DownloadInfo) {
+        if let Err(e) = app.emit(DOWNLOAD_PROGRESS_EVENT, info) {
+            eprintln!("❌ Failed to emit download progress: {}", e);
+        }
+    }
+
+    // Apply a change to a download's info and report it, unless it was cancelled meanwhile
+    async fn update(&self, app: &AppHandle, entry_id: i32, change: impl FnOnce(&mut DownloadInfo)) {
+        let mut downloads = self.downloads.write().await;
+        let Some(handle) = downloads.get_mut(&entry_id) else {
+            return;
+        };
+        if handle.info.status == DownloadStatus::Cancelled {
+            return;
+        }
+        change(&mut handle.info);
+        self.emit_progress(app, &handle.info);
+
+        let info = &handle.info;
+        match (&handle.operation, info.status) {
+            (Some(operation), DownloadStatus::Completed) => {
+                operation.finish(Ok(()));
+            }
+            (Some(operation), DownloadStatus::Failed) => {
+                operation.finish(Err(info.error.clone().unwrap_or_default()));
+            }
+            (Some(operation), _) => {
+                operation.set_progress(info.downloaded_bytes as usize, info.total_bytes.unwrap_or(0) as usize);
+            }
+            (None, _) => {}
+        }
+    }
+
+    async fn run_download(
+        &self,
+        app: AppHandle,
+        entry_id: i32,
+        url: String,
+        path: PathBuf,
+        cancel_requested: Arc<AtomicBool>,
+    ) {
+        let Ok(_slot) = self.slots.acquire().await else {
+            return;
+        };
+        if cancel_requested.load(Ordering::SeqCst) {
+            return;
+        }
+
+        self.update(&app, entry_id, |info| info.status = DownloadStatus::Downloading).await;
+
+        let result = self.download_to_file(&app, entry_id, &url, &path, &cancel_requested).await;
+
+        match result {
+            Ok(total_bytes) => {
+                println!("✅ Downloaded {} ({} bytes)", path.display(), total_bytes);
+                self.update(&app, entry_id, |info| {
+                    info.status = DownloadStatus::Completed;
+                    info.downloaded_bytes = total_bytes;
+                    info.total_bytes = Some(total_bytes);
+                })
+                .await;
+            }
+            Err(_) if cancel_requested.load(Ordering::SeqCst) => {
+                println!("⏹️ Download for entry {} cancelled", entry_id);
+                if let Some(handle) = self.downloads.read().await.get(&entry_id) {
+                    self.emit_progress(&app, &handle.info);
+                }
+            }
+            Err(e) => {
+                eprintln!("❌ Download of {} failed: {}", url, e);
+                self.update(&app, entry_id, |info| {
+                    info.status = DownloadStatus::Failed;
+                    info.error = Some(e);
+                })
+                .await;
+            }
+        }
+    }
+
+    // Stream the enclosure into a partial file, resuming from whatever is already on disk
+    // when the server supports range requests, then move it into place. Returns the file size.
+    async fn download_to_file(
+        &self,
+        app: &AppHandle,
+        entry_id: i32,
+        url: &str,
+        path: &Path,
+        cancel_requested: &AtomicBool,
+    ) -> Result<u64, String> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create downloads directory: {}", e))?;
+        }
+
+        let partial = partial_path(path);
+        let existing_bytes = tokio::fs::metadata(&partial).await.map(|m| m.len()).unwrap_or(0);
+
+        let mut request = self.http_client.get(url);
+        if existing_bytes > 0 {
+            request = request.header(header::RANGE, format!("bytes={}-", existing_bytes));
+        }
+
+        let mut response = request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to start download: {}", e))?;
+
+        // The partial file already holds everything the server has
+        if existing_bytes > 0 && response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+            tokio::fs::rename(&partial, path)
+                .await
+                .map_err(|e| format!("Failed to save download: {}", e))?;
+            return Ok(existing_bytes);
+        }
+        if !response.status().is_success() {
+            return Err(format!("Download failed with HTTP {}", response.status().as_u16()));
+        }
+
+        // Servers that ignore the range send the whole file again, so start over
+        let resumed = existing_bytes > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+        let total_bytes = if resumed {
+            response
+                .headers()
+                .get(header::CONTENT_RANGE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(content_range_total)
+        } else {
+            response.content_length()
+        };
+        if resumed {
+            println!("⏯️ Resuming download of {} at {} bytes", url, existing_bytes);
+        }
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resumed)
+            .truncate(!resumed)
+            .open(&partial)
+            .await
+            .map_err(|e| format!("Failed to open download file: {}", e))?;
+
+        let mut downloaded_bytes = if resumed { existing_bytes } else { 0 };
+        let mut last_progress_event = Instant::now();
+
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Download interrupted: {}", e))?
+        {
+            if cancel_requested.load(Ordering::SeqCst) {
+                let _ = file.flush().await;
+                return Err("Download cancelled".to_string());
+            }
+
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| format!("Failed to write download: {}", e))?;
+            downloaded_bytes += chunk.len() as u64;
+
+            if last_progress_event.elapsed() >= PROGRESS_EVENT_INTERVAL {
+                last_progress_event = Instant::now();
+                self.update(app, entry_id, |info| {
+                    info.downloaded_bytes = downloaded_bytes;
+                    info.total_bytes = total_bytes;
+                })
+                .await;
+            }
+        }
+
+        file.flush()
+            .await
+            .map_err(|e| format!("Failed to write download: {}", e))?;
+        drop(file);
+
+        tokio::fs::rename(&partial, path)
+            .await
+            .map_err(|e| format!("Failed to save download: {}", e))?;
+
+        Ok(downloaded_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_download_file_name() {
+        assert_eq!(
+            download_file_name(7, "https://cdn.example.com/shows/episode%2012.mp3?token=abc"),
+            "7-episode_2012.mp3"
+        );
+        assert_eq!(download_file_name(8, "https://example.com/"), "8-enclosure");
+        assert_eq!(download_file_name(9, "https://example.com/..%2F..%2Fetc"), "9-_2F.._2Fetc");
+        assert_eq!(
+            partial_path(Path::new("/data/downloads/7-a.mp3")),
+            PathBuf::from("/data/downloads/7-a.mp3.part")
+        );
+    }
+
+    #[test]
+    fn test_content_range_total() {
+        assert_eq!(content_range_total("bytes 100-199/1000"), Some(1000));
+        // The total may be unknown
+        assert_eq!(content_range_total("bytes 100-199/*"), None);
+    }
+}
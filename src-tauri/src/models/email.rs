@@ -0,0 +1,182 @@
+use std::time::Duration;
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use crate::entities::digest;
+use crate::models::secrets::{get_secret_value, SecretKind};
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Name of the SMTP password in the keychain, stored as an IntegrationApiKey secret
+pub const SMTP_PASSWORD_SECRET: &str = "smtp";
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    // TLS from the first byte, usually port 465
+    Tls,
+    // Plain connection upgraded with STARTTLS, usually port 587
+    #[default]
+    StartTls,
+    // Unencrypted, only for a relay on this machine or a trusted network
+    None,
+}
+
+// Where digests are emailed from and to. Email is off until a server and addresses are set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailConfig {
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub security: SmtpSecurity,
+    // Login for servers that require one; the password is kept in the keychain
+    pub username: Option<String>,
+    pub from: String,
+    pub to: String,
+    // Email each scheduled digest as soon as it is generated
+    pub send_scheduled_digest: bool,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            smtp_host: String::new(),
+            smtp_port: 587,
+            security: SmtpSecurity::StartTls,
+            username: None,
+            from: String::new(),
+            to: String::new(),
+            send_scheduled_digest: false,
+        }
+    }
+}
+
+fn parse_mailbox(address: &str, field: &str) -> Result<Mailbox, String> {
+    address
+        .trim()
+        .parse()
+        .map_err(|e| format!("Invalid {} address '{}': {}", field, address.trim(), e))
+}
+
+impl EmailConfig {
+    pub fn is_configured(&self) -> bool {
+        !self.smtp_host.trim().is_empty() && !self.from.trim().is_empty() && !self.to.trim().is_empty()
+    }
+
+    // An unconfigured (empty) config is valid; it just can't send anything
+    pub fn validate(&self) -> Result<(), String> {
+        if self.smtp_port == 0 {
+            return Err("SMTP port must be greater than 0".to_string());
+        }
+        if !self.from.trim().is_empty() {
+            parse_mailbox(&self.from, "sender")?;
+        }
+        if !self.to.trim().is_empty() {
+            parse_mailbox(&self.to, "recipient")?;
+        }
+        Ok(())
+    }
+}
+
+// The digest as an email with its Markdown as the plain-text part and its HTML as the rich part
+pub fn build_digest_message(config: &EmailConfig, digest: &digest::Model) -> Result<Message, String> {
+    Message::builder()
+        .from(parse_mailbox(&config.from, "sender")?)
+        .to(parse_mailbox(&config.to, "recipient")?)
+        .subject(&digest.title)
+        .multipart(MultiPart::alternative_plain_html(digest.markdown.clone(), digest.html.clone()))
+        .map_err(|e| format!("Failed to build digest email: {}", e))
+}
+
+async fn smtp_transport(config: &EmailConfig) -> Result<AsyncSmtpTransport<Tokio1Executor>, String> {
+    let host = config.smtp_host.trim();
+    let builder = match config.security {
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+        SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+        SmtpSecurity::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)),
+    }
+    .map_err(|e| format!("Invalid SMTP server '{}': {}", host, e))?
+    .port(config.smtp_port)
+    .timeout(Some(SMTP_TIMEOUT));
+
+    let username = config.username.as_deref().map(str::trim).filter(|username| !username.is_empty());
+    let builder = match username {
+        Some(username) => {
+            let password = get_secret_value(SecretKind::IntegrationApiKey, SMTP_PASSWORD_SECRET.to_string())
+                .await?
+                .ok_or("No SMTP password has been saved")?;
+            builder.credentials(Credentials::new(username.to_string(), password))
+        }
+        None => builder,
+    };
+    Ok(builder.build())
+}
+
+pub async fn send_digest_email(config: &EmailConfig, digest: &digest::Model) -> Result<(), String> {
+    if !config.is_configured() {
+        return Err("Email delivery is not configured".to_string());
+    }
+
+    let message = build_digest_message(config, digest)?;
+    smtp_transport(config)
+        .await?
+        .send(message)
+        .await
+        .map_err(|e| format!("Failed to send digest email: {}", e))?;
+
+    println!("📧 Emailed digest {} to {}", digest.id, config.to.trim());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn configured() -> EmailConfig {
+        EmailConfig {
+            smtp_host: "smtp.example.com".to_string(),
+            from: "Reader <reader@example.com>".to_string(),
+            to: "me@example.com".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(EmailConfig::default().validate().is_ok());
+        assert!(!EmailConfig::default().is_configured());
+        assert!(configured().validate().is_ok());
+        assert!(configured().is_configured());
+
+        let bad_recipient = EmailConfig {
+            to: "not an address".to_string(),
+            ..configured()
+        };
+        assert!(bad_recipient.validate().is_err());
+        assert!(EmailConfig { smtp_port: 0, ..configured() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_digest_message_has_plain_and_html_parts() {
+        let created_at = chrono::NaiveDateTime::parse_from_str("2024-06-01 07:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let digest = digest::Model {
+            id: 1,
+            title: "Digest for 2024-06-01".to_string(),
+            range_start: created_at - chrono::Duration::days(1),
+            range_end: created_at,
+            entry_count: 1,
+            markdown: "# Digest for 2024-06-01".to_string(),
+            html: "<h1>Digest for 2024-06-01</h1>".to_string(),
+            created_at,
+        };
+
+        let message = String::from_utf8(build_digest_message(&configured(), &digest).unwrap().formatted()).unwrap();
+
+        assert!(message.contains("Subject: Digest for 2024-06-01"));
+        assert!(message.contains("To: me@example.com"));
+        assert!(message.contains("Content-Type: multipart/alternative"));
+        assert!(message.contains("Content-Type: text/plain"));
+        assert!(message.contains("<h1>Digest for 2024-06-01</h1>"));
+    }
+}
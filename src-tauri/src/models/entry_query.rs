@@ -0,0 +1,650 @@
+use chrono::{DateTime as ChronoDateTime, NaiveDateTime};
+use sea_orm::*;
+use sea_orm::sea_query::{Expr, Func, LikeExpr, OnConflict, Query, SimpleExpr};
+use crate::entities::{prelude::*, *};
+use crate::models::requests::{AdvancedEntryQueryRequest, BulkEntryAction, BulkEntryFilter, EntryQueryRequest, EntryView, TagMatch};
+
+// The ranked timeline moves entries up by an hour for every time an entry of the same feed
+// was opened recently, up to two days, so favorite feeds surface without burying news
+const RANKING_WINDOW_DAYS: i64 = 30;
+const MAX_RANKING_BOOST_HOURS: i64 = 48;
+
+fn parse_date(value: &str, field: &str) -> Result<NaiveDateTime, String> {
+    ChronoDateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.naive_utc())
+        .map_err(|e| format!("Invalid {}: {}", field, e))
+}
+
+// Build the entry list query for a combination of filters. Folder and tag filters are
+// subqueries rather than joins so every entry appears once and pagination stays stable.
+pub fn build_entry_query(
+    request: &EntryQueryRequest,
+    now: NaiveDateTime,
+) -> Result<Select<feed_entry::Entity>, String> {
+    let mut query = FeedEntry::find();
+
+    if let Some(folder_id) = request.folder_id {
+        query = query.filter(
+            feed_entry::Column::FeedId.in_subquery(
+                Query::select()
+                    .column(feed::Column::Id)
+                    .from(Feed)
+                    .and_where(feed::Column::FolderId.eq(folder_id))
+                    .to_owned(),
+            ),
+        );
+    }
+    if let Some(feed_id) = request.feed_id {
+        query = query.filter(feed_entry::Column::FeedId.eq(feed_id));
+    }
+    if let Some(tag_id) = request.tag_id {
+        query = query.filter(
+            feed_entry::Column::Id.in_subquery(
+                Query::select()
+                    .column(entry_tag::Column::EntryId)
+                    .from(EntryTag)
+                    .and_where(entry_tag::Column::TagId.eq(tag_id))
+                    .to_owned(),
+            ),
+        );
+    }
+    // An explicit read filter outranks the view
+    let is_read = request.is_read.or((request.view == Some(EntryView::Unread)).then_some(false));
+    if let Some(is_read) = is_read {
+        query = query.filter(feed_entry::Column::IsRead.eq(is_read));
+    }
+    if let Some(is_starred) = request.is_starred {
+        query = query.filter(feed_entry::Column::IsStarred.eq(is_starred));
+    }
+    // Opening an entry records when, so viewed entries are the ones with a last open time
+    match request.viewed {
+        Some(true) => {
+            query = query.filter(feed_entry::Column::LastOpenedAt.is_not_null());
+        }
+        Some(false) => {
+            query = query.filter(feed_entry::Column::LastOpenedAt.is_null());
+        }
+        None => {}
+    }
+    match request.snoozed {
+        Some(true) => {
+            query = query.filter(feed_entry::Column::SnoozedUntil.gt(now));
+        }
+        Some(false) => {
+            query = query.filter(
+                Condition::any()
+                    .add(feed_entry::Column::SnoozedUntil.is_null())
+                    .add(feed_entry::Column::SnoozedUntil.lte(now)),
+            );
+        }
+        None => {}
+    }
+    query = query.filter(feed_entry::Column::IsHidden.eq(request.hidden.unwrap_or(false)));
+    if let Some(published_after) = &request.published_after {
+        let published_after = parse_date(published_after, "published_after")?;
+        query = query.filter(feed_entry::Column::PublishedAt.gte(published_after));
+    }
+    if let Some(published_before) = &request.published_before {
+        let published_before = parse_date(published_before, "published_before")?;
+        query = query.filter(feed_entry::Column::PublishedAt.lt(published_before));
+    }
+    // Entries with no reading time yet are left out of either bound
+    if let Some(min_reading_minutes) = request.min_reading_minutes {
+        query = query.filter(feed_entry::Column::ReadingTimeMinutes.gte(min_reading_minutes));
+    }
+    if let Some(max_reading_minutes) = request.max_reading_minutes {
+        query = query.filter(feed_entry::Column::ReadingTimeMinutes.lte(max_reading_minutes));
+    }
+
+    if request.sort.as_deref() == Some("ranked") {
+        let opened_since = now - chrono::Duration::days(RANKING_WINDOW_DAYS);
+        query = query.order_by(
+            Expr::cust_with_values(
+                format!(
+                    r#"COALESCE("feed_entry"."published_at", "feed_entry"."created_at") + LEAST((SELECT COALESCE(SUM("opened"."open_count"), 0) FROM "feed_entry" AS "opened" WHERE "opened"."feed_id" = "feed_entry"."feed_id" AND "opened"."last_opened_at" >= $1), {}) * INTERVAL '1 hour'"#,
+                    MAX_RANKING_BOOST_HOURS
+                ),
+                [opened_since],
+            ),
+            Order::Desc,
+        );
+    }
+    query = if request.sort.as_deref() == Some("oldest") {
+        query
+            .order_by_asc(feed_entry::Column::PublishedAt)
+            .order_by_asc(feed_entry::Column::Id)
+    } else {
+        query
+            .order_by_desc(feed_entry::Column::PublishedAt)
+            .order_by_desc(feed_entry::Column::Id)
+    };
+
+    if let Some(limit) = request.limit {
+        query = query.limit(limit);
+    }
+    if let Some(offset) = request.offset {
+        query = query.offset(offset);
+    }
+
+    Ok(query)
+}
+
+// Build the entry list query for a smart view: the plain filters as in build_entry_query, with
+// the folder, feed and tag lists and the text query added on as further subqueries.
+pub fn build_advanced_entry_query(
+    request: &AdvancedEntryQueryRequest,
+    now: NaiveDateTime,
+) -> Result<Select<feed_entry::Entity>, String> {
+    let mut query = build_entry_query(&request.base, now)?;
+
+    if !request.folder_ids.is_empty() {
+        query = query.filter(
+            feed_entry::Column::FeedId.in_subquery(
+                Query::select()
+                    .column(feed::Column::Id)
+                    .from(Feed)
+                    .and_where(feed::Column::FolderId.is_in(request.folder_ids.clone()))
+                    .to_owned(),
+            ),
+        );
+    }
+    if !request.feed_ids.is_empty() {
+        query = query.filter(feed_entry::Column::FeedId.is_in(request.feed_ids.clone()));
+    }
+    if !request.tag_ids.is_empty() {
+        let mut tagged = Query::select()
+            .column(entry_tag::Column::EntryId)
+            .from(EntryTag)
+            .and_where(entry_tag::Column::TagId.is_in(request.tag_ids.clone()))
+            .to_owned();
+        if request.tag_match == TagMatch::All {
+            let mut tag_ids = request.tag_ids.clone();
+            tag_ids.sort_unstable();
+            tag_ids.dedup();
+            tagged
+                .group_by_col(entry_tag::Column::EntryId)
+                .and_having(Expr::col(entry_tag::Column::TagId).count_distinct().eq(tag_ids.len() as i64));
+        }
+        query = query.filter(feed_entry::Column::Id.in_subquery(tagged));
+    }
+    if let Some(text) = &request.text {
+        query = query.filter(text_search_condition(text));
+    }
+
+    Ok(query)
+}
+
+// Keep one entry per story cluster, its earliest saved member, along with every entry that
+// isn't in a cluster. When the query's other filters exclude that member, the story is left
+// out too (e.g. an unread view hides a story already read through another feed). Members
+// hidden by domain rules never represent a story, so blocking one source keeps the others.
+pub fn only_story_representatives(query: Select<feed_entry::Entity>) -> Select<feed_entry::Entity> {
+    query.filter(Expr::cust(
+        r#""feed_entry"."story_cluster_id" IS NULL OR "feed_entry"."id" = (SELECT MIN("cluster_member"."id") FROM "feed_entry" AS "cluster_member" WHERE "cluster_member"."story_cluster_id" = "feed_entry"."story_cluster_id" AND NOT "cluster_member"."is_hidden")"#,
+    ))
+}
+
+// The new timestamp for a flag like is_read when bulk-setting it: entries that already had the
+// flag keep when they got it, and clearing the flag clears the timestamp
+fn flag_set_at(flag: feed_entry::Column, set_at: feed_entry::Column, value: bool, now: NaiveDateTime) -> SimpleExpr {
+    if value {
+        Expr::case(Expr::col(flag).eq(true), Expr::col(set_at)).finally(now).into()
+    } else {
+        Expr::value(Option::<NaiveDateTime>::None)
+    }
+}
+
+// Case-insensitive substring match on an entry's title or description. Blank text matches everything.
+fn text_search_condition(search: &str) -> Condition {
+    let search = search.trim();
+    if search.is_empty() {
+        return Condition::all();
+    }
+    let escaped = search
+        .to_lowercase()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    let pattern = || LikeExpr::new(format!("%{}%", escaped)).escape('\\');
+    Condition::any()
+        .add(Expr::expr(Func::lower(Expr::col((feed_entry::Entity, feed_entry::Column::Title)))).like(pattern()))
+        .add(Expr::expr(Func::lower(Expr::col((feed_entry::Entity, feed_entry::Column::Description)))).like(pattern()))
+}
+
+pub(crate) fn bulk_entry_condition(filter: &BulkEntryFilter, now: NaiveDateTime) -> Condition {
+    let mut condition = Condition::all();
+
+    if let Some(feed_id) = filter.feed_id {
+        condition = condition.add(feed_entry::Column::FeedId.eq(feed_id));
+    }
+    if let Some(is_read) = filter.is_read {
+        condition = condition.add(feed_entry::Column::IsRead.eq(is_read));
+    }
+    if let Some(days) = filter.older_than_days {
+        let cutoff = now - chrono::Duration::days(days.max(0));
+        condition = condition.add(
+            Condition::any()
+                .add(feed_entry::Column::PublishedAt.lt(cutoff))
+                .add(
+                    Condition::all()
+                        .add(feed_entry::Column::PublishedAt.is_null())
+                        .add(feed_entry::Column::CreatedAt.lt(cutoff)),
+                ),
+        );
+    }
+    if let Some(search) = filter.search.as_deref() {
+        condition = condition.add(text_search_condition(search));
+    }
+
+    condition
+}
+
+// Build the single statement that applies a bulk action to every entry matching the filter.
+// Deleting requires at least one filter so an empty filter can't wipe the library.
+pub fn build_bulk_entry_statement(
+    filter: &BulkEntryFilter,
+    action: &BulkEntryAction,
+    now: NaiveDateTime,
+    backend: DbBackend,
+) -> Result<Statement, String> {
+    let condition = bulk_entry_condition(filter, now);
+
+    let statement = match action {
+        BulkEntryAction::Delete => {
+            if filter.is_empty() {
+                return Err("Bulk delete requires at least one filter".to_string());
+            }
+            FeedEntry::delete_many().filter(condition).build(backend)
+        }
+        BulkEntryAction::MarkRead { is_read } => FeedEntry::update_many()
+            .col_expr(feed_entry::Column::IsRead, Expr::value(*is_read))
+            .col_expr(feed_entry::Column::ReadAt, flag_set_at(feed_entry::Column::IsRead, feed_entry::Column::ReadAt, *is_read, now))
+            .col_expr(feed_entry::Column::UpdatedAt, Expr::value(now))
+            .filter(condition)
+            .build(backend),
+        BulkEntryAction::Star { is_starred } => FeedEntry::update_many()
+            .col_expr(feed_entry::Column::IsStarred, Expr::value(*is_starred))
+            .col_expr(feed_entry::Column::StarredAt, flag_set_at(feed_entry::Column::IsStarred, feed_entry::Column::StarredAt, *is_starred, now))
+            .col_expr(feed_entry::Column::UpdatedAt, Expr::value(now))
+            .filter(condition)
+            .build(backend),
+        BulkEntryAction::Tag { tag_id } => {
+            let insert = Query::insert()
+                .into_table(EntryTag)
+                .columns([entry_tag::Column::EntryId, entry_tag::Column::TagId, entry_tag::Column::CreatedAt])
+                .select_from(
+                    Query::select()
+                        .column((feed_entry::Entity, feed_entry::Column::Id))
+                        .expr(Expr::value(*tag_id))
+                        .expr(Expr::value(now))
+                        .from(FeedEntry)
+                        .cond_where(condition)
+                        .to_owned(),
+                )
+                .map_err(|e| format!("Failed to build bulk tag: {}", e))?
+                .on_conflict(
+                    OnConflict::columns([entry_tag::Column::EntryId, entry_tag::Column::TagId])
+                        .do_nothing()
+                        .to_owned(),
+                )
+                .to_owned();
+            backend.build(&insert)
+        }
+    };
+
+    Ok(statement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sql(request: &EntryQueryRequest) -> String {
+        let now = NaiveDateTime::parse_from_str("2024-06-01 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        build_entry_query(request, now)
+            .unwrap()
+            .build(DbBackend::Postgres)
+            .to_string()
+    }
+
+    #[test]
+    fn test_unfiltered_query_lists_newest_first() {
+        let sql = sql(&EntryQueryRequest::default());
+
+        assert!(sql.contains(r#"WHERE "feed_entry"."is_hidden" = FALSE ORDER BY"#));
+        assert!(sql.ends_with(r#"ORDER BY "feed_entry"."published_at" DESC, "feed_entry"."id" DESC"#));
+    }
+
+    #[test]
+    fn test_unread_in_folder_uses_feed_subquery() {
+        let sql = sql(&EntryQueryRequest {
+            folder_id: Some(3),
+            is_read: Some(false),
+            ..Default::default()
+        });
+
+        assert!(sql.contains(r#""feed_entry"."feed_id" IN (SELECT "id" FROM "feed" WHERE "feed"."folder_id" = 3)"#));
+        assert!(sql.contains(r#""feed_entry"."is_read" = FALSE"#));
+        assert!(!sql.contains("JOIN"));
+    }
+
+    #[test]
+    fn test_starred_with_tag_uses_entry_tag_subquery() {
+        let sql = sql(&EntryQueryRequest {
+            tag_id: Some(7),
+            is_starred: Some(true),
+            ..Default::default()
+        });
+
+        assert!(sql.contains(r#""feed_entry"."id" IN (SELECT "entry_id" FROM "entry_tag" WHERE "entry_tag"."tag_id" = 7)"#));
+        assert!(sql.contains(r#""feed_entry"."is_starred" = TRUE"#));
+    }
+
+    #[test]
+    fn test_snoozed_filters() {
+        let snoozed = sql(&EntryQueryRequest {
+            snoozed: Some(true),
+            ..Default::default()
+        });
+        let not_snoozed = sql(&EntryQueryRequest {
+            snoozed: Some(false),
+            ..Default::default()
+        });
+
+        assert!(snoozed.contains(r#""feed_entry"."snoozed_until" > '2024-06-01 12:00:00'"#));
+        assert!(not_snoozed.contains(
+            r#""feed_entry"."snoozed_until" IS NULL OR "feed_entry"."snoozed_until" <= '2024-06-01 12:00:00'"#
+        ));
+    }
+
+    #[test]
+    fn test_all_filters_combine_into_one_query() {
+        let sql = sql(&EntryQueryRequest {
+            folder_id: Some(1),
+            feed_id: Some(2),
+            tag_id: Some(3),
+            is_read: Some(false),
+            is_starred: Some(true),
+            viewed: Some(true),
+            snoozed: Some(false),
+            hidden: Some(true),
+            view: None,
+            published_after: Some("2024-05-01T00:00:00Z".to_string()),
+            published_before: Some("2024-06-01T00:00:00Z".to_string()),
+            min_reading_minutes: Some(5),
+            max_reading_minutes: Some(15),
+            sort: None,
+            limit: Some(50),
+            offset: Some(100),
+        });
+
+        assert_eq!(sql.matches("SELECT").count(), 3);
+        assert!(sql.contains(r#""feed_entry"."feed_id" = 2"#));
+        assert!(sql.contains(r#""feed_entry"."last_opened_at" IS NOT NULL"#));
+        assert!(sql.contains(r#""feed_entry"."is_hidden" = TRUE"#));
+        assert!(sql.contains(r#""feed_entry"."published_at" >= '2024-05-01 00:00:00'"#));
+        assert!(sql.contains(r#""feed_entry"."published_at" < '2024-06-01 00:00:00'"#));
+        assert!(sql.contains(r#""feed_entry"."reading_time_minutes" >= 5"#));
+        assert!(sql.contains(r#""feed_entry"."reading_time_minutes" <= 15"#));
+        assert!(sql.ends_with("LIMIT 50 OFFSET 100"));
+    }
+
+    fn advanced_sql(request: &AdvancedEntryQueryRequest) -> String {
+        let now = NaiveDateTime::parse_from_str("2024-06-01 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        build_advanced_entry_query(request, now)
+            .unwrap()
+            .build(DbBackend::Postgres)
+            .to_string()
+    }
+
+    #[test]
+    fn test_smart_view_with_all_tags_is_one_query() {
+        let sql = advanced_sql(&AdvancedEntryQueryRequest {
+            base: EntryQueryRequest {
+                is_read: Some(false),
+                limit: Some(20),
+                ..Default::default()
+            },
+            folder_ids: vec![1, 2],
+            feed_ids: vec![9],
+            tag_ids: vec![4, 5, 4],
+            tag_match: TagMatch::All,
+            text: Some("Rust_lang".to_string()),
+        });
+
+        assert!(sql.contains(r#""feed_entry"."feed_id" IN (SELECT "id" FROM "feed" WHERE "feed"."folder_id" IN (1, 2))"#));
+        assert!(sql.contains(r#""feed_entry"."feed_id" IN (9)"#));
+        assert!(sql.contains(
+            r#""feed_entry"."id" IN (SELECT "entry_id" FROM "entry_tag" WHERE "entry_tag"."tag_id" IN (4, 5, 4) GROUP BY "entry_id" HAVING COUNT(DISTINCT "tag_id") = 2)"#
+        ));
+        assert!(sql.contains(r#"LIKE E'%rust\\_lang%' ESCAPE E'\\'"#));
+        assert!(sql.contains(r#""feed_entry"."is_read" = FALSE"#));
+        assert!(sql.ends_with("LIMIT 20"));
+    }
+
+    #[test]
+    fn test_any_tag_skips_grouping() {
+        let sql = advanced_sql(&AdvancedEntryQueryRequest {
+            tag_ids: vec![4, 5],
+            ..Default::default()
+        });
+
+        assert!(sql.contains(r#""feed_entry"."id" IN (SELECT "entry_id" FROM "entry_tag" WHERE "entry_tag"."tag_id" IN (4, 5))"#));
+        assert!(!sql.contains("HAVING"));
+    }
+
+    #[test]
+    fn test_ranked_sort_boosts_recently_opened_feeds() {
+        let sql = sql(&EntryQueryRequest {
+            sort: Some("ranked".to_string()),
+            ..Default::default()
+        });
+
+        assert!(sql.contains(r#""opened"."feed_id" = "feed_entry"."feed_id" AND "opened"."last_opened_at" >= '2024-05-02 12:00:00'), 48) * INTERVAL '1 hour' DESC"#));
+        assert!(sql.ends_with(r#"DESC, "feed_entry"."published_at" DESC, "feed_entry"."id" DESC"#));
+    }
+
+    #[test]
+    fn test_deduplicated_query_keeps_one_entry_per_story() {
+        let now = NaiveDateTime::parse_from_str("2024-06-01 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let request = EntryQueryRequest {
+            is_read: Some(false),
+            limit: Some(20),
+            ..Default::default()
+        };
+
+        let sql = only_story_representatives(build_entry_query(&request, now).unwrap())
+            .build(DbBackend::Postgres)
+            .to_string();
+
+        assert!(sql.contains(r#""feed_entry"."is_read" = FALSE AND "feed_entry"."is_hidden" = FALSE AND ("feed_entry"."story_cluster_id" IS NULL OR "feed_entry"."id" = (SELECT MIN("cluster_member"."id")"#));
+        assert!(sql.contains(r#"AND NOT "cluster_member"."is_hidden")"#));
+        assert!(sql.ends_with(r#"ORDER BY "feed_entry"."published_at" DESC, "feed_entry"."id" DESC LIMIT 20"#));
+    }
+
+    fn bulk_sql(filter: &BulkEntryFilter, action: &BulkEntryAction) -> Result<String, String> {
+        let now = NaiveDateTime::parse_from_str("2024-06-01 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        build_bulk_entry_statement(filter, action, now, DbBackend::Postgres).map(|statement| statement.to_string())
+    }
+
+    #[test]
+    fn test_bulk_mark_read_of_old_entries_in_feed() {
+        let sql = bulk_sql(
+            &BulkEntryFilter {
+                feed_id: Some(4),
+                older_than_days: Some(30),
+                ..Default::default()
+            },
+            &BulkEntryAction::MarkRead { is_read: true },
+        )
+        .unwrap();
+
+        assert!(sql.starts_with(r#"UPDATE "feed_entry" SET "is_read" = TRUE, "read_at" = (CASE WHEN ("is_read" = TRUE) THEN "read_at" ELSE '2024-06-01 12:00:00' END)"#));
+        assert!(sql.contains(r#""feed_entry"."feed_id" = 4"#));
+        assert!(sql.contains(r#""feed_entry"."published_at" < '2024-05-02 12:00:00'"#));
+        assert!(sql.contains(r#""feed_entry"."created_at" < '2024-05-02 12:00:00'"#));
+        // Unstarring clears when entries were starred
+        assert!(bulk_sql(&BulkEntryFilter::default(), &BulkEntryAction::Star { is_starred: false })
+            .unwrap()
+            .contains(r#""starred_at" = NULL"#));
+    }
+
+    #[test]
+    fn test_bulk_tag_is_a_single_insert_select() {
+        let sql = bulk_sql(
+            &BulkEntryFilter {
+                search: Some("Rust_lang".to_string()),
+                ..Default::default()
+            },
+            &BulkEntryAction::Tag { tag_id: 9 },
+        )
+        .unwrap();
+
+        assert!(sql.starts_with(r#"INSERT INTO "entry_tag" ("entry_id", "tag_id", "created_at") SELECT "feed_entry"."id", 9"#));
+        assert!(sql.contains(r#"LOWER("feed_entry"."title") LIKE E'%rust\\_lang%' ESCAPE E'\\'"#));
+        assert!(sql.ends_with("ON CONFLICT (\"entry_id\", \"tag_id\") DO NOTHING"));
+    }
+
+    #[test]
+    fn test_bulk_delete_requires_a_filter() {
+        assert!(bulk_sql(&BulkEntryFilter::default(), &BulkEntryAction::Delete).is_err());
+        assert!(bulk_sql(
+            &BulkEntryFilter {
+                search: Some("  ".to_string()),
+                ..Default::default()
+            },
+            &BulkEntryAction::Delete,
+        )
+        .is_err());
+
+        let sql = bulk_sql(
+            &BulkEntryFilter {
+                is_read: Some(true),
+                ..Default::default()
+            },
+            &BulkEntryAction::Delete,
+        )
+        .unwrap();
+        assert_eq!(sql, r#"DELETE FROM "feed_entry" WHERE "feed_entry"."is_read" = TRUE"#);
+    }
+
+    #[test]
+    fn test_invalid_date_is_rejected() {
+        let request = EntryQueryRequest {
+            published_after: Some("last tuesday".to_string()),
+            ..Default::default()
+        };
+
+        assert!(build_entry_query(&request, chrono::Utc::now().naive_utc()).is_err());
+    }
+
+    // Runs against the database TEST_DATABASE_URL names, which must have the migrations applied
+    #[tokio::test]
+    async fn test_viewed_filter_combines_with_folders_on_a_real_database() {
+        let Ok(database_url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL is not set, skipping");
+            return;
+        };
+        let db = Database::connect(database_url).await.unwrap();
+        let now = chrono::Utc::now().naive_utc();
+        let run = now.and_utc().timestamp_nanos_opt().unwrap_or_default();
+
+        let folder = folder::ActiveModel {
+            name: ActiveValue::Set(format!("Viewed filters {}", run)),
+            created_at: ActiveValue::Set(now),
+            updated_at: ActiveValue::Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let mut feed_ids = Vec::new();
+        for (name, folder_id) in [("in-folder", Some(folder.id)), ("elsewhere", None)] {
+            let feed = feed::ActiveModel {
+                url: ActiveValue::Set(format!("https://viewed.test/{}/{}.xml", run, name)),
+                folder_id: ActiveValue::Set(folder_id),
+                created_at: ActiveValue::Set(now),
+                updated_at: ActiveValue::Set(now),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+            feed_ids.push(feed.id);
+        }
+
+        // (feed, title, read, starred, opened), newest first
+        let entries = [
+            (feed_ids[0], "unread, never opened", false, false, false),
+            (feed_ids[0], "unread, opened", false, false, true),
+            (feed_ids[0], "read and starred, opened", true, true, true),
+            (feed_ids[0], "read, never opened", true, false, false),
+            (feed_ids[1], "unread, opened elsewhere", false, false, true),
+        ];
+        for (age, (feed_id, title, is_read, is_starred, opened)) in entries.into_iter().enumerate() {
+            feed_entry::ActiveModel {
+                feed_id: ActiveValue::Set(feed_id),
+                title: ActiveValue::Set(title.to_string()),
+                link: ActiveValue::Set(format!("https://viewed.test/{}/{}", run, age)),
+                guid: ActiveValue::Set(format!("https://viewed.test/{}/{}", run, age)),
+                published_at: ActiveValue::Set(Some(now - chrono::Duration::hours(age as i64))),
+                created_at: ActiveValue::Set(now),
+                updated_at: ActiveValue::Set(now),
+                is_read: ActiveValue::Set(is_read),
+                is_starred: ActiveValue::Set(is_starred),
+                open_count: ActiveValue::Set(opened as i32),
+                last_opened_at: ActiveValue::Set(opened.then_some(now)),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+
+        let connection = &db;
+        let titles = move |request: EntryQueryRequest| async move {
+            build_entry_query(&request, now)
+                .unwrap()
+                .all(connection)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.title)
+                .collect::<Vec<_>>()
+        };
+        let unread_viewed_in_folder = titles(EntryQueryRequest {
+            folder_id: Some(folder.id),
+            is_read: Some(false),
+            viewed: Some(true),
+            ..Default::default()
+        })
+        .await;
+        let unviewed_in_folder = titles(EntryQueryRequest {
+            folder_id: Some(folder.id),
+            viewed: Some(false),
+            ..Default::default()
+        })
+        .await;
+        let starred_viewed_in_folder = titles(EntryQueryRequest {
+            folder_id: Some(folder.id),
+            is_starred: Some(true),
+            viewed: Some(true),
+            ..Default::default()
+        })
+        .await;
+        let viewed_elsewhere = titles(EntryQueryRequest {
+            feed_id: Some(feed_ids[1]),
+            viewed: Some(true),
+            ..Default::default()
+        })
+        .await;
+        // Deleting the feeds takes their entries with them
+        Feed::delete_many().filter(feed::Column::Id.is_in(feed_ids)).exec(&db).await.unwrap();
+        Folder::delete_by_id(folder.id).exec(&db).await.unwrap();
+
+        assert_eq!(unread_viewed_in_folder, vec!["unread, opened"]);
+        assert_eq!(unviewed_in_folder, vec!["unread, never opened", "read, never opened"]);
+        assert_eq!(starred_viewed_in_folder, vec!["read and starred, opened"]);
+        assert_eq!(viewed_elsewhere, vec!["unread, opened elsewhere"]);
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::{HashMap, HashSet};
+use chrono::{NaiveDateTime, SecondsFormat};
+use quick_xml::escape::escape;
+use url::Url;
+use crate::entities::{annotation, feed, feed_entry, folder};
+use crate::models::canonical_links::share_link;
+
+fn feed_outline(feed: &feed::Model, indent: &str) -> String {
+    let title = escape(feed.title.as_deref().unwrap_or(&feed.url)).to_string();
+    let description = feed
+        .description
+        .as_deref()
+        .map(|description| format!(" description=\"{}\"", escape(description)))
+        .unwrap_or_default();
+    format!(
+        "{}<outline type=\"rss\" text=\"{}\" title=\"{}\"{} xmlUrl=\"{}\"/>\n",
+        indent,
+        title,
+        title,
+        description,
+        escape(&feed.url)
+    )
+}
+
+// Write a folder and everything inside it as nested <outline> elements
+fn write_folder(
+    out: &mut String,
+    folder: &folder::Model,
+    subfolders: &HashMap<Option<i32>, Vec<&folder::Model>>,
+    feeds: &HashMap<Option<i32>, Vec<&feed::Model>>,
+    depth: usize,
+    written: &mut HashSet<i32>,
+) {
+    if !written.insert(folder.id) {
+        return;
+    }
+
+    let indent = "  ".repeat(depth + 2);
+    let name = escape(&folder.name);
+    out.push_str(&format!("{}<outline text=\"{}\" title=\"{}\">\n", indent, name, name));
+
+    for subfolder in subfolders.get(&Some(folder.id)).into_iter().flatten() {
+        write_folder(out, subfolder, subfolders, feeds, depth + 1, written);
+    }
+    for feed in feeds.get(&Some(folder.id)).into_iter().flatten() {
+        out.push_str(&feed_outline(feed, &"  ".repeat(depth + 3)));
+    }
+
+    out.push_str(&format!("{}</outline>\n", indent));
+}
+
+// Export subscriptions as OPML 2.0, with folders (and subfolders) as nested outlines
+pub fn build_opml(folders: &[folder::Model], feeds: &[feed::Model]) -> String {
+    let mut subfolders: HashMap<Option<i32>, Vec<&folder::Model>> = HashMap::new();
+    for folder in folders {
+        subfolders.entry(folder.parent_id).or_default().push(folder);
+    }
+    for children in subfolders.values_mut() {
+        children.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    let mut feeds_by_folder: HashMap<Option<i32>, Vec<&feed::Model>> = HashMap::new();
+    for feed in feeds {
+        feeds_by_folder.entry(feed.folder_id).or_default().push(feed);
+    }
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n");
+    out.push_str("  <head>\n    <title>Subscriptions</title>\n  </head>\n  <body>\n");
+
+    let mut written = HashSet::new();
+    for folder in subfolders.get(&None).into_iter().flatten() {
+        write_folder(&mut out, folder, &subfolders, &feeds_by_folder, 0, &mut written);
+    }
+    for feed in feeds_by_folder.get(&None).into_iter().flatten() {
+        out.push_str(&feed_outline(feed, "    "));
+    }
+
+    out.push_str("  </body>\n</opml>\n");
+    out
+}
+
+pub(crate) fn escape_markdown_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '[' | ']' | '*' | '_' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// A link made safe to use as a Markdown link target
+pub(crate) fn markdown_link_target(link: &str) -> String {
+    link.replace(' ', "%20").replace('(', "%28").replace(')', "%29")
+}
+
+// Entries are exported as Markdown: this title, then each entry's section with its link,
+// publish date and the reader's notes as block quotes. Entries without notes are still listed
+// so the export reads as a reading list.
+pub const ENTRIES_MARKDOWN_HEADER: &str = "# Reading notes\n";
+
+// One entry's section of a Markdown export
+pub fn entry_markdown(entry: &feed_entry::Model, annotations: &HashMap<i32, Vec<annotation::Model>>) -> String {
+    let mut out = format!("\n## [{}]({})\n", escape_markdown_text(&entry.title), markdown_link_target(share_link(entry)));
+    if let Some(published_at) = entry.published_at {
+        out.push_str(&format!("\nPublished {}\n", published_at.format("%Y-%m-%d")));
+    }
+
+    for note in annotations.get(&entry.id).into_iter().flatten() {
+        out.push('\n');
+        for line in note.note.lines() {
+            if line.is_empty() {
+                out.push_str(">\n");
+            } else {
+                out.push_str(&format!("> {}\n", line));
+            }
+        }
+        out.push_str(&format!("\n_Noted {}_\n", note.created_at.format("%Y-%m-%d %H:%M")));
+    }
+
+    out
+}
+
+fn atom_date(date: NaiveDateTime) -> String {
+    date.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+// Atom ids must be IRIs: the publisher's guid when it is one (usually the permalink),
+// otherwise an id made from the entry's row id
+fn atom_entry_id(entry: &feed_entry::Model) -> String {
+    match Url::parse(&entry.guid) {
+        Ok(_) => entry.guid.clone(),
+        Err(_) => format!("urn:reader:entry:{}", entry.id),
+    }
+}
+
+// Republish entries as an Atom 1.0 feed. Each entry keeps its original link and names the
+// feed it came from as its <source>; `id` identifies the republished feed itself.
+pub fn build_atom_feed(
+    id: &str,
+    title: &str,
+    self_url: Option<&str>,
+    entries: &[feed_entry::Model],
+    feeds: &HashMap<i32, feed::Model>,
+    generated_at: NaiveDateTime,
+) -> String {
+    let updated = entries.iter().map(|entry| entry.updated_at).max().unwrap_or(generated_at);
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    out.push_str(&format!("  <id>{}</id>\n", escape(id)));
+    out.push_str(&format!("  <title>{}</title>\n", escape(title)));
+    out.push_str(&format!("  <updated>{}</updated>\n", atom_date(updated)));
+    // Atom requires an author; the entries' own authors aren't stored
+    out.push_str("  <author><name>Reader</name></author>\n");
+    out.push_str("  <generator>Reader</generator>\n");
+    if let Some(self_url) = self_url {
+        out.push_str(&format!("  <link rel=\"self\" href=\"{}\"/>\n", escape(self_url)));
+    }
+
+    for entry in entries {
+        out.push_str("  <entry>\n");
+        out.push_str(&format!("    <id>{}</id>\n", escape(&atom_entry_id(entry))));
+        out.push_str(&format!("    <title>{}</title>\n", escape(&entry.title)));
+        out.push_str(&format!("    <link rel=\"alternate\" href=\"{}\"/>\n", escape(share_link(entry))));
+        out.push_str(&format!("    <updated>{}</updated>\n", atom_date(entry.updated_at)));
+        if let Some(published_at) = entry.published_at {
+            out.push_str(&format!("    <published>{}</published>\n", atom_date(published_at)));
+        }
+        if let Some(description) = &entry.description {
+            out.push_str(&format!("    <summary type=\"html\">{}</summary>\n", escape(description)));
+        }
+        if let Some(content) = &entry.content {
+            out.push_str(&format!("    <content type=\"html\">{}</content>\n", escape(content)));
+        }
+        if let Some(feed) = feeds.get(&entry.feed_id) {
+            out.push_str(&format!(
+                "    <source>\n      <id>{}</id>\n      <title>{}</title>\n      <link rel=\"self\" href=\"{}\"/>\n    </source>\n",
+                escape(&feed.url),
+                escape(feed.title.as_deref().unwrap_or(&feed.url)),
+                escape(&feed.url)
+            ));
+        }
+        out.push_str("  </entry>\n");
+    }
+
+    out.push_str("</feed>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::fixtures::{self, at};
+    use crate::models::importers::parse_opml;
+
+    fn folder(id: i32, name: &str, parent_id: Option<i32>) -> folder::Model {
+        let now = chrono::Utc::now().naive_utc();
+        folder::Model {
+            id,
+            name: name.to_string(),
+            created_at: now,
+            updated_at: now,
+            parent_id,
+        }
+    }
+
+    fn feed(id: i32, url: &str, title: Option<&str>, folder_id: Option<i32>) -> feed::Model {
+        feed::Model {
+            url: url.to_string(),
+            title: title.map(str::to_string),
+            folder_id,
+            ..fixtures::feed(id)
+        }
+    }
+
+    fn entry(id: i32, title: &str, link: &str) -> feed_entry::Model {
+        feed_entry::Model {
+            title: title.to_string(),
+            link: link.to_string(),
+            guid: link.to_string(),
+            published_at: Some(at("2024-05-01 09:00")),
+            created_at: at("2024-05-01 10:00"),
+            updated_at: at("2024-05-01 10:00"),
+            is_read: true,
+            ..fixtures::feed_entry(id, 1)
+        }
+    }
+
+    #[test]
+    fn test_nested_folders_round_trip_through_opml() {
+        let folders = vec![folder(1, "Tech", None), folder(2, "Rust & Friends", Some(1))];
+        let feeds = vec![
+            feed(1, "https://blog.rust-lang.org/feed.xml", Some("Rust Blog"), Some(2)),
+            feed(2, "https://example.com/tech.xml", None, Some(1)),
+            feed(3, "https://example.com/loose.xml", Some("Loose"), None),
+        ];
+
+        let opml = build_opml(&folders, &feeds);
+        let subscriptions = parse_opml(&opml).unwrap();
+
+        assert_eq!(subscriptions.feeds.len(), 3);
+        let rust_blog = &subscriptions.feeds[0];
+        assert_eq!(rust_blog.url, "https://blog.rust-lang.org/feed.xml");
+        assert_eq!(rust_blog.folder, Some("Rust & Friends".to_string()));
+        assert_eq!(rust_blog.parent_folders, vec!["Tech".to_string()]);
+
+        assert_eq!(subscriptions.feeds[1].folder, Some("Tech".to_string()));
+        assert!(subscriptions.feeds[1].parent_folders.is_empty());
+        // Untitled feeds are labelled with their URL
+        assert_eq!(subscriptions.feeds[1].title, Some("https://example.com/tech.xml".to_string()));
+
+        assert_eq!(subscriptions.feeds[2].folder, None);
+    }
+
+    #[test]
+    fn test_entries_markdown_includes_notes() {
+        let entries = [
+            entry(1, "Ownership [explained]", "https://example.com/a_(1)"),
+            entry(2, "Unannotated", "https://example.com/b"),
+        ];
+        let mut annotations = HashMap::new();
+        annotations.insert(
+            1,
+            vec![annotation::Model {
+                id: 10,
+                entry_id: 1,
+                note: "Key point.\n\nCompare with GC.".to_string(),
+                created_at: at("2024-06-02 08:30"),
+                updated_at: at("2024-06-02 08:30"),
+            }],
+        );
+
+        let markdown: String = std::iter::once(ENTRIES_MARKDOWN_HEADER.to_string())
+            .chain(entries.iter().map(|entry| entry_markdown(entry, &annotations)))
+            .collect();
+
+        assert!(markdown.starts_with("# Reading notes\n"));
+        assert!(markdown.contains("## [Ownership \\[explained\\]](https://example.com/a_%281%29)\n\nPublished 2024-05-01\n"));
+        assert!(markdown.contains("> Key point.\n>\n> Compare with GC.\n\n_Noted 2024-06-02 08:30_\n"));
+        assert!(markdown.ends_with("## [Unannotated](https://example.com/b)\n\nPublished 2024-05-01\n"));
+    }
+
+    #[test]
+    fn test_atom_feed_parses_and_credits_sources() {
+        let mut starred = entry(1, "Fish & Chips <review>", "https://example.com/fish?a=1&b=2");
+        starred.description = Some("<p>Crispy</p>".to_string());
+        let mut untitled_source = entry(2, "Second", "https://example.org/second");
+        untitled_source.feed_id = 2;
+        untitled_source.guid = "tag-less id 42".to_string();
+        let feeds: HashMap<i32, feed::Model> = [
+            (1, feed(1, "https://example.com/feed.xml", Some("Example"), None)),
+            (2, feed(2, "https://example.org/rss", None, None)),
+        ]
+        .into_iter()
+        .collect();
+
+        let atom = build_atom_feed(
+            "urn:reader:starred",
+            "Starred entries",
+            Some("http://127.0.0.1:8787/starred.atom"),
+            &[starred, untitled_source],
+            &feeds,
+            at("2024-06-01 00:00"),
+        );
+        let parsed = feed_rs::parser::parse(atom.as_bytes()).unwrap();
+
+        assert_eq!(parsed.feed_type, feed_rs::model::FeedType::Atom);
+        assert_eq!(parsed.id, "urn:reader:starred");
+        assert_eq!(parsed.entries.len(), 2);
+        assert_eq!(parsed.entries[0].title.as_ref().unwrap().content, "Fish & Chips <review>");
+        assert_eq!(parsed.entries[0].links[0].href, "https://example.com/fish?a=1&b=2");
+        assert_eq!(parsed.entries[0].summary.as_ref().unwrap().content, "<p>Crispy</p>");
+        // Non-IRI guids are replaced so the feed stays valid
+        assert_eq!(parsed.entries[1].id, "urn:reader:entry:2");
+        assert!(atom.contains("<title>https://example.org/rss</title>"));
+        assert!(atom.contains("<updated>2024-05-01T10:00:00Z</updated>"));
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::models::http_transport::HttpTransport;
+use tokio::time::sleep;
+use crate::models::async_feed_fetcher::FeedFetchError;
+
+// Simulated network conditions for a URL or host, so retry, timeout and circuit-breaker
+// behavior can be checked deterministically. Only built with the `fault-injection` feature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FaultRule {
+    // Delay before the response arrives; counts against the request timeout
+    pub latency_ms: u64,
+    // Answer this many requests with error_status before behaving normally
+    pub error_burst: u32,
+    pub error_status: u16,
+    // Serve this body instead of making a real request
+    pub body: Option<String>,
+    // Cut the body off after this many bytes, as if the server closed the connection early
+    pub truncate_body_at: Option<usize>,
+    // Drop the connection partway through reading the body
+    pub disconnect_mid_body: bool,
+}
+
+impl Default for FaultRule {
+    fn default() -> Self {
+        Self {
+            latency_ms: 0,
+            error_burst: 0,
+            error_status: 503,
+            body: None,
+            truncate_body_at: None,
+            disconnect_mid_body: false,
+        }
+    }
+}
+
+// Rules keyed by exact URL or by host
+fn fault_rule_store() -> &'static Mutex<HashMap<String, FaultRule>> {
+    static FAULT_RULES: OnceLock<Mutex<HashMap<String, FaultRule>>> = OnceLock::new();
+    FAULT_RULES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// Apply a rule to every request for `target`, which is either a full URL or a host
+pub fn set_fault_rule(target: &str, rule: FaultRule) {
+    fault_rule_store().lock().unwrap().insert(target.to_string(), rule);
+}
+
+pub fn clear_fault_rules() {
+    fault_rule_store().lock().unwrap().clear();
+}
+
+pub fn fault_rules() -> HashMap<String, FaultRule> {
+    fault_rule_store().lock().unwrap().clone()
+}
+
+// The fault to apply to the next request for a URL, if any. An exact URL rule wins over a
+// host rule. Each call uses up one request of the rule's error burst.
+pub fn next_fault(url: &str) -> Option<FaultRule> {
+    let mut rules = fault_rule_store().lock().unwrap();
+    let host = url::Url::parse(url).ok().and_then(|url| url.host_str().map(str::to_string));
+    let key = if rules.contains_key(url) {
+        url.to_string()
+    } else {
+        host.filter(|host| rules.contains_key(host))?
+    };
+
+    let rule = rules.get_mut(&key)?;
+    let fault = FaultRule {
+        error_burst: rule.error_burst.min(1),
+        ..rule.clone()
+    };
+    rule.error_burst = rule.error_burst.saturating_sub(1);
+    Some(fault)
+}
+
+impl FaultRule {
+    // Produce the response (content type and body) a request would get under this fault
+    pub async fn respond(&self, url: &str, transport: &dyn HttpTransport) -> Result<(String, String), FeedFetchError> {
+        println!("🧪 Injecting fault for {}: {:?}", url, self);
+        sleep(Duration::from_millis(self.latency_ms)).await;
+
+        if self.error_burst > 0 {
+            return Err(FeedFetchError::HttpStatus(self.error_status));
+        }
+
+        let (content_type, mut content) = match &self.body {
+            Some(body) => ("application/rss+xml".to_string(), body.clone()),
+            None => {
+                transport.get(url).await?
+            }
+        };
+
+        if self.disconnect_mid_body {
+            return Err(FeedFetchError::NetworkError(
+                "connection closed while reading the response body".to_string(),
+            ));
+        }
+
+        if let Some(mut limit) = self.truncate_body_at {
+            if limit < content.len() {
+                while !content.is_char_boundary(limit) {
+                    limit -= 1;
+                }
+                content.truncate(limit);
+            }
+        }
+
+        Ok((content_type, content))
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use chrono::{NaiveDateTime, SecondsFormat};
+use serde::{Deserialize, Serialize};
+use crate::entities::{feed, folder};
+use crate::models::exporters::build_opml;
+use crate::models::folder_tree::self_and_descendants;
+use crate::models::importers::{parse_opml, ImportError, ImportedSubscriptions};
+
+// Identifies a bundle file, whatever its extension
+pub const FEED_BUNDLE_FORMAT: &str = "reader-feed-bundle";
+pub const FEED_BUNDLE_VERSION: u32 = 1;
+
+// One folder of a bundle, as the names from the bundle's folder down to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleFolder {
+    pub path: Vec<String>,
+    pub feed_count: usize,
+}
+
+// A shareable pack of subscriptions: one folder with its subfolders and feeds. The feeds are
+// kept as OPML, with their descriptions, so the pack can be pulled into other readers too.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedBundle {
+    pub format: String,
+    pub version: u32,
+    // The exported folder's name, e.g. "Rust starter pack"
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: String, // RFC 3339
+    pub folders: Vec<BundleFolder>,
+    pub opml: String,
+}
+
+// Bundle a folder, everything nested in it and their feeds. The folder becomes the bundle's
+// top level, wherever it sits in the library.
+pub fn build_feed_bundle(
+    root: &folder::Model,
+    folders: &[folder::Model],
+    feeds: &[feed::Model],
+    description: Option<String>,
+    now: NaiveDateTime,
+) -> FeedBundle {
+    let folder_ids = self_and_descendants(folders, root.id);
+    let bundle_folders: Vec<folder::Model> = folders
+        .iter()
+        .filter(|folder| folder_ids.contains(&folder.id))
+        .map(|folder| folder::Model {
+            parent_id: if folder.id == root.id { None } else { folder.parent_id },
+            ..folder.clone()
+        })
+        .collect();
+    let bundle_feeds: Vec<feed::Model> = feeds
+        .iter()
+        .filter(|feed| feed.folder_id.is_some_and(|folder_id| folder_ids.contains(&folder_id)))
+        .cloned()
+        .collect();
+
+    let by_id: HashMap<i32, &folder::Model> = bundle_folders.iter().map(|folder| (folder.id, folder)).collect();
+    let mut folder_paths: Vec<BundleFolder> = bundle_folders
+        .iter()
+        .map(|folder| {
+            let mut path = vec![folder.name.clone()];
+            let mut parent_id = folder.parent_id;
+            while let Some(parent) = parent_id.and_then(|id| by_id.get(&id)) {
+                path.insert(0, parent.name.clone());
+                parent_id = parent.parent_id;
+            }
+            BundleFolder {
+                path,
+                feed_count: bundle_feeds.iter().filter(|feed| feed.folder_id == Some(folder.id)).count(),
+            }
+        })
+        .collect();
+    folder_paths.sort_by(|a, b| a.path.cmp(&b.path));
+
+    FeedBundle {
+        format: FEED_BUNDLE_FORMAT.to_string(),
+        version: FEED_BUNDLE_VERSION,
+        name: root.name.clone(),
+        description: description.map(|description| description.trim().to_string()).filter(|description| !description.is_empty()),
+        created_at: now.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true),
+        folders: folder_paths,
+        opml: build_opml(&bundle_folders, &bundle_feeds),
+    }
+}
+
+// Read a bundle's subscriptions, including its folders that have no feeds of their own
+pub fn parse_feed_bundle(content: &str) -> Result<ImportedSubscriptions, ImportError> {
+    let bundle: FeedBundle = serde_json::from_str(content)
+        .map_err(|e| ImportError::ParseError(format!("Invalid feed bundle: {}", e)))?;
+    if bundle.format != FEED_BUNDLE_FORMAT {
+        return Err(ImportError::ParseError("Not a feed bundle".to_string()));
+    }
+    if bundle.version > FEED_BUNDLE_VERSION {
+        return Err(ImportError::ParseError(format!(
+            "Feed bundle version {} is newer than this app supports",
+            bundle.version
+        )));
+    }
+
+    let mut subscriptions = parse_opml(&bundle.opml)?;
+    subscriptions.folders = bundle.folders.into_iter().map(|folder| folder.path).collect();
+    Ok(subscriptions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::fixtures::{self, at};
+
+    fn folder(id: i32, name: &str, parent_id: Option<i32>) -> folder::Model {
+        folder::Model {
+            id,
+            name: name.to_string(),
+            created_at: at("2024-06-01 08:00"),
+            updated_at: at("2024-06-01 08:00"),
+            parent_id,
+        }
+    }
+
+    fn feed(id: i32, url: &str, description: Option<&str>, folder_id: Option<i32>) -> feed::Model {
+        feed::Model {
+            url: url.to_string(),
+            title: Some(format!("Feed {}", id)),
+            description: description.map(str::to_string),
+            folder_id,
+            ..fixtures::feed(id)
+        }
+    }
+
+    #[test]
+    fn test_bundle_round_trip_keeps_subtree_and_descriptions() {
+        let folders = vec![
+            folder(1, "Programming", None),
+            folder(2, "Rust starter pack", Some(1)),
+            folder(3, "Async", Some(2)),
+            folder(4, "Empty", Some(2)),
+            folder(5, "News", None),
+        ];
+        let feeds = vec![
+            feed(1, "https://blog.rust-lang.org/feed.xml", Some("Official <Rust> blog"), Some(2)),
+            feed(2, "https://tokio.rs/blog/index.xml", None, Some(3)),
+            feed(3, "https://example.com/programming.xml", None, Some(1)),
+            feed(4, "https://example.com/news.xml", None, Some(5)),
+        ];
+
+        let bundle = build_feed_bundle(&folders[1], &folders, &feeds, Some(" Where to start ".to_string()), at("2024-06-01 12:00"));
+
+        assert_eq!(bundle.name, "Rust starter pack");
+        assert_eq!(bundle.description, Some("Where to start".to_string()));
+        assert_eq!(bundle.created_at, "2024-06-01T12:00:00Z");
+        assert_eq!(
+            bundle.folders,
+            vec![
+                BundleFolder { path: vec!["Rust starter pack".to_string()], feed_count: 1 },
+                BundleFolder { path: vec!["Rust starter pack".to_string(), "Async".to_string()], feed_count: 1 },
+                BundleFolder { path: vec!["Rust starter pack".to_string(), "Empty".to_string()], feed_count: 0 },
+            ]
+        );
+
+        let subscriptions = parse_feed_bundle(&serde_json::to_string(&bundle).unwrap()).unwrap();
+        let urls: Vec<&str> = subscriptions.feeds.iter().map(|feed| feed.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://tokio.rs/blog/index.xml", "https://blog.rust-lang.org/feed.xml"]);
+        // The bundled folder is the top level, not its parent in the library
+        assert_eq!(subscriptions.feeds[0].parent_folders, vec!["Rust starter pack".to_string()]);
+        assert_eq!(subscriptions.feeds[1].folder, Some("Rust starter pack".to_string()));
+        assert!(subscriptions.feeds[1].parent_folders.is_empty());
+        assert_eq!(subscriptions.feeds[1].description, Some("Official <Rust> blog".to_string()));
+        assert_eq!(subscriptions.folders.len(), 3);
+    }
+
+    #[test]
+    fn test_other_files_are_not_bundles() {
+        assert!(parse_feed_bundle("{\"feeds\": []}").is_err());
+
+        let mut bundle = build_feed_bundle(&folder(1, "Pack", None), &[folder(1, "Pack", None)], &[], None, at("2024-06-01 12:00"));
+        bundle.format = "something-else".to_string();
+        assert!(parse_feed_bundle(&serde_json::to_string(&bundle).unwrap()).is_err());
+
+        bundle.format = FEED_BUNDLE_FORMAT.to_string();
+        bundle.version = FEED_BUNDLE_VERSION + 1;
+        assert!(parse_feed_bundle(&serde_json::to_string(&bundle).unwrap()).is_err());
+    }
+}
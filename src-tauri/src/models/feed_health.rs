@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use chrono::{Duration, NaiveDateTime};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use crate::entities::{prelude::*, *};
+use crate::models::notifications::{build_health_digest_notification, NotificationConfig};
+use crate::models::settings::{get_setting_or, set_setting_value, HEALTH_DIGEST};
+use crate::models::state::AppState;
+
+// Consecutive failed fetches before a feed counts as a subscription problem
+const MIN_FAILURES_FOR_PROBLEM: usize = 3;
+
+// How long a feed has to keep failing before it is considered dead
+const DEAD_AFTER_DAYS: i64 = 14;
+
+// How much fetch history is looked at
+const HEALTH_HISTORY_DAYS: i64 = 60;
+
+// Digests of new subscription problems go out at most once a week
+const DIGEST_INTERVAL_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedHealth {
+    Healthy,
+    // Recent fetches failed, but not for long enough to give up on the feed
+    Failing,
+    // Failing for weeks, or the feed is gone (404/410)
+    Dead,
+    // The server keeps refusing our credentials (401/403)
+    AuthBroken,
+}
+
+impl FeedHealth {
+    // Problems the user has to act on, as opposed to transient failures
+    pub fn is_problem(&self) -> bool {
+        matches!(self, FeedHealth::Dead | FeedHealth::AuthBroken)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedHealthReport {
+    pub feed_id: i32,
+    pub feed_url: String,
+    pub feed_title: Option<String>,
+    pub health: FeedHealth,
+    pub consecutive_failures: usize,
+    pub failing_since: Option<String>,
+    pub last_error: Option<String>,
+}
+
+// Which problems the last digest told the user about, so each one is only reported once
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HealthDigestState {
+    pub last_digest_at: Option<NaiveDateTime>,
+    pub reported: HashMap<i32, FeedHealth>,
+}
+
+// Judge a feed's health from its fetch log, newest first. Only the current streak of
+// failures matters; a single successful fetch makes the feed healthy again.
+pub fn assess_feed_health(
+    feed: &feed::Model,
+    fetch_logs: &[fetch_log::Model],
+    now: NaiveDateTime,
+) -> FeedHealthReport {
+    let failures: Vec<&fetch_log::Model> = fetch_logs.iter().take_while(|log| !log.success).collect();
+    let failing_since = failures.last().map(|log| log.fetched_at);
+    let has_error_type = |error_type: &str| {
+        failures.iter().all(|log| log.error_type.as_deref() == Some(error_type))
+    };
+
+    let health = if failures.is_empty() {
+        FeedHealth::Healthy
+    } else if failures.len() < MIN_FAILURES_FOR_PROBLEM {
+        FeedHealth::Failing
+    } else if has_error_type("auth") {
+        FeedHealth::AuthBroken
+    } else if has_error_type("gone")
+        || failing_since.is_some_and(|since| now - since >= Duration::days(DEAD_AFTER_DAYS))
+    {
+        FeedHealth::Dead
+    } else {
+        FeedHealth::Failing
+    };
+
+    FeedHealthReport {
+        feed_id: feed.id,
+        feed_url: feed.url.clone(),
+        feed_title: feed.title.clone(),
+        health,
+        consecutive_failures: failures.len(),
+        failing_since: failing_since.map(|dt| dt.to_string()),
+        last_error: failures.first().and_then(|log| log.error_message.clone()),
+    }
+}
+
+// Assess every feed from its recent fetch log
+pub async fn load_feed_health_reports<C: ConnectionTrait>(
+    db: &C,
+    now: NaiveDateTime,
+) -> Result<Vec<FeedHealthReport>, String> {
+    let feeds = Feed::find()
+        .order_by_asc(feed::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feeds: {}", e))?;
+
+    let mut logs_by_feed: HashMap<i32, Vec<fetch_log::Model>> = HashMap::new();
+    for log in FetchLog::find()
+        .filter(fetch_log::Column::FetchedAt.gte(now - Duration::days(HEALTH_HISTORY_DAYS)))
+        .order_by_desc(fetch_log::Column::FetchedAt)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch fetch log: {}", e))?
+    {
+        logs_by_feed.entry(log.feed_id).or_default().push(log);
+    }
+
+    Ok(feeds
+        .iter()
+        .map(|feed| {
+            let logs = logs_by_feed.get(&feed.id).map(Vec::as_slice).unwrap_or_default();
+            assess_feed_health(feed, logs, now)
+        })
+        .collect())
+}
+
+// Problems that weren't in the last digest, or whose kind changed since
+pub fn new_problems(reports: &[FeedHealthReport], digest: &HealthDigestState) -> Vec<FeedHealthReport> {
+    reports
+        .iter()
+        .filter(|report| report.health.is_problem())
+        .filter(|report| digest.reported.get(&report.feed_id) != Some(&report.health))
+        .cloned()
+        .collect()
+}
+
+// Once a week, batch feeds that newly died or lost their credentials into a single
+// "subscription problems" notification. Called from the scheduler loop.
+pub async fn send_health_digest_if_due<C: ConnectionTrait>(
+    app: &AppHandle,
+    db: &C,
+    config: &NotificationConfig,
+) -> Result<(), String> {
+    let now = chrono::Utc::now().naive_utc();
+    let mut digest: HealthDigestState = get_setting_or(db, HEALTH_DIGEST, HealthDigestState::default()).await;
+    if digest
+        .last_digest_at
+        .is_some_and(|last_digest_at| now - last_digest_at < Duration::days(DIGEST_INTERVAL_DAYS))
+    {
+        return Ok(());
+    }
+
+    let reports = load_feed_health_reports(db, now).await?;
+    let problems = new_problems(&reports, &digest);
+
+    if !problems.is_empty() {
+        println!("🩺 {} new subscription problems", problems.len());
+        if config.health_digest_enabled {
+            if let Some(notification) = build_health_digest_notification(&problems) {
+                app.state::<AppState>().notifications.push(app, notification).await;
+            }
+        }
+    }
+
+    // Recovered feeds drop out, so they are reported again if they break later
+    digest.last_digest_at = Some(now);
+    digest.reported = reports
+        .iter()
+        .filter(|report| report.health.is_problem())
+        .map(|report| (report.feed_id, report.health))
+        .collect();
+    set_setting_value(db, HEALTH_DIGEST, &digest).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::fixtures;
+
+    fn test_feed() -> feed::Model {
+        feed::Model {
+            url: "https://example.com/feed.xml".to_string(),
+            title: Some("Example".to_string()),
+            ..fixtures::feed(1)
+        }
+    }
+
+    fn fetch(fetched_at: NaiveDateTime, error_type: Option<&str>) -> fetch_log::Model {
+        fetch_log::Model {
+            id: 0,
+            feed_id: 1,
+            fetched_at,
+            duration_ms: 100,
+            success: error_type.is_none(),
+            entries_added: 0,
+            error_type: error_type.map(str::to_string),
+            error_message: error_type.map(|error_type| format!("{} error", error_type)),
+        }
+    }
+
+    // Newest first, one fetch a day
+    fn daily_fetches(now: NaiveDateTime, error_types: &[Option<&str>]) -> Vec<fetch_log::Model> {
+        error_types
+            .iter()
+            .enumerate()
+            .map(|(i, error_type)| fetch(now - Duration::days(i as i64), *error_type))
+            .collect()
+    }
+
+    #[test]
+    fn test_recent_success_is_healthy() {
+        let now = chrono::Utc::now().naive_utc();
+        let logs = daily_fetches(now, &[None, Some("network"), Some("network"), Some("network")]);
+
+        let report = assess_feed_health(&test_feed(), &logs, now);
+
+        assert_eq!(report.health, FeedHealth::Healthy);
+        assert_eq!(report.consecutive_failures, 0);
+    }
+
+    #[test]
+    fn test_failure_streaks_are_classified() {
+        let now = chrono::Utc::now().naive_utc();
+        let feed = test_feed();
+
+        let short_outage = daily_fetches(now, &[Some("network"), Some("timeout"), Some("network"), None]);
+        assert_eq!(assess_feed_health(&feed, &short_outage, now).health, FeedHealth::Failing);
+
+        let auth = daily_fetches(now, &[Some("auth"), Some("auth"), Some("auth")]);
+        assert_eq!(assess_feed_health(&feed, &auth, now).health, FeedHealth::AuthBroken);
+
+        let gone = daily_fetches(now, &[Some("gone"), Some("gone"), Some("gone")]);
+        assert_eq!(assess_feed_health(&feed, &gone, now).health, FeedHealth::Dead);
+
+        let long_outage = daily_fetches(now, &[Some("network"); 15]);
+        let report = assess_feed_health(&feed, &long_outage, now);
+        assert_eq!(report.health, FeedHealth::Dead);
+        assert_eq!(report.consecutive_failures, 15);
+        assert_eq!(report.last_error.as_deref(), Some("network error"));
+    }
+
+    #[test]
+    fn test_only_new_problems_are_reported() {
+        let now = chrono::Utc::now().naive_utc();
+        let mut dead_feed = test_feed();
+        dead_feed.id = 2;
+        let reports = vec![
+            assess_feed_health(&test_feed(), &daily_fetches(now, &[Some("auth"); 3]), now),
+            assess_feed_health(&dead_feed, &daily_fetches(now, &[Some("gone"); 3]), now),
+        ];
+
+        let digest = HealthDigestState {
+            last_digest_at: None,
+            reported: HashMap::from([(1, FeedHealth::AuthBroken), (2, FeedHealth::AuthBroken)]),
+        };
+        let problems = new_problems(&reports, &digest);
+
+        // Feed 1 was already reported; feed 2 changed from auth-broken to dead
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].feed_id, 2);
+    }
+}
@@ -1,6 +1,12 @@
+use std::ops::Range;
+use chrono::{DateTime, FixedOffset};
+use feed_rs::model::FeedType;
 use feed_rs::parser;
+use quick_xml::events::Event;
+use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
 use tauri_plugin_http::reqwest;
+use url::Url;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParsedFeed {
@@ -8,15 +14,50 @@ pub struct ParsedFeed {
     pub description: Option<String>,
     pub url: Option<String>,
     pub entries: Vec<ParsedEntry>,
+    // RSS publisher hints for how often to poll
+    pub ttl_minutes: Option<u32>,
+    pub skip_hours: Vec<u32>,    // 0-23, GMT
+    pub skip_days: Vec<String>,  // "Monday" ... "Sunday"
+    // Artwork the feed declares for itself: Atom <icon>, else Atom <logo>, RSS <image> or itunes:image
+    pub icon_url: Option<String>,
+    // Which format the document was in (see feed_format_name) and a hash of it as downloaded
+    pub format: Option<String>,
+    pub content_hash: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParsedEntry {
+    // The publisher's id for the entry (RSS guid, Atom id), falling back to the link
+    pub guid: Option<String>,
     pub title: Option<String>,
     pub description: Option<String>,
     pub link: Option<String>,
     pub published: Option<String>,
+    // When the publisher says the entry was last modified (Atom <updated>)
+    pub updated: Option<String>,
     pub content: Option<String>,
+    pub enclosure_url: Option<String>,
+    pub enclosure_type: Option<String>,
+    pub enclosure_length: Option<u64>,
+    pub duration_seconds: Option<u64>,
+    // Podcast metadata from the iTunes namespace
+    pub episode_number: Option<u32>,
+    pub season_number: Option<u32>,
+    pub explicit: Option<bool>,
+    pub artwork_url: Option<String>,
+    // When the event starts and ends, for entries made from calendar events
+    pub event_starts_at: Option<String>,
+    pub event_ends_at: Option<String>,
+}
+
+// Stand-in title for feeds that don't declare one
+pub const UNTITLED_FEED_TITLE: &str = "Untitled Feed";
+
+impl ParsedFeed {
+    // The title the publisher gave the feed, if any
+    pub fn publisher_title(&self) -> Option<&str> {
+        (self.title != UNTITLED_FEED_TITLE && !self.title.trim().is_empty()).then_some(self.title.as_str())
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -60,32 +101,364 @@ pub async fn fetch_and_parse_feed(url: &str) -> Result<ParsedFeed, FeedParseErro
     parse_feed_content(&content)
 }
 
+// The name a feed's format is recorded under
+pub fn feed_format_name(feed_type: &FeedType) -> &'static str {
+    match feed_type {
+        FeedType::RSS0 => "RSS 0.9x",
+        FeedType::RSS1 => "RSS 1.0",
+        FeedType::RSS2 => "RSS 2.0",
+        FeedType::Atom => "Atom",
+        FeedType::JSON => "JSON Feed",
+    }
+}
+
+// Fingerprint of a feed document (64-bit FNV-1a, so it doesn't change between builds)
+pub fn feed_content_hash(content: &str) -> String {
+    let hash = content
+        .as_bytes()
+        .iter()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3));
+    format!("{:016x}", hash)
+}
+
 pub fn parse_feed_content(content: &str) -> Result<ParsedFeed, FeedParseError> {
     println!("🔍 Parsing feed content...");
     
-    let feed = parser::parse(content.as_bytes())
+    // feed-rs makes up ids for entries without one by hashing the link and title, which would
+    // turn an edited title into a new entry. Leave them empty and fall back to the link instead.
+    let feed = parser::Builder::new()
+        .id_generator(|_links, _title, _uri| String::new())
+        .build()
+        .parse(content.as_bytes())
         .map_err(|e| FeedParseError::ParseError(e.to_string()))?;
     
-    let parsed_entries: Vec<ParsedEntry> = feed.entries.into_iter().map(|entry| {
+    // Items are listed in document order, so the nth <item> holds the nth entry's iTunes fields
+    let itunes_items = parse_itunes_items(content);
+    let itunes_items = (itunes_items.len() == feed.entries.len()).then_some(itunes_items);
+
+    let parsed_entries: Vec<ParsedEntry> = feed.entries.into_iter().enumerate().map(|(index, entry)| {
+        let itunes = itunes_items.as_ref().and_then(|items| items.get(index)).cloned().unwrap_or_default();
+        // RSS <enclosure>, Media RSS and itunes:duration all surface as media objects
+        let media_content = entry.media.iter()
+            .flat_map(|media| media.content.iter())
+            .find(|content| content.url.is_some());
+        let duration = entry.media.iter()
+            .find_map(|media| media.duration)
+            .or_else(|| media_content.and_then(|content| content.duration));
+        // itunes:image (and media:thumbnail) surface as thumbnails
+        let artwork_url = entry.media.iter()
+            .flat_map(|media| media.thumbnails.iter())
+            .map(|thumbnail| thumbnail.image.uri.clone())
+            .next();
+
+        let link = entry.links.first().map(|l| l.href.clone());
+
         ParsedEntry {
+            guid: Some(entry.id).filter(|id| !id.is_empty()).or_else(|| link.clone()),
             title: entry.title.map(|t| t.content),
             description: entry.summary.map(|s| s.content),
-            link: entry.links.first().map(|l| l.href.clone()),
+            link,
             published: entry.published.map(|p| p.to_rfc3339()),
+            updated: entry.updated.map(|u| u.to_rfc3339()),
+            enclosure_url: media_content.and_then(|c| c.url.as_ref()).map(|u| u.to_string()),
+            enclosure_type: media_content.and_then(|c| c.content_type.as_ref()).map(|m| m.to_string()),
+            enclosure_length: media_content.and_then(|c| c.size),
+            duration_seconds: duration.map(|d| d.as_secs()).or(itunes.duration_seconds),
+            episode_number: itunes.episode,
+            season_number: itunes.season,
+            explicit: itunes.explicit,
+            artwork_url,
             content: entry.content.and_then(|c| c.body),
+            event_starts_at: None,
+            event_ends_at: None,
         }
     }).collect();
     
+    let (skip_hours, skip_days) = parse_rss_skip_rules(content);
+
+    // feed-rs puts RSS <image> and, failing that, itunes:image in the logo. Artwork may be
+    // given relative to the site.
+    let site_url = feed.links.first().and_then(|link| Url::parse(&link.href).ok());
+    let icon_url = feed.icon.into_iter().chain(feed.logo)
+        .map(|image| image.uri.trim().to_string())
+        .filter_map(|uri| match &site_url {
+            Some(site_url) => site_url.join(&uri).ok(),
+            None => Url::parse(&uri).ok(),
+        })
+        .find(|icon_url| matches!(icon_url.scheme(), "http" | "https"))
+        .map(String::from);
+
     let parsed_feed = ParsedFeed {
-        title: feed.title.map(|t| t.content).unwrap_or_else(|| "Untitled Feed".to_string()),
+        title: feed.title.map(|t| t.content).unwrap_or_else(|| UNTITLED_FEED_TITLE.to_string()),
         description: feed.description.map(|d| d.content),
         url: feed.links.first().map(|l| l.href.clone()),
         entries: parsed_entries,
+        ttl_minutes: feed.ttl,
+        skip_hours,
+        skip_days,
+        icon_url,
+        format: Some(feed_format_name(&feed.feed_type).to_string()),
+        content_hash: Some(feed_content_hash(content)),
     };
     
     Ok(parsed_feed)
 }
 
+// Parse at most `max_entries` entries (0 for no cap). Huge feeds, like full-archive blogs and
+// planet aggregators, are cut down to their newest entries before feed-rs sees them, so the
+// entries past the cap are never built. The content hash still covers the whole document.
+pub fn parse_feed_content_limited(content: &str, max_entries: usize) -> Result<ParsedFeed, FeedParseError> {
+    let truncated = if max_entries == 0 {
+        None
+    } else if content.trim_start().starts_with('{') {
+        truncate_json_feed(content, max_entries)
+    } else {
+        truncate_feed_document(content, max_entries)
+    };
+    let Some(truncated) = truncated else {
+        return parse_feed_content(content);
+    };
+
+    println!("✂️ Parsing only the newest {} entries of a {} byte feed", max_entries, content.len());
+    let mut parsed_feed = parse_feed_content(&truncated)?;
+    parsed_feed.content_hash = Some(feed_content_hash(content));
+    Ok(parsed_feed)
+}
+
+// Elements whose text dates an RSS item or Atom entry (dc:date included)
+const ENTRY_DATE_ELEMENTS: [&[u8]; 4] = [b"pubDate", b"published", b"updated", b"date"];
+
+fn parse_entry_date(text: &str) -> Option<DateTime<FixedOffset>> {
+    let text = text.trim();
+    DateTime::parse_from_rfc3339(text)
+        .or_else(|_| DateTime::parse_from_rfc2822(text))
+        .ok()
+}
+
+// Which `max_entries` entries to keep: the newest by date, so feeds listed oldest first keep
+// their latest entries. Undated entries rank below dated ones, and ties keep document order.
+// Returns the indexes kept, in document order.
+fn newest_entry_indexes(dates: &[Option<DateTime<FixedOffset>>], max_entries: usize) -> Vec<usize> {
+    let mut indexes: Vec<usize> = (0..dates.len()).collect();
+    indexes.sort_by(|a, b| dates[*b].cmp(&dates[*a]));
+    indexes.truncate(max_entries);
+    indexes.sort_unstable();
+    indexes
+}
+
+// An RSS or Atom document with only its `max_entries` newest items or entries, and everything
+// around them as it was. None if it has no more entries than that.
+fn truncate_feed_document(content: &str, max_entries: usize) -> Option<String> {
+    let mut reader = Reader::from_str(content);
+    let mut depth = 0usize;
+    // Entries are the item/entry elements at the depth of the first one
+    let mut entry_depth = None;
+    let mut entries: Vec<(Range<usize>, Option<DateTime<FixedOffset>>)> = Vec::new();
+    let mut in_entry = false;
+    let mut in_date = false;
+
+    loop {
+        let position = reader.buffer_position();
+        match reader.read_event() {
+            Ok(Event::Start(element)) => {
+                let name = element.local_name();
+                if !in_entry
+                    && matches!(name.as_ref(), b"item" | b"entry")
+                    && *entry_depth.get_or_insert(depth) == depth
+                {
+                    in_entry = true;
+                    entries.push((position..position, None));
+                } else if in_entry {
+                    in_date = ENTRY_DATE_ELEMENTS.contains(&name.as_ref());
+                }
+                depth += 1;
+            }
+            Ok(Event::Text(text)) if in_date => {
+                if let (Ok(text), Some((_, date))) = (text.unescape(), entries.last_mut()) {
+                    *date = (*date).max(parse_entry_date(&text));
+                }
+            }
+            Ok(Event::End(_)) => {
+                depth = depth.saturating_sub(1);
+                in_date = false;
+                if in_entry && entry_depth == Some(depth) {
+                    in_entry = false;
+                    if let Some((range, _)) = entries.last_mut() {
+                        range.end = reader.buffer_position();
+                    }
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(_) => return None,
+            _ => {}
+        }
+    }
+    if entries.len() <= max_entries {
+        return None;
+    }
+
+    let dates: Vec<_> = entries.iter().map(|(_, date)| *date).collect();
+    let mut kept = newest_entry_indexes(&dates, max_entries).into_iter().peekable();
+    let mut truncated = String::with_capacity(content.len());
+    let mut copied_to = 0;
+    for (index, (range, _)) in entries.iter().enumerate() {
+        if kept.next_if_eq(&index).is_some() {
+            continue;
+        }
+        truncated.push_str(&content[copied_to..range.start]);
+        copied_to = range.end;
+    }
+    truncated.push_str(&content[copied_to..]);
+    Some(truncated)
+}
+
+// A JSON Feed with its items array cut down to the `max_entries` newest
+fn truncate_json_feed(content: &str, max_entries: usize) -> Option<String> {
+    let mut feed: serde_json::Value = serde_json::from_str(content).ok()?;
+    let items = feed.get_mut("items")?.as_array_mut()?;
+    if items.len() <= max_entries {
+        return None;
+    }
+    let dates: Vec<_> = items
+        .iter()
+        .map(|item| {
+            ["date_published", "date_modified"]
+                .iter()
+                .filter_map(|key| item.get(key)?.as_str())
+                .filter_map(parse_entry_date)
+                .max()
+        })
+        .collect();
+    let kept = newest_entry_indexes(&dates, max_entries);
+    let mut index = 0;
+    items.retain(|_| {
+        index += 1;
+        kept.binary_search(&(index - 1)).is_ok()
+    });
+    Some(feed.to_string())
+}
+
+// feed-rs doesn't expose RSS <skipHours>/<skipDays>, so pick them out of the channel directly
+fn parse_rss_skip_rules(content: &str) -> (Vec<u32>, Vec<String>) {
+    let mut reader = Reader::from_str(content);
+    let mut skip_hours = Vec::new();
+    let mut skip_days = Vec::new();
+    let mut in_skip_hours = false;
+    let mut in_skip_days = false;
+    let mut current_element = Vec::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(element)) => {
+                match element.local_name().as_ref() {
+                    b"skipHours" => in_skip_hours = true,
+                    b"skipDays" => in_skip_days = true,
+                    // Items come after the channel metadata, so there is nothing left to find
+                    b"item" => break,
+                    _ => {}
+                }
+                current_element = element.local_name().as_ref().to_vec();
+            }
+            Ok(Event::End(element)) => {
+                match element.local_name().as_ref() {
+                    b"skipHours" => in_skip_hours = false,
+                    b"skipDays" => in_skip_days = false,
+                    _ => {}
+                }
+                current_element.clear();
+            }
+            Ok(Event::Text(text)) => {
+                let Ok(text) = text.unescape() else { continue };
+                let text = text.trim();
+                if in_skip_hours && current_element == b"hour" {
+                    if let Ok(hour) = text.parse::<u32>() {
+                        if hour < 24 {
+                            skip_hours.push(hour);
+                        }
+                    }
+                } else if in_skip_days && current_element == b"day" && !text.is_empty() {
+                    skip_days.push(text.to_string());
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    (skip_hours, skip_days)
+}
+
+// iTunes item fields that feed-rs doesn't expose
+#[derive(Debug, Clone, Default)]
+struct ItunesItem {
+    episode: Option<u32>,
+    season: Option<u32>,
+    explicit: Option<bool>,
+    // Only used when feed-rs couldn't make sense of itunes:duration
+    duration_seconds: Option<u64>,
+}
+
+// itunes:explicit has been spelled "yes"/"no", "true"/"false" and "explicit"/"clean" over the years
+fn parse_itunes_explicit(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "yes" | "true" | "explicit" => Some(true),
+        "no" | "false" | "clean" => Some(false),
+        _ => None,
+    }
+}
+
+// itunes:duration is either plain seconds or [HH:]MM:SS
+fn parse_itunes_duration(value: &str) -> Option<u64> {
+    value
+        .split(':')
+        .try_fold(0u64, |total, part| Some(total * 60 + part.trim().parse::<u64>().ok()?))
+}
+
+// Collect the iTunes fields of every item (or entry) in document order
+fn parse_itunes_items(content: &str) -> Vec<ItunesItem> {
+    let mut reader = Reader::from_str(content);
+    let mut items = Vec::new();
+    let mut current_item: Option<ItunesItem> = None;
+    let mut current_element = Vec::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(element)) => {
+                let is_itunes = element.name().prefix().is_some_and(|prefix| prefix.as_ref() == b"itunes");
+                match element.local_name().as_ref() {
+                    b"item" | b"entry" if !is_itunes => current_item = Some(ItunesItem::default()),
+                    name if is_itunes => current_element = name.to_vec(),
+                    _ => current_element.clear(),
+                }
+            }
+            Ok(Event::End(element)) => {
+                if matches!(element.local_name().as_ref(), b"item" | b"entry") {
+                    if let Some(item) = current_item.take() {
+                        items.push(item);
+                    }
+                }
+                current_element.clear();
+            }
+            Ok(Event::Text(text)) => {
+                let Some(item) = current_item.as_mut() else { continue };
+                let Ok(text) = text.unescape() else { continue };
+                let text = text.trim();
+                match current_element.as_slice() {
+                    b"episode" => item.episode = text.parse().ok(),
+                    b"season" => item.season = text.parse().ok(),
+                    b"explicit" => item.explicit = parse_itunes_explicit(text),
+                    b"duration" => item.duration_seconds = parse_itunes_duration(text),
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    items
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,6 +489,69 @@ mod tests {
         assert_eq!(feed.entries[0].title, Some("Test Article".to_string()));
     }
 
+    #[test]
+    fn test_entry_guid_falls_back_to_link() {
+        let rss_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0">
+            <channel>
+                <title>Guid Feed</title>
+                <link>https://example.com</link>
+                <item>
+                    <title>With guid</title>
+                    <link>https://example.com/a</link>
+                    <guid isPermaLink="false">post-1</guid>
+                </item>
+                <item>
+                    <title>Without guid</title>
+                    <link>https://example.com/b</link>
+                </item>
+            </channel>
+        </rss>"#;
+
+        let feed = parse_feed_content(rss_content).unwrap();
+
+        assert_eq!(feed.entries[0].guid.as_deref(), Some("post-1"));
+        assert_eq!(feed.entries[1].guid.as_deref(), Some("https://example.com/b"));
+    }
+
+    #[test]
+    fn test_untitled_feed_has_no_publisher_title() {
+        let rss_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0">
+            <channel>
+                <link>https://example.com</link>
+            </channel>
+        </rss>"#;
+
+        let feed = parse_feed_content(rss_content).unwrap();
+        assert_eq!(feed.title, UNTITLED_FEED_TITLE);
+        assert_eq!(feed.publisher_title(), None);
+    }
+
+    #[test]
+    fn test_parse_rss_ttl_and_skip_rules() {
+        let rss_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0">
+            <channel>
+                <title>Office Hours Feed</title>
+                <link>https://example.com</link>
+                <ttl>60</ttl>
+                <skipHours><hour>0</hour><hour>1</hour><hour>24</hour></skipHours>
+                <skipDays><day>Saturday</day><day>Sunday</day></skipDays>
+                <item>
+                    <title>Test Article</title>
+                    <link>https://example.com/article1</link>
+                </item>
+            </channel>
+        </rss>"#;
+
+        let feed = parse_feed_content(rss_content).unwrap();
+        assert_eq!(feed.publisher_title(), Some("Office Hours Feed"));
+        assert_eq!(feed.ttl_minutes, Some(60));
+        assert_eq!(feed.skip_hours, vec![0, 1]);
+        assert_eq!(feed.skip_days, vec!["Saturday".to_string(), "Sunday".to_string()]);
+    }
+
     #[test]
     fn test_parse_atom_sample() {
         let atom_content = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -140,4 +576,182 @@ mod tests {
         assert_eq!(feed.entries.len(), 1);
         assert_eq!(feed.entries[0].title, Some("Test Entry".to_string()));
     }
+
+    #[test]
+    fn test_parse_records_format_and_content_hash() {
+        let rss = r#"<rss version="2.0"><channel><title>Blog</title></channel></rss>"#;
+        let rdf = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#" xmlns="http://purl.org/rss/1.0/">
+            <channel><title>Blog</title></channel></rdf:RDF>"#;
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>Blog</title></feed>"#;
+        let json = r#"{"version": "https://jsonfeed.org/version/1.1", "title": "Blog", "items": []}"#;
+
+        let formats: Vec<Option<String>> = [rss, rdf, atom, json]
+            .iter()
+            .map(|content| parse_feed_content(content).unwrap().format)
+            .collect();
+        assert_eq!(
+            formats,
+            vec![Some("RSS 2.0".into()), Some("RSS 1.0".into()), Some("Atom".into()), Some("JSON Feed".into())]
+        );
+
+        let feed = parse_feed_content(rss).unwrap();
+        assert_eq!(feed.content_hash, Some(feed_content_hash(rss)));
+        assert_ne!(feed_content_hash(rss), feed_content_hash(atom));
+    }
+
+    #[test]
+    fn test_parse_caps_entries() {
+        let items: String = (1..=5)
+            .map(|n| format!("<item><title>Item {0}</title><link>https://example.com/{0}</link></item>", n))
+            .collect();
+        let rss = format!(r#"<rss version="2.0"><channel><title>Archive</title><ttl>60</ttl>{}</channel></rss>"#, items);
+
+        let feed = parse_feed_content_limited(&rss, 2).unwrap();
+        let titles: Vec<_> = feed.entries.iter().filter_map(|entry| entry.title.as_deref()).collect();
+        assert_eq!(titles, vec!["Item 1", "Item 2"]);
+        assert_eq!(feed.ttl_minutes, Some(60));
+        assert_eq!(feed.content_hash, Some(feed_content_hash(&rss)));
+        assert_eq!(parse_feed_content_limited(&rss, 0).unwrap().entries.len(), 5);
+        assert_eq!(parse_feed_content_limited(&rss, 5).unwrap().entries.len(), 5);
+
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>Planet</title>
+            <entry><id>1</id><title>One</title></entry><entry><id>2</id><title>Two</title></entry></feed>"#;
+        assert_eq!(parse_feed_content_limited(atom, 1).unwrap().entries.len(), 1);
+
+        let json = r#"{"version": "https://jsonfeed.org/version/1.1", "title": "Blog",
+            "items": [{"id": "1", "url": "https://example.com/1"}, {"id": "2", "url": "https://example.com/2"}]}"#;
+        assert_eq!(parse_feed_content_limited(json, 1).unwrap().entries.len(), 1);
+    }
+
+    #[test]
+    fn test_capped_feeds_keep_their_newest_entries() {
+        // Listed oldest first, like many serialized feeds
+        let items: String = (1..=5)
+            .map(|n| format!("<item><title>Item {0}</title><link>https://example.com/{0}</link><pubDate>0{0} Jan 2024 10:00:00 GMT</pubDate></item>", n))
+            .collect();
+        let rss = format!(r#"<rss version="2.0"><channel><title>Serial</title>{}<ttl>60</ttl></channel></rss>"#, items);
+
+        let feed = parse_feed_content_limited(&rss, 2).unwrap();
+        let titles: Vec<_> = feed.entries.iter().filter_map(|entry| entry.title.as_deref()).collect();
+        assert_eq!(titles, vec!["Item 4", "Item 5"]);
+        // Whatever follows the entries is kept
+        assert_eq!(feed.ttl_minutes, Some(60));
+
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>Planet</title>
+            <entry><id>1</id><title>Old</title><updated>2024-01-01T00:00:00Z</updated></entry>
+            <entry><id>2</id><title>New</title><updated>2024-03-01T00:00:00Z</updated></entry>
+            <entry><id>3</id><title>Undated</title></entry></feed>"#;
+        let feed = parse_feed_content_limited(atom, 1).unwrap();
+        assert_eq!(feed.entries[0].title.as_deref(), Some("New"));
+
+        let json = r#"{"version": "https://jsonfeed.org/version/1.1", "title": "Blog", "items": [
+            {"id": "1", "url": "https://example.com/1", "date_published": "2024-01-01T00:00:00Z"},
+            {"id": "2", "url": "https://example.com/2", "date_published": "2024-02-01T00:00:00Z"}]}"#;
+        let feed = parse_feed_content_limited(json, 1).unwrap();
+        assert_eq!(feed.entries[0].link.as_deref(), Some("https://example.com/2"));
+    }
+
+    #[test]
+    fn test_parse_feed_artwork() {
+        let atom_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <feed xmlns="http://www.w3.org/2005/Atom">
+            <title>Atom</title>
+            <link href="https://example.com/blog/"/>
+            <icon>/favicon-192.png</icon>
+            <logo>https://example.com/logo.png</logo>
+        </feed>"#;
+        let feed = parse_feed_content(atom_content).unwrap();
+        assert_eq!(feed.icon_url.as_deref(), Some("https://example.com/favicon-192.png"));
+
+        let rss_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+            <channel>
+                <title>Podcast</title>
+                <link>https://example.com</link>
+                <itunes:image href="https://cdn.example.com/cover-3000.jpg"/>
+            </channel>
+        </rss>"#;
+        let feed = parse_feed_content(rss_content).unwrap();
+        assert_eq!(feed.icon_url.as_deref(), Some("https://cdn.example.com/cover-3000.jpg"));
+
+        let rss_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0">
+            <channel>
+                <title>Blog</title>
+                <link>https://example.com</link>
+                <image><url>https://example.com/image.png</url><title>Blog</title><link>https://example.com</link></image>
+            </channel>
+        </rss>"#;
+        let feed = parse_feed_content(rss_content).unwrap();
+        assert_eq!(feed.icon_url.as_deref(), Some("https://example.com/image.png"));
+    }
+
+    #[test]
+    fn test_parse_podcast_enclosure_duration() {
+        let rss_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+            <channel>
+                <title>Test Podcast</title>
+                <link>https://example.com</link>
+                <item>
+                    <title>Episode 1</title>
+                    <link>https://example.com/episodes/1</link>
+                    <enclosure url="https://example.com/episodes/1.mp3" length="24986239" type="audio/mpeg"/>
+                    <itunes:duration>00:25:30</itunes:duration>
+                </item>
+            </channel>
+        </rss>"#;
+
+        let feed = parse_feed_content(rss_content).unwrap();
+        let entry = &feed.entries[0];
+        assert_eq!(entry.enclosure_url, Some("https://example.com/episodes/1.mp3".to_string()));
+        assert_eq!(entry.enclosure_type, Some("audio/mpeg".to_string()));
+        assert_eq!(entry.enclosure_length, Some(24986239));
+        assert_eq!(entry.duration_seconds, Some(25 * 60 + 30));
+    }
+
+    #[test]
+    fn test_parse_itunes_episode_metadata() {
+        let rss_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+            <channel>
+                <title>Test Podcast</title>
+                <link>https://example.com</link>
+                <itunes:explicit>no</itunes:explicit>
+                <item>
+                    <title>Season 2, Episode 5</title>
+                    <link>https://example.com/episodes/5</link>
+                    <itunes:season>2</itunes:season>
+                    <itunes:episode>5</itunes:episode>
+                    <itunes:explicit>yes</itunes:explicit>
+                    <itunes:image href="https://example.com/art/5.jpg"/>
+                </item>
+                <item>
+                    <title>Bonus</title>
+                    <link>https://example.com/episodes/bonus</link>
+                </item>
+            </channel>
+        </rss>"#;
+
+        let feed = parse_feed_content(rss_content).unwrap();
+        let episode = &feed.entries[0];
+        assert_eq!(episode.season_number, Some(2));
+        assert_eq!(episode.episode_number, Some(5));
+        assert_eq!(episode.explicit, Some(true));
+        assert_eq!(episode.artwork_url, Some("https://example.com/art/5.jpg".to_string()));
+
+        let bonus = &feed.entries[1];
+        assert_eq!(bonus.episode_number, None);
+        assert_eq!(bonus.explicit, None);
+        assert_eq!(bonus.artwork_url, None);
+    }
+
+    #[test]
+    fn test_parse_itunes_duration_formats() {
+        assert_eq!(parse_itunes_duration("1530"), Some(1530));
+        assert_eq!(parse_itunes_duration("25:30"), Some(25 * 60 + 30));
+        assert_eq!(parse_itunes_duration("1:02:03"), Some(3723));
+        assert_eq!(parse_itunes_duration("about an hour"), None);
+        assert_eq!(parse_itunes_explicit("Clean"), Some(false));
+    }
 } 
\ No newline at end of file
@@ -0,0 +1,108 @@
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use crate::entities::{prelude::*, *};
+use crate::models::requests::{EntryQueryRequest, EntryView};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntrySort {
+    #[default]
+    NewestFirst,
+    // For feeds read in order, like a YouTube series or serialized fiction
+    OldestFirst,
+}
+
+// How a feed's entries are listed by default. Stored as JSON in the feed's preferences column;
+// a feed without any uses the defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedPreferences {
+    pub sort: EntrySort,
+    pub default_view: EntryView,
+    // Open entries at their link instead of in the reader
+    pub open_in_browser: bool,
+}
+
+impl FeedPreferences {
+    // A feed's preferences column, falling back to the defaults when it's unset or unreadable
+    pub fn from_column(value: Option<&str>) -> Self {
+        value.and_then(|value| serde_json::from_str(value).ok()).unwrap_or_default()
+    }
+
+    // The column value for these preferences; None when they are all the defaults
+    pub fn to_column(&self) -> Result<Option<String>, String> {
+        if *self == Self::default() {
+            return Ok(None);
+        }
+        serde_json::to_string(self)
+            .map(Some)
+            .map_err(|e| format!("Failed to serialize feed preferences: {}", e))
+    }
+
+    // Fill in the order and view a request leaves unset
+    pub fn fill_request(&self, request: &mut EntryQueryRequest) {
+        if request.sort.is_none() && self.sort == EntrySort::OldestFirst {
+            request.sort = Some("oldest".to_string());
+        }
+        if request.view.is_none() {
+            request.view = Some(self.default_view);
+        }
+    }
+}
+
+// Apply the preferences of the feed a request lists, if it lists one feed
+pub async fn apply_feed_preferences<C: ConnectionTrait>(db: &C, request: &mut EntryQueryRequest) -> Result<(), String> {
+    let Some(feed_id) = request.feed_id else {
+        return Ok(());
+    };
+    if request.sort.is_some() && request.view.is_some() {
+        return Ok(());
+    }
+    let preferences: Option<Option<String>> = Feed::find_by_id(feed_id)
+        .select_only()
+        .column(feed::Column::Preferences)
+        .into_tuple()
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed: {}", e))?;
+    FeedPreferences::from_column(preferences.flatten().as_deref()).fill_request(request);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::entry_query::build_entry_query;
+
+    #[test]
+    fn test_preferences_fill_only_what_the_request_leaves_unset() {
+        let preferences = FeedPreferences::from_column(Some(r#"{"sort":"oldest_first","default_view":"unread"}"#));
+        assert!(!preferences.open_in_browser);
+
+        let mut request = EntryQueryRequest { feed_id: Some(1), ..Default::default() };
+        preferences.fill_request(&mut request);
+        let sql = build_entry_query(&request, chrono::Utc::now().naive_utc())
+            .unwrap()
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(sql.contains(r#""feed_entry"."is_read" = FALSE"#));
+        assert!(sql.ends_with(r#"ORDER BY "feed_entry"."published_at" ASC, "feed_entry"."id" ASC"#));
+
+        let mut request = EntryQueryRequest {
+            sort: Some("newest".to_string()),
+            view: Some(EntryView::All),
+            ..Default::default()
+        };
+        preferences.fill_request(&mut request);
+        assert_eq!(request.sort.as_deref(), Some("newest"));
+        assert_eq!(request.view, Some(EntryView::All));
+    }
+
+    #[test]
+    fn test_default_preferences_clear_the_column() {
+        assert_eq!(FeedPreferences::default().to_column().unwrap(), None);
+        assert_eq!(FeedPreferences::from_column(Some("not json")), FeedPreferences::default());
+        let preferences = FeedPreferences { open_in_browser: true, ..Default::default() };
+        assert_eq!(FeedPreferences::from_column(preferences.to_column().unwrap().as_deref()), preferences);
+    }
+}
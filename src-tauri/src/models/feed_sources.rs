@@ -0,0 +1,502 @@
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use tokio::time::timeout;
+use crate::models::async_feed_fetcher::{AsyncFeedFetcher, FeedFetchError, FetcherConfig};
+use crate::models::feed_parser::{parse_feed_content_limited, FeedParseError, ParsedEntry, ParsedFeed};
+use crate::models::fetch_metrics::FetchMetrics;
+use crate::models::http_transport::HttpTransport;
+use crate::models::github::{github_feed_target, github_releases_api_url, parse_github_releases, GithubWatch};
+use crate::models::ics::{calendar_fetch_url, parse_ics_calendar};
+
+// Where a feed's document comes from, stored in feed.source_type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedSourceType {
+    // An RSS or Atom document served over HTTP(S)
+    #[default]
+    Http,
+    // An RSS, Atom or JSON Feed file on this computer, addressed by a file:// URL
+    LocalFile,
+    // A directory of Markdown files on this computer, one entry per file
+    MarkdownDirectory,
+    // An iCalendar (.ics) file served over HTTP(S) or webcal://, one entry per upcoming event
+    Calendar,
+    // A GitHub repository's releases.atom or tags.atom; releases come from the API when a token is set
+    Github,
+}
+
+impl FeedSourceType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedSourceType::Http => "http",
+            FeedSourceType::LocalFile => "local_file",
+            FeedSourceType::MarkdownDirectory => "markdown_directory",
+            FeedSourceType::Calendar => "calendar",
+            FeedSourceType::Github => "github",
+        }
+    }
+
+    // Whether fetching goes over the network, and so counts against the host's circuit
+    // breaker, rate limit and concurrent request limit
+    pub fn is_remote(&self) -> bool {
+        match self {
+            FeedSourceType::Http | FeedSourceType::Calendar | FeedSourceType::Github => true,
+            FeedSourceType::LocalFile | FeedSourceType::MarkdownDirectory => false,
+        }
+    }
+
+    // Check that a new feed's URL can be fetched from this kind of source
+    pub async fn validate_url(&self, url: &str, allow_private: bool) -> Result<(), String> {
+        match self {
+            FeedSourceType::Http => crate::models::url_guard::validate_fetch_url(url, allow_private).await,
+            FeedSourceType::Calendar => crate::models::url_guard::validate_fetch_url(&calendar_fetch_url(url), allow_private).await,
+            FeedSourceType::Github => match github_feed_target(url) {
+                Some(_) => Ok(()),
+                None => Err(format!("Not a GitHub releases or tags feed: {}", url)),
+            },
+            FeedSourceType::LocalFile | FeedSourceType::MarkdownDirectory => {
+                let path = local_file_path(url).map_err(|e| e.to_string())?;
+                let wants_directory = *self == FeedSourceType::MarkdownDirectory;
+                match tokio::fs::metadata(&path).await {
+                    Ok(metadata) if metadata.is_dir() == wants_directory => Ok(()),
+                    Ok(_) if wants_directory => Err(format!("{} is not a directory", path.display())),
+                    Ok(_) => Err(format!("{} is not a file", path.display())),
+                    Err(e) => Err(format!("Cannot read {}: {}", path.display(), e)),
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for FeedSourceType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "http" => Ok(FeedSourceType::Http),
+            "local_file" => Ok(FeedSourceType::LocalFile),
+            "markdown_directory" => Ok(FeedSourceType::MarkdownDirectory),
+            "calendar" => Ok(FeedSourceType::Calendar),
+            "github" => Ok(FeedSourceType::Github),
+            other => Err(format!("Unknown feed source type: {}", other)),
+        }
+    }
+}
+
+// A stored source type, treating anything unrecognised as HTTP like feeds from before source types
+pub fn feed_source_type(source_type: &str) -> FeedSourceType {
+    source_type.parse().unwrap_or_default()
+}
+
+// Something a feed can be fetched from. Implementations only fetch and parse; retries,
+// rate limiting and saving are the fetcher's job.
+pub trait FeedSource {
+    fn fetch(
+        &self,
+        url: &str,
+        config: &FetcherConfig,
+        bytes_downloaded: &mut u64,
+    ) -> impl Future<Output = Result<ParsedFeed, FeedFetchError>> + Send;
+}
+
+// Feeds downloaded over HTTP(S) through the fetcher's transport
+pub struct HttpSource<'a> {
+    pub transport: &'a dyn HttpTransport,
+    pub metrics: &'a FetchMetrics,
+}
+
+impl FeedSource for HttpSource<'_> {
+    async fn fetch(&self, url: &str, config: &FetcherConfig, bytes_downloaded: &mut u64) -> Result<ParsedFeed, FeedFetchError> {
+        let start_time = Instant::now();
+
+        println!("🌐 Fetching feed from: {}", url);
+
+        #[cfg(feature = "fault-injection")]
+        if let Some(fault) = crate::models::fault_injection::next_fault(url) {
+            let (content_type, content) = timeout(config.request_timeout, fault.respond(url, self.transport))
+                .await
+                .map_err(|_| FeedFetchError::Timeout)??;
+            *bytes_downloaded += content.len() as u64;
+            self.metrics.record_bytes(content.len()).await;
+            return AsyncFeedFetcher::parse_downloaded_feed(&content_type, &content, config.max_entries_per_fetch, start_time);
+        }
+
+        let (content_type, content) = timeout(config.request_timeout, self.transport.get(url))
+            .await
+            .map_err(|_| FeedFetchError::Timeout)??;
+        *bytes_downloaded += content.len() as u64;
+        self.metrics.record_bytes(content.len()).await;
+        AsyncFeedFetcher::parse_downloaded_feed(&content_type, &content, config.max_entries_per_fetch, start_time)
+    }
+}
+
+// Calendars downloaded like HTTP feeds, with each upcoming event read as an entry
+pub struct CalendarSource<'a> {
+    pub transport: &'a dyn HttpTransport,
+    pub metrics: &'a FetchMetrics,
+}
+
+impl FeedSource for CalendarSource<'_> {
+    async fn fetch(&self, url: &str, config: &FetcherConfig, bytes_downloaded: &mut u64) -> Result<ParsedFeed, FeedFetchError> {
+        let start_time = Instant::now();
+        let fetch_url = calendar_fetch_url(url);
+
+        println!("📅 Fetching calendar from: {}", fetch_url);
+
+        let (_content_type, content) = timeout(config.request_timeout, self.transport.get(&fetch_url))
+            .await
+            .map_err(|_| FeedFetchError::Timeout)??;
+        *bytes_downloaded += content.len() as u64;
+        self.metrics.record_bytes(content.len()).await;
+
+        let parsed_feed = parse_ics_calendar(&content, url, chrono::Utc::now(), config.max_entries_per_fetch).map_err(|e| match e {
+            FeedParseError::NetworkError(msg) => FeedFetchError::NetworkError(msg),
+            FeedParseError::ParseError(msg) => FeedFetchError::ParseError(msg),
+        })?;
+        println!("✅ Read {} upcoming events from '{}' in {:?}", parsed_feed.entries.len(), parsed_feed.title, start_time.elapsed());
+        Ok(parsed_feed)
+    }
+}
+
+// A GitHub repository's releases or tags. Without a token this is the repository's Atom feed;
+// with one, releases come from the API instead. Either way entries are keyed by their page on
+// GitHub, so adding or removing the token later doesn't duplicate them.
+pub struct GithubSource<'a> {
+    pub transport: &'a dyn HttpTransport,
+    pub metrics: &'a FetchMetrics,
+    pub token: Option<String>,
+}
+
+impl FeedSource for GithubSource<'_> {
+    async fn fetch(&self, url: &str, config: &FetcherConfig, bytes_downloaded: &mut u64) -> Result<ParsedFeed, FeedFetchError> {
+        let target = github_feed_target(url);
+        let (Some((owner, repo, GithubWatch::Releases)), Some(token)) = (target, &self.token) else {
+            let mut parsed_feed = HttpSource { transport: self.transport, metrics: self.metrics }
+                .fetch(url, config, bytes_downloaded)
+                .await?;
+            for entry in &mut parsed_feed.entries {
+                entry.guid = entry.link.clone().or(entry.guid.take());
+            }
+            return Ok(parsed_feed);
+        };
+
+        let start_time = Instant::now();
+        let api_url = github_releases_api_url(&owner, &repo, config.max_entries_per_fetch);
+        println!("🐙 Fetching releases from: {}", api_url);
+
+        let headers = [
+            ("Authorization", format!("Bearer {}", token)),
+            ("Accept", "application/vnd.github+json".to_string()),
+            // The API turns away requests without a user agent
+            ("User-Agent", "reader".to_string()),
+        ];
+        let (_content_type, content) = timeout(config.request_timeout, self.transport.get_with_headers(&api_url, &headers))
+            .await
+            .map_err(|_| FeedFetchError::Timeout)??;
+        *bytes_downloaded += content.len() as u64;
+        self.metrics.record_bytes(content.len()).await;
+
+        let parsed_feed = parse_github_releases(&content, &owner, &repo).map_err(|e| match e {
+            FeedParseError::NetworkError(msg) => FeedFetchError::NetworkError(msg),
+            FeedParseError::ParseError(msg) => FeedFetchError::ParseError(msg),
+        })?;
+        println!("✅ Read {} releases of {}/{} in {:?}", parsed_feed.entries.len(), owner, repo, start_time.elapsed());
+        Ok(parsed_feed)
+    }
+}
+
+// Feeds read from a file:// URL, e.g. one written by a local script or synced from elsewhere.
+// Nothing is downloaded, so no bandwidth is recorded.
+pub struct LocalFileSource;
+
+// The path a local source's file:// URL points at
+pub fn local_file_path(url: &str) -> Result<PathBuf, FeedFetchError> {
+    url::Url::parse(url)
+        .ok()
+        .filter(|parsed| parsed.scheme() == "file")
+        .and_then(|parsed| parsed.to_file_path().ok())
+        .ok_or_else(|| FeedFetchError::ParseError(format!("Not a local file URL: {}", url)))
+}
+
+impl FeedSource for LocalFileSource {
+    async fn fetch(&self, url: &str, config: &FetcherConfig, _bytes_downloaded: &mut u64) -> Result<ParsedFeed, FeedFetchError> {
+        let start_time = Instant::now();
+        let path = local_file_path(url)?;
+
+        println!("📂 Reading feed from: {}", path.display());
+
+        let content = timeout(config.request_timeout, tokio::fs::read_to_string(&path))
+            .await
+            .map_err(|_| FeedFetchError::Timeout)?
+            .map_err(|e| FeedFetchError::NetworkError(format!("Failed to read {}: {}", path.display(), e)))?;
+        // Unlike downloads, local files may also be JSON Feeds, which feed-rs reads as well
+        let parsed_feed = parse_feed_content_limited(&content, config.max_entries_per_fetch).map_err(|e| match e {
+            FeedParseError::NetworkError(msg) => FeedFetchError::NetworkError(msg),
+            FeedParseError::ParseError(msg) => FeedFetchError::ParseError(msg),
+        })?;
+        println!("✅ Successfully read and parsed feed '{}' in {:?}", parsed_feed.title, start_time.elapsed());
+        Ok(parsed_feed)
+    }
+}
+
+// Whether a file in a Markdown directory is one of its entries
+pub fn is_markdown_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("md") || extension.eq_ignore_ascii_case("markdown"))
+}
+
+// Split "---"-fenced `key: value` front matter off the top of a Markdown file
+fn split_front_matter(markdown: &str) -> (Vec<(String, String)>, &str) {
+    let Some(rest) = markdown.strip_prefix("---\n").or_else(|| markdown.strip_prefix("---\r\n")) else {
+        return (Vec::new(), markdown);
+    };
+    let Some(end) = rest.find("\n---") else {
+        return (Vec::new(), markdown);
+    };
+    let fields = rest[..end]
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| {
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+            (key.trim().to_lowercase(), value.to_string())
+        })
+        .collect();
+    let body = rest[end + 4..].split_once('\n').map_or("", |(_, body)| body);
+    (fields, body)
+}
+
+// Front matter dates may be full RFC 3339 timestamps or plain dates
+fn parse_front_matter_date(date: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(date)
+        .map(|date| date.with_timezone(&chrono::Utc))
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|date| date.and_utc())
+        })
+}
+
+// One Markdown file as an entry. The title comes from the front matter, the first heading or
+// the file name; the date from the front matter or the file's modification time, which also
+// marks the entry updated whenever the file is edited.
+pub fn parse_markdown_entry(
+    file_url: &str,
+    file_stem: &str,
+    markdown: &str,
+    modified_at: chrono::DateTime<chrono::Utc>,
+) -> ParsedEntry {
+    let (fields, body) = split_front_matter(markdown);
+    let field = |key: &str| {
+        fields
+            .iter()
+            .find(|(name, value)| name == key && !value.is_empty())
+            .map(|(_, value)| value.clone())
+    };
+
+    let title = field("title")
+        .or_else(|| {
+            body.lines()
+                .find_map(|line| line.strip_prefix("# "))
+                .map(|heading| heading.trim().to_string())
+        })
+        .unwrap_or_else(|| file_stem.to_string());
+    let published = field("date").and_then(|date| parse_front_matter_date(&date)).unwrap_or(modified_at);
+
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, pulldown_cmark::Parser::new(body));
+
+    ParsedEntry {
+        guid: Some(file_url.to_string()),
+        title: Some(title),
+        description: field("description").or_else(|| field("summary")),
+        link: Some(file_url.to_string()),
+        published: Some(published.to_rfc3339()),
+        updated: Some(modified_at.to_rfc3339()),
+        content: Some(html),
+        enclosure_url: None,
+        enclosure_type: None,
+        enclosure_length: None,
+        duration_seconds: None,
+        episode_number: None,
+        season_number: None,
+        explicit: None,
+        artwork_url: None,
+        event_starts_at: None,
+        event_ends_at: None,
+    }
+}
+
+// Feeds made from the Markdown files in a directory (not its subdirectories), e.g. notes or
+// drafts written by other tools. The feed is titled after the directory.
+pub struct MarkdownDirectorySource;
+
+impl FeedSource for MarkdownDirectorySource {
+    async fn fetch(&self, url: &str, config: &FetcherConfig, _bytes_downloaded: &mut u64) -> Result<ParsedFeed, FeedFetchError> {
+        let start_time = Instant::now();
+        let directory = local_file_path(url)?;
+        let read_error = |e: std::io::Error| FeedFetchError::NetworkError(format!("Failed to read {}: {}", directory.display(), e));
+
+        println!("📂 Reading Markdown entries from: {}", directory.display());
+
+        let read_entries = async {
+            let mut entries = Vec::new();
+            let mut files = tokio::fs::read_dir(&directory).await.map_err(read_error)?;
+            while let Some(file) = files.next_entry().await.map_err(read_error)? {
+                let path = file.path();
+                let metadata = file.metadata().await.map_err(read_error)?;
+                if !metadata.is_file() || !is_markdown_file(&path) {
+                    continue;
+                }
+                let markdown = tokio::fs::read_to_string(&path).await.map_err(read_error)?;
+                let modified_at = metadata.modified().map(chrono::DateTime::<chrono::Utc>::from).unwrap_or_else(|_| chrono::Utc::now());
+                let file_url = url::Url::from_file_path(&path)
+                    .map_err(|_| FeedFetchError::ParseError(format!("Cannot address {}", path.display())))?;
+                let file_stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+                entries.push(parse_markdown_entry(file_url.as_str(), file_stem, &markdown, modified_at));
+            }
+            Ok::<_, FeedFetchError>(entries)
+        };
+        let mut entries = timeout(config.request_timeout, read_entries)
+            .await
+            .map_err(|_| FeedFetchError::Timeout)??;
+        // Newest first, like a published feed
+        entries.sort_by(|a, b| b.published.cmp(&a.published));
+
+        let title = directory
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("Markdown")
+            .to_string();
+        println!("✅ Read {} Markdown entries from '{}' in {:?}", entries.len(), title, start_time.elapsed());
+
+        Ok(ParsedFeed {
+            title,
+            description: None,
+            url: Some(url.to_string()),
+            entries,
+            ttl_minutes: None,
+            skip_hours: Vec::new(),
+            skip_days: Vec::new(),
+            icon_url: None,
+            format: None,
+            content_hash: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_types_round_trip_and_default_to_http() {
+        for source_type in [FeedSourceType::Http, FeedSourceType::LocalFile, FeedSourceType::MarkdownDirectory, FeedSourceType::Calendar, FeedSourceType::Github] {
+            assert_eq!(source_type.as_str().parse::<FeedSourceType>(), Ok(source_type));
+        }
+        assert_eq!(feed_source_type("gopher"), FeedSourceType::Http);
+        assert!(FeedSourceType::Http.is_remote());
+        assert!(FeedSourceType::Calendar.is_remote());
+        assert!(!FeedSourceType::LocalFile.is_remote());
+        assert!(!FeedSourceType::MarkdownDirectory.is_remote());
+    }
+
+    #[test]
+    fn test_markdown_entry_from_front_matter_or_file() {
+        let modified_at = chrono::DateTime::parse_from_rfc3339("2024-06-02T12:00:00Z").unwrap().with_timezone(&chrono::Utc);
+
+        let entry = parse_markdown_entry(
+            "file:///notes/launch.md",
+            "launch",
+            "---\ntitle: \"Launch plan\"\ndate: 2024-06-01\ndescription: What ships when\n---\nWe ship **Monday**.\n",
+            modified_at,
+        );
+        assert_eq!(entry.title.as_deref(), Some("Launch plan"));
+        assert_eq!(entry.description.as_deref(), Some("What ships when"));
+        assert_eq!(entry.published.as_deref(), Some("2024-06-01T00:00:00+00:00"));
+        assert_eq!(entry.updated.as_deref(), Some("2024-06-02T12:00:00+00:00"));
+        assert_eq!(entry.guid.as_deref(), Some("file:///notes/launch.md"));
+        assert_eq!(entry.content.as_deref(), Some("<p>We ship <strong>Monday</strong>.</p>\n"));
+
+        let entry = parse_markdown_entry("file:///notes/todo.md", "todo", "Intro\n\n# Things to do\n", modified_at);
+        assert_eq!(entry.title.as_deref(), Some("Things to do"));
+        assert_eq!(entry.published.as_deref(), Some("2024-06-02T12:00:00+00:00"));
+
+        let entry = parse_markdown_entry("file:///notes/scratch.md", "scratch", "just text", modified_at);
+        assert_eq!(entry.title.as_deref(), Some("scratch"));
+        assert!(is_markdown_file(Path::new("/notes/a.MD")));
+        assert!(!is_markdown_file(Path::new("/notes/a.txt")));
+    }
+
+    #[tokio::test]
+    async fn test_github_source_keys_entries_by_release_page() {
+        let transport = crate::models::http_transport::MockTransport::new();
+        let metrics = FetchMetrics::new();
+        transport.respond(
+            "https://github.com/o/r/releases.atom",
+            crate::models::http_transport::MockResponse::ok(
+                r#"<?xml version="1.0"?><feed xmlns="http://www.w3.org/2005/Atom"><title>Release notes from r</title>
+                <entry><id>tag:github.com,2008:Repository/1/v1.0.0</id><title>v1.0.0</title><updated>2024-06-01T00:00:00Z</updated>
+                <link rel="alternate" type="text/html" href="https://github.com/o/r/releases/tag/v1.0.0"/></entry></feed>"#,
+            ),
+        );
+        transport.respond(
+            "https://api.github.com/repos/o/r/releases?per_page=100",
+            crate::models::http_transport::MockResponse::ok(
+                r#"[{"html_url": "https://github.com/o/r/releases/tag/v1.0.0", "tag_name": "v1.0.0", "body": "Notes"}]"#,
+            ),
+        );
+        let config = FetcherConfig { max_entries_per_fetch: 0, ..FetcherConfig::default() };
+        let mut bytes_downloaded = 0;
+
+        let atom = GithubSource { transport: &transport, metrics: &metrics, token: None }
+            .fetch("https://github.com/o/r/releases.atom", &config, &mut bytes_downloaded)
+            .await
+            .unwrap();
+        let api = GithubSource { transport: &transport, metrics: &metrics, token: Some("secret".to_string()) }
+            .fetch("https://github.com/o/r/releases.atom", &config, &mut bytes_downloaded)
+            .await
+            .unwrap();
+
+        assert_eq!(atom.entries[0].guid.as_deref(), Some("https://github.com/o/r/releases/tag/v1.0.0"));
+        assert_eq!(api.entries[0].guid, atom.entries[0].guid);
+        assert_eq!(api.entries[0].content.as_deref(), Some("<p>Notes</p>\n"));
+    }
+
+    #[tokio::test]
+    async fn test_local_file_source_reads_and_parses_the_file() {
+        let path = std::env::temp_dir().join(format!("feed-source-{}.xml", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Local</title><link>https://example.com</link><description>d</description>
+            <item><title>First</title><link>https://example.com/1</link></item></channel></rss>"#,
+        )
+        .unwrap();
+        let url = url::Url::from_file_path(&path).unwrap().to_string();
+        let mut bytes_downloaded = 0;
+
+        let parsed = LocalFileSource.fetch(&url, &FetcherConfig::default(), &mut bytes_downloaded).await;
+        std::fs::remove_file(&path).unwrap();
+
+        let parsed = parsed.unwrap();
+        assert_eq!(parsed.title, "Local");
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(bytes_downloaded, 0);
+        assert!(LocalFileSource.fetch("https://example.com/feed.xml", &FetcherConfig::default(), &mut bytes_downloaded).await.is_err());
+
+        // JSON Feeds are read too
+        std::fs::write(
+            &path,
+            r#"{"version": "https://jsonfeed.org/version/1.1", "title": "Generated", "items": [{"id": "1", "url": "https://example.com/1", "title": "First"}]}"#,
+        )
+        .unwrap();
+        let parsed = LocalFileSource.fetch(&url, &FetcherConfig::default(), &mut bytes_downloaded).await;
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(parsed.unwrap().entries[0].title.as_deref(), Some("First"));
+    }
+}
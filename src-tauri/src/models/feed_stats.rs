@@ -0,0 +1,154 @@
+use chrono::NaiveDateTime;
+use sea_orm::*;
+use sea_orm::sea_query::Expr;
+use crate::entities::{prelude::*, *};
+use crate::models::responses::{FeedOpenStatsResponse, FeedStatsResponse};
+
+// How many of a feed's most recent fetches are used for duration and error rate
+pub const FEED_STATS_FETCH_WINDOW: u64 = 100;
+
+const SECONDS_PER_WEEK: f64 = 7.0 * 24.0 * 60.0 * 60.0;
+
+// Load when each of a feed's entries was posted. Entries without a publish date
+// count from when we first saw them.
+pub async fn load_post_dates<C: ConnectionTrait>(db: &C, feed_id: i32) -> Result<Vec<NaiveDateTime>, String> {
+    let dates = FeedEntry::find()
+        .select_only()
+        .column(feed_entry::Column::PublishedAt)
+        .column(feed_entry::Column::CreatedAt)
+        .filter(feed_entry::Column::FeedId.eq(feed_id))
+        .into_tuple::<(Option<NaiveDateTime>, NaiveDateTime)>()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entries: {}", e))?;
+
+    Ok(dates
+        .into_iter()
+        .map(|(published_at, created_at)| published_at.unwrap_or(created_at))
+        .collect())
+}
+
+// Feed id and title, total opens, entries opened and the latest open
+type FeedOpenRow = (i32, Option<String>, i64, i64, Option<NaiveDateTime>);
+
+// Feeds whose entries were opened most often, most opened first
+pub async fn load_most_opened_feeds<C: ConnectionTrait>(db: &C, limit: u64) -> Result<Vec<FeedOpenStatsResponse>, String> {
+    let rows: Vec<FeedOpenRow> = FeedEntry::find()
+        .select_only()
+        .column(feed_entry::Column::FeedId)
+        .column(feed::Column::Title)
+        .column_as(Expr::cust(r#"SUM("feed_entry"."open_count")"#), "open_count")
+        .column_as(feed_entry::Column::Id.count(), "opened_entries")
+        .column_as(feed_entry::Column::LastOpenedAt.max(), "last_opened_at")
+        .inner_join(Feed)
+        .filter(feed_entry::Column::OpenCount.gt(0))
+        .group_by(feed_entry::Column::FeedId)
+        .group_by(feed::Column::Title)
+        .order_by_desc(Expr::cust(r#"SUM("feed_entry"."open_count")"#))
+        .limit(limit)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to count entry opens: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(feed_id, feed_title, open_count, opened_entries, last_opened_at)| FeedOpenStatsResponse {
+            feed_id,
+            feed_title,
+            open_count,
+            opened_entries,
+            last_opened_at: last_opened_at.map(|dt| dt.and_utc().to_rfc3339()),
+        })
+        .collect())
+}
+
+// Compute a feed's statistics from its entries' post dates and its recent fetch log.
+// Posting frequency is averaged from the oldest post up to now, over at least one week.
+pub fn compute_feed_stats(
+    feed_id: i32,
+    post_dates: &[NaiveDateTime],
+    fetch_logs: &[fetch_log::Model],
+    now: NaiveDateTime,
+) -> FeedStatsResponse {
+    let first_post = post_dates.iter().min();
+    let last_post = post_dates.iter().max();
+
+    let average_posts_per_week = match first_post {
+        Some(first_post) => {
+            let weeks = ((now - *first_post).num_seconds() as f64 / SECONDS_PER_WEEK).max(1.0);
+            post_dates.len() as f64 / weeks
+        }
+        None => 0.0,
+    };
+
+    let total_fetches = fetch_logs.len();
+    let (average_fetch_duration_ms, error_rate) = if total_fetches == 0 {
+        (None, 0.0)
+    } else {
+        let total_duration: i64 = fetch_logs.iter().map(|log| log.duration_ms).sum();
+        let failed = fetch_logs.iter().filter(|log| !log.success).count();
+        (
+            Some(total_duration as f64 / total_fetches as f64),
+            failed as f64 / total_fetches as f64,
+        )
+    };
+
+    FeedStatsResponse {
+        feed_id,
+        total_entries: post_dates.len(),
+        average_posts_per_week,
+        last_post_at: last_post.map(|dt| dt.and_utc().to_rfc3339()),
+        total_fetches,
+        average_fetch_duration_ms,
+        error_rate,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn log(duration_ms: i64, success: bool) -> fetch_log::Model {
+        fetch_log::Model {
+            id: 0,
+            feed_id: 1,
+            fetched_at: chrono::Utc::now().naive_utc(),
+            duration_ms,
+            success,
+            entries_added: 0,
+            error_type: if success { None } else { Some("network".to_string()) },
+            error_message: None,
+        }
+    }
+
+    #[test]
+    fn test_feed_stats_from_entries_and_fetch_log() {
+        let now = chrono::Utc::now().naive_utc();
+        let post_dates: Vec<_> = (0..8).map(|i| now - Duration::days(i * 3)).collect();
+        let fetch_logs = vec![log(100, true), log(300, true), log(200, false), log(400, true)];
+
+        let stats = compute_feed_stats(1, &post_dates, &fetch_logs, now);
+
+        assert_eq!(stats.total_entries, 8);
+        // 8 posts over the 3 weeks since the oldest one
+        assert!((stats.average_posts_per_week - 8.0 / 3.0).abs() < 0.01);
+        assert_eq!(stats.last_post_at, Some(now.and_utc().to_rfc3339()));
+        assert_eq!(stats.total_fetches, 4);
+        assert_eq!(stats.average_fetch_duration_ms, Some(250.0));
+        assert_eq!(stats.error_rate, 0.25);
+    }
+
+    #[test]
+    fn test_feed_stats_for_new_feed() {
+        let now = chrono::Utc::now().naive_utc();
+
+        let stats = compute_feed_stats(1, &[now - Duration::days(1)], &[], now);
+
+        // Less than a week of history still counts as a full week
+        assert_eq!(stats.average_posts_per_week, 1.0);
+        assert_eq!(stats.average_fetch_duration_ms, None);
+        assert_eq!(stats.error_rate, 0.0);
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use crate::models::async_feed_fetcher::FeedFetchError;
+
+// Upper bounds (in seconds) of the fetch duration histogram buckets
+const DURATION_BUCKETS_SECONDS: [f64; 8] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+// Percentiles are computed over this many of the most recent requests
+const RECENT_DURATION_SAMPLES: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DurationBucket {
+    pub le_seconds: f64,
+    // Requests that took at most le_seconds (cumulative, as in Prometheus)
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FetchMetricsSnapshot {
+    pub requests: u64,
+    pub successes: u64,
+    // Failed requests by FeedFetchError::error_type
+    pub failures: BTreeMap<String, u64>,
+    pub bytes_downloaded: u64,
+    pub duration_p50_ms: Option<u64>,
+    pub duration_p90_ms: Option<u64>,
+    pub duration_p99_ms: Option<u64>,
+    pub duration_buckets: Vec<DurationBucket>,
+    pub duration_sum_seconds: f64,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    requests: u64,
+    successes: u64,
+    failures: BTreeMap<String, u64>,
+    bytes_downloaded: u64,
+    // Non-cumulative counts per bucket; requests slower than the last bound only count in `requests`
+    bucket_counts: [u64; DURATION_BUCKETS_SECONDS.len()],
+    duration_sum_seconds: f64,
+    recent_durations_ms: VecDeque<u64>,
+}
+
+// Counters for every HTTP request the fetcher makes (each retry attempt counts), kept in
+// memory since the app started
+#[derive(Debug, Clone, Default)]
+pub struct FetchMetrics {
+    state: Arc<RwLock<MetricsState>>,
+}
+
+// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[u64], fraction: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (fraction * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.clamp(1, sorted.len()) - 1).copied()
+}
+
+impl FetchMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn record_request(&self, duration: Duration, outcome: Result<(), &FeedFetchError>) {
+        let mut state = self.state.write().await;
+        state.requests += 1;
+        match outcome {
+            Ok(()) => state.successes += 1,
+            Err(error) => *state.failures.entry(error.error_type().to_string()).or_insert(0) += 1,
+        }
+
+        let seconds = duration.as_secs_f64();
+        state.duration_sum_seconds += seconds;
+        if let Some(bucket) = DURATION_BUCKETS_SECONDS.iter().position(|bound| seconds <= *bound) {
+            state.bucket_counts[bucket] += 1;
+        }
+
+        if state.recent_durations_ms.len() == RECENT_DURATION_SAMPLES {
+            state.recent_durations_ms.pop_front();
+        }
+        state.recent_durations_ms.push_back(duration.as_millis() as u64);
+    }
+
+    pub async fn record_bytes(&self, bytes: usize) {
+        self.state.write().await.bytes_downloaded += bytes as u64;
+    }
+
+    pub async fn snapshot(&self) -> FetchMetricsSnapshot {
+        let state = self.state.read().await;
+
+        let mut sorted: Vec<u64> = state.recent_durations_ms.iter().copied().collect();
+        sorted.sort_unstable();
+
+        let mut cumulative = 0;
+        let duration_buckets = DURATION_BUCKETS_SECONDS
+            .iter()
+            .zip(state.bucket_counts)
+            .map(|(bound, count)| {
+                cumulative += count;
+                DurationBucket {
+                    le_seconds: *bound,
+                    count: cumulative,
+                }
+            })
+            .collect();
+
+        FetchMetricsSnapshot {
+            requests: state.requests,
+            successes: state.successes,
+            failures: state.failures.clone(),
+            bytes_downloaded: state.bytes_downloaded,
+            duration_p50_ms: percentile(&sorted, 0.5),
+            duration_p90_ms: percentile(&sorted, 0.9),
+            duration_p99_ms: percentile(&sorted, 0.99),
+            duration_buckets,
+            duration_sum_seconds: state.duration_sum_seconds,
+        }
+    }
+}
+
+impl FetchMetricsSnapshot {
+    // Prometheus text exposition format, for scraping or pasting into other tools
+    pub fn to_prometheus_text(&self) -> String {
+        let mut text = String::new();
+
+        let mut counter = |name: &str, help: &str| {
+            let _ = writeln!(text, "# HELP {} {}", name, help);
+            let _ = writeln!(text, "# TYPE {} counter", name);
+        };
+        counter("reader_fetch_requests_total", "Feed fetch requests sent, including retries.");
+        counter("reader_fetch_successes_total", "Feed fetch requests that returned a parsed feed.");
+        counter("reader_fetch_failures_total", "Feed fetch requests that failed, by error type.");
+        counter("reader_fetch_bytes_total", "Feed bytes downloaded.");
+
+        let _ = writeln!(text, "reader_fetch_requests_total {}", self.requests);
+        let _ = writeln!(text, "reader_fetch_successes_total {}", self.successes);
+        for (error_type, count) in &self.failures {
+            let _ = writeln!(text, "reader_fetch_failures_total{{type=\"{}\"}} {}", error_type, count);
+        }
+        let _ = writeln!(text, "reader_fetch_bytes_total {}", self.bytes_downloaded);
+
+        let _ = writeln!(text, "# HELP reader_fetch_duration_seconds Feed fetch request duration.");
+        let _ = writeln!(text, "# TYPE reader_fetch_duration_seconds histogram");
+        for bucket in &self.duration_buckets {
+            let _ = writeln!(text, "reader_fetch_duration_seconds_bucket{{le=\"{}\"}} {}", bucket.le_seconds, bucket.count);
+        }
+        let _ = writeln!(text, "reader_fetch_duration_seconds_bucket{{le=\"+Inf\"}} {}", self.requests);
+        let _ = writeln!(text, "reader_fetch_duration_seconds_sum {}", self.duration_sum_seconds);
+        let _ = writeln!(text, "reader_fetch_duration_seconds_count {}", self.requests);
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_metrics_count_requests_failures_and_bytes() {
+        let metrics = FetchMetrics::new();
+
+        metrics.record_request(Duration::from_millis(80), Ok(())).await;
+        metrics.record_bytes(2048).await;
+        metrics.record_request(Duration::from_millis(400), Err(&FeedFetchError::Timeout)).await;
+        metrics.record_request(Duration::from_secs(60), Err(&FeedFetchError::Timeout)).await;
+
+        let snapshot = metrics.snapshot().await;
+
+        assert_eq!(snapshot.requests, 3);
+        assert_eq!(snapshot.successes, 1);
+        assert_eq!(snapshot.failures.get("timeout"), Some(&2));
+        assert_eq!(snapshot.bytes_downloaded, 2048);
+        // Buckets are cumulative; the 60s request only shows up in +Inf
+        assert_eq!(snapshot.duration_buckets.first().map(|bucket| bucket.count), Some(1));
+        assert_eq!(snapshot.duration_buckets.last().map(|bucket| bucket.count), Some(2));
+    }
+
+    #[test]
+    fn test_percentiles_use_nearest_rank() {
+        let samples: Vec<u64> = (1..=100).collect();
+
+        assert_eq!(percentile(&samples, 0.5), Some(50));
+        assert_eq!(percentile(&samples, 0.99), Some(99));
+        assert_eq!(percentile(&[7], 0.9), Some(7));
+        assert_eq!(percentile(&[], 0.5), None);
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_text_format() {
+        let metrics = FetchMetrics::new();
+        metrics.record_request(Duration::from_millis(200), Err(&FeedFetchError::HttpStatus(503))).await;
+
+        let text = metrics.snapshot().await.to_prometheus_text();
+
+        assert!(text.contains("# TYPE reader_fetch_requests_total counter\n"));
+        assert!(text.contains("reader_fetch_requests_total 1\n"));
+        assert!(text.contains(&format!("reader_fetch_failures_total{{type=\"{}\"}} 1\n", FeedFetchError::HttpStatus(503).error_type())));
+        assert!(text.contains("reader_fetch_duration_seconds_bucket{le=\"0.25\"} 1\n"));
+        assert!(text.contains("reader_fetch_duration_seconds_bucket{le=\"+Inf\"} 1\n"));
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::HashSet;
+use std::time::Duration;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast;
+use crate::models::operations::Operation;
+use crate::models::responses::FeedRefreshStatus;
+use crate::models::state::AppState;
+
+pub const FOLDER_REFRESH_PROGRESS_EVENT: &str = "refresh:folder_progress";
+// Every fetch that saves to the library, queued or not, emits its FeedRefreshStatus
+pub const FETCH_RESULT_EVENT: &str = "fetch:result";
+
+// A refresh gives up waiting once no feed has finished for this long, so a feed that
+// was deleted or dropped from the queue can't keep its spinner going forever
+const REFRESH_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+// Progress of one refresh_folder call. Every event carries its operation_id, so refreshes of
+// different folders (or the same folder twice) can be told apart. The id is that of the
+// refresh's Operation, which reports the same progress as operation:progress events.
+#[derive(Debug, Clone, Serialize)]
+pub struct FolderRefreshProgress {
+    pub operation_id: u64,
+    pub folder_id: i32,
+    pub is_active: bool,
+    pub total_feeds: usize,
+    pub completed_feeds: usize,
+    pub failed_feeds: usize,
+    pub entries_added: usize,
+    // The feed whose completion produced this event
+    pub last_feed: Option<FeedRefreshStatus>,
+}
+
+impl FolderRefreshProgress {
+    pub fn new(operation_id: u64, folder_id: i32, total_feeds: usize) -> Self {
+        FolderRefreshProgress {
+            operation_id,
+            folder_id,
+            is_active: total_feeds > 0,
+            total_feeds,
+            completed_feeds: 0,
+            failed_feeds: 0,
+            entries_added: 0,
+            last_feed: None,
+        }
+    }
+
+    // Count a completed feed if it is one this operation still waits on. Returns whether it was.
+    pub fn record(&mut self, pending_urls: &mut HashSet<String>, status: FeedRefreshStatus) -> bool {
+        if !pending_urls.remove(&status.feed_url) {
+            return false;
+        }
+        self.completed_feeds += 1;
+        if status.status == "failed" {
+            self.failed_feeds += 1;
+        }
+        self.entries_added += status.entries_added;
+        self.is_active = !pending_urls.is_empty();
+        self.last_feed = Some(status);
+        true
+    }
+}
+
+fn emit_progress(app: &AppHandle, progress: &FolderRefreshProgress) {
+    if let Err(e) = app.emit(FOLDER_REFRESH_PROGRESS_EVENT, progress) {
+        eprintln!("❌ Failed to emit folder refresh progress: {}", e);
+    }
+}
+
+// How following a refresh's feeds ended
+enum FollowOutcome {
+    Done,
+    Cancelled,
+    // The feed statuses stopped arriving, or the app is shutting down
+    Interrupted,
+}
+
+// Wait for the feeds of a refresh to complete, passing each status to `on_status`, which
+// takes finished feeds out of `pending_urls`. Stops early when the operation is cancelled.
+async fn follow_feed_statuses(
+    app: &AppHandle,
+    operation: &Operation,
+    pending_urls: &mut HashSet<String>,
+    statuses: &mut broadcast::Receiver<FeedRefreshStatus>,
+    mut on_status: impl FnMut(&mut HashSet<String>, FeedRefreshStatus),
+) -> FollowOutcome {
+    let mut shutdown = app.state::<AppState>().shutdown_signal.subscribe();
+
+    while !pending_urls.is_empty() {
+        tokio::select! {
+            status = tokio::time::timeout(REFRESH_IDLE_TIMEOUT, statuses.recv()) => match status {
+                Ok(Ok(status)) => on_status(pending_urls, status),
+                Ok(Err(broadcast::error::RecvError::Lagged(skipped))) => {
+                    eprintln!("⚠️ Refresh {} missed {} feed statuses", operation.id(), skipped);
+                }
+                Ok(Err(broadcast::error::RecvError::Closed)) | Err(_) => return FollowOutcome::Interrupted,
+            },
+            _ = operation.cancelled() => return FollowOutcome::Cancelled,
+            _ = shutdown.wait_for(|stopping| *stopping) => return FollowOutcome::Interrupted,
+        }
+    }
+    FollowOutcome::Done
+}
+
+// Follow the feeds of one folder refresh as they complete, emitting progress after each, until
+// all of them are done or the refresh is cancelled. The statuses receiver must be subscribed
+// before the feeds are queued.
+pub async fn track_folder_refresh(
+    app: AppHandle,
+    operation: Operation,
+    mut progress: FolderRefreshProgress,
+    mut pending_urls: HashSet<String>,
+    mut statuses: broadcast::Receiver<FeedRefreshStatus>,
+) {
+    let outcome = follow_feed_statuses(&app, &operation, &mut pending_urls, &mut statuses, |pending_urls, status| {
+        let succeeded = status.status != "failed";
+        if progress.record(pending_urls, status) {
+            operation.record(succeeded);
+            emit_progress(&app, &progress);
+        }
+    })
+    .await;
+
+    if progress.is_active {
+        match outcome {
+            FollowOutcome::Cancelled => {
+                if let Some(fetcher) = &app.state::<AppState>().async_fetcher {
+                    fetcher.skip_queued_feeds(pending_urls.iter().cloned());
+                }
+                println!("⏹️ Folder refresh {} cancelled", progress.operation_id);
+            }
+            FollowOutcome::Interrupted => println!(
+                "⏱️ Folder refresh {} stopped waiting with {} feeds outstanding",
+                progress.operation_id,
+                pending_urls.len()
+            ),
+            FollowOutcome::Done => {}
+        }
+        progress.is_active = false;
+        progress.last_feed = None;
+        emit_progress(&app, &progress);
+    }
+    operation.finish(Ok(()));
+}
+
+// Follow the feeds of a global refresh (refresh_all_feeds, refresh_single_feed) as they
+// complete, recording each on its operation
+pub async fn track_refresh(
+    app: AppHandle,
+    operation: Operation,
+    mut pending_urls: HashSet<String>,
+    mut statuses: broadcast::Receiver<FeedRefreshStatus>,
+) {
+    let outcome = follow_feed_statuses(&app, &operation, &mut pending_urls, &mut statuses, |pending_urls, status| {
+        if pending_urls.remove(&status.feed_url) {
+            operation.record(status.status != "failed");
+        }
+    })
+    .await;
+
+    if let (FollowOutcome::Cancelled, Some(fetcher)) = (outcome, &app.state::<AppState>().async_fetcher) {
+        let skipped = fetcher.cancel_refresh().await;
+        println!("⏹️ Refresh cancelled, skipping {} queued feeds", skipped);
+    }
+    operation.finish(Ok(()));
+}
+
+// Emit a fetch:result event for every feed the fetcher finishes, for the life of the app
+pub async fn run_fetch_result_events(app: AppHandle, mut statuses: broadcast::Receiver<FeedRefreshStatus>) {
+    loop {
+        match statuses.recv().await {
+            Ok(status) => {
+                if let Err(e) = app.emit(FETCH_RESULT_EVENT, &status) {
+                    eprintln!("Failed to emit fetch result: {}", e);
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                eprintln!("⚠️ {} fetch results were not emitted", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(url: &str, status: &str, entries_added: usize) -> FeedRefreshStatus {
+        FeedRefreshStatus {
+            feed_id: 1,
+            feed_url: url.to_string(),
+            feed_title: None,
+            status: status.to_string(),
+            entries_added,
+            entries_updated: 0,
+            last_fetched_at: String::new(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_only_the_operations_own_feeds_count() {
+        let mut pending: HashSet<String> = ["https://a.example/feed", "https://b.example/feed"].map(String::from).into();
+        let mut progress = FolderRefreshProgress::new(1, 5, pending.len());
+
+        assert!(!progress.record(&mut pending, status("https://elsewhere.example/feed", "success", 3)));
+        assert!(progress.record(&mut pending, status("https://a.example/feed", "success", 4)));
+        assert!(progress.is_active);
+        // A feed finishing twice is only counted once
+        assert!(!progress.record(&mut pending, status("https://a.example/feed", "success", 4)));
+        assert!(progress.record(&mut pending, status("https://b.example/feed", "failed", 0)));
+
+        assert!(!progress.is_active);
+        assert_eq!(progress.completed_feeds, 2);
+        assert_eq!(progress.failed_feeds, 1);
+        assert_eq!(progress.entries_added, 4);
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::{HashMap, HashSet};
+use crate::entities::folder;
+use crate::models::responses::FolderUnreadCount;
+
+fn parent_ids(folders: &[folder::Model]) -> HashMap<i32, Option<i32>> {
+    folders.iter().map(|folder| (folder.id, folder.parent_id)).collect()
+}
+
+// The folder itself followed by each of its ancestors up to the top level. Stops early
+// if the stored hierarchy somehow loops.
+fn self_and_ancestors(parents: &HashMap<i32, Option<i32>>, folder_id: i32) -> Vec<i32> {
+    let mut chain = Vec::new();
+    let mut visited = HashSet::new();
+    let mut current = Some(folder_id);
+
+    while let Some(id) = current {
+        if !visited.insert(id) {
+            break;
+        }
+        chain.push(id);
+        current = parents.get(&id).copied().flatten();
+    }
+
+    chain
+}
+
+// Whether moving a folder into new_parent_id would make it its own ancestor
+pub fn would_create_cycle(folders: &[folder::Model], folder_id: i32, new_parent_id: i32) -> bool {
+    self_and_ancestors(&parent_ids(folders), new_parent_id).contains(&folder_id)
+}
+
+// The folder and every folder nested anywhere below it
+pub fn self_and_descendants(folders: &[folder::Model], folder_id: i32) -> Vec<i32> {
+    let parents = parent_ids(folders);
+    folders
+        .iter()
+        .map(|folder| folder.id)
+        .filter(|&id| self_and_ancestors(&parents, id).contains(&folder_id))
+        .collect()
+}
+
+// Add each folder's unread count to all of its ancestors, so a folder's total covers its whole subtree
+pub fn roll_up_unread_counts(folders: &[folder::Model], direct_counts: &HashMap<i32, u64>) -> Vec<FolderUnreadCount> {
+    let parents = parent_ids(folders);
+    let mut totals: HashMap<i32, u64> = HashMap::new();
+
+    for (&folder_id, &count) in direct_counts {
+        for id in self_and_ancestors(&parents, folder_id) {
+            *totals.entry(id).or_default() += count;
+        }
+    }
+
+    folders
+        .iter()
+        .map(|folder| FolderUnreadCount {
+            folder_id: folder.id,
+            unread_count: direct_counts.get(&folder.id).copied().unwrap_or(0),
+            total_unread_count: totals.get(&folder.id).copied().unwrap_or(0),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folder(id: i32, name: &str, parent_id: Option<i32>) -> folder::Model {
+        let now = chrono::Utc::now().naive_utc();
+        folder::Model {
+            id,
+            name: name.to_string(),
+            created_at: now,
+            updated_at: now,
+            parent_id,
+        }
+    }
+
+    // Tech > Rust > Async, plus a separate News folder
+    fn folders() -> Vec<folder::Model> {
+        vec![
+            folder(1, "Tech", None),
+            folder(2, "Rust", Some(1)),
+            folder(3, "Async", Some(2)),
+            folder(4, "News", None),
+        ]
+    }
+
+    #[test]
+    fn test_moving_folder_below_itself_is_a_cycle() {
+        let folders = folders();
+
+        assert!(would_create_cycle(&folders, 1, 3));
+        assert!(would_create_cycle(&folders, 2, 2));
+        assert!(!would_create_cycle(&folders, 3, 4));
+        assert!(!would_create_cycle(&folders, 4, 1));
+    }
+
+    #[test]
+    fn test_descendants_cover_the_whole_subtree() {
+        let folders = folders();
+
+        assert_eq!(self_and_descendants(&folders, 1), vec![1, 2, 3]);
+        assert_eq!(self_and_descendants(&folders, 3), vec![3]);
+        assert_eq!(self_and_descendants(&folders, 4), vec![4]);
+        assert!(self_and_descendants(&folders, 9).is_empty());
+    }
+
+    #[test]
+    fn test_unread_counts_roll_up_to_ancestors() {
+        let direct_counts = HashMap::from([(1, 1), (2, 5), (3, 2), (4, 7)]);
+
+        let counts = roll_up_unread_counts(&folders(), &direct_counts);
+
+        let total = |id: i32| counts.iter().find(|count| count.folder_id == id).unwrap().total_unread_count;
+        assert_eq!(total(1), 8);
+        assert_eq!(total(2), 7);
+        assert_eq!(total(3), 2);
+        assert_eq!(total(4), 7);
+        assert_eq!(counts[1].unread_count, 5);
+    }
+}
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use crate::models::feed_parser::{feed_content_hash, FeedParseError, ParsedEntry, ParsedFeed};
+
+// Name of the optional GitHub token among the integration API keys in the keychain. With it,
+// releases come from the API, which allows more requests and has each release's full notes.
+pub const GITHUB_TOKEN_NAME: &str = "github";
+
+pub const GITHUB_API_FORMAT: &str = "GitHub API";
+
+// What a GitHub repository subscription follows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GithubWatch {
+    #[default]
+    Releases,
+    Tags,
+}
+
+impl GithubWatch {
+    fn feed_name(&self) -> &'static str {
+        match self {
+            GithubWatch::Releases => "releases",
+            GithubWatch::Tags => "tags",
+        }
+    }
+}
+
+fn is_repository_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+// The (owner, repository) behind "owner/repo" or a github.com repository URL
+pub fn parse_github_repository(input: &str) -> Result<(String, String), String> {
+    let input = input.trim();
+    let path = input
+        .strip_prefix("https://")
+        .or_else(|| input.strip_prefix("http://"))
+        .unwrap_or(input);
+    let path = path.strip_prefix("www.").unwrap_or(path);
+    let path = path.strip_prefix("github.com/").unwrap_or(path);
+
+    let mut parts = path.trim_end_matches('/').split('/');
+    let (Some(owner), Some(repo)) = (parts.next(), parts.next()) else {
+        return Err(format!("Not a GitHub repository: {}", input));
+    };
+    let repo = repo.strip_suffix(".git").unwrap_or(repo);
+    if !is_repository_name(owner) || !is_repository_name(repo) {
+        return Err(format!("Not a GitHub repository: {}", input));
+    }
+    Ok((owner.to_string(), repo.to_string()))
+}
+
+pub fn github_feed_url(owner: &str, repo: &str, watch: GithubWatch) -> String {
+    format!("https://github.com/{}/{}/{}.atom", owner, repo, watch.feed_name())
+}
+
+// The repository and what is watched, read back from a feed URL made by github_feed_url
+pub fn github_feed_target(url: &str) -> Option<(String, String, GithubWatch)> {
+    let path = url.strip_prefix("https://github.com/")?;
+    let (repository, feed) = path.rsplit_once('/')?;
+    let watch = match feed {
+        "releases.atom" => GithubWatch::Releases,
+        "tags.atom" => GithubWatch::Tags,
+        _ => return None,
+    };
+    let (owner, repo) = parse_github_repository(repository).ok()?;
+    Some((owner, repo, watch))
+}
+
+// The API request listing a repository's latest releases
+pub fn github_releases_api_url(owner: &str, repo: &str, max_entries: usize) -> String {
+    let per_page = if max_entries == 0 { 100 } else { max_entries.min(100) };
+    format!("https://api.github.com/repos/{}/{}/releases?per_page={}", owner, repo, per_page)
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    html_url: String,
+    tag_name: String,
+    name: Option<String>,
+    body: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    published_at: Option<String>,
+    created_at: Option<String>,
+}
+
+// Read the API's release list as a feed. Entries are keyed by their release page, as entries
+// from releases.atom are once fetched (see GithubSource), so switching between the two
+// doesn't add every release again. Release notes are Markdown and rendered to HTML.
+pub fn parse_github_releases(content: &str, owner: &str, repo: &str) -> Result<ParsedFeed, FeedParseError> {
+    let releases: Vec<GithubRelease> = serde_json::from_str(content)
+        .map_err(|e| FeedParseError::ParseError(format!("Unexpected GitHub API response: {}", e)))?;
+
+    let entries = releases
+        .into_iter()
+        .filter(|release| !release.draft)
+        .map(|release| {
+            let content = release.body.filter(|body| !body.trim().is_empty()).map(|body| {
+                let mut html = String::new();
+                pulldown_cmark::html::push_html(&mut html, pulldown_cmark::Parser::new(&body));
+                html
+            });
+            ParsedEntry {
+                guid: Some(release.html_url.clone()),
+                title: Some(release.name.filter(|name| !name.trim().is_empty()).unwrap_or(release.tag_name)),
+                description: None,
+                link: Some(release.html_url),
+                published: release.published_at.or(release.created_at),
+                updated: None,
+                content,
+                enclosure_url: None,
+                enclosure_type: None,
+                enclosure_length: None,
+                duration_seconds: None,
+                episode_number: None,
+                season_number: None,
+                explicit: None,
+                artwork_url: None,
+                event_starts_at: None,
+                event_ends_at: None,
+            }
+        })
+        .collect();
+
+    Ok(ParsedFeed {
+        title: format!("Release notes from {}", repo),
+        description: None,
+        url: Some(format!("https://github.com/{}/{}/releases", owner, repo)),
+        entries,
+        ttl_minutes: None,
+        skip_hours: Vec::new(),
+        skip_days: Vec::new(),
+        icon_url: None,
+        format: Some(GITHUB_API_FORMAT.to_string()),
+        content_hash: Some(feed_content_hash(content)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repositories_from_names_and_urls() {
+        let tokio = Ok(("tokio-rs".to_string(), "tokio".to_string()));
+        assert_eq!(parse_github_repository("tokio-rs/tokio"), tokio);
+        assert_eq!(parse_github_repository("https://github.com/tokio-rs/tokio/"), tokio);
+        assert_eq!(parse_github_repository("github.com/tokio-rs/tokio.git"), tokio);
+        assert_eq!(parse_github_repository("https://github.com/tokio-rs/tokio/releases"), tokio);
+        assert!(parse_github_repository("tokio").is_err());
+        assert!(parse_github_repository("../etc").is_err());
+
+        let url = github_feed_url("tokio-rs", "tokio", GithubWatch::Tags);
+        assert_eq!(url, "https://github.com/tokio-rs/tokio/tags.atom");
+        assert_eq!(
+            github_feed_target(&url),
+            Some(("tokio-rs".to_string(), "tokio".to_string(), GithubWatch::Tags))
+        );
+        assert_eq!(github_feed_target("https://github.com/tokio-rs/tokio/commits.atom"), None);
+        assert_eq!(
+            github_releases_api_url("tokio-rs", "tokio", 500),
+            "https://api.github.com/repos/tokio-rs/tokio/releases?per_page=100"
+        );
+    }
+
+    #[test]
+    fn test_api_releases_become_entries() {
+        let json = r###"[
+            {"html_url": "https://github.com/o/r/releases/tag/v2.0.0", "tag_name": "v2.0.0", "name": "", "draft": false,
+             "body": "## Changes\n- Faster", "published_at": "2024-06-01T10:00:00Z", "created_at": "2024-05-31T10:00:00Z"},
+            {"html_url": "https://github.com/o/r/releases/tag/untagged-1", "tag_name": "v3.0.0", "name": "Next", "draft": true,
+             "body": null, "published_at": null, "created_at": "2024-06-02T10:00:00Z"}
+        ]"###;
+
+        let feed = parse_github_releases(json, "o", "r").unwrap();
+
+        assert_eq!(feed.entries.len(), 1);
+        let release = &feed.entries[0];
+        assert_eq!(release.title.as_deref(), Some("v2.0.0"));
+        assert_eq!(release.guid.as_deref(), Some("https://github.com/o/r/releases/tag/v2.0.0"));
+        assert_eq!(release.published.as_deref(), Some("2024-06-01T10:00:00Z"));
+        assert_eq!(release.content.as_deref(), Some("<h2>Changes</h2>\n<ul>\n<li>Faster</li>\n</ul>\n"));
+        assert!(parse_github_releases("{\"message\": \"Bad credentials\"}", "o", "r").is_err());
+    }
+}
@@ -0,0 +1,252 @@
+use std::time::Instant;
+use sea_orm::*;
+use sea_orm::sea_query::{Alias, Expr, Order, Query};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use crate::entities::{prelude::*, *};
+use crate::models::data_directory::{directory_size, resolve_data_directory};
+use crate::models::scheduler::SchedulerStatus;
+use crate::models::state::AppState;
+
+// A database answering slower than this shows as degraded
+const SLOW_DATABASE_MS: u64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ok,
+    Degraded,
+    Down,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseHealth {
+    pub connected: bool,
+    pub latency_ms: Option<u64>,
+    // The latest migration applied to the library, e.g. m20240101_000041_add_feed_fetch_overrides
+    pub migration_version: Option<String>,
+    pub size_bytes: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FetcherHealth {
+    pub running: bool,
+    pub paused: bool,
+    pub queue_depth: usize,
+    pub refresh_active: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerHealth {
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub status: SchedulerStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthError {
+    // Where the error came from: "fetch" or "scheduler"
+    pub source: String,
+    pub message: String,
+    pub at: String,
+}
+
+// Everything get_health reports, in one place so it can be pasted into a bug report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub app_version: String,
+    pub profile: String,
+    pub database: DatabaseHealth,
+    pub fetcher: Option<FetcherHealth>,
+    pub scheduler: SchedulerHealth,
+    pub data_directory: Option<String>,
+    pub data_directory_bytes: Option<u64>,
+    pub last_error: Option<HealthError>,
+    pub checked_at: String,
+}
+
+// Down without a database; degraded when it's slow or background fetching isn't running
+fn overall_status(database: &DatabaseHealth, fetcher: Option<&FetcherHealth>) -> HealthStatus {
+    if !database.connected {
+        return HealthStatus::Down;
+    }
+    let slow = database.latency_ms.is_some_and(|latency_ms| latency_ms > SLOW_DATABASE_MS);
+    let fetching = fetcher.is_some_and(|fetcher| fetcher.running && !fetcher.paused);
+    if slow || !fetching {
+        HealthStatus::Degraded
+    } else {
+        HealthStatus::Ok
+    }
+}
+
+// The most recent of the errors seen, by timestamp (RFC 3339, so they compare as strings)
+fn latest_error(errors: impl IntoIterator<Item = HealthError>) -> Option<HealthError> {
+    errors.into_iter().max_by(|a, b| a.at.cmp(&b.at))
+}
+
+async fn migration_version<C: ConnectionTrait>(db: &C) -> Result<Option<String>, DbErr> {
+    let query = Query::select()
+        .column(Alias::new("version"))
+        .from(Alias::new("seaql_migrations"))
+        .order_by(Alias::new("version"), Order::Desc)
+        .limit(1)
+        .to_owned();
+    let row = db.query_one(db.get_database_backend().build(&query)).await?;
+    row.map(|row| row.try_get_by_index(0)).transpose()
+}
+
+async fn database_size<C: ConnectionTrait>(db: &C) -> Result<Option<u64>, DbErr> {
+    if db.get_database_backend() != DbBackend::Postgres {
+        return Ok(None);
+    }
+    let query = Query::select().expr(Expr::cust("pg_database_size(current_database())")).to_owned();
+    let row = db.query_one(db.get_database_backend().build(&query)).await?;
+    let size: Option<i64> = row.map(|row| row.try_get_by_index(0)).transpose()?;
+    Ok(size.map(|size| size.max(0) as u64))
+}
+
+async fn check_database(db: &DatabaseConnection) -> DatabaseHealth {
+    let started_at = Instant::now();
+    if let Err(e) = db.ping().await {
+        return DatabaseHealth {
+            connected: false,
+            latency_ms: None,
+            migration_version: None,
+            size_bytes: None,
+            error: Some(e.to_string()),
+        };
+    }
+    let latency_ms = started_at.elapsed().as_millis() as u64;
+
+    let mut errors = Vec::new();
+    let migration_version = migration_version(db).await.unwrap_or_else(|e| {
+        errors.push(format!("Failed to read the migration version: {}", e));
+        None
+    });
+    let size_bytes = database_size(db).await.unwrap_or_else(|e| {
+        errors.push(format!("Failed to read the database size: {}", e));
+        None
+    });
+    DatabaseHealth {
+        connected: true,
+        latency_ms: Some(latency_ms),
+        migration_version,
+        size_bytes,
+        error: (!errors.is_empty()).then(|| errors.join("; ")),
+    }
+}
+
+async fn last_fetch_error(db: &DatabaseConnection) -> Option<HealthError> {
+    let failure = FetchLog::find()
+        .filter(fetch_log::Column::Success.eq(false))
+        .order_by_desc(fetch_log::Column::FetchedAt)
+        .one(db)
+        .await
+        .ok()??;
+    let feed_url = Feed::find_by_id(failure.feed_id)
+        .one(db)
+        .await
+        .ok()
+        .flatten()
+        .map(|feed| feed.url);
+    let message = failure.error_message.unwrap_or_else(|| "Fetch failed".to_string());
+    Some(HealthError {
+        source: "fetch".to_string(),
+        message: match feed_url {
+            Some(url) => format!("{}: {}", url, message),
+            None => message,
+        },
+        at: failure.fetched_at.and_utc().to_rfc3339(),
+    })
+}
+
+// Check the database, background fetching and storage. Never fails: whatever can't be
+// checked shows up in the report instead.
+pub async fn collect_health(app: &AppHandle) -> HealthReport {
+    let state = app.state::<AppState>();
+    let db = state.db().await;
+
+    let database = check_database(&db).await;
+    let fetcher = match &state.async_fetcher {
+        Some(fetcher) => Some(FetcherHealth {
+            running: fetcher.is_running().await,
+            paused: fetcher.is_paused(),
+            queue_depth: fetcher.queue_depth(),
+            refresh_active: fetcher.get_refresh_progress().await.is_active,
+        }),
+        None => None,
+    };
+    let scheduler = SchedulerHealth {
+        enabled: state.scheduler_config.read().await.enabled,
+        status: state.scheduler_status.read().await.clone(),
+    };
+
+    let data_directory = if database.connected {
+        resolve_data_directory(app, &db).await.ok()
+    } else {
+        app.path().app_data_dir().ok()
+    };
+    let data_directory_bytes = match data_directory.clone() {
+        Some(directory) => tokio::task::spawn_blocking(move || directory_size(&directory).ok())
+            .await
+            .ok()
+            .flatten(),
+        None => None,
+    };
+
+    let fetch_error = if database.connected { last_fetch_error(&db).await } else { None };
+    let scheduler_error = scheduler.status.last_error.clone().zip(scheduler.status.last_error_at.clone()).map(
+        |(message, at)| HealthError { source: "scheduler".to_string(), message, at },
+    );
+
+    let profile = state.active_profile.read().await.clone();
+    HealthReport {
+        status: overall_status(&database, fetcher.as_ref()),
+        app_version: app.package_info().version.to_string(),
+        profile,
+        database,
+        fetcher,
+        scheduler,
+        data_directory: data_directory.map(|directory| directory.display().to_string()),
+        data_directory_bytes,
+        last_error: latest_error(fetch_error.into_iter().chain(scheduler_error)),
+        checked_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn database(connected: bool, latency_ms: u64) -> DatabaseHealth {
+        DatabaseHealth {
+            connected,
+            latency_ms: connected.then_some(latency_ms),
+            migration_version: None,
+            size_bytes: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_status_reflects_database_and_fetcher() {
+        let fetcher = FetcherHealth { running: true, paused: false, queue_depth: 3, refresh_active: true };
+        let paused = FetcherHealth { paused: true, ..fetcher.clone() };
+
+        assert_eq!(overall_status(&database(true, 12), Some(&fetcher)), HealthStatus::Ok);
+        assert_eq!(overall_status(&database(true, 900), Some(&fetcher)), HealthStatus::Degraded);
+        assert_eq!(overall_status(&database(true, 12), Some(&paused)), HealthStatus::Degraded);
+        assert_eq!(overall_status(&database(true, 12), None), HealthStatus::Degraded);
+        assert_eq!(overall_status(&database(false, 0), Some(&fetcher)), HealthStatus::Down);
+    }
+
+    #[test]
+    fn test_latest_error_wins() {
+        let error = |source: &str, at: &str| HealthError { source: source.to_string(), message: "failed".to_string(), at: at.to_string() };
+        let latest = latest_error([error("fetch", "2024-06-01T10:00:00+00:00"), error("scheduler", "2024-06-02T08:00:00+00:00")]);
+        assert_eq!(latest.map(|error| error.source).as_deref(), Some("scheduler"));
+        assert_eq!(latest_error([]), None);
+    }
+}
@@ -0,0 +1,147 @@
+use std::fmt;
+use futures::future::BoxFuture;
+use tauri_plugin_http::reqwest;
+use crate::models::async_feed_fetcher::{map_request_error, AsyncFeedFetcher, FeedFetchError};
+
+// How the fetcher talks HTTP. The built-in transport is reqwest; tests swap in a mock, and
+// other transports (a Tor proxy, custom TLS) can be set through FetcherConfig::transport.
+// Timeouts, retries and rate limiting are applied by the fetcher around these calls.
+pub trait HttpTransport: Send + Sync + fmt::Debug {
+    // GET a document, following redirects, and return its content type and body.
+    // Non-success statuses are errors (FeedFetchError::HttpStatus).
+    fn get<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<(String, String), FeedFetchError>>;
+
+    // GET like `get`, sending extra request headers such as an API token
+    fn get_with_headers<'a>(
+        &'a self,
+        url: &'a str,
+        headers: &'a [(&'static str, String)],
+    ) -> BoxFuture<'a, Result<(String, String), FeedFetchError>>;
+
+    // Follow a link's redirects and return where it ends up, if somewhere else
+    fn resolve<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Option<String>>;
+}
+
+// The default transport: a shared reqwest client, so connections are pooled and the
+// fetcher's redirect policy applies to every request
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    fn get<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<(String, String), FeedFetchError>> {
+        Box::pin(async move {
+            let response = self.client.get(url).send().await.map_err(map_request_error)?;
+            AsyncFeedFetcher::read_response(response).await
+        })
+    }
+
+    fn get_with_headers<'a>(
+        &'a self,
+        url: &'a str,
+        headers: &'a [(&'static str, String)],
+    ) -> BoxFuture<'a, Result<(String, String), FeedFetchError>> {
+        Box::pin(async move {
+            let request = headers
+                .iter()
+                .fold(self.client.get(url), |request, (name, value)| request.header(*name, value));
+            let response = request.send().await.map_err(map_request_error)?;
+            AsyncFeedFetcher::read_response(response).await
+        })
+    }
+
+    fn resolve<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Option<String>> {
+        Box::pin(async move {
+            let response = self.client.head(url).send().await.ok()?;
+            let destination = response.url().to_string();
+            (destination != url).then_some(destination)
+        })
+    }
+}
+
+// A canned transport for tests: serves registered responses, answers 404 for anything
+// else, and remembers what was requested
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    responses: std::sync::Mutex<std::collections::HashMap<String, MockResponse>>,
+    redirects: std::sync::Mutex<std::collections::HashMap<String, String>>,
+    requests: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub body: String,
+    pub delay: std::time::Duration,
+}
+
+#[cfg(test)]
+impl MockResponse {
+    pub fn ok(body: &str) -> Self {
+        Self {
+            status: 200,
+            body: body.to_string(),
+            delay: std::time::Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn respond(&self, url: &str, response: MockResponse) {
+        self.responses.lock().unwrap().insert(url.to_string(), response);
+    }
+
+    pub fn redirect(&self, from: &str, to: &str) {
+        self.redirects.lock().unwrap().insert(from.to_string(), to.to_string());
+    }
+
+    // URLs fetched so far, in order
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl HttpTransport for MockTransport {
+    fn get<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<(String, String), FeedFetchError>> {
+        Box::pin(async move {
+            self.requests.lock().unwrap().push(url.to_string());
+            let response = self.responses.lock().unwrap().get(url).cloned();
+            let Some(response) = response else {
+                return Err(FeedFetchError::HttpStatus(404));
+            };
+            tokio::time::sleep(response.delay).await;
+            if !(200..300).contains(&response.status) {
+                return Err(FeedFetchError::HttpStatus(response.status));
+            }
+            Ok(("application/rss+xml".to_string(), response.body))
+        })
+    }
+
+    // Headers make no difference to canned responses
+    fn get_with_headers<'a>(
+        &'a self,
+        url: &'a str,
+        _headers: &'a [(&'static str, String)],
+    ) -> BoxFuture<'a, Result<(String, String), FeedFetchError>> {
+        self.get(url)
+    }
+
+    fn resolve<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Option<String>> {
+        Box::pin(async move { self.redirects.lock().unwrap().get(url).cloned() })
+    }
+}
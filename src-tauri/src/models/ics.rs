@@ -0,0 +1,278 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use quick_xml::escape::escape;
+use crate::models::feed_parser::{feed_content_hash, FeedParseError, ParsedEntry, ParsedFeed};
+
+pub const ICALENDAR_FORMAT: &str = "iCalendar";
+
+// Calendars are usually linked as webcal://, which is plain HTTPS underneath
+pub fn calendar_fetch_url(url: &str) -> String {
+    match url.get(..9) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("webcal://") => format!("https://{}", &url[9..]),
+        _ => url.to_string(),
+    }
+}
+
+// Join folded lines back up: a line starting with a space or tab continues the previous one
+fn unfold_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(previous)) => previous.push_str(continuation),
+            _ if line.is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+// A content line as (NAME, value). Parameters (between the name and the colon) may be quoted
+// and contain colons. None of the ones used here need them, so they are dropped.
+fn parse_property(line: &str) -> Option<(String, String)> {
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(i),
+        _ => None,
+    })?;
+    let name = line[..colon].split(';').next()?.trim().to_uppercase();
+    Some((name, line[colon + 1..].to_string()))
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(escaped) => text.push(escaped),
+            None => {}
+        }
+    }
+    text.trim().to_string()
+}
+
+// A DATE-TIME in UTC, floating or with a TZID, or an all-day DATE. Returns whether it was all-day.
+// There's no time zone database to hand, so floating and TZID times are taken as UTC.
+fn parse_ics_datetime(value: &str) -> Option<(DateTime<Utc>, bool)> {
+    let value = value.trim();
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((date.and_hms_opt(0, 0, 0)?.and_utc(), true));
+    }
+    NaiveDateTime::parse_from_str(value.trim_end_matches(['Z', 'z']), "%Y%m%dT%H%M%S")
+        .ok()
+        .map(|datetime| (datetime.and_utc(), false))
+}
+
+#[derive(Default)]
+struct CalendarEvent {
+    uid: Option<String>,
+    recurrence_id: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    url: Option<String>,
+    status: Option<String>,
+    starts_at: Option<(DateTime<Utc>, bool)>,
+    ends_at: Option<(DateTime<Utc>, bool)>,
+    created: Option<DateTime<Utc>>,
+    last_modified: Option<DateTime<Utc>>,
+}
+
+impl CalendarEvent {
+    fn set(&mut self, name: &str, value: &str) {
+        match name {
+            "UID" => self.uid = Some(value.trim().to_string()),
+            "RECURRENCE-ID" => self.recurrence_id = Some(value.trim().to_string()),
+            "SUMMARY" => self.summary = Some(unescape_text(value)),
+            "DESCRIPTION" => self.description = Some(unescape_text(value)),
+            "LOCATION" => self.location = Some(unescape_text(value)),
+            "URL" => self.url = Some(value.trim().to_string()),
+            "STATUS" => self.status = Some(value.trim().to_uppercase()),
+            "DTSTART" => self.starts_at = parse_ics_datetime(value),
+            "DTEND" => self.ends_at = parse_ics_datetime(value),
+            "CREATED" => self.created = parse_ics_datetime(value).map(|(datetime, _)| datetime),
+            "LAST-MODIFIED" => self.last_modified = parse_ics_datetime(value).map(|(datetime, _)| datetime),
+            _ => {}
+        }
+    }
+
+    // When the event is over. All-day events without an end last the day they start on.
+    fn end(&self) -> Option<DateTime<Utc>> {
+        let (starts_at, all_day) = self.starts_at?;
+        Some(match self.ends_at {
+            Some((ends_at, _)) => ends_at,
+            None if all_day => starts_at + Duration::days(1),
+            None => starts_at,
+        })
+    }
+
+    fn when(&self) -> Option<String> {
+        let (starts_at, all_day) = self.starts_at?;
+        let format = if all_day { "%A, %B %-d, %Y" } else { "%A, %B %-d, %Y %H:%M UTC" };
+        let mut when = starts_at.format(format).to_string();
+        // All-day ends are exclusive, so a one-day event's end is the next day
+        let ends_at = self.ends_at.map(|(ends_at, _)| if all_day { ends_at - Duration::days(1) } else { ends_at });
+        if let Some(ends_at) = ends_at.filter(|ends_at| *ends_at > starts_at) {
+            let end_format = if ends_at.date_naive() == starts_at.date_naive() { "%H:%M UTC" } else { format };
+            when.push_str(&format!(" – {}", ends_at.format(end_format)));
+        }
+        Some(when)
+    }
+
+    fn into_entry(self, calendar_url: &str) -> Option<ParsedEntry> {
+        let (starts_at, _) = self.starts_at?;
+        let when = self.when()?;
+        let ends_at = self.end();
+        // A changed occurrence of a recurring event shares the series' UID
+        let uid = self.uid.clone().unwrap_or_else(|| format!("{}-{}", starts_at.timestamp(), self.summary.as_deref().unwrap_or_default()));
+        let guid = match &self.recurrence_id {
+            Some(recurrence_id) => format!("{}/{}", uid, recurrence_id),
+            None => uid.clone(),
+        };
+        let link = self.url.clone().unwrap_or_else(|| format!("{}#{}", calendar_url, guid));
+
+        let mut content = format!("<p><strong>When:</strong> {}</p>", escape(&when));
+        let mut description = when;
+        if let Some(location) = self.location.as_deref().filter(|location| !location.is_empty()) {
+            content.push_str(&format!("<p><strong>Where:</strong> {}</p>", escape(location)));
+            description.push_str(&format!(" · {}", location));
+        }
+        if let Some(details) = self.description.as_deref().filter(|details| !details.is_empty()) {
+            content.push_str(&format!("<p>{}</p>", escape(details).replace('\n', "<br>")));
+        }
+
+        Some(ParsedEntry {
+            guid: Some(guid),
+            title: Some(self.summary.unwrap_or_else(|| "Untitled event".to_string())),
+            description: Some(description),
+            link: Some(link),
+            published: self.created.or(self.last_modified).map(|datetime| datetime.to_rfc3339()),
+            updated: self.last_modified.map(|datetime| datetime.to_rfc3339()),
+            content: Some(content),
+            enclosure_url: None,
+            enclosure_type: None,
+            enclosure_length: None,
+            duration_seconds: None,
+            episode_number: None,
+            season_number: None,
+            explicit: None,
+            artwork_url: None,
+            event_starts_at: Some(starts_at.to_rfc3339()),
+            event_ends_at: ends_at.map(|datetime| datetime.to_rfc3339()),
+        })
+    }
+}
+
+// Read an iCalendar document as a feed of its upcoming events, soonest first, keeping at most
+// `max_entries` (0 for no cap). Events already over and cancelled events are left out.
+// Recurring events appear once, at their first occurrence.
+pub fn parse_ics_calendar(
+    content: &str,
+    calendar_url: &str,
+    now: DateTime<Utc>,
+    max_entries: usize,
+) -> Result<ParsedFeed, FeedParseError> {
+    if !content.trim_start().to_uppercase().starts_with("BEGIN:VCALENDAR") {
+        return Err(FeedParseError::ParseError("Not an iCalendar document".to_string()));
+    }
+
+    let mut title = None;
+    let mut description = None;
+    let mut events = Vec::new();
+    let mut current: Option<CalendarEvent> = None;
+    // Components inside an event, like alarms, whose properties aren't the event's
+    let mut nested = 0;
+
+    for line in unfold_lines(content) {
+        let Some((name, value)) = parse_property(&line) else {
+            continue;
+        };
+        let component = value.trim().to_uppercase();
+        match (name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if component == "VEVENT" => current = Some(CalendarEvent::default()),
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) if component == "VEVENT" => events.extend(current.take()),
+            (_, Some(event)) if nested == 0 => event.set(&name, &value),
+            ("X-WR-CALNAME", None) => title = Some(unescape_text(&value)),
+            ("X-WR-CALDESC", None) => description = Some(unescape_text(&value)),
+            _ => {}
+        }
+    }
+
+    let mut upcoming: Vec<CalendarEvent> = events
+        .into_iter()
+        .filter(|event| event.status.as_deref() != Some("CANCELLED"))
+        .filter(|event| event.end().is_some_and(|ends_at| ends_at >= now))
+        .collect();
+    upcoming.sort_by_key(|event| event.starts_at.map(|(starts_at, _)| starts_at));
+    if max_entries > 0 {
+        upcoming.truncate(max_entries);
+    }
+
+    Ok(ParsedFeed {
+        title: title.filter(|title| !title.is_empty()).unwrap_or_else(|| "Calendar".to_string()),
+        description,
+        url: Some(calendar_url.to_string()),
+        entries: upcoming.into_iter().filter_map(|event| event.into_entry(calendar_url)).collect(),
+        ttl_minutes: None,
+        skip_hours: Vec::new(),
+        skip_days: Vec::new(),
+        icon_url: None,
+        format: Some(ICALENDAR_FORMAT.to_string()),
+        content_hash: Some(feed_content_hash(content)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nX-WR-CALNAME:RustConf\r\n\
+BEGIN:VEVENT\r\nUID:keynote@rustconf\r\nDTSTART:20240910T160000Z\r\nDTEND:20240910T170000Z\r\n\
+SUMMARY:Opening keynote\r\nLOCATION:Main hall\\, level 2\r\nDESCRIPTION:Welcome\\nand news\r\n\
+\x20 about the project\r\nBEGIN:VALARM\r\nDESCRIPTION:Reminder\r\nEND:VALARM\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:workshops@rustconf\r\nDTSTART;VALUE=DATE:20240909\r\nSUMMARY:Workshops\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:cancelled@rustconf\r\nDTSTART:20240911T100000Z\r\nSTATUS:CANCELLED\r\nSUMMARY:Panel\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:last-year@rustconf\r\nDTSTART:20230912T100000Z\r\nSUMMARY:Old talk\r\nEND:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_upcoming_events_become_entries() {
+        let now = DateTime::parse_from_rfc3339("2024-09-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let feed = parse_ics_calendar(CALENDAR, "https://example.com/rustconf.ics", now, 0).unwrap();
+
+        assert_eq!(feed.title, "RustConf");
+        assert_eq!(feed.format.as_deref(), Some("iCalendar"));
+        let titles: Vec<_> = feed.entries.iter().map(|entry| entry.title.as_deref().unwrap()).collect();
+        assert_eq!(titles, ["Workshops", "Opening keynote"]);
+
+        let workshops = &feed.entries[0];
+        assert_eq!(workshops.event_starts_at.as_deref(), Some("2024-09-09T00:00:00+00:00"));
+        assert_eq!(workshops.event_ends_at.as_deref(), Some("2024-09-10T00:00:00+00:00"));
+        assert_eq!(workshops.link.as_deref(), Some("https://example.com/rustconf.ics#workshops@rustconf"));
+
+        let keynote = &feed.entries[1];
+        assert_eq!(keynote.guid.as_deref(), Some("keynote@rustconf"));
+        assert_eq!(keynote.description.as_deref(), Some("Tuesday, September 10, 2024 16:00 UTC – 17:00 UTC · Main hall, level 2"));
+        assert!(keynote.content.as_deref().unwrap().ends_with("<p>Welcome<br>and news about the project</p>"));
+
+        let later = DateTime::parse_from_rfc3339("2024-09-10T12:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(parse_ics_calendar(CALENDAR, "https://example.com/rustconf.ics", later, 0).unwrap().entries.len(), 1);
+        assert!(parse_ics_calendar("<rss/>", "https://example.com/feed", now, 0).is_err());
+    }
+
+    #[test]
+    fn test_webcal_links_are_fetched_over_https() {
+        assert_eq!(calendar_fetch_url("webcal://example.com/cal.ics"), "https://example.com/cal.ics");
+        assert_eq!(calendar_fetch_url("https://example.com/cal.ics"), "https://example.com/cal.ics");
+    }
+}
@@ -0,0 +1,206 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, UriSchemeResponder};
+use tauri_plugin_http::reqwest;
+use url::Url;
+use crate::models::data_directory::resolve_data_directory;
+use crate::models::snapshots::snapshot_image_path;
+use crate::models::state::AppState;
+use crate::models::url_guard::{guard_private_addresses, is_private_host_literal, load_allow_private_addresses, validate_fetch_url};
+
+// Custom protocol the webview loads proxied images from
+pub const IMAGE_PROXY_SCHEME: &str = "reader-image";
+
+// Windows and Android webviews only reach custom protocols through an http://<scheme>.localhost origin
+#[cfg(any(windows, target_os = "android"))]
+const IMAGE_PROXY_BASE: &str = "http://reader-image.localhost/";
+#[cfg(not(any(windows, target_os = "android")))]
+const IMAGE_PROXY_BASE: &str = "reader-image://localhost/";
+
+// Cached images live under the data directory next to downloads
+pub const IMAGE_CACHE_DIR: &str = "image-cache";
+
+// Anything larger is not an inline image worth caching
+const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+const IMAGE_FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_IMAGE_REDIRECTS: usize = 10;
+
+// The local URL an entry's image is rewritten to under the proxy image policy
+pub fn proxied_image_url(url: &str) -> String {
+    let encoded: String = url::form_urlencoded::byte_serialize(url.as_bytes()).collect();
+    format!("{}?url={}", IMAGE_PROXY_BASE, encoded)
+}
+
+pub fn is_proxied_image_url(url: &str) -> bool {
+    url.starts_with(IMAGE_PROXY_BASE)
+}
+
+// The original image URL carried by a proxy request
+pub fn original_image_url(proxy_url: &str) -> Option<String> {
+    Url::parse(proxy_url)
+        .ok()?
+        .query_pairs()
+        .find(|(name, _)| name == "url")
+        .map(|(_, value)| value.into_owned())
+}
+
+// Stable cache file name for an image URL (64-bit FNV-1a, so it doesn't change between builds)
+pub fn image_cache_key(url: &str) -> String {
+    let hash = url.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+fn content_type_path(image_path: &Path) -> PathBuf {
+    image_path.with_extension("type")
+}
+
+pub async fn read_cached_image(image_path: &Path) -> Option<(Vec<u8>, String)> {
+    let bytes = tokio::fs::read(image_path).await.ok()?;
+    let content_type = tokio::fs::read_to_string(content_type_path(image_path)).await.ok()?;
+    Some((bytes, content_type))
+}
+
+// Save an image and its content type under a cache directory, as read_cached_image reads it
+pub async fn write_cached_image(image_path: &Path, bytes: &[u8], content_type: &str) -> std::io::Result<()> {
+    if let Some(dir) = image_path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    tokio::fs::write(image_path, bytes).await?;
+    tokio::fs::write(content_type_path(image_path), content_type).await
+}
+
+async fn download_image(url: &str, allow_private_addresses: bool) -> Result<(Vec<u8>, String), String> {
+    validate_fetch_url(url, allow_private_addresses).await?;
+
+    let redirect_policy = reqwest::redirect::Policy::custom(move |attempt| {
+        let next = attempt.url();
+        if !allow_private_addresses && (!matches!(next.scheme(), "http" | "https") || is_private_host_literal(next)) {
+            let error = format!("Blocked redirect to {}", next);
+            attempt.error(error)
+        } else if attempt.previous().len() > MAX_IMAGE_REDIRECTS {
+            attempt.stop()
+        } else {
+            attempt.follow()
+        }
+    });
+    let client = guard_private_addresses(reqwest::Client::builder(), allow_private_addresses)
+        .redirect(redirect_policy)
+        .timeout(IMAGE_FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to fetch image: {}", e))?;
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    if !content_type.starts_with("image/") {
+        return Err(format!("Not an image: {}", url));
+    }
+    if response.content_length().is_some_and(|length| length > MAX_IMAGE_BYTES as u64) {
+        return Err(format!("Image too large: {}", url));
+    }
+
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read image: {}", e))?;
+    if bytes.len() > MAX_IMAGE_BYTES {
+        return Err(format!("Image too large: {}", url));
+    }
+
+    Ok((bytes.to_vec(), content_type))
+}
+
+// Serve an image from the local cache, fetching and caching it on first use
+pub async fn load_proxied_image(app: &AppHandle, url: &str) -> Result<(Vec<u8>, String), String> {
+    let state = app.state::<AppState>();
+    let db = state.db().await;
+    let data_directory = resolve_data_directory(app, &db).await?;
+    let image_path = data_directory.join(IMAGE_CACHE_DIR).join(image_cache_key(url));
+
+    if let Some(cached) = read_cached_image(&image_path).await {
+        return Ok(cached);
+    }
+    // Starred entries' images are kept even after the publisher removes them
+    if let Some(snapshotted) = read_cached_image(&snapshot_image_path(&data_directory, url)).await {
+        return Ok(snapshotted);
+    }
+    if *state.metered_mode.read().await {
+        return Err(format!("Metered mode is on, not downloading image {}", url));
+    }
+
+    let (bytes, content_type) = download_image(url, load_allow_private_addresses(&db).await).await?;
+
+    // A failed cache write only costs a refetch next time
+    if let Err(e) = write_cached_image(&image_path, &bytes, &content_type).await {
+        eprintln!("❌ Failed to cache image {}: {}", url, e);
+    }
+
+    Ok((bytes, content_type))
+}
+
+// Handler for the image proxy protocol
+pub fn handle_image_proxy_request(app: AppHandle, request: Request<Vec<u8>>, responder: UriSchemeResponder) {
+    let proxy_url = request.uri().to_string();
+
+    tauri::async_runtime::spawn(async move {
+        let result = match original_image_url(&proxy_url) {
+            Some(url) => load_proxied_image(&app, &url).await,
+            None => Err(format!("Invalid image proxy URL: {}", proxy_url)),
+        };
+
+        let response = match result {
+            Ok((bytes, content_type)) => Response::builder()
+                .header(header::CONTENT_TYPE, content_type)
+                .header(header::CACHE_CONTROL, "max-age=31536000, immutable")
+                .body(bytes),
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body(Vec::new())
+            }
+        };
+
+        match response {
+            Ok(response) => responder.respond(response),
+            Err(e) => eprintln!("❌ Failed to build image response: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proxied_image_url_round_trip() {
+        let url = "https://cdn.example.com/images/a b.png?w=640&h=480";
+
+        let proxied = proxied_image_url(url);
+
+        assert!(proxied.starts_with(IMAGE_PROXY_BASE));
+        assert!(!proxied.contains("&h="));
+        assert_eq!(original_image_url(&proxied).as_deref(), Some(url));
+    }
+
+    #[test]
+    fn test_image_cache_key_is_stable() {
+        assert_eq!(image_cache_key(""), "cbf29ce484222325");
+        assert_eq!(image_cache_key("https://example.com/a.png"), image_cache_key("https://example.com/a.png"));
+        assert_ne!(image_cache_key("https://example.com/a.png"), image_cache_key("https://example.com/b.png"));
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use crate::models::feed_bundle::parse_feed_bundle;
+
+// Subscription export formats understood by the importer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportFormat {
+    // Also what Feedly and NetNewsWire export, using nested outlines for folders
+    Opml,
+    Miniflux,
+    // A subscription pack exported from this app; see feed_bundle
+    Bundle,
+}
+
+impl ImportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportFormat::Opml => "opml",
+            ImportFormat::Miniflux => "miniflux",
+            ImportFormat::Bundle => "bundle",
+        }
+    }
+}
+
+impl FromStr for ImportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "opml" | "feedly" | "netnewswire" => Ok(ImportFormat::Opml),
+            "miniflux" => Ok(ImportFormat::Miniflux),
+            "bundle" => Ok(ImportFormat::Bundle),
+            other => Err(format!("Unsupported import format: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedFeed {
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub site_url: Option<String>,
+    pub folder: Option<String>,
+    // Folders enclosing `folder`, outermost first
+    pub parent_folders: Vec<String>,
+}
+
+// An entry carried over from another reader so its read/starred state survives the switch
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedEntry {
+    pub feed_url: String,
+    pub title: String,
+    pub link: String,
+    pub content: Option<String>,
+    pub published_at: Option<String>, // RFC 3339 string
+    pub is_read: bool,
+    pub is_starred: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportedSubscriptions {
+    pub feeds: Vec<ImportedFeed>,
+    pub entries: Vec<ImportedEntry>,
+    // Folders to create even when no feed is imported into them, as names outermost first
+    pub folders: Vec<Vec<String>>,
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    ParseError(String),
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::ParseError(msg) => write!(f, "Import parse error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+pub fn parse_import(content: &str, format: ImportFormat) -> Result<ImportedSubscriptions, ImportError> {
+    match format {
+        ImportFormat::Opml => parse_opml(content),
+        ImportFormat::Miniflux => parse_miniflux_json(content),
+        ImportFormat::Bundle => parse_feed_bundle(content),
+    }
+}
+
+fn outline_attributes(element: &BytesStart, reader: &Reader<&[u8]>) -> Result<HashMap<String, String>, ImportError> {
+    let mut attributes = HashMap::new();
+
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(|e| ImportError::ParseError(e.to_string()))?;
+        let key = String::from_utf8_lossy(attribute.key.as_ref()).to_lowercase();
+        let value = attribute
+            .decode_and_unescape_value(reader)
+            .map_err(|e| ImportError::ParseError(e.to_string()))?
+            .trim()
+            .to_string();
+
+        if !value.is_empty() {
+            attributes.insert(key, value);
+        }
+    }
+
+    Ok(attributes)
+}
+
+// OPML 2.0 `category` attributes hold comma-separated, slash-delimited paths ("/Tech/Rust,/News").
+// Returns the first path's folder names, outermost first.
+fn folders_from_category(category: &str) -> Vec<String> {
+    category
+        .split(',')
+        .next()
+        .map(|path| {
+            path.split('/')
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// enclosing_folders are the names of the folder outlines around this one, outermost first
+fn imported_feed_from_outline(
+    attributes: &HashMap<String, String>,
+    enclosing_folders: &[String],
+) -> Option<ImportedFeed> {
+    let url = attributes.get("xmlurl")?.clone();
+
+    let mut folders = if enclosing_folders.is_empty() {
+        attributes.get("category").map(|c| folders_from_category(c)).unwrap_or_default()
+    } else {
+        enclosing_folders.to_vec()
+    };
+    let folder = folders.pop();
+
+    Some(ImportedFeed {
+        url,
+        title: attributes.get("title").or_else(|| attributes.get("text")).cloned(),
+        description: attributes.get("description").cloned(),
+        site_url: attributes.get("htmlurl").cloned(),
+        folder,
+        parent_folders: folders,
+    })
+}
+
+pub fn parse_opml(content: &str) -> Result<ImportedSubscriptions, ImportError> {
+    let mut reader = Reader::from_str(content);
+    reader.trim_text(true);
+
+    let mut subscriptions = ImportedSubscriptions::default();
+    // One stack slot per open <outline>: Some(name) for folders, None for feeds
+    let mut outline_stack: Vec<Option<String>> = Vec::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(element)) if element.name().as_ref().eq_ignore_ascii_case(b"outline") => {
+                let attributes = outline_attributes(&element, &reader)?;
+                let enclosing_folders: Vec<String> = outline_stack.iter().flatten().cloned().collect();
+
+                if let Some(feed) = imported_feed_from_outline(&attributes, &enclosing_folders) {
+                    subscriptions.feeds.push(feed);
+                    outline_stack.push(None);
+                } else {
+                    let folder_name = attributes.get("title").or_else(|| attributes.get("text")).cloned();
+                    outline_stack.push(folder_name);
+                }
+            }
+            Ok(Event::Empty(element)) if element.name().as_ref().eq_ignore_ascii_case(b"outline") => {
+                let attributes = outline_attributes(&element, &reader)?;
+                let enclosing_folders: Vec<String> = outline_stack.iter().flatten().cloned().collect();
+
+                if let Some(feed) = imported_feed_from_outline(&attributes, &enclosing_folders) {
+                    subscriptions.feeds.push(feed);
+                }
+            }
+            Ok(Event::End(element)) if element.name().as_ref().eq_ignore_ascii_case(b"outline") => {
+                outline_stack.pop();
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => {
+                return Err(ImportError::ParseError(format!(
+                    "Invalid OPML at position {}: {}",
+                    reader.buffer_position(),
+                    e
+                )))
+            }
+        }
+    }
+
+    Ok(subscriptions)
+}
+
+// Miniflux API shapes (`/v1/feeds` and `/v1/entries`)
+#[derive(Debug, Deserialize)]
+struct MinifluxCategory {
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MinifluxFeed {
+    id: Option<i64>,
+    feed_url: String,
+    site_url: Option<String>,
+    title: Option<String>,
+    category: Option<MinifluxCategory>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MinifluxEntry {
+    feed_id: Option<i64>,
+    url: String,
+    title: Option<String>,
+    content: Option<String>,
+    published_at: Option<String>,
+    status: Option<String>,
+    #[serde(default)]
+    starred: bool,
+    feed: Option<MinifluxFeed>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum MinifluxEntries {
+    List(Vec<MinifluxEntry>),
+    Page { entries: Vec<MinifluxEntry> },
+}
+
+impl Default for MinifluxEntries {
+    fn default() -> Self {
+        MinifluxEntries::List(Vec::new())
+    }
+}
+
+// Accepts either a bare `/v1/feeds` array or a dump object with `feeds` and optional `entries`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum MinifluxExport {
+    Feeds(Vec<MinifluxFeed>),
+    Dump {
+        feeds: Vec<MinifluxFeed>,
+        #[serde(default)]
+        entries: MinifluxEntries,
+    },
+}
+
+pub fn parse_miniflux_json(content: &str) -> Result<ImportedSubscriptions, ImportError> {
+    let export: MinifluxExport = serde_json::from_str(content)
+        .map_err(|e| ImportError::ParseError(format!("Invalid Miniflux JSON: {}", e)))?;
+
+    let (feeds, entries) = match export {
+        MinifluxExport::Feeds(feeds) => (feeds, Vec::new()),
+        MinifluxExport::Dump { feeds, entries } => match entries {
+            MinifluxEntries::List(entries) | MinifluxEntries::Page { entries } => (feeds, entries),
+        },
+    };
+
+    let feed_urls_by_id: HashMap<i64, String> = feeds
+        .iter()
+        .filter_map(|feed| feed.id.map(|id| (id, feed.feed_url.clone())))
+        .collect();
+
+    let mut subscriptions = ImportedSubscriptions::default();
+
+    for feed in feeds {
+        subscriptions.feeds.push(ImportedFeed {
+            url: feed.feed_url,
+            title: feed.title,
+            description: None,
+            site_url: feed.site_url,
+            folder: feed.category.map(|category| category.title),
+            parent_folders: Vec::new(),
+        });
+    }
+
+    for entry in entries {
+        let feed_url = entry
+            .feed
+            .map(|feed| feed.feed_url)
+            .or_else(|| entry.feed_id.and_then(|id| feed_urls_by_id.get(&id).cloned()));
+
+        let Some(feed_url) = feed_url else {
+            continue; // Entry can't be matched to a subscription
+        };
+
+        subscriptions.entries.push(ImportedEntry {
+            feed_url,
+            title: entry.title.unwrap_or_else(|| "Untitled".to_string()),
+            link: entry.url,
+            content: entry.content,
+            published_at: entry.published_at,
+            is_read: entry.status.as_deref() == Some("read"),
+            is_starred: entry.starred,
+        });
+    }
+
+    Ok(subscriptions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_feedly_opml_with_categories() {
+        let opml = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <opml version="1.0">
+            <head><title>Feedly subscriptions</title></head>
+            <body>
+                <outline text="Tech" title="Tech">
+                    <outline type="rss" text="Rust Blog" title="Rust Blog"
+                        xmlUrl="https://blog.rust-lang.org/feed.xml" htmlUrl="https://blog.rust-lang.org/"/>
+                </outline>
+                <outline type="rss" text="Loose &amp; Unfiled" xmlUrl="https://example.com/feed.xml"/>
+                <outline type="rss" text="Categorised" xmlUrl="https://example.org/rss" category="/News/World"/>
+            </body>
+        </opml>"#;
+
+        let subscriptions = parse_import(opml, "feedly".parse().unwrap()).unwrap();
+        assert_eq!(subscriptions.feeds.len(), 3);
+
+        assert_eq!(subscriptions.feeds[0].url, "https://blog.rust-lang.org/feed.xml");
+        assert_eq!(subscriptions.feeds[0].folder, Some("Tech".to_string()));
+        assert_eq!(subscriptions.feeds[0].site_url, Some("https://blog.rust-lang.org/".to_string()));
+
+        assert_eq!(subscriptions.feeds[1].title, Some("Loose & Unfiled".to_string()));
+        assert_eq!(subscriptions.feeds[1].folder, None);
+
+        assert_eq!(subscriptions.feeds[2].folder, Some("World".to_string()));
+        assert_eq!(subscriptions.feeds[2].parent_folders, vec!["News".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_netnewswire_opml() {
+        let opml = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <opml version="1.1">
+            <body>
+                <outline text="Daring Fireball" title="Daring Fireball" description="" type="rss"
+                    version="RSS" htmlUrl="https://daringfireball.net/" xmlUrl="https://daringfireball.net/feeds/main"/>
+                <outline text="Apple">
+                    <outline text="Six Colors" title="Six Colors" type="rss" xmlUrl="https://sixcolors.com/feed/"/>
+                </outline>
+            </body>
+        </opml>"#;
+
+        let subscriptions = parse_import(opml, "netnewswire".parse().unwrap()).unwrap();
+        assert_eq!(subscriptions.feeds.len(), 2);
+        assert_eq!(subscriptions.feeds[0].folder, None);
+        assert_eq!(subscriptions.feeds[0].description, None);
+        assert_eq!(subscriptions.feeds[1].folder, Some("Apple".to_string()));
+    }
+
+    #[test]
+    fn test_parse_miniflux_dump_with_read_state() {
+        let json = r#"{
+            "feeds": [
+                {"id": 1, "feed_url": "https://example.com/feed.xml", "site_url": "https://example.com",
+                 "title": "Example", "category": {"id": 3, "title": "Blogs"}}
+            ],
+            "entries": {
+                "total": 2,
+                "entries": [
+                    {"id": 10, "feed_id": 1, "url": "https://example.com/a", "title": "A",
+                     "status": "read", "starred": true, "published_at": "2024-01-01T12:00:00Z"},
+                    {"id": 11, "url": "https://example.com/b", "title": "B", "status": "unread",
+                     "feed": {"id": 1, "feed_url": "https://example.com/feed.xml"}}
+                ]
+            }
+        }"#;
+
+        let subscriptions = parse_import(json, ImportFormat::Miniflux).unwrap();
+        assert_eq!(subscriptions.feeds.len(), 1);
+        assert_eq!(subscriptions.feeds[0].folder, Some("Blogs".to_string()));
+
+        assert_eq!(subscriptions.entries.len(), 2);
+        assert!(subscriptions.entries[0].is_read);
+        assert!(subscriptions.entries[0].is_starred);
+        assert_eq!(subscriptions.entries[1].feed_url, "https://example.com/feed.xml");
+        assert!(!subscriptions.entries[1].is_read);
+    }
+
+    #[test]
+    fn test_unknown_format_is_rejected() {
+        assert!("newsblur".parse::<ImportFormat>().is_err());
+        assert_eq!("NetNewsWire".parse::<ImportFormat>(), Ok(ImportFormat::Opml));
+    }
+}
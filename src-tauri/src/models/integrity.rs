@@ -0,0 +1,345 @@
+use std::collections::BTreeMap;
+use sea_orm::*;
+use sea_orm::sea_query::{Alias, Asterisk, Expr, Func, OnConflict, Query, SelectStatement, SimpleExpr};
+use serde::{Deserialize, Serialize};
+use crate::entities::{prelude::*, *};
+
+// What a repair does with rows pointing at a parent that no longer exists: the same as the
+// foreign key's ON DELETE action would have done
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanRepair {
+    Delete,
+    SetNull,
+}
+
+// One foreign key of the schema
+struct ForeignKey {
+    table: &'static str,
+    column: &'static str,
+    references: &'static str,
+    repair: OrphanRepair,
+}
+
+// Every foreign key, parents before children, so rows orphaned by an earlier repair are
+// found (and repaired) by a later check
+const FOREIGN_KEYS: [ForeignKey; 15] = [
+    ForeignKey { table: "folder", column: "parent_id", references: "folder", repair: OrphanRepair::SetNull },
+    ForeignKey { table: "feed", column: "folder_id", references: "folder", repair: OrphanRepair::SetNull },
+    ForeignKey { table: "feed_entry", column: "feed_id", references: "feed", repair: OrphanRepair::Delete },
+    ForeignKey { table: "fetch_log", column: "feed_id", references: "feed", repair: OrphanRepair::Delete },
+    ForeignKey { table: "bandwidth_usage", column: "feed_id", references: "feed", repair: OrphanRepair::Delete },
+    ForeignKey { table: "entry_archive", column: "feed_id", references: "feed", repair: OrphanRepair::Delete },
+    ForeignKey { table: "webhook", column: "feed_id", references: "feed", repair: OrphanRepair::Delete },
+    ForeignKey { table: "webhook", column: "tag_id", references: "tag", repair: OrphanRepair::Delete },
+    ForeignKey { table: "webhook_delivery", column: "webhook_id", references: "webhook", repair: OrphanRepair::Delete },
+    ForeignKey { table: "entry_tag", column: "entry_id", references: "feed_entry", repair: OrphanRepair::Delete },
+    ForeignKey { table: "entry_tag", column: "tag_id", references: "tag", repair: OrphanRepair::Delete },
+    ForeignKey { table: "annotation", column: "entry_id", references: "feed_entry", repair: OrphanRepair::Delete },
+    ForeignKey { table: "playback_state", column: "entry_id", references: "feed_entry", repair: OrphanRepair::Delete },
+    ForeignKey { table: "entry_translation", column: "entry_id", references: "feed_entry", repair: OrphanRepair::Delete },
+    ForeignKey { table: "share_history", column: "entry_id", references: "feed_entry", repair: OrphanRepair::Delete },
+];
+
+// Rows of one table whose foreign key points at a missing row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrphanedRows {
+    pub table: String,
+    pub column: String,
+    pub references: String,
+    pub count: u64,
+    pub repair: OrphanRepair,
+}
+
+// Entries of one feed sharing a link, which a repair merges into the oldest of them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateLinks {
+    pub feed_id: i32,
+    pub link: String,
+    // Oldest first; the first is the one kept
+    pub entry_ids: Vec<i32>,
+}
+
+impl DuplicateLinks {
+    pub fn kept_entry_id(&self) -> i32 {
+        self.entry_ids[0]
+    }
+
+    pub fn removed_entry_ids(&self) -> &[i32] {
+        &self.entry_ids[1..]
+    }
+}
+
+// The result of check_library_integrity. With `repaired` set, everything listed has been fixed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub is_healthy: bool,
+    pub repaired: bool,
+    pub orphaned_rows: Vec<OrphanedRows>,
+    // Entries whose feed no longer exists, also counted among orphaned_rows
+    pub orphaned_entries: u64,
+    pub duplicate_links: Vec<DuplicateLinks>,
+    pub checked_at: String,
+}
+
+fn orphan_condition(foreign_key: &ForeignKey) -> SimpleExpr {
+    let column = (Alias::new(foreign_key.table), Alias::new(foreign_key.column));
+    Expr::col(column.clone()).is_not_null().and(
+        Expr::col(column).not_in_subquery(
+            Query::select()
+                .column(Alias::new("id"))
+                .from(Alias::new(foreign_key.references))
+                .to_owned(),
+        ),
+    )
+}
+
+fn orphan_count_query(foreign_key: &ForeignKey) -> SelectStatement {
+    Query::select()
+        .expr(Func::count(Expr::col(Asterisk)))
+        .from(Alias::new(foreign_key.table))
+        .and_where(orphan_condition(foreign_key))
+        .to_owned()
+}
+
+fn orphan_repair_statement(foreign_key: &ForeignKey, backend: DbBackend) -> Statement {
+    match foreign_key.repair {
+        OrphanRepair::Delete => backend.build(
+            Query::delete()
+                .from_table(Alias::new(foreign_key.table))
+                .cond_where(orphan_condition(foreign_key)),
+        ),
+        OrphanRepair::SetNull => backend.build(
+            Query::update()
+                .table(Alias::new(foreign_key.table))
+                .value(Alias::new(foreign_key.column), Value::Int(None))
+                .cond_where(orphan_condition(foreign_key)),
+        ),
+    }
+}
+
+// Count the rows breaking one foreign key, and fix them when repairing
+async fn check_foreign_key<C: ConnectionTrait>(
+    db: &C,
+    foreign_key: &ForeignKey,
+    repair: bool,
+) -> Result<OrphanedRows, String> {
+    let backend = db.get_database_backend();
+    let count: i64 = db
+        .query_one(backend.build(&orphan_count_query(foreign_key)))
+        .await
+        .map_err(|e| format!("Failed to check {}.{}: {}", foreign_key.table, foreign_key.column, e))?
+        .map(|row| row.try_get_by_index(0))
+        .transpose()
+        .map_err(|e| format!("Failed to check {}.{}: {}", foreign_key.table, foreign_key.column, e))?
+        .unwrap_or(0);
+
+    if repair && count > 0 {
+        db.execute(orphan_repair_statement(foreign_key, backend))
+            .await
+            .map_err(|e| format!("Failed to repair {}.{}: {}", foreign_key.table, foreign_key.column, e))?;
+    }
+
+    Ok(OrphanedRows {
+        table: foreign_key.table.to_string(),
+        column: foreign_key.column.to_string(),
+        references: foreign_key.references.to_string(),
+        count: count.max(0) as u64,
+        repair: foreign_key.repair,
+    })
+}
+
+// Group entries by feed and link, keeping the groups with more than one entry. Entries without
+// a link can't be told apart by it and are left alone.
+fn group_duplicate_links(entries: Vec<(i32, i32, String)>) -> Vec<DuplicateLinks> {
+    let mut groups: BTreeMap<(i32, String), Vec<i32>> = BTreeMap::new();
+    for (id, feed_id, link) in entries {
+        if !link.trim().is_empty() {
+            groups.entry((feed_id, link)).or_default().push(id);
+        }
+    }
+    groups
+        .into_iter()
+        .filter(|(_, entry_ids)| entry_ids.len() > 1)
+        .map(|((feed_id, link), mut entry_ids)| {
+            entry_ids.sort_unstable();
+            DuplicateLinks { feed_id, link, entry_ids }
+        })
+        .collect()
+}
+
+async fn find_duplicate_links<C: ConnectionTrait>(db: &C) -> Result<Vec<DuplicateLinks>, String> {
+    let duplicated: Vec<(i32, String)> = FeedEntry::find()
+        .select_only()
+        .columns([feed_entry::Column::FeedId, feed_entry::Column::Link])
+        .filter(feed_entry::Column::Link.ne(""))
+        .group_by(feed_entry::Column::FeedId)
+        .group_by(feed_entry::Column::Link)
+        .having(Expr::expr(Func::count(Expr::col(feed_entry::Column::Id))).gt(1))
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to find duplicate entries: {}", e))?;
+    if duplicated.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let feed_ids: Vec<i32> = duplicated.iter().map(|(feed_id, _)| *feed_id).collect();
+    let links: Vec<String> = duplicated.into_iter().map(|(_, link)| link).collect();
+    let entries: Vec<(i32, i32, String)> = FeedEntry::find()
+        .select_only()
+        .columns([feed_entry::Column::Id, feed_entry::Column::FeedId, feed_entry::Column::Link])
+        .filter(feed_entry::Column::FeedId.is_in(feed_ids))
+        .filter(feed_entry::Column::Link.is_in(links))
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch duplicate entries: {}", e))?;
+    Ok(group_duplicate_links(entries))
+}
+
+// Fold duplicates into the kept entry: it stays read or starred if any copy was, and takes
+// over their notes, tags and share history before they are deleted
+async fn merge_duplicate_links<C: ConnectionTrait>(db: &C, duplicates: &DuplicateLinks) -> Result<(), String> {
+    let kept_id = duplicates.kept_entry_id();
+    let removed_ids = duplicates.removed_entry_ids().to_vec();
+
+    let entries = FeedEntry::find()
+        .filter(feed_entry::Column::Id.is_in(duplicates.entry_ids.clone()))
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch duplicate entries: {}", e))?;
+    let Some(kept) = entries.iter().find(|entry| entry.id == kept_id).cloned() else {
+        return Ok(());
+    };
+    let read_at = entries.iter().filter(|entry| entry.is_read).filter_map(|entry| entry.read_at).min();
+    let starred_at = entries.iter().filter(|entry| entry.is_starred).filter_map(|entry| entry.starred_at).min();
+    let is_read = entries.iter().any(|entry| entry.is_read);
+    let is_starred = entries.iter().any(|entry| entry.is_starred);
+
+    let mut merged: feed_entry::ActiveModel = kept.into();
+    merged.is_read = ActiveValue::Set(is_read);
+    merged.read_at = ActiveValue::Set(read_at);
+    merged.is_starred = ActiveValue::Set(is_starred);
+    merged.starred_at = ActiveValue::Set(starred_at);
+    merged
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to merge duplicate entries: {}", e))?;
+
+    Annotation::update_many()
+        .col_expr(annotation::Column::EntryId, Expr::value(kept_id))
+        .filter(annotation::Column::EntryId.is_in(removed_ids.clone()))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to move annotations: {}", e))?;
+    ShareHistory::update_many()
+        .col_expr(share_history::Column::EntryId, Expr::value(kept_id))
+        .filter(share_history::Column::EntryId.is_in(removed_ids.clone()))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to move share history: {}", e))?;
+
+    let copy_tags = Query::insert()
+        .into_table(EntryTag)
+        .columns([entry_tag::Column::EntryId, entry_tag::Column::TagId, entry_tag::Column::CreatedAt])
+        .select_from(
+            Query::select()
+                .expr(Expr::value(kept_id))
+                .column(entry_tag::Column::TagId)
+                .column(entry_tag::Column::CreatedAt)
+                .from(EntryTag)
+                .and_where(entry_tag::Column::EntryId.is_in(removed_ids.clone()))
+                .to_owned(),
+        )
+        .map_err(|e| format!("Failed to build tag merge: {}", e))?
+        .on_conflict(
+            OnConflict::columns([entry_tag::Column::EntryId, entry_tag::Column::TagId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned();
+    db.execute(db.get_database_backend().build(&copy_tags))
+        .await
+        .map_err(|e| format!("Failed to move tags: {}", e))?;
+
+    FeedEntry::delete_many()
+        .filter(feed_entry::Column::Id.is_in(removed_ids))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to delete duplicate entries: {}", e))?;
+    Ok(())
+}
+
+// Look for rows the foreign keys should have ruled out (left behind by databases restored
+// without constraints, or written while they were off) and entries a feed has twice under
+// different guids. With `repair`, orphans are removed or detached and duplicates merged, all
+// in one transaction.
+pub async fn check_library_integrity(db: &DatabaseConnection, repair: bool) -> Result<IntegrityReport, String> {
+    let txn = db.begin().await.map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let mut orphaned_rows = Vec::new();
+    for foreign_key in &FOREIGN_KEYS {
+        let orphans = check_foreign_key(&txn, foreign_key, repair).await?;
+        if orphans.count > 0 {
+            orphaned_rows.push(orphans);
+        }
+    }
+    let orphaned_entries = orphaned_rows
+        .iter()
+        .filter(|orphans| orphans.table == "feed_entry" && orphans.column == "feed_id")
+        .map(|orphans| orphans.count)
+        .sum();
+
+    let duplicate_links = find_duplicate_links(&txn).await?;
+    if repair {
+        for duplicates in &duplicate_links {
+            merge_duplicate_links(&txn, duplicates).await?;
+        }
+    }
+
+    txn.commit().await.map_err(|e| format!("Failed to commit repairs: {}", e))?;
+
+    Ok(IntegrityReport {
+        is_healthy: orphaned_rows.is_empty() && duplicate_links.is_empty(),
+        repaired: repair,
+        orphaned_rows,
+        orphaned_entries,
+        duplicate_links,
+        checked_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orphans_are_rows_pointing_at_missing_parents() {
+        let entries = &FOREIGN_KEYS[2];
+        assert_eq!(
+            DbBackend::Postgres.build(&orphan_count_query(entries)).to_string(),
+            r#"SELECT COUNT(*) FROM "feed_entry" WHERE "feed_entry"."feed_id" IS NOT NULL AND "feed_entry"."feed_id" NOT IN (SELECT "id" FROM "feed")"#
+        );
+        assert!(orphan_repair_statement(&FOREIGN_KEYS[1], DbBackend::Postgres)
+            .to_string()
+            .starts_with(r#"UPDATE "feed" SET "folder_id" = NULL WHERE"#));
+    }
+
+    #[test]
+    fn test_duplicate_links_keep_the_oldest_entry() {
+        let link = "https://example.com/post".to_string();
+        let duplicates = group_duplicate_links(vec![
+            (9, 1, link.clone()),
+            (3, 1, link.clone()),
+            (4, 2, link.clone()),
+            (5, 1, "https://example.com/other".to_string()),
+            (6, 1, String::new()),
+            (7, 1, String::new()),
+        ]);
+
+        assert_eq!(duplicates, [DuplicateLinks { feed_id: 1, link, entry_ids: vec![3, 9] }]);
+        assert_eq!(duplicates[0].kept_entry_id(), 3);
+        assert_eq!(duplicates[0].removed_entry_ids(), [9]);
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use crate::entities::{prelude::*, *};
+use crate::models::http_transport::HttpTransport;
+use url::Url;
+use crate::models::feed_parser::{ParsedEntry, ParsedFeed};
+use crate::models::sanitizer::ImagePolicy;
+
+// Query parameters that only identify where a click came from
+const TRACKING_PARAMETER_PREFIXES: [&str; 2] = ["utm_", "_hs"];
+const TRACKING_PARAMETERS: [&str; 11] = [
+    "fbclid", "gclid", "dclid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "mkt_tok", "oly_anon_id", "oly_enc_id",
+];
+
+// Link shorteners and feed redirectors whose links only forward to the real article
+const SHORTENER_HOSTS: [&str; 11] = [
+    "bit.ly", "t.co", "tinyurl.com", "goo.gl", "ow.ly", "buff.ly", "dlvr.it", "is.gd", "trib.al", "lnkd.in",
+    "feedproxy.google.com",
+];
+
+// How long to wait for a shortener to answer before keeping the short link
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Privacy preferences for entry links and images
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    pub strip_tracking_parameters: bool,
+    // Follow known shorteners to their destination when saving new entries (one request per link)
+    pub resolve_short_links: bool,
+    // Image policy for feeds that don't set their own
+    pub image_policy: ImagePolicy,
+}
+
+impl Default for PrivacyConfig {
+    fn default() -> Self {
+        Self {
+            strip_tracking_parameters: true,
+            resolve_short_links: false,
+            image_policy: ImagePolicy::All,
+        }
+    }
+}
+
+fn is_tracking_parameter(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    TRACKING_PARAMETERS.contains(&name.as_str())
+        || TRACKING_PARAMETER_PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+}
+
+// Remove utm_*, fbclid and similar parameters, keeping every other parameter in order.
+// Links that don't parse are returned unchanged.
+pub fn strip_tracking_parameters(link: &str) -> String {
+    let Ok(mut url) = Url::parse(link) else {
+        return link.to_string();
+    };
+    if url.query().is_none() {
+        return link.to_string();
+    }
+
+    let kept: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| !is_tracking_parameter(name))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+
+    if kept.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(kept);
+    }
+    url.to_string()
+}
+
+pub fn is_short_link(link: &str) -> bool {
+    Url::parse(link)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.trim_start_matches("www.").to_ascii_lowercase()))
+        .is_some_and(|host| SHORTENER_HOSTS.contains(&host.as_str()))
+}
+
+// Follow a short link's redirects (through the fetcher's transport, so its redirect limit and
+// address checks apply) and return where it ends up
+pub async fn resolve_short_link(transport: &dyn HttpTransport, link: &str) -> Option<String> {
+    tokio::time::timeout(RESOLVE_TIMEOUT, transport.resolve(link)).await.ok().flatten()
+}
+
+// Strip tracking parameters from every entry link in a parsed feed before it is saved.
+// Entries identified only by their link keep the original link as their guid, so entries
+// saved before cleaning was turned on aren't saved again.
+pub fn clean_entry_links(parsed_feed: &mut ParsedFeed, config: &PrivacyConfig) {
+    if !config.strip_tracking_parameters && !config.resolve_short_links {
+        return;
+    }
+
+    for entry in &mut parsed_feed.entries {
+        let Some(link) = entry.link.as_deref().filter(|link| !link.is_empty()) else {
+            continue;
+        };
+        if entry.guid.is_none() {
+            entry.guid = Some(link.to_string());
+        }
+        if config.strip_tracking_parameters {
+            entry.link = Some(strip_tracking_parameters(link));
+        }
+    }
+}
+
+fn entry_guid(entry: &ParsedEntry) -> Option<String> {
+    entry.guid.clone().or_else(|| entry.link.clone())
+}
+
+// Follow the short links of entries that aren't saved yet. Entries in `saved_links` (guid to
+// link) keep the link they were saved with, so each short link is only followed once rather
+// than on every refresh.
+async fn resolve_short_links(
+    parsed_feed: &mut ParsedFeed,
+    saved_links: &HashMap<String, String>,
+    config: &PrivacyConfig,
+    transport: &dyn HttpTransport,
+) {
+    for entry in &mut parsed_feed.entries {
+        let Some(link) = entry.link.clone().filter(|link| is_short_link(link)) else {
+            continue;
+        };
+        let destination = match entry_guid(entry).and_then(|guid| saved_links.get(&guid)) {
+            Some(saved_link) => Some(saved_link.clone()),
+            None => resolve_short_link(transport, &link).await,
+        };
+        if let Some(destination) = destination {
+            entry.link = Some(match config.strip_tracking_parameters {
+                true => strip_tracking_parameters(&destination),
+                false => destination,
+            });
+        }
+    }
+}
+
+// Resolve the short links of a feed's new entries, if short links are resolved at all. Runs
+// before the entries are handed to the database writer, so the feed is known by its URL.
+pub async fn resolve_new_short_links<C: ConnectionTrait>(
+    db: &C,
+    feed_url: &str,
+    parsed_feed: &mut ParsedFeed,
+    config: &PrivacyConfig,
+    transport: &dyn HttpTransport,
+) -> Result<(), String> {
+    if !config.resolve_short_links {
+        return Ok(());
+    }
+    let guids: Vec<String> = parsed_feed
+        .entries
+        .iter()
+        .filter(|entry| entry.link.as_deref().is_some_and(is_short_link))
+        .filter_map(entry_guid)
+        .collect();
+    if guids.is_empty() {
+        return Ok(());
+    }
+    let saved_links: HashMap<String, String> = FeedEntry::find()
+        .select_only()
+        .columns([feed_entry::Column::Guid, feed_entry::Column::Link])
+        .inner_join(Feed)
+        .filter(feed::Column::Url.eq(feed_url))
+        .filter(feed_entry::Column::Guid.is_in(guids))
+        .into_tuple::<(String, String)>()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entries: {}", e))?
+        .into_iter()
+        .collect();
+
+    resolve_short_links(parsed_feed, &saved_links, config, transport).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::feed_parser::parse_feed_content;
+    use crate::models::http_transport::MockTransport;
+
+    #[test]
+    fn test_strip_tracking_parameters() {
+        assert_eq!(
+            strip_tracking_parameters("https://example.com/post?id=7&utm_source=rss&utm_medium=feed&fbclid=abc"),
+            "https://example.com/post?id=7"
+        );
+        assert_eq!(
+            strip_tracking_parameters("https://example.com/post?UTM_Campaign=x#comments"),
+            "https://example.com/post#comments"
+        );
+        assert_eq!(strip_tracking_parameters("https://example.com/post?page=2"), "https://example.com/post?page=2");
+        assert_eq!(strip_tracking_parameters("not a url"), "not a url");
+    }
+
+    #[test]
+    fn test_known_shorteners() {
+        assert!(is_short_link("https://bit.ly/3abcd"));
+        assert!(is_short_link("http://www.tinyurl.com/xyz"));
+        assert!(!is_short_link("https://example.com/bit.ly"));
+    }
+
+    #[test]
+    fn test_cleaning_keeps_the_original_link_as_guid() {
+        let mut feed = parse_feed_content(
+            r#"<?xml version="1.0"?>
+            <rss version="2.0"><channel><title>Blog</title>
+                <item><title>A</title><link>https://example.com/a?utm_source=rss</link></item>
+                <item><title>B</title><link>https://example.com/b?utm_source=rss</link><guid isPermaLink="false">post-b</guid></item>
+            </channel></rss>"#,
+        )
+        .unwrap();
+
+        clean_entry_links(&mut feed, &PrivacyConfig::default());
+
+        assert_eq!(feed.entries[0].link.as_deref(), Some("https://example.com/a"));
+        assert_eq!(feed.entries[0].guid.as_deref(), Some("https://example.com/a?utm_source=rss"));
+        assert_eq!(feed.entries[1].link.as_deref(), Some("https://example.com/b"));
+        // The publisher's own id is kept
+        assert_eq!(feed.entries[1].guid.as_deref(), Some("post-b"));
+    }
+
+    #[tokio::test]
+    async fn test_only_unsaved_short_links_resolve_through_the_transport() {
+        let mut feed = parse_feed_content(
+            r#"<?xml version="1.0"?>
+            <rss version="2.0"><channel><title>Blog</title>
+                <item><title>A</title><link>https://bit.ly/3abcd</link></item>
+                <item><title>B</title><link>https://bit.ly/saved</link></item>
+            </channel></rss>"#,
+        )
+        .unwrap();
+        let transport = MockTransport::new();
+        transport.redirect("https://bit.ly/3abcd", "https://example.com/a?utm_source=rss");
+        let config = PrivacyConfig {
+            resolve_short_links: true,
+            ..Default::default()
+        };
+        // Saved before, with the link it resolved to then
+        transport.redirect("https://bit.ly/saved", "https://example.com/moved");
+        let saved_links = HashMap::from([("https://bit.ly/saved".to_string(), "https://example.com/b".to_string())]);
+
+        clean_entry_links(&mut feed, &config);
+        resolve_short_links(&mut feed, &saved_links, &config, &transport).await;
+
+        assert_eq!(feed.entries[0].link.as_deref(), Some("https://example.com/a"));
+        assert_eq!(feed.entries[0].guid.as_deref(), Some("https://bit.ly/3abcd"));
+        assert_eq!(feed.entries[1].link.as_deref(), Some("https://example.com/b"));
+    }
+}
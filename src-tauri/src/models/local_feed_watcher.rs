@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use notify::{RecursiveMode, Watcher};
+use sea_orm::*;
+use tauri::{AppHandle, Manager};
+use tokio::sync::mpsc;
+use crate::entities::{prelude::*, *};
+use crate::models::feed_sources::{feed_source_type, is_markdown_file, local_file_path, FeedSourceType};
+use crate::models::state::AppState;
+
+// How often the set of watched feeds is reloaded, picking up new, removed and edited
+// local feeds (and profile switches) without hooking every feed command
+const RESYNC_INTERVAL: Duration = Duration::from_secs(30);
+// Editors and generators often write a file in several steps; wait for them to finish
+const CHANGE_SETTLE_DELAY: Duration = Duration::from_millis(500);
+
+// A local feed and the path it's read from
+#[derive(Debug, Clone)]
+pub struct WatchedFeed {
+    pub feed: feed::Model,
+    pub source_type: FeedSourceType,
+    pub path: PathBuf,
+}
+
+impl WatchedFeed {
+    pub fn from_feed(feed: feed::Model) -> Option<Self> {
+        let source_type = feed_source_type(&feed.source_type);
+        if source_type.is_remote() {
+            return None;
+        }
+        let path = local_file_path(&feed.url).ok()?;
+        Some(Self { feed, source_type, path })
+    }
+
+    // Files are watched through their directory, since editors often replace a file rather
+    // than write to it, which would end a watch on the file itself
+    pub fn watch_directory(&self) -> &Path {
+        match self.source_type {
+            FeedSourceType::MarkdownDirectory => &self.path,
+            _ => self.path.parent().unwrap_or(&self.path),
+        }
+    }
+
+    pub fn is_affected_by(&self, changed: &Path) -> bool {
+        match self.source_type {
+            FeedSourceType::MarkdownDirectory => {
+                changed == self.path || (changed.parent() == Some(self.path.as_path()) && is_markdown_file(changed))
+            }
+            _ => changed == self.path,
+        }
+    }
+}
+
+// The feeds a batch of changed paths affects, each once
+pub fn feeds_affected_by<'a>(watched: &'a [WatchedFeed], changed: &HashSet<PathBuf>) -> Vec<&'a WatchedFeed> {
+    watched
+        .iter()
+        .filter(|feed| changed.iter().any(|path| feed.is_affected_by(path)))
+        .collect()
+}
+
+async fn load_watched_feeds(db: &DatabaseConnection) -> Result<Vec<WatchedFeed>, String> {
+    let local_types = [FeedSourceType::LocalFile, FeedSourceType::MarkdownDirectory].map(|source_type| source_type.as_str());
+    let feeds = Feed::find()
+        .filter(feed::Column::SourceType.is_in(local_types))
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch local feeds: {}", e))?;
+    Ok(feeds.into_iter().filter_map(WatchedFeed::from_feed).collect())
+}
+
+// Watch the directories the feeds need and stop watching the ones they no longer do.
+// Directories that can't be watched yet (e.g. not created) are retried on the next resync.
+fn sync_watches(watcher: &mut impl Watcher, watching: &mut HashSet<PathBuf>, feeds: &[WatchedFeed]) {
+    let wanted: HashSet<PathBuf> = feeds.iter().map(|feed| feed.watch_directory().to_path_buf()).collect();
+
+    for directory in watching.difference(&wanted).cloned().collect::<Vec<_>>() {
+        let _ = watcher.unwatch(&directory);
+        watching.remove(&directory);
+    }
+    for directory in wanted.difference(watching).cloned().collect::<Vec<_>>() {
+        match watcher.watch(&directory, RecursiveMode::NonRecursive) {
+            Ok(()) => {
+                println!("👀 Watching {} for feed changes", directory.display());
+                watching.insert(directory);
+            }
+            Err(e) => eprintln!("❌ Failed to watch {}: {}", directory.display(), e),
+        }
+    }
+}
+
+async fn refresh_changed_feeds(app: &AppHandle, feeds: Vec<&WatchedFeed>) {
+    let state = app.state::<AppState>();
+    let Some(fetcher) = &state.async_fetcher else {
+        return;
+    };
+    for watched in feeds {
+        println!("📝 {} changed, refreshing", watched.path.display());
+        if let Err(e) = fetcher.fetch_and_save_feed(&watched.feed).await {
+            eprintln!("❌ Failed to refresh local feed {}: {}", watched.feed.url, e.error_message);
+        }
+    }
+}
+
+// Refresh local file and Markdown directory feeds as soon as their files change, instead of
+// waiting for the scheduler. Runs until the app shuts down.
+pub async fn run_local_feed_watcher(app: AppHandle) {
+    let (change_sender, mut changes) = mpsc::unbounded_channel::<Vec<PathBuf>>();
+    let mut watcher = match notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            if !event.kind.is_access() {
+                let _ = change_sender.send(event.paths);
+            }
+        }
+    }) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("❌ Failed to start the local feed watcher: {}", e);
+            return;
+        }
+    };
+
+    let mut shutdown = app.state::<AppState>().shutdown_signal.subscribe();
+    let mut resync = tokio::time::interval(RESYNC_INTERVAL);
+    let mut watching = HashSet::new();
+    let mut feeds = Vec::new();
+
+    loop {
+        let changed_paths = tokio::select! {
+            _ = resync.tick() => None,
+            Some(paths) = changes.recv() => Some(paths),
+            _ = shutdown.wait_for(|stopping| *stopping) => break,
+        };
+
+        match changed_paths {
+            Some(paths) => {
+                tokio::time::sleep(CHANGE_SETTLE_DELAY).await;
+                let mut changed: HashSet<PathBuf> = paths.into_iter().collect();
+                while let Ok(paths) = changes.try_recv() {
+                    changed.extend(paths);
+                }
+                refresh_changed_feeds(&app, feeds_affected_by(&feeds, &changed)).await;
+            }
+            None => {
+                let db = app.state::<AppState>().db().await;
+                match load_watched_feeds(&db).await {
+                    Ok(loaded) => {
+                        feeds = loaded;
+                        sync_watches(&mut watcher, &mut watching, &feeds);
+                    }
+                    Err(e) => eprintln!("❌ {}", e),
+                }
+            }
+        }
+    }
+
+    println!("🛑 Local feed watcher stopped");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::fixtures;
+
+    fn local_feed(id: i32, url: &str, source_type: FeedSourceType) -> WatchedFeed {
+        WatchedFeed::from_feed(feed::Model {
+            url: url.to_string(),
+            auto_title: true,
+            source_type: source_type.as_str().to_string(),
+            ..fixtures::feed(id)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_changes_map_to_the_feeds_reading_them() {
+        let watched = vec![
+            local_feed(1, "file:///data/out/feed.xml", FeedSourceType::LocalFile),
+            local_feed(2, "file:///data/notes", FeedSourceType::MarkdownDirectory),
+        ];
+        assert_eq!(watched[0].watch_directory(), Path::new("/data/out"));
+        assert_eq!(watched[1].watch_directory(), Path::new("/data/notes"));
+
+        let ids = |paths: &[&str]| -> Vec<i32> {
+            let changed = paths.iter().map(PathBuf::from).collect();
+            feeds_affected_by(&watched, &changed).iter().map(|watched| watched.feed.id).collect()
+        };
+        assert_eq!(ids(&["/data/out/feed.xml"]), vec![1]);
+        assert_eq!(ids(&["/data/out/other.xml", "/data/notes/todo.txt"]), Vec::<i32>::new());
+        assert_eq!(ids(&["/data/notes/idea.md", "/data/notes/more.md"]), vec![2]);
+        assert_eq!(ids(&["/data/notes/drafts/idea.md"]), Vec::<i32>::new());
+    }
+}
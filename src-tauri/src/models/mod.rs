@@ -3,10 +3,155 @@ pub mod responses;
 pub mod state;
 pub mod feed_parser;
 pub mod async_feed_fetcher;
+pub mod notifications;
+pub mod importers;
+pub mod settings;
+pub mod feed_stats;
+pub mod sanitizer;
+pub mod scheduler;
+pub mod entry_query;
+pub mod circuit_breaker;
+pub mod rate_limiter;
+pub mod fetch_metrics;
+pub mod data_directory;
+pub mod reading;
+pub mod feed_health;
+pub mod db_writer;
+pub mod downloads;
+pub mod playback;
+pub mod folder_tree;
+pub mod exporters;
+pub mod annotations;
+pub mod database;
+pub mod profiles;
+pub mod secrets;
+pub mod url_guard;
+pub mod link_cleaner;
+pub mod bandwidth;
+pub mod shutdown;
+pub mod image_proxy;
+pub mod translation;
+pub mod classifier;
+pub mod story_clusters;
+pub mod digest;
+pub mod email;
+pub mod republish;
+pub mod webhooks;
+pub mod social;
+pub mod feed_sources;
+pub mod local_feed_watcher;
+pub mod http_transport;
+pub mod domain_rules;
+pub mod canonical_links;
+pub mod bulk_undo;
+pub mod tray;
+pub mod open_requests;
+pub mod subscriptions;
+pub mod subscribe_endpoint;
+pub mod retention;
+pub mod folder_refresh;
+pub mod search_suggestions;
+pub mod ics;
+pub mod github;
+pub mod content_extraction;
+pub mod cookie_jar;
+pub mod feed_tls;
+pub mod operations;
+pub mod integrity;
+pub mod health;
+pub mod startup;
+pub mod alerts;
+pub mod feed_bundle;
+pub mod starter_packs;
+pub mod sync;
+pub mod sync_storage;
+pub mod state_log;
+pub mod refresh_history;
+pub mod archive;
+pub mod snapshots;
+pub mod related_entries;
+pub mod recommendations;
+pub mod mutes;
+pub mod display_policy;
+pub mod feed_preferences;
+pub mod backup;
+#[cfg(feature = "fault-injection")]
+pub mod fault_injection;
 
 // Re-export commonly used types
 pub use requests::*;
 pub use responses::*;
 pub use state::*;
 pub use feed_parser::*;
-pub use async_feed_fetcher::*; 
\ No newline at end of file
+pub use async_feed_fetcher::*;
+pub use notifications::*;
+pub use importers::*;
+pub use settings::*;
+pub use feed_stats::*;
+pub use sanitizer::*;
+pub use scheduler::*;
+pub use entry_query::*;
+pub use circuit_breaker::*;
+pub use rate_limiter::*;
+pub use fetch_metrics::*;
+pub use data_directory::*;
+pub use reading::*;
+pub use feed_health::*;
+pub use downloads::*;
+pub use playback::*;
+pub use folder_tree::*;
+pub use exporters::*;
+pub use annotations::*;
+pub use database::*;
+pub use profiles::*;
+pub use secrets::*;
+pub use url_guard::*;
+pub use link_cleaner::*;
+pub use bandwidth::*;
+pub use shutdown::*;
+pub use image_proxy::*;
+pub use translation::*;
+pub use classifier::*;
+pub use story_clusters::*;
+pub use digest::*;
+pub use email::*;
+pub use republish::*;
+pub use webhooks::*;
+pub use social::*;
+pub use feed_sources::*;
+pub use local_feed_watcher::*;
+pub use domain_rules::*;
+pub use canonical_links::*;
+pub use bulk_undo::*;
+pub use tray::*;
+pub use open_requests::*;
+pub use subscriptions::*;
+pub use subscribe_endpoint::*;
+pub use retention::*;
+pub use folder_refresh::*;
+pub use search_suggestions::*;
+pub use github::*;
+pub use content_extraction::*;
+pub use cookie_jar::*;
+pub use feed_tls::*;
+pub use operations::*;
+pub use integrity::*;
+pub use health::*;
+pub use startup::*;
+pub use alerts::*;
+pub use feed_bundle::*;
+pub use starter_packs::*;
+pub use sync::*;
+pub use sync_storage::*;
+pub use state_log::*;
+pub use refresh_history::*;
+pub use archive::*;
+pub use snapshots::*;
+pub use related_entries::*;
+pub use recommendations::*;
+pub use mutes::*;
+pub use display_policy::*;
+pub use feed_preferences::*;
+pub use backup::*;
+#[cfg(feature = "fault-injection")]
+pub use fault_injection::*; 
\ No newline at end of file
@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+use chrono::NaiveDateTime;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use crate::entities::{prelude::*, *};
+use crate::models::domain_rules::DomainAction;
+use crate::models::reading::html_to_text;
+use crate::models::settings::{load_domain_rules_config, load_mute_rules_config, set_setting_value, MUTE_RULES};
+
+// Entries checked against the mutes per query
+const MUTE_BATCH_SIZE: u64 = 500;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MuteRule {
+    // Lowercased, matched anywhere in an entry's title or description
+    pub keyword: String,
+    // When the mute lapses; a mute without one lasts until it's removed
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+impl MuteRule {
+    pub fn is_active(&self, now: NaiveDateTime) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+// Keywords whose entries are hidden, e.g. "hide anything mentioning X for 2 weeks". New
+// entries that mention an active mute are hidden as they are saved.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MuteRulesConfig {
+    pub rules: Vec<MuteRule>,
+}
+
+// The keyword as mutes store it, or None if there's nothing to mute
+pub fn normalize_mute_keyword(keyword: &str) -> Option<String> {
+    let keyword = keyword.trim().to_lowercase();
+    (!keyword.is_empty()).then_some(keyword)
+}
+
+impl MuteRulesConfig {
+    // Mute a keyword, or change when an existing mute of it lapses
+    pub fn mute(&mut self, keyword: String, expires_at: Option<NaiveDateTime>) {
+        match self.rules.iter_mut().find(|rule| rule.keyword == keyword) {
+            Some(rule) => rule.expires_at = expires_at,
+            None => self.rules.push(MuteRule { keyword, expires_at }),
+        }
+    }
+
+    // Remove the mute of a keyword, returning it if there was one
+    pub fn unmute(&mut self, keyword: &str) -> Option<MuteRule> {
+        let index = self.rules.iter().position(|rule| rule.keyword == keyword)?;
+        Some(self.rules.remove(index))
+    }
+
+    // Take out the mutes that have lapsed by now
+    pub fn take_expired(&mut self, now: NaiveDateTime) -> Vec<MuteRule> {
+        let (active, expired) = std::mem::take(&mut self.rules).into_iter().partition(|rule| rule.is_active(now));
+        self.rules = active;
+        expired
+    }
+
+    // The active mute an entry's title or description (without markup) mentions
+    pub fn matching_keyword(&self, title: &str, description: Option<&str>, now: NaiveDateTime) -> Option<&str> {
+        let mut active = self.rules.iter().filter(|rule| rule.is_active(now)).peekable();
+        active.peek()?;
+        let title = title.to_lowercase();
+        let description = description.map(html_to_text).unwrap_or_default().to_lowercase();
+        active
+            .find(|rule| title.contains(&rule.keyword) || description.contains(&rule.keyword))
+            .map(|rule| rule.keyword.as_str())
+    }
+
+    pub fn mutes(&self, title: &str, description: Option<&str>, now: NaiveDateTime) -> bool {
+        self.matching_keyword(title, description, now).is_some()
+    }
+}
+
+// Walk the entries a filter selects in id order, a batch at a time, keeping the ids `keep` picks
+async fn select_entry_ids<C: ConnectionTrait>(
+    db: &C,
+    filter: Condition,
+    keep: impl Fn(&str, &str, Option<&str>) -> bool,
+) -> Result<Vec<i32>, String> {
+    let mut ids = Vec::new();
+    let mut last_id = 0;
+    loop {
+        let entries: Vec<(i32, String, String, Option<String>)> = FeedEntry::find()
+            .select_only()
+            .columns([
+                feed_entry::Column::Id,
+                feed_entry::Column::Link,
+                feed_entry::Column::Title,
+                feed_entry::Column::Description,
+            ])
+            .filter(filter.clone())
+            .filter(feed_entry::Column::Id.gt(last_id))
+            .order_by_asc(feed_entry::Column::Id)
+            .limit(MUTE_BATCH_SIZE)
+            .into_tuple()
+            .all(db)
+            .await
+            .map_err(|e| format!("Failed to fetch feed entries: {}", e))?;
+        let Some((id, ..)) = entries.last() else {
+            break;
+        };
+        last_id = *id;
+        ids.extend(
+            entries
+                .iter()
+                .filter(|(_, link, title, description)| keep(link, title, description.as_deref()))
+                .map(|(id, ..)| *id),
+        );
+    }
+    Ok(ids)
+}
+
+async fn set_hidden<C: ConnectionTrait>(db: &C, ids: Vec<i32>, hidden: bool) -> Result<u64, String> {
+    let mut updated = 0;
+    for chunk in ids.chunks(MUTE_BATCH_SIZE as usize) {
+        updated += FeedEntry::update_many()
+            .col_expr(feed_entry::Column::IsHidden, sea_query::Expr::value(hidden))
+            .filter(feed_entry::Column::Id.is_in(chunk.to_vec()))
+            .exec(db)
+            .await
+            .map_err(|e| format!("Failed to update feed entries: {}", e))?
+            .rows_affected;
+    }
+    Ok(updated)
+}
+
+// Hide the unread entries already saved that an active mute covers. Entries already read
+// stay listed.
+pub async fn hide_muted_entries<C: ConnectionTrait>(db: &C, config: &MuteRulesConfig, now: NaiveDateTime) -> Result<u64, String> {
+    if !config.rules.iter().any(|rule| rule.is_active(now)) {
+        return Ok(0);
+    }
+    let unread = Condition::all()
+        .add(feed_entry::Column::IsRead.eq(false))
+        .add(feed_entry::Column::IsHidden.eq(false));
+    let ids = select_entry_ids(db, unread, |_, title, description| config.mutes(title, description, now)).await?;
+    set_hidden(db, ids, true).await
+}
+
+// Show again the entries lifted mutes hid, unless a domain rule or a remaining mute still
+// hides them. Returns how many entries were shown again.
+pub async fn unhide_unmuted_entries<C: ConnectionTrait>(
+    db: &C,
+    lifted: Vec<MuteRule>,
+    remaining: &MuteRulesConfig,
+    now: NaiveDateTime,
+) -> Result<u64, String> {
+    if lifted.is_empty() {
+        return Ok(0);
+    }
+    // Matched as if still active, to find what they hid
+    let lifted = MuteRulesConfig {
+        rules: lifted.into_iter().map(|rule| MuteRule { expires_at: None, ..rule }).collect(),
+    };
+    let domain_rules = load_domain_rules_config(db).await;
+    let hidden = Condition::all().add(feed_entry::Column::IsHidden.eq(true));
+    let ids = select_entry_ids(db, hidden, |link, title, description| {
+        lifted.mutes(title, description, now)
+            && !remaining.mutes(title, description, now)
+            && domain_rules.action_for(link) != Some(DomainAction::Hide)
+    })
+    .await?;
+    set_hidden(db, ids, false).await
+}
+
+// Which of the entries an active mute covers, so showing entries again for another reason
+// leaves them hidden
+pub async fn muted_entry_ids<C: ConnectionTrait>(db: &C, ids: &[i32], now: NaiveDateTime) -> Result<HashSet<i32>, String> {
+    let config = load_mute_rules_config(db).await;
+    if ids.is_empty() || !config.rules.iter().any(|rule| rule.is_active(now)) {
+        return Ok(HashSet::new());
+    }
+    let mut muted = HashSet::new();
+    for chunk in ids.chunks(MUTE_BATCH_SIZE as usize) {
+        let entries: Vec<(i32, String, Option<String>)> = FeedEntry::find()
+            .select_only()
+            .columns([feed_entry::Column::Id, feed_entry::Column::Title, feed_entry::Column::Description])
+            .filter(feed_entry::Column::Id.is_in(chunk.to_vec()))
+            .into_tuple()
+            .all(db)
+            .await
+            .map_err(|e| format!("Failed to fetch feed entries: {}", e))?;
+        muted.extend(
+            entries
+                .into_iter()
+                .filter(|(_, title, description)| config.mutes(title, description.as_deref(), now))
+                .map(|(id, ..)| id),
+        );
+    }
+    Ok(muted)
+}
+
+// Drop the mutes that have lapsed and bring back what they hid. The scheduler runs this every
+// tick; it costs a settings read when nothing has lapsed.
+pub async fn expire_mutes<C: ConnectionTrait>(db: &C, now: NaiveDateTime) -> Result<u64, String> {
+    let mut config = load_mute_rules_config(db).await;
+    let expired = config.take_expired(now);
+    if expired.is_empty() {
+        return Ok(0);
+    }
+    set_setting_value(db, MUTE_RULES, &config).await?;
+
+    let keywords: Vec<String> = expired.iter().map(|rule| rule.keyword.clone()).collect();
+    let unhidden = unhide_unmuted_entries(db, expired, &config, now).await?;
+    println!("🔈 Mutes lapsed for {}: {} entries shown again", keywords.join(", "), unhidden);
+    Ok(unhidden)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::fixtures::at;
+
+    #[test]
+    fn test_mutes_match_title_and_description_text_until_they_lapse() {
+        let mut config = MuteRulesConfig::default();
+        config.mute(normalize_mute_keyword("  World Cup ").unwrap(), Some(at("2024-06-15 00:00")));
+        config.mute("election".to_string(), None);
+        let now = at("2024-06-01 12:00");
+
+        assert_eq!(config.matching_keyword("The WORLD CUP draw", None, now), Some("world cup"));
+        assert_eq!(config.matching_keyword("Weekly links", Some("<p>Polls before the <b>election</b></p>"), now), Some("election"));
+        assert!(!config.mutes("Rust 1.80 released", Some("<p>world</p>"), now));
+        assert!(!config.mutes("The World Cup draw", None, at("2024-06-15 00:00")));
+        assert_eq!(normalize_mute_keyword("   "), None);
+    }
+
+    #[test]
+    fn test_expired_mutes_are_taken_out_and_muting_again_extends() {
+        let mut config = MuteRulesConfig::default();
+        config.mute("a".to_string(), Some(at("2024-06-01 00:00")));
+        config.mute("b".to_string(), Some(at("2024-06-10 00:00")));
+        config.mute("a".to_string(), Some(at("2024-06-20 00:00")));
+        assert_eq!(config.rules.len(), 2);
+
+        let expired = config.take_expired(at("2024-06-12 00:00"));
+        assert_eq!(expired.iter().map(|rule| rule.keyword.as_str()).collect::<Vec<_>>(), vec!["b"]);
+        assert_eq!(config.rules.iter().map(|rule| rule.keyword.as_str()).collect::<Vec<_>>(), vec!["a"]);
+        assert!(config.unmute("a").is_some());
+        assert!(config.unmute("a").is_none());
+    }
+}
@@ -0,0 +1,592 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::{broadcast, Mutex, RwLock};
+use crate::models::feed_health::{FeedHealth, FeedHealthReport};
+use crate::models::responses::RefreshSummary;
+use crate::models::state::AppState;
+
+// How often held notifications are checked for whether they can be shown yet
+const OUTBOX_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+// Longest batching window the settings accept
+const MAX_BATCH_MINUTES: u32 = 24 * 60;
+
+// User preferences for desktop notifications.
+// A threshold of 0 disables that trigger, so with both thresholds at their
+// defaults a refresh that adds nothing and fails nothing stays silent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    pub refresh_summary_enabled: bool,
+    pub min_new_entries: usize,
+    pub min_failed_feeds: usize,
+    // Weekly digest of feeds that died or need signing in again
+    pub health_digest_enabled: bool,
+    // Nothing is shown from the start hour until the end hour (local time, 0-23), which may
+    // wrap past midnight; what arrives meanwhile is shown as one batch afterwards
+    pub quiet_hours_enabled: bool,
+    pub quiet_hours_start: u32,
+    pub quiet_hours_end: u32,
+    // At most this many notifications in any hour, 0 for no limit; the rest wait and are batched
+    pub max_per_hour: u32,
+    // Everything arriving within this many minutes of the first is shown as a single
+    // notification; 0 shows each as it arrives
+    pub batch_minutes: u32,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            refresh_summary_enabled: false,
+            min_new_entries: 1,
+            min_failed_feeds: 1,
+            health_digest_enabled: true,
+            quiet_hours_enabled: false,
+            quiet_hours_start: 22,
+            quiet_hours_end: 7,
+            max_per_hour: 0,
+            batch_minutes: 0,
+        }
+    }
+}
+
+impl NotificationConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.quiet_hours_start > 23 || self.quiet_hours_end > 23 {
+            return Err("Quiet hours must start and end between 0 and 23".to_string());
+        }
+        if self.batch_minutes > MAX_BATCH_MINUTES {
+            return Err(format!("Notifications can be batched over at most {} minutes", MAX_BATCH_MINUTES));
+        }
+        Ok(())
+    }
+
+    // Whether the local hour falls in the quiet hours. Equal start and end hours mean none.
+    pub fn is_quiet_hour(&self, hour: u32) -> bool {
+        if !self.quiet_hours_enabled || self.quiet_hours_start == self.quiet_hours_end {
+            return false;
+        }
+        if self.quiet_hours_start < self.quiet_hours_end {
+            (self.quiet_hours_start..self.quiet_hours_end).contains(&hour)
+        } else {
+            hour >= self.quiet_hours_start || hour < self.quiet_hours_end
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+}
+
+fn pluralize(count: usize, singular: &str, plural: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, singular)
+    } else {
+        format!("{} {}", count, plural)
+    }
+}
+
+// Build a single summary notification for a completed refresh, or None if the
+// refresh doesn't cross any of the configured thresholds
+pub fn build_refresh_summary_notification(
+    summary: &RefreshSummary,
+    config: &NotificationConfig,
+) -> Option<Notification> {
+    if !config.refresh_summary_enabled {
+        return None;
+    }
+
+    let new_entries: usize = summary.feeds_updated.iter().map(|status| status.entries_added).sum();
+    let feeds_with_new_entries = summary.feeds_updated
+        .iter()
+        .filter(|status| status.entries_added > 0)
+        .count();
+    let failed_feeds = summary.failed_count;
+
+    let new_entries_triggered = config.min_new_entries > 0 && new_entries >= config.min_new_entries;
+    let failures_triggered = config.min_failed_feeds > 0 && failed_feeds >= config.min_failed_feeds;
+
+    if !new_entries_triggered && !failures_triggered {
+        return None;
+    }
+
+    let mut body = if new_entries > 0 {
+        format!(
+            "{} across {}",
+            pluralize(new_entries, "new item", "new items"),
+            pluralize(feeds_with_new_entries, "feed", "feeds"),
+        )
+    } else {
+        "No new items".to_string()
+    };
+
+    if failed_feeds > 0 {
+        body.push_str(&format!(", {} failing", pluralize(failed_feeds, "feed", "feeds")));
+    }
+
+    Some(Notification {
+        title: "Feeds refreshed".to_string(),
+        body,
+    })
+}
+
+// How many feeds a health digest names before summarizing the rest
+const HEALTH_DIGEST_MAX_NAMED_FEEDS: usize = 3;
+
+// Build the weekly "subscription problems" digest for feeds that newly died or lost their credentials
+pub fn build_health_digest_notification(problems: &[FeedHealthReport]) -> Option<Notification> {
+    if problems.is_empty() {
+        return None;
+    }
+
+    let dead_feeds = problems.iter().filter(|problem| problem.health == FeedHealth::Dead).count();
+    let auth_broken_feeds = problems.iter().filter(|problem| problem.health == FeedHealth::AuthBroken).count();
+
+    let mut parts = Vec::new();
+    if dead_feeds > 0 {
+        parts.push(format!("{} stopped working", pluralize(dead_feeds, "feed", "feeds")));
+    }
+    if auth_broken_feeds > 0 {
+        let verb = if auth_broken_feeds == 1 { "needs" } else { "need" };
+        parts.push(format!("{} {} signing in again", pluralize(auth_broken_feeds, "feed", "feeds"), verb));
+    }
+
+    let names: Vec<&str> = problems
+        .iter()
+        .take(HEALTH_DIGEST_MAX_NAMED_FEEDS)
+        .map(|problem| problem.feed_title.as_deref().unwrap_or(&problem.feed_url))
+        .collect();
+    let mut body = format!("{}: {}", parts.join(", "), names.join(", "));
+    if problems.len() > HEALTH_DIGEST_MAX_NAMED_FEEDS {
+        body.push_str(&format!(" and {} more", problems.len() - HEALTH_DIGEST_MAX_NAMED_FEEDS));
+    }
+
+    Some(Notification {
+        title: "Subscription problems".to_string(),
+        body,
+    })
+}
+
+// How many matching entries an alert notification names before summarizing the rest
+const ALERT_MAX_NAMED_ENTRIES: usize = 3;
+
+// Build the notification for an alert rule's new matches, naming the first few entries
+pub fn build_alert_notification(rule_name: &str, entry_titles: &[String]) -> Option<Notification> {
+    if entry_titles.is_empty() {
+        return None;
+    }
+
+    let names: Vec<&str> = entry_titles.iter().take(ALERT_MAX_NAMED_ENTRIES).map(String::as_str).collect();
+    let mut body = format!("{}: {}", pluralize(entry_titles.len(), "new match", "new matches"), names.join(", "));
+    if entry_titles.len() > ALERT_MAX_NAMED_ENTRIES {
+        body.push_str(&format!(" and {} more", entry_titles.len() - ALERT_MAX_NAMED_ENTRIES));
+    }
+
+    Some(Notification {
+        title: format!("Alert: {}", rule_name),
+        body,
+    })
+}
+
+pub fn show_notification(app: &AppHandle, notification: &Notification) -> Result<(), String> {
+    app.notification()
+        .builder()
+        .title(&notification.title)
+        .body(&notification.body)
+        .show()
+        .map_err(|e| format!("Failed to show notification: {}", e))
+}
+
+// Notifications waiting to be shown. Refresh summaries are merged, so a batch of refreshes
+// reads "12 new items across 3 feeds" rather than one line per refresh.
+#[derive(Debug, Default)]
+pub struct HeldNotifications {
+    refresh_summary: Option<RefreshSummary>,
+    others: Vec<Notification>,
+    // When the oldest of them arrived, which opens the batching window
+    since: Option<NaiveDateTime>,
+}
+
+impl HeldNotifications {
+    pub fn add_refresh_summary(&mut self, summary: &RefreshSummary, now: NaiveDateTime) {
+        match &mut self.refresh_summary {
+            Some(merged) => merge_refresh_summaries(merged, summary),
+            None => self.refresh_summary = Some(summary.clone()),
+        }
+        self.since.get_or_insert(now);
+    }
+
+    pub fn add(&mut self, notification: Notification, now: NaiveDateTime) {
+        self.others.push(notification);
+        self.since.get_or_insert(now);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.since.is_none()
+    }
+
+    // Everything held as one notification: a lone notification as it is, several with a line
+    // each. None when only refresh counts below the thresholds are held.
+    pub fn build(&self, config: &NotificationConfig) -> Option<Notification> {
+        let refresh_summary = self
+            .refresh_summary
+            .as_ref()
+            .and_then(|summary| build_refresh_summary_notification(summary, config));
+        let notifications: Vec<&Notification> = refresh_summary.iter().chain(self.others.iter()).collect();
+        match notifications.as_slice() {
+            [] => None,
+            [notification] => Some((*notification).clone()),
+            notifications => Some(Notification {
+                title: pluralize(notifications.len(), "notification", "notifications"),
+                body: notifications
+                    .iter()
+                    .map(|notification| format!("{}: {}", notification.title, notification.body))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            }),
+        }
+    }
+}
+
+// Add a later refresh to a held summary: each feed's new entries are added up, and a feed
+// failing in several refreshes counts once
+fn merge_refresh_summaries(merged: &mut RefreshSummary, summary: &RefreshSummary) {
+    for status in &summary.feeds_updated {
+        match merged.feeds_updated.iter_mut().find(|merged_status| merged_status.feed_id == status.feed_id) {
+            Some(merged_status) => merged_status.entries_added += status.entries_added,
+            None => merged.feeds_updated.push(status.clone()),
+        }
+    }
+    merged.errors.extend(summary.errors.iter().cloned());
+    merged.failed_count = merged.errors.iter().map(|error| error.feed_url.as_str()).collect::<HashSet<_>>().len();
+    merged.total_processed += summary.total_processed;
+    merged.successful_count += summary.successful_count;
+    merged.duration_seconds += summary.duration_seconds;
+    merged.timestamp = summary.timestamp.clone();
+}
+
+// Held notifications and when recent ones were shown, for the hourly limit
+#[derive(Debug, Default)]
+pub struct OutboxState {
+    held: HeldNotifications,
+    shown_at: VecDeque<NaiveDateTime>,
+}
+
+impl OutboxState {
+    // Take what's held as one notification, if quiet hours, the hourly limit and the batching
+    // window all allow showing it now. It is counted against the limit as shown.
+    pub fn release(&mut self, config: &NotificationConfig, local_hour: u32, now: NaiveDateTime) -> Option<Notification> {
+        let hour_ago = now - chrono::Duration::hours(1);
+        while self.shown_at.front().is_some_and(|shown_at| *shown_at <= hour_ago) {
+            self.shown_at.pop_front();
+        }
+
+        let since = self.held.since?;
+        if config.is_quiet_hour(local_hour) {
+            return None;
+        }
+        if config.max_per_hour > 0 && self.shown_at.len() >= config.max_per_hour as usize {
+            return None;
+        }
+        if now - since < chrono::Duration::minutes(config.batch_minutes.into()) {
+            return None;
+        }
+
+        let notification = std::mem::take(&mut self.held).build(config)?;
+        self.shown_at.push_back(now);
+        Some(notification)
+    }
+}
+
+// Every notification goes through here, so quiet hours, the hourly limit and batching apply
+// to refresh summaries, alerts and digests alike
+pub struct NotificationOutbox {
+    config: Arc<RwLock<NotificationConfig>>,
+    state: Mutex<OutboxState>,
+}
+
+impl NotificationOutbox {
+    pub fn new(config: Arc<RwLock<NotificationConfig>>) -> Self {
+        NotificationOutbox {
+            config,
+            state: Mutex::new(OutboxState::default()),
+        }
+    }
+
+    pub async fn push_refresh_summary(&self, app: &AppHandle, summary: &RefreshSummary) {
+        if !self.config.read().await.refresh_summary_enabled {
+            return;
+        }
+        let mut state = self.state.lock().await;
+        state.held.add_refresh_summary(summary, chrono::Utc::now().naive_utc());
+        self.flush_locked(app, &mut state).await;
+    }
+
+    pub async fn push(&self, app: &AppHandle, notification: Notification) {
+        let mut state = self.state.lock().await;
+        state.held.add(notification, chrono::Utc::now().naive_utc());
+        self.flush_locked(app, &mut state).await;
+    }
+
+    // Show what's held once nothing holds it back any more
+    pub async fn flush(&self, app: &AppHandle) {
+        let mut state = self.state.lock().await;
+        if !state.held.is_empty() {
+            self.flush_locked(app, &mut state).await;
+        }
+    }
+
+    async fn flush_locked(&self, app: &AppHandle, state: &mut OutboxState) {
+        let config = self.config.read().await.clone();
+        let local_hour = chrono::Local::now().hour();
+        if let Some(notification) = state.release(&config, local_hour, chrono::Utc::now().naive_utc()) {
+            if let Err(e) = show_notification(app, &notification) {
+                eprintln!("{}", e);
+            }
+        }
+    }
+}
+
+// Show held notifications once quiet hours end, the hourly limit frees up or their batching
+// window closes
+pub async fn run_notification_outbox(app: AppHandle) {
+    let mut ticker = tokio::time::interval(OUTBOX_FLUSH_INTERVAL);
+    loop {
+        ticker.tick().await;
+        app.state::<AppState>().notifications.flush(&app).await;
+    }
+}
+
+// Listen for completed refresh summaries and pass each to the outbox, which shows them
+// when they cross the configured thresholds
+pub async fn run_refresh_summary_notifier(app: AppHandle, mut summaries: broadcast::Receiver<RefreshSummary>) {
+    loop {
+        let summary = match summaries.recv().await {
+            Ok(summary) => summary,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                eprintln!("Refresh summary notifier skipped {} summaries", skipped);
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+
+        app.state::<AppState>().notifications.push_refresh_summary(&app, &summary).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::fixtures::at;
+    use crate::models::responses::{FeedRefreshStatus, RefreshError};
+
+    fn feed_status(feed_id: i32, entries_added: usize) -> FeedRefreshStatus {
+        FeedRefreshStatus {
+            feed_id,
+            feed_url: format!("https://example.com/{}.xml", feed_id),
+            feed_title: None,
+            status: "success".to_string(),
+            entries_added,
+            entries_updated: 0,
+            last_fetched_at: "2024-01-01T00:00:00Z".to_string(),
+            error: None,
+        }
+    }
+
+    fn summary(entries: &[usize], failed_count: usize) -> RefreshSummary {
+        RefreshSummary {
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            total_processed: entries.len() + failed_count,
+            successful_count: entries.len(),
+            failed_count,
+            duration_seconds: 3,
+            feeds_updated: entries.iter().enumerate()
+                .map(|(i, added)| feed_status(i as i32, *added))
+                .collect(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn enabled_config() -> NotificationConfig {
+        NotificationConfig {
+            refresh_summary_enabled: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_summary_notification_text() {
+        let notification = build_refresh_summary_notification(&summary(&[40, 2, 0], 2), &enabled_config())
+            .expect("notification should be built");
+        assert_eq!(notification.body, "42 new items across 2 feeds, 2 feeds failing");
+
+        let notification = build_refresh_summary_notification(&summary(&[1], 0), &enabled_config())
+            .expect("notification should be built");
+        assert_eq!(notification.body, "1 new item across 1 feed");
+    }
+
+    #[test]
+    fn test_zero_change_refresh_is_silent() {
+        assert_eq!(build_refresh_summary_notification(&summary(&[0, 0], 0), &enabled_config()), None);
+    }
+
+    #[test]
+    fn test_thresholds_and_disabled() {
+        let config = NotificationConfig {
+            refresh_summary_enabled: true,
+            min_new_entries: 10,
+            min_failed_feeds: 0,
+            ..Default::default()
+        };
+        assert_eq!(build_refresh_summary_notification(&summary(&[5], 3), &config), None);
+        assert!(build_refresh_summary_notification(&summary(&[5, 5], 0), &config).is_some());
+
+        assert_eq!(build_refresh_summary_notification(&summary(&[5], 1), &NotificationConfig::default()), None);
+    }
+
+    fn problem(feed_id: i32, title: &str, health: FeedHealth) -> FeedHealthReport {
+        FeedHealthReport {
+            feed_id,
+            feed_url: format!("https://example.com/{}.xml", feed_id),
+            feed_title: Some(title.to_string()),
+            health,
+            consecutive_failures: 3,
+            failing_since: None,
+            last_error: None,
+        }
+    }
+
+    #[test]
+    fn test_health_digest_text() {
+        let problems = vec![
+            problem(1, "Old Blog", FeedHealth::Dead),
+            problem(2, "Paid Newsletter", FeedHealth::AuthBroken),
+            problem(3, "Podcast", FeedHealth::Dead),
+            problem(4, "Forum", FeedHealth::Dead),
+        ];
+
+        let notification = build_health_digest_notification(&problems).expect("digest should be built");
+        assert_eq!(notification.title, "Subscription problems");
+        assert_eq!(
+            notification.body,
+            "3 feeds stopped working, 1 feed needs signing in again: Old Blog, Paid Newsletter, Podcast and 1 more"
+        );
+
+        assert_eq!(build_health_digest_notification(&[]), None);
+    }
+
+    #[test]
+    fn test_alert_notification_text() {
+        let titles: Vec<String> = ["Outage in eu-west-1", "Outage resolved", "Postmortem", "Follow-up"]
+            .iter()
+            .map(|title| title.to_string())
+            .collect();
+
+        let notification = build_alert_notification("Outages", &titles).expect("notification should be built");
+        assert_eq!(notification.title, "Alert: Outages");
+        assert_eq!(
+            notification.body,
+            "4 new matches: Outage in eu-west-1, Outage resolved, Postmortem and 1 more"
+        );
+
+        let notification = build_alert_notification("Outages", &titles[..1]).expect("notification should be built");
+        assert_eq!(notification.body, "1 new match: Outage in eu-west-1");
+
+        assert_eq!(build_alert_notification("Outages", &[]), None);
+    }
+
+    fn alert(title: &str) -> Notification {
+        Notification {
+            title: format!("Alert: {}", title),
+            body: "1 new match: Outage".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_quiet_hours_wrap_past_midnight() {
+        let config = NotificationConfig {
+            quiet_hours_enabled: true,
+            quiet_hours_start: 22,
+            quiet_hours_end: 7,
+            ..Default::default()
+        };
+        assert!(config.is_quiet_hour(23) && config.is_quiet_hour(0) && config.is_quiet_hour(6));
+        assert!(!config.is_quiet_hour(7) && !config.is_quiet_hour(21));
+
+        let daytime = NotificationConfig { quiet_hours_start: 9, quiet_hours_end: 17, ..config.clone() };
+        assert!(daytime.is_quiet_hour(9) && !daytime.is_quiet_hour(17));
+
+        assert!(!NotificationConfig { quiet_hours_enabled: false, ..config }.is_quiet_hour(23));
+        assert!(NotificationConfig { quiet_hours_start: 24, ..Default::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_refreshes_held_in_quiet_hours_are_merged() {
+        let config = NotificationConfig {
+            quiet_hours_enabled: true,
+            ..enabled_config()
+        };
+        let mut outbox = OutboxState::default();
+
+        let mut first = summary(&[5, 0], 0);
+        first.errors.push(RefreshError {
+            feed_url: "https://example.com/1.xml".to_string(),
+            feed_title: None,
+            error_message: "timed out".to_string(),
+            error_type: "timeout".to_string(),
+            retry_count: 3,
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+        });
+        first.failed_count = 1;
+        let second = summary(&[4, 3], 0);
+        outbox.held.add_refresh_summary(&first, at("2024-01-01 23:00"));
+        outbox.held.add_refresh_summary(&second, at("2024-01-02 02:00"));
+        outbox.held.add_refresh_summary(&first, at("2024-01-02 04:00"));
+
+        assert_eq!(outbox.release(&config, 4, at("2024-01-02 04:00")), None);
+        let notification = outbox.release(&config, 7, at("2024-01-02 07:00")).expect("batch should be released");
+        assert_eq!(notification.body, "17 new items across 2 feeds, 1 feed failing");
+        assert!(outbox.held.is_empty());
+    }
+
+    #[test]
+    fn test_hourly_limit_and_batching() {
+        let config = NotificationConfig {
+            max_per_hour: 2,
+            ..enabled_config()
+        };
+        let mut outbox = OutboxState::default();
+
+        for minute in ["10:00", "10:10"] {
+            outbox.held.add(alert("Outages"), at(&format!("2024-01-01 {}", minute)));
+            assert!(outbox.release(&config, 10, at(&format!("2024-01-01 {}", minute))).is_some());
+        }
+        outbox.held.add(alert("Outages"), at("2024-01-01 10:20"));
+        outbox.held.add(alert("Releases"), at("2024-01-01 10:30"));
+        assert_eq!(outbox.release(&config, 10, at("2024-01-01 10:30")), None);
+
+        // An hour after the first was shown there is room again, and both go out as one
+        let notification = outbox.release(&config, 11, at("2024-01-01 11:00")).expect("held alerts should be released");
+        assert_eq!(notification.title, "2 notifications");
+        assert_eq!(notification.body, "Alert: Outages: 1 new match: Outage\nAlert: Releases: 1 new match: Outage");
+
+        let batching = NotificationConfig {
+            batch_minutes: 15,
+            ..enabled_config()
+        };
+        let mut outbox = OutboxState::default();
+        outbox.held.add_refresh_summary(&summary(&[12], 0), at("2024-01-01 10:00"));
+        assert_eq!(outbox.release(&batching, 10, at("2024-01-01 10:05")), None);
+        assert_eq!(
+            outbox.release(&batching, 10, at("2024-01-01 10:15")).map(|notification| notification.body),
+            Some("12 new items across 1 feed".to_string())
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::Path;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use url::Url;
+use crate::models::state::AppState;
+use crate::models::tray::show_main_window;
+
+pub const OPEN_REQUESTED_EVENT: &str = "app:open_requested";
+
+// Something the OS asked the app to open, waiting for the user to confirm it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OpenRequest {
+    // Subscribe to the feed a feed:// link points at
+    Subscribe { url: String },
+    // Import the subscriptions in an OPML file
+    ImportOpml { path: String },
+}
+
+// The feed URL behind a feed:// link. Sites use both feed://example.com/rss and
+// feed:https://example.com/rss, and some browsers hand over feed://https://example.com/rss.
+pub fn feed_link_target(link: &str) -> Option<String> {
+    let rest = link.get(..5).filter(|scheme| scheme.eq_ignore_ascii_case("feed:")).map(|_| &link[5..])?;
+    let target = match rest.strip_prefix("//") {
+        Some(inner) if inner.starts_with("http://") || inner.starts_with("https://") => inner.to_string(),
+        Some(_) => format!("http:{}", rest),
+        None => rest.to_string(),
+    };
+
+    let url = Url::parse(&target).ok()?;
+    (matches!(url.scheme(), "http" | "https") && url.host_str().is_some()).then(|| url.to_string())
+}
+
+fn is_opml_file(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("opml"))
+}
+
+// Read a launch argument or an opened URL as a feed:// link or an OPML file
+pub fn parse_open_request(argument: &str) -> Option<OpenRequest> {
+    if let Some(url) = feed_link_target(argument) {
+        return Some(OpenRequest::Subscribe { url });
+    }
+
+    let path = match Url::parse(argument) {
+        Ok(url) if url.scheme() == "file" => url.to_file_path().ok()?,
+        _ if !argument.starts_with('-') => Path::new(argument).to_path_buf(),
+        _ => return None,
+    };
+    is_opml_file(&path).then(|| OpenRequest::ImportOpml {
+        path: std::path::absolute(&path).unwrap_or(path).to_string_lossy().into_owned(),
+    })
+}
+
+// Hold open requests until the frontend takes them with take_pending_open_requests, and let
+// it know they're waiting. Requests can arrive before the frontend listens for the event, so
+// it also takes pending requests once at startup.
+pub fn queue_open_requests(app: &AppHandle, requests: Vec<OpenRequest>) {
+    if requests.is_empty() {
+        return;
+    }
+    let state = app.state::<AppState>();
+    let mut pending = state.pending_open_requests.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    for request in requests {
+        println!("📂 Open requested: {:?}", request);
+        if let Err(e) = app.emit(OPEN_REQUESTED_EVENT, &request) {
+            eprintln!("❌ Failed to emit open request: {}", e);
+        }
+        pending.push(request);
+    }
+    show_main_window(app);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_links_and_opml_files_are_recognised() {
+        assert_eq!(feed_link_target("feed://example.com/rss.xml").as_deref(), Some("http://example.com/rss.xml"));
+        assert_eq!(feed_link_target("FEED:https://example.com/atom").as_deref(), Some("https://example.com/atom"));
+        assert_eq!(feed_link_target("feed://https://example.com/atom").as_deref(), Some("https://example.com/atom"));
+        assert_eq!(feed_link_target("feed:javascript:alert(1)"), None);
+        assert_eq!(feed_link_target("https://example.com/rss.xml"), None);
+
+        assert_eq!(
+            parse_open_request("feed://example.com/rss.xml"),
+            Some(OpenRequest::Subscribe { url: "http://example.com/rss.xml".to_string() })
+        );
+        assert_eq!(
+            parse_open_request("file:///home/me/Subscriptions.OPML"),
+            Some(OpenRequest::ImportOpml { path: "/home/me/Subscriptions.OPML".to_string() })
+        );
+        assert_eq!(
+            parse_open_request("/tmp/feeds.opml"),
+            Some(OpenRequest::ImportOpml { path: "/tmp/feeds.opml".to_string() })
+        );
+        assert_eq!(parse_open_request("/tmp/notes.txt"), None);
+        assert_eq!(parse_open_request("--flag.opml"), None);
+    }
+}
@@ -0,0 +1,370 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, EventId, Listener, Manager};
+use tokio::sync::watch;
+use crate::models::state::AppState;
+
+// Event emitted as a long-running operation starts, makes progress, or finishes
+pub const OPERATION_PROGRESS_EVENT: &str = "operation:progress";
+
+// Event the frontend emits, with an operation_id, to cancel an operation
+pub const OPERATION_CANCEL_EVENT: &str = "operation:cancel";
+
+// Minimum time between progress events for a single operation
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(250);
+
+// How many finished operations stay listed, most recent first
+const FINISHED_OPERATIONS_KEPT: usize = 20;
+
+static NEXT_OPERATION_ID: AtomicU64 = AtomicU64::new(1);
+
+// Ids are shared by every kind of operation, so events from different ones never collide
+pub fn next_operation_id() -> u64 {
+    NEXT_OPERATION_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    Refresh,
+    Import,
+    FullContent,
+    Export,
+    Download,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationStatus {
+    Running,
+    Cancelling,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+// Progress of one long-running operation, as sent with every operation:progress event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperationProgress {
+    pub operation_id: u64,
+    pub kind: OperationKind,
+    pub status: OperationStatus,
+    // Items of work (feeds, entries, bytes) the operation goes through; 0 while not yet known
+    pub total: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub progress_percentage: f32,
+    // What the operation is doing at the moment, e.g. "Fetching new feeds"
+    pub message: Option<String>,
+    pub error: Option<String>,
+}
+
+impl OperationProgress {
+    pub fn new(operation_id: u64, kind: OperationKind, total: usize) -> Self {
+        OperationProgress {
+            operation_id,
+            kind,
+            status: OperationStatus::Running,
+            total,
+            completed: 0,
+            failed: 0,
+            progress_percentage: 0.0,
+            message: None,
+            error: None,
+        }
+    }
+
+    // Count one finished item of work, failed or not
+    pub fn record(&mut self, succeeded: bool) {
+        self.set_counts(self.completed + 1, self.failed + usize::from(!succeeded));
+    }
+
+    pub fn set_counts(&mut self, completed: usize, failed: usize) {
+        self.completed = completed;
+        self.failed = failed;
+        self.update_percentage();
+    }
+
+    // Start counting a new stage of the operation, with its own total
+    pub fn restart(&mut self, total: usize, message: Option<String>) {
+        self.total = total;
+        self.message = message;
+        self.set_counts(0, 0);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            OperationStatus::Completed | OperationStatus::Cancelled | OperationStatus::Failed
+        )
+    }
+
+    fn update_percentage(&mut self) {
+        self.progress_percentage = if self.total > 0 {
+            (self.completed.min(self.total) as f32 / self.total as f32) * 100.0
+        } else {
+            0.0
+        };
+    }
+}
+
+#[derive(Deserialize)]
+struct CancelRequest {
+    operation_id: u64,
+}
+
+struct OperationState {
+    app: AppHandle,
+    progress: Mutex<OperationProgress>,
+    cancelled: watch::Sender<bool>,
+    last_event: Mutex<Instant>,
+    cancel_listener: Mutex<Option<EventId>>,
+    // Run once when the operation is cancelled, for work that has to be told to stop
+    on_cancel: Mutex<Option<Box<dyn FnOnce() + Send>>>,
+}
+
+// A running operation: reports its progress through operation:progress events and is listed
+// in AppState's operations until long after it finishes. It can be cancelled with
+// cancel_operation or an operation:cancel event; the work itself checks is_cancelled()
+// between items (or waits on cancelled()) and stops, and finish() sends the final event.
+// Clones share the same operation.
+#[derive(Clone)]
+pub struct Operation {
+    state: Arc<OperationState>,
+}
+
+impl Operation {
+    pub fn start(app: &AppHandle, kind: OperationKind, total: usize) -> Self {
+        let operation_id = next_operation_id();
+        let operation = Operation {
+            state: Arc::new(OperationState {
+                app: app.clone(),
+                progress: Mutex::new(OperationProgress::new(operation_id, kind, total)),
+                cancelled: watch::Sender::new(false),
+                last_event: Mutex::new(Instant::now()),
+                cancel_listener: Mutex::new(None),
+                on_cancel: Mutex::new(None),
+            }),
+        };
+
+        let weak = Arc::downgrade(&operation.state);
+        let listener = app.listen(OPERATION_CANCEL_EVENT, move |event| {
+            let Ok(request) = serde_json::from_str::<CancelRequest>(event.payload()) else {
+                return;
+            };
+            if let Some(state) = weak.upgrade().filter(|_| request.operation_id == operation_id) {
+                Operation { state }.cancel();
+            }
+        });
+        *operation.state.cancel_listener.lock().unwrap() = Some(listener);
+
+        if let Some(app_state) = app.try_state::<AppState>() {
+            app_state.operations.register(&operation);
+        }
+        operation.emit();
+        operation
+    }
+
+    pub fn id(&self) -> u64 {
+        self.state.progress.lock().unwrap().operation_id
+    }
+
+    pub fn progress(&self) -> OperationProgress {
+        self.state.progress.lock().unwrap().clone()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.state.cancelled.borrow()
+    }
+
+    // Resolves once the operation is cancelled
+    pub async fn cancelled(&self) {
+        let mut cancelled = self.state.cancelled.subscribe();
+        let _ = cancelled.wait_for(|cancelled| *cancelled).await;
+    }
+
+    // Have `stop` run if the operation is cancelled, e.g. to drop queued work
+    pub fn on_cancel(&self, stop: impl FnOnce() + Send + 'static) {
+        *self.state.on_cancel.lock().unwrap() = Some(Box::new(stop));
+    }
+
+    pub fn cancel(&self) {
+        {
+            let mut progress = self.state.progress.lock().unwrap();
+            if progress.is_finished() || self.state.cancelled.send_replace(true) {
+                return;
+            }
+            progress.status = OperationStatus::Cancelling;
+        }
+        if let Some(stop) = self.state.on_cancel.lock().unwrap().take() {
+            stop();
+        }
+        self.emit();
+    }
+
+    // Move on to a new stage, e.g. from saving an import to fetching its feeds
+    pub fn restart(&self, total: usize, message: impl Into<String>) {
+        self.state.progress.lock().unwrap().restart(total, Some(message.into()));
+        self.emit();
+    }
+
+    // Count one finished item. Events are rate-limited, except for the last item.
+    pub fn record(&self, succeeded: bool) {
+        let done = {
+            let mut progress = self.state.progress.lock().unwrap();
+            progress.record(succeeded);
+            progress.completed >= progress.total
+        };
+        self.emit_throttled(done);
+    }
+
+    // Report progress measured in something other than items, such as bytes downloaded
+    pub fn set_progress(&self, completed: usize, total: usize) {
+        {
+            let mut progress = self.state.progress.lock().unwrap();
+            progress.total = total;
+            let failed = progress.failed;
+            progress.set_counts(completed, failed);
+        }
+        self.emit_throttled(false);
+    }
+
+    // End the operation and send its final event. An operation that was asked to cancel ends
+    // as cancelled, unless it failed.
+    pub fn finish(&self, result: Result<(), String>) -> OperationProgress {
+        if let Some(listener) = self.state.cancel_listener.lock().unwrap().take() {
+            self.state.app.unlisten(listener);
+        }
+        self.state.on_cancel.lock().unwrap().take();
+        let finished = {
+            let mut progress = self.state.progress.lock().unwrap();
+            if progress.is_finished() {
+                return progress.clone();
+            }
+            match result {
+                Ok(()) if self.is_cancelled() => progress.status = OperationStatus::Cancelled,
+                Ok(()) => progress.status = OperationStatus::Completed,
+                Err(error) => {
+                    progress.status = OperationStatus::Failed;
+                    progress.error = Some(error);
+                }
+            }
+            progress.clone()
+        };
+        self.emit();
+        finished
+    }
+
+    fn emit_throttled(&self, force: bool) {
+        let mut last_event = self.state.last_event.lock().unwrap();
+        if force || last_event.elapsed() >= PROGRESS_EVENT_INTERVAL {
+            *last_event = Instant::now();
+            drop(last_event);
+            self.emit();
+        }
+    }
+
+    fn emit(&self) {
+        let progress = self.progress();
+        if let Err(e) = self.state.app.emit(OPERATION_PROGRESS_EVENT, &progress) {
+            eprintln!("❌ Failed to emit operation progress: {}", e);
+        }
+    }
+}
+
+// Which finished operations to forget so that only the `keep` most recent remain listed.
+// Running operations are always kept.
+fn finished_operations_to_forget(operations: &[OperationProgress], keep: usize) -> Vec<u64> {
+    let mut finished: Vec<u64> = operations
+        .iter()
+        .filter(|progress| progress.is_finished())
+        .map(|progress| progress.operation_id)
+        .collect();
+    finished.sort_unstable_by(|a, b| b.cmp(a));
+    finished.into_iter().skip(keep).collect()
+}
+
+// Every operation started this session, so the frontend can list background work (refreshes,
+// imports, exports, downloads) in one place and cancel any of it. Finished operations are
+// kept for a while so their outcome can still be shown.
+#[derive(Clone, Default)]
+pub struct OperationRegistry {
+    operations: Arc<Mutex<HashMap<u64, Operation>>>,
+}
+
+impl OperationRegistry {
+    fn register(&self, operation: &Operation) {
+        let mut operations = self.operations.lock().unwrap();
+        let progresses: Vec<OperationProgress> = operations.values().map(Operation::progress).collect();
+        for operation_id in finished_operations_to_forget(&progresses, FINISHED_OPERATIONS_KEPT) {
+            operations.remove(&operation_id);
+        }
+        operations.insert(operation.id(), operation.clone());
+    }
+
+    pub fn get(&self, operation_id: u64) -> Option<Operation> {
+        self.operations.lock().unwrap().get(&operation_id).cloned()
+    }
+
+    // Every listed operation, oldest first
+    pub fn list(&self) -> Vec<OperationProgress> {
+        let mut progresses: Vec<OperationProgress> =
+            self.operations.lock().unwrap().values().map(Operation::progress).collect();
+        progresses.sort_by_key(|progress| progress.operation_id);
+        progresses
+    }
+
+    pub fn cancel(&self, operation_id: u64) -> Result<OperationProgress, String> {
+        let operation = self.get(operation_id).ok_or("Operation not found")?;
+        if operation.progress().is_finished() {
+            return Err("Operation already finished".to_string());
+        }
+        operation.cancel();
+        Ok(operation.progress())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_counts_items_and_stages() {
+        let mut progress = OperationProgress::new(next_operation_id(), OperationKind::Import, 4);
+        progress.record(true);
+        progress.record(false);
+        assert_eq!((progress.completed, progress.failed), (2, 1));
+        assert_eq!(progress.progress_percentage, 50.0);
+
+        progress.restart(10, Some("Fetching new feeds".to_string()));
+        assert_eq!((progress.completed, progress.failed, progress.progress_percentage), (0, 0, 0.0));
+        progress.record(true);
+        assert_eq!(progress.progress_percentage, 10.0);
+        assert!(!progress.is_finished());
+
+        let unknown_total = OperationProgress::new(next_operation_id(), OperationKind::Export, 0);
+        assert_eq!(unknown_total.progress_percentage, 0.0);
+        assert_ne!(unknown_total.operation_id, progress.operation_id);
+    }
+
+    #[test]
+    fn test_only_old_finished_operations_are_forgotten() {
+        let operations: Vec<OperationProgress> = (1..=5)
+            .map(|operation_id| {
+                let mut progress = OperationProgress::new(operation_id, OperationKind::Refresh, 1);
+                if operation_id != 1 {
+                    progress.status = OperationStatus::Completed;
+                }
+                progress
+            })
+            .collect();
+
+        let mut forgotten = finished_operations_to_forget(&operations, 2);
+        forgotten.sort_unstable();
+        assert_eq!(forgotten, [2, 3]);
+        assert!(finished_operations_to_forget(&operations, 10).is_empty());
+    }
+}
@@ -0,0 +1,36 @@
+// Treat an episode as finished once playback gets this close to the end, since players
+// rarely report the exact duration and outros are commonly skipped
+const FINISHED_REMAINING_SECONDS: i32 = 30;
+
+// Whether a saved position means the episode was listened to. An explicit flag from the
+// player wins; otherwise it is finished when little enough is left of a known duration.
+pub fn is_playback_finished(position_seconds: i32, duration_seconds: Option<i32>, finished: Option<bool>) -> bool {
+    if let Some(finished) = finished {
+        return finished;
+    }
+
+    match duration_seconds {
+        Some(duration) if duration > 0 => duration - position_seconds <= FINISHED_REMAINING_SECONDS,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finished_near_the_end() {
+        assert!(!is_playback_finished(600, Some(3600), None));
+        assert!(is_playback_finished(3580, Some(3600), None));
+        assert!(is_playback_finished(3600, Some(3600), None));
+        // Without a duration only the player can say so
+        assert!(!is_playback_finished(3580, None, None));
+    }
+
+    #[test]
+    fn test_explicit_flag_wins() {
+        assert!(is_playback_finished(10, Some(3600), Some(true)));
+        assert!(!is_playback_finished(3600, Some(3600), Some(false)));
+    }
+}
@@ -0,0 +1,77 @@
+use std::env;
+use sea_orm::*;
+use crate::entities::prelude::*;
+use crate::models::database::connect_database;
+use crate::models::settings::{get_setting_or, ACTIVE_PROFILE};
+
+// The library in the main (DATABASE_URL) database, which also stores the list of other profiles
+pub const DEFAULT_PROFILE: &str = "default";
+
+// Event emitted after the active profile changes, so the UI can reload everything
+pub const PROFILE_SWITCHED_EVENT: &str = "profile:switched";
+
+const MAX_PROFILE_NAME_LENGTH: usize = 64;
+
+pub fn validate_profile_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_PROFILE_NAME_LENGTH {
+        return Err(format!("Profile name cannot be longer than {} characters", MAX_PROFILE_NAME_LENGTH));
+    }
+    if name.eq_ignore_ascii_case(DEFAULT_PROFILE) {
+        return Err(format!("\"{}\" is reserved for the main library", DEFAULT_PROFILE));
+    }
+    Ok(name.to_string())
+}
+
+// The profile to open at startup: READER_PROFILE from the environment, otherwise the last one used
+pub async fn startup_profile_name<C: ConnectionTrait>(home_db: &C) -> String {
+    match env::var("READER_PROFILE") {
+        Ok(name) if !name.trim().is_empty() => name.trim().to_string(),
+        _ => get_setting_or(home_db, ACTIVE_PROFILE, DEFAULT_PROFILE.to_string()).await,
+    }
+}
+
+// A profile database has to be migrated before it can be used as a library
+pub async fn ensure_library_schema<C: ConnectionTrait>(db: &C) -> Result<(), String> {
+    Feed::find()
+        .limit(1)
+        .all(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("Profile database is not set up (run the migrations against it first): {}", e))
+}
+
+// Open a profile's library database. The default profile is the main database itself.
+pub async fn open_profile_database(home_db: &DatabaseConnection, name: &str) -> Result<DatabaseConnection, String> {
+    if name == DEFAULT_PROFILE {
+        return Ok(home_db.clone());
+    }
+
+    let profile = Profile::find_by_id(name.to_string())
+        .one(home_db)
+        .await
+        .map_err(|e| format!("Failed to fetch profile: {}", e))?
+        .ok_or_else(|| format!("Profile \"{}\" not found", name))?;
+
+    let db = connect_database(profile.database_url)
+        .await
+        .map_err(|e| format!("Failed to connect to profile database: {}", e))?;
+    ensure_library_schema(&db).await?;
+    Ok(db)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_profile_name() {
+        assert_eq!(validate_profile_name("  Work ").unwrap(), "Work");
+        assert!(validate_profile_name("").is_err());
+        assert!(validate_profile_name("Default").is_err());
+        assert!(validate_profile_name(&"x".repeat(65)).is_err());
+    }
+}
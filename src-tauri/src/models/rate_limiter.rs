@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+
+// Requests a domain can make back to back after being idle; further ones are spaced out
+const RATE_LIMIT_BURST: f64 = 1.0;
+// Domains with a full bucket and no request for this long are forgotten
+const IDLE_DOMAIN_TTL: Duration = Duration::from_secs(10 * 60);
+// How often idle domains are looked for
+pub(crate) const EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimiterStats {
+    pub domain: String,
+    pub requests: u64,
+    // Requests that had to wait for a token, and how long they waited in total
+    pub delayed_requests: u64,
+    pub total_wait_ms: u64,
+    // Requests waiting for a token right now
+    pub queued: usize,
+    pub idle_seconds: u64,
+}
+
+#[derive(Debug)]
+struct DomainBucket {
+    // Tokens as of refilled_at; below zero while requests are queued for tokens to come
+    tokens: f64,
+    refilled_at: Instant,
+    // When the latest request was (or, if queued, will be) let through
+    last_request_at: Instant,
+    requests: u64,
+    delayed_requests: u64,
+    total_wait: Duration,
+    queued: usize,
+}
+
+impl DomainBucket {
+    fn new(now: Instant) -> Self {
+        Self {
+            tokens: RATE_LIMIT_BURST,
+            refilled_at: now,
+            last_request_at: now,
+            requests: 0,
+            delayed_requests: 0,
+            total_wait: Duration::ZERO,
+            queued: 0,
+        }
+    }
+
+    // Take a token, refilled at one per interval, and return how long to wait for it.
+    // Requests are let through in the order they took their token.
+    fn take(&mut self, interval: Duration, now: Instant) -> Duration {
+        let refilled = now.saturating_duration_since(self.refilled_at).as_secs_f64() / interval.as_secs_f64();
+        self.tokens = (self.tokens + refilled).min(RATE_LIMIT_BURST) - 1.0;
+        self.refilled_at = now;
+
+        let wait = if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            interval.mul_f64(-self.tokens)
+        };
+        self.requests += 1;
+        if !wait.is_zero() {
+            self.delayed_requests += 1;
+            self.total_wait += wait;
+        }
+        self.last_request_at = now + wait;
+        wait
+    }
+
+    fn is_idle(&self, now: Instant) -> bool {
+        self.queued == 0 && now.saturating_duration_since(self.last_request_at) >= IDLE_DOMAIN_TTL
+    }
+}
+
+#[derive(Debug)]
+struct Buckets {
+    domains: HashMap<String, DomainBucket>,
+    evicted_at: Instant,
+}
+
+// A request waiting for its token, counted in its domain's queue until it's dropped: when
+// the wait is over, or when the waiting future is dropped mid-sleep (e.g. a cancelled fetch)
+struct QueuedRequest<'a> {
+    buckets: &'a Mutex<Buckets>,
+    domain: &'a str,
+}
+
+impl Drop for QueuedRequest<'_> {
+    fn drop(&mut self) {
+        if let Some(bucket) = self.buckets.lock().unwrap().domains.get_mut(self.domain) {
+            bucket.queued = bucket.queued.saturating_sub(1);
+        }
+    }
+}
+
+// Spaces out requests to each domain with a token bucket. Requests over the rate wait their
+// turn rather than failing.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    buckets: Arc<Mutex<Buckets>>,
+    min_delay: Duration,
+}
+
+impl RateLimiter {
+    pub fn new(min_delay: Duration) -> Self {
+        Self {
+            buckets: Arc::new(Mutex::new(Buckets {
+                domains: HashMap::new(),
+                evicted_at: Instant::now(),
+            })),
+            min_delay,
+        }
+    }
+
+    // Wait for the domain's next token. A feed's own delay replaces the fetcher's as the
+    // refill interval. Returns how long the request waited.
+    pub async fn acquire(&self, domain: &str, feed_delay: Option<Duration>) -> Duration {
+        let interval = feed_delay.unwrap_or(self.min_delay);
+        let now = Instant::now();
+
+        let wait = {
+            let mut buckets = self.buckets.lock().unwrap();
+            if now.saturating_duration_since(buckets.evicted_at) >= EVICTION_INTERVAL {
+                buckets.domains.retain(|_, bucket| !bucket.is_idle(now));
+                buckets.evicted_at = now;
+            }
+
+            let bucket = buckets
+                .domains
+                .entry(domain.to_string())
+                .or_insert_with(|| DomainBucket::new(now));
+            if interval.is_zero() {
+                bucket.requests += 1;
+                bucket.last_request_at = now;
+                return Duration::ZERO;
+            }
+            let wait = bucket.take(interval, now);
+            if !wait.is_zero() {
+                bucket.queued += 1;
+            }
+            wait
+        };
+
+        if !wait.is_zero() {
+            let _queued = QueuedRequest { buckets: &self.buckets, domain };
+            sleep(wait).await;
+        }
+        wait
+    }
+
+    pub async fn stats(&self) -> Vec<RateLimiterStats> {
+        let now = Instant::now();
+        let buckets = self.buckets.lock().unwrap();
+        let mut stats: Vec<RateLimiterStats> = buckets
+            .domains
+            .iter()
+            .map(|(domain, bucket)| RateLimiterStats {
+                domain: domain.clone(),
+                requests: bucket.requests,
+                delayed_requests: bucket.delayed_requests,
+                total_wait_ms: bucket.total_wait.as_millis() as u64,
+                queued: bucket.queued,
+                idle_seconds: now.saturating_duration_since(bucket.last_request_at).as_secs(),
+            })
+            .collect();
+        stats.sort_by(|a, b| a.domain.cmp(&b.domain));
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_spaces_requests_by_the_interval() {
+        let start = Instant::now();
+        let interval = Duration::from_millis(100);
+        let mut bucket = DomainBucket::new(start);
+
+        assert_eq!(bucket.take(interval, start), Duration::ZERO);
+        // Two more at once queue behind each other instead of failing
+        assert_eq!(bucket.take(interval, start), interval);
+        assert_eq!(bucket.take(interval, start), interval * 2);
+        // Tokens come back while idle, up to the burst
+        assert_eq!(bucket.take(interval, start + Duration::from_secs(10)), Duration::ZERO);
+
+        assert_eq!(bucket.requests, 4);
+        assert_eq!(bucket.delayed_requests, 2);
+        assert_eq!(bucket.total_wait, interval * 3);
+    }
+
+    #[test]
+    fn test_idle_buckets_are_evictable() {
+        let start = Instant::now();
+        let mut bucket = DomainBucket::new(start);
+        bucket.take(Duration::from_millis(100), start);
+
+        assert!(!bucket.is_idle(start + Duration::from_secs(60)));
+        assert!(bucket.is_idle(start + IDLE_DOMAIN_TTL));
+        bucket.queued = 1;
+        assert!(!bucket.is_idle(start + IDLE_DOMAIN_TTL));
+    }
+
+    #[tokio::test]
+    async fn test_long_waits_queue_instead_of_failing() {
+        let rate_limiter = RateLimiter::new(Duration::from_millis(20));
+        let start = Instant::now();
+        for _ in 0..4 {
+            rate_limiter.acquire("example.com", None).await;
+        }
+        rate_limiter.acquire("other.example", None).await;
+
+        assert!(start.elapsed() >= Duration::from_millis(60));
+        let stats = rate_limiter.stats().await;
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].domain, "example.com");
+        assert_eq!(stats[0].requests, 4);
+        assert_eq!(stats[0].queued, 0);
+        assert_eq!(stats[1].requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_waits_leave_the_queue() {
+        let rate_limiter = RateLimiter::new(Duration::from_secs(60));
+        rate_limiter.acquire("example.com", None).await;
+
+        let waiting = tokio::time::timeout(Duration::from_millis(10), rate_limiter.acquire("example.com", None)).await;
+        assert!(waiting.is_err());
+        assert_eq!(rate_limiter.stats().await[0].queued, 0);
+    }
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+// Global reading preferences. Individual feeds can override mark_read_on_scroll.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReadingConfig {
+    pub mark_read_on_scroll: bool,
+}
+
+// Whether entries of a feed are marked read as they scroll past: the feed's own
+// override if it has one, otherwise the global setting
+pub fn mark_read_on_scroll_enabled(config: &ReadingConfig, feed_override: Option<bool>) -> bool {
+    feed_override.unwrap_or(config.mark_read_on_scroll)
+}
+
+// Average adult silent reading speed
+const WORDS_PER_MINUTE: usize = 230;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadingStats {
+    pub word_count: i32,
+    pub reading_time_minutes: i32,
+}
+
+// The text of an HTML fragment, with tags and character references replaced by spaces
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    let mut in_reference = false;
+
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            '&' if !in_tag => in_reference = true,
+            ';' if in_reference => {
+                in_reference = false;
+                text.push(' ');
+            }
+            c if in_reference && !c.is_ascii_alphanumeric() && c != '#' => {
+                // A bare ampersand, not a reference
+                in_reference = false;
+                text.push(c);
+            }
+            _ if in_tag || in_reference => {}
+            c => text.push(c),
+        }
+    }
+
+    text
+}
+
+// Count the words in an HTML fragment, ignoring tags and character references
+pub fn count_words(html: &str) -> usize {
+    html_to_text(html)
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count()
+}
+
+// Whole minutes, rounded up, so any text takes at least a minute
+pub fn reading_time_minutes(word_count: usize) -> usize {
+    word_count.div_ceil(WORDS_PER_MINUTE)
+}
+
+// Word count and reading time of an entry's content, or its description when it has no
+// content. None when there is no text to read.
+pub fn entry_reading_stats(content: Option<&str>, description: Option<&str>) -> Option<ReadingStats> {
+    let html = content.filter(|content| !content.trim().is_empty()).or(description)?;
+    let word_count = count_words(html);
+    if word_count == 0 {
+        return None;
+    }
+
+    Some(ReadingStats {
+        word_count: word_count as i32,
+        reading_time_minutes: reading_time_minutes(word_count) as i32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_override_wins_over_global_setting() {
+        let enabled = ReadingConfig { mark_read_on_scroll: true };
+        let disabled = ReadingConfig::default();
+
+        assert!(mark_read_on_scroll_enabled(&enabled, None));
+        assert!(!mark_read_on_scroll_enabled(&disabled, None));
+        assert!(!mark_read_on_scroll_enabled(&enabled, Some(false)));
+        assert!(mark_read_on_scroll_enabled(&disabled, Some(true)));
+    }
+
+    #[test]
+    fn test_count_words_ignores_markup() {
+        assert_eq!(count_words("<p>Hello, <b>brave</b>&nbsp;new world</p>"), 4);
+        assert_eq!(count_words("<img src=\"a.png\" alt=\"not counted\"> Tom &amp; Jerry"), 2);
+        assert_eq!(count_words("Fish & chips"), 2);
+        assert_eq!(count_words("<p> </p>"), 0);
+    }
+
+    #[test]
+    fn test_reading_stats_prefer_content_over_description() {
+        let content = "word ".repeat(231);
+
+        let stats = entry_reading_stats(Some(&content), Some("short summary")).unwrap();
+
+        assert_eq!(stats, ReadingStats { word_count: 231, reading_time_minutes: 2 });
+        assert_eq!(entry_reading_stats(Some(" "), Some("short summary")).map(|s| s.reading_time_minutes), Some(1));
+        assert_eq!(entry_reading_stats(None, None), None);
+    }
+}
@@ -0,0 +1,402 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use futures::stream::{self, StreamExt};
+use kuchikiki::traits::TendrilSink;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use url::Url;
+use crate::entities::{prelude::*, *};
+use crate::models::canonical_links::PAGE_TIMEOUT;
+use crate::models::content_extraction::fetch_page_html;
+use crate::models::http_transport::HttpTransport;
+use crate::models::importers::parse_opml;
+use crate::models::url_guard::validate_fetch_url;
+
+// Feeds recommended by get_feed_recommendations when no limit is given
+pub const DEFAULT_RECOMMENDATION_LIMIT: usize = 20;
+
+// Links in entries saved this recently are counted
+const RECOMMENDATION_WINDOW_DAYS: i64 = 90;
+const MAX_SCANNED_ENTRIES: u64 = 5000;
+// Subscribed sites whose home pages are checked for blogrolls and rel="me" links
+const MAX_SCANNED_SITES: usize = 50;
+// Recommended sites whose home pages are checked for the feed they advertise
+const MAX_DISCOVERED_FEEDS: usize = 30;
+const CONCURRENT_PAGE_FETCHES: usize = 6;
+
+// A site needs this score to be recommended: linked from two subscriptions, or listed in a
+// blogroll or as an author's other site once
+const MIN_RECOMMENDATION_SCORE: u32 = 2;
+
+// Sites linked from everywhere that aren't blogs to follow
+const IGNORED_SITES: [&str; 16] = [
+    "amazon.com", "apple.com", "bit.ly", "bsky.app", "facebook.com", "github.com", "google.com", "instagram.com",
+    "linkedin.com", "medium.com", "reddit.com", "t.co", "twitter.com", "wikipedia.org", "x.com", "youtube.com",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecommendationSource {
+    // Listed in a subscribed site's blogroll (an OPML file linked with rel="blogroll")
+    Blogroll,
+    // Linked from a subscribed site's home page with rel="me", i.e. the author's other site
+    RelMe,
+    // Linked from entries of subscribed feeds
+    OutboundLinks,
+}
+
+impl RecommendationSource {
+    fn weight(&self) -> u32 {
+        match self {
+            RecommendationSource::Blogroll => 2,
+            RecommendationSource::RelMe => 2,
+            RecommendationSource::OutboundLinks => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedRecommendation {
+    pub site_url: String,
+    // None when the site doesn't advertise a feed on its home page
+    pub feed_url: Option<String>,
+    pub title: Option<String>,
+    pub score: u32,
+    pub sources: Vec<RecommendationSource>,
+    // Subscribed feeds that link to or list the site
+    pub recommended_by: Vec<String>,
+}
+
+// The links in a page that recommendations are made from, resolved against the page's URL
+#[derive(Debug, Default)]
+pub struct PageLinks {
+    pub anchors: Vec<String>,
+    pub rel_me: Vec<String>,
+    pub blogrolls: Vec<String>,
+    // Advertised feeds, with their titles
+    pub feeds: Vec<(String, Option<String>)>,
+}
+
+pub fn page_links(html: &str, page_url: &str) -> PageLinks {
+    let mut links = PageLinks::default();
+    let Ok(page_url) = Url::parse(page_url) else {
+        return links;
+    };
+    let document = kuchikiki::parse_html().one(html);
+    let Ok(elements) = document.select("a[href], link[href]") else {
+        return links;
+    };
+
+    for element in elements {
+        let attributes = element.attributes.borrow();
+        let Some(href) = attributes.get("href").and_then(|href| page_url.join(href.trim()).ok()) else {
+            continue;
+        };
+        if !matches!(href.scheme(), "http" | "https") {
+            continue;
+        }
+        let href = href.to_string();
+        let rel: Vec<String> = attributes.get("rel").unwrap_or_default().split_ascii_whitespace().map(str::to_ascii_lowercase).collect();
+        let is_feed = attributes
+            .get("type")
+            .is_some_and(|content_type| matches!(content_type.trim().to_ascii_lowercase().as_str(), "application/rss+xml" | "application/atom+xml" | "application/feed+json"));
+
+        if rel.iter().any(|rel| rel == "me") {
+            links.rel_me.push(href);
+        } else if rel.iter().any(|rel| rel == "blogroll") {
+            links.blogrolls.push(href);
+        } else if rel.iter().any(|rel| rel == "alternate") && is_feed {
+            links.feeds.push((href, attributes.get("title").map(str::to_string)));
+        } else if &*element.name.local == "a" {
+            links.anchors.push(href);
+        }
+    }
+    links
+}
+
+// What identifies a site: its host without "www."
+pub fn site_key(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    Some(url.host_str()?.trim_start_matches("www.").to_ascii_lowercase())
+}
+
+fn is_ignored_site(site: &str) -> bool {
+    IGNORED_SITES.iter().any(|ignored| site == *ignored || site.ends_with(&format!(".{}", ignored)))
+}
+
+fn site_origin(url: &str) -> Option<String> {
+    Url::parse(url).ok().map(|url| url.origin().ascii_serialization())
+}
+
+#[derive(Debug, Default)]
+struct Candidate {
+    site_url: String,
+    feed_url: Option<String>,
+    title: Option<String>,
+    score: u32,
+    sources: BTreeSet<RecommendationSource>,
+    recommended_by: BTreeSet<String>,
+    // Which feeds recommended the site which way, so each counts once
+    counted: HashSet<(RecommendationSource, String)>,
+}
+
+// Sites recommended so far, keyed by site_key. Subscribed sites are never added.
+#[derive(Debug, Default)]
+pub struct Candidates {
+    subscribed_sites: HashSet<String>,
+    subscribed_feeds: HashSet<String>,
+    candidates: HashMap<String, Candidate>,
+}
+
+impl Candidates {
+    pub fn new(subscribed_sites: HashSet<String>, subscribed_feeds: HashSet<String>) -> Self {
+        Self {
+            subscribed_sites,
+            subscribed_feeds,
+            candidates: HashMap::new(),
+        }
+    }
+
+    // Count a recommendation of the site at `url` by a subscribed feed. Each feed counts once
+    // per site and source.
+    pub fn add(&mut self, url: &str, feed_url: Option<&str>, title: Option<&str>, source: RecommendationSource, recommended_by: &str) {
+        let (Some(site), Some(site_url)) = (site_key(url), site_origin(url)) else {
+            return;
+        };
+        if is_ignored_site(&site)
+            || self.subscribed_sites.contains(&site)
+            || feed_url.is_some_and(|feed_url| self.subscribed_feeds.contains(feed_url))
+        {
+            return;
+        }
+
+        let candidate = self.candidates.entry(site).or_insert_with(|| Candidate {
+            site_url,
+            ..Default::default()
+        });
+        if candidate.feed_url.is_none() {
+            candidate.feed_url = feed_url.map(str::to_string);
+        }
+        if candidate.title.is_none() {
+            candidate.title = title.map(str::to_string).filter(|title| !title.trim().is_empty());
+        }
+        if candidate.counted.insert((source, recommended_by.to_string())) {
+            candidate.score += source.weight();
+        }
+        candidate.sources.insert(source);
+        candidate.recommended_by.insert(recommended_by.to_string());
+    }
+
+    // Sites that scored enough, best first
+    pub fn ranked(self) -> Vec<FeedRecommendation> {
+        let mut recommendations: Vec<FeedRecommendation> = self
+            .candidates
+            .into_values()
+            .filter(|candidate| candidate.score >= MIN_RECOMMENDATION_SCORE)
+            .map(|candidate| FeedRecommendation {
+                site_url: candidate.site_url,
+                feed_url: candidate.feed_url,
+                title: candidate.title,
+                score: candidate.score,
+                sources: candidate.sources.into_iter().collect(),
+                recommended_by: candidate.recommended_by.into_iter().collect(),
+            })
+            .collect();
+        recommendations.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.site_url.cmp(&b.site_url)));
+        recommendations
+    }
+}
+
+async fn fetch_links(transport: &dyn HttpTransport, url: &str, allow_private_addresses: bool) -> Option<PageLinks> {
+    validate_fetch_url(url, allow_private_addresses).await.ok()?;
+    let html = fetch_page_html(transport, url, None).await.ok()?;
+    Some(page_links(&html, url))
+}
+
+// The feeds listed in a blogroll, as (feed URL, site URL, title)
+async fn fetch_blogroll(transport: &dyn HttpTransport, url: &str, allow_private_addresses: bool) -> Vec<(String, Option<String>, Option<String>)> {
+    if validate_fetch_url(url, allow_private_addresses).await.is_err() {
+        return Vec::new();
+    }
+    let Ok(Ok((_, body))) = tokio::time::timeout(PAGE_TIMEOUT, transport.get(url)).await else {
+        return Vec::new();
+    };
+    parse_opml(&body)
+        .map(|blogroll| blogroll.feeds.into_iter().map(|feed| (feed.url, feed.site_url, feed.title)).collect())
+        .unwrap_or_default()
+}
+
+// Recommend feeds from what subscriptions already point at: sites their entries link to,
+// blogrolls and rel="me" links on their home pages. Everything is worked out here from the
+// subscribed sites' own pages; no recommendation service is asked.
+pub async fn load_feed_recommendations<C: ConnectionTrait>(
+    db: &C,
+    transport: &dyn HttpTransport,
+    allow_private_addresses: bool,
+    limit: usize,
+) -> Result<Vec<FeedRecommendation>, String> {
+    let feeds = Feed::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feeds: {}", e))?;
+    let feed_names: HashMap<i32, String> = feeds
+        .iter()
+        .map(|feed| (feed.id, feed.title.clone().unwrap_or_else(|| feed.url.clone())))
+        .collect();
+
+    let since = chrono::Utc::now().naive_utc() - chrono::Duration::days(RECOMMENDATION_WINDOW_DAYS);
+    let entries: Vec<(i32, String, Option<String>, Option<String>)> = FeedEntry::find()
+        .select_only()
+        .columns([
+            feed_entry::Column::FeedId,
+            feed_entry::Column::Link,
+            feed_entry::Column::Content,
+            feed_entry::Column::Description,
+        ])
+        .filter(feed_entry::Column::CreatedAt.gte(since))
+        .order_by_desc(feed_entry::Column::CreatedAt)
+        .limit(MAX_SCANNED_ENTRIES)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entries: {}", e))?;
+
+    // A feed's site is where its entries link to, which isn't always where the feed is served
+    let mut feed_sites: HashMap<i32, String> = HashMap::new();
+    for (feed_id, link, _, _) in &entries {
+        if let Some(origin) = site_origin(link) {
+            feed_sites.entry(*feed_id).or_insert(origin);
+        }
+    }
+    for feed in &feeds {
+        if let Some(origin) = site_origin(&feed.url) {
+            feed_sites.entry(feed.id).or_insert(origin);
+        }
+    }
+    let subscribed_sites: HashSet<String> = feeds
+        .iter()
+        .filter_map(|feed| site_key(&feed.url))
+        .chain(feed_sites.values().filter_map(|site| site_key(site)))
+        .collect();
+    let mut candidates = Candidates::new(subscribed_sites, feeds.iter().map(|feed| feed.url.clone()).collect());
+
+    for (feed_id, link, content, description) in &entries {
+        let Some(html) = content.as_deref().or(description.as_deref()) else {
+            continue;
+        };
+        let Some(recommended_by) = feed_names.get(feed_id) else {
+            continue;
+        };
+        for anchor in page_links(html, link).anchors {
+            candidates.add(&anchor, None, None, RecommendationSource::OutboundLinks, recommended_by);
+        }
+    }
+
+    let mut sites: Vec<(i32, String)> = feed_sites.into_iter().collect();
+    sites.sort();
+    sites.truncate(MAX_SCANNED_SITES);
+    let home_pages: Vec<(i32, Option<PageLinks>)> = stream::iter(sites)
+        .map(|(feed_id, site)| async move { (feed_id, fetch_links(transport, &site, allow_private_addresses).await) })
+        .buffer_unordered(CONCURRENT_PAGE_FETCHES)
+        .collect()
+        .await;
+    for (feed_id, links) in home_pages {
+        let (Some(links), Some(recommended_by)) = (links, feed_names.get(&feed_id)) else {
+            continue;
+        };
+        for rel_me in &links.rel_me {
+            candidates.add(rel_me, None, None, RecommendationSource::RelMe, recommended_by);
+        }
+        for blogroll in &links.blogrolls {
+            for (feed_url, site_url, title) in fetch_blogroll(transport, blogroll, allow_private_addresses).await {
+                let site_url = site_url.unwrap_or_else(|| feed_url.clone());
+                candidates.add(&site_url, Some(&feed_url), title.as_deref(), RecommendationSource::Blogroll, recommended_by);
+            }
+        }
+    }
+
+    let mut recommendations = candidates.ranked();
+    recommendations.truncate(limit);
+
+    // Find the feed each recommended site advertises, when it wasn't listed with one
+    let undiscovered: Vec<(usize, String)> = recommendations
+        .iter()
+        .enumerate()
+        .filter(|(_, recommendation)| recommendation.feed_url.is_none())
+        .map(|(index, recommendation)| (index, recommendation.site_url.clone()))
+        .take(MAX_DISCOVERED_FEEDS)
+        .collect();
+    let discovered: Vec<(usize, Option<PageLinks>)> = stream::iter(undiscovered)
+        .map(|(index, site)| async move { (index, fetch_links(transport, &site, allow_private_addresses).await) })
+        .buffer_unordered(CONCURRENT_PAGE_FETCHES)
+        .collect()
+        .await;
+    for (index, links) in discovered {
+        if let Some((feed_url, title)) = links.and_then(|links| links.feeds.into_iter().next()) {
+            let recommendation = &mut recommendations[index];
+            recommendation.feed_url = Some(feed_url);
+            if recommendation.title.is_none() {
+                recommendation.title = title;
+            }
+        }
+    }
+
+    let subscribed_feeds: HashSet<&str> = feeds.iter().map(|feed| feed.url.as_str()).collect();
+    recommendations.retain(|recommendation| !recommendation.feed_url.as_deref().is_some_and(|feed_url| subscribed_feeds.contains(feed_url)));
+    Ok(recommendations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_links_are_sorted_by_rel() {
+        let html = r#"<html><head>
+            <link rel="alternate" type="application/rss+xml" title="Posts" href="/feed.xml">
+            <link rel="blogroll" type="text/xml" href="https://example.com/blogroll.opml">
+            <link rel="stylesheet" href="/style.css">
+        </head><body>
+            <a rel="me" href="https://social.example/@author">Me</a>
+            <a href="/about">About</a>
+            <a href="mailto:author@example.com">Mail</a>
+            <a href="https://friend.example.net/post">A friend</a>
+        </body></html>"#;
+
+        let links = page_links(html, "https://example.com/");
+
+        assert_eq!(links.feeds, vec![("https://example.com/feed.xml".to_string(), Some("Posts".to_string()))]);
+        assert_eq!(links.blogrolls, vec!["https://example.com/blogroll.opml"]);
+        assert_eq!(links.rel_me, vec!["https://social.example/@author"]);
+        assert_eq!(links.anchors, vec!["https://example.com/about", "https://friend.example.net/post"]);
+    }
+
+    #[test]
+    fn test_candidates_need_two_recommendations() {
+        let mut candidates = Candidates::new(
+            HashSet::from(["example.com".to_string()]),
+            HashSet::from(["https://subscribed.example.org/feed.xml".to_string()]),
+        );
+
+        // Linked from two subscriptions, however often
+        candidates.add("https://friend.example.net/a", None, None, RecommendationSource::OutboundLinks, "Blog A");
+        candidates.add("https://www.friend.example.net/b", None, None, RecommendationSource::OutboundLinks, "Blog A");
+        candidates.add("https://friend.example.net/c", None, None, RecommendationSource::OutboundLinks, "Blog B");
+        // Linked once
+        candidates.add("https://once.example.net/", None, None, RecommendationSource::OutboundLinks, "Blog A");
+        // Listed in a blogroll
+        candidates.add("https://listed.example.net", Some("https://listed.example.net/rss"), Some("Listed"), RecommendationSource::Blogroll, "Blog B");
+        // Already subscribed, or never worth recommending
+        candidates.add("https://example.com/post", None, None, RecommendationSource::RelMe, "Blog A");
+        candidates.add("https://subscribed.example.org/", Some("https://subscribed.example.org/feed.xml"), None, RecommendationSource::Blogroll, "Blog A");
+        candidates.add("https://en.wikipedia.org/wiki/RSS", None, None, RecommendationSource::OutboundLinks, "Blog A");
+        candidates.add("https://en.wikipedia.org/wiki/Atom", None, None, RecommendationSource::OutboundLinks, "Blog B");
+
+        let ranked = candidates.ranked();
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].site_url, "https://friend.example.net");
+        assert_eq!(ranked[0].recommended_by, vec!["Blog A", "Blog B"]);
+        assert_eq!(ranked[1].site_url, "https://listed.example.net");
+        assert_eq!(ranked[1].feed_url.as_deref(), Some("https://listed.example.net/rss"));
+        assert_eq!(ranked[1].sources, vec![RecommendationSource::Blogroll]);
+    }
+}
@@ -0,0 +1,215 @@
+use chrono::{NaiveDateTime, Utc};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast;
+use crate::entities::{prelude::*, *};
+use crate::models::responses::{RefreshError, RefreshSummary};
+use crate::models::state::AppState;
+
+// Refreshes listed by get_refresh_history when no limit is given
+pub const DEFAULT_REFRESH_HISTORY_LIMIT: u64 = 50;
+
+// Only the newest refreshes of the last month are kept. The scheduler adds one for every tick
+// that found feeds due, so there can be a few hundred a day.
+const REFRESH_HISTORY_MAX_ROWS: u64 = 10_000;
+const REFRESH_HISTORY_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefreshSource {
+    // Started from the app, e.g. refresh all
+    Manual,
+    // Feeds the scheduler refreshed as they came due
+    Scheduled,
+}
+
+impl RefreshSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RefreshSource::Manual => "manual",
+            RefreshSource::Scheduled => "scheduled",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshHistoryEntry {
+    pub id: i32,
+    pub source: String,
+    pub finished_at: NaiveDateTime,
+    pub total_processed: usize,
+    pub successful_count: usize,
+    pub failed_count: usize,
+    pub entries_added: usize,
+    pub duration_seconds: u64,
+    pub errors: Vec<RefreshError>,
+}
+
+impl From<refresh_history::Model> for RefreshHistoryEntry {
+    fn from(model: refresh_history::Model) -> Self {
+        RefreshHistoryEntry {
+            id: model.id,
+            source: model.source,
+            finished_at: model.finished_at,
+            total_processed: model.total_processed.max(0) as usize,
+            successful_count: model.successful_count.max(0) as usize,
+            failed_count: model.failed_count.max(0) as usize,
+            entries_added: model.entries_added.max(0) as usize,
+            duration_seconds: model.duration_seconds.max(0) as u64,
+            errors: serde_json::from_str(&model.errors).unwrap_or_default(),
+        }
+    }
+}
+
+fn history_row(summary: &RefreshSummary, source: RefreshSource) -> Result<refresh_history::ActiveModel, String> {
+    let finished_at = chrono::DateTime::parse_from_rfc3339(&summary.timestamp)
+        .map(|timestamp| timestamp.naive_utc())
+        .unwrap_or_else(|_| Utc::now().naive_utc());
+    let entries_added: usize = summary.feeds_updated.iter().map(|status| status.entries_added).sum();
+    let errors = serde_json::to_string(&summary.errors).map_err(|e| format!("Failed to serialize refresh errors: {}", e))?;
+
+    Ok(refresh_history::ActiveModel {
+        source: ActiveValue::Set(source.as_str().to_string()),
+        finished_at: ActiveValue::Set(finished_at),
+        total_processed: ActiveValue::Set(summary.total_processed as i32),
+        successful_count: ActiveValue::Set(summary.successful_count as i32),
+        failed_count: ActiveValue::Set(summary.failed_count as i32),
+        entries_added: ActiveValue::Set(entries_added as i32),
+        duration_seconds: ActiveValue::Set(summary.duration_seconds as i64),
+        errors: ActiveValue::Set(errors),
+        ..Default::default()
+    })
+}
+
+// Add a finished refresh to the history, dropping whatever has aged out of it
+pub async fn record_refresh_summary<C: ConnectionTrait>(db: &C, summary: &RefreshSummary, source: RefreshSource) -> Result<(), String> {
+    RefreshHistory::insert(history_row(summary, source)?)
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to record refresh history: {}", e))?;
+    prune_refresh_history(db, Utc::now().naive_utc()).await?;
+    Ok(())
+}
+
+// The most recent refreshes, newest first
+pub async fn load_refresh_history<C: ConnectionTrait>(db: &C, limit: u64) -> Result<Vec<RefreshHistoryEntry>, String> {
+    Ok(RefreshHistory::find()
+        .order_by_desc(refresh_history::Column::FinishedAt)
+        .order_by_desc(refresh_history::Column::Id)
+        .limit(limit)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch refresh history: {}", e))?
+        .into_iter()
+        .map(RefreshHistoryEntry::from)
+        .collect())
+}
+
+async fn prune_refresh_history<C: ConnectionTrait>(db: &C, now: NaiveDateTime) -> Result<u64, String> {
+    let mut pruned = RefreshHistory::delete_many()
+        .filter(refresh_history::Column::FinishedAt.lt(now - chrono::Duration::days(REFRESH_HISTORY_DAYS)))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to prune refresh history: {}", e))?
+        .rows_affected;
+
+    // The newest row past the cap, and everything older than it
+    let oldest_dropped: Option<i32> = RefreshHistory::find()
+        .select_only()
+        .column(refresh_history::Column::Id)
+        .order_by_desc(refresh_history::Column::Id)
+        .offset(REFRESH_HISTORY_MAX_ROWS)
+        .into_tuple()
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to prune refresh history: {}", e))?;
+    if let Some(id) = oldest_dropped {
+        pruned += RefreshHistory::delete_many()
+            .filter(refresh_history::Column::Id.lte(id))
+            .exec(db)
+            .await
+            .map_err(|e| format!("Failed to prune refresh history: {}", e))?
+            .rows_affected;
+    }
+    Ok(pruned)
+}
+
+// Record every refresh the fetcher reports finishing, for the life of the app
+pub async fn run_refresh_history_recorder(app: AppHandle, mut summaries: broadcast::Receiver<RefreshSummary>) {
+    loop {
+        let summary = match summaries.recv().await {
+            Ok(summary) => summary,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                eprintln!("⚠️ {} refreshes were left out of the refresh history", skipped);
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+
+        let db = app.state::<AppState>().db().await;
+        if let Err(e) = record_refresh_summary(&db, &summary, RefreshSource::Manual).await {
+            eprintln!("❌ {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::responses::FeedRefreshStatus;
+
+    fn status(entries_added: usize, error: Option<RefreshError>) -> FeedRefreshStatus {
+        FeedRefreshStatus {
+            feed_id: 1,
+            feed_url: "https://example.com/feed.xml".to_string(),
+            feed_title: None,
+            status: if error.is_some() { "failed" } else { "success" }.to_string(),
+            entries_added,
+            entries_updated: 0,
+            last_fetched_at: String::new(),
+            error,
+        }
+    }
+
+    #[test]
+    fn test_history_rows_round_trip() {
+        let error = RefreshError {
+            feed_url: "https://example.com/broken.xml".to_string(),
+            feed_title: Some("Broken".to_string()),
+            error_message: "Request timeout".to_string(),
+            error_type: "timeout".to_string(),
+            retry_count: 3,
+            timestamp: "2024-06-01T02:00:00+00:00".to_string(),
+        };
+        let summary = RefreshSummary {
+            timestamp: "2024-06-01T02:00:05+00:00".to_string(),
+            total_processed: 3,
+            successful_count: 2,
+            failed_count: 1,
+            duration_seconds: 5,
+            feeds_updated: vec![status(4, None), status(2, None), status(0, Some(error.clone()))],
+            errors: vec![error],
+        };
+
+        let row = history_row(&summary, RefreshSource::Scheduled).unwrap();
+        let entry = RefreshHistoryEntry::from(refresh_history::Model {
+            id: 1,
+            source: row.source.unwrap(),
+            finished_at: row.finished_at.unwrap(),
+            total_processed: row.total_processed.unwrap(),
+            successful_count: row.successful_count.unwrap(),
+            failed_count: row.failed_count.unwrap(),
+            entries_added: row.entries_added.unwrap(),
+            duration_seconds: row.duration_seconds.unwrap(),
+            errors: row.errors.unwrap(),
+        });
+
+        assert_eq!(entry.source, "scheduled");
+        assert_eq!(entry.finished_at.to_string(), "2024-06-01 02:00:05");
+        assert_eq!(entry.entries_added, 6);
+        assert_eq!(entry.failed_count, 1);
+        assert_eq!(entry.errors.len(), 1);
+        assert_eq!(entry.errors[0].error_type, "timeout");
+    }
+}
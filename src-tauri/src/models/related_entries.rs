@@ -0,0 +1,173 @@
+use std::collections::HashSet;
+use chrono::NaiveDateTime;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use crate::entities::{prelude::*, *};
+use crate::models::story_clusters::title_words;
+
+// Related entries suggested by get_related_entries when no limit is given
+pub const DEFAULT_RELATED_ENTRIES_LIMIT: u64 = 10;
+
+// Entries saved this long before or after the entry are considered
+const RELATED_WINDOW_DAYS: i64 = 90;
+// Cap on how many entries around the entry are scored
+const MAX_RELATED_CANDIDATES: u64 = 2000;
+
+// Weight of a post from the same feed published at the same time. It halves about every
+// five days apart, so a feed's neighbouring posts are suggested without any shared words.
+const SAME_FEED_WEIGHT: f64 = 0.3;
+const SAME_FEED_DECAY_DAYS: f64 = 7.0;
+// Score a candidate needs to be suggested
+const MIN_RELATED_SCORE: f64 = 0.2;
+
+// Words too common in titles to say two entries are about the same thing
+const STOP_WORDS: [&str; 32] = [
+    "a", "about", "an", "and", "are", "as", "at", "be", "by", "for", "from", "how", "in", "is", "it", "its",
+    "new", "of", "on", "or", "our", "that", "the", "this", "to", "we", "what", "why", "with", "you", "your", "vs",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedEntry {
+    pub id: i32,
+    pub feed_id: i32,
+    pub title: String,
+    pub link: String,
+    pub published_at: Option<String>,
+    pub is_read: bool,
+    pub score: f64,
+    // Title words it has in common with the entry, for explaining the suggestion
+    pub shared_keywords: Vec<String>,
+}
+
+// A title's distinctive words: no stop words, numbers or single letters
+pub fn title_keywords(title: &str) -> HashSet<String> {
+    title_words(title)
+        .into_iter()
+        .filter(|word| word.chars().count() > 1 && !word.chars().all(|c| c.is_ascii_digit()) && !STOP_WORDS.contains(&word.as_str()))
+        .collect()
+}
+
+fn entry_date(entry: &feed_entry::Model) -> NaiveDateTime {
+    entry.published_at.unwrap_or(entry.created_at)
+}
+
+// How related a candidate is to the entry: the share of the entry's keywords in the
+// candidate's title, plus a bonus for a post from the same feed published around the same
+// time. A cheap stand-in for comparing embeddings; only this needs replacing to upgrade.
+pub fn related_score(
+    entry: &feed_entry::Model,
+    keywords: &HashSet<String>,
+    candidate: &feed_entry::Model,
+) -> (f64, Vec<String>) {
+    let mut shared_keywords: Vec<String> = keywords.intersection(&title_keywords(&candidate.title)).cloned().collect();
+    shared_keywords.sort();
+    let overlap = if keywords.is_empty() {
+        0.0
+    } else {
+        shared_keywords.len() as f64 / keywords.len() as f64
+    };
+
+    let proximity = if candidate.feed_id == entry.feed_id {
+        let days_apart = (entry_date(entry) - entry_date(candidate)).num_minutes().abs() as f64 / (24.0 * 60.0);
+        SAME_FEED_WEIGHT * (-days_apart / SAME_FEED_DECAY_DAYS).exp()
+    } else {
+        0.0
+    };
+
+    (overlap + proximity, shared_keywords)
+}
+
+// Entries to read after this one, best first: same-story duplicates and hidden entries are
+// left out
+pub async fn load_related_entries<C: ConnectionTrait>(db: &C, entry_id: i32, limit: u64) -> Result<Vec<RelatedEntry>, String> {
+    let entry = FeedEntry::find_by_id(entry_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entry: {}", e))?
+        .ok_or("Feed entry not found")?;
+    let keywords = title_keywords(&entry.title);
+
+    let window = chrono::Duration::days(RELATED_WINDOW_DAYS);
+    let mut candidates = FeedEntry::find()
+        .filter(feed_entry::Column::Id.ne(entry.id))
+        .filter(feed_entry::Column::IsHidden.eq(false))
+        .filter(feed_entry::Column::CreatedAt.between(entry.created_at - window, entry.created_at + window));
+    if let Some(story_cluster_id) = entry.story_cluster_id {
+        candidates = candidates.filter(
+            Condition::any()
+                .add(feed_entry::Column::StoryClusterId.is_null())
+                .add(feed_entry::Column::StoryClusterId.ne(story_cluster_id)),
+        );
+    }
+    let candidates = candidates
+        .order_by_desc(feed_entry::Column::CreatedAt)
+        .limit(MAX_RELATED_CANDIDATES)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entries: {}", e))?;
+
+    let mut related: Vec<RelatedEntry> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let (score, shared_keywords) = related_score(&entry, &keywords, &candidate);
+            (score >= MIN_RELATED_SCORE).then(|| RelatedEntry {
+                id: candidate.id,
+                feed_id: candidate.feed_id,
+                title: candidate.title,
+                link: candidate.link,
+                published_at: candidate.published_at.map(|dt| dt.to_string()),
+                is_read: candidate.is_read,
+                score,
+                shared_keywords,
+            })
+        })
+        .collect();
+    related.sort_by(|a, b| b.score.total_cmp(&a.score).then(b.id.cmp(&a.id)));
+    related.truncate(limit as usize);
+    Ok(related)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::fixtures::{self, at};
+
+    fn entry(id: i32, feed_id: i32, title: &str, published_at: &str) -> feed_entry::Model {
+        feed_entry::Model {
+            title: title.to_string(),
+            published_at: Some(at(published_at)),
+            created_at: at(published_at),
+            updated_at: at(published_at),
+            guid: id.to_string(),
+            ..fixtures::feed_entry(id, feed_id)
+        }
+    }
+
+    #[test]
+    fn test_keywords_skip_common_words() {
+        let keywords = title_keywords("Why the Rust 2024 edition is a big deal");
+        let mut keywords: Vec<_> = keywords.into_iter().collect();
+        keywords.sort();
+        assert_eq!(keywords, vec!["big", "deal", "edition", "rust"]);
+    }
+
+    #[test]
+    fn test_score_combines_keywords_and_same_feed_proximity() {
+        let current = entry(1, 1, "Async closures in Rust", "2024-06-10 09:00");
+        let keywords = title_keywords(&current.title);
+
+        let (score, shared) = related_score(&current, &keywords, &entry(2, 2, "Rust async closures are stable", "2024-03-01 09:00"));
+        assert_eq!(shared, vec!["async", "closures", "rust"]);
+        assert!((score - 1.0).abs() < 1e-9);
+
+        // A feed's neighbouring post is related by proximity alone, until it's too far apart
+        let (score, shared) = related_score(&current, &keywords, &entry(3, 1, "Weekly links", "2024-06-09 09:00"));
+        assert!(shared.is_empty());
+        assert!(score >= MIN_RELATED_SCORE);
+        let (score, _) = related_score(&current, &keywords, &entry(4, 1, "Weekly links", "2024-05-10 09:00"));
+        assert!(score < MIN_RELATED_SCORE);
+
+        let (score, _) = related_score(&current, &keywords, &entry(5, 2, "Gardening tips", "2024-06-10 09:00"));
+        assert_eq!(score, 0.0);
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{watch, Mutex};
+use crate::entities::{prelude::*, *};
+use crate::models::entry_query::build_entry_query;
+use crate::models::exporters::build_atom_feed;
+use crate::models::requests::EntryQueryRequest;
+use crate::models::settings::load_republish_config;
+use crate::models::state::AppState;
+
+// Largest request head the feed server reads; feed readers send a few hundred bytes
+const MAX_REQUEST_HEAD_BYTES: usize = 8 * 1024;
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Republishing starred or tagged entries as Atom feeds, and the embedded server that serves them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepublishConfig {
+    // Serve /starred.atom and /tags/<tag id>.atom over HTTP
+    pub serve: bool,
+    pub port: u16,
+    // Listen on every network interface so others on the network can subscribe,
+    // rather than only on this computer
+    pub allow_remote: bool,
+    pub max_entries: u64,
+}
+
+impl Default for RepublishConfig {
+    fn default() -> Self {
+        Self {
+            serve: false,
+            port: 8787,
+            allow_remote: false,
+            max_entries: 50,
+        }
+    }
+}
+
+impl RepublishConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.port == 0 {
+            return Err("Feed server port must be greater than 0".to_string());
+        }
+        if self.max_entries == 0 {
+            return Err("A republished feed needs at least one entry".to_string());
+        }
+        Ok(())
+    }
+
+    fn bind_address(&self) -> SocketAddr {
+        let ip = if self.allow_remote { Ipv4Addr::UNSPECIFIED } else { Ipv4Addr::LOCALHOST };
+        SocketAddr::from((ip, self.port))
+    }
+}
+
+// Render the starred entries, or the entries with a tag, as an Atom feed along with the
+// number of entries in it. None if the tag doesn't exist.
+pub async fn build_republished_feed<C: ConnectionTrait>(
+    db: &C,
+    tag_id: Option<i32>,
+    max_entries: u64,
+    self_url: Option<&str>,
+) -> Result<Option<(String, usize)>, String> {
+    let (id, title, request) = match tag_id {
+        Some(tag_id) => {
+            let Some(tag) = Tag::find_by_id(tag_id)
+                .one(db)
+                .await
+                .map_err(|e| format!("Failed to fetch tag: {}", e))?
+            else {
+                return Ok(None);
+            };
+            let request = EntryQueryRequest {
+                tag_id: Some(tag_id),
+                limit: Some(max_entries),
+                ..Default::default()
+            };
+            (format!("urn:reader:tag:{}", tag_id), format!("Entries tagged {}", tag.name), request)
+        }
+        None => {
+            let request = EntryQueryRequest {
+                is_starred: Some(true),
+                limit: Some(max_entries),
+                ..Default::default()
+            };
+            ("urn:reader:starred".to_string(), "Starred entries".to_string(), request)
+        }
+    };
+
+    let now = chrono::Utc::now().naive_utc();
+    let entries = build_entry_query(&request, now)?
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to query feed entries: {}", e))?;
+    let feeds: HashMap<i32, feed::Model> = Feed::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feeds: {}", e))?
+        .into_iter()
+        .map(|feed| (feed.id, feed))
+        .collect();
+
+    Ok(Some((build_atom_feed(&id, &title, self_url, &entries, &feeds, now), entries.len())))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedRoute {
+    Starred,
+    Tag(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteError {
+    NotFound,
+    MethodNotAllowed,
+    BadRequest,
+}
+
+// Which feed a request line ("GET /starred.atom HTTP/1.1") asks for
+pub fn route_request(request_line: &str) -> Result<FeedRoute, RouteError> {
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target), Some(_version)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(RouteError::BadRequest);
+    };
+    if method != "GET" && method != "HEAD" {
+        return Err(RouteError::MethodNotAllowed);
+    }
+
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+    if path == "/starred.atom" {
+        return Ok(FeedRoute::Starred);
+    }
+    path.strip_prefix("/tags/")
+        .and_then(|rest| rest.strip_suffix(".atom"))
+        .and_then(|tag_id| tag_id.parse().ok())
+        .map(FeedRoute::Tag)
+        .ok_or(RouteError::NotFound)
+}
+
+fn http_response(status: &str, content_type: &str, body: &str, include_body: bool) -> Vec<u8> {
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )
+    .into_bytes();
+    if include_body {
+        response.extend_from_slice(body.as_bytes());
+    }
+    response
+}
+
+async fn read_request_head(stream: &mut TcpStream) -> Option<String> {
+    let mut head = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() >= MAX_REQUEST_HEAD_BYTES {
+            return None;
+        }
+        let read = stream.read(&mut buffer).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buffer[..read]);
+    }
+    String::from_utf8(head).ok()
+}
+
+async fn handle_connection(app: AppHandle, mut stream: TcpStream) {
+    let head = tokio::time::timeout(REQUEST_READ_TIMEOUT, read_request_head(&mut stream))
+        .await
+        .ok()
+        .flatten();
+    let request_line = head.as_deref().and_then(|head| head.lines().next()).unwrap_or_default();
+    let include_body = !request_line.starts_with("HEAD ");
+    let host = head
+        .as_deref()
+        .into_iter()
+        .flat_map(str::lines)
+        .find_map(|line| line.split_once(':').filter(|(name, _)| name.eq_ignore_ascii_case("host")))
+        .map(|(_, value)| value.trim().to_string());
+
+    let response = match route_request(request_line) {
+        Ok(route) => {
+            let state = app.state::<AppState>();
+            let db = state.db().await;
+            let config = load_republish_config(&db).await;
+            let (tag_id, path) = match route {
+                FeedRoute::Starred => (None, "/starred.atom".to_string()),
+                FeedRoute::Tag(tag_id) => (Some(tag_id), format!("/tags/{}.atom", tag_id)),
+            };
+            let self_url = host.map(|host| format!("http://{}{}", host, path));
+            match build_republished_feed(&db, tag_id, config.max_entries, self_url.as_deref()).await {
+                Ok(Some((atom, _))) => http_response("200 OK", "application/atom+xml; charset=utf-8", &atom, include_body),
+                Ok(None) => http_response("404 Not Found", "text/plain", "Not found", include_body),
+                Err(e) => {
+                    eprintln!("❌ Failed to serve republished feed: {}", e);
+                    http_response("500 Internal Server Error", "text/plain", "Internal error", include_body)
+                }
+            }
+        }
+        Err(RouteError::NotFound) => http_response("404 Not Found", "text/plain", "Not found", include_body),
+        Err(RouteError::MethodNotAllowed) => http_response("405 Method Not Allowed", "text/plain", "Method not allowed", true),
+        Err(RouteError::BadRequest) => http_response("400 Bad Request", "text/plain", "Bad request", true),
+    };
+
+    let _ = stream.write_all(&response).await;
+    let _ = stream.shutdown().await;
+}
+
+async fn accept_connections(app: AppHandle, listener: TcpListener, mut stop: watch::Receiver<bool>) {
+    let mut shutdown = app.state::<AppState>().shutdown_signal.subscribe();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_connection(app.clone(), stream));
+                }
+                Err(e) => eprintln!("❌ Feed server failed to accept a connection: {}", e),
+            },
+            _ = stop.wait_for(|stopping| *stopping) => break,
+            _ = shutdown.wait_for(|stopping| *stopping) => break,
+        }
+    }
+}
+
+// The embedded HTTP server for republished feeds. Restarted whenever its settings change.
+#[derive(Default)]
+pub struct FeedServer {
+    stop: Mutex<Option<watch::Sender<bool>>>,
+}
+
+impl FeedServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Stop the running server, if any, and start one with the new settings if serving is on
+    pub async fn apply(&self, app: &AppHandle, config: &RepublishConfig) -> Result<(), String> {
+        let mut stop = self.stop.lock().await;
+        if let Some(running) = stop.take() {
+            let _ = running.send(true);
+        }
+        if !config.serve {
+            return Ok(());
+        }
+
+        let address = config.bind_address();
+        let listener = TcpListener::bind(address)
+            .await
+            .map_err(|e| format!("Failed to start feed server on {}: {}", address, e))?;
+        let (stop_sender, stop_receiver) = watch::channel(false);
+        tauri::async_runtime::spawn(accept_connections(app.clone(), listener, stop_receiver));
+        *stop = Some(stop_sender);
+
+        println!("📡 Serving republished feeds on http://{}", address);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes() {
+        assert_eq!(route_request("GET /starred.atom HTTP/1.1"), Ok(FeedRoute::Starred));
+        assert_eq!(route_request("HEAD /starred.atom?v=2 HTTP/1.1"), Ok(FeedRoute::Starred));
+        assert_eq!(route_request("GET /tags/12.atom HTTP/1.1"), Ok(FeedRoute::Tag(12)));
+        assert_eq!(route_request("GET /tags/rust.atom HTTP/1.1"), Err(RouteError::NotFound));
+        assert_eq!(route_request("GET / HTTP/1.1"), Err(RouteError::NotFound));
+        assert_eq!(route_request("POST /starred.atom HTTP/1.1"), Err(RouteError::MethodNotAllowed));
+        assert_eq!(route_request(""), Err(RouteError::BadRequest));
+    }
+
+    #[test]
+    fn test_server_listens_locally_unless_remote_is_allowed() {
+        let config = RepublishConfig::default();
+        assert_eq!(config.bind_address().to_string(), "127.0.0.1:8787");
+        let remote = RepublishConfig { allow_remote: true, ..config };
+        assert_eq!(remote.bind_address().to_string(), "0.0.0.0:8787");
+    }
+}
@@ -1,10 +1,13 @@
 use serde::{Deserialize, Serialize};
+use crate::models::feed_sources::FeedSourceType;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateFeedRequest {
     pub url: String,
     pub title: Option<String>,
     pub description: Option<String>,
+    pub folder_id: Option<i32>,
+    pub source_type: Option<FeedSourceType>, // defaults to HTTP
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -13,6 +16,13 @@ pub struct UpdateFeedRequest {
     pub url: Option<String>,
     pub title: Option<String>,
     pub description: Option<String>,
+    pub image_policy: Option<String>, // see ImagePolicy; applies to entries fetched afterwards
+    pub auto_title: Option<bool>, // setting a title or description turns this off unless given
+    pub resolve_canonical_links: Option<bool>, // applies to entries saved afterwards
+    pub fetch_full_content: Option<bool>, // applies to entries saved afterwards
+    pub extraction_rule: Option<String>, // CSS selector or XPath; empty goes back to the heuristic
+    pub tls_ca_certificate: Option<String>, // PEM; empty removes it
+    pub tls_pinned_certificate: Option<String>, // PEM or SHA-256 fingerprint; empty removes the pin
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -41,4 +51,201 @@ pub struct UpdateFeedEntryRequest {
     pub content: Option<String>,
     pub is_read: Option<bool>,
     pub is_starred: Option<bool>,
-} 
\ No newline at end of file
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateFolderRequest {
+    pub name: String,
+    pub parent_id: Option<i32>, // None creates a top-level folder
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateFolderRequest {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MediaEntryQueryRequest {
+    pub feed_id: Option<i32>,
+    pub min_duration_seconds: Option<i32>,
+    pub max_duration_seconds: Option<i32>,
+    pub sort: Option<String>, // "newest" (default), "shortest", "longest"
+    pub limit: Option<u64>,
+}
+
+// Filters for the entry list. Every filter is optional and they all combine with AND,
+// so each sidebar view (folder, feed, tag, unread, starred, snoozed, ...) is a single query.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntryQueryRequest {
+    pub folder_id: Option<i32>,
+    pub feed_id: Option<i32>,
+    pub tag_id: Option<i32>,
+    pub is_read: Option<bool>,
+    pub is_starred: Option<bool>,
+    pub viewed: Option<bool>, // true: only entries opened at least once, false: entries never opened
+    pub snoozed: Option<bool>, // true: only snoozed entries, false: hide snoozed entries
+    pub hidden: Option<bool>, // true: only entries hidden by domain rules or mutes; hidden entries are left out otherwise
+    pub view: Option<EntryView>, // "unread" lists unread entries unless is_read is set; None follows the feed's default view
+    pub published_after: Option<String>, // ISO 8601 string
+    pub published_before: Option<String>, // ISO 8601 string
+    pub min_reading_minutes: Option<i32>,
+    pub max_reading_minutes: Option<i32>,
+    pub sort: Option<String>, // "newest" (default), "oldest", "ranked" (favors feeds whose entries get opened); None follows the feed's preference
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+// Which entries a view lists before any read filter is picked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryView {
+    #[default]
+    All,
+    Unread,
+}
+
+// How a list of tags narrows a smart view
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagMatch {
+    // Entries with at least one of the tags
+    #[default]
+    Any,
+    // Entries with every one of the tags
+    All,
+}
+
+// Filters for smart views: everything EntryQueryRequest takes, plus lists of folders, feeds
+// and tags and a text query. An entry matches a list when it matches any of its values
+// (or all of them, for tags with TagMatch::All). Everything still compiles to one query.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdvancedEntryQueryRequest {
+    #[serde(flatten)]
+    pub base: EntryQueryRequest,
+    #[serde(default)]
+    pub folder_ids: Vec<i32>,
+    #[serde(default)]
+    pub feed_ids: Vec<i32>,
+    #[serde(default)]
+    pub tag_ids: Vec<i32>,
+    #[serde(default)]
+    pub tag_match: TagMatch,
+    pub text: Option<String>, // case-insensitive match on title or description
+}
+
+// Server-side filter for bulk entry operations. Filters combine with AND.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BulkEntryFilter {
+    pub feed_id: Option<i32>,
+    pub is_read: Option<bool>,
+    pub older_than_days: Option<i64>, // published (or saved, if undated) more than this many days ago
+    pub search: Option<String>, // case-insensitive match on title or description
+}
+
+impl BulkEntryFilter {
+    pub fn is_empty(&self) -> bool {
+        self.feed_id.is_none()
+            && self.is_read.is_none()
+            && self.older_than_days.is_none()
+            && self.search.as_deref().is_none_or(|search| search.trim().is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulkEntryAction {
+    Delete,
+    MarkRead { is_read: bool },
+    Star { is_starred: bool },
+    Tag { tag_id: i32 },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateTagRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateAnnotationRequest {
+    pub entry_id: i32,
+    pub note: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateAnnotationRequest {
+    pub id: i32,
+    pub note: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateReadProgressRequest {
+    pub scrolled_past_entry_ids: Vec<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavePlaybackPositionRequest {
+    pub entry_id: i32,
+    pub position_seconds: i32,
+    pub duration_seconds: Option<i32>, // as reported by the player, which may differ from the feed's
+    pub finished: Option<bool>, // worked out from the position when not given
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateProfileRequest {
+    pub name: String,
+    pub database_url: String, // must already have the migrations applied
+}
+
+// Time range for a digest; defaults to the day before `until`, which defaults to now
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DigestRangeRequest {
+    pub since: Option<String>, // ISO 8601 string
+    pub until: Option<String>, // ISO 8601 string
+}
+
+// A webhook's filters combine with AND; leave one unset to not filter on it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub name: String,
+    pub url: String,
+    pub feed_id: Option<i32>,
+    pub tag_id: Option<i32>,
+    pub keyword: Option<String>, // matched case-insensitively in the title or description
+    pub enabled: Option<bool>, // defaults to true
+}
+
+// Replaces every field of the webhook, so unset filters are cleared
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateWebhookRequest {
+    pub id: i32,
+    pub name: String,
+    pub url: String,
+    pub feed_id: Option<i32>,
+    pub tag_id: Option<i32>,
+    pub keyword: Option<String>,
+    pub enabled: bool,
+}
+
+// An alert rule's scope filters combine with AND; leave both unset to watch every feed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateAlertRuleRequest {
+    pub name: String,
+    pub pattern: String, // matched case-insensitively in the title or description
+    pub is_regex: Option<bool>, // defaults to false, a plain keyword
+    pub feed_id: Option<i32>,
+    pub folder_id: Option<i32>, // includes its subfolders
+    pub enabled: Option<bool>, // defaults to true
+}
+
+// Replaces every field of the rule except its snooze, so unset scope filters are cleared
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateAlertRuleRequest {
+    pub id: i32,
+    pub name: String,
+    pub pattern: String,
+    pub is_regex: bool,
+    pub feed_id: Option<i32>,
+    pub folder_id: Option<i32>,
+    pub enabled: bool,
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
-use crate::entities::{feed, feed_entry};
-use chrono::{DateTime, Utc};
+use crate::models::feed_preferences::FeedPreferences;
+use crate::entities::{alert_rule, annotation, digest, entry_translation, feed, feed_entry, folder, playback_state, share_history, tag, webhook, webhook_delivery};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FeedResponse {
@@ -11,6 +11,27 @@ pub struct FeedResponse {
     pub created_at: String,
     pub updated_at: String,
     pub last_fetched_at: Option<String>,
+    pub folder_id: Option<i32>,
+    pub image_policy: Option<String>, // None follows the global setting
+    pub fetch_interval_minutes: Option<i32>,
+    pub next_fetch_at: Option<String>,
+    pub mark_read_on_scroll: Option<bool>, // None follows the global setting
+    pub auto_title: bool, // title and description follow the publisher's
+    pub source_type: String, // see FeedSourceType
+    pub resolve_canonical_links: bool, // fetch each new entry's page for its canonical link
+    pub icon: Option<String>, // artwork the feed declares
+    pub format: Option<String>, // e.g. "RSS 2.0", as last fetched
+    pub content_hash: Option<String>, // of the last payload saved
+    pub max_entries: Option<i32>, // None follows the global setting, 0 keeps everything
+    pub fetch_full_content: bool, // replace entry content with the article from its page
+    pub extraction_rule: Option<String>, // CSS selector or XPath for the article, None for the heuristic
+    pub tls_ca_certificate: Option<String>, // PEM of an extra CA trusted for this feed
+    pub tls_pinned_sha256: Option<String>, // fingerprint of the only certificate accepted for this feed
+    pub request_timeout_seconds: Option<i32>, // None follows the global setting, as do the two below
+    pub max_retries: Option<i32>,
+    pub rate_limit_delay_ms: Option<i32>,
+    pub hide_read_after_days: Option<i32>, // None follows the global display policy, 0 keeps read entries listed
+    pub preferences: FeedPreferences, // default entry order and view, and whether entries open in the browser
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,9 +43,28 @@ pub struct FeedWithEntriesResponse {
     pub created_at: String,
     pub updated_at: String,
     pub last_fetched_at: Option<String>,
+    pub folder_id: Option<i32>,
     pub entries: Vec<FeedEntryResponse>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FolderResponse {
+    pub id: i32,
+    pub name: String,
+    pub parent_id: Option<i32>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FolderUnreadCount {
+    pub folder_id: i32,
+    // Unread entries in feeds directly inside the folder
+    pub unread_count: u64,
+    // Unread entries in the folder and all of its subfolders
+    pub total_unread_count: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FeedEntryResponse {
     pub id: i32,
@@ -38,9 +78,178 @@ pub struct FeedEntryResponse {
     pub updated_at: String,
     pub is_read: bool,
     pub is_starred: bool,
+    pub enclosure_url: Option<String>,
+    pub enclosure_type: Option<String>,
+    pub duration_seconds: Option<i32>,
+    pub episode_number: Option<i32>,
+    pub season_number: Option<i32>,
+    pub is_explicit: Option<bool>,
+    pub artwork_url: Option<String>,
+    pub word_count: Option<i32>,
+    pub reading_time_minutes: Option<i32>,
+    // The canonical link the entry's page declares, once resolved
+    pub canonical_link: Option<String>,
+    // How often the entry's link was opened from the app, and when last
+    pub open_count: i32,
+    pub last_opened_at: Option<String>,
+    // When the entry was marked read; None for unread entries and ones marked read automatically
+    pub read_at: Option<String>,
+    // When the entry was starred; None unless starred
+    pub starred_at: Option<String>,
+    // When the event behind the entry starts and ends, for entries made from calendar events
+    pub event_starts_at: Option<String>,
+    pub event_ends_at: Option<String>,
+    // Shared by entries from different feeds that cover the same story
+    pub story_cluster_id: Option<i32>,
+    pub snoozed_until: Option<String>,
+    // When the publisher last changed the entry after we first saved it
+    pub updated_at_source: Option<String>,
+    pub is_updated: bool,
+    pub annotations: Vec<AnnotationResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnotationResponse {
+    pub id: i32,
+    pub entry_id: i32,
+    pub note: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadProgressResponse {
+    pub marked_read_entry_ids: Vec<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlaybackStateResponse {
+    pub entry_id: i32,
+    pub position_seconds: i32,
+    pub duration_seconds: Option<i32>,
+    pub finished: bool,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagResponse {
+    pub id: i32,
+    pub name: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagWithCountResponse {
+    pub id: i32,
+    pub name: String,
+    pub created_at: String,
+    pub entry_count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileResponse {
+    pub name: String,
+    pub is_active: bool,
+    pub created_at: Option<String>, // None for the default profile
 }
 
 // Convert entity model to response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DigestResponse {
+    pub id: i32,
+    pub title: String,
+    pub range_start: String,
+    pub range_end: String,
+    pub entry_count: i32,
+    pub markdown: String,
+    pub html: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookResponse {
+    pub id: i32,
+    pub name: String,
+    pub url: String,
+    pub feed_id: Option<i32>,
+    pub tag_id: Option<i32>,
+    pub keyword: Option<String>,
+    pub enabled: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// One logged attempt to notify a webhook, including its retries
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookDeliveryResponse {
+    pub id: i32,
+    pub webhook_id: i32,
+    pub entry_count: i32,
+    pub status: String, // "delivered" or "failed"
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub completed_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AlertRuleResponse {
+    pub id: i32,
+    pub name: String,
+    pub pattern: String,
+    pub is_regex: bool,
+    pub feed_id: Option<i32>,
+    pub folder_id: Option<i32>,
+    pub enabled: bool,
+    pub snoozed_until: Option<String>, // notifications are held back until then
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// An entry in the alerts inbox, with the rule it matched
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AlertResponse {
+    pub id: i32,
+    pub alert_rule_id: i32,
+    pub rule_name: String,
+    pub is_read: bool,
+    pub created_at: String,
+    pub entry: FeedEntryResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShareHistoryResponse {
+    pub id: i32,
+    pub entry_id: i32,
+    pub network: String,
+    pub comment: Option<String>,
+    pub post_url: Option<String>,
+    pub created_at: String,
+}
+
+// An entry in the timeline, with how many other feeds covered the same story
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimelineEntryResponse {
+    #[serde(flatten)]
+    pub entry: FeedEntryResponse,
+    pub also_covered_by: u64,
+}
+
+// An entry's title and text in another language
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntryTranslationResponse {
+    pub entry_id: i32,
+    pub target_lang: String,
+    pub provider: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub content: Option<String>,
+    pub translated_at: String,
+    // Whether the translation was served from the cache instead of calling the provider
+    pub cached: bool,
+}
+
 impl From<feed::Model> for FeedResponse {
     fn from(model: feed::Model) -> Self {
         Self {
@@ -51,6 +260,39 @@ impl From<feed::Model> for FeedResponse {
             created_at: model.created_at.to_string(),
             updated_at: model.updated_at.to_string(),
             last_fetched_at: model.last_fetched_at.map(|dt| dt.to_string()),
+            folder_id: model.folder_id,
+            image_policy: model.image_policy,
+            fetch_interval_minutes: model.fetch_interval_minutes,
+            next_fetch_at: model.next_fetch_at.map(|dt| dt.to_string()),
+            mark_read_on_scroll: model.mark_read_on_scroll,
+            auto_title: model.auto_title,
+            source_type: model.source_type,
+            resolve_canonical_links: model.resolve_canonical_links,
+            icon: model.icon,
+            format: model.format,
+            content_hash: model.content_hash,
+            max_entries: model.max_entries,
+            fetch_full_content: model.fetch_full_content,
+            extraction_rule: model.extraction_rule,
+            tls_ca_certificate: model.tls_ca_certificate,
+            tls_pinned_sha256: model.tls_pinned_sha256,
+            request_timeout_seconds: model.request_timeout_seconds,
+            max_retries: model.max_retries,
+            rate_limit_delay_ms: model.rate_limit_delay_ms,
+            hide_read_after_days: model.hide_read_after_days,
+            preferences: FeedPreferences::from_column(model.preferences.as_deref()),
+        }
+    }
+}
+
+impl From<folder::Model> for FolderResponse {
+    fn from(model: folder::Model) -> Self {
+        Self {
+            id: model.id,
+            name: model.name,
+            parent_id: model.parent_id,
+            created_at: model.created_at.to_string(),
+            updated_at: model.updated_at.to_string(),
         }
     }
 }
@@ -69,17 +311,178 @@ impl From<feed_entry::Model> for FeedEntryResponse {
             updated_at: model.updated_at.to_string(),
             is_read: model.is_read,
             is_starred: model.is_starred,
+            enclosure_url: model.enclosure_url,
+            enclosure_type: model.enclosure_type,
+            duration_seconds: model.duration_seconds,
+            episode_number: model.episode_number,
+            season_number: model.season_number,
+            is_explicit: model.is_explicit,
+            artwork_url: model.artwork_url,
+            word_count: model.word_count,
+            reading_time_minutes: model.reading_time_minutes,
+            canonical_link: model.canonical_link,
+            open_count: model.open_count,
+            last_opened_at: model.last_opened_at.map(|dt| dt.to_string()),
+            read_at: model.read_at.map(|dt| dt.to_string()),
+            starred_at: model.starred_at.map(|dt| dt.to_string()),
+            event_starts_at: model.event_starts_at.map(|dt| dt.to_string()),
+            event_ends_at: model.event_ends_at.map(|dt| dt.to_string()),
+            story_cluster_id: model.story_cluster_id,
+            snoozed_until: model.snoozed_until.map(|dt| dt.to_string()),
+            updated_at_source: model.updated_at_source.map(|dt| dt.to_string()),
+            is_updated: model.is_updated,
+            annotations: Vec::new(),
+        }
+    }
+}
+
+impl From<annotation::Model> for AnnotationResponse {
+    fn from(model: annotation::Model) -> Self {
+        Self {
+            id: model.id,
+            entry_id: model.entry_id,
+            note: model.note,
+            created_at: model.created_at.to_string(),
+            updated_at: model.updated_at.to_string(),
+        }
+    }
+}
+
+impl From<playback_state::Model> for PlaybackStateResponse {
+    fn from(model: playback_state::Model) -> Self {
+        Self {
+            entry_id: model.entry_id,
+            position_seconds: model.position_seconds,
+            duration_seconds: model.duration_seconds,
+            finished: model.finished,
+            updated_at: model.updated_at.to_string(),
+        }
+    }
+}
+
+impl EntryTranslationResponse {
+    pub fn from_model(model: entry_translation::Model, cached: bool) -> Self {
+        Self {
+            entry_id: model.entry_id,
+            target_lang: model.target_lang,
+            provider: model.provider,
+            title: model.title,
+            description: model.description,
+            content: model.content,
+            translated_at: model.created_at.to_string(),
+            cached,
+        }
+    }
+}
+
+impl From<digest::Model> for DigestResponse {
+    fn from(model: digest::Model) -> Self {
+        Self {
+            id: model.id,
+            title: model.title,
+            range_start: model.range_start.to_string(),
+            range_end: model.range_end.to_string(),
+            entry_count: model.entry_count,
+            markdown: model.markdown,
+            html: model.html,
+            created_at: model.created_at.to_string(),
+        }
+    }
+}
+
+impl From<webhook::Model> for WebhookResponse {
+    fn from(model: webhook::Model) -> Self {
+        Self {
+            id: model.id,
+            name: model.name,
+            url: model.url,
+            feed_id: model.feed_id,
+            tag_id: model.tag_id,
+            keyword: model.keyword,
+            enabled: model.enabled,
+            created_at: model.created_at.to_string(),
+            updated_at: model.updated_at.to_string(),
+        }
+    }
+}
+
+impl From<webhook_delivery::Model> for WebhookDeliveryResponse {
+    fn from(model: webhook_delivery::Model) -> Self {
+        Self {
+            id: model.id,
+            webhook_id: model.webhook_id,
+            entry_count: model.entry_count,
+            status: model.status,
+            attempts: model.attempts,
+            response_status: model.response_status,
+            error: model.error,
+            created_at: model.created_at.to_string(),
+            completed_at: model.completed_at.to_string(),
+        }
+    }
+}
+
+impl From<alert_rule::Model> for AlertRuleResponse {
+    fn from(model: alert_rule::Model) -> Self {
+        Self {
+            id: model.id,
+            name: model.name,
+            pattern: model.pattern,
+            is_regex: model.is_regex,
+            feed_id: model.feed_id,
+            folder_id: model.folder_id,
+            enabled: model.enabled,
+            snoozed_until: model.snoozed_until.map(|dt| dt.to_string()),
+            created_at: model.created_at.to_string(),
+            updated_at: model.updated_at.to_string(),
+        }
+    }
+}
+
+impl From<share_history::Model> for ShareHistoryResponse {
+    fn from(model: share_history::Model) -> Self {
+        Self {
+            id: model.id,
+            entry_id: model.entry_id,
+            network: model.network,
+            comment: model.comment,
+            post_url: model.post_url,
+            created_at: model.created_at.to_string(),
+        }
+    }
+}
+
+impl From<tag::Model> for TagResponse {
+    fn from(model: tag::Model) -> Self {
+        Self {
+            id: model.id,
+            name: model.name,
+            created_at: model.created_at.to_string(),
         }
     }
 }
 
 // Refresh-related response structures
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RefreshStartStatus {
+    Started,
+    // A forced refresh cancelled one that was still running and started over
+    Restarted,
+    // Another refresh is still running; nothing was queued
+    AlreadyInProgress,
+    NoFeeds,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RefreshResponse {
     pub success: bool,
+    pub status: RefreshStartStatus,
     pub message: String,
     pub total_feeds: usize,
     pub estimated_completion_time: Option<u64>, // seconds
+    // The refresh's entry in list_operations, when one was started
+    pub operation_id: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -94,12 +497,26 @@ pub struct RefreshProgress {
     pub errors: Vec<RefreshError>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FetchQueueStatus {
+    // Feeds queued for a fetch slot
+    pub pending_tasks: usize,
+    pub in_flight: usize,
+    // Fetch results not yet collected, when there is no database to save them to
+    pub results_waiting: usize,
+    // Database writes (mostly fetched entries being saved) queued or running
+    pub pending_writes: usize,
+    pub capacity: usize,
+    // The queue is close enough to capacity that new fetches may soon be refused
+    pub saturated: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RefreshError {
     pub feed_url: String,
     pub feed_title: Option<String>,
     pub error_message: String,
-    pub error_type: String, // "network", "parse", "timeout", "rate_limited", "too_many_retries"
+    pub error_type: String, // "network", "parse", "timeout", "too_many_retries"
     pub retry_count: u32,
     pub timestamp: String,
 }
@@ -122,6 +539,82 @@ pub struct FeedRefreshStatus {
     pub feed_title: Option<String>,
     pub status: String, // "success", "failed", "skipped"
     pub entries_added: usize,
+    // Existing entries whose title, content or enclosure changed
+    pub entries_updated: usize,
     pub last_fetched_at: String,
     pub error: Option<RefreshError>,
-} 
\ No newline at end of file
+}
+
+// Import-related response structures
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ImportSummary {
+    pub format: String,
+    pub feeds_imported: usize,
+    pub feeds_skipped: usize,
+    pub folders_created: usize,
+    pub entries_imported: usize,
+    pub entries_updated: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportProgress {
+    pub import_id: String,
+    // Id of the import's operation:progress events, which also cover saving the subscriptions
+    pub operation_id: u64,
+    pub status: String, // "fetching", "cancelling", "completed", "rolled_back"
+    pub summary: ImportSummary,
+    pub total_feeds: usize,
+    pub completed_feeds: usize,
+    pub failed_feeds: usize,
+    pub entries_added: usize,
+    pub errors: Vec<RefreshError>,
+}
+
+// Starter pack response structures
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StarterPackFeedResponse {
+    pub url: String,
+    pub title: String,
+    pub description: String,
+    // Already in the library, so installing the pack leaves it where it is
+    pub subscribed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StarterPackResponse {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub feeds: Vec<StarterPackFeedResponse>,
+}
+
+// Feed statistics response structures
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FeedStatsResponse {
+    pub feed_id: i32,
+    pub total_entries: usize,
+    pub average_posts_per_week: f64,
+    pub last_post_at: Option<String>,
+    pub total_fetches: usize,
+    pub average_fetch_duration_ms: Option<f64>,
+    pub error_rate: f64, // 0.0 - 1.0 over the fetches considered
+}
+
+// How often a feed's entries get opened
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FeedOpenStatsResponse {
+    pub feed_id: i32,
+    pub feed_title: Option<String>,
+    pub open_count: i64,
+    pub opened_entries: i64,
+    pub last_opened_at: Option<String>,
+}
+
+// Bytes downloaded for one feed on one (UTC) day
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BandwidthUsageResponse {
+    pub feed_id: i32,
+    pub feed_title: Option<String>,
+    pub day: String, // YYYY-MM-DD
+    pub bytes: i64,
+}
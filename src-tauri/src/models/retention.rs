@@ -0,0 +1,106 @@
+use std::collections::HashSet;
+use sea_orm::*;
+use sea_orm::sea_query::Expr;
+use serde::{Deserialize, Serialize};
+use crate::entities::{prelude::*, *};
+
+// How many entries each feed keeps. Past that, the oldest entries that are read, unstarred and
+// without notes are pruned after each save. Individual feeds can override the cap.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    // 0 keeps every entry
+    pub max_entries_per_feed: u32,
+}
+
+// The cap for a feed: its own override if it has one, otherwise the global setting.
+// None when the feed keeps every entry.
+pub fn effective_max_entries(config: &RetentionConfig, feed_override: Option<i32>) -> Option<u64> {
+    let max_entries = feed_override.map_or(config.max_entries_per_feed as u64, |max_entries| max_entries.max(0) as u64);
+    (max_entries > 0).then_some(max_entries)
+}
+
+// A feed's entries past the newest `max_entries`, newest first
+fn overflow_entries(feed_id: i32, max_entries: u64) -> Select<FeedEntry> {
+    FeedEntry::find()
+        .filter(feed_entry::Column::FeedId.eq(feed_id))
+        .order_by_desc(Expr::cust(r#"COALESCE("feed_entry"."published_at", "feed_entry"."created_at")"#))
+        .order_by_desc(feed_entry::Column::Id)
+        .offset(max_entries)
+}
+
+// Delete the feed's entries beyond its cap that nobody would miss: read, unstarred and without
+// notes. Entries still in the feed's document (`current_guids`) are kept too, or the next fetch
+// would add them back as unread. Kept entries stay even when that leaves the feed over its cap.
+// Returns how many entries were deleted.
+pub async fn prune_feed_entries<C: ConnectionTrait>(
+    db: &C,
+    feed_id: i32,
+    max_entries: u64,
+    current_guids: &HashSet<&str>,
+) -> Result<u64, String> {
+    let overflow: Vec<(i32, String, bool, bool)> = overflow_entries(feed_id, max_entries)
+        .select_only()
+        .columns([
+            feed_entry::Column::Id,
+            feed_entry::Column::Guid,
+            feed_entry::Column::IsRead,
+            feed_entry::Column::IsStarred,
+        ])
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entries: {}", e))?;
+    let candidates: Vec<i32> = overflow
+        .into_iter()
+        .filter(|(_, guid, is_read, is_starred)| *is_read && !*is_starred && !current_guids.contains(guid.as_str()))
+        .map(|(id, ..)| id)
+        .collect();
+    if candidates.is_empty() {
+        return Ok(0);
+    }
+
+    let annotated: Vec<i32> = Annotation::find()
+        .select_only()
+        .column(annotation::Column::EntryId)
+        .filter(annotation::Column::EntryId.is_in(candidates.clone()))
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch annotations: {}", e))?;
+    let prunable: Vec<i32> = candidates.into_iter().filter(|id| !annotated.contains(id)).collect();
+    if prunable.is_empty() {
+        return Ok(0);
+    }
+
+    let result = FeedEntry::delete_many()
+        .filter(feed_entry::Column::Id.is_in(prunable))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to prune feed entries: {}", e))?;
+    Ok(result.rows_affected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_cap_overrides_the_global_one() {
+        let unlimited = RetentionConfig::default();
+        let capped = RetentionConfig { max_entries_per_feed: 200 };
+        assert_eq!(effective_max_entries(&unlimited, None), None);
+        assert_eq!(effective_max_entries(&capped, None), Some(200));
+        assert_eq!(effective_max_entries(&unlimited, Some(50)), Some(50));
+        // A feed can opt out of a global cap
+        assert_eq!(effective_max_entries(&capped, Some(0)), None);
+    }
+
+    #[test]
+    fn test_overflow_skips_the_newest_entries() {
+        let sql = overflow_entries(7, 100).build(DbBackend::Postgres).to_string();
+        assert!(sql.ends_with(
+            r#"WHERE "feed_entry"."feed_id" = 7 ORDER BY COALESCE("feed_entry"."published_at", "feed_entry"."created_at") DESC, "feed_entry"."id" DESC OFFSET 100"#
+        ));
+    }
+}
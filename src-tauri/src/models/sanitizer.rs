@@ -0,0 +1,370 @@
+use std::borrow::Cow;
+use std::str::FromStr;
+use ammonia::{Builder, UrlRelative};
+use serde::{Deserialize, Serialize};
+use url::Url;
+use crate::models::image_proxy::proxied_image_url;
+
+// Per-feed policy for images and embeds, applied when entry HTML is sanitized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImagePolicy {
+    // Keep every image and embed
+    #[default]
+    All,
+    // Keep only media served from the publisher's own site
+    FirstParty,
+    // Keep media but drop 1x1 tracking pixels
+    NoTrackingPixels,
+    // Load images through the local image cache, so publishers never see when an entry is read
+    Proxy,
+    // Remove every image and embed
+    Block,
+}
+
+impl ImagePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImagePolicy::All => "all",
+            ImagePolicy::FirstParty => "first_party",
+            ImagePolicy::NoTrackingPixels => "no_tracking_pixels",
+            ImagePolicy::Proxy => "proxy",
+            ImagePolicy::Block => "block",
+        }
+    }
+}
+
+impl FromStr for ImagePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(ImagePolicy::All),
+            "first_party" => Ok(ImagePolicy::FirstParty),
+            "no_tracking_pixels" => Ok(ImagePolicy::NoTrackingPixels),
+            "proxy" => Ok(ImagePolicy::Proxy),
+            "block" => Ok(ImagePolicy::Block),
+            other => Err(format!("Unknown image policy: {}", other)),
+        }
+    }
+}
+
+// A feed's own policy, or the global default when it doesn't set one
+pub fn effective_image_policy(feed_override: Option<&str>, default: ImagePolicy) -> ImagePolicy {
+    feed_override
+        .and_then(|policy| policy.parse().ok())
+        .unwrap_or(default)
+}
+
+// Attributes that load media, and so are subject to the image policy
+const MEDIA_URL_ATTRIBUTES: &[&str] = &["src", "srcset", "poster"];
+
+// Media and embed tags allowed on top of ammonia's defaults
+const MEDIA_TAGS: &[&str] = &["audio", "iframe", "picture", "source", "video"];
+
+// Image attributes rewritten to the local cache under the proxy policy. Audio and video
+// sources are left alone; only pictures and posters are proxied.
+const PROXIED_ATTRIBUTES: &[(&str, &str)] = &[
+    ("img", "src"),
+    ("img", "srcset"),
+    ("source", "srcset"),
+    ("video", "poster"),
+];
+
+// Strip a leading "www." so example.com and www.example.com count as the same site
+fn site_host(url: &Url) -> Option<String> {
+    url.host_str()
+        .map(|host| host.trim_start_matches("www.").to_ascii_lowercase())
+}
+
+// Whether a media URL belongs to the same site as the page it appears on.
+// Relative URLs are always first party; subdomains in either direction count too
+// (e.g. images.example.com on example.com).
+pub fn is_first_party(media_url: &str, page_url: &Url) -> bool {
+    let media_url = match page_url.join(media_url) {
+        Ok(url) => url,
+        Err(_) => return false,
+    };
+
+    match (site_host(&media_url), site_host(page_url)) {
+        (Some(media_host), Some(page_host)) => {
+            media_host == page_host
+                || media_host.ends_with(&format!(".{}", page_host))
+                || page_host.ends_with(&format!(".{}", media_host))
+        }
+        _ => false,
+    }
+}
+
+// srcset holds a comma-separated list of "url descriptor" candidates
+fn srcset_urls(srcset: &str) -> impl Iterator<Item = &str> {
+    srcset
+        .split(',')
+        .filter_map(|candidate| candidate.split_whitespace().next())
+}
+
+// Value of an attribute in a lowercased start tag, e.g. `width` in `<img width="1" ...>`
+fn tag_attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!("{}=", name);
+    let mut search_from = 0;
+
+    while let Some(offset) = tag[search_from..].find(&pattern) {
+        let start = search_from + offset;
+        search_from = start + pattern.len();
+        if !tag[..start].ends_with(|c: char| c.is_ascii_whitespace()) {
+            continue;
+        }
+
+        let rest = &tag[search_from..];
+        return Some(match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => rest[1..].split(quote).next().unwrap_or_default(),
+            _ => rest
+                .split(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+                .next()
+                .unwrap_or_default(),
+        });
+    }
+
+    None
+}
+
+// An image sized 1x1 (or 0x0) is only there to report that the entry was opened
+fn is_tracking_pixel(img_tag: &str) -> bool {
+    let is_tiny = |name| {
+        tag_attribute(img_tag, name)
+            .and_then(|value| value.trim().trim_end_matches("px").parse::<f32>().ok())
+            .is_some_and(|size| size <= 1.0)
+    };
+    is_tiny("width") && is_tiny("height")
+}
+
+// Remove tracking pixel <img> tags. Runs before ammonia, which can only look at one
+// attribute at a time.
+pub fn strip_tracking_pixels(html: &str) -> Cow<'_, str> {
+    // ASCII lowercasing keeps byte offsets the same as in the original
+    let lowercase = html.to_ascii_lowercase();
+    let mut stripped = String::new();
+    let mut copied_up_to = 0;
+    let mut search_from = 0;
+
+    while let Some(offset) = lowercase[search_from..].find("<img") {
+        let start = search_from + offset;
+        let Some(length) = lowercase[start..].find('>') else {
+            break;
+        };
+        let end = start + length + 1;
+        search_from = end;
+
+        if is_tracking_pixel(&lowercase[start..end]) {
+            stripped.push_str(&html[copied_up_to..start]);
+            copied_up_to = end;
+        }
+    }
+
+    if copied_up_to == 0 {
+        return Cow::Borrowed(html);
+    }
+    stripped.push_str(&html[copied_up_to..]);
+    Cow::Owned(stripped)
+}
+
+// Point an image URL (or each srcset candidate) at the local image cache. Relative URLs are
+// resolved first, since ammonia only rewrites them after the attribute filter has run.
+fn proxy_media_url(attribute: &str, value: &str, page_url: Option<&Url>) -> String {
+    let proxy = |url: &str| {
+        let resolved = match page_url {
+            Some(page_url) => page_url.join(url),
+            None => Url::parse(url),
+        };
+        match resolved {
+            Ok(resolved) if matches!(resolved.scheme(), "http" | "https") => proxied_image_url(resolved.as_str()),
+            _ => url.to_string(),
+        }
+    };
+
+    if attribute != "srcset" {
+        return proxy(value);
+    }
+
+    value
+        .split(',')
+        .map(|candidate| {
+            let mut parts = candidate.split_whitespace();
+            match parts.next() {
+                Some(url) => std::iter::once(proxy(url)).chain(parts.map(str::to_string)).collect::<Vec<_>>().join(" "),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn first_party_media<'a>(attribute: &str, value: &'a str, page_url: Option<&Url>) -> Option<Cow<'a, str>> {
+    let Some(page_url) = page_url else {
+        // Without a page URL only relative (and so first-party) sources can be kept
+        return Url::parse(value).is_err().then_some(Cow::Borrowed(value));
+    };
+
+    let first_party = if attribute == "srcset" {
+        srcset_urls(value).all(|url| is_first_party(url, page_url))
+    } else {
+        is_first_party(value, page_url)
+    };
+
+    first_party.then_some(Cow::Borrowed(value))
+}
+
+// Sanitize entry HTML for display. page_url (the entry's link, or the feed's URL) is used to
+// resolve relative URLs and, under the first-party policy, to drop third-party media sources.
+pub fn sanitize_html(html: &str, page_url: Option<&str>, policy: ImagePolicy) -> String {
+    let page_url = page_url.and_then(|url| Url::parse(url).ok());
+
+    let html = match policy {
+        ImagePolicy::All => Cow::Borrowed(html),
+        _ => strip_tracking_pixels(html),
+    };
+
+    let mut builder = Builder::default();
+    if policy == ImagePolicy::Block {
+        builder.rm_tags(&["img"]);
+    } else {
+        builder
+            .add_tags(MEDIA_TAGS)
+            .add_tag_attributes("img", &["srcset"])
+            .add_tag_attributes("audio", &["src", "controls"])
+            .add_tag_attributes("video", &["src", "poster", "controls", "width", "height"])
+            .add_tag_attributes("source", &["src", "srcset", "type", "media"])
+            .add_tag_attributes("iframe", &["src", "width", "height", "allowfullscreen"]);
+    }
+
+    if let Some(page_url) = &page_url {
+        builder.url_relative(UrlRelative::RewriteWithBase(page_url.clone()));
+    }
+
+    match policy {
+        ImagePolicy::FirstParty => {
+            let page_url = page_url.clone();
+            builder.attribute_filter(move |_element, attribute, value| {
+                if !MEDIA_URL_ATTRIBUTES.contains(&attribute) {
+                    return Some(Cow::Borrowed(value));
+                }
+                first_party_media(attribute, value, page_url.as_ref())
+            });
+        }
+        ImagePolicy::Proxy => {
+            let page_url = page_url.clone();
+            builder.attribute_filter(move |element, attribute, value| {
+                if !PROXIED_ATTRIBUTES.contains(&(element, attribute)) {
+                    return Some(Cow::Borrowed(value));
+                }
+                Some(Cow::Owned(proxy_media_url(attribute, value, page_url.as_ref())))
+            });
+        }
+        ImagePolicy::All | ImagePolicy::NoTrackingPixels | ImagePolicy::Block => {}
+    }
+
+    builder.clean(&html).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE_URL: &str = "https://www.example.com/posts/1";
+
+    #[test]
+    fn test_sanitize_removes_scripts_and_resolves_relative_urls() {
+        let html = r#"<p onclick="steal()">Hi<script>alert(1)</script></p><img src="/a.png">"#;
+
+        let sanitized = sanitize_html(html, Some(PAGE_URL), ImagePolicy::All);
+
+        assert!(!sanitized.contains("script"));
+        assert!(!sanitized.contains("onclick"));
+        assert!(sanitized.contains(r#"src="https://www.example.com/a.png""#));
+    }
+
+    #[test]
+    fn test_all_policy_keeps_third_party_media() {
+        let html = r#"<img src="https://ads.tracker.net/pixel.gif"><iframe src="https://video.example.org/embed/1"></iframe>"#;
+
+        let sanitized = sanitize_html(html, Some(PAGE_URL), ImagePolicy::All);
+
+        assert!(sanitized.contains("https://ads.tracker.net/pixel.gif"));
+        assert!(sanitized.contains("https://video.example.org/embed/1"));
+    }
+
+    #[test]
+    fn test_first_party_policy_strips_third_party_media() {
+        let html = r#"<img src="/hero.jpg"><img src="https://cdn.example.com/inline.jpg"><img src="https://ads.tracker.net/pixel.gif"><iframe src="https://video.example.org/embed/1"></iframe><img srcset="/a.jpg 1x, https://ads.tracker.net/b.jpg 2x">"#;
+
+        let sanitized = sanitize_html(html, Some(PAGE_URL), ImagePolicy::FirstParty);
+
+        assert!(sanitized.contains("https://www.example.com/hero.jpg"));
+        assert!(sanitized.contains("https://cdn.example.com/inline.jpg"));
+        assert!(!sanitized.contains("tracker.net"));
+        assert!(!sanitized.contains("video.example.org"));
+    }
+
+    #[test]
+    fn test_first_party_matching() {
+        let page_url = Url::parse(PAGE_URL).unwrap();
+
+        assert!(is_first_party("/img.png", &page_url));
+        assert!(is_first_party("https://example.com/img.png", &page_url));
+        assert!(is_first_party("https://static.example.com/img.png", &page_url));
+        assert!(!is_first_party("https://notexample.com/img.png", &page_url));
+        assert!(!is_first_party("https://example.com.evil.net/img.png", &page_url));
+    }
+
+    #[test]
+    fn test_image_policy_round_trip() {
+        assert_eq!("first_party".parse::<ImagePolicy>(), Ok(ImagePolicy::FirstParty));
+        assert_eq!(ImagePolicy::All.as_str(), "all");
+        assert_eq!("no_tracking_pixels".parse::<ImagePolicy>(), Ok(ImagePolicy::NoTrackingPixels));
+        assert_eq!(ImagePolicy::Proxy.as_str(), "proxy");
+        assert!("none".parse::<ImagePolicy>().is_err());
+    }
+
+    #[test]
+    fn test_feed_policy_overrides_global_default() {
+        assert_eq!(effective_image_policy(None, ImagePolicy::Proxy), ImagePolicy::Proxy);
+        assert_eq!(effective_image_policy(Some("all"), ImagePolicy::Proxy), ImagePolicy::All);
+        assert_eq!(effective_image_policy(Some("bogus"), ImagePolicy::Block), ImagePolicy::Block);
+    }
+
+    #[test]
+    fn test_tracking_pixels_are_stripped() {
+        let html = r#"<p>Hi</p><IMG src="https://ads.tracker.net/open.gif" width="1" height="1"><img src="/hero.jpg" width="640" height="1"><img src="https://t.example.net/p.png" height=0 width='0px' />"#;
+
+        let sanitized = sanitize_html(html, Some(PAGE_URL), ImagePolicy::NoTrackingPixels);
+
+        assert!(!sanitized.contains("tracker.net"));
+        assert!(!sanitized.contains("t.example.net"));
+        assert!(sanitized.contains("https://www.example.com/hero.jpg"));
+        // The all policy leaves them in place
+        assert!(sanitize_html(html, Some(PAGE_URL), ImagePolicy::All).contains("tracker.net"));
+    }
+
+    #[test]
+    fn test_block_policy_removes_images_and_embeds() {
+        let html = r#"<p>Text</p><img src="/hero.jpg"><picture><source srcset="/a.webp"><img src="/a.jpg"></picture><iframe src="https://video.example.org/embed/1"></iframe>"#;
+
+        let sanitized = sanitize_html(html, Some(PAGE_URL), ImagePolicy::Block);
+
+        assert_eq!(sanitized, "<p>Text</p>");
+    }
+
+    #[test]
+    fn test_proxy_policy_rewrites_image_urls() {
+        let html = r#"<img src="/hero.jpg" srcset="/a.jpg 1x, https://cdn.example.net/b.jpg 2x"><a href="/post">Read</a><video src="/clip.mp4" poster="/poster.jpg"></video>"#;
+
+        let sanitized = sanitize_html(html, Some(PAGE_URL), ImagePolicy::Proxy);
+
+        assert!(sanitized.contains(&proxied_image_url("https://www.example.com/hero.jpg")));
+        assert!(sanitized.contains(&format!("{} 2x", proxied_image_url("https://cdn.example.net/b.jpg"))));
+        assert!(sanitized.contains(&proxied_image_url("https://www.example.com/poster.jpg")));
+        // Links and video sources are not images
+        assert!(sanitized.contains(r#"href="https://www.example.com/post""#));
+        assert!(sanitized.contains(r#"src="https://www.example.com/clip.mp4""#));
+    }
+}
@@ -0,0 +1,489 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{Datelike, NaiveDateTime, Timelike, Weekday};
+use futures::stream::{self, StreamExt};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::{watch, RwLock};
+use crate::entities::{prelude::*, *};
+use crate::models::archive::archive_old_entries_if_due;
+use crate::models::backup::back_up_if_due;
+use crate::models::mutes::expire_mutes;
+use crate::models::snapshots::snapshot_starred_entries;
+use crate::models::async_feed_fetcher::AsyncFeedFetcher;
+use crate::models::bandwidth::{metered_refresh_interval_minutes, METERED_MAX_CONCURRENT_REQUESTS};
+use crate::models::digest::generate_scheduled_digest_if_due;
+use crate::models::email::send_digest_email;
+use crate::models::feed_health::send_health_digest_if_due;
+use crate::models::feed_stats::load_post_dates;
+use crate::models::operations::{Operation, OperationKind};
+use crate::models::refresh_history::{record_refresh_summary, RefreshSource};
+use crate::models::responses::RefreshSummary;
+use crate::models::settings::{load_digest_config, load_email_config};
+use crate::models::state::AppState;
+use crate::models::state_log::compact_state_log_if_due;
+
+// How often the scheduler looks for feeds that are due
+const SCHEDULER_TICK: Duration = Duration::from_secs(60);
+
+// How many of a feed's most recent posts are used to estimate its cadence
+const CADENCE_SAMPLE_SIZE: usize = 10;
+
+// Poll a few times per expected post so new items show up reasonably soon after publishing
+const POLLS_PER_EXPECTED_POST: i64 = 4;
+
+// Longest window the launch refresh may be spread over
+const MAX_STARTUP_STAGGER_SECONDS: u32 = 10 * 60;
+
+// What the scheduler loop last did, for diagnostics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SchedulerStatus {
+    pub last_tick_at: Option<String>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<String>,
+}
+
+impl SchedulerStatus {
+    fn record_tick(&mut self, result: &Result<(), String>) {
+        let now = chrono::Utc::now().to_rfc3339();
+        if let Err(e) = result {
+            self.last_error = Some(e.clone());
+            self.last_error_at = Some(now.clone());
+        }
+        self.last_tick_at = Some(now);
+    }
+}
+
+// User preferences for background refresh. Each feed's interval adapts to how often it
+// posts, bounded by the min/max interval.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    pub enabled: bool,
+    pub min_interval_minutes: u32,
+    pub max_interval_minutes: u32,
+    // The feeds due at launch are queued over this many seconds rather than all at once; 0
+    // queues them immediately
+    pub startup_stagger_seconds: u32,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_interval_minutes: 15,
+            max_interval_minutes: 24 * 60,
+            startup_stagger_seconds: 30,
+        }
+    }
+}
+
+impl SchedulerConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_interval_minutes == 0 {
+            return Err("Minimum refresh interval must be at least 1 minute".to_string());
+        }
+        if self.min_interval_minutes > self.max_interval_minutes {
+            return Err("Minimum refresh interval cannot exceed the maximum".to_string());
+        }
+        if self.startup_stagger_seconds > MAX_STARTUP_STAGGER_SECONDS {
+            return Err(format!(
+                "Startup refresh can be spread over at most {} seconds",
+                MAX_STARTUP_STAGGER_SECONDS
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Work out how often to poll a feed from its recent posting cadence.
+// The expected gap until the next post is the average gap between recent posts, or the time
+// since the last post if that is longer, so feeds that have gone quiet back off over time.
+pub fn compute_refresh_interval_minutes(
+    post_dates: &[NaiveDateTime],
+    now: NaiveDateTime,
+    config: &SchedulerConfig,
+) -> u32 {
+    let mut recent_posts = post_dates.to_vec();
+    recent_posts.sort_unstable_by(|a, b| b.cmp(a));
+    recent_posts.truncate(CADENCE_SAMPLE_SIZE);
+
+    let (Some(newest), Some(oldest)) = (recent_posts.first(), recent_posts.last()) else {
+        return config.max_interval_minutes;
+    };
+
+    let minutes_since_last_post = (now - *newest).num_minutes().max(0);
+    let average_gap_minutes = if recent_posts.len() > 1 {
+        (*newest - *oldest).num_minutes() / (recent_posts.len() as i64 - 1)
+    } else {
+        minutes_since_last_post
+    };
+
+    let expected_gap_minutes = average_gap_minutes.max(minutes_since_last_post);
+    let interval_minutes = expected_gap_minutes / POLLS_PER_EXPECTED_POST;
+
+    interval_minutes.clamp(
+        config.min_interval_minutes as i64,
+        config.max_interval_minutes as i64,
+    ) as u32
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+// Parse the comma-separated skip lists stored on a feed
+fn parse_skip_hours(skip_hours: Option<&str>) -> Vec<u32> {
+    skip_hours
+        .map(|hours| hours.split(',').filter_map(|hour| hour.trim().parse().ok()).collect())
+        .unwrap_or_default()
+}
+
+fn parse_skip_days(skip_days: Option<&str>) -> Vec<String> {
+    skip_days
+        .map(|days| days.split(',').map(|day| day.trim().to_string()).collect())
+        .unwrap_or_default()
+}
+
+// Move a fetch time out of any hours (GMT) or days the publisher asked readers to skip,
+// to the start of the next allowed hour. If every slot is skipped the hints are ignored.
+pub fn next_allowed_fetch_time(
+    candidate: NaiveDateTime,
+    skip_hours: &[u32],
+    skip_days: &[String],
+) -> NaiveDateTime {
+    let is_skipped = |time: NaiveDateTime| {
+        skip_hours.contains(&time.hour())
+            || skip_days
+                .iter()
+                .any(|day| day.eq_ignore_ascii_case(weekday_name(time.weekday())))
+    };
+
+    let mut next_time = candidate;
+    for _ in 0..(7 * 24) {
+        if !is_skipped(next_time) {
+            return next_time;
+        }
+        next_time = next_time
+            .date()
+            .and_hms_opt(next_time.hour(), 0, 0)
+            .unwrap_or(next_time)
+            + chrono::Duration::hours(1);
+    }
+
+    candidate
+}
+
+// When the `index`th of `count` feeds is queued within a startup stagger window. Each feed
+// gets an equal slot and a random point in it (`random` is in [0, 1)), so feeds spread
+// evenly across the window without lining up on exact intervals.
+pub fn startup_delay(index: usize, count: usize, window: Duration, random: f64) -> Duration {
+    if count == 0 || window.is_zero() {
+        return Duration::ZERO;
+    }
+    let slot = window.as_secs_f64() / count as f64;
+    Duration::from_secs_f64(slot * (index as f64 + random.clamp(0.0, 1.0)))
+}
+
+// Recompute a feed's refresh interval and schedule its next fetch, honoring the
+// publisher's ttl (never poll more often) and skipHours/skipDays, and backing off further
+// on a metered connection
+pub async fn schedule_next_fetch<C: ConnectionTrait>(
+    db: &C,
+    feed: &feed::Model,
+    config: &SchedulerConfig,
+    metered_mode: bool,
+) -> Result<(), String> {
+    let post_dates = load_post_dates(db, feed.id).await?;
+    let now = chrono::Utc::now().naive_utc();
+    let mut interval_minutes = metered_refresh_interval_minutes(
+        compute_refresh_interval_minutes(&post_dates, now, config),
+        metered_mode,
+    );
+    if let Some(ttl_minutes) = feed.ttl_minutes {
+        interval_minutes = interval_minutes.max(ttl_minutes.max(0) as u32);
+    }
+
+    let next_fetch_at = next_allowed_fetch_time(
+        now + chrono::Duration::minutes(interval_minutes as i64),
+        &parse_skip_hours(feed.skip_hours.as_deref()),
+        &parse_skip_days(feed.skip_days.as_deref()),
+    );
+
+    let mut updated_feed: feed::ActiveModel = feed.clone().into();
+    updated_feed.fetch_interval_minutes = ActiveValue::Set(Some(interval_minutes as i32));
+    updated_feed.next_fetch_at = ActiveValue::Set(Some(next_fetch_at));
+    updated_feed
+        .update(db)
+        .await
+        .map_err(|e| format!("Failed to schedule next fetch: {}", e))?;
+
+    Ok(())
+}
+
+// Fetch every feed whose next fetch is due (or that has never been scheduled). With a
+// `stagger` window, each feed waits for its startup_delay before being fetched. The refresh is
+// listed among the operations; cancelling it leaves the feeds not yet fetched due for the
+// next tick.
+async fn refresh_due_feeds(app: &AppHandle, config: &SchedulerConfig, stagger: Option<Duration>) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let Some(fetcher) = &state.async_fetcher else {
+        return Ok(());
+    };
+    if fetcher.is_paused() {
+        return Ok(());
+    }
+
+    let db = state.db().await;
+    let due_feeds = Feed::find()
+        .filter(
+            Condition::any()
+                .add(feed::Column::NextFetchAt.is_null())
+                .add(feed::Column::NextFetchAt.lte(chrono::Utc::now().naive_utc())),
+        )
+        .all(&db)
+        .await
+        .map_err(|e| format!("Failed to fetch due feeds: {}", e))?;
+
+    if due_feeds.is_empty() {
+        return Ok(());
+    }
+
+    println!("⏰ Scheduler refreshing {} due feeds", due_feeds.len());
+
+    let metered_mode = *state.metered_mode.read().await;
+    let concurrency = if metered_mode {
+        METERED_MAX_CONCURRENT_REQUESTS
+    } else {
+        fetcher.config().max_concurrent_requests.max(1)
+    };
+    let db = &db;
+    let started_at = tokio::time::Instant::now();
+    let feed_count = due_feeds.len();
+    let operation = &Operation::start(app, OperationKind::Refresh, feed_count);
+    let feed_statuses = &std::sync::Mutex::new(Vec::new());
+    let refresh = stream::iter(due_feeds.into_iter().enumerate())
+        .map(|(index, feed)| async move {
+            // Waiting for a fixed instant keeps the spread even when earlier fetches hold up
+            // the concurrency slots
+            if let Some(window) = stagger {
+                let delay = startup_delay(index, feed_count, window, rand::random::<f64>());
+                tokio::time::sleep_until(started_at + delay).await;
+            }
+            let result = fetcher.fetch_and_save_feed(&feed).await;
+            operation.record(result.is_ok());
+            feed_statuses.lock().unwrap().push(AsyncFeedFetcher::feed_refresh_status(&feed, &result));
+            if let Err(e) = result {
+                eprintln!("❌ Scheduled refresh of {} failed: {}", feed.url, e.error_message);
+            }
+
+            // Reload so the schedule sees the ttl/skip hints from this fetch
+            let feed = match Feed::find_by_id(feed.id).one(db).await {
+                Ok(Some(feed)) => feed,
+                _ => feed,
+            };
+            if let Err(e) = schedule_next_fetch(db, &feed, config, metered_mode).await {
+                eprintln!("❌ {}", e);
+            }
+        })
+        .buffer_unordered(concurrency)
+        .collect::<Vec<_>>();
+
+    tokio::select! {
+        _ = refresh => {}
+        _ = operation.cancelled() => println!("⏹️ Scheduled refresh cancelled"),
+    }
+    operation.finish(Ok(()));
+
+    // Sent to refresh summary listeners and kept in the refresh history, so a missed or
+    // failing night shows up there
+    let feeds_updated = std::mem::take(&mut *feed_statuses.lock().unwrap());
+    let errors: Vec<_> = feeds_updated.iter().filter_map(|status| status.error.clone()).collect();
+    let summary = RefreshSummary {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        total_processed: feeds_updated.len(),
+        successful_count: feeds_updated.len() - errors.len(),
+        failed_count: errors.len(),
+        duration_seconds: started_at.elapsed().as_secs(),
+        feeds_updated,
+        errors,
+    };
+    fetcher.publish_refresh_summary(summary.clone()).await;
+    record_refresh_summary(db, &summary, RefreshSource::Scheduled).await
+}
+
+// Background loop that refreshes feeds as they come due while the scheduler is enabled,
+// sends the weekly subscription problems digest and generates (and optionally emails) the
+// daily digest. Stops when `shutdown` is set; a refresh cut short leaves the remaining feeds
+// due for the next launch. The first refresh after launch, when every overdue feed comes due
+// at once, is spread over the configured startup stagger window.
+pub async fn run_scheduler(app: AppHandle, config: Arc<RwLock<SchedulerConfig>>, mut shutdown: watch::Receiver<bool>) {
+    let mut ticker = tokio::time::interval(SCHEDULER_TICK);
+    let mut launch_refresh = true;
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = shutdown.wait_for(|stopping| *stopping) => break,
+        }
+
+        let state = app.state::<AppState>();
+        let db = state.db().await;
+        let notification_config = state.notification_config.read().await.clone();
+        if let Err(e) = send_health_digest_if_due(&app, &db, &notification_config).await {
+            eprintln!("❌ Health digest failed: {}", e);
+        }
+        match generate_scheduled_digest_if_due(&db, &load_digest_config(&db).await).await {
+            Ok(Some(digest)) => {
+                let email_config = load_email_config(&db).await;
+                if email_config.send_scheduled_digest {
+                    if let Err(e) = send_digest_email(&email_config, &digest).await {
+                        eprintln!("❌ Digest email failed: {}", e);
+                    }
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("❌ Scheduled digest failed: {}", e),
+        }
+        if let Err(e) = compact_state_log_if_due(&db, chrono::Utc::now().naive_utc()).await {
+            eprintln!("❌ State log compaction failed: {}", e);
+        }
+        if let Err(e) = archive_old_entries_if_due(&db, chrono::Utc::now().naive_utc()).await {
+            eprintln!("❌ Archiving old entries failed: {}", e);
+        }
+        if let Err(e) = expire_mutes(&db, chrono::Utc::now().naive_utc()).await {
+            eprintln!("❌ Expiring mutes failed: {}", e);
+        }
+        if let Err(e) = back_up_if_due(&app, &db, chrono::Utc::now().naive_utc()).await {
+            eprintln!("❌ Backup failed: {}", e);
+        }
+        // Catches up on entries starred in bulk, by sync, or while metered
+        let (snapshot_app, snapshot_db) = (app.clone(), db.clone());
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = snapshot_starred_entries(&snapshot_app, &snapshot_db).await {
+                eprintln!("❌ Snapshotting starred entries failed: {}", e);
+            }
+        });
+        if let Err(e) = state.sync_engine.sync_if_due(&db).await {
+            eprintln!("❌ Sync failed: {}", e);
+        }
+
+        let config = config.read().await.clone();
+        if !config.enabled {
+            continue;
+        }
+
+        let stagger = (launch_refresh && config.startup_stagger_seconds > 0)
+            .then(|| Duration::from_secs(config.startup_stagger_seconds as u64));
+        launch_refresh = false;
+
+        let result = tokio::select! {
+            result = refresh_due_feeds(&app, &config, stagger) => result,
+            _ = shutdown.wait_for(|stopping| *stopping) => break,
+        };
+        if let Err(e) = &result {
+            eprintln!("❌ Scheduler tick failed: {}", e);
+        }
+        state.scheduler_status.write().await.record_tick(&result);
+    }
+
+    println!("🛑 Scheduler stopped");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    fn posts_every(gap: ChronoDuration, count: i32, last_post: NaiveDateTime) -> Vec<NaiveDateTime> {
+        (0..count).map(|i| last_post - gap * i).collect()
+    }
+
+    #[test]
+    fn test_busy_feed_polls_at_minimum_interval() {
+        let now = chrono::Utc::now().naive_utc();
+        let config = SchedulerConfig::default();
+        let post_dates = posts_every(ChronoDuration::minutes(20), 10, now - ChronoDuration::minutes(5));
+
+        assert_eq!(compute_refresh_interval_minutes(&post_dates, now, &config), 15);
+    }
+
+    #[test]
+    fn test_interval_follows_posting_cadence() {
+        let now = chrono::Utc::now().naive_utc();
+        let config = SchedulerConfig::default();
+        // Roughly every 8 hours, last one an hour ago
+        let post_dates = posts_every(ChronoDuration::hours(8), 10, now - ChronoDuration::hours(1));
+
+        assert_eq!(compute_refresh_interval_minutes(&post_dates, now, &config), 120);
+    }
+
+    #[test]
+    fn test_dormant_feed_backs_off_to_maximum_interval() {
+        let now = chrono::Utc::now().naive_utc();
+        let config = SchedulerConfig::default();
+        // Used to post hourly, but nothing for two months
+        let post_dates = posts_every(ChronoDuration::hours(1), 10, now - ChronoDuration::days(60));
+
+        assert_eq!(compute_refresh_interval_minutes(&post_dates, now, &config), config.max_interval_minutes);
+        assert_eq!(compute_refresh_interval_minutes(&[], now, &config), config.max_interval_minutes);
+    }
+
+    #[test]
+    fn test_next_fetch_skips_publisher_hours_and_days() {
+        let at = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        let skip_hours = vec![0, 1, 2];
+        let skip_days = vec!["Saturday".to_string(), "sunday".to_string()];
+
+        // Friday 2024-05-31 14:30 is allowed
+        assert_eq!(next_allowed_fetch_time(at("2024-05-31 14:30"), &skip_hours, &skip_days), at("2024-05-31 14:30"));
+        // Thursday 00:45 moves to 03:00
+        assert_eq!(next_allowed_fetch_time(at("2024-05-30 00:45"), &skip_hours, &skip_days), at("2024-05-30 03:00"));
+        // Saturday afternoon moves past the weekend and the skipped early hours to Monday 03:00
+        assert_eq!(next_allowed_fetch_time(at("2024-06-01 15:10"), &skip_hours, &skip_days), at("2024-06-03 03:00"));
+        // Skipping everything is ignored
+        let every_hour: Vec<u32> = (0..24).collect();
+        assert_eq!(next_allowed_fetch_time(at("2024-05-31 14:30"), &every_hour, &[]), at("2024-05-31 14:30"));
+    }
+
+    #[test]
+    fn test_parse_stored_skip_lists() {
+        assert_eq!(parse_skip_hours(Some("0, 1,23")), vec![0, 1, 23]);
+        assert_eq!(parse_skip_days(Some("Saturday,Sunday")), vec!["Saturday", "Sunday"]);
+        assert!(parse_skip_hours(None).is_empty());
+    }
+
+    #[test]
+    fn test_scheduler_config_validation() {
+        assert!(SchedulerConfig::default().validate().is_ok());
+
+        let inverted = SchedulerConfig {
+            min_interval_minutes: 120,
+            max_interval_minutes: 60,
+            ..Default::default()
+        };
+        assert!(inverted.validate().is_err());
+
+        let too_slow = SchedulerConfig { startup_stagger_seconds: 3600, ..Default::default() };
+        assert!(too_slow.validate().is_err());
+    }
+
+    #[test]
+    fn test_startup_delays_spread_across_the_window() {
+        let window = Duration::from_secs(30);
+
+        assert_eq!(startup_delay(0, 10, window, 0.0), Duration::ZERO);
+        assert_eq!(startup_delay(5, 10, window, 0.5), Duration::from_millis(16_500));
+        assert!(startup_delay(9, 10, window, 0.999) < window);
+        assert_eq!(startup_delay(3, 10, Duration::ZERO, 0.5), Duration::ZERO);
+    }
+}
@@ -0,0 +1,189 @@
+use chrono::NaiveDateTime;
+use sea_orm::*;
+use sea_orm::sea_query::{Expr, SimpleExpr};
+use serde::Serialize;
+use crate::entities::{prelude::*, *};
+
+// Only entries saved this recently are suggested, so old titles don't crowd out current ones
+const SUGGESTION_ENTRY_WINDOW_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSuggestionKind {
+    Feed,
+    Tag,
+    Entry,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchSuggestion {
+    pub kind: SearchSuggestionKind,
+    pub id: i32,
+    pub text: String,
+    // Entries saved for a feed, entries carrying a tag, or times an entry was opened
+    pub frequency: i64,
+}
+
+// Turn what the user has typed so far into a tsquery: every word must appear, and the last one
+// may still be incomplete. Only letters and digits are kept, so the result is always valid.
+pub fn prefix_tsquery(prefix: &str) -> Option<String> {
+    let words: Vec<String> = prefix
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let (last, rest) = words.split_last()?;
+    Some(rest.iter().map(|word| format!("{} & ", word)).collect::<String>() + last + ":*")
+}
+
+// Whether a text column matches the tsquery, in the same form as the title search index
+fn matches_tsquery(column: &str, tsquery: &str) -> SimpleExpr {
+    Expr::cust_with_values(format!("to_tsvector('simple', {}) @@ to_tsquery('simple', $1)", column), [tsquery])
+}
+
+// Keep the `limit` suggestions that come up most often for their kind. Entry counts, tag uses
+// and opens aren't on one scale, so each is scored against the most frequent suggestion of its
+// kind and every kind's top suggestion ranks level. The sort is stable, so on ties feeds stay
+// ahead of tags and tags ahead of entries.
+fn rank_suggestions(suggestions: Vec<SearchSuggestion>, limit: u64) -> Vec<SearchSuggestion> {
+    let most_frequent = |kind: SearchSuggestionKind| {
+        suggestions
+            .iter()
+            .filter(|suggestion| suggestion.kind == kind)
+            .map(|suggestion| suggestion.frequency)
+            .max()
+            .unwrap_or(0)
+    };
+    // Counted from one, so suggestions nobody has used yet still score against each other
+    let scores: Vec<f64> = suggestions
+        .iter()
+        .map(|suggestion| (suggestion.frequency + 1) as f64 / (most_frequent(suggestion.kind) + 1) as f64)
+        .collect();
+    let mut ranked: Vec<(f64, SearchSuggestion)> = scores.into_iter().zip(suggestions).collect();
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+    ranked.into_iter().take(limit as usize).map(|(_, suggestion)| suggestion).collect()
+}
+
+// Suggestions for the search bar from matching feed titles, tags and recent entry titles,
+// ranked together by how often they come up for their kind. Returns nothing until a word has
+// been typed.
+pub async fn load_search_suggestions<C: ConnectionTrait>(
+    db: &C,
+    prefix: &str,
+    limit: u64,
+    now: NaiveDateTime,
+) -> Result<Vec<SearchSuggestion>, String> {
+    let Some(tsquery) = prefix_tsquery(prefix) else {
+        return Ok(Vec::new());
+    };
+
+    let entry_count = r#"(SELECT COUNT(*) FROM "feed_entry" WHERE "feed_entry"."feed_id" = "feed"."id")"#;
+    let feeds: Vec<(i32, Option<String>, i64)> = Feed::find()
+        .select_only()
+        .column(feed::Column::Id)
+        .column(feed::Column::Title)
+        .column_as(Expr::cust(entry_count), "frequency")
+        .filter(matches_tsquery(r#"COALESCE("feed"."title", '')"#, &tsquery))
+        .order_by_desc(Expr::cust(entry_count))
+        .limit(limit)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to search feeds: {}", e))?;
+
+    let tag_uses = r#"(SELECT COUNT(*) FROM "entry_tag" WHERE "entry_tag"."tag_id" = "tag"."id")"#;
+    let tags: Vec<(i32, String, i64)> = Tag::find()
+        .select_only()
+        .column(tag::Column::Id)
+        .column(tag::Column::Name)
+        .column_as(Expr::cust(tag_uses), "frequency")
+        .filter(matches_tsquery(r#""tag"."name""#, &tsquery))
+        .order_by_desc(Expr::cust(tag_uses))
+        .limit(limit)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to search tags: {}", e))?;
+
+    let saved_since = now - chrono::Duration::days(SUGGESTION_ENTRY_WINDOW_DAYS);
+    let entries: Vec<(i32, String, i32)> = FeedEntry::find()
+        .select_only()
+        .column(feed_entry::Column::Id)
+        .column(feed_entry::Column::Title)
+        .column(feed_entry::Column::OpenCount)
+        .filter(matches_tsquery(r#""feed_entry"."title""#, &tsquery))
+        .filter(feed_entry::Column::CreatedAt.gte(saved_since))
+        .filter(feed_entry::Column::IsHidden.eq(false))
+        .order_by_desc(feed_entry::Column::OpenCount)
+        .order_by_desc(feed_entry::Column::CreatedAt)
+        .limit(limit)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to search entries: {}", e))?;
+
+    let feeds = feeds.into_iter().filter_map(|(id, title, frequency)| {
+        title.map(|text| SearchSuggestion { kind: SearchSuggestionKind::Feed, id, text, frequency })
+    });
+    let tags = tags
+        .into_iter()
+        .map(|(id, text, frequency)| SearchSuggestion { kind: SearchSuggestionKind::Tag, id, text, frequency });
+    let mut seen_titles = std::collections::HashSet::new();
+    let entries = entries
+        .into_iter()
+        // The same story syndicated by several feeds is suggested once
+        .filter(|(_, title, _)| seen_titles.insert(title.clone()))
+        .map(|(id, text, open_count)| SearchSuggestion {
+            kind: SearchSuggestionKind::Entry,
+            id,
+            text,
+            frequency: open_count as i64,
+        });
+
+    Ok(rank_suggestions(feeds.chain(tags).chain(entries).collect(), limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_becomes_a_prefix_tsquery() {
+        assert_eq!(prefix_tsquery("Rus").as_deref(), Some("rus:*"));
+        assert_eq!(prefix_tsquery("  async rus").as_deref(), Some("async & rus:*"));
+        assert_eq!(prefix_tsquery("C++ & 'tokio':*").as_deref(), Some("c & tokio:*"));
+        assert_eq!(prefix_tsquery(" !? "), None);
+    }
+
+    #[test]
+    fn test_limit_applies_to_all_kinds_together() {
+        let suggestion = |kind, id, frequency| SearchSuggestion { kind, id, text: format!("Rust {}", id), frequency };
+        let ranked = rank_suggestions(
+            vec![
+                suggestion(SearchSuggestionKind::Feed, 1, 3),
+                suggestion(SearchSuggestionKind::Feed, 2, 40),
+                suggestion(SearchSuggestionKind::Tag, 3, 12),
+                suggestion(SearchSuggestionKind::Entry, 4, 12),
+                suggestion(SearchSuggestionKind::Entry, 5, 0),
+            ],
+            3,
+        );
+
+        assert_eq!(ranked.iter().map(|suggestion| suggestion.id).collect::<Vec<_>>(), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn test_large_feed_does_not_crowd_out_entries() {
+        let suggestion = |kind, id, frequency| SearchSuggestion { kind, id, text: format!("Rust {}", id), frequency };
+        let ranked = rank_suggestions(
+            vec![
+                suggestion(SearchSuggestionKind::Feed, 1, 5000),
+                suggestion(SearchSuggestionKind::Feed, 2, 800),
+                suggestion(SearchSuggestionKind::Entry, 3, 2),
+            ],
+            2,
+        );
+
+        assert_eq!(ranked.iter().map(|suggestion| suggestion.id).collect::<Vec<_>>(), vec![1, 3]);
+    }
+}
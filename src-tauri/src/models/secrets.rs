@@ -0,0 +1,96 @@
+use keyring::Entry;
+use serde::{Deserialize, Serialize};
+
+// Keychain service name that every secret is stored under
+const SECRETS_SERVICE: &str = "com.reader.app";
+
+// What a secret is for. Secrets live only in the OS keychain, never in the database;
+// the database refers to them by name (e.g. a feed id) through these kinds.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretKind {
+    FeedAuth,
+    SyncToken,
+    IntegrationApiKey,
+    FeedCookies,
+    DatabaseUrl,
+}
+
+impl SecretKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SecretKind::FeedAuth => "feed_auth",
+            SecretKind::SyncToken => "sync_token",
+            SecretKind::IntegrationApiKey => "integration_api_key",
+            SecretKind::FeedCookies => "feed_cookies",
+            SecretKind::DatabaseUrl => "database_url",
+        }
+    }
+}
+
+// Keychain account for a secret, e.g. "feed_auth:42"
+pub fn secret_account(kind: SecretKind, name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Secret name cannot be empty".to_string());
+    }
+    Ok(format!("{}:{}", kind.as_str(), name))
+}
+
+fn keychain_entry(kind: SecretKind, name: &str) -> Result<Entry, String> {
+    Entry::new(SECRETS_SERVICE, &secret_account(kind, name)?)
+        .map_err(|e| format!("Failed to open keychain entry: {}", e))
+}
+
+// Keychain calls block (and may show an OS prompt), so they run off the async runtime
+async fn with_keychain<T, F>(operation: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(operation)
+        .await
+        .map_err(|e| format!("Keychain task failed: {}", e))?
+}
+
+pub async fn store_secret_value(kind: SecretKind, name: String, value: String) -> Result<(), String> {
+    with_keychain(move || {
+        keychain_entry(kind, &name)?
+            .set_password(&value)
+            .map_err(|e| format!("Failed to store secret: {}", e))
+    })
+    .await
+}
+
+// Look up a secret, or None if it was never stored
+pub async fn get_secret_value(kind: SecretKind, name: String) -> Result<Option<String>, String> {
+    with_keychain(move || match keychain_entry(kind, &name)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read secret: {}", e)),
+    })
+    .await
+}
+
+// Remove a secret. Returns false if there was nothing to remove.
+pub async fn delete_secret_value(kind: SecretKind, name: String) -> Result<bool, String> {
+    with_keychain(move || match keychain_entry(kind, &name)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(format!("Failed to delete secret: {}", e)),
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_accounts_are_namespaced_by_kind() {
+        assert_eq!(secret_account(SecretKind::FeedAuth, "42").unwrap(), "feed_auth:42");
+        assert_eq!(secret_account(SecretKind::IntegrationApiKey, " pocket ").unwrap(), "integration_api_key:pocket");
+        assert!(secret_account(SecretKind::SyncToken, "  ").is_err());
+        assert_eq!(serde_json::to_string(&SecretKind::SyncToken).unwrap(), "\"sync_token\"");
+    }
+}
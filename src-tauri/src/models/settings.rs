@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use sea_orm::*;
+use sea_orm::sea_query::OnConflict;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::entities::{prelude::*, *};
+use crate::models::async_feed_fetcher::FetcherConfig;
+use crate::models::notifications::NotificationConfig;
+use crate::models::scheduler::SchedulerConfig;
+use crate::models::reading::ReadingConfig;
+use crate::models::retention::RetentionConfig;
+use crate::models::link_cleaner::PrivacyConfig;
+use crate::models::translation::TranslationConfig;
+use crate::models::classifier::ClassifierConfig;
+use crate::models::digest::DigestConfig;
+use crate::models::email::EmailConfig;
+use crate::models::republish::RepublishConfig;
+use crate::models::subscribe_endpoint::SubscribeEndpointConfig;
+use crate::models::social::SocialConfig;
+use crate::models::domain_rules::DomainRulesConfig;
+use crate::models::mutes::MuteRulesConfig;
+use crate::models::display_policy::DisplayPolicyConfig;
+use crate::models::backup::BackupConfig;
+use crate::models::sync::SyncConfig;
+use crate::models::archive::ArchiveConfig;
+
+// Setting keys. Values are stored JSON-encoded in the `setting` table.
+pub const FETCHER_MAX_CONCURRENT_REQUESTS: &str = "fetcher.max_concurrent_requests";
+pub const FETCHER_MAX_REQUESTS_PER_DOMAIN: &str = "fetcher.max_requests_per_domain";
+pub const FETCHER_RATE_LIMIT_DELAY_MS: &str = "fetcher.rate_limit_delay_ms";
+pub const FETCHER_REQUEST_TIMEOUT_SECS: &str = "fetcher.request_timeout_secs";
+pub const FETCHER_MAX_RETRIES: &str = "fetcher.max_retries";
+pub const FETCHER_BASE_RETRY_DELAY_MS: &str = "fetcher.base_retry_delay_ms";
+pub const FETCHER_MAX_RETRY_DELAY_SECS: &str = "fetcher.max_retry_delay_secs";
+pub const FETCHER_RETRY_JITTER: &str = "fetcher.retry_jitter";
+pub const FETCHER_MAX_REDIRECTS: &str = "fetcher.max_redirects";
+pub const FETCHER_CIRCUIT_BREAKER_THRESHOLD: &str = "fetcher.circuit_breaker_threshold";
+pub const FETCHER_CIRCUIT_BREAKER_COOLDOWN_SECS: &str = "fetcher.circuit_breaker_cooldown_secs";
+pub const FETCHER_ALLOW_PRIVATE_ADDRESSES: &str = "fetcher.allow_private_addresses";
+pub const FETCHER_MAX_ENTRIES_PER_FETCH: &str = "fetcher.max_entries_per_fetch";
+pub const NOTIFICATIONS: &str = "notifications";
+pub const SCHEDULER: &str = "scheduler";
+pub const DATA_DIRECTORY: &str = "data_directory";
+pub const READING: &str = "reading";
+pub const RETENTION: &str = "retention";
+pub const HEALTH_DIGEST: &str = "health_digest";
+pub const PRIVACY: &str = "privacy";
+pub const TRANSLATION: &str = "translation";
+pub const CLASSIFIER: &str = "classifier";
+pub const DIGEST: &str = "digest";
+// When the scheduled digest last ran
+pub const DIGEST_SCHEDULE: &str = "digest_schedule";
+pub const EMAIL: &str = "email";
+pub const REPUBLISH: &str = "republish";
+pub const SOCIAL: &str = "social";
+pub const DOMAIN_RULES: &str = "domain_rules";
+pub const MUTE_RULES: &str = "mute_rules";
+pub const DISPLAY_POLICY: &str = "display_policy";
+pub const BACKUP: &str = "backup";
+// When the last backup was written or attempted, and how it went
+pub const BACKUP_STATE: &str = "backup_state";
+pub const SYNC: &str = "sync";
+// This device's sync changelog and the library as of its last sync
+pub const SYNC_STATE: &str = "sync_state";
+// When the entry state log was last compacted
+pub const STATE_LOG_COMPACTED_AT: &str = "state_log_compacted_at";
+pub const ARCHIVE: &str = "archive";
+// When old entries were last moved to the archive
+pub const ARCHIVED_AT: &str = "archived_at";
+// Stored in the main database: the profile to open at startup
+pub const ACTIVE_PROFILE: &str = "active_profile";
+// Stored in the main database: whether the connection is metered
+pub const METERED_MODE: &str = "metered_mode";
+// Stored in the main database: whether closing the window hides it to the tray
+pub const CLOSE_TO_TRAY: &str = "close_to_tray";
+// Stored in the main database: the localhost endpoint browser extensions subscribe through
+pub const SUBSCRIBE_ENDPOINT: &str = "subscribe_endpoint";
+
+// Read and decode a single setting, returning None if it has never been set
+pub async fn get_setting_value<T, C>(db: &C, key: &str) -> Result<Option<T>, String>
+where
+    T: DeserializeOwned,
+    C: ConnectionTrait,
+{
+    let setting = Setting::find_by_id(key.to_string())
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch setting '{}': {}", key, e))?;
+
+    match setting {
+        Some(setting) => serde_json::from_str(&setting.value)
+            .map(Some)
+            .map_err(|e| format!("Invalid value for setting '{}': {}", key, e)),
+        None => Ok(None),
+    }
+}
+
+// Read a setting, falling back to the default when it is unset or unreadable
+pub async fn get_setting_or<T, C>(db: &C, key: &str, default: T) -> T
+where
+    T: DeserializeOwned,
+    C: ConnectionTrait,
+{
+    match get_setting_value(db, key).await {
+        Ok(Some(value)) => value,
+        Ok(None) => default,
+        Err(e) => {
+            eprintln!("{}", e);
+            default
+        }
+    }
+}
+
+// Encode and store a setting, inserting or replacing the existing value
+pub async fn set_setting_value<T, C>(db: &C, key: &str, value: &T) -> Result<(), String>
+where
+    T: Serialize,
+    C: ConnectionTrait,
+{
+    let encoded = serde_json::to_string(value)
+        .map_err(|e| format!("Failed to encode setting '{}': {}", key, e))?;
+
+    let setting = setting::ActiveModel {
+        key: ActiveValue::Set(key.to_string()),
+        value: ActiveValue::Set(encoded),
+        updated_at: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+    };
+
+    Setting::insert(setting)
+        .on_conflict(
+            OnConflict::column(setting::Column::Key)
+                .update_columns([setting::Column::Value, setting::Column::UpdatedAt])
+                .to_owned(),
+        )
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to save setting '{}': {}", key, e))?;
+
+    Ok(())
+}
+
+pub async fn get_all_setting_values<C: ConnectionTrait>(
+    db: &C,
+) -> Result<HashMap<String, serde_json::Value>, String> {
+    let settings = Setting::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch settings: {}", e))?;
+
+    Ok(settings
+        .into_iter()
+        .filter_map(|setting| {
+            serde_json::from_str(&setting.value)
+                .ok()
+                .map(|value| (setting.key, value))
+        })
+        .collect())
+}
+
+fn decode_setting<T: DeserializeOwned>(key: &str, value: &serde_json::Value) -> Result<T, String> {
+    serde_json::from_value(value.clone()).map_err(|e| format!("Invalid value for setting '{}': {}", key, e))
+}
+
+// Check a value before set_setting stores it under a key: it has to decode as the key's
+// setting and pass that setting's validate(). Keys the app keeps for itself, and keys no
+// setting uses, are rejected.
+pub fn validate_setting_value(key: &str, value: &serde_json::Value) -> Result<(), String> {
+    let mut fetcher = FetcherConfig::default();
+    match key {
+        FETCHER_MAX_CONCURRENT_REQUESTS => fetcher.max_concurrent_requests = decode_setting(key, value)?,
+        FETCHER_MAX_REQUESTS_PER_DOMAIN => fetcher.max_requests_per_domain = decode_setting(key, value)?,
+        FETCHER_RATE_LIMIT_DELAY_MS => fetcher.rate_limit_delay = Duration::from_millis(decode_setting(key, value)?),
+        FETCHER_REQUEST_TIMEOUT_SECS => fetcher.request_timeout = Duration::from_secs(decode_setting(key, value)?),
+        FETCHER_MAX_RETRIES => fetcher.max_retries = decode_setting(key, value)?,
+        // Checked against the other delay's default, as load_fetcher_config would combine them
+        FETCHER_BASE_RETRY_DELAY_MS => fetcher.base_retry_delay = Duration::from_millis(decode_setting(key, value)?),
+        FETCHER_MAX_RETRY_DELAY_SECS => fetcher.max_retry_delay = Duration::from_secs(decode_setting(key, value)?),
+        FETCHER_RETRY_JITTER => fetcher.retry_jitter = decode_setting(key, value)?,
+        FETCHER_MAX_REDIRECTS => fetcher.max_redirects = decode_setting(key, value)?,
+        FETCHER_CIRCUIT_BREAKER_THRESHOLD => fetcher.circuit_breaker_threshold = decode_setting(key, value)?,
+        FETCHER_CIRCUIT_BREAKER_COOLDOWN_SECS => {
+            fetcher.circuit_breaker_cooldown = Duration::from_secs(decode_setting(key, value)?)
+        }
+        FETCHER_ALLOW_PRIVATE_ADDRESSES => fetcher.allow_private_addresses = decode_setting(key, value)?,
+        FETCHER_MAX_ENTRIES_PER_FETCH => fetcher.max_entries_per_fetch = decode_setting(key, value)?,
+        NOTIFICATIONS => return decode_setting::<NotificationConfig>(key, value)?.validate(),
+        SCHEDULER => return decode_setting::<SchedulerConfig>(key, value)?.validate(),
+        DIGEST => return decode_setting::<DigestConfig>(key, value)?.validate(),
+        EMAIL => return decode_setting::<EmailConfig>(key, value)?.validate(),
+        REPUBLISH => return decode_setting::<RepublishConfig>(key, value)?.validate(),
+        BACKUP => return decode_setting::<BackupConfig>(key, value)?.validate(),
+        SYNC => return decode_setting::<SyncConfig>(key, value)?.validate(),
+        ARCHIVE => return decode_setting::<ArchiveConfig>(key, value)?.validate(),
+        READING => return decode_setting::<ReadingConfig>(key, value).map(drop),
+        RETENTION => return decode_setting::<RetentionConfig>(key, value).map(drop),
+        PRIVACY => return decode_setting::<PrivacyConfig>(key, value).map(drop),
+        TRANSLATION => return decode_setting::<TranslationConfig>(key, value).map(drop),
+        CLASSIFIER => return decode_setting::<ClassifierConfig>(key, value).map(drop),
+        SOCIAL => return decode_setting::<SocialConfig>(key, value).map(drop),
+        DOMAIN_RULES => return decode_setting::<DomainRulesConfig>(key, value).map(drop),
+        MUTE_RULES => return decode_setting::<MuteRulesConfig>(key, value).map(drop),
+        DISPLAY_POLICY => return decode_setting::<DisplayPolicyConfig>(key, value).map(drop),
+        DATA_DIRECTORY | HEALTH_DIGEST | DIGEST_SCHEDULE | BACKUP_STATE | SYNC_STATE | STATE_LOG_COMPACTED_AT
+        | ARCHIVED_AT | ACTIVE_PROFILE | METERED_MODE | CLOSE_TO_TRAY | SUBSCRIBE_ENDPOINT => {
+            return Err(format!("Setting '{}' has its own command and can't be set directly", key));
+        }
+        _ => return Err(format!("Unknown setting '{}'", key)),
+    }
+    fetcher.validate()
+}
+
+// Build the fetcher configuration from stored settings, using defaults for anything unset
+pub async fn load_fetcher_config<C: ConnectionTrait>(db: &C) -> FetcherConfig {
+    let defaults = FetcherConfig::default();
+
+    FetcherConfig {
+        max_concurrent_requests: get_setting_or(db, FETCHER_MAX_CONCURRENT_REQUESTS, defaults.max_concurrent_requests).await,
+        max_requests_per_domain: get_setting_or(db, FETCHER_MAX_REQUESTS_PER_DOMAIN, defaults.max_requests_per_domain).await,
+        rate_limit_delay: Duration::from_millis(
+            get_setting_or(db, FETCHER_RATE_LIMIT_DELAY_MS, defaults.rate_limit_delay.as_millis() as u64).await,
+        ),
+        request_timeout: Duration::from_secs(
+            get_setting_or(db, FETCHER_REQUEST_TIMEOUT_SECS, defaults.request_timeout.as_secs()).await,
+        ),
+        max_retries: get_setting_or(db, FETCHER_MAX_RETRIES, defaults.max_retries).await,
+        base_retry_delay: Duration::from_millis(
+            get_setting_or(db, FETCHER_BASE_RETRY_DELAY_MS, defaults.base_retry_delay.as_millis() as u64).await,
+        ),
+        max_retry_delay: Duration::from_secs(
+            get_setting_or(db, FETCHER_MAX_RETRY_DELAY_SECS, defaults.max_retry_delay.as_secs()).await,
+        ),
+        retry_jitter: get_setting_or(db, FETCHER_RETRY_JITTER, defaults.retry_jitter).await,
+        max_redirects: get_setting_or(db, FETCHER_MAX_REDIRECTS, defaults.max_redirects).await,
+        circuit_breaker_threshold: get_setting_or(db, FETCHER_CIRCUIT_BREAKER_THRESHOLD, defaults.circuit_breaker_threshold).await,
+        circuit_breaker_cooldown: Duration::from_secs(
+            get_setting_or(db, FETCHER_CIRCUIT_BREAKER_COOLDOWN_SECS, defaults.circuit_breaker_cooldown.as_secs()).await,
+        ),
+        allow_private_addresses: get_setting_or(db, FETCHER_ALLOW_PRIVATE_ADDRESSES, defaults.allow_private_addresses).await,
+        max_entries_per_fetch: get_setting_or(db, FETCHER_MAX_ENTRIES_PER_FETCH, defaults.max_entries_per_fetch).await,
+        transport: defaults.transport,
+    }
+}
+
+pub async fn load_notification_config<C: ConnectionTrait>(db: &C) -> NotificationConfig {
+    get_setting_or(db, NOTIFICATIONS, NotificationConfig::default()).await
+}
+
+pub async fn load_scheduler_config<C: ConnectionTrait>(db: &C) -> SchedulerConfig {
+    get_setting_or(db, SCHEDULER, SchedulerConfig::default()).await
+}
+
+pub async fn load_privacy_config<C: ConnectionTrait>(db: &C) -> PrivacyConfig {
+    get_setting_or(db, PRIVACY, PrivacyConfig::default()).await
+}
+
+pub async fn load_reading_config<C: ConnectionTrait>(db: &C) -> ReadingConfig {
+    get_setting_or(db, READING, ReadingConfig::default()).await
+}
+
+pub async fn load_retention_config<C: ConnectionTrait>(db: &C) -> RetentionConfig {
+    get_setting_or(db, RETENTION, RetentionConfig::default()).await
+}
+
+pub async fn load_translation_config<C: ConnectionTrait>(db: &C) -> TranslationConfig {
+    get_setting_or(db, TRANSLATION, TranslationConfig::default()).await
+}
+
+pub async fn load_classifier_config<C: ConnectionTrait>(db: &C) -> ClassifierConfig {
+    get_setting_or(db, CLASSIFIER, ClassifierConfig::default()).await
+}
+
+pub async fn load_digest_config<C: ConnectionTrait>(db: &C) -> DigestConfig {
+    get_setting_or(db, DIGEST, DigestConfig::default()).await
+}
+
+pub async fn load_email_config<C: ConnectionTrait>(db: &C) -> EmailConfig {
+    get_setting_or(db, EMAIL, EmailConfig::default()).await
+}
+
+pub async fn load_republish_config<C: ConnectionTrait>(db: &C) -> RepublishConfig {
+    get_setting_or(db, REPUBLISH, RepublishConfig::default()).await
+}
+
+pub async fn load_subscribe_endpoint_config<C: ConnectionTrait>(home_db: &C) -> SubscribeEndpointConfig {
+    get_setting_or(home_db, SUBSCRIBE_ENDPOINT, SubscribeEndpointConfig::default()).await
+}
+
+pub async fn load_social_config<C: ConnectionTrait>(db: &C) -> SocialConfig {
+    get_setting_or(db, SOCIAL, SocialConfig::default()).await
+}
+
+pub async fn load_domain_rules_config<C: ConnectionTrait>(db: &C) -> DomainRulesConfig {
+    get_setting_or(db, DOMAIN_RULES, DomainRulesConfig::default()).await
+}
+
+pub async fn load_mute_rules_config<C: ConnectionTrait>(db: &C) -> MuteRulesConfig {
+    get_setting_or(db, MUTE_RULES, MuteRulesConfig::default()).await
+}
+
+pub async fn load_display_policy_config<C: ConnectionTrait>(db: &C) -> DisplayPolicyConfig {
+    get_setting_or(db, DISPLAY_POLICY, DisplayPolicyConfig::default()).await
+}
+
+pub async fn load_backup_config<C: ConnectionTrait>(db: &C) -> BackupConfig {
+    get_setting_or(db, BACKUP, BackupConfig::default()).await
+}
+
+pub async fn load_sync_config<C: ConnectionTrait>(db: &C) -> SyncConfig {
+    get_setting_or(db, SYNC, SyncConfig::default()).await
+}
+
+pub async fn load_archive_config<C: ConnectionTrait>(db: &C) -> ArchiveConfig {
+    get_setting_or(db, ARCHIVE, ArchiveConfig::default()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_settings_are_checked_against_their_types() {
+        assert!(validate_setting_value(FETCHER_MAX_CONCURRENT_REQUESTS, &json!(4)).is_ok());
+        assert!(validate_setting_value(FETCHER_MAX_CONCURRENT_REQUESTS, &json!(0)).is_err());
+        assert!(validate_setting_value(FETCHER_MAX_CONCURRENT_REQUESTS, &json!("four")).is_err());
+        assert!(validate_setting_value(FETCHER_RETRY_JITTER, &json!(1.5)).is_err());
+        assert!(validate_setting_value(SCHEDULER, &json!({ "min_interval_minutes": 0 })).is_err());
+        assert!(validate_setting_value(READING, &json!({ "mark_read_on_scroll": true })).is_ok());
+        assert!(validate_setting_value(READING, &json!({ "mark_read_on_scroll": "yes" })).is_err());
+
+        assert_eq!(
+            validate_setting_value("fetcher.max_concurent_requests", &json!(4)),
+            Err("Unknown setting 'fetcher.max_concurent_requests'".to_string())
+        );
+        assert!(validate_setting_value(ACTIVE_PROFILE, &json!("Work")).is_err());
+    }
+}
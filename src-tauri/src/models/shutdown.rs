@@ -0,0 +1,56 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, ExitRequestApi, Manager};
+use crate::models::state::AppState;
+
+// How long quitting waits for in-flight fetches and queued writes before abandoning them
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+const RUNNING: u8 = 0;
+const SHUTTING_DOWN: u8 = 1;
+const SHUT_DOWN: u8 = 2;
+
+static SHUTDOWN_PHASE: AtomicU8 = AtomicU8::new(RUNNING);
+
+// Stop background work before the app exits: the scheduler stops picking up feeds, in-flight
+// fetches get a grace period to finish and save, pending writes are flushed and the database
+// pools are closed. Feeds that weren't fetched stay due and are picked up on the next launch.
+pub async fn shutdown_background_work(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    println!("👋 Shutting down background workers");
+
+    let _ = state.shutdown_signal.send(true);
+
+    if let Some(fetcher) = &state.async_fetcher {
+        if !fetcher.shutdown(SHUTDOWN_GRACE_PERIOD).await {
+            eprintln!("❌ Fetches still running after {:?}, abandoning them", SHUTDOWN_GRACE_PERIOD);
+        }
+    }
+
+    // Profiles share the main database's pool when there is only the default one; closing twice is fine
+    for db in [state.db().await, state.home_db.clone()] {
+        if let Err(e) = db.close().await {
+            eprintln!("❌ Failed to close database: {}", e);
+        }
+    }
+
+    println!("✅ Background workers stopped");
+}
+
+// Hold the first exit request until background work has shut down, then exit for real.
+// Further requests while shutting down are held too.
+pub fn handle_exit_requested(app: &AppHandle, api: &ExitRequestApi, code: Option<i32>) {
+    match SHUTDOWN_PHASE.compare_exchange(RUNNING, SHUTTING_DOWN, Ordering::SeqCst, Ordering::SeqCst) {
+        Ok(_) => {
+            api.prevent_exit();
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                shutdown_background_work(&app).await;
+                SHUTDOWN_PHASE.store(SHUT_DOWN, Ordering::SeqCst);
+                app.exit(code.unwrap_or(0));
+            });
+        }
+        Err(SHUTTING_DOWN) => api.prevent_exit(),
+        Err(_) => {}
+    }
+}
@@ -0,0 +1,303 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use chrono::NaiveDateTime;
+use kuchikiki::traits::TendrilSink;
+use sea_orm::*;
+use sea_orm::sea_query::{Expr, OnConflict, Query};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use url::Url;
+use crate::entities::{prelude::*, *};
+use crate::models::content_extraction::{extract_content, fetch_page_html};
+use crate::models::cookie_jar::feed_cookie_header;
+use crate::models::data_directory::{directory_size, resolve_data_directory};
+use crate::models::image_proxy::{image_cache_key, is_proxied_image_url, load_proxied_image, original_image_url, proxied_image_url, write_cached_image};
+use crate::models::sanitizer::{effective_image_policy, sanitize_html};
+use crate::models::settings::load_privacy_config;
+use crate::models::state::AppState;
+
+// Snapshotted images live under the data directory, named like the image cache's
+pub const SNAPSHOTS_DIR: &str = "snapshots";
+
+// Starred entries snapshotted per run. Entries starred in bulk, by sync or in metered mode are
+// picked up by the scheduler over the following ticks.
+const SNAPSHOT_BATCH_SIZE: u64 = 10;
+
+// Set while a batch is being snapshotted, so the scheduler and starring don't snapshot the
+// same entries side by side
+static SNAPSHOTTING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntrySnapshotResponse {
+    pub entry_id: i32,
+    pub feed_title: Option<String>,
+    pub title: String,
+    pub link: String,
+    // Sanitized HTML; its images load through the image proxy, which serves the saved copies
+    pub content: String,
+    pub image_urls: Vec<String>,
+    pub snapshotted_at: NaiveDateTime,
+}
+
+impl From<entry_snapshot::Model> for EntrySnapshotResponse {
+    fn from(model: entry_snapshot::Model) -> Self {
+        EntrySnapshotResponse {
+            entry_id: model.entry_id,
+            feed_title: model.feed_title,
+            title: model.title,
+            link: model.link,
+            content: model.content,
+            image_urls: serde_json::from_str(&model.image_urls).unwrap_or_default(),
+            snapshotted_at: model.snapshotted_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotStorageUsage {
+    pub snapshot_count: u64,
+    // Characters of snapshotted HTML in the database
+    pub content_bytes: u64,
+    // Snapshotted images on disk
+    pub image_bytes: u64,
+    pub total_bytes: u64,
+}
+
+// Where a snapshotted image is saved
+pub fn snapshot_image_path(data_directory: &Path, url: &str) -> PathBuf {
+    data_directory.join(SNAPSHOTS_DIR).join(image_cache_key(url))
+}
+
+// Point every image in the HTML at the image proxy, returning the rewritten HTML and the
+// images' original URLs. Images already going through the proxy keep their URLs.
+pub fn localize_images(html: &str) -> (String, Vec<String>) {
+    let mut image_urls: Vec<String> = Vec::new();
+    let mut localize = |url: &str| -> String {
+        let original = if is_proxied_image_url(url) {
+            original_image_url(url)
+        } else {
+            Url::parse(url)
+                .ok()
+                .filter(|url| matches!(url.scheme(), "http" | "https"))
+                .map(|url| url.to_string())
+        };
+        match original {
+            Some(original) => {
+                if !image_urls.contains(&original) {
+                    image_urls.push(original.clone());
+                }
+                proxied_image_url(&original)
+            }
+            None => url.to_string(),
+        }
+    };
+
+    let document = kuchikiki::parse_html().one(html);
+    if let Ok(images) = document.select("img, picture source") {
+        for image in images {
+            let mut attributes = image.attributes.borrow_mut();
+            if let Some(src) = attributes.get("src").map(str::to_string) {
+                attributes.insert("src", localize(&src));
+            }
+            if let Some(srcset) = attributes.get("srcset").map(str::to_string) {
+                let localized: Vec<String> = srcset
+                    .split(',')
+                    .filter_map(|candidate| {
+                        let mut parts = candidate.split_whitespace();
+                        let url = parts.next()?;
+                        Some(std::iter::once(localize(url)).chain(parts.map(str::to_string)).collect::<Vec<_>>().join(" "))
+                    })
+                    .collect();
+                attributes.insert("srcset", localized.join(", "));
+            }
+        }
+    }
+
+    let content = match document.select_first("body") {
+        Ok(body) => body.as_node().children().map(|child| child.to_string()).collect(),
+        Err(_) => html.to_string(),
+    };
+    (content, image_urls)
+}
+
+// The article from the entry's page, with the feed's extraction rule, cookies and transport
+async fn fetch_article(app: &AppHandle, feed: &feed::Model, entry: &feed_entry::Model) -> Option<String> {
+    let state = app.state::<AppState>();
+    let fetcher = state.async_fetcher.as_ref()?;
+    let transport = fetcher.transport_for(&feed.url).await;
+    let cookies = feed_cookie_header(feed.id, &entry.link).await;
+    let html = fetch_page_html(transport.as_ref(), &entry.link, cookies).await.ok()?;
+    extract_content(&html, feed.extraction_rule.as_deref()).ok().flatten()
+}
+
+// Snapshot an entry: the article from its page, or the feed's content when the page can't be
+// fetched or has no recognisable article, and every image in it. An image that can't be
+// downloaded is left out, and loads from the publisher for as long as it's there.
+async fn snapshot_entry(app: &AppHandle, db: &DatabaseConnection, entry: &feed_entry::Model) -> Result<(), String> {
+    let feed = Feed::find_by_id(entry.feed_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed: {}", e))?
+        .ok_or("Feed not found")?;
+
+    let html = match fetch_article(app, &feed, entry).await {
+        Some(article) => {
+            let default_image_policy = load_privacy_config(db).await.image_policy;
+            let image_policy = effective_image_policy(feed.image_policy.as_deref(), default_image_policy);
+            sanitize_html(&article, Some(&entry.link), image_policy)
+        }
+        None => entry.content.clone().or_else(|| entry.description.clone()).unwrap_or_default(),
+    };
+    let (content, image_urls) = localize_images(&html);
+
+    let data_directory = resolve_data_directory(app, db).await?;
+    let mut saved_image_urls = Vec::new();
+    for url in image_urls {
+        let image_path = snapshot_image_path(&data_directory, &url);
+        if tokio::fs::try_exists(&image_path).await.unwrap_or(false) {
+            saved_image_urls.push(url);
+            continue;
+        }
+        let saved = match load_proxied_image(app, &url).await {
+            Ok((bytes, content_type)) => write_cached_image(&image_path, &bytes, &content_type).await.map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        };
+        match saved {
+            Ok(()) => saved_image_urls.push(url),
+            Err(e) => eprintln!("⚠️ Snapshot of entry {} is missing an image: {}", entry.id, e),
+        }
+    }
+    let image_urls = serde_json::to_string(&saved_image_urls).map_err(|e| format!("Failed to serialize image URLs: {}", e))?;
+
+    let snapshot = entry_snapshot::ActiveModel {
+        entry_id: ActiveValue::Set(entry.id),
+        feed_title: ActiveValue::Set(feed.title),
+        title: ActiveValue::Set(entry.title.clone()),
+        link: ActiveValue::Set(entry.link.clone()),
+        content: ActiveValue::Set(content),
+        image_urls: ActiveValue::Set(image_urls),
+        snapshotted_at: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+        ..Default::default()
+    };
+    EntrySnapshot::insert(snapshot)
+        .on_conflict(OnConflict::column(entry_snapshot::Column::EntryId).do_nothing().to_owned())
+        .do_nothing()
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to save snapshot: {}", e))?;
+    Ok(())
+}
+
+async fn snapshot_batch(app: &AppHandle, db: &DatabaseConnection) -> Result<usize, String> {
+    let entries = FeedEntry::find()
+        .filter(feed_entry::Column::IsStarred.eq(true))
+        .filter(
+            feed_entry::Column::Id.not_in_subquery(
+                Query::select().column(entry_snapshot::Column::EntryId).from(EntrySnapshot).to_owned(),
+            ),
+        )
+        .order_by_desc(feed_entry::Column::StarredAt)
+        .limit(SNAPSHOT_BATCH_SIZE)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch starred entries: {}", e))?;
+
+    let mut snapshotted = 0;
+    for entry in &entries {
+        match snapshot_entry(app, db, entry).await {
+            Ok(()) => snapshotted += 1,
+            Err(e) => eprintln!("❌ Failed to snapshot entry {}: {}", entry.id, e),
+        }
+    }
+    if snapshotted > 0 {
+        println!("📸 Snapshotted {} starred entries", snapshotted);
+    }
+    Ok(snapshotted)
+}
+
+// Snapshot the most recently starred entries that don't have a snapshot yet. Does nothing in
+// metered mode, or while another batch is running. Returns how many were snapshotted.
+pub async fn snapshot_starred_entries(app: &AppHandle, db: &DatabaseConnection) -> Result<usize, String> {
+    if *app.state::<AppState>().metered_mode.read().await {
+        return Ok(0);
+    }
+    if SNAPSHOTTING.swap(true, Ordering::AcqRel) {
+        return Ok(0);
+    }
+    let snapshotted = snapshot_batch(app, db).await;
+    SNAPSHOTTING.store(false, Ordering::Release);
+    snapshotted
+}
+
+pub async fn load_entry_snapshot<C: ConnectionTrait>(db: &C, entry_id: i32) -> Result<Option<EntrySnapshotResponse>, String> {
+    Ok(EntrySnapshot::find()
+        .filter(entry_snapshot::Column::EntryId.eq(entry_id))
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch snapshot: {}", e))?
+        .map(EntrySnapshotResponse::from))
+}
+
+pub async fn load_snapshot_storage_usage<C: ConnectionTrait>(app: &AppHandle, db: &C) -> Result<SnapshotStorageUsage, String> {
+    let snapshot_count = EntrySnapshot::find()
+        .count(db)
+        .await
+        .map_err(|e| format!("Failed to count snapshots: {}", e))?;
+    let content_bytes: Option<i64> = EntrySnapshot::find()
+        .select_only()
+        .column_as(Expr::cust("SUM(LENGTH(content))"), "content_bytes")
+        .into_tuple::<Option<i64>>()
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to measure snapshots: {}", e))?
+        .flatten();
+    let content_bytes = content_bytes.unwrap_or(0).max(0) as u64;
+
+    let snapshots_dir = resolve_data_directory(app, db).await?.join(SNAPSHOTS_DIR);
+    let image_bytes = tokio::task::spawn_blocking(move || directory_size(&snapshots_dir))
+        .await
+        .map_err(|e| format!("Failed to measure snapshot images: {}", e))?
+        .map_err(|e| format!("Failed to measure snapshot images: {}", e))?;
+
+    Ok(SnapshotStorageUsage {
+        snapshot_count,
+        content_bytes,
+        image_bytes,
+        total_bytes: content_bytes + image_bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_images_are_pointed_at_the_proxy() {
+        let proxied = proxied_image_url("https://cdn.example.com/b.png");
+        let html = format!(
+            r#"<p>Intro</p><img src="https://example.com/a.png" srcset="https://example.com/a.png 1x, https://example.com/a@2x.png 2x"><img src="{}"><img src="data:image/gif;base64,R0lGOD">"#,
+            proxied
+        );
+
+        let (content, image_urls) = localize_images(&html);
+
+        assert_eq!(
+            image_urls,
+            vec![
+                "https://example.com/a.png".to_string(),
+                "https://example.com/a@2x.png".to_string(),
+                "https://cdn.example.com/b.png".to_string(),
+            ]
+        );
+        assert!(content.starts_with("<p>Intro</p>"));
+        assert!(!content.contains(r#"src="https://example.com/a.png""#));
+        assert!(content.contains(&format!("{} 2x", proxied_image_url("https://example.com/a@2x.png"))));
+        assert!(content.contains("data:image/gif"));
+    }
+
+    #[test]
+    fn test_snapshot_images_share_the_cache_naming() {
+        let path = snapshot_image_path(Path::new("/data"), "https://example.com/a.png");
+        assert_eq!(path, Path::new("/data").join(SNAPSHOTS_DIR).join(image_cache_key("https://example.com/a.png")));
+    }
+}
@@ -0,0 +1,469 @@
+use std::time::Duration;
+use quick_xml::escape::escape;
+use serde::{Deserialize, Serialize};
+use tauri_plugin_http::reqwest;
+use url::Url;
+use crate::entities::feed_entry;
+use crate::models::canonical_links::share_link;
+use crate::models::exporters::{escape_markdown_text, markdown_link_target};
+use crate::models::secrets::{delete_secret_value, get_secret_value, store_secret_value, SecretKind};
+
+const SOCIAL_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Mastodon shows the authorization code to paste back instead of redirecting to a web page
+const MASTODON_REDIRECT_URI: &str = "urn:ietf:wg:oauth:2.0:oob";
+const MASTODON_SCOPES: &str = "write:statuses";
+const MASTODON_MAX_CHARS: usize = 500;
+// Mastodon counts every link as this many characters, however long it is
+const MASTODON_LINK_CHARS: usize = 23;
+
+// Bluesky's limit is 300 graphemes; counting characters instead never exceeds it
+const BLUESKY_MAX_CHARS: usize = 300;
+
+// Names of the credentials in the keychain, stored as IntegrationApiKey secrets
+const MASTODON_CLIENT_SECRET: &str = "mastodon_client_secret";
+const MASTODON_ACCESS_TOKEN: &str = "mastodon";
+const BLUESKY_APP_PASSWORD: &str = "bluesky";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SocialNetwork {
+    Mastodon,
+    Bluesky,
+}
+
+impl SocialNetwork {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SocialNetwork::Mastodon => "mastodon",
+            SocialNetwork::Bluesky => "bluesky",
+        }
+    }
+}
+
+// Accounts entries are shared to. Tokens and passwords are kept in the keychain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SocialConfig {
+    // e.g. "https://mastodon.social"
+    pub mastodon_instance: Option<String>,
+    // Client id of the app registered on the instance while connecting
+    pub mastodon_client_id: Option<String>,
+    pub mastodon_connected: bool,
+    pub bluesky_handle: Option<String>,
+    // Personal data server the account lives on
+    pub bluesky_service: String,
+}
+
+impl Default for SocialConfig {
+    fn default() -> Self {
+        Self {
+            mastodon_instance: None,
+            mastodon_client_id: None,
+            mastodon_connected: false,
+            bluesky_handle: None,
+            bluesky_service: "https://bsky.social".to_string(),
+        }
+    }
+}
+
+// An instance address reduced to its origin, accepting a bare domain ("mastodon.social")
+pub fn normalize_instance_url(instance: &str) -> Result<String, String> {
+    let instance = instance.trim();
+    let with_scheme = if instance.contains("://") {
+        instance.to_string()
+    } else {
+        format!("https://{}", instance)
+    };
+    let url = Url::parse(&with_scheme).map_err(|e| format!("Invalid instance address: {}", e))?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return Err(format!("Invalid instance address: {}", instance));
+    }
+    Ok(url.origin().ascii_serialization())
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    truncated = truncated.trim_end().to_string();
+    truncated.push('…');
+    truncated
+}
+
+// The post for a shared entry: the comment, then the title and link. The title is shortened
+// to fit the network's length limit; a comment that doesn't fit is an error.
+pub fn compose_post_text(network: SocialNetwork, title: &str, link: &str, comment: Option<&str>) -> Result<String, String> {
+    let (max_chars, link_chars) = match network {
+        SocialNetwork::Mastodon => (MASTODON_MAX_CHARS, MASTODON_LINK_CHARS),
+        SocialNetwork::Bluesky => (BLUESKY_MAX_CHARS, link.chars().count()),
+    };
+    let comment = comment.map(str::trim).filter(|comment| !comment.is_empty());
+
+    // "comment\n\ntitle\nlink"
+    let fixed_chars = comment.map(|comment| comment.chars().count() + 2).unwrap_or(0) + 1 + link_chars;
+    if fixed_chars >= max_chars {
+        return Err(format!("Comment is too long to share on {}", network.as_str()));
+    }
+    let title = truncate_chars(title.trim(), max_chars - fixed_chars);
+
+    let mut text = String::new();
+    if let Some(comment) = comment {
+        text.push_str(comment);
+        text.push_str("\n\n");
+    }
+    text.push_str(&title);
+    text.push('\n');
+    text.push_str(link);
+    Ok(text)
+}
+
+// Text formats an entry can be copied or handed to another app in
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareFormat {
+    PlainText,
+    Markdown,
+    Html,
+    // The selected text in quotes, cited with the entry's title, link and date
+    Quote,
+}
+
+// An entry as a snippet to share: its title and link, and where and when it was published.
+// Only quotes use the selected text, and they need some.
+pub fn format_entry_snippet(
+    entry: &feed_entry::Model,
+    feed_title: Option<&str>,
+    format: ShareFormat,
+    selected_text: Option<&str>,
+) -> Result<String, String> {
+    let title = entry.title.trim();
+    let link = share_link(entry);
+    let date = entry.published_at.unwrap_or(entry.created_at).format("%Y-%m-%d").to_string();
+    let source = match feed_title.map(str::trim).filter(|feed_title| !feed_title.is_empty()) {
+        Some(feed_title) => format!("{}, {}", feed_title, date),
+        None => date,
+    };
+
+    Ok(match format {
+        ShareFormat::PlainText => format!("{} ({})\n{}", title, source, link),
+        ShareFormat::Markdown => format!("[{}]({}) ({})", escape_markdown_text(title), markdown_link_target(link), escape_markdown_text(&source)),
+        ShareFormat::Html => format!("<a href=\"{}\">{}</a> ({})", escape(link), escape(title), escape(&source)),
+        ShareFormat::Quote => {
+            let quote = selected_text
+                .map(str::trim)
+                .filter(|quote| !quote.is_empty())
+                .ok_or("Select some text to quote")?;
+            format!("“{}”\n— {} ({})\n{}", quote, title, source, link)
+        }
+    })
+}
+
+pub fn mastodon_authorize_url(instance: &str, client_id: &str) -> Result<String, String> {
+    let mut url = Url::parse(&format!("{}/oauth/authorize", instance)).map_err(|e| format!("Invalid instance address: {}", e))?;
+    url.query_pairs_mut()
+        .append_pair("client_id", client_id)
+        .append_pair("scope", MASTODON_SCOPES)
+        .append_pair("redirect_uri", MASTODON_REDIRECT_URI)
+        .append_pair("response_type", "code");
+    Ok(url.to_string())
+}
+
+// Bluesky doesn't detect links in post text; the link's byte range is marked with a facet
+pub fn bluesky_link_facet(text: &str, link: &str) -> Option<serde_json::Value> {
+    let start = text.rfind(link)?;
+    Some(serde_json::json!({
+        "index": { "byteStart": start, "byteEnd": start + link.len() },
+        "features": [{ "$type": "app.bsky.richtext.facet#link", "uri": link }],
+    }))
+}
+
+async fn post_json<T: for<'de> Deserialize<'de>>(
+    request: reqwest::RequestBuilder,
+    body: &serde_json::Value,
+) -> Result<T, String> {
+    let response = request
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body.to_string())
+        .timeout(SOCIAL_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    let status = response.status();
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() {
+        return Err(format!("Request failed with {}: {}", status, String::from_utf8_lossy(&bytes)));
+    }
+    serde_json::from_slice(&bytes).map_err(|e| format!("Invalid response: {}", e))
+}
+
+async fn integration_secret(name: &str) -> Result<Option<String>, String> {
+    get_secret_value(SecretKind::IntegrationApiKey, name.to_string()).await
+}
+
+#[derive(Deserialize)]
+struct MastodonApp {
+    client_id: String,
+    client_secret: String,
+}
+
+#[derive(Deserialize)]
+struct MastodonToken {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct MastodonStatus {
+    url: Option<String>,
+}
+
+// Register this app on a Mastodon instance and return the page where the user authorizes it.
+// The config is updated with the instance and client id; the caller saves it.
+pub async fn start_mastodon_authorization(config: &mut SocialConfig, instance: &str) -> Result<String, String> {
+    let instance = normalize_instance_url(instance)?;
+    let app: MastodonApp = post_json(
+        reqwest::Client::new().post(format!("{}/api/v1/apps", instance)),
+        &serde_json::json!({
+            "client_name": "Reader",
+            "redirect_uris": MASTODON_REDIRECT_URI,
+            "scopes": MASTODON_SCOPES,
+        }),
+    )
+    .await
+    .map_err(|e| format!("Failed to register with {}: {}", instance, e))?;
+
+    store_secret_value(SecretKind::IntegrationApiKey, MASTODON_CLIENT_SECRET.to_string(), app.client_secret).await?;
+    let authorize_url = mastodon_authorize_url(&instance, &app.client_id)?;
+    config.mastodon_instance = Some(instance);
+    config.mastodon_client_id = Some(app.client_id);
+    config.mastodon_connected = false;
+    Ok(authorize_url)
+}
+
+// Exchange the code shown after authorizing for an access token
+pub async fn finish_mastodon_authorization(config: &mut SocialConfig, code: &str) -> Result<(), String> {
+    let (Some(instance), Some(client_id)) = (config.mastodon_instance.as_deref(), config.mastodon_client_id.as_deref()) else {
+        return Err("Start connecting a Mastodon account first".to_string());
+    };
+    let client_secret = integration_secret(MASTODON_CLIENT_SECRET)
+        .await?
+        .ok_or("Start connecting a Mastodon account first")?;
+
+    let token: MastodonToken = post_json(
+        reqwest::Client::new().post(format!("{}/oauth/token", instance)),
+        &serde_json::json!({
+            "grant_type": "authorization_code",
+            "code": code.trim(),
+            "client_id": client_id,
+            "client_secret": client_secret,
+            "redirect_uri": MASTODON_REDIRECT_URI,
+            "scope": MASTODON_SCOPES,
+        }),
+    )
+    .await
+    .map_err(|e| format!("Failed to authorize with {}: {}", instance, e))?;
+
+    store_secret_value(SecretKind::IntegrationApiKey, MASTODON_ACCESS_TOKEN.to_string(), token.access_token).await?;
+    config.mastodon_connected = true;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlueskySession {
+    access_jwt: String,
+    did: String,
+    handle: String,
+}
+
+#[derive(Deserialize)]
+struct BlueskyRecord {
+    uri: String,
+}
+
+async fn create_bluesky_session(service: &str, handle: &str, app_password: &str) -> Result<BlueskySession, String> {
+    post_json(
+        reqwest::Client::new().post(format!("{}/xrpc/com.atproto.server.createSession", service.trim_end_matches('/'))),
+        &serde_json::json!({ "identifier": handle, "password": app_password }),
+    )
+    .await
+    .map_err(|e| format!("Failed to sign in to Bluesky: {}", e))
+}
+
+// Check the handle and app password by signing in, then keep the password in the keychain.
+// The config is updated with the handle; the caller saves it.
+pub async fn connect_bluesky(config: &mut SocialConfig, handle: &str, app_password: String) -> Result<(), String> {
+    let handle = handle.trim().trim_start_matches('@');
+    let session = create_bluesky_session(&config.bluesky_service, handle, &app_password).await?;
+
+    store_secret_value(SecretKind::IntegrationApiKey, BLUESKY_APP_PASSWORD.to_string(), app_password).await?;
+    config.bluesky_handle = Some(session.handle);
+    Ok(())
+}
+
+// Forget an account's credentials. The config is updated; the caller saves it.
+pub async fn disconnect_social_account(config: &mut SocialConfig, network: SocialNetwork) -> Result<(), String> {
+    match network {
+        SocialNetwork::Mastodon => {
+            for name in [MASTODON_ACCESS_TOKEN, MASTODON_CLIENT_SECRET] {
+                delete_secret_value(SecretKind::IntegrationApiKey, name.to_string()).await?;
+            }
+            config.mastodon_instance = None;
+            config.mastodon_client_id = None;
+            config.mastodon_connected = false;
+        }
+        SocialNetwork::Bluesky => {
+            delete_secret_value(SecretKind::IntegrationApiKey, BLUESKY_APP_PASSWORD.to_string()).await?;
+            config.bluesky_handle = None;
+        }
+    }
+    Ok(())
+}
+
+// Post the text to a connected account, returning the post's web address when known
+pub async fn publish_post(config: &SocialConfig, network: SocialNetwork, text: &str, link: &str) -> Result<Option<String>, String> {
+    match network {
+        SocialNetwork::Mastodon => {
+            let instance = config
+                .mastodon_instance
+                .as_deref()
+                .filter(|_| config.mastodon_connected)
+                .ok_or("No Mastodon account is connected")?;
+            let access_token = integration_secret(MASTODON_ACCESS_TOKEN)
+                .await?
+                .ok_or("No Mastodon account is connected")?;
+
+            let status: MastodonStatus = post_json(
+                reqwest::Client::new()
+                    .post(format!("{}/api/v1/statuses", instance))
+                    .bearer_auth(access_token),
+                &serde_json::json!({ "status": text }),
+            )
+            .await
+            .map_err(|e| format!("Failed to post to Mastodon: {}", e))?;
+            Ok(status.url)
+        }
+        SocialNetwork::Bluesky => {
+            let handle = config.bluesky_handle.as_deref().ok_or("No Bluesky account is connected")?;
+            let app_password = integration_secret(BLUESKY_APP_PASSWORD)
+                .await?
+                .ok_or("No Bluesky account is connected")?;
+            let session = create_bluesky_session(&config.bluesky_service, handle, &app_password).await?;
+
+            let mut post = serde_json::json!({
+                "$type": "app.bsky.feed.post",
+                "text": text,
+                "createdAt": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            });
+            if let Some(facet) = bluesky_link_facet(text, link) {
+                post["facets"] = serde_json::json!([facet]);
+            }
+            let record: BlueskyRecord = post_json(
+                reqwest::Client::new()
+                    .post(format!("{}/xrpc/com.atproto.repo.createRecord", config.bluesky_service.trim_end_matches('/')))
+                    .bearer_auth(session.access_jwt),
+                &serde_json::json!({
+                    "repo": session.did,
+                    "collection": "app.bsky.feed.post",
+                    "record": post,
+                }),
+            )
+            .await
+            .map_err(|e| format!("Failed to post to Bluesky: {}", e))?;
+
+            // at://<did>/app.bsky.feed.post/<record key>
+            let post_url = record
+                .uri
+                .rsplit('/')
+                .next()
+                .map(|record_key| format!("https://bsky.app/profile/{}/post/{}", session.handle, record_key));
+            Ok(post_url)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::fixtures;
+
+    #[test]
+    fn test_instance_urls_are_normalized() {
+        assert_eq!(normalize_instance_url("mastodon.social").unwrap(), "https://mastodon.social");
+        assert_eq!(normalize_instance_url(" https://fosstodon.org/about ").unwrap(), "https://fosstodon.org");
+        assert!(normalize_instance_url("ftp://example.com").is_err());
+
+        let authorize_url = mastodon_authorize_url("https://mastodon.social", "abc").unwrap();
+        assert!(authorize_url.starts_with("https://mastodon.social/oauth/authorize?client_id=abc&scope=write%3Astatuses"));
+        assert!(authorize_url.contains("redirect_uri=urn%3Aietf%3Awg%3Aoauth%3A2.0%3Aoob"));
+    }
+
+    #[test]
+    fn test_entry_snippets_in_each_format() {
+        let published_at = chrono::NaiveDate::from_ymd_opt(2024, 6, 1).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let entry = feed_entry::Model {
+            title: "Rust & [friends]".to_string(),
+            link: "https://example.com/feed-redirect".to_string(),
+            published_at: Some(published_at),
+            created_at: published_at,
+            updated_at: published_at,
+            guid: "post-1".to_string(),
+            canonical_link: Some("https://example.com/posts/1".to_string()),
+            ..fixtures::feed_entry(1, 1)
+        };
+
+        assert_eq!(
+            format_entry_snippet(&entry, Some("Example Blog"), ShareFormat::PlainText, None).unwrap(),
+            "Rust & [friends] (Example Blog, 2024-06-01)\nhttps://example.com/posts/1"
+        );
+        assert_eq!(
+            format_entry_snippet(&entry, Some("Example Blog"), ShareFormat::Markdown, None).unwrap(),
+            "[Rust & \\[friends\\]](https://example.com/posts/1) (Example Blog, 2024-06-01)"
+        );
+        assert_eq!(
+            format_entry_snippet(&entry, None, ShareFormat::Html, None).unwrap(),
+            "<a href=\"https://example.com/posts/1\">Rust &amp; [friends]</a> (2024-06-01)"
+        );
+        assert_eq!(
+            format_entry_snippet(&entry, Some("Example Blog"), ShareFormat::Quote, Some(" Ownership is the key. ")).unwrap(),
+            "“Ownership is the key.”\n— Rust & [friends] (Example Blog, 2024-06-01)\nhttps://example.com/posts/1"
+        );
+        assert!(format_entry_snippet(&entry, None, ShareFormat::Quote, Some("  ")).is_err());
+    }
+
+    #[test]
+    fn test_post_text_fits_each_network() {
+        let link = format!("https://example.com/{}", "a".repeat(100));
+        let title = "t".repeat(600);
+
+        let mastodon = compose_post_text(SocialNetwork::Mastodon, &title, &link, Some("Worth a read")).unwrap();
+        assert!(mastodon.starts_with("Worth a read\n\nttt"));
+        assert!(mastodon.ends_with(&format!("…\n{}", link)));
+        // The link counts as 23 characters on Mastodon
+        assert_eq!(mastodon.chars().count() - link.chars().count() + MASTODON_LINK_CHARS, MASTODON_MAX_CHARS);
+
+        let bluesky = compose_post_text(SocialNetwork::Bluesky, &title, &link, None).unwrap();
+        assert_eq!(bluesky.chars().count(), BLUESKY_MAX_CHARS);
+
+        assert_eq!(
+            compose_post_text(SocialNetwork::Bluesky, "Short", "https://example.com", Some("  ")).unwrap(),
+            "Short\nhttps://example.com"
+        );
+        assert!(compose_post_text(SocialNetwork::Bluesky, "Short", &link, Some(&"c".repeat(300))).is_err());
+    }
+
+    #[test]
+    fn test_bluesky_link_facet_uses_byte_offsets() {
+        let text = "Café ☕\nhttps://example.com/x";
+        let facet = bluesky_link_facet(text, "https://example.com/x").unwrap();
+
+        assert_eq!(facet["index"]["byteStart"], 10);
+        assert_eq!(facet["index"]["byteEnd"], text.len());
+        assert_eq!(facet["features"][0]["uri"], "https://example.com/x");
+    }
+}
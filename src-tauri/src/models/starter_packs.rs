@@ -0,0 +1,175 @@
+use crate::models::importers::{ImportedFeed, ImportedSubscriptions};
+
+pub struct StarterFeed {
+    pub url: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+}
+
+// A built-in themed collection of feeds, offered while the library is still empty
+pub struct StarterPack {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub feeds: &'static [StarterFeed],
+}
+
+const STARTER_PACKS: &[StarterPack] = &[
+    StarterPack {
+        id: "news",
+        name: "World News",
+        description: "Headlines from major international newsrooms",
+        feeds: &[
+            StarterFeed {
+                url: "https://feeds.bbci.co.uk/news/world/rss.xml",
+                title: "BBC News - World",
+                description: "International news from the BBC",
+            },
+            StarterFeed {
+                url: "https://feeds.npr.org/1001/rss.xml",
+                title: "NPR News",
+                description: "Top stories from NPR",
+            },
+            StarterFeed {
+                url: "https://www.theguardian.com/world/rss",
+                title: "The Guardian - World",
+                description: "World news from the Guardian",
+            },
+            StarterFeed {
+                url: "https://www.aljazeera.com/xml/rss/all.xml",
+                title: "Al Jazeera",
+                description: "Breaking news and analysis from Al Jazeera",
+            },
+        ],
+    },
+    StarterPack {
+        id: "rust",
+        name: "Rust",
+        description: "Releases, community news and writing about the Rust language",
+        feeds: &[
+            StarterFeed {
+                url: "https://blog.rust-lang.org/feed.xml",
+                title: "Rust Blog",
+                description: "Announcements from the Rust project",
+            },
+            StarterFeed {
+                url: "https://blog.rust-lang.org/inside-rust/feed.xml",
+                title: "Inside Rust Blog",
+                description: "Updates from the Rust teams and working groups",
+            },
+            StarterFeed {
+                url: "https://this-week-in-rust.org/rss.xml",
+                title: "This Week in Rust",
+                description: "Weekly roundup of the Rust community",
+            },
+            StarterFeed {
+                url: "https://www.reddit.com/r/rust/.rss",
+                title: "r/rust",
+                description: "Discussion from the Rust subreddit",
+            },
+            StarterFeed {
+                url: "https://fasterthanli.me/index.xml",
+                title: "fasterthanli.me",
+                description: "Long-form articles on Rust and systems programming",
+            },
+        ],
+    },
+    StarterPack {
+        id: "science",
+        name: "Science",
+        description: "Research news and science journalism",
+        feeds: &[
+            StarterFeed {
+                url: "https://www.quantamagazine.org/feed/",
+                title: "Quanta Magazine",
+                description: "Mathematics, physics, biology and computer science",
+            },
+            StarterFeed {
+                url: "https://www.sciencedaily.com/rss/top/science.xml",
+                title: "ScienceDaily - Top Science",
+                description: "Top science news from ScienceDaily",
+            },
+            StarterFeed {
+                url: "https://www.nature.com/nature.rss",
+                title: "Nature",
+                description: "Latest research from the journal Nature",
+            },
+            StarterFeed {
+                url: "https://www.newscientist.com/feed/home/",
+                title: "New Scientist",
+                description: "Science and technology news from New Scientist",
+            },
+            StarterFeed {
+                url: "https://www.nasa.gov/news-release/feed/",
+                title: "NASA News Releases",
+                description: "News releases from NASA",
+            },
+        ],
+    },
+];
+
+pub fn starter_packs() -> &'static [StarterPack] {
+    STARTER_PACKS
+}
+
+pub fn find_starter_pack(id: &str) -> Option<&'static StarterPack> {
+    STARTER_PACKS.iter().find(|pack| pack.id == id)
+}
+
+impl StarterPack {
+    // The pack as an import, with every feed in a folder named after the pack
+    pub fn subscriptions(&self) -> ImportedSubscriptions {
+        ImportedSubscriptions {
+            feeds: self
+                .feeds
+                .iter()
+                .map(|feed| ImportedFeed {
+                    url: feed.url.to_string(),
+                    title: Some(feed.title.to_string()),
+                    description: Some(feed.description.to_string()),
+                    site_url: None,
+                    folder: Some(self.name.to_string()),
+                    parent_folders: Vec::new(),
+                })
+                .collect(),
+            entries: Vec::new(),
+            folders: vec![vec![self.name.to_string()]],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_packs_have_unique_ids_and_valid_feed_urls() {
+        let ids: HashSet<&str> = starter_packs().iter().map(|pack| pack.id).collect();
+        assert_eq!(ids.len(), starter_packs().len());
+        for id in ["news", "rust", "science"] {
+            assert!(find_starter_pack(id).is_some(), "missing pack {}", id);
+        }
+
+        let mut urls = HashSet::new();
+        for pack in starter_packs() {
+            assert!(!pack.feeds.is_empty());
+            for feed in pack.feeds {
+                let url = url::Url::parse(feed.url).unwrap();
+                assert_eq!(url.scheme(), "https");
+                assert!(urls.insert(feed.url), "{} is in more than one pack", feed.url);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pack_subscribes_into_its_own_folder() {
+        let pack = find_starter_pack("rust").unwrap();
+        let subscriptions = pack.subscriptions();
+
+        assert_eq!(subscriptions.feeds.len(), pack.feeds.len());
+        assert!(subscriptions.feeds.iter().all(|feed| feed.folder.as_deref() == Some("Rust")));
+        assert_eq!(subscriptions.folders, vec![vec!["Rust".to_string()]]);
+        assert!(find_starter_pack("unknown").is_none());
+    }
+}
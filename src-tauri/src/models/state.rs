@@ -1,8 +1,70 @@
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
 use sea_orm::DatabaseConnection;
+use tokio::sync::{watch, RwLock};
 use crate::models::async_feed_fetcher::AsyncFeedFetcher;
+use crate::models::bulk_undo::BulkUndo;
+use crate::models::downloads::DownloadManager;
+use crate::models::link_cleaner::PrivacyConfig;
+use crate::models::notifications::{NotificationConfig, NotificationOutbox};
+use crate::models::open_requests::OpenRequest;
+use crate::models::operations::{Operation, OperationRegistry};
+use crate::models::republish::FeedServer;
+use crate::models::scheduler::{SchedulerConfig, SchedulerStatus};
+use crate::models::subscribe_endpoint::SubscribeEndpoint;
+use crate::models::sync::SyncEngine;
+use crate::models::responses::ImportProgress;
+
+// An import whose initial fetch is still running, with what it needs to be rolled back
+pub struct ImportOperation {
+    pub progress: ImportProgress,
+    // Reports progress and carries cancellation, from cancel_import or cancel_operation
+    pub operation: Operation,
+    pub created_feed_ids: Vec<i32>,
+    pub created_folder_ids: Vec<i32>,
+}
 
 // Wrapper for database connection and async fetcher to use in Tauri state
 pub struct AppState {
-    pub db: DatabaseConnection,
+    // The active profile's library. Swapped by switch_profile, so read it through db()
+    pub db: RwLock<DatabaseConnection>,
+    // The main (DATABASE_URL) database, which holds the list of profiles
+    pub home_db: DatabaseConnection,
+    pub active_profile: RwLock<String>,
     pub async_fetcher: Option<AsyncFeedFetcher>,
-} 
\ No newline at end of file
+    pub notification_config: Arc<RwLock<NotificationConfig>>,
+    // Applies quiet hours, the hourly limit and batching to every notification
+    pub notifications: NotificationOutbox,
+    pub scheduler_config: Arc<RwLock<SchedulerConfig>>,
+    pub scheduler_status: RwLock<SchedulerStatus>,
+    pub privacy_config: Arc<RwLock<PrivacyConfig>>,
+    // Shared with the fetcher; kept in the main database, so it survives profile switches
+    pub metered_mode: Arc<RwLock<bool>>,
+    // Whether closing the main window hides it to the tray, leaving background fetching running.
+    // Read from the window event handler, which can't await a lock
+    pub close_to_tray: AtomicBool,
+    // Every background job this session, with its progress; see operations
+    pub operations: OperationRegistry,
+    pub import_operations: Arc<RwLock<HashMap<String, ImportOperation>>>,
+    // The latest bulk mark-read or star, while it can still be undone
+    pub last_bulk_action: RwLock<Option<BulkUndo>>,
+    // feed:// links and OPML files the OS asked us to open, until the frontend takes them
+    pub pending_open_requests: Mutex<Vec<OpenRequest>>,
+    pub downloads: DownloadManager,
+    // Serves republished (starred or tagged) entries as Atom feeds when enabled
+    pub feed_server: FeedServer,
+    // Lets a browser extension subscribe to the page it's on, when enabled
+    pub subscribe_endpoint: SubscribeEndpoint,
+    // Syncs the library with other devices, one sync at a time
+    pub sync_engine: SyncEngine,
+    // Set to true when the app starts shutting down, to stop background loops
+    pub shutdown_signal: watch::Sender<bool>,
+}
+
+impl AppState {
+    // The active profile's library database
+    pub async fn db(&self) -> DatabaseConnection {
+        self.db.read().await.clone()
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use chrono::NaiveDateTime;
+use sea_orm::*;
+use sea_orm::sea_query::Expr;
+use serde::{Deserialize, Serialize};
+use crate::entities::{prelude::*, *};
+use crate::models::settings::{get_setting_or, set_setting_value, STATE_LOG_COMPACTED_AT};
+
+// Origin of the changes made on this device
+pub const LOCAL_ORIGIN: &str = "local";
+
+// Rows per insert or delete statement, to stay under the database's parameter limit
+const STATE_LOG_BATCH_SIZE: usize = 1000;
+// The log is compacted at most this often from the scheduler
+const STATE_LOG_COMPACTION_INTERVAL_HOURS: i64 = 24;
+
+// An entry flag whose changes are logged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateField {
+    Read,
+    Starred,
+}
+
+impl StateField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StateField::Read => "read",
+            StateField::Starred => "starred",
+        }
+    }
+
+    fn columns(&self) -> (feed_entry::Column, feed_entry::Column) {
+        match self {
+            StateField::Read => (feed_entry::Column::IsRead, feed_entry::Column::ReadAt),
+            StateField::Starred => (feed_entry::Column::IsStarred, feed_entry::Column::StarredAt),
+        }
+    }
+}
+
+impl FromStr for StateField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(StateField::Read),
+            "starred" => Ok(StateField::Starred),
+            other => Err(format!("Unknown entry state field: {}", other)),
+        }
+    }
+}
+
+// One change to an entry's flag. Copies of the library merge these last-writer-wins per
+// entry and field.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateChange {
+    pub entry_id: i32,
+    pub field: StateField,
+    pub value: bool,
+    pub changed_at: NaiveDateTime,
+    pub origin: String,
+}
+
+impl StateChange {
+    // The later change wins. Changes made at the same instant are settled by origin and then
+    // value, so every copy picks the same one whatever order it sees them in.
+    pub fn supersedes(&self, other: &StateChange) -> bool {
+        (self.changed_at, &self.origin, self.value) > (other.changed_at, &other.origin, other.value)
+    }
+
+    fn from_row(row: state_log::Model) -> Option<(i32, StateChange)> {
+        let field = row.field.parse().ok()?;
+        Some((
+            row.id,
+            StateChange {
+                entry_id: row.entry_id,
+                field,
+                value: row.value,
+                changed_at: row.changed_at,
+                origin: row.origin,
+            },
+        ))
+    }
+}
+
+// The winning change of every entry and field, along with its key (e.g. its log row id)
+pub fn latest_state_changes<K>(changes: impl IntoIterator<Item = (K, StateChange)>) -> HashMap<(i32, StateField), (K, StateChange)> {
+    let mut latest: HashMap<(i32, StateField), (K, StateChange)> = HashMap::new();
+    for (key, change) in changes {
+        let replace = latest
+            .get(&(change.entry_id, change.field))
+            .is_none_or(|(_, existing)| change.supersedes(existing));
+        if replace {
+            latest.insert((change.entry_id, change.field), (key, change));
+        }
+    }
+    latest
+}
+
+// Log that the entries' field was set to value. Call alongside the update itself, in the
+// same transaction where there is one.
+pub async fn record_state_changes<C: ConnectionTrait>(
+    db: &C,
+    entry_ids: &[i32],
+    field: StateField,
+    value: bool,
+    changed_at: NaiveDateTime,
+    origin: &str,
+) -> Result<(), String> {
+    for chunk in entry_ids.chunks(STATE_LOG_BATCH_SIZE) {
+        let rows = chunk.iter().map(|entry_id| state_log::ActiveModel {
+            entry_id: ActiveValue::Set(*entry_id),
+            field: ActiveValue::Set(field.as_str().to_string()),
+            value: ActiveValue::Set(value),
+            changed_at: ActiveValue::Set(changed_at),
+            origin: ActiveValue::Set(origin.to_string()),
+            ..Default::default()
+        });
+        StateLog::insert_many(rows)
+            .exec(db)
+            .await
+            .map_err(|e| format!("Failed to record entry state changes: {}", e))?;
+    }
+    Ok(())
+}
+
+async fn load_state_log<C: ConnectionTrait>(db: &C, entry_ids: Option<Vec<i32>>) -> Result<Vec<(i32, StateChange)>, String> {
+    let mut query = StateLog::find();
+    if let Some(entry_ids) = entry_ids {
+        query = query.filter(state_log::Column::EntryId.is_in(entry_ids));
+    }
+    Ok(query
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch entry state log: {}", e))?
+        .into_iter()
+        .filter_map(StateChange::from_row)
+        .collect())
+}
+
+// Merge changes made elsewhere. A change is applied, and logged, only when it supersedes the
+// latest change logged here for its entry and field; a flag nobody has changed yet takes any
+// change. Changes to entries this library doesn't have are dropped. Returns how many were applied.
+pub async fn merge_state_changes<C: ConnectionTrait>(db: &C, changes: Vec<StateChange>) -> Result<usize, String> {
+    let incoming = latest_state_changes(changes.into_iter().map(|change| ((), change)));
+    let entry_ids: Vec<i32> = incoming.keys().map(|(entry_id, _)| *entry_id).collect::<HashSet<_>>().into_iter().collect();
+    if entry_ids.is_empty() {
+        return Ok(0);
+    }
+
+    let existing_entry_ids: HashSet<i32> = FeedEntry::find()
+        .select_only()
+        .column(feed_entry::Column::Id)
+        .filter(feed_entry::Column::Id.is_in(entry_ids.clone()))
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entries: {}", e))?
+        .into_iter()
+        .collect();
+    let logged = latest_state_changes(load_state_log(db, Some(entry_ids)).await?);
+
+    let mut applied = 0;
+    for (key, ((), change)) in incoming {
+        if !existing_entry_ids.contains(&change.entry_id) {
+            continue;
+        }
+        if logged.get(&key).is_some_and(|(_, latest)| !change.supersedes(latest)) {
+            continue;
+        }
+
+        let (flag, set_at) = change.field.columns();
+        FeedEntry::update_many()
+            .col_expr(flag, Expr::value(change.value))
+            .col_expr(set_at, Expr::value(change.value.then_some(change.changed_at)))
+            .filter(feed_entry::Column::Id.eq(change.entry_id))
+            .exec(db)
+            .await
+            .map_err(|e| format!("Failed to update feed entry: {}", e))?;
+        record_state_changes(db, &[change.entry_id], change.field, change.value, change.changed_at, &change.origin).await?;
+        applied += 1;
+    }
+    Ok(applied)
+}
+
+// Drop every logged change that a later one to the same entry and field has replaced; they
+// can no longer win a merge. Returns how many were dropped.
+pub async fn compact_state_log<C: ConnectionTrait>(db: &C) -> Result<u64, String> {
+    let changes = load_state_log(db, None).await?;
+    let kept: HashSet<i32> = latest_state_changes(changes.iter().cloned()).into_values().map(|(id, _)| id).collect();
+    let superseded: Vec<i32> = changes.into_iter().map(|(id, _)| id).filter(|id| !kept.contains(id)).collect();
+
+    let mut dropped = 0;
+    for chunk in superseded.chunks(STATE_LOG_BATCH_SIZE) {
+        dropped += StateLog::delete_many()
+            .filter(state_log::Column::Id.is_in(chunk.to_vec()))
+            .exec(db)
+            .await
+            .map_err(|e| format!("Failed to compact entry state log: {}", e))?
+            .rows_affected;
+    }
+    Ok(dropped)
+}
+
+// Compact the log when it hasn't been for a day. Returns how many changes were dropped, or
+// None when it wasn't due.
+pub async fn compact_state_log_if_due<C: ConnectionTrait>(db: &C, now: NaiveDateTime) -> Result<Option<u64>, String> {
+    let last_compacted_at: Option<NaiveDateTime> = get_setting_or(db, STATE_LOG_COMPACTED_AT, None).await;
+    if last_compacted_at.is_some_and(|at| now - at < chrono::Duration::hours(STATE_LOG_COMPACTION_INTERVAL_HOURS)) {
+        return Ok(None);
+    }
+
+    let dropped = compact_state_log(db).await?;
+    set_setting_value(db, STATE_LOG_COMPACTED_AT, &Some(now)).await?;
+    if dropped > 0 {
+        println!("🗜️ Compacted the entry state log: {} superseded changes dropped", dropped);
+    }
+    Ok(Some(dropped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(entry_id: i32, field: StateField, value: bool, changed_at: &str, origin: &str) -> StateChange {
+        StateChange {
+            entry_id,
+            field,
+            value,
+            changed_at: NaiveDateTime::parse_from_str(changed_at, "%Y-%m-%d %H:%M:%S").unwrap(),
+            origin: origin.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_latest_change_wins_per_entry_and_field() {
+        let changes = vec![
+            (1, change(7, StateField::Read, true, "2024-06-01 08:00:00", LOCAL_ORIGIN)),
+            (2, change(7, StateField::Read, false, "2024-06-01 09:00:00", "laptop")),
+            (3, change(7, StateField::Starred, true, "2024-06-01 07:00:00", LOCAL_ORIGIN)),
+            (4, change(8, StateField::Read, true, "2024-06-01 06:00:00", LOCAL_ORIGIN)),
+        ];
+
+        let latest = latest_state_changes(changes);
+        assert_eq!(latest.len(), 3);
+        assert_eq!(latest[&(7, StateField::Read)].0, 2);
+        assert!(!latest[&(7, StateField::Read)].1.value);
+        assert_eq!(latest[&(7, StateField::Starred)].0, 3);
+        assert_eq!(latest[&(8, StateField::Read)].0, 4);
+    }
+
+    #[test]
+    fn test_simultaneous_changes_settle_the_same_way_in_any_order() {
+        let laptop = change(7, StateField::Read, true, "2024-06-01 08:00:00", "laptop");
+        let desktop = change(7, StateField::Read, false, "2024-06-01 08:00:00", "desktop");
+
+        let forward = latest_state_changes(vec![((), laptop.clone()), ((), desktop.clone())]);
+        let backward = latest_state_changes(vec![((), desktop), ((), laptop.clone())]);
+        assert_eq!(forward, backward);
+        assert_eq!(forward[&(7, StateField::Read)].1, laptop);
+    }
+
+    #[test]
+    fn test_state_fields_round_trip() {
+        for field in [StateField::Read, StateField::Starred] {
+            assert_eq!(field.as_str().parse::<StateField>(), Ok(field));
+        }
+        assert!("hidden".parse::<StateField>().is_err());
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::{HashMap, HashSet};
+use chrono::NaiveDateTime;
+use sea_orm::*;
+use sea_orm::sea_query::Expr;
+use url::Url;
+use crate::entities::{prelude::*, *};
+use crate::models::link_cleaner::strip_tracking_parameters;
+
+// Coverage of a story comes out within a few days, so only entries saved this recently are compared
+const CLUSTER_WINDOW_HOURS: i64 = 72;
+
+// Cap on how many recent entries from other feeds a save compares its new entries against
+const MAX_CLUSTER_CANDIDATES: u64 = 5000;
+
+// Share of title words two entries must have in common (Jaccard index) to be the same story
+const TITLE_SIMILARITY_THRESHOLD: f64 = 0.8;
+
+// Shorter titles ("Weekly links", "Open thread") are too generic to compare
+const MIN_TITLE_WORDS: usize = 4;
+
+// The link reduced to what identifies the article: no scheme, "www.", fragment, trailing
+// slash or tracking parameters. None for links that aren't web URLs.
+pub fn dedup_key(link: &str) -> Option<String> {
+    let url = Url::parse(&strip_tracking_parameters(link)).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+
+    let mut canonical = url.host_str()?.trim_start_matches("www.").to_ascii_lowercase();
+    if let Some(port) = url.port() {
+        canonical.push_str(&format!(":{}", port));
+    }
+    canonical.push_str(url.path().trim_end_matches('/'));
+    if let Some(query) = url.query() {
+        canonical.push('?');
+        canonical.push_str(query);
+    }
+    Some(canonical)
+}
+
+pub(crate) fn title_words(title: &str) -> HashSet<String> {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+// A recent entry from another feed that a new entry may duplicate
+#[derive(Debug, Clone)]
+pub struct ClusterCandidate {
+    pub id: i32,
+    pub dedup_key: Option<String>,
+    pub story_cluster_id: Option<i32>,
+    title_words: HashSet<String>,
+}
+
+impl ClusterCandidate {
+    pub fn new(id: i32, title: &str, dedup_key: Option<String>, story_cluster_id: Option<i32>) -> Self {
+        Self {
+            id,
+            dedup_key,
+            story_cluster_id,
+            title_words: title_words(title),
+        }
+    }
+}
+
+// The candidate covering the same story: one with the same dedup key if there is one,
+// otherwise the one with the most similar title
+pub fn find_duplicate<'a>(
+    candidates: &'a [ClusterCandidate],
+    dedup_key: Option<&str>,
+    title: &str,
+) -> Option<&'a ClusterCandidate> {
+    if let Some(dedup_key) = dedup_key {
+        let same_url = candidates
+            .iter()
+            .find(|candidate| candidate.dedup_key.as_deref() == Some(dedup_key));
+        if same_url.is_some() {
+            return same_url;
+        }
+    }
+
+    let words = title_words(title);
+    if words.len() < MIN_TITLE_WORDS {
+        return None;
+    }
+    candidates
+        .iter()
+        .filter(|candidate| candidate.title_words.len() >= MIN_TITLE_WORDS)
+        .map(|candidate| (jaccard(&words, &candidate.title_words), candidate))
+        .filter(|(similarity, _)| *similarity >= TITLE_SIMILARITY_THRESHOLD)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, candidate)| candidate)
+}
+
+async fn load_cluster_candidates<C: ConnectionTrait>(
+    db: &C,
+    feed_id: i32,
+    since: NaiveDateTime,
+) -> Result<Vec<ClusterCandidate>, String> {
+    let rows: Vec<(i32, String, Option<String>, Option<i32>)> = FeedEntry::find()
+        .select_only()
+        .columns([
+            feed_entry::Column::Id,
+            feed_entry::Column::Title,
+            feed_entry::Column::DedupKey,
+            feed_entry::Column::StoryClusterId,
+        ])
+        .filter(feed_entry::Column::FeedId.ne(feed_id))
+        .filter(feed_entry::Column::CreatedAt.gte(since))
+        .order_by_desc(feed_entry::Column::CreatedAt)
+        .limit(MAX_CLUSTER_CANDIDATES)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch recent entries: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, title, dedup_key, story_cluster_id)| {
+            ClusterCandidate::new(id, &title, dedup_key, story_cluster_id)
+        })
+        .collect())
+}
+
+async fn set_story_cluster<C: ConnectionTrait>(db: &C, entry_id: i32, story_cluster_id: i32) -> Result<(), String> {
+    FeedEntry::update_many()
+        .col_expr(feed_entry::Column::StoryClusterId, Expr::value(story_cluster_id))
+        .filter(feed_entry::Column::Id.eq(entry_id))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to set story cluster: {}", e))?;
+    Ok(())
+}
+
+// Put a feed's newly added entries into the story cluster of a matching recent entry from
+// another feed. A cluster's id is the id of the entry that started it.
+pub async fn assign_story_clusters<C: ConnectionTrait>(
+    db: &C,
+    feed_id: i32,
+    new_entries: &[feed_entry::Model],
+) -> Result<(), String> {
+    let Some(oldest) = new_entries.iter().map(|entry| entry.created_at).min() else {
+        return Ok(());
+    };
+    let since = oldest - chrono::Duration::hours(CLUSTER_WINDOW_HOURS);
+    let mut candidates = load_cluster_candidates(db, feed_id, since).await?;
+
+    for entry in new_entries {
+        let Some(duplicate) = find_duplicate(&candidates, entry.dedup_key.as_deref(), &entry.title) else {
+            continue;
+        };
+        let duplicate_id = duplicate.id;
+        let story_cluster_id = match duplicate.story_cluster_id {
+            Some(story_cluster_id) => story_cluster_id,
+            None => {
+                // The matched entry starts a new cluster
+                set_story_cluster(db, duplicate_id, duplicate_id).await?;
+                if let Some(candidate) = candidates.iter_mut().find(|candidate| candidate.id == duplicate_id) {
+                    candidate.story_cluster_id = Some(duplicate_id);
+                }
+                duplicate_id
+            }
+        };
+        set_story_cluster(db, entry.id, story_cluster_id).await?;
+    }
+
+    Ok(())
+}
+
+// How many different feeds cover each story cluster
+pub async fn load_cluster_feed_counts<C: ConnectionTrait>(
+    db: &C,
+    story_cluster_ids: Vec<i32>,
+) -> Result<HashMap<i32, u64>, String> {
+    if story_cluster_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let counts: Vec<(i32, i64)> = FeedEntry::find()
+        .select_only()
+        .column(feed_entry::Column::StoryClusterId)
+        .column_as(Expr::cust(r#"COUNT(DISTINCT "feed_entry"."feed_id")"#), "feed_count")
+        .filter(feed_entry::Column::StoryClusterId.is_in(story_cluster_ids))
+        .group_by(feed_entry::Column::StoryClusterId)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to count story coverage: {}", e))?;
+
+    Ok(counts
+        .into_iter()
+        .map(|(story_cluster_id, feed_count)| (story_cluster_id, feed_count.max(0) as u64))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_key_ignores_presentation_details() {
+        let key = dedup_key("https://www.example.com/news/story-1/?utm_source=rss#comments");
+
+        assert_eq!(key.as_deref(), Some("example.com/news/story-1"));
+        assert_eq!(dedup_key("http://EXAMPLE.com/news/story-1"), key);
+        assert_eq!(dedup_key("https://example.com/item?id=7").as_deref(), Some("example.com/item?id=7"));
+        assert_eq!(dedup_key("mailto:editor@example.com"), None);
+    }
+
+    #[test]
+    fn test_duplicates_match_by_url_then_title() {
+        let candidates = vec![
+            ClusterCandidate::new(1, "Central bank raises interest rates again", None, None),
+            ClusterCandidate::new(2, "Something else entirely", Some("example.com/a".to_string()), Some(9)),
+        ];
+
+        let by_url = find_duplicate(&candidates, Some("example.com/a"), "Unrelated title here today");
+        assert_eq!(by_url.map(|candidate| candidate.id), Some(2));
+
+        let by_title = find_duplicate(&candidates, Some("other.org/b"), "Central Bank raises interest rates, again");
+        assert_eq!(by_title.map(|candidate| candidate.id), Some(1));
+
+        assert!(find_duplicate(&candidates, None, "Central bank holds interest rates").is_none());
+        // Identical but short titles are too generic to match
+        assert!(find_duplicate(&[ClusterCandidate::new(3, "Open thread", None, None)], None, "Open thread").is_none());
+    }
+}
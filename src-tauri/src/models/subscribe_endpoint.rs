@@ -0,0 +1,360 @@
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{watch, Mutex};
+use url::Url;
+use crate::entities::{prelude::*, *};
+use crate::models::async_feed_fetcher::FetchPriority;
+use crate::models::feed_sources::FeedSourceType;
+use crate::models::open_requests::feed_link_target;
+use crate::models::requests::CreateFeedRequest;
+use crate::models::responses::FeedResponse;
+use crate::models::secrets::{get_secret_value, store_secret_value, SecretKind};
+use crate::models::state::AppState;
+use crate::models::subscriptions::create_subscription;
+
+// Keychain name of the token the browser extension or bookmarklet sends
+const SUBSCRIBE_ENDPOINT_TOKEN: &str = "subscribe_endpoint_token";
+// A subscribe request is a small JSON body; anything bigger isn't one
+const MAX_REQUEST_BYTES: usize = 16 * 1024;
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub const FEED_SUBSCRIBED_EVENT: &str = "feed:subscribed";
+
+// The localhost endpoint a companion browser extension or bookmarklet subscribes through.
+// Stored in the main database, since the port belongs to the machine rather than a library.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubscribeEndpointConfig {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for SubscribeEndpointConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 8788,
+        }
+    }
+}
+
+impl SubscribeEndpointConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.port == 0 {
+            return Err("Subscribe endpoint port must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+
+    // Only ever this computer: the endpoint changes the library, unlike the feed server
+    fn bind_address(&self) -> SocketAddr {
+        SocketAddr::from((Ipv4Addr::LOCALHOST, self.port))
+    }
+}
+
+pub fn generate_endpoint_token() -> String {
+    rand::random::<[u8; 24]>().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// The endpoint's token, creating one the first time it's needed
+pub async fn load_or_create_endpoint_token() -> Result<String, String> {
+    if let Some(token) = get_secret_value(SecretKind::IntegrationApiKey, SUBSCRIBE_ENDPOINT_TOKEN.to_string()).await? {
+        return Ok(token);
+    }
+    regenerate_endpoint_token().await
+}
+
+// Replace the token, locking out every extension set up with the old one
+pub async fn regenerate_endpoint_token() -> Result<String, String> {
+    let token = generate_endpoint_token();
+    store_secret_value(SecretKind::IntegrationApiKey, SUBSCRIBE_ENDPOINT_TOKEN.to_string(), token.clone()).await?;
+    Ok(token)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EndpointRoute {
+    // A browser's CORS check before a bookmarklet's POST
+    Preflight,
+    Subscribe(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EndpointError {
+    NotFound,
+    MethodNotAllowed,
+    Unauthorized,
+    BadRequest(String),
+}
+
+#[derive(Deserialize)]
+struct SubscribeBody {
+    url: String,
+}
+
+// Compare without stopping at the first difference, so timing doesn't give the token away
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0, |difference, (a, b)| difference | (a ^ b)) == 0
+}
+
+// What a request (its head, and the body after it) asks for: `POST /subscribe` with
+// `Authorization: Bearer <token>` and a `{"url": ...}` body naming an http(s) or feed:// URL
+pub fn route_endpoint_request(head: &str, body: &[u8], token: &str) -> Result<EndpointRoute, EndpointError> {
+    let mut lines = head.lines();
+    let mut parts = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(target), Some(_version)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(EndpointError::BadRequest("Malformed request".to_string()));
+    };
+    if target.split(['?', '#']).next() != Some("/subscribe") {
+        return Err(EndpointError::NotFound);
+    }
+    match method {
+        "OPTIONS" => return Ok(EndpointRoute::Preflight),
+        "POST" => {}
+        _ => return Err(EndpointError::MethodNotAllowed),
+    }
+
+    let authorization = lines
+        .find_map(|line| line.split_once(':').filter(|(name, _)| name.trim().eq_ignore_ascii_case("authorization")))
+        .map(|(_, value)| value.trim());
+    let given = authorization.and_then(|value| value.strip_prefix("Bearer ")).unwrap_or_default().trim();
+    if token.is_empty() || !tokens_match(given, token) {
+        return Err(EndpointError::Unauthorized);
+    }
+
+    let body: SubscribeBody = serde_json::from_slice(body)
+        .map_err(|_| EndpointError::BadRequest("Expected a JSON body with a url".to_string()))?;
+    let url = body.url.trim();
+    let url = feed_link_target(url).unwrap_or_else(|| url.to_string());
+    match Url::parse(&url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(EndpointRoute::Subscribe(url)),
+        _ => Err(EndpointError::BadRequest(format!("Not a feed URL: {}", url))),
+    }
+}
+
+fn endpoint_response(status: &str, body: &serde_json::Value) -> Vec<u8> {
+    let body = body.to_string();
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\nAccess-Control-Allow-Methods: POST, OPTIONS\r\n\
+         Access-Control-Allow-Headers: Authorization, Content-Type\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+    .into_bytes()
+}
+
+// Read the request head and as much body as its Content-Length announces
+async fn read_request(stream: &mut TcpStream) -> Option<(String, Vec<u8>)> {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    let head_end = loop {
+        if let Some(position) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position;
+        }
+        if request.len() >= MAX_REQUEST_BYTES {
+            return None;
+        }
+        let read = stream.read(&mut buffer).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    };
+
+    let head = String::from_utf8(request[..head_end].to_vec()).ok()?;
+    let content_length = head
+        .lines()
+        .find_map(|line| line.split_once(':').filter(|(name, _)| name.trim().eq_ignore_ascii_case("content-length")))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_REQUEST_BYTES {
+        return None;
+    }
+
+    let mut body = request.split_off(head_end + 4);
+    while body.len() < content_length {
+        let read = stream.read(&mut buffer).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&buffer[..read]);
+    }
+    body.truncate(content_length);
+    Some((head, body))
+}
+
+// Subscribe to a feed, or find the existing subscription, and fetch it straight away.
+// Returns the feed and whether it is new.
+async fn subscribe(app: &AppHandle, url: String) -> Result<(feed::Model, bool), String> {
+    let state = app.state::<AppState>();
+    let db = state.db().await;
+
+    let existing = Feed::find()
+        .filter(feed::Column::Url.eq(url.as_str()))
+        .one(&db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed: {}", e))?;
+    if let Some(feed) = existing {
+        return Ok((feed, false));
+    }
+
+    let request = CreateFeedRequest {
+        url,
+        title: None,
+        description: None,
+        folder_id: None,
+        source_type: None,
+    };
+    let feed = create_subscription(&db, request).await?;
+    println!("➕ Subscribed to {} from the subscribe endpoint", feed.url);
+
+    // Queued behind a paused fetcher, the feed waits until it's resumed. A stopped fetcher is
+    // left stopped; the scheduler picks the feed up as due once it's started again.
+    if let Some(fetcher) = &state.async_fetcher {
+        if fetcher.is_running().await {
+            if let Err(e) = fetcher.queue_feed(feed.url.clone(), FeedSourceType::Http, FetchPriority::High) {
+                eprintln!("❌ Failed to queue feed: {}", e);
+            }
+        }
+    }
+    if let Err(e) = app.emit(FEED_SUBSCRIBED_EVENT, &FeedResponse::from(feed.clone())) {
+        eprintln!("❌ Failed to emit subscription: {}", e);
+    }
+    Ok((feed, true))
+}
+
+async fn handle_connection(app: AppHandle, mut stream: TcpStream, token: Arc<String>) {
+    let request = tokio::time::timeout(REQUEST_READ_TIMEOUT, read_request(&mut stream))
+        .await
+        .ok()
+        .flatten();
+    let Some((head, body)) = request else {
+        let _ = stream.write_all(&endpoint_response("400 Bad Request", &json!({ "error": "Bad request" }))).await;
+        return;
+    };
+
+    let response = match route_endpoint_request(&head, &body, &token) {
+        Ok(EndpointRoute::Preflight) => endpoint_response("200 OK", &json!({})),
+        Ok(EndpointRoute::Subscribe(url)) => match subscribe(&app, url).await {
+            Ok((feed, created)) => endpoint_response(
+                if created { "201 Created" } else { "200 OK" },
+                &json!({ "id": feed.id, "url": feed.url, "title": feed.title, "created": created }),
+            ),
+            Err(e) => endpoint_response("422 Unprocessable Entity", &json!({ "error": e })),
+        },
+        Err(EndpointError::NotFound) => endpoint_response("404 Not Found", &json!({ "error": "Not found" })),
+        Err(EndpointError::MethodNotAllowed) => {
+            endpoint_response("405 Method Not Allowed", &json!({ "error": "Method not allowed" }))
+        }
+        Err(EndpointError::Unauthorized) => endpoint_response("401 Unauthorized", &json!({ "error": "Invalid token" })),
+        Err(EndpointError::BadRequest(message)) => endpoint_response("400 Bad Request", &json!({ "error": message })),
+    };
+
+    let _ = stream.write_all(&response).await;
+    let _ = stream.shutdown().await;
+}
+
+async fn accept_connections(app: AppHandle, listener: TcpListener, token: String, mut stop: watch::Receiver<bool>) {
+    let mut shutdown = app.state::<AppState>().shutdown_signal.subscribe();
+    let token = Arc::new(token);
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_connection(app.clone(), stream, token.clone()));
+                }
+                Err(e) => eprintln!("❌ Subscribe endpoint failed to accept a connection: {}", e),
+            },
+            _ = stop.wait_for(|stopping| *stopping) => break,
+            _ = shutdown.wait_for(|stopping| *stopping) => break,
+        }
+    }
+}
+
+// The localhost listener for one-click subscribing. Restarted whenever its settings or token change.
+#[derive(Default)]
+pub struct SubscribeEndpoint {
+    stop: Mutex<Option<watch::Sender<bool>>>,
+}
+
+impl SubscribeEndpoint {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Stop the running listener, if any, and start one with the new settings if it's enabled
+    pub async fn apply(&self, app: &AppHandle, config: &SubscribeEndpointConfig) -> Result<(), String> {
+        let mut stop = self.stop.lock().await;
+        if let Some(running) = stop.take() {
+            let _ = running.send(true);
+        }
+        if !config.enabled {
+            return Ok(());
+        }
+
+        let token = load_or_create_endpoint_token().await?;
+        let address = config.bind_address();
+        let listener = TcpListener::bind(address)
+            .await
+            .map_err(|e| format!("Failed to start subscribe endpoint on {}: {}", address, e))?;
+        let (stop_sender, stop_receiver) = watch::channel(false);
+        tauri::async_runtime::spawn(accept_connections(app.clone(), listener, token, stop_receiver));
+        *stop = Some(stop_sender);
+
+        println!("🔌 Accepting subscriptions on http://{}/subscribe", address);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "0123abcd";
+
+    fn post(authorization: &str) -> String {
+        format!("POST /subscribe HTTP/1.1\r\nHost: 127.0.0.1:8788\r\n{}Content-Type: application/json", authorization)
+    }
+
+    #[test]
+    fn test_subscribe_requests_need_the_token() {
+        let body = br#"{"url": " https://example.com/feed.xml "}"#;
+        assert_eq!(
+            route_endpoint_request(&post("authorization: Bearer 0123abcd\r\n"), body, TOKEN),
+            Ok(EndpointRoute::Subscribe("https://example.com/feed.xml".to_string()))
+        );
+        assert_eq!(
+            route_endpoint_request(&post("Authorization: Bearer 0123abce\r\n"), body, TOKEN),
+            Err(EndpointError::Unauthorized)
+        );
+        assert_eq!(route_endpoint_request(&post(""), body, TOKEN), Err(EndpointError::Unauthorized));
+        // An endpoint without a token lets nobody in
+        assert_eq!(route_endpoint_request(&post("Authorization: Bearer \r\n"), body, ""), Err(EndpointError::Unauthorized));
+    }
+
+    #[test]
+    fn test_endpoint_routes() {
+        let authorized = post("Authorization: Bearer 0123abcd\r\n");
+        assert_eq!(
+            route_endpoint_request(&authorized, br#"{"url": "feed://example.com/rss"}"#, TOKEN),
+            Ok(EndpointRoute::Subscribe("http://example.com/rss".to_string()))
+        );
+        assert!(matches!(
+            route_endpoint_request(&authorized, br#"{"url": "file:///etc/passwd"}"#, TOKEN),
+            Err(EndpointError::BadRequest(_))
+        ));
+        assert!(matches!(route_endpoint_request(&authorized, b"url=x", TOKEN), Err(EndpointError::BadRequest(_))));
+        assert_eq!(route_endpoint_request("OPTIONS /subscribe HTTP/1.1", b"", TOKEN), Ok(EndpointRoute::Preflight));
+        assert_eq!(route_endpoint_request("GET /subscribe HTTP/1.1", b"", TOKEN), Err(EndpointError::MethodNotAllowed));
+        assert_eq!(route_endpoint_request("POST /feeds HTTP/1.1", b"", TOKEN), Err(EndpointError::NotFound));
+    }
+}
@@ -0,0 +1,41 @@
+use sea_orm::*;
+use crate::entities::{prelude::*, *};
+use crate::models::settings::load_domain_rules_config;
+use crate::models::requests::CreateFeedRequest;
+use crate::models::url_guard::load_allow_private_addresses;
+
+// Subscribe to a feed, after checking its URL is allowed. Shared by create_feed and the
+// local subscribe endpoint.
+pub async fn create_subscription<C: ConnectionTrait>(db: &C, request: CreateFeedRequest) -> Result<feed::Model, String> {
+    let source_type = request.source_type.unwrap_or_default();
+    source_type.validate_url(&request.url, load_allow_private_addresses(db).await).await?;
+    if let Some(domain) = load_domain_rules_config(db).await.blocked_subscription(&request.url) {
+        return Err(format!("Subscribing to {} is blocked", domain));
+    }
+
+    let now = chrono::Utc::now().naive_utc();
+
+    let new_feed = feed::ActiveModel {
+        url: ActiveValue::Set(request.url),
+        auto_title: ActiveValue::Set(request.title.is_none()),
+        title: ActiveValue::Set(request.title),
+        description: ActiveValue::Set(request.description),
+        created_at: ActiveValue::Set(now),
+        updated_at: ActiveValue::Set(now),
+        last_fetched_at: ActiveValue::Set(None),
+        folder_id: ActiveValue::Set(request.folder_id),
+        source_type: ActiveValue::Set(source_type.as_str().to_string()),
+        ..Default::default()
+    };
+
+    let result = Feed::insert(new_feed)
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to create feed: {}", e))?;
+
+    Feed::find_by_id(result.last_insert_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch created feed: {}", e))?
+        .ok_or_else(|| "Failed to find created feed".to_string())
+}
@@ -0,0 +1,817 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::num::NonZeroU32;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use sea_orm::*;
+use sea_orm::sea_query::{Expr, OnConflict};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use crate::entities::{prelude::*, *};
+use crate::models::feed_sources::FeedSourceType;
+use crate::models::requests::CreateFeedRequest;
+use crate::models::secrets::{get_secret_value, SecretKind};
+use crate::models::settings::{get_setting_or, load_sync_config, set_setting_value, SYNC_STATE};
+use crate::models::state_log::{record_state_changes, StateField};
+use crate::models::subscriptions::create_subscription;
+use crate::models::sync_storage::SyncStorage;
+
+// Name of the sync passphrase in the keychain, stored as a SyncToken secret
+pub const SYNC_PASSPHRASE_SECRET: &str = "passphrase";
+
+// The changelog file is this header, a salt, a nonce, then the ChaCha20-Poly1305 sealed JSON
+const SYNC_FILE_MAGIC: &[u8] = b"RSYNC1";
+const SYNC_SALT_LEN: usize = 16;
+const SYNC_KDF_ITERATIONS: u32 = 100_000;
+
+// At most this many apply errors are kept in a sync report
+const MAX_REPORTED_ERRORS: usize = 20;
+
+// Syncing subscriptions, read/star state and tags between devices through storage the user
+// provides. Off until a storage and a passphrase are set up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub enabled: bool,
+    pub storage: Option<SyncStorage>,
+    // How often the scheduler syncs in the background; 0 syncs only on request
+    pub interval_minutes: u32,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            storage: None,
+            interval_minutes: 15,
+        }
+    }
+}
+
+impl SyncConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.storage {
+            Some(storage) => storage.validate(),
+            None if self.enabled => Err("Choose where to keep the sync file before turning sync on".to_string()),
+            None => Ok(()),
+        }
+    }
+}
+
+// Per-device counters of the changes each record has seen
+pub type VectorClock = BTreeMap<String, u64>;
+
+// How a and b are ordered by happened-before, or None when they are concurrent
+fn compare_clocks(a: &VectorClock, b: &VectorClock) -> Option<Ordering> {
+    let mut ordering = Ordering::Equal;
+    for device_id in a.keys().chain(b.keys()) {
+        let a_count = a.get(device_id).copied().unwrap_or(0);
+        let b_count = b.get(device_id).copied().unwrap_or(0);
+        match (ordering, a_count.cmp(&b_count)) {
+            (_, Ordering::Equal) => {}
+            (Ordering::Equal, step) => ordering = step,
+            (current, step) if current != step => return None,
+            _ => {}
+        }
+    }
+    Some(ordering)
+}
+
+// The synced value of one key. Keys are "feed:<url>", or "read:", "star:" or "tags:"
+// followed by an entry's "<feed url>\n<guid>".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SyncValue {
+    Subscribed {
+        // Only set once renamed by hand; otherwise the feed's own title is used
+        title: Option<String>,
+        folder: Option<String>,
+        source_type: String,
+    },
+    Unsubscribed,
+    Flag { value: bool },
+    Tags { names: Vec<String> },
+}
+
+fn feed_key(url: &str) -> String {
+    format!("feed:{}", url)
+}
+
+fn entry_key(kind: &str, feed_url: &str, guid: &str) -> String {
+    format!("{}:{}\n{}", kind, feed_url, guid)
+}
+
+// The value a key has when nothing was recorded for it
+fn default_value(key: &str) -> SyncValue {
+    match key.split_once(':').map(|(kind, _)| kind) {
+        Some("tags") => SyncValue::Tags { names: Vec::new() },
+        Some("read") | Some("star") => SyncValue::Flag { value: false },
+        _ => SyncValue::Unsubscribed,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncRecord {
+    pub value: SyncValue,
+    pub clock: VectorClock,
+    pub device_id: String,
+}
+
+impl SyncRecord {
+    // Whether this record replaces other. Concurrent changes are settled the same way on
+    // every device: the one that has seen more changes, then the higher device id.
+    fn supersedes(&self, other: &SyncRecord) -> bool {
+        match compare_clocks(&self.clock, &other.clock) {
+            Some(ordering) => ordering == Ordering::Greater,
+            None => {
+                let seen = |clock: &VectorClock| clock.values().sum::<u64>();
+                (seen(&self.clock), &self.device_id) > (seen(&other.clock), &other.device_id)
+            }
+        }
+    }
+}
+
+// The latest record of every key, which is all a device needs to catch up. Every device
+// keeps a copy and merges in the one on the storage, so copies converge in any order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncChangelog {
+    pub records: BTreeMap<String, SyncRecord>,
+}
+
+impl SyncChangelog {
+    // Keep the superseding record of every key. Returns how many records were taken from other.
+    pub fn merge(&mut self, other: SyncChangelog) -> usize {
+        let mut taken = 0;
+        for (key, record) in other.records {
+            let replace = self.records.get(&key).is_none_or(|existing| record.supersedes(existing));
+            if replace {
+                self.records.insert(key, record);
+                taken += 1;
+            }
+        }
+        taken
+    }
+
+    // Everything this changelog has seen, per device
+    pub fn clock(&self) -> VectorClock {
+        let mut clock = VectorClock::new();
+        for record in self.records.values() {
+            for (device_id, count) in &record.clock {
+                let seen = clock.entry(device_id.clone()).or_insert(0);
+                *seen = (*seen).max(*count);
+            }
+        }
+        clock
+    }
+}
+
+// This device's side of sync, kept in the library's settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncState {
+    pub device_id: String,
+    pub changelog: SyncChangelog,
+    // The library as of the last sync, to tell what changed here since
+    pub snapshot: BTreeMap<String, SyncValue>,
+    pub last_synced_at: Option<String>, // RFC 3339
+    pub last_attempted_at: Option<String>, // RFC 3339
+    pub last_error: Option<String>,
+}
+
+// What the library holds now, in sync keys
+#[derive(Debug, Default)]
+pub struct LocalLibrary {
+    // Every key whose value differs from its default
+    pub values: BTreeMap<String, SyncValue>,
+    // Entries in this library by "<feed url>\n<guid>"; only these can take entry changes
+    pub entry_ids: HashMap<String, i32>,
+    pub feeds: HashMap<String, feed::Model>,
+}
+
+impl LocalLibrary {
+    fn value(&self, key: &str) -> SyncValue {
+        self.values.get(key).cloned().unwrap_or_else(|| default_value(key))
+    }
+
+    // Entry keys are only meaningful while the entry is here: an entry that hasn't been
+    // fetched yet, or was cleaned up, is neither unread nor untagged
+    fn entry_id(&self, key: &str) -> Option<i32> {
+        key.split_once(':').and_then(|(_, entry)| self.entry_ids.get(entry)).copied()
+    }
+
+    fn tracks(&self, key: &str) -> bool {
+        key.starts_with("feed:") || self.entry_id(key).is_some()
+    }
+}
+
+// Record what changed in the library since the last sync as records that supersede
+// everything this device has seen. Returns how many were recorded.
+pub fn record_local_changes(state: &mut SyncState, library: &LocalLibrary) -> usize {
+    let mut clock = state.changelog.clock();
+    let keys: BTreeSet<&String> = state.snapshot.keys().chain(library.values.keys()).collect();
+
+    let mut recorded = 0;
+    for key in keys {
+        if !library.tracks(key) {
+            continue;
+        }
+        let value = library.value(key);
+        let previous = state.snapshot.get(key).cloned().unwrap_or_else(|| default_value(key));
+        if value == previous {
+            continue;
+        }
+
+        *clock.entry(state.device_id.clone()).or_insert(0) += 1;
+        state.changelog.records.insert(
+            key.clone(),
+            SyncRecord {
+                value,
+                clock: clock.clone(),
+                device_id: state.device_id.clone(),
+            },
+        );
+        recorded += 1;
+    }
+    recorded
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<LessSafeKey, String> {
+    let mut key = [0u8; 32];
+    let iterations = NonZeroU32::new(SYNC_KDF_ITERATIONS).ok_or("Invalid key derivation settings")?;
+    pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, iterations, salt, passphrase.as_bytes(), &mut key);
+    let key = UnboundKey::new(&CHACHA20_POLY1305, &key).map_err(|_| "Failed to create sync key".to_string())?;
+    Ok(LessSafeKey::new(key))
+}
+
+// Seal the changelog with a key derived from the passphrase, under a fresh salt and nonce
+pub fn encrypt_changelog(changelog: &SyncChangelog, passphrase: &str) -> Result<Vec<u8>, String> {
+    let random = SystemRandom::new();
+    let mut salt = [0u8; SYNC_SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    random.fill(&mut salt).map_err(|_| "Failed to generate sync salt".to_string())?;
+    random.fill(&mut nonce).map_err(|_| "Failed to generate sync nonce".to_string())?;
+
+    let mut sealed = serde_json::to_vec(changelog).map_err(|e| format!("Failed to serialize sync changelog: {}", e))?;
+    derive_key(passphrase, &salt)?
+        .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(SYNC_FILE_MAGIC), &mut sealed)
+        .map_err(|_| "Failed to encrypt sync changelog".to_string())?;
+
+    Ok([SYNC_FILE_MAGIC, &salt[..], &nonce[..], &sealed[..]].concat())
+}
+
+pub fn decrypt_changelog(bytes: &[u8], passphrase: &str) -> Result<SyncChangelog, String> {
+    let rest = bytes.strip_prefix(SYNC_FILE_MAGIC).ok_or("The sync file isn't one this app wrote")?;
+    if rest.len() < SYNC_SALT_LEN + NONCE_LEN {
+        return Err("The sync file is truncated".to_string());
+    }
+    let (salt, rest) = rest.split_at(SYNC_SALT_LEN);
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| "The sync file is truncated".to_string())?;
+
+    let mut sealed = sealed.to_vec();
+    let json = derive_key(passphrase, salt)?
+        .open_in_place(nonce, Aad::from(SYNC_FILE_MAGIC), &mut sealed)
+        .map_err(|_| "Couldn't decrypt the sync file: the passphrase is wrong or the file is damaged".to_string())?;
+    serde_json::from_slice(json).map_err(|e| format!("Invalid sync changelog: {}", e))
+}
+
+pub async fn load_local_library<C: ConnectionTrait>(db: &C) -> Result<LocalLibrary, String> {
+    let mut library = LocalLibrary::default();
+
+    let folder_names: HashMap<i32, String> = Folder::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch folders: {}", e))?
+        .into_iter()
+        .map(|folder| (folder.id, folder.name))
+        .collect();
+    let feeds = Feed::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feeds: {}", e))?;
+
+    // Local files and directories are paths on this computer, so they stay out of sync
+    let mut feed_urls: HashMap<i32, String> = HashMap::new();
+    for feed in feeds {
+        let source_type: FeedSourceType = feed.source_type.parse().unwrap_or_default();
+        if !source_type.is_remote() {
+            continue;
+        }
+        library.values.insert(
+            feed_key(&feed.url),
+            SyncValue::Subscribed {
+                title: if feed.auto_title { None } else { feed.title.clone() },
+                folder: feed.folder_id.and_then(|folder_id| folder_names.get(&folder_id).cloned()),
+                source_type: feed.source_type.clone(),
+            },
+        );
+        feed_urls.insert(feed.id, feed.url.clone());
+        library.feeds.insert(feed.url.clone(), feed);
+    }
+
+    let entries: Vec<(i32, i32, String, bool, bool)> = FeedEntry::find()
+        .select_only()
+        .columns([
+            feed_entry::Column::Id,
+            feed_entry::Column::FeedId,
+            feed_entry::Column::Guid,
+            feed_entry::Column::IsRead,
+            feed_entry::Column::IsStarred,
+        ])
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch feed entries: {}", e))?;
+    let mut entry_names: HashMap<i32, (String, String)> = HashMap::new();
+    for (id, feed_id, guid, is_read, is_starred) in entries {
+        let Some(feed_url) = feed_urls.get(&feed_id) else {
+            continue;
+        };
+        if is_read {
+            library.values.insert(entry_key("read", feed_url, &guid), SyncValue::Flag { value: true });
+        }
+        if is_starred {
+            library.values.insert(entry_key("star", feed_url, &guid), SyncValue::Flag { value: true });
+        }
+        library.entry_ids.insert(format!("{}\n{}", feed_url, guid), id);
+        entry_names.insert(id, (feed_url.clone(), guid));
+    }
+
+    let entry_tags = EntryTag::find()
+        .find_also_related(Tag)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch entry tags: {}", e))?;
+    let mut tags_by_entry: HashMap<i32, Vec<String>> = HashMap::new();
+    for (entry_tag, tag) in entry_tags {
+        if let Some(tag) = tag {
+            tags_by_entry.entry(entry_tag.entry_id).or_default().push(tag.name);
+        }
+    }
+    for (entry_id, mut names) in tags_by_entry {
+        let Some((feed_url, guid)) = entry_names.get(&entry_id) else {
+            continue;
+        };
+        names.sort();
+        library.values.insert(entry_key("tags", feed_url, guid), SyncValue::Tags { names });
+    }
+
+    Ok(library)
+}
+
+async fn find_or_create_folder_named<C: ConnectionTrait>(db: &C, name: &str) -> Result<i32, String> {
+    if let Some(existing_folder) = Folder::find()
+        .filter(folder::Column::Name.eq(name))
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch folder: {}", e))?
+    {
+        return Ok(existing_folder.id);
+    }
+
+    let now = chrono::Utc::now().naive_utc();
+    let new_folder = folder::ActiveModel {
+        name: ActiveValue::Set(name.to_string()),
+        parent_id: ActiveValue::Set(None),
+        created_at: ActiveValue::Set(now),
+        updated_at: ActiveValue::Set(now),
+        ..Default::default()
+    };
+    Ok(new_folder
+        .insert(db)
+        .await
+        .map_err(|e| format!("Failed to create folder: {}", e))?
+        .id)
+}
+
+async fn find_or_create_tag<C: ConnectionTrait>(db: &C, name: &str) -> Result<i32, String> {
+    if let Some(existing_tag) = Tag::find()
+        .filter(tag::Column::Name.eq(name))
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch tag: {}", e))?
+    {
+        return Ok(existing_tag.id);
+    }
+
+    let new_tag = tag::ActiveModel {
+        name: ActiveValue::Set(name.to_string()),
+        created_at: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+        ..Default::default()
+    };
+    Ok(new_tag
+        .insert(db)
+        .await
+        .map_err(|e| format!("Failed to create tag: {}", e))?
+        .id)
+}
+
+async fn apply_feed_value<C: ConnectionTrait>(
+    db: &C,
+    library: &LocalLibrary,
+    url: &str,
+    value: &SyncValue,
+) -> Result<(), String> {
+    match value {
+        SyncValue::Subscribed { title, folder, source_type } => {
+            let folder_id = match folder {
+                Some(folder) => Some(find_or_create_folder_named(db, folder).await?),
+                None => None,
+            };
+            match library.feeds.get(url) {
+                Some(existing_feed) => {
+                    let mut updated_feed: feed::ActiveModel = existing_feed.clone().into();
+                    if let Some(title) = title {
+                        updated_feed.title = ActiveValue::Set(Some(title.clone()));
+                        updated_feed.auto_title = ActiveValue::Set(false);
+                    }
+                    updated_feed.folder_id = ActiveValue::Set(folder_id);
+                    updated_feed.updated_at = ActiveValue::Set(chrono::Utc::now().naive_utc());
+                    updated_feed
+                        .update(db)
+                        .await
+                        .map_err(|e| format!("Failed to update feed {}: {}", url, e))?;
+                }
+                None => {
+                    create_subscription(
+                        db,
+                        CreateFeedRequest {
+                            url: url.to_string(),
+                            title: title.clone(),
+                            description: None,
+                            folder_id,
+                            source_type: source_type.parse().ok(),
+                        },
+                    )
+                    .await
+                    .map_err(|e| format!("{} ({})", e, url))?;
+                }
+            }
+        }
+        SyncValue::Unsubscribed => {
+            Feed::delete_many()
+                .filter(feed::Column::Url.eq(url))
+                .exec(db)
+                .await
+                .map_err(|e| format!("Failed to delete feed {}: {}", url, e))?;
+        }
+        _ => return Err(format!("Unexpected sync value for feed {}", url)),
+    }
+    Ok(())
+}
+
+async fn apply_entry_value<C: ConnectionTrait>(db: &C, kind: &str, entry_id: i32, record: &SyncRecord) -> Result<(), String> {
+    let now = chrono::Utc::now().naive_utc();
+    match (kind, &record.value) {
+        ("read", SyncValue::Flag { value }) => {
+            FeedEntry::update_many()
+                .col_expr(feed_entry::Column::IsRead, Expr::value(*value))
+                .col_expr(feed_entry::Column::ReadAt, Expr::value(value.then_some(now)))
+                .filter(feed_entry::Column::Id.eq(entry_id))
+                .exec(db)
+                .await
+                .map_err(|e| format!("Failed to update read state: {}", e))?;
+            record_state_changes(db, &[entry_id], StateField::Read, *value, now, &record.device_id).await?;
+        }
+        ("star", SyncValue::Flag { value }) => {
+            FeedEntry::update_many()
+                .col_expr(feed_entry::Column::IsStarred, Expr::value(*value))
+                .col_expr(feed_entry::Column::StarredAt, Expr::value(value.then_some(now)))
+                .filter(feed_entry::Column::Id.eq(entry_id))
+                .exec(db)
+                .await
+                .map_err(|e| format!("Failed to update starred state: {}", e))?;
+            record_state_changes(db, &[entry_id], StateField::Starred, *value, now, &record.device_id).await?;
+        }
+        ("tags", SyncValue::Tags { names }) => {
+            let mut tag_ids = Vec::new();
+            for name in names {
+                tag_ids.push(find_or_create_tag(db, name).await?);
+            }
+            EntryTag::delete_many()
+                .filter(entry_tag::Column::EntryId.eq(entry_id))
+                .filter(entry_tag::Column::TagId.is_not_in(tag_ids.clone()))
+                .exec(db)
+                .await
+                .map_err(|e| format!("Failed to update entry tags: {}", e))?;
+            for tag_id in tag_ids {
+                let new_entry_tag = entry_tag::ActiveModel {
+                    entry_id: ActiveValue::Set(entry_id),
+                    tag_id: ActiveValue::Set(tag_id),
+                    created_at: ActiveValue::Set(now),
+                };
+                EntryTag::insert(new_entry_tag)
+                    .on_conflict(
+                        OnConflict::columns([entry_tag::Column::EntryId, entry_tag::Column::TagId])
+                            .do_nothing()
+                            .to_owned(),
+                    )
+                    .do_nothing()
+                    .exec(db)
+                    .await
+                    .map_err(|e| format!("Failed to tag entry: {}", e))?;
+            }
+        }
+        _ => return Err(format!("Unexpected sync value for {}", kind)),
+    }
+    Ok(())
+}
+
+// Bring the library in line with the changelog. Records are applied in key order, so
+// subscriptions ("feed:") land before entry state. Returns how many changes were applied
+// and the errors of those that couldn't be.
+pub async fn apply_changelog<C: ConnectionTrait>(
+    db: &C,
+    changelog: &SyncChangelog,
+    library: &LocalLibrary,
+) -> (usize, Vec<String>) {
+    let mut applied = 0;
+    let mut errors = Vec::new();
+
+    for (key, record) in &changelog.records {
+        if !library.tracks(key) || library.value(key) == record.value {
+            continue;
+        }
+        let result = match key.split_once(':') {
+            Some(("feed", url)) => apply_feed_value(db, library, url, &record.value).await,
+            Some((kind, _)) => match library.entry_id(key) {
+                Some(entry_id) => apply_entry_value(db, kind, entry_id, record).await,
+                None => continue,
+            },
+            None => Err(format!("Invalid sync key: {}", key)),
+        };
+        match result {
+            Ok(()) => applied += 1,
+            Err(e) => errors.push(e),
+        }
+    }
+
+    (applied, errors)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub device_id: String,
+    // Devices that have written to the changelog, this one included
+    pub device_count: usize,
+    pub local_changes: usize,
+    pub remote_changes: usize,
+    pub applied_changes: usize,
+    pub errors: Vec<String>,
+    pub synced_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncStatus {
+    pub config: SyncConfig,
+    pub device_id: Option<String>,
+    pub has_passphrase: bool,
+    pub last_synced_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+pub async fn load_sync_state<C: ConnectionTrait>(db: &C) -> SyncState {
+    get_setting_or(db, SYNC_STATE, SyncState::default()).await
+}
+
+pub async fn load_sync_status<C: ConnectionTrait>(db: &C) -> Result<SyncStatus, String> {
+    let state = load_sync_state(db).await;
+    Ok(SyncStatus {
+        config: load_sync_config(db).await,
+        device_id: Some(state.device_id).filter(|device_id| !device_id.is_empty()),
+        has_passphrase: get_secret_value(SecretKind::SyncToken, SYNC_PASSPHRASE_SECRET.to_string()).await?.is_some(),
+        last_synced_at: state.last_synced_at,
+        last_error: state.last_error,
+    })
+}
+
+// Runs one sync at a time, whether asked for or scheduled
+#[derive(Default)]
+pub struct SyncEngine {
+    running: Mutex<()>,
+}
+
+impl SyncEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Record local changes, merge in the changelog on the storage, apply what other devices
+    // changed and write the merged changelog back
+    pub async fn sync<C: ConnectionTrait>(&self, db: &C) -> Result<SyncReport, String> {
+        let _running = self.running.lock().await;
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let result = sync_library(db).await;
+        let mut state = load_sync_state(db).await;
+        state.last_attempted_at = Some(now);
+        state.last_error = result.as_ref().err().cloned();
+        set_setting_value(db, SYNC_STATE, &state).await?;
+        result
+    }
+
+    // Sync in the background when it's on and the interval has passed since the last attempt
+    pub async fn sync_if_due<C: ConnectionTrait>(&self, db: &C) -> Result<Option<SyncReport>, String> {
+        let config = load_sync_config(db).await;
+        if !config.enabled || config.storage.is_none() || config.interval_minutes == 0 {
+            return Ok(None);
+        }
+
+        let last_attempted_at = load_sync_state(db)
+            .await
+            .last_attempted_at
+            .and_then(|at| chrono::DateTime::parse_from_rfc3339(&at).ok());
+        let due = last_attempted_at.is_none_or(|at| {
+            chrono::Utc::now().signed_duration_since(at) >= chrono::Duration::minutes(config.interval_minutes as i64)
+        });
+        if !due {
+            return Ok(None);
+        }
+        self.sync(db).await.map(Some)
+    }
+}
+
+async fn sync_library<C: ConnectionTrait>(db: &C) -> Result<SyncReport, String> {
+    let storage = load_sync_config(db).await.storage.ok_or("Sync storage is not set up")?;
+    let passphrase = get_secret_value(SecretKind::SyncToken, SYNC_PASSPHRASE_SECRET.to_string())
+        .await?
+        .ok_or("No sync passphrase has been saved")?;
+
+    let mut state = load_sync_state(db).await;
+    if state.device_id.is_empty() {
+        state.device_id = rand::random::<[u8; 8]>().iter().map(|byte| format!("{:02x}", byte)).collect();
+    }
+
+    let library = load_local_library(db).await?;
+    let local_changes = record_local_changes(&mut state, &library);
+
+    let remote_changes = match storage.read().await? {
+        Some(bytes) => state.changelog.merge(decrypt_changelog(&bytes, &passphrase)?),
+        None => 0,
+    };
+
+    let (applied_changes, mut errors) = apply_changelog(db, &state.changelog, &library).await;
+    if !errors.is_empty() {
+        eprintln!("⚠️ {} sync changes could not be applied", errors.len());
+    }
+    errors.truncate(MAX_REPORTED_ERRORS);
+
+    // What couldn't be applied stays out of the snapshot, so it isn't sent back as a change
+    state.snapshot = load_local_library(db).await?.values;
+    let synced_at = chrono::Utc::now().to_rfc3339();
+    state.last_synced_at = Some(synced_at.clone());
+    // Saved before uploading: if the upload fails, the merged changelog still holds this
+    // device's changes for next time
+    set_setting_value(db, SYNC_STATE, &state).await?;
+
+    storage.write(encrypt_changelog(&state.changelog, &passphrase)?).await?;
+
+    println!(
+        "🔄 Synced: {} local changes, {} from other devices, {} applied",
+        local_changes, remote_changes, applied_changes
+    );
+
+    Ok(SyncReport {
+        device_count: state.changelog.clock().len().max(1),
+        device_id: state.device_id,
+        local_changes,
+        remote_changes,
+        applied_changes,
+        errors,
+        synced_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library(values: &[(&str, SyncValue)], entries: &[&str]) -> LocalLibrary {
+        LocalLibrary {
+            values: values.iter().map(|(key, value)| (key.to_string(), value.clone())).collect(),
+            entry_ids: entries.iter().enumerate().map(|(id, entry)| (entry.to_string(), id as i32)).collect(),
+            feeds: HashMap::new(),
+        }
+    }
+
+    fn subscribed(folder: Option<&str>) -> SyncValue {
+        SyncValue::Subscribed {
+            title: None,
+            folder: folder.map(str::to_string),
+            source_type: "http".to_string(),
+        }
+    }
+
+    fn device(id: &str) -> SyncState {
+        SyncState {
+            device_id: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    // Record local changes and merge with the shared copy, as a sync does, then take the
+    // merged changelog as the library's state
+    fn sync(state: &mut SyncState, shared: &mut SyncChangelog, library: &LocalLibrary) -> BTreeMap<String, SyncValue> {
+        record_local_changes(state, library);
+        state.changelog.merge(shared.clone());
+        *shared = state.changelog.clone();
+        let values: BTreeMap<String, SyncValue> = state
+            .changelog
+            .records
+            .iter()
+            .filter(|(key, record)| library.tracks(key) && record.value != default_value(key))
+            .map(|(key, record)| (key.clone(), record.value.clone()))
+            .collect();
+        state.snapshot = values.clone();
+        values
+    }
+
+    #[test]
+    fn test_clock_comparison() {
+        let clock = |counts: &[(&str, u64)]| counts.iter().map(|(id, count)| (id.to_string(), *count)).collect::<VectorClock>();
+        assert_eq!(compare_clocks(&clock(&[("a", 2)]), &clock(&[("a", 1)])), Some(Ordering::Greater));
+        assert_eq!(compare_clocks(&clock(&[("a", 1)]), &clock(&[("a", 1), ("b", 1)])), Some(Ordering::Less));
+        assert_eq!(compare_clocks(&clock(&[("a", 1)]), &clock(&[("a", 1)])), Some(Ordering::Equal));
+        assert_eq!(compare_clocks(&clock(&[("a", 2)]), &clock(&[("a", 1), ("b", 1)])), None);
+    }
+
+    #[test]
+    fn test_two_devices_converge() {
+        let entry = "https://example.com/feed.xml\nguid-1";
+        let read_key = entry_key("read", "https://example.com/feed.xml", "guid-1");
+        let mut shared = SyncChangelog::default();
+
+        // Laptop subscribes and reads an entry
+        let mut laptop = device("laptop");
+        let laptop_library = library(
+            &[
+                ("feed:https://example.com/feed.xml", subscribed(None)),
+                (read_key.as_str(), SyncValue::Flag { value: true }),
+            ],
+            &[entry],
+        );
+        sync(&mut laptop, &mut shared, &laptop_library);
+
+        // Desktop picks it up, then moves the feed into a folder
+        let mut desktop = device("desktop");
+        let desktop_values = sync(&mut desktop, &mut shared, &library(&[], &[entry]));
+        assert_eq!(desktop_values.get(&read_key), Some(&SyncValue::Flag { value: true }));
+        let desktop_library = library(
+            &[
+                ("feed:https://example.com/feed.xml", subscribed(Some("News"))),
+                (read_key.as_str(), SyncValue::Flag { value: true }),
+            ],
+            &[entry],
+        );
+        sync(&mut desktop, &mut shared, &desktop_library);
+
+        // Meanwhile the laptop marks the entry unread; neither change hides the other
+        let laptop_library = library(&[("feed:https://example.com/feed.xml", subscribed(None))], &[entry]);
+        let laptop_values = sync(&mut laptop, &mut shared, &laptop_library);
+        let desktop_values = sync(&mut desktop, &mut shared, &desktop_library);
+
+        assert_eq!(laptop_values, desktop_values);
+        assert_eq!(laptop_values.get("feed:https://example.com/feed.xml"), Some(&subscribed(Some("News"))));
+        assert_eq!(laptop_values.get(&read_key), None);
+    }
+
+    #[test]
+    fn test_concurrent_changes_settle_the_same_way_on_both_devices() {
+        let key = "feed:https://example.com/feed.xml";
+        let mut laptop = device("laptop");
+        let mut desktop = device("desktop");
+        record_local_changes(&mut laptop, &library(&[(key, subscribed(Some("Laptop")))], &[]));
+        record_local_changes(&mut desktop, &library(&[(key, subscribed(Some("Desktop")))], &[]));
+
+        let mut laptop_merged = laptop.changelog.clone();
+        laptop_merged.merge(desktop.changelog.clone());
+        let mut desktop_merged = desktop.changelog.clone();
+        desktop_merged.merge(laptop.changelog.clone());
+
+        assert_eq!(laptop_merged, desktop_merged);
+        assert_eq!(laptop_merged.records[key].value, subscribed(Some("Laptop")));
+    }
+
+    #[test]
+    fn test_missing_entries_are_not_recorded_as_unread() {
+        let read_key = entry_key("read", "https://example.com/feed.xml", "cleaned-up");
+        let mut state = device("laptop");
+        state.snapshot.insert(read_key.clone(), SyncValue::Flag { value: true });
+
+        assert_eq!(record_local_changes(&mut state, &library(&[], &[])), 0);
+        assert!(state.changelog.records.is_empty());
+    }
+
+    #[test]
+    fn test_changelog_encryption_round_trip() {
+        let mut state = device("laptop");
+        record_local_changes(&mut state, &library(&[("feed:https://example.com/feed.xml", subscribed(None))], &[]));
+
+        let sealed = encrypt_changelog(&state.changelog, "correct horse").unwrap();
+        assert!(!String::from_utf8_lossy(&sealed).contains("example.com"));
+        assert_eq!(decrypt_changelog(&sealed, "correct horse").unwrap(), state.changelog);
+        assert!(decrypt_changelog(&sealed, "wrong horse").is_err());
+        assert!(decrypt_changelog(b"RSYNC1", "correct horse").is_err());
+    }
+}
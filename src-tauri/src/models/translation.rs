@@ -0,0 +1,316 @@
+use std::time::Duration;
+use sea_orm::*;
+use sea_orm::sea_query::OnConflict;
+use serde::{Deserialize, Serialize};
+use tauri_plugin_http::reqwest;
+use crate::entities::{prelude::*, *};
+use crate::models::sanitizer::{sanitize_html, ImagePolicy};
+use crate::models::secrets::{get_secret_value, SecretKind};
+
+const TRANSLATION_TIMEOUT: Duration = Duration::from_secs(60);
+
+// DeepL issues separate keys for its free and paid APIs; free keys end in ":fx"
+const DEEPL_FREE_URL: &str = "https://api-free.deepl.com/v2/translate";
+const DEEPL_PRO_URL: &str = "https://api.deepl.com/v2/translate";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranslationProvider {
+    Deepl,
+    LibreTranslate,
+    // A LibreTranslate-compatible server on this machine (e.g. one running Argos models),
+    // so entry text never leaves the computer
+    Local,
+}
+
+impl TranslationProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TranslationProvider::Deepl => "deepl",
+            TranslationProvider::LibreTranslate => "libre_translate",
+            TranslationProvider::Local => "local",
+        }
+    }
+
+    // Name of the provider's API key in the keychain, stored as an IntegrationApiKey secret
+    fn api_key_name(&self) -> Option<&'static str> {
+        match self {
+            TranslationProvider::Deepl => Some("deepl"),
+            TranslationProvider::LibreTranslate => Some("libre_translate"),
+            TranslationProvider::Local => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranslationConfig {
+    // Translation is off until a provider is picked
+    pub provider: Option<TranslationProvider>,
+    pub libre_translate_url: String,
+    pub local_url: String,
+}
+
+impl Default for TranslationConfig {
+    fn default() -> Self {
+        Self {
+            provider: None,
+            libre_translate_url: "https://libretranslate.com".to_string(),
+            local_url: "http://localhost:5000".to_string(),
+        }
+    }
+}
+
+// Language codes are stored lowercase ("de", "pt-br") so each language has one cache row
+pub fn normalize_target_lang(target_lang: &str) -> Result<String, String> {
+    let target_lang = target_lang.trim().to_lowercase();
+    let valid = (2..=8).contains(&target_lang.len())
+        && target_lang.starts_with(|c: char| c.is_ascii_alphabetic())
+        && target_lang.chars().all(|c| c.is_ascii_alphabetic() || c == '-');
+    if !valid {
+        return Err(format!("Invalid target language: {}", target_lang));
+    }
+    Ok(target_lang)
+}
+
+// Fingerprint of the text being translated (64-bit FNV-1a, so it doesn't change between builds).
+// A cached translation is reused only while the entry's text still matches it.
+pub fn translation_source_hash(title: &str, description: Option<&str>, content: Option<&str>) -> String {
+    let fields = [Some(title), description, content];
+    let hash = fields.iter().fold(0xcbf29ce484222325_u64, |hash, field| {
+        // Mark each field's presence so moving text between fields changes the hash
+        let marker: &[u8] = if field.is_some() { b"\x01" } else { b"\x00" };
+        marker
+            .iter()
+            .chain(field.unwrap_or_default().as_bytes())
+            .fold(hash, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+    });
+    format!("{:016x}", hash)
+}
+
+fn deepl_url(api_key: &str) -> &'static str {
+    if api_key.ends_with(":fx") {
+        DEEPL_FREE_URL
+    } else {
+        DEEPL_PRO_URL
+    }
+}
+
+fn build_deepl_body(texts: &[String], target_lang: &str) -> serde_json::Value {
+    serde_json::json!({
+        "text": texts,
+        "target_lang": target_lang.to_uppercase(),
+        "tag_handling": "html",
+    })
+}
+
+fn build_libre_translate_body(texts: &[String], target_lang: &str, api_key: Option<&str>) -> serde_json::Value {
+    let mut body = serde_json::json!({
+        "q": texts,
+        "source": "auto",
+        "target": target_lang,
+        "format": "html",
+    });
+    if let Some(api_key) = api_key {
+        body["api_key"] = serde_json::Value::from(api_key);
+    }
+    body
+}
+
+#[derive(Deserialize)]
+struct DeeplResponse {
+    translations: Vec<DeeplTranslation>,
+}
+
+#[derive(Deserialize)]
+struct DeeplTranslation {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct LibreTranslateResponse {
+    #[serde(rename = "translatedText")]
+    translated_text: Vec<String>,
+}
+
+async fn post_json<T: for<'de> Deserialize<'de>>(
+    request: reqwest::RequestBuilder,
+    body: &serde_json::Value,
+) -> Result<T, String> {
+    let response = request
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body.to_string())
+        .timeout(TRANSLATION_TIMEOUT)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Translation request failed: {}", e))?
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read translation response: {}", e))?;
+
+    serde_json::from_slice(&response).map_err(|e| format!("Invalid translation response: {}", e))
+}
+
+// Translate each text into the target language, returning them in the same order
+async fn translate_texts(
+    config: &TranslationConfig,
+    provider: TranslationProvider,
+    texts: &[String],
+    target_lang: &str,
+) -> Result<Vec<String>, String> {
+    let api_key = match provider.api_key_name() {
+        Some(name) => get_secret_value(SecretKind::IntegrationApiKey, name.to_string()).await?,
+        None => None,
+    };
+    let client = reqwest::Client::new();
+
+    let translated = match provider {
+        TranslationProvider::Deepl => {
+            let api_key = api_key.ok_or("No DeepL API key has been saved")?;
+            let response: DeeplResponse = post_json(
+                client
+                    .post(deepl_url(&api_key))
+                    .header(reqwest::header::AUTHORIZATION, format!("DeepL-Auth-Key {}", api_key)),
+                &build_deepl_body(texts, target_lang),
+            )
+            .await?;
+            response.translations.into_iter().map(|translation| translation.text).collect()
+        }
+        TranslationProvider::LibreTranslate | TranslationProvider::Local => {
+            let base_url = match provider {
+                TranslationProvider::Local => &config.local_url,
+                _ => &config.libre_translate_url,
+            };
+            let url = format!("{}/translate", base_url.trim_end_matches('/'));
+            let response: LibreTranslateResponse = post_json(
+                client.post(url),
+                &build_libre_translate_body(texts, target_lang, api_key.as_deref()),
+            )
+            .await?;
+            response.translated_text
+        }
+    };
+
+    if translated.len() != texts.len() {
+        return Err(format!(
+            "Translation returned {} texts, expected {}",
+            translated.len(),
+            texts.len()
+        ));
+    }
+    Ok(translated)
+}
+
+// The entry's translation into target_lang, from the cache when the entry hasn't changed
+// since it was translated. Returns the translation and whether it came from the cache.
+pub async fn load_or_translate_entry<C: ConnectionTrait>(
+    db: &C,
+    config: &TranslationConfig,
+    entry: &feed_entry::Model,
+    target_lang: &str,
+    image_policy: ImagePolicy,
+) -> Result<(entry_translation::Model, bool), String> {
+    let provider = config.provider.ok_or("No translation provider is configured")?;
+    let target_lang = normalize_target_lang(target_lang)?;
+    let source_hash = translation_source_hash(&entry.title, entry.description.as_deref(), entry.content.as_deref());
+
+    let cached = EntryTranslation::find_by_id((entry.id, target_lang.clone()))
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch translation: {}", e))?;
+    if let Some(cached) = cached.filter(|cached| cached.source_hash == source_hash) {
+        return Ok((cached, true));
+    }
+
+    // One request for every field, in a fixed order: title, then description and content if present
+    let mut texts = vec![entry.title.clone()];
+    texts.extend(entry.description.clone());
+    texts.extend(entry.content.clone());
+
+    let mut translated = translate_texts(config, provider, &texts, &target_lang).await?.into_iter();
+    // Providers return HTML, which is sanitized like any other entry content
+    let page_url = Some(entry.link.as_str());
+    let mut next_html = |present: bool| {
+        present
+            .then(|| translated.next())
+            .flatten()
+            .map(|html| sanitize_html(&html, page_url, image_policy))
+    };
+    let title = next_html(true).unwrap_or_default();
+    let description = next_html(entry.description.is_some());
+    let content = next_html(entry.content.is_some());
+
+    let translation = entry_translation::Model {
+        entry_id: entry.id,
+        target_lang,
+        provider: provider.as_str().to_string(),
+        source_hash,
+        title,
+        description,
+        content,
+        created_at: chrono::Utc::now().naive_utc(),
+    };
+
+    let active_model: entry_translation::ActiveModel = translation.clone().into();
+    EntryTranslation::insert(active_model)
+        .on_conflict(
+            OnConflict::columns([entry_translation::Column::EntryId, entry_translation::Column::TargetLang])
+                .update_columns([
+                    entry_translation::Column::Provider,
+                    entry_translation::Column::SourceHash,
+                    entry_translation::Column::Title,
+                    entry_translation::Column::Description,
+                    entry_translation::Column::Content,
+                    entry_translation::Column::CreatedAt,
+                ])
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await
+        .map_err(|e| format!("Failed to save translation: {}", e))?;
+
+    Ok((translation, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_lang_is_normalized() {
+        assert_eq!(normalize_target_lang(" DE ").unwrap(), "de");
+        assert_eq!(normalize_target_lang("pt-BR").unwrap(), "pt-br");
+        assert!(normalize_target_lang("").is_err());
+        assert!(normalize_target_lang("-de").is_err());
+        assert!(normalize_target_lang("de; DROP").is_err());
+    }
+
+    #[test]
+    fn test_source_hash_tracks_every_field() {
+        let hash = translation_source_hash("Title", Some("Summary"), None);
+
+        assert_eq!(hash, translation_source_hash("Title", Some("Summary"), None));
+        assert_ne!(hash, translation_source_hash("Title", Some("Summary"), Some("")));
+        assert_ne!(hash, translation_source_hash("Title", None, Some("Summary")));
+        assert_ne!(hash, translation_source_hash("Title!", Some("Summary"), None));
+    }
+
+    #[test]
+    fn test_provider_request_bodies() {
+        let texts = vec!["Hello".to_string(), "<p>World</p>".to_string()];
+
+        let deepl = build_deepl_body(&texts, "pt-br");
+        assert_eq!(deepl["target_lang"], "PT-BR");
+        assert_eq!(deepl["tag_handling"], "html");
+        assert_eq!(deepl["text"][1], "<p>World</p>");
+        assert_eq!(deepl_url("abc:fx"), DEEPL_FREE_URL);
+        assert_eq!(deepl_url("abc"), DEEPL_PRO_URL);
+
+        let libre = build_libre_translate_body(&texts, "de", None);
+        assert_eq!(libre["target"], "de");
+        assert_eq!(libre["format"], "html");
+        assert!(libre.get("api_key").is_none());
+        assert_eq!(build_libre_translate_body(&texts, "de", Some("key"))["api_key"], "key");
+    }
+}
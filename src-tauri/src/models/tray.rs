@@ -0,0 +1,110 @@
+use std::time::Duration;
+use sea_orm::*;
+use tauri::menu::CheckMenuItem;
+use tauri::tray::TrayIcon;
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast;
+use crate::entities::{prelude::*, *};
+use crate::models::responses::RefreshSummary;
+use crate::models::settings::{get_setting_or, CLOSE_TO_TRAY};
+use crate::models::state::AppState;
+
+pub const TRAY_ID: &str = "main";
+pub const MAIN_WINDOW: &str = "main";
+
+// Reading entries doesn't announce itself like a refresh does, so the unread count is also
+// refreshed on a timer
+const TRAY_UPDATE_INTERVAL: Duration = Duration::from_secs(30);
+
+// Whether closing the main window hides it to the tray instead of quitting. Belongs to the
+// machine rather than a library, so it is read from the main database.
+pub async fn load_close_to_tray<C: ConnectionTrait>(home_db: &C) -> bool {
+    get_setting_or(home_db, CLOSE_TO_TRAY, true).await
+}
+
+// Unread entries across all feeds, leaving out those hidden by domain rules or mutes
+pub async fn count_unread_entries<C: ConnectionTrait>(db: &C) -> Result<u64, String> {
+    FeedEntry::find()
+        .filter(feed_entry::Column::IsRead.eq(false))
+        .filter(feed_entry::Column::IsHidden.eq(false))
+        .count(db)
+        .await
+        .map_err(|e| format!("Failed to count unread entries: {}", e))
+}
+
+pub fn tray_tooltip(unread: u64, paused: bool) -> String {
+    let mut tooltip = match unread {
+        0 => "reader: no unread entries".to_string(),
+        1 => "reader: 1 unread entry".to_string(),
+        unread => format!("reader: {} unread entries", unread),
+    };
+    if paused {
+        tooltip.push_str(" (fetching paused)");
+    }
+    tooltip
+}
+
+// Bring the main window back from the tray
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+async fn update_tray(app: &AppHandle, tray: &TrayIcon, pause_item: &CheckMenuItem<tauri::Wry>) {
+    let state = app.state::<AppState>();
+    let unread = match count_unread_entries(&state.db().await).await {
+        Ok(unread) => unread,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            return;
+        }
+    };
+    let paused = state.async_fetcher.as_ref().is_some_and(|fetcher| fetcher.is_paused());
+
+    let _ = tray.set_tooltip(Some(tray_tooltip(unread, paused)));
+    let _ = pause_item.set_checked(paused);
+    // Not every platform shows a badge; those that don't just ignore it
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.set_badge_count((unread > 0).then_some(unread as i64));
+    }
+}
+
+// Keep the tray's unread count and pause state current, after every refresh and on a timer
+pub async fn run_tray_updater(
+    app: AppHandle,
+    tray: TrayIcon,
+    pause_item: CheckMenuItem<tauri::Wry>,
+    mut summaries: broadcast::Receiver<RefreshSummary>,
+) {
+    let mut shutdown = app.state::<AppState>().shutdown_signal.subscribe();
+    let mut ticker = tokio::time::interval(TRAY_UPDATE_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            summary = summaries.recv() => {
+                if let Err(broadcast::error::RecvError::Closed) = summary {
+                    break;
+                }
+            }
+            _ = shutdown.wait_for(|stopping| *stopping) => break,
+        }
+
+        update_tray(&app, &tray, &pause_item).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tray_tooltip_counts_unread_entries() {
+        assert_eq!(tray_tooltip(0, false), "reader: no unread entries");
+        assert_eq!(tray_tooltip(1, false), "reader: 1 unread entry");
+        assert_eq!(tray_tooltip(42, true), "reader: 42 unread entries (fetching paused)");
+    }
+}
@@ -0,0 +1,171 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use sea_orm::ConnectionTrait;
+use tauri_plugin_http::reqwest;
+use url::{Host, Url};
+use crate::models::settings::{get_setting_or, FETCHER_ALLOW_PRIVATE_ADDRESSES};
+
+fn is_private_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        // Carrier-grade NAT (100.64.0.0/10)
+        || (a == 100 && (64..128).contains(&b))
+}
+
+fn is_private_ipv6(ip: Ipv6Addr) -> bool {
+    if let Some(mapped) = ip.to_ipv4_mapped() {
+        return is_private_ipv4(mapped);
+    }
+    let first_segment = ip.segments()[0];
+    ip.is_loopback()
+        || ip.is_unspecified()
+        // Unique local (fc00::/7) and link-local (fe80::/10)
+        || (first_segment & 0xfe00) == 0xfc00
+        || (first_segment & 0xffc0) == 0xfe80
+}
+
+// Loopback, RFC 1918, link-local and similar addresses that point into the user's own
+// machine or network rather than the public internet
+pub fn is_private_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_private_ipv4(ip),
+        IpAddr::V6(ip) => is_private_ipv6(ip),
+    }
+}
+
+// Only plain web URLs with a host can be fetched
+pub fn parse_fetch_url(url: &str) -> Result<Url, String> {
+    let parsed = Url::parse(url.trim()).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Unsupported URL scheme \"{}\"; only http and https are allowed", parsed.scheme()));
+    }
+    if parsed.host().is_none() {
+        return Err("URL has no host".to_string());
+    }
+    Ok(parsed)
+}
+
+// Whether a URL names a private host without a DNS lookup (an IP literal or localhost).
+// Used where resolving isn't possible, such as while following redirects.
+pub fn is_private_host_literal(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Ipv4(ip)) => is_private_ipv4(ip),
+        Some(Host::Ipv6(ip)) => is_private_ipv6(ip),
+        Some(Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+        None => false,
+    }
+}
+
+// Check a user-supplied URL before fetching it: http(s) only, and unless private addresses
+// are allowed, every address the host resolves to must be public. This stops a crafted feed
+// URL from probing services on localhost or the LAN.
+pub async fn validate_fetch_url(url: &str, allow_private: bool) -> Result<(), String> {
+    let parsed = parse_fetch_url(url)?;
+    if allow_private {
+        return Ok(());
+    }
+    if is_private_host_literal(&parsed) {
+        return Err("URL points to a private or local address".to_string());
+    }
+
+    let host = parsed.host_str().unwrap_or_default().to_string();
+    let port = parsed.port_or_known_default().unwrap_or(80);
+    let addresses = tokio::net::lookup_host((host.as_str(), port))
+        .await
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?;
+
+    for address in addresses {
+        if is_private_address(address.ip()) {
+            return Err(format!("{} resolves to a private or local address", host));
+        }
+    }
+    Ok(())
+}
+
+// Resolves host names for an HTTP client, refusing any that resolve to a private address.
+// Checking where the client actually connects covers what validate_fetch_url can't: redirects
+// to other hosts and names that resolve differently by the time they're fetched (DNS rebinding).
+struct PublicAddressResolver;
+
+impl reqwest::dns::Resolve for PublicAddressResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            if addresses.iter().any(|address| is_private_address(address.ip())) {
+                return Err(format!("{} resolves to a private or local address", host).into());
+            }
+            let addresses: reqwest::dns::Addrs = Box::new(addresses.into_iter());
+            Ok(addresses)
+        })
+    }
+}
+
+// Make a client refuse to connect to private addresses, unless they are allowed. IP literals
+// skip name resolution, so redirect policies still check those with is_private_host_literal.
+pub fn guard_private_addresses(builder: reqwest::ClientBuilder, allow_private: bool) -> reqwest::ClientBuilder {
+    if allow_private {
+        builder
+    } else {
+        builder.dns_resolver(Arc::new(PublicAddressResolver))
+    }
+}
+
+pub async fn load_allow_private_addresses<C: ConnectionTrait>(db: &C) -> bool {
+    get_setting_or(db, FETCHER_ALLOW_PRIVATE_ADDRESSES, false).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_private_addresses() {
+        for ip in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "0.0.0.0", "100.64.0.1", "::1", "fd00::1", "fe80::1", "::ffff:192.168.0.1"] {
+            assert!(is_private_address(ip.parse().unwrap()), "{} should be private", ip);
+        }
+        for ip in ["93.184.216.34", "172.32.0.1", "100.128.0.1", "2606:4700::1111"] {
+            assert!(!is_private_address(ip.parse().unwrap()), "{} should be public", ip);
+        }
+    }
+
+    #[test]
+    fn test_only_web_urls_can_be_fetched() {
+        assert!(parse_fetch_url("https://example.com/feed.xml").is_ok());
+        assert!(parse_fetch_url("file:///etc/passwd").is_err());
+        assert!(parse_fetch_url("gopher://example.com").is_err());
+        assert!(parse_fetch_url("not a url").is_err());
+    }
+
+    #[test]
+    fn test_private_host_literals() {
+        let url = |u: &str| Url::parse(u).unwrap();
+        assert!(is_private_host_literal(&url("http://localhost:8080/admin")));
+        assert!(is_private_host_literal(&url("http://api.localhost/")));
+        assert!(is_private_host_literal(&url("http://[::1]/")));
+        assert!(is_private_host_literal(&url("http://192.168.0.10/feed")));
+        assert!(!is_private_host_literal(&url("https://example.com/feed")));
+    }
+
+    #[tokio::test]
+    async fn test_validate_blocks_local_targets_unless_allowed() {
+        assert!(validate_fetch_url("http://127.0.0.1:5432/", false).await.is_err());
+        assert!(validate_fetch_url("http://localhost/", false).await.is_err());
+        assert!(validate_fetch_url("http://127.0.0.1:5432/", true).await.is_ok());
+        assert!(validate_fetch_url("ftp://127.0.0.1/", true).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_guarded_client_refuses_names_resolving_to_local_addresses() {
+        let client = guard_private_addresses(reqwest::Client::builder(), false).build().unwrap();
+        let error = client.get("http://localhost:9/").send().await.unwrap_err();
+        assert!(format!("{:?}", error).contains("private or local address"), "{:?}", error);
+    }
+}
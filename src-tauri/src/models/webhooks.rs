@@ -0,0 +1,295 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use sea_orm::*;
+use tauri_plugin_http::reqwest;
+use crate::entities::{prelude::*, *};
+use crate::models::reading::html_to_text;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(15);
+
+// A delivery is tried this many times, waiting BASE_RETRY_DELAY and then twice as long
+// after each failure, before it is logged as failed
+const MAX_DELIVERY_ATTEMPTS: u32 = 4;
+const BASE_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+pub const DELIVERY_DELIVERED: &str = "delivered";
+pub const DELIVERY_FAILED: &str = "failed";
+
+// Blank keywords mean "no keyword filter"
+pub fn normalize_keyword(keyword: Option<String>) -> Option<String> {
+    keyword.map(|keyword| keyword.trim().to_string()).filter(|keyword| !keyword.is_empty())
+}
+
+// Whether a newly saved entry passes the webhook's filters. Every filter that is set must
+// match: the entry's feed, one of its tags, and the keyword (case-insensitive) in its
+// title or description.
+pub fn webhook_matches(webhook: &webhook::Model, entry: &feed_entry::Model, entry_tag_ids: &HashSet<i32>) -> bool {
+    if webhook.feed_id.is_some_and(|feed_id| feed_id != entry.feed_id) {
+        return false;
+    }
+    if webhook.tag_id.is_some_and(|tag_id| !entry_tag_ids.contains(&tag_id)) {
+        return false;
+    }
+    match webhook.keyword.as_deref() {
+        Some(keyword) => {
+            let keyword = keyword.to_lowercase();
+            entry.title.to_lowercase().contains(&keyword)
+                || entry
+                    .description
+                    .as_deref()
+                    .is_some_and(|description| html_to_text(description).to_lowercase().contains(&keyword))
+        }
+        None => true,
+    }
+}
+
+// The JSON body POSTed to a webhook: the feed and the matching entries it just saved
+pub fn build_webhook_payload(feed: &feed::Model, entries: &[&feed_entry::Model]) -> serde_json::Value {
+    let entries: Vec<serde_json::Value> = entries
+        .iter()
+        .map(|entry| {
+            serde_json::json!({
+                "id": entry.id,
+                "title": entry.title,
+                "link": entry.link,
+                "canonical_link": entry.canonical_link,
+                "description": entry.description,
+                "published_at": entry.published_at.map(|date| date.and_utc().to_rfc3339()),
+                "saved_at": entry.created_at.and_utc().to_rfc3339(),
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "event": "entries.added",
+        "feed": {
+            "id": feed.id,
+            "title": feed.title,
+            "url": feed.url,
+        },
+        "entries": entries,
+    })
+}
+
+// Wait before retry number `attempt` (1 for the first retry)
+pub fn retry_delay(attempt: u32) -> Duration {
+    BASE_RETRY_DELAY * 2_u32.pow(attempt.saturating_sub(1))
+}
+
+// Timeouts, rate limiting and server errors may pass; other client errors won't
+fn is_retryable_status(status: u16) -> bool {
+    status == 408 || status == 429 || status >= 500
+}
+
+struct DeliveryOutcome {
+    delivered: bool,
+    attempts: u32,
+    response_status: Option<u16>,
+    error: Option<String>,
+}
+
+async fn deliver(client: &reqwest::Client, url: &str, body: &str) -> DeliveryOutcome {
+    let mut outcome = DeliveryOutcome {
+        delivered: false,
+        attempts: 0,
+        response_status: None,
+        error: None,
+    };
+
+    while outcome.attempts < MAX_DELIVERY_ATTEMPTS {
+        if outcome.attempts > 0 {
+            tokio::time::sleep(retry_delay(outcome.attempts)).await;
+        }
+        outcome.attempts += 1;
+
+        let response = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .timeout(WEBHOOK_TIMEOUT)
+            .send()
+            .await;
+        match response {
+            Ok(response) => {
+                let status = response.status();
+                outcome.response_status = Some(status.as_u16());
+                if status.is_success() {
+                    outcome.delivered = true;
+                    outcome.error = None;
+                    return outcome;
+                }
+                outcome.error = Some(format!("Endpoint responded with {}", status));
+                if !is_retryable_status(status.as_u16()) {
+                    return outcome;
+                }
+            }
+            Err(e) => {
+                outcome.response_status = None;
+                outcome.error = Some(format!("Request failed: {}", e));
+            }
+        }
+    }
+    outcome
+}
+
+async fn record_delivery<C: ConnectionTrait>(
+    db: &C,
+    webhook_id: i32,
+    entry_count: usize,
+    outcome: &DeliveryOutcome,
+    created_at: chrono::NaiveDateTime,
+) -> Result<(), String> {
+    let delivery = webhook_delivery::ActiveModel {
+        webhook_id: ActiveValue::Set(webhook_id),
+        entry_count: ActiveValue::Set(entry_count as i32),
+        status: ActiveValue::Set(if outcome.delivered { DELIVERY_DELIVERED } else { DELIVERY_FAILED }.to_string()),
+        attempts: ActiveValue::Set(outcome.attempts as i32),
+        response_status: ActiveValue::Set(outcome.response_status.map(i32::from)),
+        error: ActiveValue::Set(outcome.error.clone()),
+        created_at: ActiveValue::Set(created_at),
+        completed_at: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+        ..Default::default()
+    };
+    delivery
+        .insert(db)
+        .await
+        .map_err(|e| format!("Failed to log webhook delivery: {}", e))?;
+    Ok(())
+}
+
+// POST a feed's newly saved entries to every enabled webhook they match, one request per
+// webhook, and log each delivery. Meant to run in the background after a save, since
+// retries can take a while.
+pub async fn dispatch_webhooks(
+    db: DatabaseConnection,
+    feed: feed::Model,
+    new_entries: Vec<feed_entry::Model>,
+) -> Result<(), String> {
+    // Entries hidden by domain rules or mutes aren't announced
+    let new_entries: Vec<feed_entry::Model> = new_entries.into_iter().filter(|entry| !entry.is_hidden).collect();
+    if new_entries.is_empty() {
+        return Ok(());
+    }
+    let webhooks = Webhook::find()
+        .filter(webhook::Column::Enabled.eq(true))
+        .all(&db)
+        .await
+        .map_err(|e| format!("Failed to fetch webhooks: {}", e))?;
+    if webhooks.is_empty() {
+        return Ok(());
+    }
+
+    let mut entry_tag_ids: HashMap<i32, HashSet<i32>> = HashMap::new();
+    if webhooks.iter().any(|webhook| webhook.tag_id.is_some()) {
+        let entry_tags = EntryTag::find()
+            .filter(entry_tag::Column::EntryId.is_in(new_entries.iter().map(|entry| entry.id)))
+            .all(&db)
+            .await
+            .map_err(|e| format!("Failed to fetch entry tags: {}", e))?;
+        for entry_tag in entry_tags {
+            entry_tag_ids.entry(entry_tag.entry_id).or_default().insert(entry_tag.tag_id);
+        }
+    }
+
+    let client = reqwest::Client::new();
+    let no_tags = HashSet::new();
+    let deliveries = webhooks.iter().filter_map(|webhook| {
+        let matching: Vec<&feed_entry::Model> = new_entries
+            .iter()
+            .filter(|entry| webhook_matches(webhook, entry, entry_tag_ids.get(&entry.id).unwrap_or(&no_tags)))
+            .collect();
+        if matching.is_empty() {
+            return None;
+        }
+        let body = build_webhook_payload(&feed, &matching).to_string();
+        let (client, db) = (&client, &db);
+        Some(async move {
+            let created_at = chrono::Utc::now().naive_utc();
+            let outcome = deliver(client, &webhook.url, &body).await;
+            if !outcome.delivered {
+                eprintln!(
+                    "❌ Webhook {} failed after {} attempts: {}",
+                    webhook.name,
+                    outcome.attempts,
+                    outcome.error.as_deref().unwrap_or_default()
+                );
+            }
+            if let Err(e) = record_delivery(db, webhook.id, matching.len(), &outcome, created_at).await {
+                eprintln!("❌ {}", e);
+            }
+        })
+    });
+    futures::future::join_all(deliveries).await;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::fixtures::{self, at};
+
+    fn webhook(feed_id: Option<i32>, tag_id: Option<i32>, keyword: Option<&str>) -> webhook::Model {
+        webhook::Model {
+            id: 1,
+            name: "Automation".to_string(),
+            url: "https://hooks.example.com/new".to_string(),
+            feed_id,
+            tag_id,
+            keyword: keyword.map(str::to_string),
+            enabled: true,
+            created_at: at("2024-06-01 08:00"),
+            updated_at: at("2024-06-01 08:00"),
+        }
+    }
+
+    fn entry(feed_id: i32, title: &str, description: Option<&str>) -> feed_entry::Model {
+        feed_entry::Model {
+            title: title.to_string(),
+            description: description.map(str::to_string),
+            link: "https://example.com/post".to_string(),
+            guid: "https://example.com/post".to_string(),
+            published_at: Some(at("2024-06-01 09:00")),
+            created_at: at("2024-06-01 10:00"),
+            updated_at: at("2024-06-01 10:00"),
+            ..fixtures::feed_entry(7, feed_id)
+        }
+    }
+
+    #[test]
+    fn test_every_set_filter_must_match() {
+        let tags: HashSet<i32> = [3].into_iter().collect();
+        let post = entry(1, "Release notes", Some("<p>Now with <b>WebAssembly</b> support</p>"));
+
+        assert!(webhook_matches(&webhook(None, None, None), &post, &HashSet::new()));
+        assert!(webhook_matches(&webhook(Some(1), Some(3), Some("webassembly")), &post, &tags));
+        assert!(!webhook_matches(&webhook(Some(2), None, None), &post, &tags));
+        assert!(!webhook_matches(&webhook(None, Some(4), None), &post, &tags));
+        assert!(!webhook_matches(&webhook(None, None, Some("wasm")), &post, &tags));
+        // Markup isn't searched, only the text
+        assert!(!webhook_matches(&webhook(None, None, Some("<b>")), &post, &tags));
+        assert_eq!(normalize_keyword(Some("  ".to_string())), None);
+    }
+
+    #[test]
+    fn test_payload_and_retry_schedule() {
+        let feed = feed::Model {
+            url: "https://example.com/feed.xml".to_string(),
+            title: Some("Example".to_string()),
+            ..fixtures::feed(1)
+        };
+        let post = entry(1, "Release notes", None);
+
+        let payload = build_webhook_payload(&feed, &[&post]);
+
+        assert_eq!(payload["event"], "entries.added");
+        assert_eq!(payload["feed"]["title"], "Example");
+        assert_eq!(payload["entries"][0]["link"], "https://example.com/post");
+        assert_eq!(payload["entries"][0]["published_at"], "2024-06-01T09:00:00+00:00");
+
+        assert_eq!(retry_delay(1), Duration::from_secs(2));
+        assert_eq!(retry_delay(3), Duration::from_secs(8));
+        assert!(is_retryable_status(429) && is_retryable_status(503));
+        assert!(!is_retryable_status(404));
+    }
+}
@@ -112,7 +112,7 @@ export interface RefreshError {
   feed_url: string
   feed_title?: string
   error_message: string
-  error_type: string // "network", "parse", "timeout", "rate_limited", "too_many_retries", "database"
+  error_type: string // "network", "parse", "timeout", "too_many_retries", "database"
   retry_count: number
   timestamp: string
 }